@@ -4,6 +4,7 @@
 
 use crate::{
     accept_type::AcceptType,
+    execution_limits::{ApiKey, ExecutionLimiter, TierLimiter},
     metrics,
     response::{
         bcs_api_disabled, block_not_found_by_height, block_not_found_by_version,
@@ -39,7 +40,7 @@ use aptos_types::{
     state_store::{
         state_key::{inner::StateKeyInner, prefix::StateKeyPrefix, StateKey},
         state_value::StateValue,
-        TStateView,
+        StateView, TStateView,
     },
     transaction::{
         block_epilogue::BlockEndInfo, SignedTransaction, Transaction, TransactionWithProof, Version,
@@ -78,6 +79,7 @@ pub struct Context {
     simulate_txn_stats: Arc<FunctionStats>,
     pub indexer_reader: Option<Arc<dyn IndexerReader>>,
     pub wait_for_hash_active_connections: Arc<AtomicUsize>,
+    execution_limiter: Arc<ExecutionLimiter>,
 }
 
 impl std::fmt::Debug for Context {
@@ -107,6 +109,7 @@ impl Context {
                 )),
             )
         };
+        let execution_limiter = Arc::new(ExecutionLimiter::new(&node_config.api));
         Self {
             chain_id,
             db,
@@ -131,6 +134,7 @@ impl Context {
             simulate_txn_stats,
             indexer_reader,
             wait_for_hash_active_connections: Arc::new(AtomicUsize::new(0)),
+            execution_limiter,
         }
     }
 
@@ -204,6 +208,15 @@ impl Context {
         self.node_config.api.max_submit_transaction_batch_size
     }
 
+    pub fn max_view_batch_size(&self) -> usize {
+        self.node_config.api.max_view_batch_size
+    }
+
+    /// Returns the execution limits of the API tier of the caller.
+    pub fn execution_tier(&self, api_key: &ApiKey) -> Arc<TierLimiter> {
+        self.execution_limiter.tier(api_key)
+    }
+
     pub async fn submit_transaction(&self, txn: SignedTransaction) -> Result<SubmissionStatus> {
         let (req_sender, callback) = oneshot::channel();
         self.mp_sender
@@ -686,6 +699,21 @@ impl Context {
         }
 
         let state_view = self.latest_state_view_poem(ledger_info)?;
+        self.render_transactions_with_state_view(ledger_info, &state_view, data)
+    }
+
+    /// Same as [`Self::render_transactions_non_sequential`], but resolves types against the
+    /// given state view, e.g. one with overridden modules.
+    pub fn render_transactions_with_state_view<E: InternalError>(
+        &self,
+        ledger_info: &LedgerInfo,
+        state_view: &impl StateView,
+        data: Vec<TransactionOnChainData>,
+    ) -> Result<Vec<aptos_api_types::Transaction>, E> {
+        if data.is_empty() {
+            return Ok(vec![]);
+        }
+
         let converter = state_view.as_converter(self.db.clone(), self.indexer_reader.clone());
         let txns: Vec<aptos_api_types::Transaction> = data
             .into_iter()
@@ -0,0 +1,331 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Limits on the view functions and transaction simulations executed by the API, configured
+//! per tier of callers. Callers are assigned a tier by the API key they send in the
+//! `x-aptos-api-key` header, and requests without a known API key use the default limits.
+
+use crate::{
+    metrics::EXECUTION_LIMIT_REJECTIONS,
+    response::{InternalError, ServiceUnavailableError},
+};
+use aptos_api_types::{AptosErrorCode, X_APTOS_API_KEY};
+use aptos_config::config::{ApiConfig, ExecutionLimits};
+use poem::{FromRequest, Request, RequestBody, Result};
+use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
+use tokio::sync::Semaphore;
+
+const DEFAULT_TIER_NAME: &str = "default";
+
+/// API key from the input headers, if any
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ApiKey(pub Option<String>);
+
+#[async_trait::async_trait]
+impl<'a> FromRequest<'a> for ApiKey {
+    async fn from_request(request: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(ApiKey(
+            request
+                .headers()
+                .get(X_APTOS_API_KEY)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        ))
+    }
+}
+
+/// The kinds of executions limited per tier
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecutionKind {
+    ViewFunction,
+    Simulation,
+}
+
+impl fmt::Display for ExecutionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ExecutionKind::ViewFunction => "view_function",
+            ExecutionKind::Simulation => "simulation",
+        })
+    }
+}
+
+/// The execution limits of all the API tiers
+pub struct ExecutionLimiter {
+    default_tier: Arc<TierLimiter>,
+    tiers_by_api_key: HashMap<String, Arc<TierLimiter>>,
+}
+
+impl ExecutionLimiter {
+    pub fn new(config: &ApiConfig) -> Self {
+        let mut tiers_by_api_key = HashMap::new();
+        for tier in &config.api_tiers {
+            let limiter = Arc::new(TierLimiter::new(tier.name.clone(), &tier.limits, config));
+            for api_key in &tier.api_keys {
+                tiers_by_api_key.insert(api_key.clone(), limiter.clone());
+            }
+        }
+        Self {
+            default_tier: Arc::new(TierLimiter::new(
+                DEFAULT_TIER_NAME.to_string(),
+                &config.execution_limits,
+                config,
+            )),
+            tiers_by_api_key,
+        }
+    }
+
+    /// Returns the limits of the tier of the API key, or the default limits if the API key is
+    /// missing or unknown.
+    pub fn tier(&self, api_key: &ApiKey) -> Arc<TierLimiter> {
+        api_key
+            .0
+            .as_ref()
+            .and_then(|api_key| self.tiers_by_api_key.get(api_key))
+            .unwrap_or(&self.default_tier)
+            .clone()
+    }
+}
+
+/// The execution limits of one API tier
+pub struct TierLimiter {
+    name: String,
+    max_gas_view_function: u64,
+    max_gas_simulation: Option<u64>,
+    view_function_timeout: Option<Duration>,
+    simulation_timeout: Option<Duration>,
+    view_functions: Option<Arc<Semaphore>>,
+    simulations: Option<Arc<Semaphore>>,
+}
+
+impl TierLimiter {
+    fn new(name: String, limits: &ExecutionLimits, config: &ApiConfig) -> Self {
+        Self {
+            name,
+            max_gas_view_function: limits
+                .max_gas_view_function
+                .unwrap_or(config.max_gas_view_function),
+            max_gas_simulation: limits.max_gas_simulation,
+            view_function_timeout: limits.view_function_timeout_ms.map(Duration::from_millis),
+            simulation_timeout: limits.simulation_timeout_ms.map(Duration::from_millis),
+            view_functions: limits
+                .max_concurrent_view_functions
+                .map(|permits| Arc::new(Semaphore::new(permits))),
+            simulations: limits
+                .max_concurrent_simulations
+                .map(|permits| Arc::new(Semaphore::new(permits))),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Maximum gas unit limit for each view function
+    pub fn max_gas_view_function(&self) -> u64 {
+        self.max_gas_view_function
+    }
+
+    /// Maximum gas unit limit for each simulated transaction, if any
+    pub fn max_gas_simulation(&self) -> Option<u64> {
+        self.max_gas_simulation
+    }
+
+    /// Runs the function on a blocking thread, like `api_spawn_blocking`, within the
+    /// concurrency limit and the timeout of the tier for this kind of execution.
+    ///
+    /// Requests over the concurrency limit are rejected right away rather than queued. On
+    /// timeout the request is answered with an error, but the function keeps running (bounded
+    /// by its gas limit) and holds its concurrency permit until it returns. The config
+    /// sanitizer requires a concurrency limit for every timeout, so timed out executions can't
+    /// pile up.
+    pub async fn spawn_blocking<F, T, E>(&self, kind: ExecutionKind, func: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E> + Send + 'static,
+        T: Send + 'static,
+        E: InternalError + ServiceUnavailableError + Send + 'static,
+    {
+        let (semaphore, timeout) = match kind {
+            ExecutionKind::ViewFunction => (&self.view_functions, self.view_function_timeout),
+            ExecutionKind::Simulation => (&self.simulations, self.simulation_timeout),
+        };
+        let permit = match semaphore {
+            Some(semaphore) => match semaphore.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    return Err(self.reject(
+                        kind,
+                        "concurrency",
+                        format!("Too many concurrent {} requests", kind),
+                    ))
+                },
+            },
+            None => None,
+        };
+
+        // The permit is moved into the blocking task, so that it's only released once the
+        // function returns, even if the request times out first.
+        let handle = tokio::task::spawn_blocking(move || {
+            let result = func();
+            drop(permit);
+            result
+        });
+        let result = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, handle).await {
+                Ok(result) => result,
+                Err(_) => {
+                    return Err(self.reject(
+                        kind,
+                        "timeout",
+                        format!(
+                            "The {} request did not complete within {}ms",
+                            kind,
+                            timeout.as_millis()
+                        ),
+                    ))
+                },
+            },
+            None => handle.await,
+        };
+        result.map_err(|err| E::internal_with_code_no_info(err, AptosErrorCode::InternalError))?
+    }
+
+    fn reject<E: ServiceUnavailableError>(
+        &self,
+        kind: ExecutionKind,
+        reason: &str,
+        message: String,
+    ) -> E {
+        EXECUTION_LIMIT_REJECTIONS
+            .with_label_values(&[&self.name, &kind.to_string(), reason])
+            .inc();
+        E::service_unavailable_with_code_no_info(
+            format!("{} for API tier {}", message, self.name),
+            AptosErrorCode::ExecutionLimitExceeded,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::BasicError;
+    use std::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_concurrency_limit() {
+        let limiter = create_tier_limiter(ExecutionLimits {
+            max_concurrent_view_functions: Some(1),
+            ..Default::default()
+        });
+
+        // Start a view function that blocks until it's released
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let tier = limiter.clone();
+        let blocked = tokio::spawn(async move {
+            tier.spawn_blocking(ExecutionKind::ViewFunction, move || {
+                release_rx.recv().unwrap();
+                Ok::<_, BasicError>(1)
+            })
+            .await
+        });
+        wait_for_permits(&limiter, ExecutionKind::ViewFunction, 0).await;
+
+        // Verify that another view function is rejected, but not a simulation
+        assert_execution_limit_exceeded(
+            limiter
+                .spawn_blocking(ExecutionKind::ViewFunction, || Ok::<_, BasicError>(2))
+                .await,
+        );
+        assert_eq!(
+            limiter
+                .spawn_blocking(ExecutionKind::Simulation, || Ok::<_, BasicError>(3))
+                .await
+                .unwrap(),
+            3
+        );
+
+        // Release the first view function and verify it completes
+        release_tx.send(()).unwrap();
+        assert_eq!(blocked.await.unwrap().unwrap(), 1);
+        assert_eq!(
+            limiter
+                .spawn_blocking(ExecutionKind::ViewFunction, || Ok::<_, BasicError>(4))
+                .await
+                .unwrap(),
+            4
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeout_holds_permit() {
+        let limiter = create_tier_limiter(ExecutionLimits {
+            simulation_timeout_ms: Some(50),
+            max_concurrent_simulations: Some(1),
+            ..Default::default()
+        });
+
+        // Verify that a simulation that doesn't return in time is rejected
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        assert_execution_limit_exceeded(
+            limiter
+                .spawn_blocking(ExecutionKind::Simulation, move || {
+                    release_rx.recv().unwrap();
+                    Ok::<_, BasicError>(1)
+                })
+                .await,
+        );
+
+        // Verify that the timed out simulation still holds its permit
+        assert_execution_limit_exceeded(
+            limiter
+                .spawn_blocking(ExecutionKind::Simulation, || Ok::<_, BasicError>(2))
+                .await,
+        );
+
+        // Release the timed out simulation and verify the permit is returned
+        release_tx.send(()).unwrap();
+        wait_for_permits(&limiter, ExecutionKind::Simulation, 1).await;
+        assert_eq!(
+            limiter
+                .spawn_blocking(ExecutionKind::Simulation, || Ok::<_, BasicError>(3))
+                .await
+                .unwrap(),
+            3
+        );
+    }
+
+    /// Verifies that the result is a 503 with the execution limit error code
+    fn assert_execution_limit_exceeded<T: fmt::Debug>(result: Result<T, BasicError>) {
+        match result {
+            Err(BasicError::ServiceUnavailable(error, ..)) => {
+                assert_eq!(error.0.error_code, AptosErrorCode::ExecutionLimitExceeded)
+            },
+            result => panic!(
+                "Expected the execution limit to be exceeded, got: {:?}",
+                result
+            ),
+        }
+    }
+
+    /// Creates a tier limiter with the given limits
+    fn create_tier_limiter(limits: ExecutionLimits) -> Arc<TierLimiter> {
+        Arc::new(TierLimiter::new(
+            "test".to_string(),
+            &limits,
+            &ApiConfig::default(),
+        ))
+    }
+
+    /// Waits until the given number of permits are available for the kind of execution
+    async fn wait_for_permits(limiter: &TierLimiter, kind: ExecutionKind, num_permits: usize) {
+        let semaphore = match kind {
+            ExecutionKind::ViewFunction => limiter.view_functions.as_ref(),
+            ExecutionKind::Simulation => limiter.simulations.as_ref(),
+        }
+        .unwrap();
+        while semaphore.available_permits() != num_permits {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
@@ -13,6 +13,7 @@ mod check_size;
 pub mod context;
 mod error_converter;
 mod events;
+pub mod execution_limits;
 mod failpoint;
 mod index;
 mod log;
@@ -22,6 +23,7 @@ mod response;
 pub mod runtime;
 pub mod set_failpoints;
 pub mod state;
+pub mod state_override;
 #[cfg(test)]
 pub mod tests;
 pub mod transactions;
@@ -105,3 +105,12 @@ pub static WAIT_TRANSACTION_POLL_TIME: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static EXECUTION_LIMIT_REJECTIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_api_execution_limit_rejections",
+        "View function and simulation requests rejected by the execution limits of their API tier, grouped by tier, kind of execution and reason",
+        &["tier", "kind", "reason"]
+    )
+    .unwrap()
+});
@@ -0,0 +1,192 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Context;
+use anyhow::{format_err, Context as AnyhowContext, Result};
+use aptos_api_types::{AsConverter, StateOverrides};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::CoinStoreResource,
+    on_chain_config::CurrentTimeMicroseconds,
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+        Result as StateViewResult, StateView, StateViewId, TStateView,
+    },
+    write_set::WriteSet,
+};
+use aptos_vm::data_cache::get_resource_group_member_from_metadata;
+use bytes::Bytes;
+use move_binary_format::CompiledModule;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use std::collections::{BTreeMap, HashMap};
+
+/// A state view which layers overridden state values on top of a base state view (usually the
+/// `DbStateView` at the requested ledger version). The overridden values are only visible
+/// through this view, and are never written to storage.
+pub struct OverriddenStateView<S> {
+    base: S,
+    overrides: HashMap<StateKey, Option<StateValue>>,
+}
+
+impl<S: StateView> OverriddenStateView<S> {
+    pub fn new(base: S) -> Self {
+        Self {
+            base,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Applies the user provided overrides on top of the current view. Modules are applied
+    /// first, so that resources of overridden modules can be converted from JSON.
+    pub fn apply_state_overrides(
+        &mut self,
+        context: &Context,
+        state_overrides: &StateOverrides,
+    ) -> Result<()> {
+        for module in state_overrides.modules() {
+            let compiled_module = CompiledModule::deserialize(module.bytecode.inner())
+                .context("Failed to deserialize module override")?;
+            self.set_bytes(
+                StateKey::module_id(&compiled_module.self_id()),
+                Some(module.bytecode.inner().to_vec().into()),
+            )?;
+        }
+
+        for resource in state_overrides.resources() {
+            let struct_tag: StructTag = resource.resource_type.clone().try_into()?;
+            let bytes = match resource.data.clone() {
+                Some(data) => {
+                    let value = self
+                        .as_converter(context.db.clone(), context.indexer_reader.clone())
+                        .try_into_vm_value(&TypeTag::Struct(Box::new(struct_tag.clone())), data)
+                        .with_context(|| {
+                            format!("Failed to convert resource override {}", struct_tag)
+                        })?;
+                    Some(bcs::to_bytes(&value)?.into())
+                },
+                None => None,
+            };
+            self.set_resource(resource.address.inner(), &struct_tag, bytes)?;
+        }
+
+        for balance in state_overrides.balances() {
+            let state_key = StateKey::resource_typed::<CoinStoreResource>(balance.address.inner())?;
+            let coin_store: CoinStoreResource = self
+                .get_state_value_bytes(&state_key)?
+                .map(|bytes| bcs::from_bytes(&bytes))
+                .transpose()?
+                .ok_or_else(|| {
+                    format_err!(
+                        "Account {} does not have a CoinStore<AptosCoin> to override",
+                        balance.address
+                    )
+                })?;
+            let coin_store = CoinStoreResource::new(
+                balance.amount.into(),
+                coin_store.frozen(),
+                coin_store.deposit_events().clone(),
+                coin_store.withdraw_events().clone(),
+            );
+            self.set_bytes(state_key, Some(bcs::to_bytes(&coin_store)?.into()))?;
+        }
+
+        if let Some(timestamp_usecs) = state_overrides.timestamp_usecs {
+            let timestamp = CurrentTimeMicroseconds {
+                microseconds: timestamp_usecs.into(),
+            };
+            self.set_bytes(
+                StateKey::on_chain_config::<CurrentTimeMicroseconds>()?,
+                Some(bcs::to_bytes(&timestamp)?.into()),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the value at the given key, or deletes it if `None`. Metadata of existing values is
+    /// preserved, so that storage fees are charged as they would be on-chain.
+    pub fn set_bytes(&mut self, state_key: StateKey, bytes: Option<Bytes>) -> Result<()> {
+        let value = match (bytes, self.get_state_value(&state_key)?) {
+            (Some(bytes), Some(existing)) => Some(existing.map_bytes(|_| Ok(bytes))?),
+            (Some(bytes), None) => Some(StateValue::new_legacy(bytes)),
+            (None, _) => None,
+        };
+        self.overrides.insert(state_key, value);
+        Ok(())
+    }
+
+    /// Applies the write set of an executed transaction on top of the current view, so that
+    /// its changes are visible to the transactions executed after it.
+    pub fn apply_write_set(&mut self, write_set: &WriteSet) {
+        for (state_key, write_op) in write_set {
+            let value = write_op.bytes().map(|bytes| {
+                StateValue::new_with_metadata(bytes.clone(), write_op.metadata().clone())
+            });
+            self.overrides.insert(state_key.clone(), value);
+        }
+    }
+
+    /// Sets or deletes a resource, taking care of resources stored in resource groups.
+    fn set_resource(
+        &mut self,
+        address: &AccountAddress,
+        struct_tag: &StructTag,
+        bytes: Option<Bytes>,
+    ) -> Result<()> {
+        let group_tag = match self.resource_group_of(struct_tag)? {
+            Some(group_tag) => group_tag,
+            None => return self.set_bytes(StateKey::resource(address, struct_tag)?, bytes),
+        };
+
+        let state_key = StateKey::resource_group(address, &group_tag);
+        let mut group: BTreeMap<StructTag, Bytes> = self
+            .get_state_value_bytes(&state_key)?
+            .map(|bytes| bcs::from_bytes(&bytes))
+            .transpose()?
+            .unwrap_or_default();
+        match bytes {
+            Some(bytes) => group.insert(struct_tag.clone(), bytes),
+            None => group.remove(struct_tag),
+        };
+
+        let group_bytes = if group.is_empty() {
+            None
+        } else {
+            Some(bcs::to_bytes(&group)?.into())
+        };
+        self.set_bytes(state_key, group_bytes)
+    }
+
+    fn resource_group_of(&self, struct_tag: &StructTag) -> Result<Option<StructTag>> {
+        let module_bytes =
+            match self.get_state_value_bytes(&StateKey::module_id(&struct_tag.module_id()))? {
+                Some(module_bytes) => module_bytes,
+                None => return Ok(None),
+            };
+        let module = CompiledModule::deserialize(&module_bytes)
+            .with_context(|| format!("Failed to deserialize module {}", struct_tag.module_id()))?;
+        Ok(get_resource_group_member_from_metadata(
+            struct_tag,
+            &module.metadata,
+        ))
+    }
+}
+
+impl<S: StateView> TStateView for OverriddenStateView<S> {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.base.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> StateViewResult<Option<StateValue>> {
+        match self.overrides.get(state_key) {
+            Some(value) => Ok(value.clone()),
+            None => self.base.get_state_value(state_key),
+        }
+    }
+
+    fn get_usage(&self) -> StateViewResult<StateStorageUsage> {
+        self.base.get_usage()
+    }
+}
@@ -1,11 +1,14 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_test_context, new_test_context_with_config};
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_config::config::NodeConfig;
 use aptos_crypto::ed25519::Ed25519Signature;
-use aptos_types::transaction::{
-    authenticator::TransactionAuthenticator, EntryFunction, TransactionPayload,
+use aptos_sdk::types::LocalAccount;
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{authenticator::TransactionAuthenticator, EntryFunction, TransactionPayload},
 };
 use move_core_types::{ident_str, language_storage::ModuleId};
 use serde_json::json;
@@ -153,3 +156,324 @@ async fn test_simulate_txn_with_aggregator() {
         unreachable!("Simulation uses Ed25519 authenticator.");
     }
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_balance_override() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = context.account_transfer_to(alice, bob.address(), LARGE_TRANSFER_AMOUNT);
+    let public_key = match txn.authenticator_ref() {
+        TransactionAuthenticator::Ed25519 { public_key, .. } => public_key.to_string(),
+        _ => unreachable!("Simulation uses Ed25519 authenticator."),
+    };
+    let transaction = json!({
+        "sender": txn.sender().to_string(),
+        "sequence_number": txn.sequence_number().to_string(),
+        "max_gas_amount": txn.max_gas_amount().to_string(),
+        "gas_unit_price": txn.gas_unit_price().to_string(),
+        "expiration_timestamp_secs": txn.expiration_timestamp_secs().to_string(),
+        "payload": {
+            "type": "entry_function_payload",
+            "function": "0x1::aptos_account::transfer",
+            "type_arguments": [],
+            "arguments": [
+                bob.address().to_standard_string(), LARGE_TRANSFER_AMOUNT.to_string(),
+            ]
+        },
+        "signature": {
+            "type": "ed25519_signature",
+            "public_key": public_key,
+            "signature": Ed25519Signature::dummy_signature().to_string(),
+        }
+    });
+
+    // Without overrides, the sender can't afford the transfer.
+    let resp = context
+        .expect_status_code(200)
+        .post("/transactions/simulate", transaction.clone())
+        .await;
+    assert!(!resp[0]["success"].as_bool().is_some_and(|v| v));
+
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": transaction,
+                "state_overrides": {
+                    "balances": [{
+                        "address": alice.address().to_standard_string(),
+                        "amount": (2 * LARGE_TRANSFER_AMOUNT).to_string(),
+                    }],
+                },
+            }),
+        )
+        .await;
+    assert!(resp[0]["success"].as_bool().is_some_and(|v| v));
+
+    // The override must not have been committed.
+    let resp = context
+        .expect_status_code(200)
+        .post("/transactions/simulate", transaction)
+        .await;
+    assert!(!resp[0]["success"].as_bool().is_some_and(|v| v));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_invalid_override() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let txn = context.account_transfer_to(alice, alice.address(), SMALL_TRANSFER_AMOUNT);
+    let public_key = match txn.authenticator_ref() {
+        TransactionAuthenticator::Ed25519 { public_key, .. } => public_key.to_string(),
+        _ => unreachable!("Simulation uses Ed25519 authenticator."),
+    };
+    let resp = context
+        .expect_status_code(400)
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": {
+                    "sender": txn.sender().to_string(),
+                    "sequence_number": txn.sequence_number().to_string(),
+                    "max_gas_amount": txn.max_gas_amount().to_string(),
+                    "gas_unit_price": txn.gas_unit_price().to_string(),
+                    "expiration_timestamp_secs": txn.expiration_timestamp_secs().to_string(),
+                    "payload": {
+                        "type": "entry_function_payload",
+                        "function": "0x1::aptos_account::transfer",
+                        "type_arguments": [],
+                        "arguments": [
+                            alice.address().to_standard_string(), SMALL_TRANSFER_AMOUNT.to_string(),
+                        ]
+                    },
+                    "signature": {
+                        "type": "ed25519_signature",
+                        "public_key": public_key,
+                        "signature": Ed25519Signature::dummy_signature().to_string(),
+                    }
+                },
+                "state_overrides": {
+                    "modules": [{ "bytecode": "0x0102" }],
+                },
+            }),
+        )
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
+
+fn transfer_simulation_request(
+    sender: &LocalAccount,
+    sequence_number: u64,
+    receiver: AccountAddress,
+    amount: u64,
+) -> serde_json::Value {
+    json!({
+        "sender": sender.address().to_standard_string(),
+        "sequence_number": sequence_number.to_string(),
+        "max_gas_amount": "200000",
+        "gas_unit_price": "100",
+        "expiration_timestamp_secs": "16373698888888",
+        "payload": {
+            "type": "entry_function_payload",
+            "function": "0x1::aptos_account::transfer",
+            "type_arguments": [],
+            "arguments": [
+                receiver.to_standard_string(), amount.to_string(),
+            ]
+        },
+        "signature": {
+            "type": "ed25519_signature",
+            "public_key": sender.public_key().to_string(),
+            "signature": Ed25519Signature::dummy_signature().to_string(),
+        }
+    })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_bundle() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    // Bob only exists once the first transaction of the bundle has been executed.
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate_bundle",
+            json!([
+                transfer_simulation_request(alice, 0, bob.address(), 50_000_000),
+                transfer_simulation_request(bob, 0, alice.address(), SMALL_TRANSFER_AMOUNT),
+            ]),
+        )
+        .await;
+    let transactions = resp["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 2);
+    for transaction in transactions {
+        assert!(transaction["success"].as_bool().is_some_and(|v| v));
+        assert!(
+            transaction["gas_used"]
+                .as_str()
+                .unwrap()
+                .parse::<u64>()
+                .unwrap()
+                > 0
+        );
+    }
+    // The changes of both transactions to bob's account are folded into a single change.
+    let bob_address = bob.address().to_hex_literal();
+    let bob_account_changes: Vec<_> = resp["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|change| {
+            change["address"] == bob_address && change["data"]["type"] == "0x1::account::Account"
+        })
+        .collect();
+    assert_eq!(bob_account_changes.len(), 1);
+    assert_eq!(
+        bob_account_changes[0]["data"]["data"]["sequence_number"],
+        "1"
+    );
+
+    // The bundle must not have been committed.
+    context
+        .expect_status_code(404)
+        .get(&format!("/accounts/{}", bob.address()))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_bundle_with_filter() {
+    let mut node_config = NodeConfig::default();
+    let alice = LocalAccount::generate(&mut rand::rngs::OsRng);
+    let bob = LocalAccount::generate(&mut rand::rngs::OsRng);
+
+    // Only allow simulations sent by alice
+    node_config.api.simulation_filter = node_config
+        .api
+        .simulation_filter
+        .clone()
+        .add_allow_sender(alice.address())
+        .add_deny_all();
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    let txn = context.mint_user_account(&alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    // The bundle must be rejected, as its second transaction is sent by bob
+    let resp = context
+        .expect_status_code(403)
+        .post(
+            "/transactions/simulate_bundle",
+            json!([
+                transfer_simulation_request(&alice, 0, bob.address(), 50_000_000),
+                transfer_simulation_request(&bob, 0, alice.address(), SMALL_TRANSFER_AMOUNT),
+            ]),
+        )
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_empty_transaction_bundle() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(400)
+        .post("/transactions/simulate_bundle", json!([]))
+        .await;
+    assert_eq!(resp["error_code"], "invalid_input");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_trace() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate?trace=true",
+            transfer_simulation_request(alice, 0, bob.address(), SMALL_TRANSFER_AMOUNT),
+        )
+        .await;
+    assert!(resp[0]["success"].as_bool().is_some_and(|v| v));
+    let trace = &resp[0]["trace"];
+    assert!(trace["abort_location"].is_null());
+    let call_tree = &trace["call_tree"];
+    assert_eq!(call_tree["function"], "0x1::aptos_account::transfer");
+    assert_eq!(
+        call_tree["arguments"],
+        json!([
+            format!("@{}", bob.address().to_hex_literal()),
+            SMALL_TRANSFER_AMOUNT.to_string()
+        ])
+    );
+    assert_eq!(call_tree["returned"], true);
+    let calls = call_tree["calls"].as_array().unwrap();
+    assert!(!calls.is_empty());
+    assert!(calls
+        .iter()
+        .all(|call| call["function"].is_string() && call["returned"] == true));
+    assert!(calls
+        .iter()
+        .any(|call| !call["arguments"].as_array().unwrap().is_empty()));
+
+    // The values returned by Move (i.e., non-native) functions are recorded as well: bob
+    // doesn't exist before the transfer.
+    let exists_at = calls
+        .iter()
+        .find(|call| call["function"] == "0x1::account::exists_at")
+        .unwrap();
+    assert_eq!(exists_at["is_native"], false);
+    assert_eq!(
+        exists_at["arguments"],
+        json!([format!("@{}", bob.address().to_hex_literal())])
+    );
+    assert_eq!(exists_at["return_values"], json!(["false"]));
+
+    // The trace is only returned when requested.
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate",
+            transfer_simulation_request(alice, 0, bob.address(), SMALL_TRANSFER_AMOUNT),
+        )
+        .await;
+    assert!(resp[0].get("trace").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_aborted_transaction_with_trace() {
+    let mut context = new_test_context(current_function_name!());
+    let alice = &mut context.gen_account();
+    let bob = &mut context.gen_account();
+    let txn = context.mint_user_account(alice).await;
+    context.commit_block(&vec![txn]).await;
+
+    let resp = context
+        .expect_status_code(200)
+        .post(
+            "/transactions/simulate?trace=true",
+            transfer_simulation_request(alice, 0, bob.address(), LARGE_TRANSFER_AMOUNT),
+        )
+        .await;
+    assert!(!resp[0]["success"].as_bool().is_some_and(|v| v));
+    let abort_location = &resp[0]["trace"]["abort_location"];
+    assert!(abort_location["abort_code"].is_string());
+    let call_stack = abort_location["call_stack"].as_array().unwrap();
+    assert!(call_stack.len() > 1);
+    assert_eq!(call_stack[0], "0x1::aptos_account::transfer");
+    assert_eq!(resp[0]["trace"]["call_tree"]["returned"], false);
+}
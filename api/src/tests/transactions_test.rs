@@ -1549,6 +1549,37 @@ async fn test_simulation_failure_with_move_abort_error_rendering() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_txn_decodes_move_abort_error() {
+    let mut context = new_test_context(current_function_name!());
+    let mut account = context.create_account().await;
+    let receiver = context.gen_account();
+    let txn = context.account_transfer(&mut account, &receiver, u64::MAX);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let resp = context
+        .get(
+            format!(
+                "/transactions/by_hash/{}",
+                txn.committed_hash().to_hex_literal()
+            )
+            .as_str(),
+        )
+        .await;
+    assert!(!resp["success"].as_bool().unwrap(), "{}", pretty(&resp));
+    assert_eq!(
+        resp["decoded_error"],
+        json!({
+            "module": "0x1::coin",
+            "code": "65542",
+            "name": "EINSUFFICIENT_BALANCE",
+            "description": "Not enough coins to complete transaction",
+        }),
+        "{}",
+        pretty(&resp)
+    );
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_simulation_failure_with_detail_error() {
     let mut context = new_test_context(current_function_name!());
@@ -4,7 +4,7 @@
 use super::{new_test_context, new_test_context_with_config};
 use aptos_api_test_context::{current_function_name, TestContext};
 use aptos_cached_packages::aptos_stdlib;
-use aptos_config::config::{NodeConfig, ViewFilter, ViewFunctionId};
+use aptos_config::config::{ApiTier, ExecutionLimits, NodeConfig, ViewFilter, ViewFunctionId};
 use aptos_types::account_address::AccountAddress;
 use serde_json::{json, Value};
 use std::{path::PathBuf, str::FromStr};
@@ -252,3 +252,217 @@ async fn test_view_aggregator() {
         .await;
     context.check_golden_output_no_prune(resp);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_with_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    let mut request = build_coin_balance_request(&owner.address());
+    request["state_overrides"] = json!({
+        "balances": [{
+            "address": owner.address().to_standard_string(),
+            "amount": "12345",
+        }],
+    });
+    let resp = context.post("/view_with_overrides", request).await;
+    assert_eq!(resp, json!(["12345"]));
+
+    // The override is only visible to the request it was sent with.
+    let resp = context
+        .post("/view", build_coin_balance_request(&owner.address()))
+        .await;
+    assert_eq!(resp, json!(["100000"]));
+
+    let resp = context
+        .post(
+            "/view_with_overrides",
+            json!({
+                "function": "0x1::timestamp::now_microseconds",
+                "arguments": [],
+                "type_arguments": [],
+                "state_overrides": { "timestamp_usecs": "1234567890" },
+            }),
+        )
+        .await;
+    assert_eq!(resp, json!(["1234567890"]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    let resp = context
+        .post(
+            "/view/batch",
+            json!({
+                "requests": [
+                    build_coin_balance_request(&owner.address()),
+                    {
+                        "function": "0x1::coin::missing_function",
+                        "arguments": [],
+                        "type_arguments": [],
+                    },
+                    build_coin_decimals_request(),
+                ],
+            }),
+        )
+        .await;
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["values"], json!(["100000"]));
+    assert!(results[0]["error"].is_null());
+    assert!(results[1]["values"].is_null());
+    assert_eq!(results[1]["error"]["error_code"], json!("invalid_input"));
+    assert_eq!(results[2]["values"], json!([8]));
+
+    // Empty batches are rejected
+    context
+        .expect_status_code(400)
+        .post("/view/batch", json!({ "requests": [] }))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_gas_limit() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.max_gas_view_batch = 1;
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    let creator = &mut context.gen_account();
+    let txn = context.mint_user_account(creator).await;
+    context.commit_block(&vec![txn]).await;
+
+    let resp = context
+        .post(
+            "/view/batch",
+            json!({
+                "requests": [
+                    build_coin_balance_request(&creator.address()),
+                    build_coin_balance_request(&creator.address()),
+                ],
+            }),
+        )
+        .await;
+    let results = resp.as_array().unwrap();
+    // The first view function runs out of the gas of the batch, and the second one is not
+    // executed.
+    assert!(results[0]["error"].is_object());
+    assert!(results[1]["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Gas limit of the view function batch reached"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_blocklist() {
+    let mut node_config = NodeConfig::default();
+
+    // Blocklist the balance function.
+    node_config.api.view_filter = ViewFilter::Blocklist(vec![ViewFunctionId {
+        address: AccountAddress::from_str("0x1").unwrap(),
+        module: "coin".to_string(),
+        function_name: "balance".to_string(),
+    }]);
+
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    let creator = &mut context.gen_account();
+    let txn = context.mint_user_account(creator).await;
+    context.commit_block(&vec![txn]).await;
+
+    // See that a batch containing a blocked function is rejected, like a single view function.
+    let resp = context
+        .expect_status_code(403)
+        .post(
+            "/view/batch",
+            json!({
+                "requests": [
+                    build_coin_decimals_request(),
+                    build_coin_balance_request(&creator.address()),
+                ],
+            }),
+        )
+        .await;
+    assert_eq!(resp["error_code"], json!("invalid_input"));
+
+    // See that a batch of non-blocked functions is allowed.
+    context
+        .expect_status_code(200)
+        .post(
+            "/view/batch",
+            json!({ "requests": [build_coin_decimals_request()] }),
+        )
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_api_tier_gas_limit() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.execution_limits.max_gas_view_function = Some(1);
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    let creator = &mut context.gen_account();
+    let txn = context.mint_user_account(creator).await;
+    context.commit_block(&vec![txn]).await;
+
+    let resp = context
+        .post(
+            "/view/batch",
+            json!({
+                "requests": [
+                    build_coin_balance_request(&creator.address()),
+                    build_coin_balance_request(&creator.address()),
+                ],
+            }),
+        )
+        .await;
+    // Each view function runs out of the gas of the tier, while the gas of the batch is
+    // not used up.
+    let results = resp.as_array().unwrap();
+    for result in results {
+        assert!(result["values"].is_null());
+        assert!(!result["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Gas limit of the view function batch reached"));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_api_tier_gas_limit() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.execution_limits.max_gas_view_function = Some(1);
+    node_config.api.api_tiers = vec![ApiTier {
+        name: "partner".to_string(),
+        api_keys: vec!["partner-key".to_string()],
+        limits: ExecutionLimits::default(),
+    }];
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+    let creator = &mut context.gen_account();
+    let txn = context.mint_user_account(creator).await;
+    context.commit_block(&vec![txn]).await;
+
+    // Without an API key, the view function runs out of the gas of the default tier
+    context
+        .expect_status_code(400)
+        .post("/view", build_coin_balance_request(&creator.address()))
+        .await;
+
+    // The partner tier falls back to the default gas limit of view functions
+    let req = warp::test::request()
+        .method("POST")
+        .path("/v1/view")
+        .header("x-aptos-api-key", "partner-key")
+        .json(&build_coin_balance_request(&creator.address()));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+}
@@ -7,6 +7,7 @@ use crate::{
     accounts::Account,
     bcs_payload::Bcs,
     context::{api_spawn_blocking, Context, FunctionStats},
+    execution_limits::{ApiKey, ExecutionKind},
     failpoint::fail_point_poem,
     generate_error_response, generate_success_response, metrics,
     metrics::WAIT_TRANSACTION_GAUGE,
@@ -17,30 +18,39 @@ use crate::{
         BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404,
         ForbiddenError, InsufficientStorageError, InternalError,
     },
+    state_override::OverriddenStateView,
     ApiTags,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
-    AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
-    Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
+    AsConverter, EncodeSubmissionRequest, ExecutionTrace, GasEstimation, GasEstimationBcs,
+    HashValue, HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction,
+    SimulateTransactionWithOverridesRequest, SimulatedTransactionBundle,
+    SimulatedTransactionBundleOnChainData, SubmitTransactionRequest, TraceAbortLocation,
+    TraceEvent, TraceFrame, TraceResourceRead, TraceResourceWrite, Transaction, TransactionData,
+    TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
     TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion,
     MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
+use aptos_gas_profiling::{render_move_value, GasProfiler};
 use aptos_types::{
     account_address::AccountAddress,
     mempool_status::MempoolStatusCode,
+    state_store::{state_key::inner::StateKeyInner, StateView},
     transaction::{
         EntryFunction, ExecutionStatus, MultisigTransactionPayload, RawTransaction,
         RawTransactionWithData, SignedTransaction, TransactionPayload,
     },
     vm_status::StatusCode,
+    write_set::WriteSetMut,
     APTOS_COIN_TYPE,
 };
 use aptos_vm::{AptosSimulationVM, AptosVM};
-use move_core_types::{ident_str, language_storage::ModuleId, vm_status::VMStatus};
+use move_core_types::{
+    ident_str, language_storage::ModuleId, value::MoveValue, vm_status::VMStatus,
+};
 use poem_openapi::{
     param::{Path, Query},
     payload::Json,
@@ -130,6 +140,15 @@ impl VerifyInput for SubmitTransactionsBatchPost {
     }
 }
 
+/// A transaction of a simulated bundle, as it was submitted
+///
+/// JSON requests can only be converted to a `SignedTransaction` once the transactions before
+/// them have been executed, as they may depend on modules published by those transactions.
+enum BundledTransaction {
+    Json(SubmitTransactionRequest),
+    Bcs(SignedTransaction),
+}
+
 /// API for interacting with transactions
 #[derive(Clone)]
 pub struct TransactionsApi {
@@ -471,6 +490,17 @@ impl TransactionsApi {
     ///
     /// To use this endpoint with BCS, you must submit a SignedTransaction
     /// encoded as BCS. See SignedTransaction in types/src/transaction/mod.rs.
+    ///
+    /// If `trace` is set, the transaction is returned with a trace of its execution: the tree
+    /// of function calls with their arguments, the resources read and written and the events
+    /// emitted by each call, and where the transaction aborted.
+    /// Tracing makes the simulation significantly slower, and is only supported with JSON
+    /// output.
+    ///
+    /// Simulations are subject to the execution limits of the caller's API tier, selected by
+    /// the `x-aptos-api-key` header: the max gas amount of the transaction may be lowered, and
+    /// requests over the concurrency limit or the timeout of the tier fail with a 503 and the
+    /// `execution_limit_exceeded` error code.
     #[oai(
         path = "/transactions/simulate",
         method = "post",
@@ -480,6 +510,7 @@ impl TransactionsApi {
     pub async fn simulate_transaction(
         &self,
         accept_type: AcceptType,
+        api_key: ApiKey,
         /// If set to true, the max gas value in the transaction will be ignored
         /// and the maximum possible gas will be used
         estimate_max_gas_amount: Query<Option<bool>>,
@@ -489,6 +520,8 @@ impl TransactionsApi {
         /// If set to true, the transaction will use a higher price than the original
         /// estimate.
         estimate_prioritized_gas_unit_price: Query<Option<bool>>,
+        /// If set to true, a trace of the execution will be returned with the transaction
+        trace: Query<Option<bool>>,
         data: SubmitTransactionPost,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
         data.verify()
@@ -505,119 +538,174 @@ impl TransactionsApi {
         }
         self.context
             .check_api_output_enabled("Simulate transaction", &accept_type)?;
+        let trace = trace.0.unwrap_or_default();
+        if trace && accept_type == AcceptType::Bcs {
+            return Err(SubmitTransactionError::bad_request_with_code_no_info(
+                "Tracing is not supported with BCS output",
+                AptosErrorCode::BcsNotSupported,
+            ));
+        }
 
         let api = self.clone();
-        let context = self.context.clone();
-        api_spawn_blocking(move || {
-            let ledger_info = context.get_latest_ledger_info()?;
-            let mut signed_transaction = api.get_signed_transaction(&ledger_info, data)?;
-
-            // Confirm the simulation filter allows the transaction. We use HashValue::zero()
-            // here for the block ID because we don't allow filtering by block ID for the
-            // simulation filters. See the ConfigSanitizer for ApiConfig.
-            if !context.node_config.api.simulation_filter.allows(
-                aptos_crypto::HashValue::zero(),
-                ledger_info.timestamp(),
-                &signed_transaction,
-            ) {
-                return Err(SubmitTransactionError::forbidden_with_code(
-                    "Transaction not allowed by simulation filter",
-                    AptosErrorCode::InvalidInput,
-                    &ledger_info,
-                ));
-            }
-
-            let estimated_gas_unit_price = match (
+        let tier = self.context.execution_tier(&api_key);
+        let max_gas_simulation = tier.max_gas_simulation();
+        tier.spawn_blocking(ExecutionKind::Simulation, move || {
+            let ledger_info = api.context.get_latest_ledger_info()?;
+            let signed_transaction = api.get_signed_transaction(&ledger_info, data)?;
+            let state_view = api.context.latest_state_view_poem(&ledger_info)?;
+            api.estimate_gas_and_simulate(
+                &accept_type,
+                ledger_info,
+                signed_transaction,
+                &state_view,
+                false,
+                estimate_max_gas_amount.0.unwrap_or_default(),
                 estimate_gas_unit_price.0.unwrap_or_default(),
                 estimate_prioritized_gas_unit_price.0.unwrap_or_default(),
-            ) {
-                (_, true) => {
-                    let gas_estimation = context.estimate_gas_price(&ledger_info)?;
-                    // The prioritized gas estimate should always be set, but if it's not use the gas estimate
-                    Some(
-                        gas_estimation
-                            .prioritized_gas_estimate
-                            .unwrap_or(gas_estimation.gas_estimate),
-                    )
-                },
-                (true, false) => Some(context.estimate_gas_price(&ledger_info)?.gas_estimate),
-                (false, false) => None,
-            };
+                max_gas_simulation,
+                trace,
+            )
+        })
+        .await
+    }
 
-            // If estimate max gas amount is provided, we will just make it the maximum value
-            let estimated_max_gas_amount = if estimate_max_gas_amount.0.unwrap_or_default() {
-                // Retrieve max possible gas units
-                let (_, gas_params) = context.get_gas_schedule(&ledger_info)?;
-                let min_number_of_gas_units =
-                    u64::from(gas_params.vm.txn.min_transaction_gas_units)
-                        / u64::from(gas_params.vm.txn.gas_unit_scaling_factor);
-                let max_number_of_gas_units =
-                    u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
-
-                // Retrieve account balance to determine max gas available, right now this is using
-                // a view function, but we may want to re-evaluate this based on performance
-                let (_, _, state_view) = context
-                    .state_view::<BasicErrorWith404>(Option::None)
-                    .map_err(|err| {
-                        SubmitTransactionError::bad_request_with_code_no_info(
-                            err,
-                            AptosErrorCode::InvalidInput,
-                        )
-                    })?;
-                let output = AptosVM::execute_view_function(
-                    &state_view,
-                    ModuleId::new(AccountAddress::ONE, ident_str!("coin").into()),
-                    ident_str!("balance").into(),
-                    vec![APTOS_COIN_TYPE.clone()],
-                    vec![signed_transaction.sender().to_vec()],
-                    context.node_config.api.max_gas_view_function,
-                );
-                let values = output.values.map_err(|err| {
-                    SubmitTransactionError::bad_request_with_code_no_info(
-                        err,
-                        AptosErrorCode::InvalidInput,
-                    )
-                })?;
-                let balance: u64 = bcs::from_bytes(&values[0]).map_err(|err| {
-                    SubmitTransactionError::bad_request_with_code_no_info(
+    /// Simulate transaction with state overrides
+    ///
+    /// Same as `/transactions/simulate`, but the transaction is executed on top of the
+    /// given state overrides: module bytecode can be replaced, and resources, APT balances
+    /// and the on-chain timestamp can be set. This can be used to test contract upgrades
+    /// or other "what-if" scenarios. The overrides are only visible to this request and
+    /// are never committed.
+    ///
+    /// The transaction must be submitted as JSON, with a zero-padded signature, in the same
+    /// format as for `/transactions/simulate`.
+    #[oai(
+        path = "/transactions/simulate_with_overrides",
+        method = "post",
+        operation_id = "simulate_transaction_with_overrides",
+        tag = "ApiTags::Transactions"
+    )]
+    pub async fn simulate_transaction_with_overrides(
+        &self,
+        accept_type: AcceptType,
+        api_key: ApiKey,
+        /// If set to true, the max gas value in the transaction will be ignored
+        /// and the maximum possible gas will be used
+        estimate_max_gas_amount: Query<Option<bool>>,
+        /// If set to true, the gas unit price in the transaction will be ignored
+        /// and the estimated value will be used
+        estimate_gas_unit_price: Query<Option<bool>>,
+        /// If set to true, the transaction will use a higher price than the original
+        /// estimate.
+        estimate_prioritized_gas_unit_price: Query<Option<bool>>,
+        data: Json<SimulateTransactionWithOverridesRequest>,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        data.0
+            .verify()
+            .context("Simulated transaction invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_with_overrides")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction"));
+        }
+        self.context
+            .check_api_output_enabled("Simulate transaction", &accept_type)?;
+
+        let api = self.clone();
+        let tier = self.context.execution_tier(&api_key);
+        let max_gas_simulation = tier.max_gas_simulation();
+        tier.spawn_blocking(ExecutionKind::Simulation, move || {
+            let SimulateTransactionWithOverridesRequest {
+                transaction,
+                state_overrides,
+            } = data.0;
+            let ledger_info = api.context.get_latest_ledger_info()?;
+            let signed_transaction = api.get_signed_transaction(
+                &ledger_info,
+                SubmitTransactionPost::Json(Json(transaction)),
+            )?;
+
+            let mut state_view =
+                OverriddenStateView::new(api.context.latest_state_view_poem(&ledger_info)?);
+            state_view
+                .apply_state_overrides(&api.context, &state_overrides)
+                .context("Failed to apply state overrides")
+                .map_err(|err| {
+                    SubmitTransactionError::bad_request_with_code(
                         err,
                         AptosErrorCode::InvalidInput,
+                        &ledger_info,
                     )
                 })?;
 
-                let gas_unit_price =
-                    estimated_gas_unit_price.unwrap_or_else(|| signed_transaction.gas_unit_price());
-
-                // With 0 gas price, we set it to max gas units, since we can't divide by 0
-                let max_account_gas_units = if gas_unit_price == 0 {
-                    balance
-                } else {
-                    balance / gas_unit_price
-                };
-
-                // To give better error messaging, we should not go below the minimum number of gas units
-                let max_account_gas_units =
-                    std::cmp::max(min_number_of_gas_units, max_account_gas_units);
-
-                // Minimum of the max account and the max total needs to be used for estimation
-                Some(std::cmp::min(
-                    max_account_gas_units,
-                    max_number_of_gas_units,
-                ))
-            } else {
-                None
-            };
+            api.estimate_gas_and_simulate(
+                &accept_type,
+                ledger_info,
+                signed_transaction,
+                &state_view,
+                state_overrides.overrides_code(),
+                estimate_max_gas_amount.0.unwrap_or_default(),
+                estimate_gas_unit_price.0.unwrap_or_default(),
+                estimate_prioritized_gas_unit_price.0.unwrap_or_default(),
+                max_gas_simulation,
+                false,
+            )
+        })
+        .await
+    }
 
-            // If there is an estimation of either, replace the values
-            if estimated_max_gas_amount.is_some() || estimated_gas_unit_price.is_some() {
-                signed_transaction = override_gas_parameters(
-                    &signed_transaction,
-                    estimated_max_gas_amount,
-                    estimated_gas_unit_price,
-                );
-            }
+    /// Simulate a bundle of transactions
+    ///
+    /// Simulates an ordered list of transactions, each executed on top of the changes of the
+    /// transactions before it, without committing any of them. This can be used to simulate
+    /// flows spanning multiple transactions, e.g. publishing a module and then calling it, or
+    /// creating a multisig transaction and then executing it.
+    ///
+    /// The output contains each simulated transaction, with its events and gas used, and the
+    /// aggregated changes of the whole bundle. As for `/transactions/simulate`, transactions
+    /// must have zero-padded signatures, and their sequence numbers must follow each other if
+    /// they are sent by the same account.
+    ///
+    /// To use this endpoint with BCS, you must submit the SignedTransactions
+    /// encoded as BCS. See SignedTransaction in types/src/transaction/mod.rs.
+    #[oai(
+        path = "/transactions/simulate_bundle",
+        method = "post",
+        operation_id = "simulate_transaction_bundle",
+        tag = "ApiTags::Transactions"
+    )]
+    pub async fn simulate_transaction_bundle(
+        &self,
+        accept_type: AcceptType,
+        api_key: ApiKey,
+        data: SubmitTransactionsBatchPost,
+    ) -> SimulateTransactionResult<SimulatedTransactionBundle> {
+        data.verify()
+            .context("Simulated transactions invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_bundle")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction bundle"));
+        }
+        self.context
+            .check_api_output_enabled("Simulate transaction bundle", &accept_type)?;
 
-            api.simulate(&accept_type, ledger_info, signed_transaction)
+        let api = self.clone();
+        let tier = self.context.execution_tier(&api_key);
+        let max_gas_simulation = tier.max_gas_simulation();
+        tier.spawn_blocking(ExecutionKind::Simulation, move || {
+            let ledger_info = api.context.get_latest_ledger_info()?;
+            api.simulate_bundle(&accept_type, ledger_info, data, max_gas_simulation)
         })
         .await
     }
@@ -1339,6 +1427,141 @@ impl TransactionsApi {
         ))
     }
 
+    /// Estimates the gas parameters of the transaction if requested, and then simulates it on
+    /// top of the given state view, with at most `max_gas_simulation` gas
+    #[allow(clippy::too_many_arguments)]
+    fn estimate_gas_and_simulate(
+        &self,
+        accept_type: &AcceptType,
+        ledger_info: LedgerInfo,
+        mut signed_transaction: SignedTransaction,
+        state_view: &impl StateView,
+        isolated_code_cache: bool,
+        estimate_max_gas_amount: bool,
+        estimate_gas_unit_price: bool,
+        estimate_prioritized_gas_unit_price: bool,
+        max_gas_simulation: Option<u64>,
+        trace: bool,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        self.check_simulation_filter(&ledger_info, &signed_transaction)?;
+
+        let estimated_gas_unit_price =
+            match (estimate_gas_unit_price, estimate_prioritized_gas_unit_price) {
+                (_, true) => {
+                    let gas_estimation = self.context.estimate_gas_price(&ledger_info)?;
+                    // The prioritized gas estimate should always be set, but if it's not use the gas estimate
+                    Some(
+                        gas_estimation
+                            .prioritized_gas_estimate
+                            .unwrap_or(gas_estimation.gas_estimate),
+                    )
+                },
+                (true, false) => Some(self.context.estimate_gas_price(&ledger_info)?.gas_estimate),
+                (false, false) => None,
+            };
+
+        // If estimate max gas amount is provided, we will just make it the maximum value
+        let estimated_max_gas_amount = if estimate_max_gas_amount {
+            // Retrieve max possible gas units
+            let (_, gas_params) = self.context.get_gas_schedule(&ledger_info)?;
+            let min_number_of_gas_units = u64::from(gas_params.vm.txn.min_transaction_gas_units)
+                / u64::from(gas_params.vm.txn.gas_unit_scaling_factor);
+            let max_number_of_gas_units = u64::from(gas_params.vm.txn.maximum_number_of_gas_units);
+
+            // Retrieve account balance to determine max gas available, right now this is using
+            // a view function, but we may want to re-evaluate this based on performance.
+            // The balance is read from the same state the transaction is simulated on.
+            let vm = if isolated_code_cache {
+                AptosVM::new_with_isolated_code_cache(state_view)
+            } else {
+                AptosVM::new(state_view)
+            };
+            let output = vm.execute_view(
+                state_view,
+                ModuleId::new(AccountAddress::ONE, ident_str!("coin").into()),
+                ident_str!("balance").into(),
+                vec![APTOS_COIN_TYPE.clone()],
+                vec![signed_transaction.sender().to_vec()],
+                self.context.node_config.api.max_gas_view_function,
+            );
+            let values = output.values.map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+            let balance: u64 = bcs::from_bytes(&values[0]).map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+
+            let gas_unit_price =
+                estimated_gas_unit_price.unwrap_or_else(|| signed_transaction.gas_unit_price());
+
+            // With 0 gas price, we set it to max gas units, since we can't divide by 0
+            let max_account_gas_units = if gas_unit_price == 0 {
+                balance
+            } else {
+                balance / gas_unit_price
+            };
+
+            // To give better error messaging, we should not go below the minimum number of gas units
+            let max_account_gas_units =
+                std::cmp::max(min_number_of_gas_units, max_account_gas_units);
+
+            // Minimum of the max account and the max total needs to be used for estimation
+            Some(std::cmp::min(
+                max_account_gas_units,
+                max_number_of_gas_units,
+            ))
+        } else {
+            None
+        };
+
+        // If there is an estimation of either, replace the values
+        if estimated_max_gas_amount.is_some() || estimated_gas_unit_price.is_some() {
+            signed_transaction = override_gas_parameters(
+                &signed_transaction,
+                estimated_max_gas_amount,
+                estimated_gas_unit_price,
+            );
+        }
+        let signed_transaction = cap_max_gas_amount(signed_transaction, max_gas_simulation);
+
+        self.simulate_with_state_view(
+            accept_type,
+            ledger_info,
+            signed_transaction,
+            state_view,
+            isolated_code_cache,
+            trace,
+        )
+    }
+
+    /// Confirms the simulation filter allows the transaction. We use HashValue::zero()
+    /// here for the block ID because we don't allow filtering by block ID for the
+    /// simulation filters. See the ConfigSanitizer for ApiConfig.
+    fn check_simulation_filter(
+        &self,
+        ledger_info: &LedgerInfo,
+        signed_transaction: &SignedTransaction,
+    ) -> Result<(), SubmitTransactionError> {
+        if !self.context.node_config.api.simulation_filter.allows(
+            aptos_crypto::HashValue::zero(),
+            ledger_info.timestamp(),
+            signed_transaction,
+        ) {
+            return Err(SubmitTransactionError::forbidden_with_code(
+                "Transaction not allowed by simulation filter",
+                AptosErrorCode::InvalidInput,
+                ledger_info,
+            ));
+        }
+        Ok(())
+    }
+
     // TODO: This function leverages a lot of types from aptos_types, use the
     // local API types and just return those directly, instead of converting
     // from these types in render_transactions.
@@ -1352,27 +1575,276 @@ impl TransactionsApi {
         ledger_info: LedgerInfo,
         txn: SignedTransaction,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
+        self.simulate_with_state_view(accept_type, ledger_info, txn, &state_view, false, false)
+    }
+
+    /// Simulate a transaction in the VM on top of the given state view
+    ///
+    /// If `isolated_code_cache` is set, the VM does not share its code cache with other VMs,
+    /// which is required when the state view contains code which is not on-chain. If `trace`
+    /// is set, the JSON output contains a trace of the execution.
+    pub fn simulate_with_state_view(
+        &self,
+        accept_type: &AcceptType,
+        ledger_info: LedgerInfo,
+        txn: SignedTransaction,
+        state_view: &impl StateView,
+        isolated_code_cache: bool,
+        trace: bool,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        let (vm_status, simulated_txn, execution_trace) =
+            self.execute_simulation(&ledger_info, txn, state_view, isolated_code_cache, trace)?;
+        let gas_used = simulated_txn.info.gas_used();
+
+        let result = match accept_type {
+            AcceptType::Json => {
+                let mut user_transactions = self.render_simulated_transactions(
+                    &ledger_info,
+                    state_view,
+                    vec![(vm_status, simulated_txn)],
+                )?;
+                if let Some(user_txn) = user_transactions.first_mut() {
+                    user_txn.trace = execution_trace;
+                }
+                BasicResponse::try_from_json((
+                    user_transactions,
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
+            },
+        };
+
+        result.map(|r| r.with_gas_used(Some(gas_used)))
+    }
+
+    /// Simulate a bundle of transactions in the VM
+    ///
+    /// The transactions are executed in order, each on top of the changes of the transactions
+    /// before it. JSON requests are converted lazily, so that they can call modules published
+    /// earlier in the bundle.
+    pub fn simulate_bundle(
+        &self,
+        accept_type: &AcceptType,
+        ledger_info: LedgerInfo,
+        data: SubmitTransactionsBatchPost,
+        max_gas_simulation: Option<u64>,
+    ) -> SimulateTransactionResult<SimulatedTransactionBundle> {
+        let bundle = match data {
+            SubmitTransactionsBatchPost::Bcs(data) => {
+                let signed_transactions: Vec<SignedTransaction> = bcs::from_bytes(&data.0)
+                    .context("Failed to deserialize input into SignedTransaction")
+                    .map_err(|err| {
+                        SubmitTransactionError::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            &ledger_info,
+                        )
+                    })?;
+                signed_transactions
+                    .into_iter()
+                    .map(BundledTransaction::Bcs)
+                    .collect::<Vec<_>>()
+            },
+            SubmitTransactionsBatchPost::Json(data) => {
+                data.0.into_iter().map(BundledTransaction::Json).collect()
+            },
+        };
+        if bundle.is_empty() || self.context.max_submit_transaction_batch_size() < bundle.len() {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                format!(
+                    "Simulated bundle must contain between 1 and {} transactions, got {}",
+                    self.context.max_submit_transaction_batch_size(),
+                    bundle.len(),
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let mut state_view =
+            OverriddenStateView::new(self.context.latest_state_view_poem(&ledger_info)?);
+        let mut simulated_txns = Vec::with_capacity(bundle.len());
+        let mut changes = WriteSetMut::default();
+        // Once a transaction in the bundle writes code, the following transactions must not
+        // share their code cache, as the code is not on-chain.
+        let mut isolated_code_cache = false;
+        for (index, bundled_txn) in bundle.into_iter().enumerate() {
+            let txn = match bundled_txn {
+                BundledTransaction::Bcs(txn) => txn,
+                BundledTransaction::Json(request) => state_view
+                    .as_converter(self.context.db.clone(), self.context.indexer_reader.clone())
+                    .try_into_signed_transaction_poem(request, self.context.chain_id())
+                    .context(format!(
+                        "Failed to create SignedTransaction from SubmitTransactionRequest at position {}",
+                        index
+                    ))
+                    .map_err(|err| {
+                        SubmitTransactionError::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            &ledger_info,
+                        )
+                    })?,
+            };
+            self.check_simulation_filter(&ledger_info, &txn)?;
+            let txn = cap_max_gas_amount(txn, max_gas_simulation);
+
+            let (vm_status, simulated_txn, _trace) = self.execute_simulation(
+                &ledger_info,
+                txn,
+                &state_view,
+                isolated_code_cache,
+                false,
+            )?;
+            state_view.apply_write_set(&simulated_txn.changes);
+            isolated_code_cache |= simulated_txn.changes.iter().any(|(state_key, _)| {
+                matches!(state_key.inner(), StateKeyInner::AccessPath(path) if path.is_code())
+            });
+            // Fold the writes into the bundle's changes, so that each key reflects the
+            // net effect of the bundle (e.g., a resource created and then deleted is dropped).
+            changes = changes
+                .squash(simulated_txn.changes.clone().into_mut())
+                .context(format!(
+                    "Failed to squash the changes of the transaction at position {}",
+                    index
+                ))
+                .map_err(|err| {
+                    SubmitTransactionError::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                })?;
+            simulated_txns.push((vm_status, simulated_txn));
+        }
+
+        let gas_used = simulated_txns
+            .iter()
+            .map(|(_, simulated_txn)| simulated_txn.info.gas_used())
+            .sum();
+        let changes = changes.freeze().map_err(|err| {
+            SubmitTransactionError::internal_with_code(
+                err,
+                AptosErrorCode::InternalError,
+                &ledger_info,
+            )
+        })?;
+
+        let result = match accept_type {
+            AcceptType::Json => {
+                let converter = state_view
+                    .as_converter(self.context.db.clone(), self.context.indexer_reader.clone());
+                let changes = changes
+                    .into_iter()
+                    .map(|(state_key, write_op)| {
+                        converter.try_into_write_set_changes(state_key, write_op)
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .context("Failed to convert the changes of the simulated bundle")
+                    .map_err(|err| {
+                        SubmitTransactionError::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                let transactions =
+                    self.render_simulated_transactions(&ledger_info, &state_view, simulated_txns)?;
+                BasicResponse::try_from_json((
+                    SimulatedTransactionBundle {
+                        transactions,
+                        changes,
+                    },
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => BasicResponse::try_from_bcs((
+                SimulatedTransactionBundleOnChainData {
+                    transactions: simulated_txns
+                        .into_iter()
+                        .map(|(_, simulated_txn)| simulated_txn)
+                        .collect(),
+                    changes,
+                },
+                &ledger_info,
+                BasicResponseStatus::Ok,
+            )),
+        };
+
+        result.map(|r| r.with_gas_used(Some(gas_used)))
+    }
+
+    /// Executes a transaction in the simulation VM, and builds the transaction as it would
+    /// have been committed from its output. If `trace` is set, the execution is traced with
+    /// the gas profiler, and the trace is returned as well.
+    fn execute_simulation(
+        &self,
+        ledger_info: &LedgerInfo,
+        txn: SignedTransaction,
+        state_view: &impl StateView,
+        isolated_code_cache: bool,
+        trace: bool,
+    ) -> Result<(VMStatus, TransactionOnChainData, Option<ExecutionTrace>), SubmitTransactionError>
+    {
         // The caller must ensure that the signature is not valid, as otherwise
         // a malicious actor could execute the transaction without their knowledge
         if txn.verify_signature().is_ok() {
             return Err(SubmitTransactionError::bad_request_with_code(
                 "Simulated transactions must not have a valid signature",
                 AptosErrorCode::InvalidInput,
-                &ledger_info,
+                ledger_info,
+            ));
+        }
+        // The function executed by a multisig transaction without a payload is only known
+        // once the transaction is executed
+        let is_multisig_without_payload = match txn.payload() {
+            TransactionPayload::Multisig(multisig) => multisig.transaction_payload.is_none(),
+            _ => false,
+        };
+        if trace && is_multisig_without_payload {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                "Tracing is not supported for multisig transactions without a payload",
+                AptosErrorCode::InvalidInput,
+                ledger_info,
             ));
         }
 
         // Simulate transaction
-        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
-        let (vm_status, output) =
-            AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, &state_view);
-        let version = ledger_info.version();
+        let vm = if isolated_code_cache {
+            AptosSimulationVM::new_with_isolated_code_cache(state_view)
+        } else {
+            AptosSimulationVM::new(state_view)
+        };
+        let (vm_status, output, gas_profiler) = if trace {
+            let args = self.render_payload_arguments(state_view, txn.payload());
+            vm.simulate_signed_transaction_with_modified_gas_meter(&txn, state_view, |gas_meter| {
+                new_tracing_gas_profiler(gas_meter, txn.payload(), args)
+            })
+        } else {
+            let (vm_status, output) = vm.simulate_signed_transaction(&txn, state_view);
+            (vm_status, output, None)
+        };
 
         // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
         let exe_status = ExecutionStatus::conmbine_vm_status_for_simulation(
             output.auxiliary_data(),
             output.status().clone(),
         );
+        let execution_trace = gas_profiler.map(|gas_profiler| {
+            let call_graph = gas_profiler.finish().exec_io.call_graph;
+            api_execution_trace(aptos_gas_profiling::ExecutionTrace::new(
+                &call_graph,
+                &exe_status,
+            ))
+        });
 
         let stats_key = match txn.payload() {
             TransactionPayload::Script(_) => {
@@ -1415,63 +1887,89 @@ impl TransactionsApi {
             exe_status,
         );
         let simulated_txn = TransactionOnChainData {
-            version,
+            version: ledger_info.version(),
             transaction: txn,
             info,
             events: output.events().to_vec(),
             accumulator_root_hash: zero_hash,
             changes: output.write_set().clone(),
         };
+        Ok((vm_status, simulated_txn, execution_trace))
+    }
 
-        let result = match accept_type {
-            AcceptType::Json => {
-                let transactions = self
-                    .context
-                    .render_transactions_non_sequential(&ledger_info, vec![simulated_txn])?;
-
-                // Users can only make requests to simulate UserTransactions, so unpack
-                // the Vec<Transaction> into Vec<UserTransaction>.
-                let mut user_transactions = Vec::new();
-                for transaction in transactions.into_iter() {
-                    match transaction {
-                        Transaction::UserTransaction(user_txn) => {
-                            let mut txn = *user_txn;
-                            match &vm_status {
-                                VMStatus::Error {
-                                    message: Some(msg), ..
-                                }
-                                | VMStatus::ExecutionFailure {
-                                    message: Some(msg), ..
-                                } => {
-                                    txn.info.vm_status +=
-                                        format!("\nExecution failed with message: {}", msg)
-                                            .as_str();
-                                },
-                                _ => (),
-                            }
-                            user_transactions.push(txn);
-                        },
-                        _ => {
-                            return Err(SubmitTransactionError::internal_with_code(
-                                "Simulation transaction resulted in a non-UserTransaction",
-                                AptosErrorCode::InternalError,
-                                &ledger_info,
-                            ))
-                        },
-                    }
-                }
-                BasicResponse::try_from_json((
-                    user_transactions,
-                    &ledger_info,
-                    BasicResponseStatus::Ok,
-                ))
-            },
-            AcceptType::Bcs => {
-                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
-            },
+    /// Renders the arguments of the function executed by the payload, which are not observed by
+    /// the gas profiler. Arguments which cannot be decoded are rendered as bytes.
+    fn render_payload_arguments(
+        &self,
+        state_view: &impl StateView,
+        payload: &TransactionPayload,
+    ) -> Vec<String> {
+        if let TransactionPayload::Script(script) = payload {
+            return script
+                .args()
+                .iter()
+                .map(|arg| render_move_value(&MoveValue::from(arg.clone())))
+                .collect();
+        }
+        let Some(entry_function) = payload_entry_function(payload) else {
+            return vec![];
         };
+        let values = state_view
+            .as_converter(self.context.db.clone(), self.context.indexer_reader.clone())
+            .try_into_vm_entry_function_arguments(entry_function)
+            .unwrap_or_else(|_| {
+                entry_function
+                    .args()
+                    .iter()
+                    .map(|arg| MoveValue::vector_u8(arg.clone()))
+                    .collect()
+            });
+        values.iter().map(render_move_value).collect()
+    }
 
-        result.map(|r| r.with_gas_used(Some(output.gas_used())))
+    /// Renders simulated transactions as JSON, adding the VM error messages to their status
+    fn render_simulated_transactions(
+        &self,
+        ledger_info: &LedgerInfo,
+        state_view: &impl StateView,
+        simulated_txns: Vec<(VMStatus, TransactionOnChainData)>,
+    ) -> Result<Vec<UserTransaction>, SubmitTransactionError> {
+        let (vm_statuses, data): (Vec<_>, Vec<_>) = simulated_txns.into_iter().unzip();
+        let transactions =
+            self.context
+                .render_transactions_with_state_view(ledger_info, state_view, data)?;
+
+        // Users can only make requests to simulate UserTransactions, so unpack
+        // the Vec<Transaction> into Vec<UserTransaction>.
+        let mut user_transactions = Vec::new();
+        for (transaction, vm_status) in transactions.into_iter().zip(vm_statuses) {
+            match transaction {
+                Transaction::UserTransaction(user_txn) => {
+                    let mut txn = *user_txn;
+                    match vm_status {
+                        VMStatus::Error {
+                            message: Some(msg), ..
+                        }
+                        | VMStatus::ExecutionFailure {
+                            message: Some(msg), ..
+                        } => {
+                            txn.info.vm_status +=
+                                format!("\nExecution failed with message: {}", msg).as_str();
+                        },
+                        _ => (),
+                    }
+                    user_transactions.push(txn);
+                },
+                _ => {
+                    return Err(SubmitTransactionError::internal_with_code(
+                        "Simulation transaction resulted in a non-UserTransaction",
+                        AptosErrorCode::InternalError,
+                        ledger_info,
+                    ))
+                },
+            }
+        }
+        Ok(user_transactions)
     }
 
     /// Encode message as BCS
@@ -1532,6 +2030,84 @@ impl TransactionsApi {
     }
 }
 
+/// The entry function executed by the payload, if any
+fn payload_entry_function(payload: &TransactionPayload) -> Option<&EntryFunction> {
+    match payload {
+        TransactionPayload::EntryFunction(entry_function) => Some(entry_function),
+        TransactionPayload::Multisig(multisig) => match &multisig.transaction_payload {
+            Some(MultisigTransactionPayload::EntryFunction(entry_function)) => Some(entry_function),
+            None => None,
+        },
+        TransactionPayload::Script(_) | TransactionPayload::ModuleBundle(_) => None,
+    }
+}
+
+/// Creates a gas profiler recording the values of the execution of the given payload, with the
+/// called function and its rendered arguments as its root frame
+fn new_tracing_gas_profiler<G>(
+    gas_meter: G,
+    payload: &TransactionPayload,
+    args: Vec<String>,
+) -> GasProfiler<G> {
+    let gas_profiler = match payload_entry_function(payload) {
+        Some(entry_function) => GasProfiler::new_function(
+            gas_meter,
+            entry_function.module().clone(),
+            entry_function.function().to_owned(),
+            entry_function.ty_args().to_vec(),
+        ),
+        None => GasProfiler::new_script(gas_meter),
+    };
+    gas_profiler.with_value_tracing(args)
+}
+
+/// Converts the execution trace built from the gas profiler into its API representation
+fn api_execution_trace(trace: aptos_gas_profiling::ExecutionTrace) -> ExecutionTrace {
+    ExecutionTrace {
+        call_tree: api_trace_frame(trace.call_tree),
+        abort_location: trace.abort_location.map(|location| TraceAbortLocation {
+            module: location.module,
+            abort_code: location.abort_code.map(U64::from),
+            call_stack: location.call_stack,
+        }),
+    }
+}
+
+fn api_trace_frame(frame: aptos_gas_profiling::TraceFrame) -> TraceFrame {
+    TraceFrame {
+        function: frame.function,
+        is_native: frame.is_native,
+        arguments: frame.arguments,
+        returned: frame.returned,
+        return_values: frame.return_values,
+        resource_reads: frame
+            .resource_reads
+            .into_iter()
+            .map(|read| TraceResourceRead {
+                address: read.address.into(),
+                resource_type: read.resource_type,
+            })
+            .collect(),
+        resource_writes: frame
+            .resource_writes
+            .into_iter()
+            .map(|write| TraceResourceWrite {
+                operation: write.operation,
+                resource_type: write.resource_type,
+            })
+            .collect(),
+        events: frame
+            .events
+            .into_iter()
+            .map(|event| TraceEvent {
+                event_type: event.event_type,
+                data: event.data,
+            })
+            .collect(),
+        calls: frame.calls.into_iter().map(api_trace_frame).collect(),
+    }
+}
+
 fn override_gas_parameters(
     signed_txn: &SignedTransaction,
     max_gas_amount: Option<u64>,
@@ -1553,6 +2129,19 @@ fn override_gas_parameters(
     SignedTransaction::new_signed_transaction(raw_txn, signed_txn.authenticator())
 }
 
+/// Lowers the max gas amount of the transaction to `max_gas_amount`, if it is higher
+fn cap_max_gas_amount(
+    signed_txn: SignedTransaction,
+    max_gas_amount: Option<u64>,
+) -> SignedTransaction {
+    match max_gas_amount {
+        Some(max_gas_amount) if signed_txn.max_gas_amount() > max_gas_amount => {
+            override_gas_parameters(&signed_txn, Some(max_gas_amount), None)
+        },
+        _ => signed_txn,
+    }
+}
+
 enum GetByVersionResponse {
     VersionTooNew,
     VersionTooOld,
@@ -4,20 +4,24 @@
 use crate::{
     accept_type::AcceptType,
     bcs_payload::Bcs,
-    context::{api_spawn_blocking, FunctionStats},
+    context::FunctionStats,
+    execution_limits::{ApiKey, ExecutionKind},
     failpoint::fail_point_poem,
     response::{
         BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
         ForbiddenError, InternalError,
     },
+    state_override::OverriddenStateView,
     ApiTags, Context,
 };
 use anyhow::Context as anyhowContext;
 use aptos_api_types::{
-    AptosErrorCode, AsConverter, MoveValue, ViewFunction, ViewRequest, MAX_RECURSIVE_TYPES_ALLOWED,
-    U64,
+    AptosError, AptosErrorCode, AsConverter, LedgerInfo, MoveValue, VerifyInput, ViewBatchRequest,
+    ViewBatchResult, ViewFunction, ViewRequest, ViewWithOverridesRequest,
+    MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_bcs_utils::serialize_uleb128;
+use aptos_types::state_store::StateView;
 use aptos_vm::AptosVM;
 use itertools::Itertools;
 use move_core_types::language_storage::TypeTag;
@@ -45,6 +49,10 @@ impl ViewFunctionApi {
     ///
     /// Execute the Move function with the given parameters and return its execution result.
     ///
+    /// View functions are subject to the execution limits of the caller's API tier, selected
+    /// by the `x-aptos-api-key` header: requests over the concurrency limit or the timeout of
+    /// the tier fail with a 503 and the `execution_limit_exceeded` error code.
+    ///
     /// The Aptos nodes prune account state history, via a configurable time window.
     /// If the requested ledger version has been pruned, the server responds with a 410.
     #[oai(
@@ -56,6 +64,7 @@ impl ViewFunctionApi {
     async fn view_function(
         &self,
         accept_type: AcceptType,
+        api_key: ApiKey,
         /// View function request with type and position arguments
         request: ViewFunctionRequest,
         /// Ledger version to get state of account
@@ -68,8 +77,118 @@ impl ViewFunctionApi {
             .check_api_output_enabled("View function", &accept_type)?;
 
         let context = self.context.clone();
-        api_spawn_blocking(move || view_request(context, accept_type, request, ledger_version))
-            .await
+        let tier = context.execution_tier(&api_key);
+        let max_gas = tier.max_gas_view_function();
+        tier.spawn_blocking(ExecutionKind::ViewFunction, move || {
+            view_request(context, accept_type, request, ledger_version, max_gas)
+        })
+        .await
+    }
+
+    /// Execute view function of a module with state overrides
+    ///
+    /// Same as `/view`, but the function is executed on top of the given state overrides,
+    /// e.g. to test a module upgrade or a balance change before it happens on-chain. The
+    /// overrides are only visible to this request and are never committed.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/view_with_overrides",
+        method = "post",
+        operation_id = "view_with_overrides",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function_with_overrides(
+        &self,
+        accept_type: AcceptType,
+        api_key: ApiKey,
+        /// View function request with type and position arguments, and state overrides
+        request: Json<ViewWithOverridesRequest>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<MoveValue>> {
+        request
+            .0
+            .verify()
+            .context("View function request invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_view_function_with_overrides")?;
+        self.context
+            .check_api_output_enabled("View function", &accept_type)?;
+
+        let context = self.context.clone();
+        let tier = context.execution_tier(&api_key);
+        let max_gas = tier.max_gas_view_function();
+        tier.spawn_blocking(ExecutionKind::ViewFunction, move || {
+            view_request_with_overrides(context, accept_type, request.0, ledger_version, max_gas)
+        })
+        .await
+    }
+
+    /// Execute a batch of view functions
+    ///
+    /// Executes each view function of the batch against the same ledger version, and returns
+    /// the return values or the error of each of them, in the order of the requests. A failing
+    /// view function doesn't fail the other ones.
+    ///
+    /// Each view function is limited to the gas of a single view function, and all of them
+    /// together to the gas of a batch. The view functions which are left when the gas of the
+    /// batch is used up fail without being executed. If any of the view functions is not
+    /// allowed by the node, the whole batch is rejected with a 403. Only JSON output is
+    /// supported.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/view/batch",
+        method = "post",
+        operation_id = "view_batch",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function_batch(
+        &self,
+        accept_type: AcceptType,
+        api_key: ApiKey,
+        /// View function requests with type and position arguments
+        request: Json<ViewBatchRequest>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<ViewBatchResult>> {
+        fail_point_poem("endpoint_view_function_batch")?;
+        self.context
+            .check_api_output_enabled("View function batch", &accept_type)?;
+        if accept_type == AcceptType::Bcs {
+            return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                "BCS is not supported for view function batches",
+                AptosErrorCode::BcsNotSupported,
+            ));
+        }
+        let num_requests = request.0.requests.len();
+        if num_requests == 0 || num_requests > self.context.max_view_batch_size() {
+            return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                format!(
+                    "View function batch must contain between 1 and {} requests, got {}",
+                    self.context.max_view_batch_size(),
+                    num_requests,
+                ),
+                AptosErrorCode::InvalidInput,
+            ));
+        }
+
+        let context = self.context.clone();
+        let tier = context.execution_tier(&api_key);
+        let max_gas = tier.max_gas_view_function();
+        tier.spawn_blocking(ExecutionKind::ViewFunction, move || {
+            view_batch_request(context, request.0, ledger_version, max_gas)
+        })
+        .await
     }
 }
 
@@ -78,6 +197,7 @@ fn view_request(
     accept_type: AcceptType,
     request: ViewFunctionRequest,
     ledger_version: Query<Option<U64>>,
+    max_gas: u64,
 ) -> BasicResultWith404<Vec<MoveValue>> {
     // Retrieve the current state of the chain
     let (ledger_info, requested_version) = context
@@ -117,28 +237,251 @@ fn view_request(
         },
     };
 
-    // Reject the request if it's not allowed by the filter.
-    if !context.node_config.api.view_filter.allows(
+    execute_view_function(
+        &context,
+        &accept_type,
+        &ledger_info,
+        &state_view,
+        view_function,
+        max_gas,
+        false,
+    )
+}
+
+fn view_request_with_overrides(
+    context: Arc<Context>,
+    accept_type: AcceptType,
+    request: ViewWithOverridesRequest,
+    ledger_version: Query<Option<U64>>,
+    max_gas: u64,
+) -> BasicResultWith404<Vec<MoveValue>> {
+    let (ledger_info, requested_version) = context
+        .get_latest_ledger_info_and_verify_lookup_version(ledger_version.map(|inner| inner.0))?;
+
+    let mut state_view =
+        OverriddenStateView::new(context.state_view_at_version(requested_version).map_err(
+            |err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            },
+        )?);
+    state_view
+        .apply_state_overrides(&context, &request.state_overrides)
+        .context("Failed to apply state overrides")
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            )
+        })?;
+
+    // The function may only exist in the overridden modules, so it has to be resolved
+    // against the overridden state.
+    let view_function = state_view
+        .as_converter(context.db.clone(), context.indexer_reader.clone())
+        .convert_view_function(request.view_request)
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            )
+        })?;
+
+    execute_view_function(
+        &context,
+        &accept_type,
+        &ledger_info,
+        &state_view,
+        view_function,
+        max_gas,
+        request.state_overrides.overrides_code(),
+    )
+}
+
+fn view_batch_request(
+    context: Arc<Context>,
+    request: ViewBatchRequest,
+    ledger_version: Query<Option<U64>>,
+    max_gas: u64,
+) -> BasicResultWith404<Vec<ViewBatchResult>> {
+    let (ledger_info, requested_version) = context
+        .get_latest_ledger_info_and_verify_lookup_version(ledger_version.map(|inner| inner.0))?;
+
+    let state_view = context
+        .state_view_at_version(requested_version)
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InternalError,
+                &ledger_info,
+            )
+        })?;
+
+    // Reject the whole batch if any of the view functions is not allowed by the filter,
+    // like a single view function would be.
+    let view_functions = request
+        .requests
+        .into_iter()
+        .map(|view_request| {
+            state_view
+                .as_converter(context.db.clone(), context.indexer_reader.clone())
+                .convert_view_function(view_request)
+                .map_err(|err| AptosError::new_with_error_code(err, AptosErrorCode::InvalidInput))
+        })
+        .collect::<Vec<_>>();
+    for view_function in view_functions.iter().flatten() {
+        check_view_function_allowed(&context, view_function)?;
+    }
+
+    // All the view functions share one VM, so that modules are only loaded once per batch
+    let vm = AptosVM::new(&state_view);
+    let mut remaining_gas = context.node_config.api.max_gas_view_batch;
+    let mut results = Vec::with_capacity(view_functions.len());
+    for view_function in view_functions {
+        let (result, gas_used) = match view_function {
+            Ok(view_function) => execute_batched_view_function(
+                &context,
+                &vm,
+                &state_view,
+                &view_function,
+                max_gas.min(remaining_gas),
+            ),
+            Err(error) => (Err(error), 0),
+        };
+        remaining_gas = remaining_gas.saturating_sub(gas_used);
+        let (values, error) = match result {
+            Ok(values) => (Some(values), None),
+            Err(error) => (None, Some(error)),
+        };
+        results.push(ViewBatchResult {
+            values,
+            error,
+            gas_used: gas_used.into(),
+        });
+    }
+
+    let total_gas_used = context.node_config.api.max_gas_view_batch - remaining_gas;
+    BasicResponse::try_from_json((results, &ledger_info, BasicResponseStatus::Ok))
+        .map(|r| r.with_gas_used(Some(total_gas_used)))
+}
+
+/// Executes one view function of a batch with at most `max_gas`, and returns its rendered
+/// return values or error, along with the gas it used. `max_gas` is 0 once the gas of the
+/// batch is used up.
+fn execute_batched_view_function(
+    context: &Context,
+    vm: &AptosVM,
+    state_view: &impl StateView,
+    view_function: &ViewFunction,
+    max_gas: u64,
+) -> (Result<Vec<MoveValue>, AptosError>, u64) {
+    if max_gas == 0 {
+        return (
+            Err(AptosError::new_with_error_code(
+                "Gas limit of the view function batch reached",
+                AptosErrorCode::InvalidInput,
+            )),
+            0,
+        );
+    }
+    let output = vm.execute_view(
+        state_view,
+        view_function.module.clone(),
+        view_function.function.clone(),
+        view_function.ty_args.clone(),
+        view_function.args.clone(),
+        max_gas,
+    );
+    context.view_function_stats().increment(
+        FunctionStats::function_to_key(&view_function.module, &view_function.function),
+        output.gas_used,
+    );
+    let result = output
+        .values
+        .map_err(|err| AptosError::new_with_error_code(err, AptosErrorCode::InvalidInput))
+        .and_then(|values| {
+            render_json_values(context, state_view, view_function, values)
+                .map_err(|err| AptosError::new_with_error_code(err, AptosErrorCode::InternalError))
+        });
+    (result, output.gas_used)
+}
+
+/// Rejects the view function if it's not allowed by the view filter of the node.
+fn check_view_function_allowed(
+    context: &Context,
+    view_function: &ViewFunction,
+) -> Result<(), BasicErrorWith404> {
+    if context.node_config.api.view_filter.allows(
         view_function.module.address(),
         view_function.module.name().as_str(),
         view_function.function.as_str(),
     ) {
-        return Err(BasicErrorWith404::forbidden_with_code_no_info(
+        Ok(())
+    } else {
+        Err(BasicErrorWith404::forbidden_with_code_no_info(
             format!(
                 "Function {}::{} is not allowed",
                 view_function.module, view_function.function
             ),
             AptosErrorCode::InvalidInput,
-        ));
+        ))
     }
+}
 
-    let output = AptosVM::execute_view_function(
-        &state_view,
+/// Converts the BCS encoded return values of the view function to JSON values.
+fn render_json_values(
+    context: &Context,
+    state_view: &impl StateView,
+    view_function: &ViewFunction,
+    values: Vec<Vec<u8>>,
+) -> anyhow::Result<Vec<MoveValue>> {
+    let converter = state_view.as_converter(context.db.clone(), context.indexer_reader.clone());
+    let return_types = converter
+        .function_return_types(view_function)
+        .and_then(|tys| {
+            tys.into_iter()
+                .map(TypeTag::try_from)
+                .collect::<anyhow::Result<Vec<_>>>()
+        })?;
+    values
+        .into_iter()
+        .zip(return_types.into_iter())
+        .map(|(v, ty)| converter.try_into_move_value(&ty, &v))
+        .collect()
+}
+
+/// Executes the view function against the given state view and renders its return values.
+/// If `isolated_code_cache` is set, the VM does not share its code cache with other VMs,
+/// which is required when the state view contains code which is not on-chain.
+fn execute_view_function(
+    context: &Context,
+    accept_type: &AcceptType,
+    ledger_info: &LedgerInfo,
+    state_view: &impl StateView,
+    view_function: ViewFunction,
+    max_gas: u64,
+    isolated_code_cache: bool,
+) -> BasicResultWith404<Vec<MoveValue>> {
+    // Reject the request if it's not allowed by the filter.
+    check_view_function_allowed(context, &view_function)?;
+
+    let vm = if isolated_code_cache {
+        AptosVM::new_with_isolated_code_cache(state_view)
+    } else {
+        AptosVM::new(state_view)
+    };
+    let output = vm.execute_view(
+        state_view,
         view_function.module.clone(),
         view_function.function.clone(),
         view_function.ty_args.clone(),
         view_function.args.clone(),
-        context.node_config.api.max_gas_view_function,
+        max_gas,
     );
     let values = output.values.map_err(|err| {
         BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
@@ -155,7 +498,7 @@ fn view_request(
                 BasicErrorWith404::internal_with_code(
                     err,
                     AptosErrorCode::InternalError,
-                    &ledger_info,
+                    ledger_info,
                 )
             })?;
 
@@ -163,43 +506,19 @@ fn view_request(
             let values = values.into_iter().concat();
             let ret = [length, values].concat();
 
-            BasicResponse::try_from_encoded((ret, &ledger_info, BasicResponseStatus::Ok))
+            BasicResponse::try_from_encoded((ret, ledger_info, BasicResponseStatus::Ok))
         },
         AcceptType::Json => {
-            let return_types = state_view
-                .as_converter(context.db.clone(), context.indexer_reader.clone())
-                .function_return_types(&view_function)
-                .and_then(|tys| {
-                    tys.into_iter()
-                        .map(TypeTag::try_from)
-                        .collect::<anyhow::Result<Vec<_>>>()
-                })
-                .map_err(|err| {
-                    BasicErrorWith404::bad_request_with_code(
-                        err,
-                        AptosErrorCode::InternalError,
-                        &ledger_info,
-                    )
-                })?;
-
-            let move_vals = values
-                .into_iter()
-                .zip(return_types.into_iter())
-                .map(|(v, ty)| {
-                    state_view
-                        .as_converter(context.db.clone(), context.indexer_reader.clone())
-                        .try_into_move_value(&ty, &v)
-                })
-                .collect::<anyhow::Result<Vec<_>>>()
+            let move_vals = render_json_values(context, state_view, &view_function, values)
                 .map_err(|err| {
                     BasicErrorWith404::bad_request_with_code(
                         err,
                         AptosErrorCode::InternalError,
-                        &ledger_info,
+                        ledger_info,
                     )
                 })?;
 
-            BasicResponse::try_from_json((move_vals, &ledger_info, BasicResponseStatus::Ok))
+            BasicResponse::try_from_json((move_vals, ledger_info, BasicResponseStatus::Ok))
         },
     };
     context.view_function_stats().increment(
@@ -9,7 +9,7 @@ use crate::{
         UserTransactionRequestInner, WriteModule, WriteResource, WriteTableItem,
     },
     view::{ViewFunction, ViewRequest},
-    Address, Bytecode, DirectWriteSet, EntryFunctionId, EntryFunctionPayload, Event,
+    Address, Bytecode, DecodedError, DirectWriteSet, EntryFunctionId, EntryFunctionPayload, Event,
     HexEncodedBytes, MoveFunction, MoveModuleBytecode, MoveResource, MoveScriptBytecode, MoveType,
     MoveValue, PendingTransaction, ResourceGroup, ScriptPayload, ScriptWriteSet,
     SubmitTransactionRequest, Transaction, TransactionInfo, TransactionOnChainData,
@@ -32,8 +32,8 @@ use aptos_types::{
         StateView,
     },
     transaction::{
-        BlockEndInfo, BlockEpiloguePayload, EntryFunction, ExecutionStatus, Multisig,
-        RawTransaction, Script, SignedTransaction, TransactionAuxiliaryData,
+        BlockEpiloguePayload, EntryFunction, ExecutionStatus, Multisig, RawTransaction, Script,
+        SignedTransaction, TransactionAuxiliaryData,
     },
     vm_status::AbortLocation,
     write_set::WriteOp,
@@ -213,21 +213,9 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
                     info,
                     timestamp: timestamp.into(),
                     block_end_info: match block_epilogue_payload {
-                        BlockEpiloguePayload::V0 {
-                            block_end_info:
-                                BlockEndInfo::V0 {
-                                    block_gas_limit_reached,
-                                    block_output_limit_reached,
-                                    block_effective_block_gas_units,
-                                    block_approx_output_size,
-                                },
-                            ..
-                        } => Some(crate::transaction::BlockEndInfo {
-                            block_gas_limit_reached,
-                            block_output_limit_reached,
-                            block_effective_block_gas_units,
-                            block_approx_output_size,
-                        }),
+                        BlockEpiloguePayload::V0 { block_end_info, .. } => {
+                            Some(block_end_info.into())
+                        },
                     },
                 })
             },
@@ -254,6 +242,7 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
             gas_used: info.gas_used().into(),
             success: info.status().is_success(),
             vm_status: self.explain_vm_status(info.status(), txn_aux_data),
+            decoded_error: self.decode_error(info.status()),
             accumulator_root_hash: accumulator_root_hash.into(),
             // TODO: the resource value is interpreted by the type definition at the version of the converter, not the version of the tx: must be fixed before we allow module updates
             changes: write_set
@@ -774,6 +763,19 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
         Ok(ret)
     }
 
+    /// Decodes the BCS encoded arguments of an entry function, except its signer arguments
+    pub fn try_into_vm_entry_function_arguments(
+        &self,
+        entry_function: &EntryFunction,
+    ) -> Result<Vec<move_core_types::value::MoveValue>> {
+        self.inner.view_function_argument_values(
+            entry_function.module(),
+            entry_function.function(),
+            entry_function.ty_args(),
+            entry_function.args(),
+        )
+    }
+
     pub fn try_into_vm_values(
         &self,
         func: MoveFunction,
@@ -1071,6 +1073,33 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
         }
     }
 
+    /// Resolves the abort code of a failed transaction to the error constant in the error
+    /// map of the aborting module. Committed transactions no longer carry the abort info
+    /// computed by the VM, so it is looked up from the metadata of the module.
+    fn decode_error(&self, status: &ExecutionStatus) -> Option<DecodedError> {
+        let (location, module_id, code, info) = match status {
+            ExecutionStatus::MoveAbort {
+                location: location @ AbortLocation::Module(module_id),
+                code,
+                info,
+            } => (location, module_id, *code, info),
+            _ => return None,
+        };
+        let info = match info {
+            Some(info) => info.clone(),
+            None => {
+                let module = self.inner.view_module(module_id).ok()??;
+                aptos_framework::get_metadata(&module.metadata)?.extract_abort_info(code)?
+            },
+        };
+        Some(DecodedError {
+            module: abort_location_to_str(location),
+            code: code.into(),
+            name: info.reason_name,
+            description: info.description,
+        })
+    }
+
     fn explain_function_index(&self, module_id: &ModuleId, function: &u16) -> Result<String> {
         let code = self.inner.view_existing_module(module_id)?;
         let func = code.function_handle_at(FunctionHandleIndex::new(*function));
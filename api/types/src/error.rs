@@ -110,6 +110,9 @@ pub enum AptosErrorCode {
     BcsNotSupported = 602,
     /// API Disabled
     ApiDisabled = 603,
+    /// The request was rejected by the execution limits of the caller's API tier, because too
+    /// many requests were executing or the execution took too long
+    ExecutionLimitExceeded = 604,
 }
 
 impl AptosErrorCode {
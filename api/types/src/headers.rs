@@ -22,3 +22,5 @@ pub const X_APTOS_CURSOR: &str = "X-Aptos-Cursor";
 pub const X_APTOS_GAS_USED: &str = "X-Aptos-Gas-Used";
 /// Provided by the client to identify what client it is.
 pub const X_APTOS_CLIENT: &str = "x-aptos-client";
+/// Provided by the client to identify its API tier, which sets its execution limits.
+pub const X_APTOS_API_KEY: &str = "x-aptos-api-key";
@@ -18,7 +18,9 @@ mod ledger_info;
 pub mod mime_types;
 mod move_types;
 mod state;
+mod state_override;
 mod table;
+mod trace;
 pub mod transaction;
 mod view;
 mod wrappers;
@@ -42,22 +44,31 @@ pub use move_types::{
 };
 use serde::{Deserialize, Deserializer};
 pub use state::RawStateValueRequest;
+pub use state_override::{
+    BalanceOverride, ModuleOverride, ResourceOverride, SimulateTransactionWithOverridesRequest,
+    StateOverrides, ViewWithOverridesRequest, MAX_STATE_OVERRIDES,
+};
 use std::str::FromStr;
 pub use table::{RawTableItemRequest, TableItemRequest};
+pub use trace::{
+    ExecutionTrace, TraceAbortLocation, TraceEvent, TraceFrame, TraceResourceRead,
+    TraceResourceWrite,
+};
 pub use transaction::{
-    AccountSignature, BlockMetadataTransaction, DeleteModule, DeleteResource, DeleteTableItem,
-    DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest, EntryFunctionPayload, Event,
-    FeePayerSignature, GasEstimation, GasEstimationBcs, GenesisPayload, GenesisTransaction,
-    MultiAgentSignature, MultiEd25519Signature, MultiKeySignature, MultisigPayload,
-    MultisigTransactionPayload, PendingTransaction, PublicKey, ScriptPayload, ScriptWriteSet,
-    Signature, SingleKeySignature, SubmitTransactionRequest, Transaction, TransactionData,
-    TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
-    TransactionSignature, TransactionSigningMessage, TransactionsBatchSingleSubmissionFailure,
-    TransactionsBatchSubmissionResult, UserCreateSigningMessageRequest, UserTransaction,
-    UserTransactionRequest, VersionedEvent, WriteModule, WriteResource, WriteSet, WriteSetChange,
-    WriteSetPayload, WriteTableItem,
+    AccountSignature, BlockMetadataTransaction, DecodedError, DeleteModule, DeleteResource,
+    DeleteTableItem, DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest,
+    EntryFunctionPayload, Event, FeePayerSignature, GasEstimation, GasEstimationBcs,
+    GenesisPayload, GenesisTransaction, MultiAgentSignature, MultiEd25519Signature,
+    MultiKeySignature, MultisigPayload, MultisigTransactionPayload, PendingTransaction, PublicKey,
+    ScriptPayload, ScriptWriteSet, Signature, SimulatedTransactionBundle,
+    SimulatedTransactionBundleOnChainData, SingleKeySignature, SubmitTransactionRequest,
+    Transaction, TransactionData, TransactionId, TransactionInfo, TransactionOnChainData,
+    TransactionPayload, TransactionSignature, TransactionSigningMessage,
+    TransactionsBatchSingleSubmissionFailure, TransactionsBatchSubmissionResult,
+    UserCreateSigningMessageRequest, UserTransaction, UserTransactionRequest, VersionedEvent,
+    WriteModule, WriteResource, WriteSet, WriteSetChange, WriteSetPayload, WriteTableItem,
 };
-pub use view::{ViewFunction, ViewRequest};
+pub use view::{ViewBatchRequest, ViewBatchResult, ViewFunction, ViewRequest};
 pub use wrappers::{EventGuid, IdentifierWrapper, StateKeyWrapper};
 
 pub fn deserialize_from_string<'de, D, T>(deserializer: D) -> Result<T, D::Error>
@@ -0,0 +1,149 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    transaction::SubmitTransactionRequest, Address, HexEncodedBytes, MoveStructTag, VerifyInput,
+    VerifyInputWithRecursion, ViewRequest, U64,
+};
+use anyhow::bail;
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Maximum number of individual overrides allowed in a single request
+pub const MAX_STATE_OVERRIDES: usize = 1000;
+
+/// A set of overrides layered on top of the ledger state
+///
+/// The overrides are only visible to the request they are sent with, and are
+/// never committed to storage. They are applied in order: modules first, then
+/// resources, then balances and finally the timestamp.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct StateOverrides {
+    /// Modules to publish, or to replace if they already exist on-chain
+    pub modules: Option<Vec<ModuleOverride>>,
+    /// Resources to set, or to delete if `data` is null
+    pub resources: Option<Vec<ResourceOverride>>,
+    /// APT balances (`0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`) to set
+    pub balances: Option<Vec<BalanceOverride>>,
+    /// On-chain time in microseconds (`0x1::timestamp::CurrentTimeMicroseconds`)
+    pub timestamp_usecs: Option<U64>,
+}
+
+impl StateOverrides {
+    pub fn modules(&self) -> &[ModuleOverride] {
+        self.modules.as_deref().unwrap_or_default()
+    }
+
+    pub fn resources(&self) -> &[ResourceOverride] {
+        self.resources.as_deref().unwrap_or_default()
+    }
+
+    pub fn balances(&self) -> &[BalanceOverride] {
+        self.balances.as_deref().unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules().is_empty()
+            && self.resources().is_empty()
+            && self.balances().is_empty()
+            && self.timestamp_usecs.is_none()
+    }
+
+    /// Returns true if any on-chain code is overridden
+    pub fn overrides_code(&self) -> bool {
+        !self.modules().is_empty()
+    }
+}
+
+impl VerifyInput for StateOverrides {
+    fn verify(&self) -> anyhow::Result<()> {
+        let num_overrides = self.modules().len() + self.resources().len() + self.balances().len();
+        if num_overrides > MAX_STATE_OVERRIDES {
+            bail!(
+                "Too many state overrides: {} is over the limit of {}",
+                num_overrides,
+                MAX_STATE_OVERRIDES
+            )
+        }
+
+        for module in self.modules() {
+            module.verify()?;
+        }
+        for resource in self.resources() {
+            resource.verify()?;
+        }
+        Ok(())
+    }
+}
+
+/// Publishes or replaces a module
+///
+/// The address and name of the module are taken from the bytecode itself.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ModuleOverride {
+    pub bytecode: HexEncodedBytes,
+}
+
+impl VerifyInput for ModuleOverride {
+    fn verify(&self) -> anyhow::Result<()> {
+        if self.bytecode.is_empty() {
+            bail!("Module override bytecode is empty")
+        }
+        Ok(())
+    }
+}
+
+/// Sets or deletes a resource under an account
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceOverride {
+    pub address: Address,
+    pub resource_type: MoveStructTag,
+    /// JSON representation of the resource, in the same format as returned by
+    /// the resource APIs. If null, the resource is deleted.
+    pub data: Option<Value>,
+}
+
+impl VerifyInput for ResourceOverride {
+    fn verify(&self) -> anyhow::Result<()> {
+        self.resource_type.verify(0)
+    }
+}
+
+/// Sets the APT balance of an account
+///
+/// The account must already have a `CoinStore<AptosCoin>`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct BalanceOverride {
+    pub address: Address,
+    pub amount: U64,
+}
+
+/// A request to simulate a transaction on top of overridden state
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SimulateTransactionWithOverridesRequest {
+    pub transaction: SubmitTransactionRequest,
+    pub state_overrides: StateOverrides,
+}
+
+impl VerifyInput for SimulateTransactionWithOverridesRequest {
+    fn verify(&self) -> anyhow::Result<()> {
+        self.transaction.verify()?;
+        self.state_overrides.verify()
+    }
+}
+
+/// A request to execute a view function on top of overridden state
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ViewWithOverridesRequest {
+    #[serde(flatten)]
+    #[oai(flatten)]
+    pub view_request: ViewRequest,
+    pub state_overrides: StateOverrides,
+}
+
+impl VerifyInput for ViewWithOverridesRequest {
+    fn verify(&self) -> anyhow::Result<()> {
+        self.state_overrides.verify()
+    }
+}
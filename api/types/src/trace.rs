@@ -0,0 +1,88 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Address, U64};
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+
+/// Trace of the execution of a simulated transaction
+///
+/// Values are rendered in a compact format: structs as `{field1, field2}`
+/// (in declaration order), vectors as `[a, b]`, `vector<u8>` as hex strings,
+/// addresses as `@0x1` and references prefixed with `&`. Long values are
+/// truncated.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ExecutionTrace {
+    /// The entry function or script of the transaction, with all the calls it made
+    pub call_tree: TraceFrame,
+    /// Where the transaction aborted, if it did not succeed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub abort_location: Option<TraceAbortLocation>,
+}
+
+/// A function call made during the execution of a transaction
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TraceFrame {
+    /// Fully qualified name of the function, e.g.
+    /// `0x1::coin::transfer<0x1::aptos_coin::AptosCoin>`, or `<script>`
+    pub function: String,
+    pub is_native: bool,
+    /// Arguments the function was called with. Signer arguments of the entry
+    /// function are omitted
+    pub arguments: Vec<String>,
+    /// False if the function did not return, e.g. because it aborted or ran out of gas
+    pub returned: bool,
+    /// Values returned by the function
+    pub return_values: Vec<String>,
+    /// Resources loaded from storage by this call
+    pub resource_reads: Vec<TraceResourceRead>,
+    /// Resources mutably borrowed, moved to or moved from global storage by this call
+    pub resource_writes: Vec<TraceResourceWrite>,
+    /// Events emitted by this call
+    pub events: Vec<TraceEvent>,
+    /// Functions called by this call, in order
+    pub calls: Vec<TraceFrame>,
+}
+
+/// A resource loaded from global storage
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TraceResourceRead {
+    pub address: Address,
+    pub resource_type: String,
+}
+
+/// A resource modified in global storage
+///
+/// The address of the resource is not known at this point of the execution;
+/// it can be found in the changes of the transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TraceResourceWrite {
+    /// One of `borrow_global_mut`, `move_to` or `move_from`
+    pub operation: String,
+    pub resource_type: String,
+}
+
+/// An event emitted during the execution of a transaction
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TraceEvent {
+    pub event_type: String,
+    pub data: String,
+}
+
+/// Where a transaction aborted
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TraceAbortLocation {
+    /// Module that aborted, as reported by the VM. Absent if the transaction
+    /// aborted in a script, or did not abort in Move code (e.g. ran out of gas)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub module: Option<String>,
+    /// Abort code, if the transaction aborted with one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub abort_code: Option<U64>,
+    /// The functions that were executing when the transaction stopped,
+    /// starting from the entry function
+    pub call_stack: Vec<String>,
+}
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    Address, AptosError, EntryFunctionId, EventGuid, HashValue, HexEncodedBytes,
+    Address, AptosError, EntryFunctionId, EventGuid, ExecutionTrace, HashValue, HexEncodedBytes,
     MoveModuleBytecode, MoveModuleId, MoveResource, MoveScriptBytecode, MoveStructTag, MoveType,
     MoveValue, VerifyInput, VerifyInputWithRecursion, U64,
 };
@@ -297,6 +297,7 @@ impl
             request: (txn, payload).into(),
             events,
             timestamp: timestamp.into(),
+            trace: None,
         }))
     }
 }
@@ -370,6 +371,10 @@ pub struct TransactionInfo {
     pub success: bool,
     /// The VM status of the transaction, can tell useful information in a failure
     pub vm_status: String,
+    /// The error constant the transaction aborted with, if the aborting module declares it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub decoded_error: Option<DecodedError>,
     pub accumulator_root_hash: HashValue,
     /// Final state of resources changed by the transaction
     pub changes: Vec<WriteSetChange>,
@@ -383,6 +388,19 @@ pub struct TransactionInfo {
     pub epoch: Option<U64>,
 }
 
+/// Abort code of a failed transaction resolved to the error constant declared in the
+/// error map of the aborting module
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct DecodedError {
+    /// Module which aborted, e.g. `0x1::coin`
+    pub module: String,
+    pub code: U64,
+    /// Name of the error constant, e.g. `EINSUFFICIENT_BALANCE`
+    pub name: String,
+    /// Doc comment of the error constant
+    pub description: String,
+}
+
 /// A transaction waiting in mempool
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
 pub struct PendingTransaction {
@@ -413,6 +431,10 @@ pub struct UserTransaction {
     /// Events generated by the transaction
     pub events: Vec<Event>,
     pub timestamp: U64,
+    /// Trace of the execution, only returned by the simulation APIs when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub trace: Option<ExecutionTrace>,
 }
 
 /// A state checkpoint transaction
@@ -430,6 +452,68 @@ pub struct BlockEndInfo {
     pub block_output_limit_reached: bool,
     pub block_effective_block_gas_units: u64,
     pub block_approx_output_size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub block_limit_breakdown: Option<BlockLimitBreakdown>,
+}
+
+impl From<aptos_types::transaction::BlockEndInfo> for BlockEndInfo {
+    fn from(block_end_info: aptos_types::transaction::BlockEndInfo) -> Self {
+        use aptos_types::transaction::BlockEndInfo::*;
+
+        match block_end_info {
+            V0 {
+                block_gas_limit_reached,
+                block_output_limit_reached,
+                block_effective_block_gas_units,
+                block_approx_output_size,
+            } => Self {
+                block_gas_limit_reached,
+                block_output_limit_reached,
+                block_effective_block_gas_units,
+                block_approx_output_size,
+                block_limit_breakdown: None,
+            },
+            V1 {
+                block_gas_limit_reached,
+                block_output_limit_reached,
+                block_effective_block_gas_units,
+                block_approx_output_size,
+                block_execution_gas_units,
+                block_io_gas_units,
+                block_conflict_penalty_gas_units,
+                max_conflict_multiplier,
+                module_publishing_conflict,
+            } => Self {
+                block_gas_limit_reached,
+                block_output_limit_reached,
+                block_effective_block_gas_units,
+                block_approx_output_size,
+                block_limit_breakdown: Some(BlockLimitBreakdown {
+                    block_execution_gas_units,
+                    block_io_gas_units,
+                    block_conflict_penalty_gas_units,
+                    max_conflict_multiplier,
+                    module_publishing_conflict,
+                }),
+            },
+        }
+    }
+}
+
+/// Breakdown of the effective block gas, only present once enabled on-chain
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct BlockLimitBreakdown {
+    /// Total execution gas units of the committed transactions, before multipliers
+    pub block_execution_gas_units: u64,
+    /// Total io gas units of the committed transactions, before multipliers
+    pub block_io_gas_units: u64,
+    /// Effective gas units added by the conflict multipliers
+    pub block_conflict_penalty_gas_units: u64,
+    /// Largest conflict multiplier applied to a committed transaction
+    pub max_conflict_multiplier: u64,
+    /// Whether a module was both published and used in the block
+    pub module_publishing_conflict: bool,
 }
 
 /// A block epilogue transaction
@@ -477,6 +561,28 @@ pub struct TransactionsBatchSingleSubmissionFailure {
     pub transaction_index: usize,
 }
 
+/// The result of simulating a bundle of transactions
+///
+/// Each transaction is executed on top of the changes of the transactions before it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct SimulatedTransactionBundle {
+    /// The simulated transactions, in execution order, with their events and gas used
+    pub transactions: Vec<UserTransaction>,
+    /// The aggregated changes of the whole bundle
+    ///
+    /// If a state item is changed by multiple transactions, only the last change is included
+    pub changes: Vec<WriteSetChange>,
+}
+
+/// The BCS representation of a simulated bundle of transactions
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SimulatedTransactionBundleOnChainData {
+    /// The simulated transactions, in execution order
+    pub transactions: Vec<TransactionOnChainData>,
+    /// The aggregated changes of the whole bundle
+    pub changes: aptos_types::write_set::WriteSet,
+}
+
 // TODO: Rename this to remove the Inner when we cut over.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
 pub struct UserTransactionRequestInner {
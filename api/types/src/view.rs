@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{EntryFunctionId, MoveType};
+use crate::{AptosError, EntryFunctionId, MoveType, MoveValue, U64};
 use aptos_types::serde_helper::vec_bytes;
 use move_core_types::{
     identifier::Identifier,
@@ -20,6 +20,27 @@ pub struct ViewRequest {
     pub arguments: Vec<serde_json::Value>,
 }
 
+/// A batch of view requests for the Move View Function API
+///
+/// All the view functions of the batch are executed against the same ledger version.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ViewBatchRequest {
+    pub requests: Vec<ViewRequest>,
+}
+
+/// The result of one view function of a batch, in the same order as the requests
+///
+/// Exactly one of `values` and `error` is set.
+#[derive(Clone, Debug, Serialize, Object)]
+pub struct ViewBatchResult {
+    /// Return values of the function
+    pub values: Option<Vec<MoveValue>>,
+    /// Why the function could not be executed
+    pub error: Option<AptosError>,
+    /// Gas used by the function
+    pub gas_used: U64,
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ViewFunction {
     pub module: ModuleId,
@@ -1,16 +1,18 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{bail, format_err, Result};
+use crate::replay_diff::{diff_transaction_outputs, ExecutionDivergence, ReplayedTransaction};
+use anyhow::{bail, ensure, format_err, Result};
 use aptos_block_executor::txn_commit_hook::NoOpTransactionCommitHook;
-use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
+use aptos_gas_profiling::{render_move_value, GasProfiler, TransactionGasLog};
+use aptos_resource_viewer::AptosValueAnnotator;
 use aptos_rest_client::Client;
 use aptos_types::{
     account_address::AccountAddress,
     block_executor::config::{
         BlockExecutorConfig, BlockExecutorConfigFromOnchain, BlockExecutorLocalConfig,
     },
-    state_store::TStateView,
+    state_store::{StateView, TStateView},
     transaction::{
         signature_verified_transaction::SignatureVerifiedTransaction, BlockOutput,
         SignedTransaction, Transaction, TransactionInfo, TransactionOutput, TransactionPayload,
@@ -24,20 +26,46 @@ use aptos_validator_interface::{
 use aptos_vm::{
     block_executor::{AptosTransactionOutput, BlockAptosVM},
     data_cache::AsMoveResolver,
+    execution_trace::{record_execution_trace, ExecutionTraceBundle},
     AptosVM,
 };
 use aptos_vm_logging::log_schema::AdapterLogSchema;
 use aptos_vm_types::output::VMOutput;
 use itertools::Itertools;
-use std::{path::Path, sync::Arc, time::Instant};
+use move_core_types::value::MoveValue;
+use std::{
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+/// Max number of versions searched for the last state checkpoint before a recorded transaction
+const MAX_VERSIONS_TO_STATE_CHECKPOINT: u64 = 100_000;
+/// Number of transaction infos fetched at once when searching for the last state checkpoint
+const STATE_CHECKPOINT_SEARCH_BATCH_SIZE: u64 = 1_000;
+/// Number of transactions fetched at once when replaying a range of transactions
+const REPLAY_BATCH_SIZE: u64 = 1_000;
 
 pub struct AptosDebugger {
     debugger: Arc<dyn AptosValidatorInterface + Send>,
+    conflict_report_dir: Option<PathBuf>,
 }
 
 impl AptosDebugger {
     pub fn new(debugger: Arc<dyn AptosValidatorInterface + Send>) -> Self {
-        Self { debugger }
+        Self {
+            debugger,
+            conflict_report_dir: None,
+        }
+    }
+
+    /// Writes a conflict report for every block executed in parallel, into a sub-directory
+    /// of `dir` named after the version of the first transaction of the block.
+    pub fn with_conflict_report_dir(mut self, dir: PathBuf) -> Self {
+        self.conflict_report_dir = Some(dir);
+        self
     }
 
     pub fn rest_client(rest_client: Client) -> Result<Self> {
@@ -60,6 +88,10 @@ impl AptosDebugger {
         let sig_verified_txns: Vec<SignatureVerifiedTransaction> =
             txns.into_iter().map(|x| x.into()).collect::<Vec<_>>();
         let state_view = DebuggerStateView::new(self.debugger.clone(), version);
+        let conflict_report_dir = self
+            .conflict_report_dir
+            .as_ref()
+            .map(|dir| dir.join(version.to_string()));
 
         print_transaction_stats(&sig_verified_txns, version);
 
@@ -68,9 +100,14 @@ impl AptosDebugger {
         for concurrency_level in concurrency_levels {
             for i in 0..repeat_execution_times {
                 let start_time = Instant::now();
-                let cur_result =
-                    execute_block_no_limit(&sig_verified_txns, &state_view, *concurrency_level)
-                        .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+                let cur_result = execute_block_no_limit(
+                    &sig_verified_txns,
+                    &state_view,
+                    *concurrency_level,
+                    true,
+                    conflict_report_dir.clone(),
+                )
+                .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
 
                 println!(
                     "[{} txns from {}] Finished execution round {}/{} with concurrency_level={} in {}ms",
@@ -106,6 +143,25 @@ impl AptosDebugger {
         &self,
         version: Version,
         txn: SignedTransaction,
+    ) -> Result<(VMStatus, VMOutput, TransactionGasLog)> {
+        self.execute_transaction_at_version_with_profiler(version, txn, false)
+    }
+
+    /// Same as [`Self::execute_transaction_at_version_with_gas_profiler`], but the arguments of
+    /// every call are recorded as well. See [`GasProfiler::with_value_tracing`].
+    pub fn execute_transaction_at_version_with_value_tracing(
+        &self,
+        version: Version,
+        txn: SignedTransaction,
+    ) -> Result<(VMStatus, VMOutput, TransactionGasLog)> {
+        self.execute_transaction_at_version_with_profiler(version, txn, true)
+    }
+
+    fn execute_transaction_at_version_with_profiler(
+        &self,
+        version: Version,
+        txn: SignedTransaction,
+        trace_values: bool,
     ) -> Result<(VMStatus, VMOutput, TransactionGasLog)> {
         let state_view = DebuggerStateView::new(self.debugger.clone(), version);
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
@@ -118,6 +174,12 @@ impl AptosDebugger {
             bail!("Module bundle payload has been removed")
         }
 
+        let args = if trace_values {
+            render_payload_arguments(&state_view, txn.payload())
+        } else {
+            vec![]
+        };
+
         let vm = AptosVM::new(&state_view);
         let resolver = state_view.as_move_resolver();
 
@@ -141,7 +203,11 @@ impl AptosDebugger {
                         unreachable!("Module bundle payload has already been checked because before this function is called")
                     },
                 };
-                gas_profiler
+                if trace_values {
+                    gas_profiler.with_value_tracing(args)
+                } else {
+                    gas_profiler
+                }
             },
         )?;
 
@@ -185,6 +251,286 @@ impl AptosDebugger {
         }
     }
 
+    /// Re-executes the committed transactions in the range block by block, passing their
+    /// outputs along with the recorded ones to `on_replayed`, in order of version. The range
+    /// is fetched and replayed in batches, so it doesn't have to fit into memory at once.
+    /// Returns the number of replayed transactions.
+    pub async fn replay_past_transactions(
+        &self,
+        begin: Version,
+        limit: u64,
+        concurrency_level: usize,
+        mut on_replayed: impl FnMut(ReplayedTransaction) -> Result<()> + Send,
+    ) -> Result<u64> {
+        let end = begin
+            .checked_add(limit)
+            .ok_or_else(|| format_err!("Version range overflows: {} + {}", begin, limit))?;
+
+        // Transactions which were fetched, but not replayed yet (as their block may continue
+        // in the next batch), starting at `pending_version`
+        let mut pending_txns = vec![];
+        let mut pending_txn_infos = vec![];
+        let mut pending_write_sets = vec![];
+        let mut pending_version = begin;
+        let mut next_version = begin;
+
+        while next_version < end {
+            let batch_size = REPLAY_BATCH_SIZE.min(end - next_version);
+            let (txns, txn_infos) = self
+                .debugger
+                .get_committed_transactions(next_version, batch_size)
+                .await?;
+            let write_sets = self
+                .debugger
+                .get_committed_write_sets(next_version, batch_size)
+                .await?;
+            ensure!(txns.len() as u64 == batch_size && txn_infos.len() as u64 == batch_size);
+            ensure!(write_sets.len() as u64 == batch_size);
+            next_version += batch_size;
+
+            pending_txns.extend(txns);
+            pending_txn_infos.extend(txn_infos);
+            pending_write_sets.extend(write_sets);
+
+            // Only replay the complete blocks, unless the end of the range was reached
+            let num_ready = if next_version == end {
+                pending_txns.len()
+            } else {
+                pending_txns
+                    .iter()
+                    .rposition(Transaction::is_block_start)
+                    .unwrap_or(0)
+            };
+            if num_ready == 0 {
+                continue;
+            }
+
+            let txns = pending_txns.drain(..num_ready).collect::<Vec<_>>();
+            let txn_infos = pending_txn_infos.drain(..num_ready).collect::<Vec<_>>();
+            let write_sets = pending_write_sets.drain(..num_ready).collect::<Vec<_>>();
+            let outputs = self
+                .execute_transactions_by_block(pending_version, txns.clone(), 1, &[
+                    concurrency_level,
+                ])
+                .await?;
+            ensure!(txns.len() == outputs.len());
+
+            for (idx, (((txn, txn_info), expected_write_set), output)) in txns
+                .into_iter()
+                .zip(txn_infos)
+                .zip(write_sets)
+                .zip(outputs)
+                .enumerate()
+            {
+                on_replayed(ReplayedTransaction {
+                    version: pending_version + idx as Version,
+                    txn,
+                    txn_info,
+                    expected_write_set,
+                    output,
+                })?;
+            }
+            pending_version += num_ready as Version;
+        }
+
+        Ok(pending_version - begin)
+    }
+
+    /// Re-executes the committed transactions in the range block by block, sequentially and
+    /// in parallel with each of the concurrency levels, and compares the outputs transaction
+    /// by transaction. Returns the first transaction whose parallel output differs from the
+    /// sequential one, if any.
+    pub async fn compare_parallel_execution(
+        &self,
+        begin: Version,
+        limit: u64,
+        concurrency_levels: &[usize],
+    ) -> Result<Option<ExecutionDivergence>> {
+        ensure!(
+            !concurrency_levels.is_empty(),
+            "At least one concurrency level is required"
+        );
+        ensure!(
+            concurrency_levels.iter().all(|level| *level > 1),
+            "Concurrency levels must be greater than 1 to execute in parallel"
+        );
+
+        let (txns, _) = self
+            .debugger
+            .get_committed_transactions(begin, limit)
+            .await?;
+
+        let mut block_version = begin;
+        for block in split_into_blocks(txns) {
+            let sig_verified_txns: Vec<SignatureVerifiedTransaction> =
+                block.into_iter().map(|x| x.into()).collect::<Vec<_>>();
+            let state_view = DebuggerStateView::new(self.debugger.clone(), block_version);
+
+            let sequential_outputs =
+                execute_block_no_limit(&sig_verified_txns, &state_view, 1, true, None)
+                    .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+            for concurrency_level in concurrency_levels {
+                // Fallback to sequential execution is disabled, as it would hide any failure
+                // of the parallel execution behind the (matching) sequential outputs.
+                let parallel_outputs = catch_parallel_execution_failure(|| {
+                    execute_block_no_limit(
+                        &sig_verified_txns,
+                        &state_view,
+                        *concurrency_level,
+                        false,
+                        self.conflict_report_dir
+                            .as_ref()
+                            .map(|dir| dir.join(block_version.to_string())),
+                    )
+                })
+                .map_err(|err| {
+                    err.context(format!(
+                        "[{} txns from {}] Parallel execution failed with concurrency_level={}",
+                        sig_verified_txns.len(),
+                        block_version,
+                        concurrency_level
+                    ))
+                })?
+                .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+                ensure!(sequential_outputs.len() == parallel_outputs.len());
+
+                for (idx, (sequential_output, parallel_output)) in
+                    sequential_outputs.iter().zip(&parallel_outputs).enumerate()
+                {
+                    let divergences = diff_transaction_outputs(sequential_output, parallel_output);
+                    if !divergences.is_empty() {
+                        return Ok(Some(ExecutionDivergence {
+                            version: block_version + idx as Version,
+                            block_version,
+                            concurrency_level: *concurrency_level,
+                            divergences,
+                        }));
+                    }
+                }
+            }
+
+            println!(
+                "[{} txns from {}] Parallel execution matches sequential execution",
+                sig_verified_txns.len(),
+                block_version
+            );
+            block_version += sig_verified_txns.len() as Version;
+        }
+
+        Ok(None)
+    }
+
+    /// Re-executes the committed transaction at `version` and records the state values it
+    /// read into a bundle, along with what it committed with. The reads are proven against
+    /// the last state checkpoint at or before `version - 1` (and the write sets since then).
+    pub async fn record_execution_trace(&self, version: Version) -> Result<ExecutionTraceBundle> {
+        ensure!(version > 0, "Cannot record the execution of genesis");
+        let (txn, txn_info) = self.get_committed_transaction_at_version(version).await?;
+        let expected_write_set = self
+            .debugger
+            .get_committed_write_sets(version, 1)
+            .await?
+            .pop()
+            .expect("there must be exactly 1 write set in the vec");
+
+        let state_view = DebuggerStateView::new(self.debugger.clone(), version);
+        let (_, mut bundle) = record_execution_trace(&state_view, version, txn)
+            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+        bundle.expected_txn_info = Some(txn_info);
+        bundle.expected_write_set = Some(expected_write_set);
+
+        let (checkpoint_version, checkpoint_txn_info) = match self
+            .get_last_state_checkpoint(version - 1)
+            .await?
+        {
+            Some(checkpoint) => checkpoint,
+            None => {
+                println!(
+                        "No state checkpoint found within {} versions before version {}, the reads are not proven",
+                        MAX_VERSIONS_TO_STATE_CHECKPOINT, version
+                    );
+                return Ok(bundle);
+            },
+        };
+        let (txn_infos_since_checkpoint, write_sets_since_checkpoint) =
+            if checkpoint_version < version - 1 {
+                let num_versions = version - 1 - checkpoint_version;
+                let (_, txn_infos) = self
+                    .debugger
+                    .get_committed_transactions(checkpoint_version + 1, num_versions)
+                    .await?;
+                let write_sets = self
+                    .debugger
+                    .get_committed_write_sets(checkpoint_version + 1, num_versions)
+                    .await?;
+                (txn_infos, write_sets)
+            } else {
+                (vec![], vec![])
+            };
+
+        let mut proofs = HashMap::new();
+        for read in &bundle.reads {
+            let is_written = write_sets_since_checkpoint
+                .iter()
+                .any(|write_set| write_set.get(&read.state_key).is_some());
+            if !is_written {
+                let (_, proof) = self
+                    .debugger
+                    .get_state_value_with_proof_by_version(&read.state_key, checkpoint_version)
+                    .await?;
+                proofs.insert(read.state_key.clone(), proof);
+            }
+        }
+        bundle.attach_proofs(
+            checkpoint_version,
+            checkpoint_txn_info,
+            txn_infos_since_checkpoint,
+            write_sets_since_checkpoint,
+            |state_key| {
+                proofs
+                    .remove(state_key)
+                    .ok_or_else(|| format_err!("Missing proof of {:?}", state_key))
+            },
+        )?;
+
+        Ok(bundle)
+    }
+
+    /// Returns the version and transaction info of the last state checkpoint at or before
+    /// `version`, if there is one within [`MAX_VERSIONS_TO_STATE_CHECKPOINT`] versions.
+    async fn get_last_state_checkpoint(
+        &self,
+        version: Version,
+    ) -> Result<Option<(Version, TransactionInfo)>> {
+        let min_version = version.saturating_sub(MAX_VERSIONS_TO_STATE_CHECKPOINT);
+        let mut end = version + 1; // Exclusive
+        while end > min_version {
+            let start = end
+                .saturating_sub(STATE_CHECKPOINT_SEARCH_BATCH_SIZE)
+                .max(min_version);
+            let (_, txn_infos) = self
+                .debugger
+                .get_committed_transactions(start, end - start)
+                .await?;
+            if let Some((idx, txn_info)) = txn_infos
+                .into_iter()
+                .enumerate()
+                .rev()
+                .find(|(_, txn_info)| txn_info.state_checkpoint_hash().is_some())
+            {
+                return Ok(Some((start + idx as Version, txn_info)));
+            }
+            end = start;
+        }
+        Ok(None)
+    }
+
+    /// Re-executes the transaction of the bundle against the reads it recorded only, after
+    /// checking their proofs.
+    pub fn execute_trace_bundle(bundle: &ExecutionTraceBundle) -> Result<TransactionOutput> {
+        bundle.replay()
+    }
+
     fn print_mismatches(
         txn_outputs: &[TransactionOutput],
         expected_txn_infos: &[TransactionInfo],
@@ -292,29 +638,15 @@ impl AptosDebugger {
         concurrency_levels: &[usize],
     ) -> Result<Vec<TransactionOutput>> {
         let mut ret = vec![];
-        let mut cur = vec![];
         let mut cur_version = begin;
-        for txn in txns {
-            if txn.is_block_start() && !cur.is_empty() {
-                let to_execute = std::mem::take(&mut cur);
-                let results = self.execute_transactions_at_version(
-                    cur_version,
-                    to_execute,
-                    repeat_execution_times,
-                    concurrency_levels,
-                )?;
-                cur_version += results.len() as u64;
-                ret.extend(results);
-            }
-            cur.push(txn);
-        }
-        if !cur.is_empty() {
+        for block in split_into_blocks(txns) {
             let results = self.execute_transactions_at_version(
                 cur_version,
-                cur,
+                block,
                 repeat_execution_times,
                 concurrency_levels,
             )?;
+            cur_version += results.len() as u64;
             ret.extend(results);
         }
 
@@ -411,6 +743,23 @@ fn print_transaction_stats(sig_verified_txns: &[SignatureVerifiedTransaction], v
     );
 }
 
+/// Splits the transactions into the blocks they were committed in, using the block start
+/// transactions (e.g. `BlockMetadata`) as boundaries.
+fn split_into_blocks(txns: Vec<Transaction>) -> Vec<Vec<Transaction>> {
+    let mut blocks = vec![];
+    let mut cur = vec![];
+    for txn in txns {
+        if txn.is_block_start() && !cur.is_empty() {
+            blocks.push(std::mem::take(&mut cur));
+        }
+        cur.push(txn);
+    }
+    if !cur.is_empty() {
+        blocks.push(cur);
+    }
+    blocks
+}
+
 fn is_reconfiguration(vm_output: &TransactionOutput) -> bool {
     let new_epoch_event_key = aptos_types::on_chain_config::new_epoch_event_key();
     vm_output
@@ -419,10 +768,25 @@ fn is_reconfiguration(vm_output: &TransactionOutput) -> bool {
         .any(|event| event.event_key() == Some(&new_epoch_event_key))
 }
 
-fn execute_block_no_limit(
+/// Runs the given block execution, converting a failure of the parallel execution (which
+/// panics when the fallback to sequential execution is not allowed) into an error.
+fn catch_parallel_execution_failure<T>(execute: impl FnOnce() -> T) -> Result<T> {
+    panic::catch_unwind(AssertUnwindSafe(execute)).map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Unknown panic".to_string());
+        format_err!("{}", message)
+    })
+}
+
+fn execute_block_no_limit<S: StateView + Sync>(
     sig_verified_txns: &[SignatureVerifiedTransaction],
-    state_view: &DebuggerStateView,
+    state_view: &S,
     concurrency_level: usize,
+    allow_fallback: bool,
+    conflict_report_dir: Option<PathBuf>,
 ) -> Result<Vec<TransactionOutput>, VMStatus> {
     BlockAptosVM::execute_block::<_, NoOpTransactionCommitHook<AptosTransactionOutput, VMStatus>>(
         sig_verified_txns,
@@ -430,8 +794,9 @@ fn execute_block_no_limit(
         BlockExecutorConfig {
             local: BlockExecutorLocalConfig {
                 concurrency_level,
-                allow_fallback: true,
+                allow_fallback,
                 discard_failed_blocks: false,
+                conflict_report_dir,
             },
             onchain: BlockExecutorConfigFromOnchain::new_no_block_limit(),
         },
@@ -439,3 +804,64 @@ fn execute_block_no_limit(
     )
     .map(BlockOutput::into_transaction_outputs_forced)
 }
+
+/// Renders the arguments of the entry function or script of a transaction, which are not
+/// observed by the gas profiler. Arguments which cannot be decoded are rendered as bytes.
+fn render_payload_arguments(
+    state_view: &impl StateView,
+    payload: &TransactionPayload,
+) -> Vec<String> {
+    let values = match payload {
+        TransactionPayload::Script(script) => script
+            .args()
+            .iter()
+            .map(|arg| MoveValue::from(arg.clone()))
+            .collect(),
+        TransactionPayload::EntryFunction(entry_func) => AptosValueAnnotator::new(state_view)
+            .view_function_argument_values(
+                entry_func.module(),
+                entry_func.function(),
+                entry_func.ty_args(),
+                entry_func.args(),
+            )
+            .unwrap_or_else(|_| {
+                entry_func
+                    .args()
+                    .iter()
+                    .map(|arg| MoveValue::vector_u8(arg.clone()))
+                    .collect()
+            }),
+        TransactionPayload::Multisig(..) | TransactionPayload::ModuleBundle(..) => vec![],
+    };
+    values.iter().map(render_move_value).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catch_parallel_execution_failure() {
+        // Verify the result of a successful execution is returned
+        let result = catch_parallel_execution_failure(|| 10).unwrap();
+        assert_eq!(result, 10);
+
+        // Verify a failed parallel execution is surfaced as an error
+        let error = catch_parallel_execution_failure(|| -> u64 {
+            panic!("Parallel execution failed and fallback is not allowed")
+        })
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Parallel execution failed and fallback is not allowed"
+        );
+
+        // Verify formatted panic messages are also surfaced
+        let concurrency_level = 4;
+        let error = catch_parallel_execution_failure(|| -> u64 {
+            panic!("Failed with concurrency_level={}", concurrency_level)
+        })
+        .unwrap_err();
+        assert_eq!(error.to_string(), "Failed with concurrency_level=4");
+    }
+}
@@ -1,7 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{execute_past_transactions, execute_pending_block};
+use crate::{
+    compare_parallel_execution, execute_past_transactions, execute_pending_block, execute_trace,
+    record_execution_trace,
+};
 use anyhow::Result;
 use clap::Parser;
 use std::path::PathBuf;
@@ -29,12 +32,20 @@ pub struct Opts {
 
     #[clap(long, num_args = 0..)]
     pub(crate) concurrency_level: Vec<usize>,
+
+    /// Write a report of the conflicts between transactions of every block executed in
+    /// parallel to this directory.
+    #[clap(long)]
+    pub(crate) conflict_report_dir: Option<PathBuf>,
 }
 
 #[derive(Parser)]
 pub enum Command {
     ExecutePastTransactions(execute_past_transactions::Command),
     ExecutePendingBlock(execute_pending_block::Command),
+    CompareParallelExecution(compare_parallel_execution::Command),
+    RecordExecutionTrace(record_execution_trace::Command),
+    ExecuteTrace(execute_trace::Command),
 }
 
 impl Command {
@@ -42,6 +53,9 @@ impl Command {
         match self {
             Command::ExecutePastTransactions(cmd) => cmd.run().await,
             Command::ExecutePendingBlock(cmd) => cmd.run().await,
+            Command::CompareParallelExecution(cmd) => cmd.run().await,
+            Command::RecordExecutionTrace(cmd) => cmd.run().await,
+            Command::ExecuteTrace(cmd) => cmd.run().await,
         }
     }
 }
@@ -0,0 +1,65 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{aptos_debugger::AptosDebugger, common::Opts};
+use anyhow::{bail, Result};
+use aptos_rest_client::Client;
+use clap::Parser;
+use url::Url;
+
+/// Re-executes past blocks sequentially and with the parallel block executor at each
+/// `--concurrency-level`, and reports the first transaction whose outputs differ.
+#[derive(Parser)]
+pub struct Command {
+    #[clap(flatten)]
+    opts: Opts,
+
+    #[clap(long)]
+    begin_version: u64,
+
+    #[clap(long)]
+    limit: u64,
+}
+
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        let mut debugger = if let Some(rest_endpoint) = self.opts.target.rest_endpoint {
+            AptosDebugger::rest_client(Client::new(Url::parse(&rest_endpoint)?))?
+        } else if let Some(db_path) = self.opts.target.db_path {
+            AptosDebugger::db(db_path)?
+        } else {
+            unreachable!("Must provide one target.");
+        };
+        if let Some(conflict_report_dir) = self.opts.conflict_report_dir {
+            debugger = debugger.with_conflict_report_dir(conflict_report_dir);
+        }
+
+        let divergence = debugger
+            .compare_parallel_execution(
+                self.begin_version,
+                self.limit,
+                &self.opts.concurrency_level,
+            )
+            .await?;
+
+        if let Some(divergence) = divergence {
+            println!(
+                "Parallel execution with concurrency_level={} diverged from sequential execution at version {} (block starting at version {}):",
+                divergence.concurrency_level, divergence.version, divergence.block_version
+            );
+            for diff in &divergence.divergences {
+                println!("  {}", diff);
+            }
+            bail!(
+                "Parallel execution diverged at version {}",
+                divergence.version
+            );
+        }
+
+        println!(
+            "Parallel execution matches sequential execution for {} transactions from version {}",
+            self.limit, self.begin_version
+        );
+        Ok(())
+    }
+}
@@ -41,13 +41,16 @@ pub struct Command {
 
 impl Command {
     pub async fn run(self) -> Result<()> {
-        let debugger = if let Some(rest_endpoint) = self.opts.target.rest_endpoint {
+        let mut debugger = if let Some(rest_endpoint) = self.opts.target.rest_endpoint {
             AptosDebugger::rest_client(Client::new(Url::parse(&rest_endpoint)?))?
         } else if let Some(db_path) = self.opts.target.db_path {
             AptosDebugger::db(db_path)?
         } else {
             unreachable!("Must provide one target.");
         };
+        if let Some(conflict_report_dir) = self.opts.conflict_report_dir {
+            debugger = debugger.with_conflict_report_dir(conflict_report_dir);
+        }
 
         let user_txns = if let Some(block_rest_endpoint) = self.block_rest_endpoint {
             info!(
@@ -0,0 +1,58 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{aptos_debugger::AptosDebugger, replay_diff::ReplayedTransaction};
+use anyhow::{bail, Result};
+use aptos_vm::execution_trace::ExecutionTraceBundle;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Re-executes the transaction of a bundle written by `record-execution-trace`, from the
+/// recorded reads alone, and compares the output against the committed one.
+#[derive(Parser)]
+pub struct Command {
+    /// File the bundle was written to
+    #[clap(long)]
+    bundle: PathBuf,
+}
+
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        let bundle = ExecutionTraceBundle::load(&self.bundle)?;
+        if !bundle.is_proven() {
+            println!("The reads of the bundle are not proven");
+        }
+        let output = AptosDebugger::execute_trace_bundle(&bundle)?;
+        println!(
+            "Executed the transaction at version {}: {:?}, gas used {}",
+            bundle.version,
+            output.status(),
+            output.gas_used()
+        );
+
+        let (txn_info, expected_write_set) =
+            match (bundle.expected_txn_info, bundle.expected_write_set) {
+                (Some(txn_info), Some(expected_write_set)) => (txn_info, expected_write_set),
+                _ => return Ok(()),
+            };
+        let divergences = ReplayedTransaction {
+            version: bundle.version,
+            txn: bundle.txn,
+            txn_info,
+            expected_write_set,
+            output,
+        }
+        .divergences();
+        if !divergences.is_empty() {
+            for divergence in &divergences {
+                println!("  {}", divergence);
+            }
+            bail!(
+                "Execution diverged from the committed transaction at version {}",
+                bundle.version
+            );
+        }
+        println!("Output matches the committed transaction");
+        Ok(())
+    }
+}
@@ -4,5 +4,9 @@
 pub mod aptos_debugger;
 pub mod bcs_txn_decoder;
 pub mod common;
+pub mod compare_parallel_execution;
 pub mod execute_past_transactions;
 pub mod execute_pending_block;
+pub mod execute_trace;
+pub mod record_execution_trace;
+pub mod replay_diff;
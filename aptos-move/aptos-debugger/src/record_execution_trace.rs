@@ -0,0 +1,52 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{aptos_debugger::AptosDebugger, common::Target};
+use anyhow::Result;
+use aptos_rest_client::Client;
+use aptos_types::transaction::Version;
+use clap::Parser;
+use std::path::PathBuf;
+use url::Url;
+
+/// Re-executes a committed transaction and records the state values it read into a bundle,
+/// which `execute-trace` can re-execute without access to the DB.
+#[derive(Parser)]
+pub struct Command {
+    #[clap(flatten)]
+    target: Target,
+
+    #[clap(long)]
+    version: Version,
+
+    /// File to write the bundle to
+    #[clap(long)]
+    output: PathBuf,
+}
+
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        let debugger = if let Some(rest_endpoint) = self.target.rest_endpoint {
+            AptosDebugger::rest_client(Client::new(Url::parse(&rest_endpoint)?))?
+        } else if let Some(db_path) = self.target.db_path {
+            AptosDebugger::db(db_path)?
+        } else {
+            unreachable!("Must provide one target.");
+        };
+
+        let bundle = debugger.record_execution_trace(self.version).await?;
+        bundle.save(&self.output)?;
+        println!(
+            "Recorded {} reads of the transaction at version {} ({}) to {}",
+            bundle.reads.len(),
+            self.version,
+            if bundle.is_proven() {
+                "proven"
+            } else {
+                "unproven"
+            },
+            self.output.display()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_types::{
+    proof::accumulator::InMemoryEventAccumulator,
+    state_store::state_key::StateKey,
+    transaction::{Transaction, TransactionInfo, TransactionOutput, TransactionStatus, Version},
+    write_set::{WriteOp, WriteSet},
+};
+use std::fmt::{Display, Formatter};
+
+/// A committed transaction re-executed by the debugger, along with what was recorded for it
+pub struct ReplayedTransaction {
+    pub version: Version,
+    pub txn: Transaction,
+    pub txn_info: TransactionInfo,
+    pub expected_write_set: WriteSet,
+    pub output: TransactionOutput,
+}
+
+impl ReplayedTransaction {
+    /// Compares the output of the replay against the recorded one. Returns an empty list if
+    /// the transaction was replayed faithfully.
+    pub fn divergences(&self) -> Vec<ReplayDivergence> {
+        let mut divergences = vec![];
+
+        let expected_status: TransactionStatus = self.txn_info.status().clone().into();
+        if self.output.status() != &expected_status {
+            divergences.push(ReplayDivergence::Status {
+                expected: expected_status,
+                actual: self.output.status().clone(),
+            });
+        }
+
+        if self.output.gas_used() != self.txn_info.gas_used() {
+            divergences.push(ReplayDivergence::GasUsed {
+                expected: self.txn_info.gas_used(),
+                actual: self.output.gas_used(),
+            });
+        }
+
+        divergences.extend(
+            diff_write_sets(&self.expected_write_set, self.output.write_set())
+                .into_iter()
+                .map(|(state_key, expected, actual)| ReplayDivergence::Write {
+                    state_key,
+                    expected,
+                    actual,
+                }),
+        );
+
+        let event_root_hash = event_root_hash(&self.output);
+        if event_root_hash != self.txn_info.event_root_hash() {
+            divergences.push(ReplayDivergence::Events {
+                expected_root_hash: self.txn_info.event_root_hash(),
+                actual_root_hash: event_root_hash,
+            });
+        }
+
+        divergences
+    }
+}
+
+/// The first transaction whose output differs between sequential and parallel execution
+pub struct ExecutionDivergence {
+    pub version: Version,
+    /// Version of the first transaction of the block the transaction was executed in
+    pub block_version: Version,
+    pub concurrency_level: usize,
+    /// Differences of the parallel output, with the sequential output as the expected one
+    pub divergences: Vec<ReplayDivergence>,
+}
+
+/// Compares the output of a transaction against the expected one. Returns an empty list if
+/// they are identical.
+pub fn diff_transaction_outputs(
+    expected: &TransactionOutput,
+    actual: &TransactionOutput,
+) -> Vec<ReplayDivergence> {
+    let mut divergences = vec![];
+
+    if actual.status() != expected.status() {
+        divergences.push(ReplayDivergence::Status {
+            expected: expected.status().clone(),
+            actual: actual.status().clone(),
+        });
+    }
+
+    if actual.gas_used() != expected.gas_used() {
+        divergences.push(ReplayDivergence::GasUsed {
+            expected: expected.gas_used(),
+            actual: actual.gas_used(),
+        });
+    }
+
+    divergences.extend(
+        diff_write_sets(expected.write_set(), actual.write_set())
+            .into_iter()
+            .map(|(state_key, expected, actual)| ReplayDivergence::Write {
+                state_key,
+                expected,
+                actual,
+            }),
+    );
+
+    let expected_root_hash = event_root_hash(expected);
+    let actual_root_hash = event_root_hash(actual);
+    if actual_root_hash != expected_root_hash {
+        divergences.push(ReplayDivergence::Events {
+            expected_root_hash,
+            actual_root_hash,
+        });
+    }
+
+    divergences
+}
+
+fn event_root_hash(output: &TransactionOutput) -> HashValue {
+    let event_hashes = output
+        .events()
+        .iter()
+        .map(CryptoHash::hash)
+        .collect::<Vec<_>>();
+    InMemoryEventAccumulator::from_leaves(&event_hashes).root_hash
+}
+
+/// A difference between the output of a replayed transaction and the recorded one
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReplayDivergence {
+    Status {
+        expected: TransactionStatus,
+        actual: TransactionStatus,
+    },
+    GasUsed {
+        expected: u64,
+        actual: u64,
+    },
+    /// A state item was written differently. `None` means that the item was not written.
+    Write {
+        state_key: StateKey,
+        expected: Option<WriteOp>,
+        actual: Option<WriteOp>,
+    },
+    /// Events are only recorded by hash, so the individual events which differ are unknown.
+    Events {
+        expected_root_hash: HashValue,
+        actual_root_hash: HashValue,
+    },
+}
+
+impl Display for ReplayDivergence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayDivergence::Status { expected, actual } => {
+                write!(f, "status: {:?}, expected: {:?}", actual, expected)
+            },
+            ReplayDivergence::GasUsed { expected, actual } => {
+                write!(f, "gas used: {}, expected: {}", actual, expected)
+            },
+            ReplayDivergence::Write {
+                state_key,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "write to {:?}: {:?}, expected: {:?}",
+                state_key, actual, expected
+            ),
+            ReplayDivergence::Events {
+                expected_root_hash,
+                actual_root_hash,
+            } => write!(
+                f,
+                "event root hash: {}, expected: {}",
+                actual_root_hash, expected_root_hash
+            ),
+        }
+    }
+}
+
+/// Returns the state items written differently by the two write sets, with their writes in
+/// the expected and the actual write set respectively
+pub fn diff_write_sets(
+    expected: &WriteSet,
+    actual: &WriteSet,
+) -> Vec<(StateKey, Option<WriteOp>, Option<WriteOp>)> {
+    let mut diff = vec![];
+    for (state_key, expected_op) in expected.iter() {
+        let actual_op = actual.get(state_key);
+        if actual_op != Some(expected_op) {
+            diff.push((
+                state_key.clone(),
+                Some(expected_op.clone()),
+                actual_op.cloned(),
+            ));
+        }
+    }
+    for (state_key, actual_op) in actual.iter() {
+        if expected.get(state_key).is_none() {
+            diff.push((state_key.clone(), None, Some(actual_op.clone())));
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::write_set::WriteSetMut;
+
+    #[test]
+    fn test_diff_identical_write_sets() {
+        let write_set = create_write_set(vec![
+            (state_key(1), WriteOp::legacy_creation(vec![1].into())),
+            (state_key(2), WriteOp::legacy_deletion()),
+        ]);
+        assert!(diff_write_sets(&write_set, &write_set.clone()).is_empty());
+        assert!(diff_write_sets(&WriteSet::default(), &WriteSet::default()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_write_sets() {
+        let expected = create_write_set(vec![
+            (state_key(1), WriteOp::legacy_modification(vec![1].into())),
+            (state_key(2), WriteOp::legacy_modification(vec![2].into())),
+            (state_key(3), WriteOp::legacy_deletion()),
+        ]);
+        let actual = create_write_set(vec![
+            (state_key(1), WriteOp::legacy_modification(vec![1].into())),
+            (state_key(2), WriteOp::legacy_modification(vec![3].into())),
+            (state_key(4), WriteOp::legacy_creation(vec![4].into())),
+        ]);
+
+        // Verify the differing, missing and unexpected writes are all reported
+        let mut diff = diff_write_sets(&expected, &actual);
+        diff.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+        assert_eq!(diff, vec![
+            (
+                state_key(2),
+                Some(WriteOp::legacy_modification(vec![2].into())),
+                Some(WriteOp::legacy_modification(vec![3].into())),
+            ),
+            (state_key(3), Some(WriteOp::legacy_deletion()), None),
+            (
+                state_key(4),
+                None,
+                Some(WriteOp::legacy_creation(vec![4].into())),
+            ),
+        ]);
+
+        // Verify swapping the write sets swaps the reported writes
+        let mut reversed_diff = diff_write_sets(&actual, &expected);
+        reversed_diff.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+        let swapped_diff = diff
+            .into_iter()
+            .map(|(state_key, expected, actual)| (state_key, actual, expected))
+            .collect::<Vec<_>>();
+        assert_eq!(reversed_diff, swapped_diff);
+    }
+
+    #[test]
+    fn test_diff_write_ops_with_different_kinds() {
+        // Verify a creation and a modification with the same data differ
+        let expected = create_write_set(vec![(
+            state_key(1),
+            WriteOp::legacy_creation(vec![1].into()),
+        )]);
+        let actual = create_write_set(vec![(
+            state_key(1),
+            WriteOp::legacy_modification(vec![1].into()),
+        )]);
+        assert_eq!(diff_write_sets(&expected, &actual), vec![(
+            state_key(1),
+            Some(WriteOp::legacy_creation(vec![1].into())),
+            Some(WriteOp::legacy_modification(vec![1].into())),
+        )]);
+    }
+
+    fn create_write_set(write_ops: Vec<(StateKey, WriteOp)>) -> WriteSet {
+        WriteSetMut::new(write_ops).freeze().unwrap()
+    }
+
+    fn state_key(index: u8) -> StateKey {
+        StateKey::raw(&[index])
+    }
+}
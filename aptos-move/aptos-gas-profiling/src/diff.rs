@@ -0,0 +1,123 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::export::{GasProfile, GasProfileFrame};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Gas used by a function, aggregated over all of its calls in a profile, in internal gas units
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionGasUsage {
+    pub calls: u64,
+    /// Execution and IO costs of the function itself, excluding its callees
+    pub self_cost: u64,
+    /// Execution and IO costs of the function, including its callees. Recursive calls are
+    /// only accounted for once.
+    pub total: u64,
+}
+
+/// Change of the gas used by a function between two profiles
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionGasDiff {
+    pub name: String,
+    pub base: FunctionGasUsage,
+    pub new: FunctionGasUsage,
+    pub self_cost_delta: i64,
+    pub total_delta: i64,
+}
+
+/// Comparison of two gas profiles. Costs are in internal gas units, except for storage fees
+/// which are in Octas.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasProfileDiff {
+    pub gas_scaling_factor: u64,
+    pub base_execution_and_io: u64,
+    pub new_execution_and_io: u64,
+    pub execution_and_io_delta: i64,
+    pub base_storage_fee: u64,
+    pub new_storage_fee: u64,
+    pub storage_fee_delta: i64,
+    /// Functions whose gas usage changed, sorted by the absolute change of their total cost,
+    /// from high to low
+    pub functions: Vec<FunctionGasDiff>,
+}
+
+fn delta(base: u64, new: u64) -> i64 {
+    new as i64 - base as i64
+}
+
+fn aggregate_frame<'a>(
+    frame: &'a GasProfileFrame,
+    on_stack: &mut BTreeSet<&'a str>,
+    usage: &mut BTreeMap<String, FunctionGasUsage>,
+) {
+    let entry = usage.entry(frame.name.clone()).or_default();
+    entry.calls += 1;
+    entry.self_cost += frame.execution + frame.io;
+
+    let is_recursive = !on_stack.insert(&frame.name);
+    if !is_recursive {
+        entry.total += frame.total;
+    }
+    for callee in &frame.calls {
+        aggregate_frame(callee, on_stack, usage);
+    }
+    if !is_recursive {
+        on_stack.remove(frame.name.as_str());
+    }
+}
+
+impl GasProfile {
+    /// Aggregates the gas used by each function of the call graph.
+    pub fn function_gas_usage(&self) -> BTreeMap<String, FunctionGasUsage> {
+        let mut usage = BTreeMap::new();
+        aggregate_frame(&self.call_graph, &mut BTreeSet::new(), &mut usage);
+        usage
+    }
+
+    /// Compares this profile against a newer one.
+    pub fn diff(&self, new: &GasProfile) -> GasProfileDiff {
+        let mut base_usage = self.function_gas_usage();
+        let mut new_usage = new.function_gas_usage();
+
+        let names = base_usage
+            .keys()
+            .chain(new_usage.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let mut functions = names
+            .into_iter()
+            .filter_map(|name| {
+                let base = base_usage.remove(&name).unwrap_or_default();
+                let new = new_usage.remove(&name).unwrap_or_default();
+                if base == new {
+                    return None;
+                }
+                Some(FunctionGasDiff {
+                    self_cost_delta: delta(base.self_cost, new.self_cost),
+                    total_delta: delta(base.total, new.total),
+                    name,
+                    base,
+                    new,
+                })
+            })
+            .collect::<Vec<_>>();
+        functions.sort_by(|lhs, rhs| {
+            rhs.total_delta
+                .unsigned_abs()
+                .cmp(&lhs.total_delta.unsigned_abs())
+                .then_with(|| lhs.name.cmp(&rhs.name))
+        });
+
+        GasProfileDiff {
+            gas_scaling_factor: new.gas_scaling_factor,
+            base_execution_and_io: self.total_execution_and_io,
+            new_execution_and_io: new.total_execution_and_io,
+            execution_and_io_delta: delta(self.total_execution_and_io, new.total_execution_and_io),
+            base_storage_fee: self.total_storage_fee,
+            new_storage_fee: new.total_storage_fee,
+            storage_fee_delta: delta(self.total_storage_fee, new.total_storage_fee),
+            functions,
+        }
+    }
+}
@@ -141,6 +141,7 @@ impl ExecutionGasEvent {
                 fn_name,
                 ty_args,
                 cost,
+                ..
             } => Node::new(
                 format!(
                     "{}",
@@ -0,0 +1,298 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::log::{
+    CallFrame, Dependency, EventStorage, EventTransient, ExecutionGasEvent, FrameName,
+    TransactionGasLog, WriteOpType, WriteStorage, WriteTransient,
+};
+use aptos_types::{
+    access_path::Path,
+    state_store::state_key::{inner::StateKeyInner, StateKey},
+};
+use move_core_types::{
+    identifier::IdentStr,
+    language_storage::{ModuleId, TypeTag},
+};
+use serde::{Deserialize, Serialize};
+
+/// Version of the schema of [`GasProfile`]. Bumped on any change that is not backward compatible.
+pub const GAS_PROFILE_SCHEMA_VERSION: u32 = 1;
+
+/// A machine-readable export of a [`TransactionGasLog`], meant to be stored and compared
+/// across runs.
+///
+/// Execution and IO costs are in internal gas units (divide by `gas_scaling_factor` to get
+/// gas units) and storage fees in Octas. Names are fully qualified, so they do not depend on
+/// how the profile is rendered.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasProfile {
+    pub schema_version: u32,
+    pub gas_scaling_factor: u64,
+
+    /// Total execution and IO cost of the transaction, in internal gas units
+    pub total_execution_and_io: u64,
+    /// Total storage fee of the transaction, in Octas
+    pub total_storage_fee: u64,
+    /// Total storage refund of the transaction, in Octas
+    pub total_storage_refund: u64,
+
+    pub intrinsic: u64,
+    pub keyless: u64,
+    pub dependencies: Vec<GasProfileDependency>,
+    pub call_graph: GasProfileFrame,
+    pub ledger_writes: GasProfileLedgerWrites,
+    pub storage: GasProfileStorage,
+}
+
+/// A function call, or a native function call, with the costs attributed to it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasProfileFrame {
+    /// Fully qualified name of the function, e.g. `0x1::coin::transfer<0x1::aptos_coin::AptosCoin>`,
+    /// or `<script>`
+    pub name: String,
+    pub is_native: bool,
+    /// Cost of the instructions executed by the call itself, or of the native function
+    pub execution: u64,
+    /// Cost of the resources loaded from storage by the call itself
+    pub io: u64,
+    /// Cost of the call, including all of its callees
+    pub total: u64,
+    pub calls: Vec<GasProfileFrame>,
+}
+
+/// A module loaded by the transaction
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasProfileDependency {
+    pub module: String,
+    pub is_new: bool,
+    pub size: u64,
+    pub cost: u64,
+}
+
+/// IO costs of writing the outputs of the transaction to the ledger, in internal gas units
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasProfileLedgerWrites {
+    pub transaction: u64,
+    pub events: Vec<GasProfileItem>,
+    pub state_write_ops: Vec<GasProfileItem>,
+}
+
+/// Storage fees of the transaction, in Octas
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasProfileStorage {
+    pub transaction: u64,
+    pub writes: Vec<GasProfileItem>,
+    pub events: Vec<GasProfileItem>,
+    pub event_discount: u64,
+}
+
+/// A named cost, e.g. an event or a write to a state item
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasProfileItem {
+    pub name: String,
+    pub cost: u64,
+    /// Only set for storage writes which are refunded
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub refund: u64,
+}
+
+fn is_zero(val: &u64) -> bool {
+    *val == 0
+}
+
+/// Fully qualified name of a frame, e.g. `0x1::coin::transfer<0x1::aptos_coin::AptosCoin>`, or
+/// `<script>`
+pub(crate) fn frame_name(name: &FrameName) -> String {
+    match name {
+        FrameName::Script => "<script>".to_string(),
+        FrameName::Function {
+            module_id,
+            name,
+            ty_args,
+        } => function_name(module_id, name, ty_args),
+    }
+}
+
+pub(crate) fn function_name(module_id: &ModuleId, name: &IdentStr, ty_args: &[TypeTag]) -> String {
+    let mut function = format!("{}::{}", module_id.short_str_lossless(), name);
+    if !ty_args.is_empty() {
+        let ty_args = ty_args
+            .iter()
+            .map(|ty| ty.to_canonical_string())
+            .collect::<Vec<_>>();
+        function.push_str(&format!("<{}>", ty_args.join(", ")));
+    }
+    function
+}
+
+fn state_key_name(key: &StateKey) -> String {
+    match key.inner() {
+        StateKeyInner::AccessPath(access_path) => match access_path.get_path() {
+            Path::Code(module_id) => module_id.short_str_lossless(),
+            Path::Resource(struct_tag) | Path::ResourceGroup(struct_tag) => format!(
+                "{}::{}",
+                access_path.address.to_hex_literal(),
+                struct_tag.to_canonical_string()
+            ),
+        },
+        StateKeyInner::TableItem { handle, key } => {
+            format!(
+                "table_item<{},0x{}>",
+                handle.0.to_hex_literal(),
+                hex::encode(key)
+            )
+        },
+        StateKeyInner::Raw(bytes) => format!("raw<0x{}>", hex::encode(bytes)),
+    }
+}
+
+fn write_op_name(op_type: &WriteOpType, key: &StateKey) -> String {
+    let op = match op_type {
+        WriteOpType::Creation => "create",
+        WriteOpType::Modification => "modify",
+        WriteOpType::Deletion => "delete",
+    };
+    format!("{}<{}>", op, state_key_name(key))
+}
+
+impl CallFrame {
+    fn to_gas_profile_frame(&self) -> GasProfileFrame {
+        use ExecutionGasEvent::*;
+
+        let mut frame = GasProfileFrame {
+            name: frame_name(&self.name),
+            is_native: false,
+            execution: 0,
+            io: 0,
+            total: 0,
+            calls: vec![],
+        };
+        for event in &self.events {
+            match event {
+                Loc(_) => (),
+                Bytecode { cost, .. } | CreateTy { cost } => frame.execution += u64::from(*cost),
+                LoadResource { cost, .. } => frame.io += u64::from(*cost),
+                Call(callee) => frame.calls.push(callee.to_gas_profile_frame()),
+                CallNative {
+                    module_id,
+                    fn_name,
+                    ty_args,
+                    cost,
+                    ..
+                } => frame.calls.push(GasProfileFrame {
+                    name: function_name(module_id, fn_name, ty_args),
+                    is_native: true,
+                    execution: u64::from(*cost),
+                    io: 0,
+                    total: u64::from(*cost),
+                    calls: vec![],
+                }),
+            }
+        }
+        frame.total =
+            frame.execution + frame.io + frame.calls.iter().map(|callee| callee.total).sum::<u64>();
+        frame
+    }
+}
+
+impl Dependency {
+    fn to_gas_profile_dependency(&self) -> GasProfileDependency {
+        GasProfileDependency {
+            module: self.id.short_str_lossless(),
+            is_new: self.is_new,
+            size: u64::from(self.size),
+            cost: u64::from(self.cost),
+        }
+    }
+}
+
+impl EventTransient {
+    fn to_gas_profile_item(&self) -> GasProfileItem {
+        GasProfileItem {
+            name: self.ty.to_canonical_string(),
+            cost: u64::from(self.cost),
+            refund: 0,
+        }
+    }
+}
+
+impl WriteTransient {
+    fn to_gas_profile_item(&self) -> GasProfileItem {
+        GasProfileItem {
+            name: write_op_name(&self.op_type, &self.key),
+            cost: u64::from(self.cost),
+            refund: 0,
+        }
+    }
+}
+
+impl WriteStorage {
+    fn to_gas_profile_item(&self) -> GasProfileItem {
+        GasProfileItem {
+            name: write_op_name(&self.op_type, &self.key),
+            cost: u64::from(self.cost),
+            refund: u64::from(self.refund),
+        }
+    }
+}
+
+impl EventStorage {
+    fn to_gas_profile_item(&self) -> GasProfileItem {
+        GasProfileItem {
+            name: self.ty.to_canonical_string(),
+            cost: u64::from(self.cost),
+            refund: 0,
+        }
+    }
+}
+
+impl TransactionGasLog {
+    /// Exports the gas log into the machine-readable [`GasProfile`] format.
+    pub fn to_gas_profile(&self) -> GasProfile {
+        let exec_io = &self.exec_io;
+        let storage = &self.storage;
+
+        GasProfile {
+            schema_version: GAS_PROFILE_SCHEMA_VERSION,
+            gas_scaling_factor: u64::from(exec_io.gas_scaling_factor),
+            total_execution_and_io: u64::from(exec_io.total),
+            total_storage_fee: u64::from(storage.total),
+            total_storage_refund: u64::from(storage.total_refund),
+            intrinsic: u64::from(exec_io.intrinsic_cost),
+            keyless: u64::from(exec_io.keyless_cost),
+            dependencies: exec_io
+                .dependencies
+                .iter()
+                .map(|dep| dep.to_gas_profile_dependency())
+                .collect(),
+            call_graph: exec_io.call_graph.to_gas_profile_frame(),
+            ledger_writes: GasProfileLedgerWrites {
+                transaction: exec_io.transaction_transient.map_or(0, u64::from),
+                events: exec_io
+                    .events_transient
+                    .iter()
+                    .map(|event| event.to_gas_profile_item())
+                    .collect(),
+                state_write_ops: exec_io
+                    .write_set_transient
+                    .iter()
+                    .map(|write| write.to_gas_profile_item())
+                    .collect(),
+            },
+            storage: GasProfileStorage {
+                transaction: u64::from(storage.txn_storage),
+                writes: storage
+                    .write_set_storage
+                    .iter()
+                    .map(|write| write.to_gas_profile_item())
+                    .collect(),
+                events: storage
+                    .events
+                    .iter()
+                    .map(|event| event.to_gas_profile_item())
+                    .collect(),
+                event_discount: u64::from(storage.event_discount),
+            },
+        }
+    }
+}
@@ -139,6 +139,7 @@ impl ExecutionAndIOCosts {
                             fn_name,
                             ty_args,
                             cost,
+                            ..
                         } => self.lines.push(
                             format!(
                                 "{};{}",
@@ -2,13 +2,33 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod aggregate;
+mod diff;
 mod erased;
+mod export;
 mod flamegraph;
 mod log;
 mod misc;
+mod pprof;
 mod profiler;
 mod render;
 mod report;
+#[cfg(test)]
+mod tests;
+mod trace;
+mod value;
 
-pub use log::{FrameName, TransactionGasLog};
+pub use diff::{FunctionGasDiff, FunctionGasUsage, GasProfileDiff};
+pub use export::{
+    GasProfile, GasProfileDependency, GasProfileFrame, GasProfileItem, GasProfileLedgerWrites,
+    GasProfileStorage, GAS_PROFILE_SCHEMA_VERSION,
+};
+pub use log::{
+    CallFrame, ExecutionAndIOCosts, ExecutionGasEvent, FrameName, FrameTrace, ResourceWrite,
+    TransactionGasLog,
+};
 pub use profiler::GasProfiler;
+pub use trace::{
+    ExecutionTrace, TraceAbortLocation, TraceEvent, TraceFrame, TraceResourceRead,
+    TraceResourceWrite,
+};
+pub use value::render_move_value;
@@ -29,6 +29,7 @@ pub enum ExecutionGasEvent {
         fn_name: Identifier,
         ty_args: Vec<TypeTag>,
         cost: InternalGas,
+        trace: Option<FrameTrace>,
     },
     LoadResource {
         addr: AccountAddress,
@@ -58,6 +59,25 @@ pub enum FrameName {
 pub struct CallFrame {
     pub name: FrameName,
     pub events: Vec<ExecutionGasEvent>,
+    /// Only recorded if value tracing is enabled in the profiler.
+    pub trace: Option<FrameTrace>,
+}
+
+/// The values and the global storage mutations of a function call.
+#[derive(Debug, Clone, Default)]
+pub struct FrameTrace {
+    pub args: Vec<String>,
+    /// `false` if the function did not return, e.g. because the execution aborted.
+    pub returned: bool,
+    pub return_values: Vec<String>,
+    pub resource_writes: Vec<ResourceWrite>,
+}
+
+/// A mutation of a resource in global storage: a `move_to`, a `move_from` or a mutable borrow.
+#[derive(Debug, Clone)]
+pub struct ResourceWrite {
+    pub op: Opcodes,
+    pub ty: TypeTag,
 }
 
 /// The type of an operation performed on a storage item.
@@ -183,6 +203,7 @@ impl CallFrame {
                 ty_args,
             },
             events: vec![],
+            trace: None,
         }
     }
 
@@ -190,6 +211,7 @@ impl CallFrame {
         Self {
             name: FrameName::Script,
             events: vec![],
+            trace: None,
         }
     }
 }
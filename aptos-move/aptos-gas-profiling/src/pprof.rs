@@ -0,0 +1,327 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Export of gas profiles in the pprof format, so that they can be explored with the
+//! existing tooling, e.g. `go tool pprof -http=: profile.pb.gz`.
+//!
+//! Only the subset of the pprof schema needed to describe call stacks is defined here, see
+//! https://github.com/google/pprof/blob/main/proto/profile.proto for the full schema.
+
+use crate::export::{GasProfile, GasProfileFrame};
+use anyhow::Result;
+use flate2::{write::GzEncoder, Compression};
+use prost::Message;
+use std::{collections::BTreeMap, io::Write};
+
+#[derive(Clone, PartialEq, Message)]
+struct Profile {
+    #[prost(message, repeated, tag = "1")]
+    sample_type: Vec<ValueType>,
+    #[prost(message, repeated, tag = "2")]
+    sample: Vec<Sample>,
+    #[prost(message, repeated, tag = "4")]
+    location: Vec<Location>,
+    #[prost(message, repeated, tag = "5")]
+    function: Vec<Function>,
+    #[prost(string, repeated, tag = "6")]
+    string_table: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ValueType {
+    #[prost(int64, tag = "1")]
+    r#type: i64,
+    #[prost(int64, tag = "2")]
+    unit: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Sample {
+    /// The leaf is at `location_id[0]`
+    #[prost(uint64, repeated, tag = "1")]
+    location_id: Vec<u64>,
+    #[prost(int64, repeated, tag = "2")]
+    value: Vec<i64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Location {
+    #[prost(uint64, tag = "1")]
+    id: u64,
+    #[prost(message, repeated, tag = "4")]
+    line: Vec<Line>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Line {
+    #[prost(uint64, tag = "1")]
+    function_id: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Function {
+    #[prost(uint64, tag = "1")]
+    id: u64,
+    #[prost(int64, tag = "2")]
+    name: i64,
+}
+
+/// Index of each kind of cost in the values of a sample
+const EXECUTION: usize = 0;
+const IO: usize = 1;
+const STORAGE: usize = 2;
+
+/// Costs aggregated by call stack, from the root to the leaf
+#[derive(Default)]
+struct Stacks(BTreeMap<Vec<String>, [i64; 3]>);
+
+impl Stacks {
+    fn add(&mut self, stack: &[&str], kind: usize, cost: u64) {
+        if cost == 0 {
+            return;
+        }
+        let stack = stack.iter().map(|name| name.to_string()).collect();
+        self.0.entry(stack).or_default()[kind] += cost as i64;
+    }
+
+    fn add_frame(&mut self, path: &mut Vec<String>, frame: &GasProfileFrame) {
+        path.push(frame.name.clone());
+        let stack = path.iter().map(String::as_str).collect::<Vec<_>>();
+        self.add(&stack, EXECUTION, frame.execution);
+        self.add(&stack, IO, frame.io);
+        for callee in &frame.calls {
+            self.add_frame(path, callee);
+        }
+        path.pop();
+    }
+}
+
+#[derive(Default)]
+struct ProfileBuilder {
+    profile: Profile,
+    strings: BTreeMap<String, i64>,
+    functions: BTreeMap<String, u64>,
+}
+
+impl ProfileBuilder {
+    fn string(&mut self, s: &str) -> i64 {
+        if let Some(idx) = self.strings.get(s) {
+            return *idx;
+        }
+        let idx = self.profile.string_table.len() as i64;
+        self.profile.string_table.push(s.to_string());
+        self.strings.insert(s.to_string(), idx);
+        idx
+    }
+
+    /// Every function has a single location, with the same id.
+    fn location(&mut self, name: &str) -> u64 {
+        if let Some(id) = self.functions.get(name) {
+            return *id;
+        }
+        let id = self.profile.function.len() as u64 + 1;
+        let name_idx = self.string(name);
+        self.profile.function.push(Function { id, name: name_idx });
+        self.profile.location.push(Location {
+            id,
+            line: vec![Line { function_id: id }],
+        });
+        self.functions.insert(name.to_string(), id);
+        id
+    }
+}
+
+impl GasProfile {
+    /// Exports the profile in the gzipped pprof protobuf format.
+    ///
+    /// The profile has three sample types: `execution` and `io` in internal gas units, and
+    /// `storage` in Octas. Costs which are not incurred by a function call, such as the
+    /// intrinsic cost or the ledger writes, are attributed to pseudo functions named after them.
+    pub fn to_pprof(&self) -> Result<Vec<u8>> {
+        let mut stacks = Stacks::default();
+
+        stacks.add(&["intrinsic"], EXECUTION, self.intrinsic);
+        stacks.add(&["keyless"], EXECUTION, self.keyless);
+        for dep in &self.dependencies {
+            stacks.add(&["dependencies", &dep.module], IO, dep.cost);
+        }
+        stacks.add_frame(&mut vec![], &self.call_graph);
+
+        let ledger_writes = &self.ledger_writes;
+        stacks.add(
+            &["ledger_writes", "transaction"],
+            IO,
+            ledger_writes.transaction,
+        );
+        for event in &ledger_writes.events {
+            stacks.add(&["ledger_writes", "events", &event.name], IO, event.cost);
+        }
+        for write in &ledger_writes.state_write_ops {
+            stacks.add(
+                &["ledger_writes", "state_write_ops", &write.name],
+                IO,
+                write.cost,
+            );
+        }
+
+        let storage = &self.storage;
+        stacks.add(&["storage", "transaction"], STORAGE, storage.transaction);
+        for write in &storage.writes {
+            stacks.add(&["storage", "writes", &write.name], STORAGE, write.cost);
+        }
+        for event in &storage.events {
+            stacks.add(&["storage", "events", &event.name], STORAGE, event.cost);
+        }
+
+        let mut builder = ProfileBuilder::default();
+        // The first string of the table must be the empty string.
+        builder.string("");
+        for (ty, unit) in [
+            ("execution", "internal_gas"),
+            ("io", "internal_gas"),
+            ("storage", "octas"),
+        ] {
+            let value_type = ValueType {
+                r#type: builder.string(ty),
+                unit: builder.string(unit),
+            };
+            builder.profile.sample_type.push(value_type);
+        }
+        for (stack, values) in stacks.0 {
+            let location_id = stack
+                .iter()
+                .rev()
+                .map(|name| builder.location(name))
+                .collect();
+            builder.profile.sample.push(Sample {
+                location_id,
+                value: values.to_vec(),
+            });
+        }
+
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&builder.profile.encode_to_vec())?;
+        Ok(encoder.finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{aptos_coin, coin_struct, synthetic_gas_log};
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    /// Decodes the profile into its samples, as call stacks from the root to the leaf
+    fn decode_samples(bytes: &[u8]) -> (Profile, BTreeMap<Vec<String>, Vec<i64>>) {
+        let mut decoded = vec![];
+        GzDecoder::new(bytes).read_to_end(&mut decoded).unwrap();
+        let profile = Profile::decode(decoded.as_slice()).unwrap();
+
+        let function_names = profile
+            .function
+            .iter()
+            .map(|function| (function.id, function.name))
+            .collect::<BTreeMap<_, _>>();
+        let location_functions = profile
+            .location
+            .iter()
+            .map(|location| (location.id, location.line[0].function_id))
+            .collect::<BTreeMap<_, _>>();
+        let samples = profile
+            .sample
+            .iter()
+            .map(|sample| {
+                let stack = sample
+                    .location_id
+                    .iter()
+                    .rev()
+                    .map(|id| {
+                        let name = function_names[&location_functions[id]];
+                        profile.string_table[name as usize].clone()
+                    })
+                    .collect();
+                (stack, sample.value.clone())
+            })
+            .collect();
+        (profile, samples)
+    }
+
+    fn stack(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_pprof_export() {
+        let gas_profile = synthetic_gas_log().to_gas_profile();
+        let (profile, samples) = decode_samples(&gas_profile.to_pprof().unwrap());
+
+        assert_eq!(profile.string_table[0], "");
+        let sample_types = profile
+            .sample_type
+            .iter()
+            .map(|ty| {
+                (
+                    profile.string_table[ty.r#type as usize].as_str(),
+                    profile.string_table[ty.unit as usize].as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sample_types, vec![
+            ("execution", "internal_gas"),
+            ("io", "internal_gas"),
+            ("storage", "octas"),
+        ]);
+
+        let aptos_coin = aptos_coin().to_canonical_string();
+        let transfer = format!("0x1::coin::transfer<{}>", aptos_coin);
+        let withdraw = format!("0x1::coin::withdraw<{}>", aptos_coin);
+        let withdraw_event = coin_struct("WithdrawEvent").to_canonical_string();
+        let expected = BTreeMap::from([
+            (stack(&["intrinsic"]), vec![100, 0, 0]),
+            (stack(&["dependencies", "0x1::coin"]), vec![0, 30, 0]),
+            (stack(&[&transfer]), vec![15, 50, 0]),
+            (stack(&[&transfer, &withdraw]), vec![20, 0, 0]),
+            // The recursive call has its own stack
+            (stack(&[&transfer, &withdraw, &withdraw]), vec![5, 0, 0]),
+            (
+                stack(&[&transfer, &withdraw, "0x1::signer::address_of"]),
+                vec![7, 0, 0],
+            ),
+            (stack(&["ledger_writes", "transaction"]), vec![0, 40, 0]),
+            (stack(&["ledger_writes", "events", &withdraw_event]), vec![
+                0, 8, 0,
+            ]),
+            (
+                stack(&["ledger_writes", "state_write_ops", "modify<raw<0x6b6579>>"]),
+                vec![0, 60, 0],
+            ),
+            (stack(&["storage", "transaction"]), vec![0, 0, 300]),
+            (
+                stack(&["storage", "writes", "create<raw<0x6b6579>>"]),
+                vec![0, 0, 500],
+            ),
+            (stack(&["storage", "events", &withdraw_event]), vec![
+                0, 0, 20,
+            ]),
+        ]);
+        assert_eq!(samples, expected);
+
+        // Costs are only attributed once, so the samples add up to the totals of the profile
+        let execution_and_io: i64 = samples.values().map(|value| value[0] + value[1]).sum();
+        let storage: i64 = samples.values().map(|value| value[2]).sum();
+        assert_eq!(execution_and_io as u64, gas_profile.total_execution_and_io);
+        assert_eq!(storage as u64, gas_profile.total_storage_fee);
+
+        // Every function has a single location, even if it is called from several stacks
+        assert_eq!(profile.function.len(), profile.location.len());
+        let mut function_names = profile
+            .function
+            .iter()
+            .map(|function| function.name)
+            .collect::<Vec<_>>();
+        function_names.sort();
+        function_names.dedup();
+        assert_eq!(function_names.len(), profile.function.len());
+    }
+}
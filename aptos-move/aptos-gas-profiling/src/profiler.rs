@@ -1,9 +1,13 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::log::{
-    CallFrame, Dependency, EventStorage, EventTransient, ExecutionAndIOCosts, ExecutionGasEvent,
-    FrameName, StorageFees, TransactionGasLog, WriteOpType, WriteStorage, WriteTransient,
+use crate::{
+    log::{
+        CallFrame, Dependency, EventStorage, EventTransient, ExecutionAndIOCosts,
+        ExecutionGasEvent, FrameName, FrameTrace, ResourceWrite, StorageFees, TransactionGasLog,
+        WriteOpType, WriteStorage, WriteTransient,
+    },
+    value::render_value,
 };
 use aptos_gas_algebra::{Fee, FeePerGasUnit, InternalGas, NumArgs, NumBytes, NumTypeNodes};
 use aptos_gas_meter::{AptosGasMeter, GasAlgebra};
@@ -41,6 +45,8 @@ pub struct GasProfiler<G> {
     events_transient: Vec<EventTransient>,
     write_set_transient: Vec<WriteTransient>,
     storage_fees: Option<StorageFees>,
+
+    trace_values: bool,
 }
 
 // TODO: consider switching to a library like https://docs.rs/delegate/latest/delegate/.
@@ -98,6 +104,8 @@ impl<G> GasProfiler<G> {
             events_transient: vec![],
             write_set_transient: vec![],
             storage_fees: None,
+
+            trace_values: false,
         }
     }
 
@@ -118,8 +126,29 @@ impl<G> GasProfiler<G> {
             events_transient: vec![],
             write_set_transient: vec![],
             storage_fees: None,
+
+            trace_values: false,
         }
     }
+
+    /// Enables the recording of the arguments and return values of every function call, as
+    /// well as of the resources mutated by each call.
+    ///
+    /// The arguments of the entry function or script are not observed by the gas meter, so
+    /// they must be provided by the caller, e.g. rendered with [`crate::render_move_value`].
+    ///
+    /// Values are rendered as strings when they are recorded, which makes the execution
+    /// significantly slower. This is meant for debugging only.
+    pub fn with_value_tracing(mut self, args: Vec<String>) -> Self {
+        self.trace_values = true;
+        for frame in &mut self.frames {
+            frame.trace = Some(FrameTrace {
+                args: args.clone(),
+                ..FrameTrace::default()
+            });
+        }
+        self
+    }
 }
 
 impl<G> GasProfiler<G>
@@ -142,6 +171,23 @@ where
         self.record_gas_event(ExecutionGasEvent::Loc(offset))
     }
 
+    fn record_resource_write(&mut self, op: Opcodes, ty: Option<TypeTag>) {
+        let trace = &mut self.frames.last_mut().expect("frame must exist").trace;
+        if let (Some(trace), Some(ty)) = (trace, ty) {
+            trace.resource_writes.push(ResourceWrite { op, ty });
+        }
+    }
+
+    fn push_frame(&mut self, mut frame: CallFrame, args: impl Iterator<Item = impl ValueView>) {
+        if self.trace_values {
+            frame.trace = Some(FrameTrace {
+                args: args.map(render_value).collect(),
+                ..FrameTrace::default()
+            });
+        }
+        self.frames.push(frame);
+    }
+
     /// Delegate the charging call to the base gas meter and measure variation in balance.
     fn delegate_charge<F, R>(&mut self, charge: F) -> (InternalGas, R)
     where
@@ -171,12 +217,6 @@ where
             ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
             args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
         ) -> PartialVMResult<()>;
-
-        // Note: we don't use this to charge gas so no need to record anything.
-        fn charge_drop_frame(
-            &mut self,
-            locals: impl Iterator<Item = impl ValueView> + Clone,
-        ) -> PartialVMResult<()>;
     }
 
     record_bytecode! {
@@ -225,22 +265,6 @@ where
         [NEQ]
         fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()>;
 
-        [
-            match (is_mut, is_generic) {
-                (false, false) => IMM_BORROW_GLOBAL,
-                (false, true) => IMM_BORROW_GLOBAL_GENERIC,
-                (true, false) => MUT_BORROW_GLOBAL,
-                (true, true) => MUT_BORROW_GLOBAL_GENERIC
-            }
-        ]
-        fn charge_borrow_global(
-            &mut self,
-            is_mut: bool,
-            is_generic: bool,
-            ty: impl TypeView,
-            is_success: bool,
-        ) -> PartialVMResult<()>;
-
         [if is_generic { EXISTS } else { EXISTS_GENERIC }]
         fn charge_exists(
             &mut self,
@@ -249,23 +273,6 @@ where
             exists: bool,
         ) -> PartialVMResult<()>;
 
-        [if is_generic { MOVE_FROM } else { MOVE_FROM_GENERIC }]
-        fn charge_move_from(
-            &mut self,
-            is_generic: bool,
-            ty: impl TypeView,
-            val: Option<impl ValueView>,
-        ) -> PartialVMResult<()>;
-
-        [if is_generic { MOVE_TO } else { MOVE_TO_GENERIC }]
-        fn charge_move_to(
-            &mut self,
-            is_generic: bool,
-            ty: impl TypeView,
-            val: impl ValueView,
-            is_success: bool,
-        ) -> PartialVMResult<()>;
-
         [VEC_PACK]
         fn charge_vec_pack<'a>(
             &mut self,
@@ -314,11 +321,87 @@ where
         self.base.balance_internal()
     }
 
+    fn charge_borrow_global(
+        &mut self,
+        is_mut: bool,
+        is_generic: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        let op = match (is_mut, is_generic) {
+            (false, false) => Opcodes::IMM_BORROW_GLOBAL,
+            (false, true) => Opcodes::IMM_BORROW_GLOBAL_GENERIC,
+            (true, false) => Opcodes::MUT_BORROW_GLOBAL,
+            (true, true) => Opcodes::MUT_BORROW_GLOBAL_GENERIC,
+        };
+        let ty_tag = (self.trace_values && is_mut && is_success).then(|| ty.to_type_tag());
+
+        let (cost, res) = self
+            .delegate_charge(|base| base.charge_borrow_global(is_mut, is_generic, ty, is_success));
+
+        self.record_bytecode(op, cost);
+        self.record_resource_write(op, ty_tag);
+
+        res
+    }
+
+    fn charge_move_from(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let op = if is_generic {
+            Opcodes::MOVE_FROM_GENERIC
+        } else {
+            Opcodes::MOVE_FROM
+        };
+        let ty_tag = (self.trace_values && val.is_some()).then(|| ty.to_type_tag());
+
+        let (cost, res) = self.delegate_charge(|base| base.charge_move_from(is_generic, ty, val));
+
+        self.record_bytecode(op, cost);
+        self.record_resource_write(op, ty_tag);
+
+        res
+    }
+
+    fn charge_move_to(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: impl ValueView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        let op = if is_generic {
+            Opcodes::MOVE_TO_GENERIC
+        } else {
+            Opcodes::MOVE_TO
+        };
+        let ty_tag = (self.trace_values && is_success).then(|| ty.to_type_tag());
+
+        let (cost, res) =
+            self.delegate_charge(|base| base.charge_move_to(is_generic, ty, val, is_success));
+
+        self.record_bytecode(op, cost);
+        self.record_resource_write(op, ty_tag);
+
+        res
+    }
+
     fn charge_native_function(
         &mut self,
         amount: InternalGas,
         ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView> + Clone>,
     ) -> PartialVMResult<()> {
+        let returned = ret_vals.is_some();
+        let return_values = match &ret_vals {
+            Some(ret_vals) if self.trace_values => {
+                ret_vals.clone().map(render_value).collect::<Vec<_>>()
+            },
+            _ => vec![],
+        };
+
         let (cost, res) =
             self.delegate_charge(|base| base.charge_native_function(amount, ret_vals));
 
@@ -350,6 +433,11 @@ where
             fn_name: name,
             ty_args,
             cost,
+            trace: cur.trace.map(|trace| FrameTrace {
+                returned,
+                return_values,
+                ..trace
+            }),
         });
 
         res
@@ -391,12 +479,37 @@ where
 
         self.record_bytecode(instr.to_opcode(), cost);
 
-        // TODO: Right now we keep the last frame on the stack even after hitting the ret instruction,
+        if matches!(instr, SimpleInstruction::Ret) && res.is_ok() {
+            let cur_frame = self.frames.last_mut().expect("frame must exist");
+            if let Some(trace) = &mut cur_frame.trace {
+                trace.returned = true;
+            }
+        }
+
+        res
+    }
+
+    fn charge_drop_frame(
+        &mut self,
+        locals: impl Iterator<Item = impl ValueView> + Clone,
+        ret_vals: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+    ) -> PartialVMResult<()> {
+        // This is called right after the ret instruction, with the values returned by the
+        // current frame at the top of the operand stack.
+        let cur_frame = self.frames.last_mut().expect("frame must exist");
+        if let Some(trace) = &mut cur_frame.trace {
+            trace.return_values = ret_vals.clone().map(render_value).collect();
+        }
+
+        // Note: we don't use this to charge gas so no need to record anything.
+        let res = self.base.charge_drop_frame(locals, ret_vals);
+
+        // TODO: Right now we keep the last frame on the stack even after it has returned,
         //       so that it can be picked up by finishing procedure.
         //       This is a bit hacky and can lead to weird behaviors if the profiler is used
         //       over multiple transactions, but again, guarding against that case is a broader
         //       problem we can deal with in the future.
-        if matches!(instr, SimpleInstruction::Ret) && self.frames.len() > 1 {
+        if self.frames.len() > 1 {
             let cur_frame = self.frames.pop().expect("frame must exist");
             let last_frame = self.frames.last_mut().expect("frame must exist");
             last_frame.events.push(ExecutionGasEvent::Call(cur_frame));
@@ -412,15 +525,19 @@ where
         args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
         num_locals: NumArgs,
     ) -> PartialVMResult<()> {
+        let traced_args = args.clone();
         let (cost, res) =
             self.delegate_charge(|base| base.charge_call(module_id, func_name, args, num_locals));
 
         self.record_bytecode(Opcodes::CALL, cost);
-        self.frames.push(CallFrame::new_function(
-            module_id.clone(),
-            Identifier::new(func_name).unwrap(),
-            vec![],
-        ));
+        self.push_frame(
+            CallFrame::new_function(
+                module_id.clone(),
+                Identifier::new(func_name).unwrap(),
+                vec![],
+            ),
+            traced_args,
+        );
 
         res
     }
@@ -437,17 +554,21 @@ where
             .clone()
             .map(|ty| ty.to_type_tag())
             .collect::<Vec<_>>();
+        let traced_args = args.clone();
 
         let (cost, res) = self.delegate_charge(|base| {
             base.charge_call_generic(module_id, func_name, ty_args, args, num_locals)
         });
 
         self.record_bytecode(Opcodes::CALL_GENERIC, cost);
-        self.frames.push(CallFrame::new_function(
-            module_id.clone(),
-            Identifier::new(func_name).unwrap(),
-            ty_tags,
-        ));
+        self.push_frame(
+            CallFrame::new_function(
+                module_id.clone(),
+                Identifier::new(func_name).unwrap(),
+                ty_tags,
+            ),
+            traced_args,
+        );
 
         res
     }
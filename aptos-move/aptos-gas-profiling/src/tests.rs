@@ -0,0 +1,313 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    export::{
+        GasProfile, GasProfileDependency, GasProfileFrame, GasProfileItem, GasProfileLedgerWrites,
+        GasProfileStorage, GAS_PROFILE_SCHEMA_VERSION,
+    },
+    log::{
+        CallFrame, Dependency, EventStorage, EventTransient, ExecutionAndIOCosts,
+        ExecutionGasEvent, StorageFees, TransactionGasLog, WriteOpType, WriteStorage,
+        WriteTransient,
+    },
+};
+use aptos_types::state_store::state_key::StateKey;
+use move_binary_format::file_format_common::Opcodes;
+use move_core_types::{
+    account_address::AccountAddress,
+    ident_str,
+    language_storage::{ModuleId, StructTag, TypeTag},
+};
+
+pub(crate) fn aptos_coin() -> TypeTag {
+    TypeTag::Struct(Box::new(StructTag {
+        address: AccountAddress::ONE,
+        module: ident_str!("aptos_coin").to_owned(),
+        name: ident_str!("AptosCoin").to_owned(),
+        type_args: vec![],
+    }))
+}
+
+pub(crate) fn coin_struct(name: &str) -> StructTag {
+    StructTag {
+        address: AccountAddress::ONE,
+        module: ident_str!("coin").to_owned(),
+        name: name.parse().unwrap(),
+        type_args: vec![aptos_coin()],
+    }
+}
+
+fn coin_function(name: &str, events: Vec<ExecutionGasEvent>) -> CallFrame {
+    let mut frame = CallFrame::new_function(
+        ModuleId::new(AccountAddress::ONE, ident_str!("coin").to_owned()),
+        name.parse().unwrap(),
+        vec![aptos_coin()],
+    );
+    frame.events = events;
+    frame
+}
+
+fn bytecode(op: Opcodes, cost: u64) -> ExecutionGasEvent {
+    ExecutionGasEvent::Bytecode {
+        op,
+        cost: cost.into(),
+    }
+}
+
+/// A small gas log of a coin transfer, which withdraws the coins with a recursive call and
+/// calls a native function.
+///
+/// Execution and IO costs add up to 335 internal gas units, and storage fees to 820 Octas
+/// with a refund of 200 Octas.
+pub(crate) fn synthetic_gas_log() -> TransactionGasLog {
+    let inner_withdraw = coin_function("withdraw", vec![bytecode(Opcodes::RET, 5)]);
+    let withdraw = coin_function("withdraw", vec![
+        bytecode(Opcodes::CALL_GENERIC, 20),
+        ExecutionGasEvent::Call(inner_withdraw),
+        ExecutionGasEvent::CallNative {
+            module_id: ModuleId::new(AccountAddress::ONE, ident_str!("signer").to_owned()),
+            fn_name: ident_str!("address_of").to_owned(),
+            ty_args: vec![],
+            cost: 7.into(),
+            trace: None,
+        },
+    ]);
+    let transfer = coin_function("transfer", vec![
+        bytecode(Opcodes::LD_U64, 10),
+        ExecutionGasEvent::LoadResource {
+            addr: AccountAddress::ONE,
+            ty: TypeTag::Struct(Box::new(coin_struct("CoinStore"))),
+            cost: 50.into(),
+        },
+        ExecutionGasEvent::Call(withdraw),
+        ExecutionGasEvent::Loc(3),
+        ExecutionGasEvent::CreateTy { cost: 3.into() },
+        bytecode(Opcodes::RET, 2),
+    ]);
+
+    let withdraw_event = TypeTag::Struct(Box::new(coin_struct("WithdrawEvent")));
+    let exec_io = ExecutionAndIOCosts {
+        gas_scaling_factor: 100.into(),
+        total: 335.into(),
+        intrinsic_cost: 100.into(),
+        keyless_cost: 0.into(),
+        dependencies: vec![Dependency {
+            is_new: false,
+            id: ModuleId::new(AccountAddress::ONE, ident_str!("coin").to_owned()),
+            size: 1000.into(),
+            cost: 30.into(),
+        }],
+        call_graph: transfer,
+        transaction_transient: Some(40.into()),
+        events_transient: vec![EventTransient {
+            ty: withdraw_event.clone(),
+            cost: 8.into(),
+        }],
+        write_set_transient: vec![WriteTransient {
+            key: StateKey::raw(b"key"),
+            op_type: WriteOpType::Modification,
+            cost: 60.into(),
+        }],
+    };
+    let storage = StorageFees {
+        total: 820.into(),
+        total_refund: 200.into(),
+        write_set_storage: vec![
+            WriteStorage {
+                key: StateKey::raw(b"key"),
+                op_type: WriteOpType::Creation,
+                cost: 500.into(),
+                refund: 0.into(),
+            },
+            WriteStorage {
+                key: StateKey::raw(b"old"),
+                op_type: WriteOpType::Deletion,
+                cost: 0.into(),
+                refund: 200.into(),
+            },
+        ],
+        events: vec![EventStorage {
+            ty: withdraw_event,
+            cost: 20.into(),
+        }],
+        event_discount: 0.into(),
+        txn_storage: 300.into(),
+    };
+    exec_io.assert_consistency();
+    storage.assert_consistency();
+    TransactionGasLog { exec_io, storage }
+}
+
+fn frame(name: String, execution: u64, io: u64, calls: Vec<GasProfileFrame>) -> GasProfileFrame {
+    let total = execution + io + calls.iter().map(|callee| callee.total).sum::<u64>();
+    GasProfileFrame {
+        name,
+        is_native: false,
+        execution,
+        io,
+        total,
+        calls,
+    }
+}
+
+fn item(name: &str, cost: u64, refund: u64) -> GasProfileItem {
+    GasProfileItem {
+        name: name.to_string(),
+        cost,
+        refund,
+    }
+}
+
+#[test]
+fn test_export_gas_profile() {
+    let aptos_coin = aptos_coin().to_canonical_string();
+    let withdraw_event = coin_struct("WithdrawEvent").to_canonical_string();
+    let withdraw = format!("0x1::coin::withdraw<{}>", aptos_coin);
+
+    let expected = GasProfile {
+        schema_version: GAS_PROFILE_SCHEMA_VERSION,
+        gas_scaling_factor: 100,
+        total_execution_and_io: 335,
+        total_storage_fee: 820,
+        total_storage_refund: 200,
+        intrinsic: 100,
+        keyless: 0,
+        dependencies: vec![GasProfileDependency {
+            module: "0x1::coin".to_string(),
+            is_new: false,
+            size: 1000,
+            cost: 30,
+        }],
+        call_graph: frame(
+            format!("0x1::coin::transfer<{}>", aptos_coin),
+            15,
+            50,
+            vec![frame(withdraw.clone(), 20, 0, vec![
+                frame(withdraw, 5, 0, vec![]),
+                GasProfileFrame {
+                    name: "0x1::signer::address_of".to_string(),
+                    is_native: true,
+                    execution: 7,
+                    io: 0,
+                    total: 7,
+                    calls: vec![],
+                },
+            ])],
+        ),
+        ledger_writes: GasProfileLedgerWrites {
+            transaction: 40,
+            events: vec![item(&withdraw_event, 8, 0)],
+            state_write_ops: vec![item("modify<raw<0x6b6579>>", 60, 0)],
+        },
+        storage: GasProfileStorage {
+            transaction: 300,
+            writes: vec![
+                item("create<raw<0x6b6579>>", 500, 0),
+                item("delete<raw<0x6f6c64>>", 0, 200),
+            ],
+            events: vec![item(&withdraw_event, 20, 0)],
+            event_discount: 0,
+        },
+    };
+
+    let profile = synthetic_gas_log().to_gas_profile();
+    assert_eq!(profile, expected);
+    assert_eq!(profile.call_graph.total, 97);
+
+    // The profile survives a round trip through JSON, and refunds are only serialized if set
+    let json = serde_json::to_value(&profile).unwrap();
+    assert_eq!(json["storage"]["writes"][1]["refund"], 200);
+    assert!(json["storage"]["writes"][0].get("refund").is_none());
+    assert_eq!(
+        serde_json::from_value::<GasProfile>(json).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn test_function_gas_usage() {
+    let aptos_coin = aptos_coin().to_canonical_string();
+    let usage = synthetic_gas_log().to_gas_profile().function_gas_usage();
+
+    let names = usage.keys().cloned().collect::<Vec<_>>();
+    assert_eq!(names, vec![
+        format!("0x1::coin::transfer<{}>", aptos_coin),
+        format!("0x1::coin::withdraw<{}>", aptos_coin),
+        "0x1::signer::address_of".to_string(),
+    ]);
+
+    let transfer = &usage[&names[0]];
+    assert_eq!(
+        (transfer.calls, transfer.self_cost, transfer.total),
+        (1, 65, 97)
+    );
+    // The recursive call of withdraw is only accounted for once in its total
+    let withdraw = &usage[&names[1]];
+    assert_eq!(
+        (withdraw.calls, withdraw.self_cost, withdraw.total),
+        (2, 25, 32)
+    );
+    let address_of = &usage[&names[2]];
+    assert_eq!(
+        (address_of.calls, address_of.self_cost, address_of.total),
+        (1, 7, 7)
+    );
+}
+
+#[test]
+fn test_diff_gas_profiles() {
+    let base = synthetic_gas_log().to_gas_profile();
+    assert!(base.diff(&base).functions.is_empty());
+
+    // Make the native function more expensive, and the outer withdraw cheaper
+    let mut new = base.clone();
+    let withdraw = &mut new.call_graph.calls[0];
+    withdraw.calls[1].execution = 10;
+    withdraw.calls[1].total = 10;
+    withdraw.execution = 15;
+    withdraw.total = 30;
+    new.call_graph.total = 95;
+    new.total_execution_and_io = 333;
+    new.total_storage_fee = 900;
+
+    let diff = base.diff(&new);
+    assert_eq!(diff.gas_scaling_factor, 100);
+    assert_eq!(
+        (
+            diff.base_execution_and_io,
+            diff.new_execution_and_io,
+            diff.execution_and_io_delta
+        ),
+        (335, 333, -2)
+    );
+    assert_eq!(
+        (
+            diff.base_storage_fee,
+            diff.new_storage_fee,
+            diff.storage_fee_delta
+        ),
+        (820, 900, 80)
+    );
+
+    // Sorted by the absolute change of the total cost, then by name
+    let aptos_coin = aptos_coin().to_canonical_string();
+    let functions = diff
+        .functions
+        .iter()
+        .map(|function| {
+            (
+                function.name.clone(),
+                function.self_cost_delta,
+                function.total_delta,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(functions, vec![
+        ("0x1::signer::address_of".to_string(), 3, 3),
+        (format!("0x1::coin::transfer<{}>", aptos_coin), 0, -2),
+        (format!("0x1::coin::withdraw<{}>", aptos_coin), -5, -2),
+    ]);
+    assert_eq!(diff.functions[0].base.self_cost, 7);
+    assert_eq!(diff.functions[0].new.self_cost, 10);
+}
@@ -0,0 +1,224 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    export::{frame_name, function_name},
+    log::{CallFrame, ExecutionGasEvent, FrameTrace},
+};
+use aptos_types::transaction::ExecutionStatus;
+use move_binary_format::file_format_common::Opcodes;
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::IdentStr,
+    language_storage::{ModuleId, TypeTag, CORE_CODE_ADDRESS},
+    vm_status::AbortLocation,
+};
+use serde::{Deserialize, Serialize};
+
+/// Trace of the execution of a transaction, built from the call graph recorded by a
+/// [`crate::GasProfiler`] with value tracing enabled.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionTrace {
+    pub call_tree: TraceFrame,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub abort_location: Option<TraceAbortLocation>,
+}
+
+impl ExecutionTrace {
+    /// Builds the trace from the call graph of the transaction and the status it was executed
+    /// with.
+    pub fn new(call_graph: &CallFrame, status: &ExecutionStatus) -> Self {
+        let call_tree = TraceFrame::from_call_frame(call_graph);
+        let abort_location = match status {
+            ExecutionStatus::Success => None,
+            ExecutionStatus::MoveAbort { location, code, .. } => Some(TraceAbortLocation::new(
+                &call_tree,
+                Some(location),
+                Some(*code),
+            )),
+            ExecutionStatus::ExecutionFailure { location, .. } => {
+                Some(TraceAbortLocation::new(&call_tree, Some(location), None))
+            },
+            ExecutionStatus::OutOfGas | ExecutionStatus::MiscellaneousError(_) => {
+                Some(TraceAbortLocation::new(&call_tree, None, None))
+            },
+        };
+        Self {
+            call_tree,
+            abort_location,
+        }
+    }
+}
+
+/// A function call, or a native function call
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceFrame {
+    /// Fully qualified name of the function, e.g.
+    /// `0x1::coin::transfer<0x1::aptos_coin::AptosCoin>`, or `<script>`
+    pub function: String,
+    pub is_native: bool,
+    pub arguments: Vec<String>,
+    /// `false` if the function did not return, e.g. because it aborted or ran out of gas
+    pub returned: bool,
+    pub return_values: Vec<String>,
+    pub resource_reads: Vec<TraceResourceRead>,
+    pub resource_writes: Vec<TraceResourceWrite>,
+    pub events: Vec<TraceEvent>,
+    pub calls: Vec<TraceFrame>,
+}
+
+impl TraceFrame {
+    fn new(function: String, is_native: bool, trace: Option<&FrameTrace>) -> Self {
+        let trace = trace.cloned().unwrap_or_default();
+        Self {
+            function,
+            is_native,
+            arguments: trace.args,
+            returned: trace.returned,
+            return_values: trace.return_values,
+            resource_reads: vec![],
+            resource_writes: trace
+                .resource_writes
+                .iter()
+                .map(|write| TraceResourceWrite {
+                    operation: write_operation_name(write.op).to_string(),
+                    resource_type: write.ty.to_canonical_string(),
+                })
+                .collect(),
+            events: vec![],
+            calls: vec![],
+        }
+    }
+
+    fn from_call_frame(frame: &CallFrame) -> Self {
+        let mut trace_frame = Self::new(frame_name(&frame.name), false, frame.trace.as_ref());
+
+        for event in &frame.events {
+            match event {
+                ExecutionGasEvent::Call(callee) => {
+                    trace_frame.calls.push(Self::from_call_frame(callee));
+                },
+                ExecutionGasEvent::CallNative {
+                    module_id,
+                    fn_name,
+                    ty_args,
+                    trace,
+                    ..
+                } => {
+                    if let Some(event) =
+                        TraceEvent::from_native_call(module_id, fn_name, ty_args, trace.as_ref())
+                    {
+                        trace_frame.events.push(event);
+                    }
+                    trace_frame.calls.push(Self::new(
+                        function_name(module_id, fn_name, ty_args),
+                        true,
+                        trace.as_ref(),
+                    ));
+                },
+                ExecutionGasEvent::LoadResource { addr, ty, .. } => {
+                    trace_frame.resource_reads.push(TraceResourceRead {
+                        address: *addr,
+                        resource_type: ty.to_canonical_string(),
+                    });
+                },
+                ExecutionGasEvent::Loc(_)
+                | ExecutionGasEvent::Bytecode { .. }
+                | ExecutionGasEvent::CreateTy { .. } => (),
+            }
+        }
+
+        trace_frame
+    }
+}
+
+/// A resource loaded from global storage
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceResourceRead {
+    pub address: AccountAddress,
+    pub resource_type: String,
+}
+
+/// A resource modified in global storage. The address of the resource is not known by the gas
+/// meter.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceResourceWrite {
+    /// One of `borrow_global_mut`, `move_to` or `move_from`
+    pub operation: String,
+    pub resource_type: String,
+}
+
+/// An event emitted by a call
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEvent {
+    pub event_type: String,
+    pub data: String,
+}
+
+impl TraceEvent {
+    /// Events are emitted by calling one of the natives of `0x1::event`, with the event as
+    /// one of the arguments.
+    fn from_native_call(
+        module_id: &ModuleId,
+        fn_name: &IdentStr,
+        ty_args: &[TypeTag],
+        trace: Option<&FrameTrace>,
+    ) -> Option<Self> {
+        if module_id.address() != &CORE_CODE_ADDRESS || module_id.name().as_str() != "event" {
+            return None;
+        }
+        let data_idx = match fn_name.as_str() {
+            "write_module_event_to_store" => 0,
+            "write_to_event_store" => 2,
+            _ => return None,
+        };
+        Some(Self {
+            event_type: ty_args.first()?.to_canonical_string(),
+            data: trace?.args.get(data_idx)?.clone(),
+        })
+    }
+}
+
+/// Where a transaction stopped, if it did not succeed
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceAbortLocation {
+    /// Module that aborted, as reported by the VM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub abort_code: Option<u64>,
+    /// The functions that were executing when the transaction stopped, starting from the
+    /// entry function
+    pub call_stack: Vec<String>,
+}
+
+impl TraceAbortLocation {
+    fn new(call_tree: &TraceFrame, location: Option<&AbortLocation>, code: Option<u64>) -> Self {
+        // Functions that did not return are the ones on the call stack, and only the last call
+        // of a frame can be one of them.
+        let mut call_stack = vec![];
+        let mut frame = Some(call_tree);
+        while let Some(cur) = frame.filter(|frame| !frame.returned) {
+            call_stack.push(cur.function.clone());
+            frame = cur.calls.last();
+        }
+
+        let module = match location {
+            Some(AbortLocation::Module(module_id)) => Some(module_id.short_str_lossless()),
+            Some(AbortLocation::Script) | None => None,
+        };
+        Self {
+            module,
+            abort_code: code,
+            call_stack,
+        }
+    }
+}
+
+fn write_operation_name(op: Opcodes) -> &'static str {
+    match op {
+        Opcodes::MOVE_TO | Opcodes::MOVE_TO_GENERIC => "move_to",
+        Opcodes::MOVE_FROM | Opcodes::MOVE_FROM_GENERIC => "move_from",
+        _ => "borrow_global_mut",
+    }
+}
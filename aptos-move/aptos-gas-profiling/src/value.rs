@@ -0,0 +1,217 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_core_types::{
+    account_address::AccountAddress,
+    u256::U256,
+    value::{MoveStruct, MoveValue},
+};
+use move_vm_types::{
+    delayed_values::delayed_field_id::DelayedFieldID,
+    views::{ValueView, ValueVisitor},
+};
+use std::fmt::{Display, Write};
+
+/// Maximum length of a rendered value. Longer values are truncated.
+const MAX_RENDERED_VALUE_LENGTH: usize = 1024;
+
+/// Renders a Move value in a compact, human readable format, e.g. `{@0x1, [1, 2], true}`.
+///
+/// Struct fields are rendered in declaration order (field names are not available at runtime),
+/// `vector<u8>` values are rendered as hex strings and references are prefixed with `&`.
+pub(crate) fn render_value(val: impl ValueView) -> String {
+    let mut renderer = ValueRenderer::new();
+    val.visit(&mut renderer);
+    renderer.finish()
+}
+
+/// Renders a decoded Move value in the same format as the values recorded by the profiler, e.g.
+/// the arguments of an entry function.
+///
+/// Non-empty vectors of `u8` are rendered as hex strings, and signers as their address.
+pub fn render_move_value(val: &MoveValue) -> String {
+    let mut renderer = ValueRenderer::new();
+    visit_move_value(&mut renderer, val);
+    renderer.finish()
+}
+
+fn visit_move_value(renderer: &mut ValueRenderer, val: &MoveValue) {
+    match val {
+        MoveValue::U8(val) => renderer.visit_u8(0, *val),
+        MoveValue::U16(val) => renderer.visit_u16(0, *val),
+        MoveValue::U32(val) => renderer.visit_u32(0, *val),
+        MoveValue::U64(val) => renderer.visit_u64(0, *val),
+        MoveValue::U128(val) => renderer.visit_u128(0, *val),
+        MoveValue::U256(val) => renderer.visit_u256(0, *val),
+        MoveValue::Bool(val) => renderer.visit_bool(0, *val),
+        MoveValue::Address(val) | MoveValue::Signer(val) => renderer.visit_address(0, *val),
+        MoveValue::Vector(vals) => {
+            let bytes = vals
+                .iter()
+                .map(|val| match val {
+                    MoveValue::U8(byte) => Some(*byte),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            match bytes {
+                Some(bytes) if !bytes.is_empty() => renderer.visit_vec_u8(0, &bytes),
+                _ => {
+                    renderer.visit_vec(0, vals.len());
+                    for val in vals {
+                        visit_move_value(renderer, val);
+                    }
+                },
+            }
+        },
+        MoveValue::Struct(val) => {
+            let fields = match val {
+                MoveStruct::Runtime(fields) => fields.iter().collect::<Vec<_>>(),
+                MoveStruct::WithFields(fields) | MoveStruct::WithTypes { fields, .. } => {
+                    fields.iter().map(|(_, field)| field).collect()
+                },
+            };
+            renderer.visit_struct(0, fields.len());
+            for field in fields {
+                visit_move_value(renderer, field);
+            }
+        },
+    }
+}
+
+struct ValueRenderer {
+    output: String,
+    /// Closing delimiter, number of children and number of children visited so far of the
+    /// containers currently being rendered.
+    containers: Vec<(&'static str, usize, usize)>,
+}
+
+impl ValueRenderer {
+    fn new() -> Self {
+        Self {
+            output: String::new(),
+            containers: vec![],
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut output = self.output;
+        if output.len() > MAX_RENDERED_VALUE_LENGTH {
+            let mut end = MAX_RENDERED_VALUE_LENGTH;
+            while !output.is_char_boundary(end) {
+                end -= 1;
+            }
+            output.truncate(end);
+            output.push_str("..");
+        }
+        output
+    }
+
+    fn is_full(&self) -> bool {
+        self.output.len() > MAX_RENDERED_VALUE_LENGTH
+    }
+
+    fn write(&mut self, s: impl Display) {
+        if !self.is_full() {
+            let _ = write!(self.output, "{}", s);
+        }
+    }
+
+    fn begin_value(&mut self) {
+        let is_first_child = match self.containers.last_mut() {
+            Some((_, _, visited)) => {
+                *visited += 1;
+                *visited == 1
+            },
+            None => true,
+        };
+        if !is_first_child {
+            self.write(", ");
+        }
+    }
+
+    fn end_value(&mut self) {
+        while let Some((close, len, visited)) = self.containers.last().copied() {
+            if visited < len {
+                break;
+            }
+            self.write(close);
+            self.containers.pop();
+        }
+    }
+
+    fn leaf(&mut self, s: impl Display) {
+        self.begin_value();
+        self.write(s);
+        self.end_value();
+    }
+
+    fn container(&mut self, open: &'static str, close: &'static str, len: usize) -> bool {
+        self.begin_value();
+        self.write(open);
+        self.containers.push((close, len, 0));
+        self.end_value();
+        true
+    }
+}
+
+impl ValueVisitor for ValueRenderer {
+    fn visit_delayed(&mut self, _depth: usize, _id: DelayedFieldID) {
+        self.leaf("<delayed>");
+    }
+
+    fn visit_u8(&mut self, _depth: usize, val: u8) {
+        self.leaf(val);
+    }
+
+    fn visit_u16(&mut self, _depth: usize, val: u16) {
+        self.leaf(val);
+    }
+
+    fn visit_u32(&mut self, _depth: usize, val: u32) {
+        self.leaf(val);
+    }
+
+    fn visit_u64(&mut self, _depth: usize, val: u64) {
+        self.leaf(val);
+    }
+
+    fn visit_u128(&mut self, _depth: usize, val: u128) {
+        self.leaf(val);
+    }
+
+    fn visit_u256(&mut self, _depth: usize, val: U256) {
+        self.leaf(val);
+    }
+
+    fn visit_bool(&mut self, _depth: usize, val: bool) {
+        self.leaf(val);
+    }
+
+    fn visit_address(&mut self, _depth: usize, val: AccountAddress) {
+        self.leaf(format_args!("@{}", val.to_hex_literal()));
+    }
+
+    fn visit_struct(&mut self, _depth: usize, len: usize) -> bool {
+        self.container("{", "}", len)
+    }
+
+    fn visit_vec(&mut self, _depth: usize, len: usize) -> bool {
+        self.container("[", "]", len)
+    }
+
+    fn visit_ref(&mut self, _depth: usize, _is_global: bool) -> bool {
+        self.container("&", "", 1)
+    }
+
+    fn visit_vec_u8(&mut self, _depth: usize, vals: &[u8]) {
+        self.begin_value();
+        self.write("0x");
+        for val in vals {
+            if self.is_full() {
+                break;
+            }
+            self.write(format_args!("{:02x}", val));
+        }
+        self.end_value();
+    }
+}
@@ -450,6 +450,7 @@ where
     fn charge_drop_frame(
         &mut self,
         locals: impl Iterator<Item = impl ValueView> + Clone,
+        ret_vals: impl ExactSizeIterator<Item = impl ValueView> + Clone,
     ) -> PartialVMResult<()> {
         self.release_heap_memory(locals.clone().fold(AbstractValueSize::zero(), |acc, val| {
             acc + self
@@ -459,7 +460,7 @@ where
                 .abstract_heap_size(val, self.feature_version())
         }));
 
-        self.base.charge_drop_frame(locals)
+        self.base.charge_drop_frame(locals, ret_vals)
     }
 }
 
@@ -15,7 +15,7 @@ use move_binary_format::CompiledModule;
 use move_core_types::{
     identifier::{IdentStr, Identifier},
     language_storage::{ModuleId, StructTag, TypeTag},
-    value::{MoveTypeLayout, MoveValue},
+    value::{MoveStruct, MoveTypeLayout, MoveValue},
 };
 use move_resource_viewer::MoveValueAnnotator;
 pub use move_resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue};
@@ -80,6 +80,21 @@ impl<'a, S: StateView> AptosValueAnnotator<'a, S> {
             .view_function_arguments(module, function, ty_args, args)
     }
 
+    /// Same as [`Self::view_function_arguments`], without the types of the values.
+    pub fn view_function_argument_values(
+        &self,
+        module: &ModuleId,
+        function: &IdentStr,
+        ty_args: &[TypeTag],
+        args: &[Vec<u8>],
+    ) -> anyhow::Result<Vec<MoveValue>> {
+        Ok(self
+            .view_function_arguments(module, function, ty_args, args)?
+            .into_iter()
+            .map(into_move_value)
+            .collect())
+    }
+
     pub fn view_fully_decorated_ty_layout(
         &self,
         type_tag: &TypeTag,
@@ -87,3 +102,26 @@ impl<'a, S: StateView> AptosValueAnnotator<'a, S> {
         self.0.get_type_layout_with_types(type_tag)
     }
 }
+
+fn into_move_value(val: AnnotatedMoveValue) -> MoveValue {
+    match val {
+        AnnotatedMoveValue::U8(v) => MoveValue::U8(v),
+        AnnotatedMoveValue::U16(v) => MoveValue::U16(v),
+        AnnotatedMoveValue::U32(v) => MoveValue::U32(v),
+        AnnotatedMoveValue::U64(v) => MoveValue::U64(v),
+        AnnotatedMoveValue::U128(v) => MoveValue::U128(v),
+        AnnotatedMoveValue::U256(v) => MoveValue::U256(v),
+        AnnotatedMoveValue::Bool(v) => MoveValue::Bool(v),
+        AnnotatedMoveValue::Address(v) => MoveValue::Address(v),
+        AnnotatedMoveValue::Vector(_, vals) => {
+            MoveValue::Vector(vals.into_iter().map(into_move_value).collect())
+        },
+        AnnotatedMoveValue::Bytes(v) => MoveValue::vector_u8(v),
+        AnnotatedMoveValue::Struct(v) => MoveValue::Struct(MoveStruct::WithFields(
+            v.value
+                .into_iter()
+                .map(|(name, val)| (name, into_move_value(val)))
+                .collect(),
+        )),
+    }
+}
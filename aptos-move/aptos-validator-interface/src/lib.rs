@@ -10,11 +10,13 @@ use anyhow::Result;
 use aptos_framework::natives::code::PackageMetadata;
 use aptos_types::{
     account_address::AccountAddress,
+    proof::SparseMerkleProof,
     state_store::{
         state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
         Result as StateViewResult, StateViewId, TStateView,
     },
     transaction::{Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
 };
 use lru::LruCache;
 use move_core_types::language_storage::ModuleId;
@@ -42,12 +44,23 @@ pub trait AptosValidatorInterface: Sync {
         version: Version,
     ) -> Result<Option<StateValue>>;
 
+    /// Returns the state value along with its proof against the root hash of the state at
+    /// the version. Only available when the state is read from a local DB.
+    async fn get_state_value_with_proof_by_version(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<(Option<StateValue>, SparseMerkleProof)>;
+
     async fn get_committed_transactions(
         &self,
         start: Version,
         limit: u64,
     ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)>;
 
+    /// Returns the write sets recorded for the committed transactions in the range
+    async fn get_committed_write_sets(&self, start: Version, limit: u64) -> Result<Vec<WriteSet>>;
+
     async fn get_and_filter_committed_transactions(
         &self,
         start: Version,
@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{AptosValidatorInterface, FilterCondition};
-use anyhow::{anyhow, Result};
-use aptos_api_types::{AptosError, AptosErrorCode};
+use anyhow::{anyhow, bail, Result};
+use aptos_api_types::{AptosError, AptosErrorCode, TransactionOnChainData};
 use aptos_framework::{
     natives::code::{PackageMetadata, PackageRegistry},
     APTOS_PACKAGES,
@@ -14,11 +14,13 @@ use aptos_rest_client::{
 };
 use aptos_types::{
     account_address::AccountAddress,
+    proof::SparseMerkleProof,
     state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{
         EntryFunction, ExecutionStatus::MiscellaneousError, Transaction, TransactionInfo,
         TransactionPayload, Version,
     },
+    write_set::WriteSet,
 };
 use async_recursion::async_recursion;
 use move_core_types::language_storage::ModuleId;
@@ -30,6 +32,38 @@ impl RestDebuggerInterface {
     pub fn new(client: Client) -> Self {
         Self(client)
     }
+
+    /// Fetches the committed transactions in the range page by page, as a single request
+    /// returns at most `u16::MAX` transactions (and usually fewer, due to the page size
+    /// limit of the node).
+    async fn get_transactions_in_range(
+        &self,
+        start: Version,
+        limit: u64,
+    ) -> Result<Vec<TransactionOnChainData>> {
+        let end = start
+            .checked_add(limit)
+            .ok_or_else(|| anyhow!("Version range overflows: {} + {}", start, limit))?;
+
+        let mut txns = vec![];
+        let mut cursor = start;
+        while cursor < end {
+            let page_size = (end - cursor).min(u16::MAX as u64) as u16;
+            let page = self
+                .0
+                .get_transactions_bcs(Some(cursor), Some(page_size))
+                .await?
+                .into_inner();
+            if page.is_empty() {
+                bail!("No committed transactions returned at version {}", cursor);
+            }
+            cursor += page.len() as u64;
+            txns.extend(page);
+            println!("Got {}/{} txns from RestApi.", txns.len(), limit);
+        }
+
+        Ok(txns)
+    }
 }
 
 #[async_recursion]
@@ -218,31 +252,34 @@ impl AptosValidatorInterface for RestDebuggerInterface {
         }
     }
 
+    async fn get_state_value_with_proof_by_version(
+        &self,
+        _state_key: &StateKey,
+        _version: Version,
+    ) -> Result<(Option<StateValue>, SparseMerkleProof)> {
+        Err(anyhow!("State proofs are not served by the REST API"))
+    }
+
     async fn get_committed_transactions(
         &self,
         start: Version,
         limit: u64,
     ) -> Result<(Vec<Transaction>, Vec<TransactionInfo>)> {
-        let mut txns = Vec::with_capacity(limit as usize);
-        let mut txn_infos = Vec::with_capacity(limit as usize);
-
-        while txns.len() < limit as usize {
-            self.0
-                .get_transactions_bcs(
-                    Some(start + txns.len() as u64),
-                    Some(limit as u16 - txns.len() as u16),
-                )
-                .await?
-                .into_inner()
-                .into_iter()
-                .for_each(|txn| {
-                    txns.push(txn.transaction);
-                    txn_infos.push(txn.info);
-                });
-            println!("Got {}/{} txns from RestApi.", txns.len(), limit);
-        }
+        Ok(self
+            .get_transactions_in_range(start, limit)
+            .await?
+            .into_iter()
+            .map(|txn| (txn.transaction, txn.info))
+            .unzip())
+    }
 
-        Ok((txns, txn_infos))
+    async fn get_committed_write_sets(&self, start: Version, limit: u64) -> Result<Vec<WriteSet>> {
+        Ok(self
+            .get_transactions_in_range(start, limit)
+            .await?
+            .into_iter()
+            .map(|txn| txn.changes)
+            .collect())
     }
 
     async fn get_and_filter_committed_transactions(
@@ -13,8 +13,10 @@ use aptos_framework::natives::code::PackageMetadata;
 use aptos_storage_interface::DbReader;
 use aptos_types::{
     account_address::AccountAddress,
+    proof::SparseMerkleProof,
     state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
 };
 use move_core_types::language_storage::ModuleId;
 use std::{collections::HashMap, path::Path, sync::Arc};
@@ -50,6 +52,16 @@ impl AptosValidatorInterface for DBDebuggerInterface {
             .map_err(Into::into)
     }
 
+    async fn get_state_value_with_proof_by_version(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<(Option<StateValue>, SparseMerkleProof)> {
+        self.0
+            .get_state_value_with_proof_by_version(state_key, version)
+            .map_err(Into::into)
+    }
+
     async fn get_committed_transactions(
         &self,
         start: Version,
@@ -67,6 +79,13 @@ impl AptosValidatorInterface for DBDebuggerInterface {
         Ok((txns, txn_infos))
     }
 
+    async fn get_committed_write_sets(&self, start: Version, limit: u64) -> Result<Vec<WriteSet>> {
+        self.0
+            .get_write_set_iterator(start, limit)?
+            .map(|res| res.map_err(Into::into))
+            .collect()
+    }
+
     async fn get_and_filter_committed_transactions(
         &self,
         _start: Version,
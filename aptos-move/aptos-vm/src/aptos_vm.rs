@@ -105,6 +105,7 @@ use std::{
     cmp::{max, min},
     collections::{BTreeMap, BTreeSet},
     marker::Sync,
+    path::PathBuf,
     sync::Arc,
 };
 
@@ -113,6 +114,7 @@ static NUM_EXECUTION_SHARD: OnceCell<usize> = OnceCell::new();
 static NUM_PROOF_READING_THREADS: OnceCell<usize> = OnceCell::new();
 static DISCARD_FAILED_BLOCKS: OnceCell<bool> = OnceCell::new();
 static PROCESSED_TRANSACTIONS_DETAILED_COUNTERS: OnceCell<bool> = OnceCell::new();
+static CONFLICT_REPORT_DIR: OnceCell<PathBuf> = OnceCell::new();
 
 macro_rules! deprecated_module_bundle {
     () => {
@@ -223,13 +225,38 @@ impl AptosVM {
     // TODO: Passing `state_view` is not needed once we move keyless and gas-related
     //       configs to the environment.
     pub(crate) fn new_with_environment(env: Arc<Environment>, state_view: &impl StateView) -> Self {
+        Self::new_impl(env, state_view, true)
+    }
+
+    /// Creates a new VM instance whose code cache is not shared with any other VM instance.
+    /// Must be used when the state view may contain code which is not on-chain (e.g., state
+    /// overrides when simulating), so that this code never leaks into other VM instances.
+    pub fn new_with_isolated_code_cache(state_view: &impl StateView) -> Self {
+        let env = Arc::new(Environment::new(state_view));
+        Self::new_impl(env, state_view, false)
+    }
+
+    fn new_impl(
+        env: Arc<Environment>,
+        state_view: &impl StateView,
+        use_warm_vm_cache: bool,
+    ) -> Self {
         let _timer = TIMER.timer_with(&["AptosVM::new"]);
 
         let (gas_params, storage_gas_params, gas_feature_version) =
             get_gas_parameters(env.features(), state_view);
 
         let resolver = state_view.as_move_resolver();
-        let move_vm = MoveVmExt::new(gas_feature_version, gas_params.as_ref(), env, &resolver);
+        let move_vm = if use_warm_vm_cache {
+            MoveVmExt::new(gas_feature_version, gas_params.as_ref(), env, &resolver)
+        } else {
+            MoveVmExt::new_with_isolated_code_cache(
+                gas_feature_version,
+                gas_params.as_ref(),
+                env,
+                &resolver,
+            )
+        };
 
         // We use an `Option` to handle the VK not being set on-chain, or an incorrect VK being set
         // via governance (although, currently, we do check for that in `keyless_account.move`).
@@ -353,6 +380,19 @@ impl AptosVM {
         }
     }
 
+    /// Sets the directory where conflict reports of blocks executed in parallel are written,
+    /// when invoked the first time.
+    pub fn set_conflict_report_dir_once(dir: PathBuf) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        CONFLICT_REPORT_DIR.set(dir).ok();
+    }
+
+    /// Get the directory where conflict reports are written if set, otherwise return None
+    /// (conflict reports are disabled).
+    pub fn get_conflict_report_dir() -> Option<PathBuf> {
+        CONFLICT_REPORT_DIR.get().cloned()
+    }
+
     /// Returns the internal gas schedule if it has been loaded, or an error if it hasn't.
     #[cfg(any(test, feature = "testing"))]
     pub fn gas_params(&self) -> Result<&AptosGasParameters, VMStatus> {
@@ -2133,17 +2173,37 @@ impl AptosVM {
         max_gas_amount: u64,
     ) -> ViewFunctionOutput {
         let vm = AptosVM::new(state_view);
+        vm.execute_view(
+            state_view,
+            module_id,
+            func_name,
+            type_args,
+            arguments,
+            max_gas_amount,
+        )
+    }
 
+    /// Executes a view function on this VM instance. The state view must be the same one
+    /// (or have the same configs as) the one the VM was created with.
+    pub fn execute_view(
+        &self,
+        state_view: &impl StateView,
+        module_id: ModuleId,
+        func_name: Identifier,
+        type_args: Vec<TypeTag>,
+        arguments: Vec<Vec<u8>>,
+        max_gas_amount: u64,
+    ) -> ViewFunctionOutput {
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
 
-        let vm_gas_params = match get_or_vm_startup_failure(&vm.gas_params, &log_context) {
+        let vm_gas_params = match get_or_vm_startup_failure(&self.gas_params, &log_context) {
             Ok(gas_params) => gas_params.vm.clone(),
             Err(err) => {
                 return ViewFunctionOutput::new(Err(anyhow::Error::msg(format!("{}", err))), 0)
             },
         };
         let storage_gas_params =
-            match get_or_vm_startup_failure(&vm.storage_gas_params, &log_context) {
+            match get_or_vm_startup_failure(&self.storage_gas_params, &log_context) {
                 Ok(gas_params) => gas_params.clone(),
                 Err(err) => {
                     return ViewFunctionOutput::new(Err(anyhow::Error::msg(format!("{}", err))), 0)
@@ -2151,7 +2211,7 @@ impl AptosVM {
             };
 
         let mut gas_meter = make_prod_gas_meter(
-            vm.gas_feature_version,
+            self.gas_feature_version,
             vm_gas_params,
             storage_gas_params,
             /* is_approved_gov_script */ false,
@@ -2159,10 +2219,10 @@ impl AptosVM {
         );
 
         let resolver = state_view.as_move_resolver();
-        let mut session = vm.new_session(&resolver, SessionId::Void, None);
+        let mut session = self.new_session(&resolver, SessionId::Void, None);
         let execution_result = Self::execute_view_function_in_vm(
             &mut session,
-            &vm,
+            self,
             module_id,
             func_name,
             type_args,
@@ -2466,6 +2526,7 @@ impl VMExecutor for AptosVM {
                     concurrency_level: Self::get_concurrency_level(),
                     allow_fallback: true,
                     discard_failed_blocks: Self::get_discard_failed_blocks(),
+                    conflict_report_dir: Self::get_conflict_report_dir(),
                 },
                 onchain: onchain_config,
             },
@@ -2614,21 +2675,72 @@ impl AptosSimulationVM {
         transaction: &SignedTransaction,
         state_view: &impl StateView,
     ) -> (VMStatus, TransactionOutput) {
+        Self::new(state_view).simulate_signed_transaction(transaction, state_view)
+    }
+
+    /// Creates a simulation VM whose code cache is not shared with any other VM instance. See
+    /// [`AptosVM::new_with_isolated_code_cache`].
+    pub fn new_with_isolated_code_cache(state_view: &impl StateView) -> Self {
+        let mut vm = AptosVM::new_with_isolated_code_cache(state_view);
+        vm.is_simulation = true;
+        Self(vm)
+    }
+
+    /// Simulates a signed transaction (i.e., executes it without performing
+    /// signature verification) on this VM instance.
+    /// *Precondition:* the transaction must **not** have a valid signature.
+    pub fn simulate_signed_transaction(
+        &self,
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutput) {
+        let (vm_status, txn_output, _gas_meter) = self
+            .simulate_signed_transaction_with_modified_gas_meter(
+                transaction,
+                state_view,
+                |gas_meter| gas_meter,
+            );
+        (vm_status, txn_output)
+    }
+
+    /// Same as [`Self::simulate_signed_transaction`], but allows the production gas meter to
+    /// be wrapped, e.g. to profile or trace the execution of the transaction. The gas meter is
+    /// returned unless the transaction failed before it could be executed.
+    pub fn simulate_signed_transaction_with_modified_gas_meter<G, F>(
+        &self,
+        transaction: &SignedTransaction,
+        state_view: &impl StateView,
+        modify_gas_meter: F,
+    ) -> (VMStatus, TransactionOutput, Option<G>)
+    where
+        F: FnOnce(ProdGasMeter) -> G,
+        G: AptosGasMeter,
+    {
         assert_err!(
             transaction.verify_signature(),
             "Simulated transaction should not have a valid signature"
         );
 
-        let vm = Self::new(state_view);
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
 
         let resolver = state_view.as_move_resolver();
-        let (vm_status, vm_output) =
-            vm.0.execute_user_transaction(&resolver, transaction, &log_context);
+        let (vm_status, vm_output, gas_meter) =
+            match self.0.execute_user_transaction_with_modified_gas_meter(
+                &resolver,
+                transaction,
+                &log_context,
+                modify_gas_meter,
+            ) {
+                Ok((vm_status, vm_output, gas_meter)) => (vm_status, vm_output, Some(gas_meter)),
+                Err(vm_status) => {
+                    let vm_output = discarded_output(vm_status.status_code());
+                    (vm_status, vm_output, None)
+                },
+            };
         let txn_output = vm_output
             .try_materialize_into_transaction_output(&resolver)
             .expect("Materializing aggregator V1 deltas should never fail");
-        (vm_status, txn_output)
+        (vm_status, txn_output, gas_meter)
     }
 }
 
@@ -25,7 +25,7 @@ use aptos_types::{
     fee_statement::FeeStatement,
     state_store::{state_key::StateKey, state_value::StateValueMetadata, StateView, StateViewId},
     transaction::{
-        signature_verified_transaction::SignatureVerifiedTransaction, BlockOutput,
+        signature_verified_transaction::SignatureVerifiedTransaction, BlockEndInfo, BlockOutput,
         TransactionOutput, TransactionStatus,
     },
     write_set::WriteOp,
@@ -435,10 +435,17 @@ impl BlockAptosVM {
 
         let environment =
             Arc::new(Environment::new(state_view).try_enable_delayed_field_optimization());
+        let block_end_info_v1_enabled = environment.features().is_block_end_info_v1_enabled();
         let ret = executor.execute_block(environment, signature_verified_block, state_view);
         match ret {
             Ok(block_output) => {
                 let (transaction_outputs, block_end_info) = block_output.into_inner();
+                // The breakdown of the block limits is only persisted once enabled on-chain.
+                let block_end_info = if block_end_info_v1_enabled {
+                    block_end_info
+                } else {
+                    block_end_info.map(BlockEndInfo::into_v0)
+                };
                 let output_vec: Vec<_> = transaction_outputs
                     .into_iter()
                     .map(|output| output.take_output())
@@ -0,0 +1,461 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recording of the state values a transaction reads during execution into a self-contained
+//! bundle, from which the transaction can be re-executed without access to the DB.
+
+use crate::{data_cache::AsMoveResolver, AptosVM};
+use anyhow::{ensure, format_err, Result};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_infallible::Mutex;
+use aptos_types::{
+    proof::SparseMerkleProof,
+    state_store::{
+        errors::StateviewError, state_key::StateKey, state_storage_usage::StateStorageUsage,
+        state_value::StateValue, StateView, StateViewId, TStateView,
+    },
+    transaction::{Transaction, TransactionInfo, TransactionOutput, Version},
+    write_set::{TransactionWrite, WriteSet},
+};
+use aptos_vm_logging::log_schema::AdapterLogSchema;
+use move_core_types::vm_status::VMStatus;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// Executes the transaction on top of the given state view, with a VM whose code cache is not
+/// shared with any other VM instance. Otherwise, modules loaded by other VM instances of the
+/// process (e.g., the warm VM cache) would be served without being read through the state view.
+pub fn execute_with_isolated_code_cache(
+    state_view: &impl StateView,
+    txn: Transaction,
+) -> Result<TransactionOutput, VMStatus> {
+    let vm = AptosVM::new_with_isolated_code_cache(state_view);
+    let resolver = state_view.as_move_resolver();
+    let log_context = AdapterLogSchema::new(state_view.id(), 0);
+    let (_, vm_output) = vm.execute_single_transaction(&txn.into(), &resolver, &log_context)?;
+    vm_output.try_materialize_into_transaction_output(&resolver)
+}
+
+/// Executes the transaction on top of the given state view (see
+/// [`execute_with_isolated_code_cache`]), and bundles every state value it read. The transaction
+/// is the one committed at `version`, i.e., the state view is the state at `version - 1`.
+pub fn record_execution_trace(
+    state_view: &impl StateView,
+    version: Version,
+    txn: Transaction,
+) -> Result<(TransactionOutput, ExecutionTraceBundle), VMStatus> {
+    let recorder = ReadSetRecorder::new(state_view);
+    let output = execute_with_isolated_code_cache(&recorder, txn.clone())?;
+    Ok((output, recorder.into_bundle(version, txn)))
+}
+
+/// State view recording every state value read through it, as of the first read, as well as
+/// the storage usage if the VM queried it.
+pub struct ReadSetRecorder<'s, S> {
+    base_view: &'s S,
+    reads: Mutex<BTreeMap<StateKey, Option<StateValue>>>,
+    usage: Mutex<Option<StateStorageUsage>>,
+}
+
+impl<'s, S: TStateView<Key = StateKey>> ReadSetRecorder<'s, S> {
+    pub fn new(base_view: &'s S) -> Self {
+        Self {
+            base_view,
+            reads: Mutex::new(BTreeMap::new()),
+            usage: Mutex::new(None),
+        }
+    }
+
+    /// Bundles the recorded reads with the transaction they were recorded for, which executed
+    /// on top of the state at `version - 1`.
+    pub fn into_bundle(self, version: Version, txn: Transaction) -> ExecutionTraceBundle {
+        ExecutionTraceBundle {
+            version,
+            txn,
+            usage: self.usage.into_inner(),
+            reads: self
+                .reads
+                .into_inner()
+                .into_iter()
+                .map(|(state_key, value)| RecordedRead {
+                    state_key,
+                    value,
+                    proof: None,
+                })
+                .collect(),
+            checkpoint_version: None,
+            state_root_hash: None,
+            checkpoint_txn_info: None,
+            txn_infos_since_checkpoint: vec![],
+            write_sets_since_checkpoint: vec![],
+            expected_txn_info: None,
+            expected_write_set: None,
+        }
+    }
+}
+
+impl<'s, S: TStateView<Key = StateKey>> TStateView for ReadSetRecorder<'s, S> {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.base_view.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>, StateviewError> {
+        let value = self.base_view.get_state_value(state_key)?;
+        self.reads
+            .lock()
+            .entry(state_key.clone())
+            .or_insert_with(|| value.clone());
+        Ok(value)
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage, StateviewError> {
+        let usage = self.base_view.get_usage()?;
+        self.usage.lock().get_or_insert(usage);
+        Ok(usage)
+    }
+}
+
+/// A state value read by the transaction. `None` if the key did not exist.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedRead {
+    pub state_key: StateKey,
+    pub value: Option<StateValue>,
+    /// Proof of the value against [`ExecutionTraceBundle::state_root_hash`]. Not present if
+    /// the key was written since the checkpoint, as the value is then checked against the
+    /// write sets instead.
+    pub proof: Option<SparseMerkleProof>,
+}
+
+/// Everything needed to re-execute a transaction: the transaction and the state values it
+/// read, optionally proven against the root hash of the state it executed on.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExecutionTraceBundle {
+    pub version: Version,
+    pub txn: Transaction,
+    pub usage: Option<StateStorageUsage>,
+    pub reads: Vec<RecordedRead>,
+    /// Version and root hash of the last state checkpoint at or before `version - 1`, if the
+    /// reads are proven.
+    pub checkpoint_version: Option<Version>,
+    pub state_root_hash: Option<HashValue>,
+    /// Transaction info of the checkpoint, which the state root hash is checked against.
+    pub checkpoint_txn_info: Option<TransactionInfo>,
+    /// Transaction infos of the versions after the checkpoint (up to `version - 1`, included),
+    /// which the write sets since the checkpoint are checked against.
+    pub txn_infos_since_checkpoint: Vec<TransactionInfo>,
+    /// Write sets committed after the checkpoint (up to `version - 1`, included), on top of
+    /// which the transaction executed.
+    pub write_sets_since_checkpoint: Vec<WriteSet>,
+    /// What the transaction committed with, to compare the re-execution against.
+    pub expected_txn_info: Option<TransactionInfo>,
+    pub expected_write_set: Option<WriteSet>,
+}
+
+impl ExecutionTraceBundle {
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(bcs::from_bytes(&fs::read(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, bcs::to_bytes(self)?)?;
+        Ok(())
+    }
+
+    /// Proves the reads against the state checkpoint at `checkpoint_version`, given the write
+    /// sets committed after it and the transaction infos authenticating them. Keys written
+    /// since the checkpoint are checked against the write sets, the other ones are proven (with
+    /// `get_proof`) against the state root hash of the checkpoint.
+    pub fn attach_proofs(
+        &mut self,
+        checkpoint_version: Version,
+        checkpoint_txn_info: TransactionInfo,
+        txn_infos_since_checkpoint: Vec<TransactionInfo>,
+        write_sets_since_checkpoint: Vec<WriteSet>,
+        mut get_proof: impl FnMut(&StateKey) -> Result<SparseMerkleProof>,
+    ) -> Result<()> {
+        let state_root_hash = checkpoint_txn_info.state_checkpoint_hash().ok_or_else(|| {
+            format_err!("Version {} is not a state checkpoint", checkpoint_version)
+        })?;
+        self.checkpoint_version = Some(checkpoint_version);
+        self.state_root_hash = Some(state_root_hash);
+        self.checkpoint_txn_info = Some(checkpoint_txn_info);
+        self.txn_infos_since_checkpoint = txn_infos_since_checkpoint;
+        self.write_sets_since_checkpoint = write_sets_since_checkpoint;
+        self.verify_checkpoint(state_root_hash)?;
+
+        let written_values = latest_written_values(&self.write_sets_since_checkpoint);
+        for read in &mut self.reads {
+            read.proof = if written_values.contains_key(&read.state_key) {
+                None
+            } else {
+                Some(get_proof(&read.state_key)?)
+            };
+        }
+        self.verify_proofs()
+    }
+
+    /// Checks the proofs of the reads, if the bundle has any. Either all reads are proven (or
+    /// checked against the write sets since the checkpoint) or none of them is.
+    pub fn verify_proofs(&self) -> Result<()> {
+        let state_root_hash = match self.state_root_hash {
+            Some(state_root_hash) => state_root_hash,
+            None => {
+                ensure!(
+                    self.reads.iter().all(|read| read.proof.is_none()),
+                    "Bundle has proofs but no state root hash"
+                );
+                return Ok(());
+            },
+        };
+        self.verify_checkpoint(state_root_hash)?;
+        let written_values = latest_written_values(&self.write_sets_since_checkpoint);
+        for read in &self.reads {
+            if let Some(written_value) = written_values.get(&read.state_key) {
+                ensure!(
+                    written_value == &read.value,
+                    "Value of {:?} does not match the write sets since the checkpoint",
+                    read.state_key
+                );
+                continue;
+            }
+            read.proof
+                .as_ref()
+                .ok_or_else(|| format_err!("Missing proof of {:?}", read.state_key))?
+                .verify(state_root_hash, read.state_key.hash(), read.value.as_ref())
+                .map_err(|err| format_err!("Invalid proof of {:?}: {}", read.state_key, err))?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the state root hash is the one of the checkpoint, and that the write sets
+    /// since the checkpoint cover all the versions up to `version - 1` and are the ones their
+    /// transaction infos committed to.
+    fn verify_checkpoint(&self, state_root_hash: HashValue) -> Result<()> {
+        let checkpoint_version = self
+            .checkpoint_version
+            .ok_or_else(|| format_err!("Bundle has a state root hash but no checkpoint version"))?;
+        let checkpoint_txn_info = self.checkpoint_txn_info.as_ref().ok_or_else(|| {
+            format_err!("Bundle has a state root hash but no checkpoint transaction info")
+        })?;
+        ensure!(
+            checkpoint_txn_info.state_checkpoint_hash() == Some(state_root_hash),
+            "State root hash {} does not match the checkpoint at version {}: {:?}",
+            state_root_hash,
+            checkpoint_version,
+            checkpoint_txn_info.state_checkpoint_hash()
+        );
+
+        ensure!(
+            checkpoint_version + self.write_sets_since_checkpoint.len() as Version + 1
+                == self.version,
+            "Write sets must cover versions {} to {}",
+            checkpoint_version + 1,
+            self.version.saturating_sub(1)
+        );
+        ensure!(
+            self.txn_infos_since_checkpoint.len() == self.write_sets_since_checkpoint.len(),
+            "Expected {} transaction infos since the checkpoint, got {}",
+            self.write_sets_since_checkpoint.len(),
+            self.txn_infos_since_checkpoint.len()
+        );
+        for (idx, (write_set, txn_info)) in self
+            .write_sets_since_checkpoint
+            .iter()
+            .zip(&self.txn_infos_since_checkpoint)
+            .enumerate()
+        {
+            ensure!(
+                CryptoHash::hash(write_set) == txn_info.state_change_hash(),
+                "Write set at version {} does not match its transaction info",
+                checkpoint_version + 1 + idx as Version
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks the proofs of the reads, and re-executes the transaction against them only (see
+    /// [`execute_with_isolated_code_cache`]).
+    pub fn replay(&self) -> Result<TransactionOutput> {
+        self.verify_proofs()?;
+        execute_with_isolated_code_cache(&self.state_view(), self.txn.clone())
+            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))
+    }
+
+    pub fn is_proven(&self) -> bool {
+        self.state_root_hash.is_some()
+    }
+
+    /// State view serving the recorded reads only. Reading any other key is an error, as it
+    /// means the re-execution diverged from the recorded one.
+    pub fn state_view(&self) -> TraceStateView {
+        TraceStateView {
+            reads: self
+                .reads
+                .iter()
+                .map(|read| (read.state_key.clone(), read.value.clone()))
+                .collect(),
+            usage: self.usage,
+        }
+    }
+}
+
+/// Returns the value of every key written by the write sets, after applying all of them.
+fn latest_written_values(write_sets: &[WriteSet]) -> BTreeMap<StateKey, Option<StateValue>> {
+    let mut written_values = BTreeMap::new();
+    for write_set in write_sets {
+        for (state_key, write_op) in write_set {
+            written_values.insert(state_key.clone(), write_op.as_state_value());
+        }
+    }
+    written_values
+}
+
+/// State view built from an [`ExecutionTraceBundle`].
+pub struct TraceStateView {
+    reads: BTreeMap<StateKey, Option<StateValue>>,
+    usage: Option<StateStorageUsage>,
+}
+
+impl TStateView for TraceStateView {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        StateViewId::Replay
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>, StateviewError> {
+        self.reads.get(state_key).cloned().ok_or_else(|| {
+            StateviewError::NotFound(format!("{:?} in the recorded read set", state_key))
+        })
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage, StateviewError> {
+        self.usage.ok_or_else(|| {
+            StateviewError::NotFound("Storage usage in the recorded read set".to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_types::{
+        state_store::in_memory_state_view::InMemoryStateView,
+        transaction::ExecutionStatus,
+        write_set::{WriteOp, WriteSetMut},
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_recorded_reads_replay() {
+        let existing = StateKey::raw(b"existing");
+        let missing = StateKey::raw(b"missing");
+        let unread = StateKey::raw(b"unread");
+        let value = StateValue::from(b"value".to_vec());
+        let base_view = InMemoryStateView::new(HashMap::from([
+            (existing.clone(), value.clone()),
+            (unread.clone(), StateValue::from(b"other".to_vec())),
+        ]));
+
+        let recorder = ReadSetRecorder::new(&base_view);
+        assert_eq!(
+            recorder.get_state_value(&existing).unwrap(),
+            Some(value.clone())
+        );
+        assert_eq!(recorder.get_state_value(&missing).unwrap(), None);
+        assert_eq!(
+            recorder.get_state_value(&existing).unwrap(),
+            Some(value.clone())
+        );
+
+        let bundle = recorder.into_bundle(1, Transaction::StateCheckpoint(HashValue::zero()));
+        assert_eq!(bundle.reads.len(), 2);
+        assert!(bundle.verify_proofs().is_ok());
+
+        let view = bundle.state_view();
+        assert_eq!(view.get_state_value(&existing).unwrap(), Some(value));
+        assert_eq!(view.get_state_value(&missing).unwrap(), None);
+        assert!(view.get_state_value(&unread).is_err());
+        assert!(view.get_usage().is_err());
+    }
+
+    #[test]
+    fn test_reads_checked_against_write_sets_since_checkpoint() {
+        let written = StateKey::raw(b"written");
+        let value = StateValue::from(b"value".to_vec());
+        let base_view = InMemoryStateView::new(HashMap::from([(written.clone(), value.clone())]));
+
+        let recorder = ReadSetRecorder::new(&base_view);
+        recorder.get_state_value(&written).unwrap();
+        let mut bundle = recorder.into_bundle(3, Transaction::StateCheckpoint(HashValue::zero()));
+
+        // The write sets must cover all the versions since the checkpoint
+        let write_set = WriteSetMut::new(vec![(
+            written.clone(),
+            WriteOp::legacy_modification(value.bytes().clone()),
+        )])
+        .freeze()
+        .unwrap();
+        let state_root_hash = HashValue::random();
+        let checkpoint_txn_info = create_txn_info(HashValue::zero(), Some(state_root_hash));
+        let txn_info = create_txn_info(CryptoHash::hash(&write_set), None);
+        assert!(bundle
+            .attach_proofs(1, checkpoint_txn_info.clone(), vec![], vec![], |_| {
+                unreachable!()
+            })
+            .is_err());
+
+        // The write sets must match their transaction infos
+        let other_write_set = WriteSetMut::new(vec![(written.clone(), WriteOp::legacy_deletion())])
+            .freeze()
+            .unwrap();
+        assert!(bundle
+            .attach_proofs(
+                1,
+                checkpoint_txn_info.clone(),
+                vec![txn_info.clone()],
+                vec![other_write_set],
+                |_| unreachable!()
+            )
+            .is_err());
+
+        // Keys written since the checkpoint are checked against the write sets, not proven
+        bundle
+            .attach_proofs(
+                1,
+                checkpoint_txn_info,
+                vec![txn_info],
+                vec![write_set],
+                |_| unreachable!(),
+            )
+            .unwrap();
+        assert!(bundle.is_proven());
+        assert!(bundle.reads[0].proof.is_none());
+
+        // The state root hash must be the one of the checkpoint
+        bundle.state_root_hash = Some(HashValue::random());
+        assert!(bundle.verify_proofs().is_err());
+        bundle.state_root_hash = Some(state_root_hash);
+        assert!(bundle.verify_proofs().is_ok());
+
+        // A value which does not match the write sets is rejected
+        bundle.reads[0].value = None;
+        assert!(bundle.verify_proofs().is_err());
+    }
+
+    fn create_txn_info(
+        state_change_hash: HashValue,
+        state_checkpoint_hash: Option<HashValue>,
+    ) -> TransactionInfo {
+        TransactionInfo::new(
+            HashValue::zero(),
+            state_change_hash,
+            HashValue::zero(),
+            state_checkpoint_hash,
+            0,
+            ExecutionStatus::Success,
+        )
+    }
+}
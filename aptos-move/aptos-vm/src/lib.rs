@@ -108,6 +108,7 @@ pub mod data_cache;
 pub mod aptos_vm;
 pub mod block_executor;
 mod errors;
+pub mod execution_trace;
 pub mod gas;
 #[cfg(not(feature = "testing"))]
 mod keyless_validation;
@@ -107,6 +107,7 @@ impl MoveVmExt {
         env: Arc<Environment>,
         gas_hook: Option<Arc<dyn Fn(DynamicExpression) + Send + Sync>>,
         resolver: &impl AptosMoveResolver,
+        use_warm_vm_cache: bool,
     ) -> Self {
         // TODO(Gas): Right now, we have to use some dummy values for gas parameters if they are not found on-chain.
         //            This only happens in a edge case that is probably related to write set transactions or genesis,
@@ -132,7 +133,7 @@ impl MoveVmExt {
             },
         };
 
-        let builder = SafeNativeBuilder::new(
+        let mut builder = SafeNativeBuilder::new(
             gas_feature_version,
             native_gas_params,
             misc_gas_params,
@@ -155,16 +156,19 @@ impl MoveVmExt {
             ty_builder,
         };
 
-        Self {
-            inner: WarmVmCache::get_warm_vm(
+        let inner = if use_warm_vm_cache {
+            WarmVmCache::get_warm_vm(
                 builder,
                 vm_config,
                 resolver,
                 env.features().is_enabled(FeatureFlag::VM_BINARY_FORMAT_V7),
             )
-            .expect("should be able to create Move VM; check if there are duplicated natives"),
-            env,
-        }
+            .expect("should be able to create Move VM; check if there are duplicated natives")
+        } else {
+            MoveVM::new_with_config(aptos_natives_with_builder(&mut builder), vm_config)
+        };
+
+        Self { inner, env }
     }
 
     pub fn new(
@@ -173,7 +177,19 @@ impl MoveVmExt {
         env: Arc<Environment>,
         resolver: &impl AptosMoveResolver,
     ) -> Self {
-        Self::new_impl(gas_feature_version, gas_params, env, None, resolver)
+        Self::new_impl(gas_feature_version, gas_params, env, None, resolver, true)
+    }
+
+    /// Creates a VM which does not share its code cache with any other VM. Code loaded by it
+    /// never ends up in the warm VM cache, so the resolver is allowed to return modules which
+    /// are not on-chain (e.g., when simulating on top of overridden state).
+    pub fn new_with_isolated_code_cache(
+        gas_feature_version: u64,
+        gas_params: Result<&AptosGasParameters, &String>,
+        env: Arc<Environment>,
+        resolver: &impl AptosMoveResolver,
+    ) -> Self {
+        Self::new_impl(gas_feature_version, gas_params, env, None, resolver, false)
     }
 
     pub fn new_with_gas_hook(
@@ -183,7 +199,14 @@ impl MoveVmExt {
         gas_hook: Option<Arc<dyn Fn(DynamicExpression) + Send + Sync>>,
         resolver: &impl AptosMoveResolver,
     ) -> Self {
-        Self::new_impl(gas_feature_version, gas_params, env, gas_hook, resolver)
+        Self::new_impl(
+            gas_feature_version,
+            gas_params,
+            env,
+            gas_hook,
+            resolver,
+            true,
+        )
     }
 
     pub fn new_session<'r, R: AptosMoveResolver>(
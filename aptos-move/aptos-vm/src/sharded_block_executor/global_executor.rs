@@ -64,6 +64,7 @@ impl<S: StateView + Sync + Send + 'static> GlobalExecutor<S> {
                     concurrency_level: self.concurrency_level,
                     allow_fallback: true,
                     discard_failed_blocks: false,
+                    conflict_report_dir: None,
                 },
                 onchain: onchain_config,
             },
@@ -32,7 +32,7 @@ use std::{
             Entry,
             Entry::{Occupied, Vacant},
         },
-        BTreeMap, HashMap, HashSet,
+        BTreeMap, BTreeSet, HashMap, HashSet,
     },
     sync::Arc,
 };
@@ -549,85 +549,152 @@ impl<T: Transaction> CapturedReads<T> {
         self.incorrect_use
     }
 
-    pub(crate) fn validate_data_reads(
-        &self,
+    fn validate_data_read(
         data_map: &VersionedData<T::Key, T::Value>,
         idx_to_validate: TxnIndex,
+        key: &T::Key,
+        read: &DataRead<T::Value>,
     ) -> bool {
-        if self.speculative_failure {
-            return false;
-        }
-
         use MVDataError::*;
         use MVDataOutput::*;
-        self.data_reads.iter().all(|(k, r)| {
-            match data_map.fetch_data(k, idx_to_validate) {
-                Ok(Versioned(version, v)) => {
+        match data_map.fetch_data(key, idx_to_validate) {
+            Ok(Versioned(version, v)) => {
+                matches!(
+                    DataRead::from_value_with_layout(version, v).contains(read),
+                    DataReadComparison::Contains
+                )
+            },
+            Ok(Resolved(value)) => matches!(
+                DataRead::Resolved(value).contains(read),
+                DataReadComparison::Contains
+            ),
+            // Dependency implies a validation failure, and if the original read were to
+            // observe an unresolved delta, it would set the aggregator base value in the
+            // multi-versioned data-structure, resolve, and record the resolved value.
+            Err(Dependency(_))
+            | Err(Unresolved(_))
+            | Err(DeltaApplicationFailure)
+            | Err(Uninitialized) => false,
+        }
+    }
+
+    fn validate_group_read(
+        group_map: &VersionedGroupData<T::Key, T::Tag, T::Value>,
+        idx_to_validate: TxnIndex,
+        key: &T::Key,
+        group: &GroupRead<T>,
+    ) -> bool {
+        use MVGroupError::*;
+
+        let mut ret = true;
+        if let Some(size) = group.collected_size {
+            ret &= group_map.validate_group_size(key, idx_to_validate, size);
+        }
+
+        ret && group.inner_reads.iter().all(|(tag, r)| {
+            match group_map.fetch_tagged_data(key, tag, idx_to_validate) {
+                Ok((version, v)) => {
                     matches!(
                         DataRead::from_value_with_layout(version, v).contains(r),
                         DataReadComparison::Contains
                     )
                 },
-                Ok(Resolved(value)) => matches!(
-                    DataRead::Resolved(value).contains(r),
-                    DataReadComparison::Contains
-                ),
-                // Dependency implies a validation failure, and if the original read were to
-                // observe an unresolved delta, it would set the aggregator base value in the
-                // multi-versioned data-structure, resolve, and record the resolved value.
-                Err(Dependency(_))
-                | Err(Unresolved(_))
-                | Err(DeltaApplicationFailure)
-                | Err(Uninitialized) => false,
+                Err(TagNotFound) => {
+                    let sentinel_deletion =
+                        Arc::<T::Value>::new(TransactionWrite::from_state_value(None));
+                    assert!(sentinel_deletion.is_deletion());
+                    matches!(
+                        DataRead::Versioned(Err(StorageVersion), sentinel_deletion, None)
+                            .contains(r),
+                        DataReadComparison::Contains
+                    )
+                },
+                Err(Dependency(_)) => false,
+                Err(Uninitialized) => {
+                    unreachable!("May not be uninitialized if captured for validation");
+                },
+                Err(TagSerializationError(_)) => {
+                    unreachable!("Should not require tag serialization");
+                },
             }
         })
     }
 
+    pub(crate) fn validate_data_reads(
+        &self,
+        data_map: &VersionedData<T::Key, T::Value>,
+        idx_to_validate: TxnIndex,
+    ) -> bool {
+        if self.speculative_failure {
+            return false;
+        }
+
+        self.data_reads
+            .iter()
+            .all(|(k, r)| Self::validate_data_read(data_map, idx_to_validate, k, r))
+    }
+
     pub(crate) fn validate_group_reads(
         &self,
         group_map: &VersionedGroupData<T::Key, T::Tag, T::Value>,
         idx_to_validate: TxnIndex,
     ) -> bool {
-        use MVGroupError::*;
-
         if self.speculative_failure {
             return false;
         }
 
-        self.group_reads.iter().all(|(key, group)| {
-            let mut ret = true;
-            if let Some(size) = group.collected_size {
-                ret &= group_map.validate_group_size(key, idx_to_validate, size);
-            }
+        self.group_reads
+            .iter()
+            .all(|(key, group)| Self::validate_group_read(group_map, idx_to_validate, key, group))
+    }
 
-            ret && group.inner_reads.iter().all(|(tag, r)| {
-                match group_map.fetch_tagged_data(key, tag, idx_to_validate) {
-                    Ok((version, v)) => {
-                        matches!(
-                            DataRead::from_value_with_layout(version, v).contains(r),
-                            DataReadComparison::Contains
-                        )
-                    },
-                    Err(TagNotFound) => {
-                        let sentinel_deletion =
-                            Arc::<T::Value>::new(TransactionWrite::from_state_value(None));
-                        assert!(sentinel_deletion.is_deletion());
-                        matches!(
-                            DataRead::Versioned(Err(StorageVersion), sentinel_deletion, None)
-                                .contains(r),
-                            DataReadComparison::Contains
-                        )
-                    },
-                    Err(Dependency(_)) => false,
-                    Err(Uninitialized) => {
-                        unreachable!("May not be uninitialized if captured for validation");
-                    },
-                    Err(TagSerializationError(_)) => {
-                        unreachable!("Should not require tag serialization");
-                    },
-                }
+    /// Returns the keys of the data and group reads that currently fail validation. Unlike
+    /// validation, does not stop at the first failure, so should only be used for reporting.
+    pub(crate) fn invalid_keys(
+        &self,
+        data_map: &VersionedData<T::Key, T::Value>,
+        group_map: &VersionedGroupData<T::Key, T::Tag, T::Value>,
+        idx_to_validate: TxnIndex,
+    ) -> Vec<T::Key> {
+        let invalid_data_keys = self
+            .data_reads
+            .iter()
+            .filter(|(k, r)| !Self::validate_data_read(data_map, idx_to_validate, k, r))
+            .map(|(k, _)| k.clone());
+        let invalid_group_keys = self
+            .group_reads
+            .iter()
+            .filter(|(key, group)| {
+                !Self::validate_group_read(group_map, idx_to_validate, key, group)
             })
-        })
+            .map(|(key, _)| key.clone());
+        invalid_data_keys.chain(invalid_group_keys).collect()
+    }
+
+    /// Returns the keys whose captured value was written by an earlier transaction of the
+    /// block (rather than read from storage), grouped by the index of that transaction.
+    pub(crate) fn read_dependencies(&self) -> BTreeMap<TxnIndex, Vec<T::Key>> {
+        let mut dependencies: BTreeMap<TxnIndex, Vec<T::Key>> = BTreeMap::new();
+        for (key, read) in &self.data_reads {
+            if let DataRead::Versioned(Ok((txn_idx, _)), _, _) = read {
+                dependencies.entry(*txn_idx).or_default().push(key.clone());
+            }
+        }
+
+        for (key, group_reads) in &self.group_reads {
+            let writers = group_reads
+                .inner_reads
+                .values()
+                .filter_map(|read| match read {
+                    DataRead::Versioned(Ok((txn_idx, _)), _, _) => Some(*txn_idx),
+                    _ => None,
+                })
+                .collect::<BTreeSet<_>>();
+            for txn_idx in writers {
+                dependencies.entry(txn_idx).or_default().push(key.clone());
+            }
+        }
+        dependencies
     }
 
     // This validation needs to be called at commit time
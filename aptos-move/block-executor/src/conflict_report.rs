@@ -0,0 +1,222 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Opt-in report of the conflicts between the transactions of a block executed in parallel,
+//! i.e. which transactions depend on each other, and which state keys caused re-executions.
+//! Enabled by setting `conflict_report_dir` in the local block executor config.
+
+use anyhow::Result;
+use aptos_mvhashmap::types::TxnIndex;
+use aptos_types::state_store::StateViewId;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    fs,
+    hash::Hash,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU32, AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Maximum number of reports kept in the report directory. The oldest reports are removed
+/// when a new one is written.
+pub const MAX_CONFLICT_REPORTS: usize = 1_000;
+
+const REPORT_FILE_PREFIX: &str = "conflicts_";
+const REPORT_FILE_EXTENSION: &str = "json";
+
+/// Sequence number of the next report written by this process, used to name the reports of
+/// blocks which cannot be identified from their state view.
+static NEXT_REPORT_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Conflicts between the transactions of a block executed in parallel. State keys are
+/// rendered with their `Debug` representation.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockConflictReport {
+    pub num_txns: u32,
+    /// Number of workers which executed the block
+    pub concurrency_level: usize,
+    /// Number of incarnations of each transaction, by index in the block. Every incarnation
+    /// after the first one is a re-execution.
+    pub executions: Vec<u32>,
+    /// Number of times each transaction was aborted after failing validation, by index in
+    /// the block.
+    pub aborts: Vec<u32>,
+    /// Reads of the last incarnation of each transaction from the outputs of earlier
+    /// transactions of the block, i.e. the edges of the dependency graph.
+    pub dependencies: Vec<TxnDependency>,
+    /// State keys whose reads failed validation, with the number of aborts they caused,
+    /// sorted from high to low.
+    pub hot_keys: Vec<HotKey>,
+}
+
+/// `txn_idx` read `keys` from the output of `depends_on`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxnDependency {
+    pub txn_idx: TxnIndex,
+    pub depends_on: TxnIndex,
+    pub keys: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HotKey {
+    pub key: String,
+    pub aborts: u32,
+}
+
+impl BlockConflictReport {
+    pub fn num_re_executions(&self) -> u64 {
+        self.executions
+            .iter()
+            .map(|executions| executions.saturating_sub(1) as u64)
+            .sum()
+    }
+
+    /// Writes the report as JSON to the directory, which is created if needed, and returns
+    /// the path of the file. The file is named after the block, identified by the id of the
+    /// state view it was executed on, so that re-executing a block replaces its report. Only
+    /// the `max_reports` most recent reports are kept in the directory.
+    pub fn save(
+        &self,
+        dir: &Path,
+        state_view_id: StateViewId,
+        max_reports: usize,
+    ) -> Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "{}{}.{}",
+            REPORT_FILE_PREFIX,
+            report_name(state_view_id)?,
+            REPORT_FILE_EXTENSION
+        ));
+        fs::write(&path, serde_json::to_vec_pretty(self)?)?;
+        remove_old_reports(dir, &path, max_reports)?;
+        Ok(path)
+    }
+}
+
+fn report_name(state_view_id: StateViewId) -> Result<String> {
+    Ok(match state_view_id {
+        StateViewId::BlockExecution { block_id } => format!("block_{}", block_id.to_hex()),
+        StateViewId::ChunkExecution { first_version } => format!("chunk_{:020}", first_version),
+        StateViewId::TransactionValidation { .. }
+        | StateViewId::Miscellaneous
+        | StateViewId::Replay => {
+            // Unknown block, the timestamp keeps the names unique across restarts.
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_micros();
+            let seq = NEXT_REPORT_SEQ.fetch_add(1, Ordering::Relaxed);
+            format!("unknown_{}_{:06}", timestamp, seq)
+        },
+    })
+}
+
+/// Removes the least recently written reports from the directory, other than `latest`, so
+/// that at most `max_reports` reports are left.
+fn remove_old_reports(dir: &Path, latest: &Path, max_reports: usize) -> Result<()> {
+    let mut reports = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_report = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| {
+                name.starts_with(REPORT_FILE_PREFIX)
+                    && name.ends_with(&format!(".{}", REPORT_FILE_EXTENSION))
+            });
+        if is_report && path != latest {
+            reports.push((fs::metadata(&path)?.modified()?, path));
+        }
+    }
+    // Leave room for the latest report.
+    let num_to_remove = (reports.len() + 1).saturating_sub(max_reports.max(1));
+    reports.sort();
+    for (_, path) in reports.into_iter().take(num_to_remove) {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Collects the conflicts while the block is executed by the workers.
+pub(crate) struct ConflictRecorder<K> {
+    executions: Vec<AtomicU32>,
+    aborts: Vec<AtomicU32>,
+    aborted_keys: DashMap<K, u32>,
+}
+
+impl<K: Hash + Eq + Debug> ConflictRecorder<K> {
+    pub(crate) fn new(num_txns: u32) -> Self {
+        Self {
+            executions: (0..num_txns).map(|_| AtomicU32::new(0)).collect(),
+            aborts: (0..num_txns).map(|_| AtomicU32::new(0)).collect(),
+            aborted_keys: DashMap::new(),
+        }
+    }
+
+    pub(crate) fn record_execution(&self, txn_idx: TxnIndex) {
+        self.executions[txn_idx as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records an abort of the transaction, caused by reads of the given keys. Keys are
+    /// unknown for aborts caused by delayed fields or speculative failures.
+    pub(crate) fn record_abort(&self, txn_idx: TxnIndex, keys: Vec<K>) {
+        self.aborts[txn_idx as usize].fetch_add(1, Ordering::Relaxed);
+        for key in keys {
+            *self.aborted_keys.entry(key).or_insert(0) += 1;
+        }
+    }
+
+    /// Builds the report, given the dependencies of each transaction on the earlier
+    /// transactions of the block, from the read set of its last incarnation.
+    pub(crate) fn into_report(
+        self,
+        concurrency_level: usize,
+        dependencies: impl IntoIterator<Item = (TxnIndex, BTreeMap<TxnIndex, Vec<K>>)>,
+    ) -> BlockConflictReport {
+        let dependencies = dependencies
+            .into_iter()
+            .flat_map(|(txn_idx, deps)| {
+                deps.into_iter().map(move |(depends_on, keys)| {
+                    let mut keys = keys
+                        .iter()
+                        .map(|key| format!("{:?}", key))
+                        .collect::<Vec<_>>();
+                    keys.sort();
+                    TxnDependency {
+                        txn_idx,
+                        depends_on,
+                        keys,
+                    }
+                })
+            })
+            .collect();
+
+        let mut hot_keys = self
+            .aborted_keys
+            .into_iter()
+            .map(|(key, aborts)| HotKey {
+                key: format!("{:?}", key),
+                aborts,
+            })
+            .collect::<Vec<_>>();
+        hot_keys.sort_by(|lhs, rhs| {
+            rhs.aborts
+                .cmp(&lhs.aborts)
+                .then_with(|| lhs.key.cmp(&rhs.key))
+        });
+
+        BlockConflictReport {
+            num_txns: self.executions.len() as u32,
+            concurrency_level,
+            executions: self
+                .executions
+                .into_iter()
+                .map(AtomicU32::into_inner)
+                .collect(),
+            aborts: self.aborts.into_iter().map(AtomicU32::into_inner).collect(),
+            dependencies,
+            hot_keys,
+        }
+    }
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    conflict_report::{ConflictRecorder, MAX_CONFLICT_REPORTS},
     counters,
     counters::{
         PARALLEL_EXECUTION_SECONDS, RAYON_EXECUTION_SECONDS, TASK_EXECUTE_SECONDS,
@@ -25,7 +26,7 @@ use aptos_aggregator::{
     types::{code_invariant_error, expect_ok, PanicOr},
 };
 use aptos_drop_helper::DEFAULT_DROPPER;
-use aptos_logger::{debug, error, info};
+use aptos_logger::{debug, error, info, warn};
 use aptos_mvhashmap::{
     types::{Incarnation, MVDelayedFieldsError, TxnIndex, ValueWithLayout},
     unsync_map::UnsyncMap,
@@ -394,10 +395,22 @@ where
         last_input_output: &TxnLastInputOutput<T, E::Output, E::Error>,
         versioned_cache: &MVHashMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
         scheduler: &Scheduler,
+        conflict_recorder: Option<&ConflictRecorder<T::Key>>,
     ) -> Result<SchedulerTask, PanicError> {
         let aborted = !valid && scheduler.try_abort(txn_idx, incarnation);
 
         if aborted {
+            if let Some(conflict_recorder) = conflict_recorder {
+                let invalid_keys = last_input_output
+                    .read_set(txn_idx)
+                    .expect("[BlockSTM]: Prior read-set must be recorded")
+                    .invalid_keys(
+                        versioned_cache.data(),
+                        versioned_cache.group_data(),
+                        txn_idx,
+                    );
+                conflict_recorder.record_abort(txn_idx, invalid_keys);
+            }
             Self::update_transaction_on_abort(txn_idx, last_input_output, versioned_cache);
             scheduler.finish_abort(txn_idx, incarnation)
         } else {
@@ -465,6 +478,7 @@ where
         executor: &E,
         block: &[T],
         num_workers: usize,
+        conflict_recorder: Option<&ConflictRecorder<T::Key>>,
     ) -> Result<(), PanicOr<ParallelBlockExecutionError>> {
         let mut block_limit_processor = shared_commit_state.acquire();
 
@@ -473,6 +487,11 @@ where
                 // Transaction needs to be re-executed, one final time.
 
                 Self::update_transaction_on_abort(txn_idx, last_input_output, versioned_cache);
+                if let Some(conflict_recorder) = conflict_recorder {
+                    // Commit-time validation only covers delayed fields, which are not keys.
+                    conflict_recorder.record_abort(txn_idx, vec![]);
+                    conflict_recorder.record_execution(txn_idx);
+                }
                 // We are going to skip reducing validation index here, as we
                 // are executing immediately, and will reduce it unconditionally
                 // after execution, inside finish_execution_during_commit.
@@ -759,6 +778,7 @@ where
         shared_commit_state: &ExplicitSyncWrapper<BlockGasLimitProcessor<T>>,
         final_results: &ExplicitSyncWrapper<Vec<E::Output>>,
         num_workers: usize,
+        conflict_recorder: Option<&ConflictRecorder<T::Key>>,
     ) -> Result<(), PanicOr<ParallelBlockExecutionError>> {
         // Make executor for each task. TODO: fast concurrent executor.
         let init_timer = VM_INIT_SECONDS.start_timer();
@@ -799,6 +819,7 @@ where
                     &executor,
                     block,
                     num_workers,
+                    conflict_recorder,
                 )?;
                 scheduler.queueing_commits_mark_done();
             }
@@ -816,6 +837,7 @@ where
                         last_input_output,
                         versioned_cache,
                         scheduler,
+                        conflict_recorder,
                     )?
                 },
                 SchedulerTask::ExecutionTask(
@@ -823,6 +845,9 @@ where
                     incarnation,
                     ExecutionTaskType::Execution,
                 ) => {
+                    if let Some(conflict_recorder) = conflict_recorder {
+                        conflict_recorder.record_execution(txn_idx);
+                    }
                     let needs_suffix_validation = Self::execute(
                         txn_idx,
                         incarnation,
@@ -907,6 +932,12 @@ where
 
         let last_input_output = TxnLastInputOutput::new(num_txns);
         let scheduler = Scheduler::new(num_txns);
+        let conflict_recorder = self
+            .config
+            .local
+            .conflict_report_dir
+            .as_ref()
+            .map(|_| ConflictRecorder::new(num_txns));
 
         let timer = RAYON_EXECUTION_SECONDS.start_timer();
         self.executor_thread_pool.scope(|s| {
@@ -924,6 +955,7 @@ where
                         &shared_commit_state,
                         &final_results,
                         num_workers,
+                        conflict_recorder.as_ref(),
                     ) {
                         // If there are multiple errors, they all get logged:
                         // ModulePathReadWriteError and FatalVMError variant is logged at construction,
@@ -943,6 +975,30 @@ where
 
         counters::update_state_counters(versioned_cache.stats(), true);
 
+        if let (Some(conflict_recorder), Some(dir)) =
+            (conflict_recorder, &self.config.local.conflict_report_dir)
+        {
+            if !shared_maybe_error.load(Ordering::SeqCst) {
+                let report = conflict_recorder.into_report(
+                    num_workers,
+                    (0..num_txns).filter_map(|txn_idx| {
+                        last_input_output
+                            .read_set(txn_idx)
+                            .map(|read_set| (txn_idx, read_set.read_dependencies()))
+                    }),
+                );
+                match report.save(dir, base_view.id(), MAX_CONFLICT_REPORTS) {
+                    Ok(path) => info!(
+                        "[BlockSTM]: conflict report for {} txns with {} re-executions written to {}",
+                        num_txns,
+                        report.num_re_executions(),
+                        path.display()
+                    ),
+                    Err(err) => warn!("[BlockSTM]: failed to write conflict report: {:?}", err),
+                }
+            }
+        }
+
         // Explicit async drops.
         DEFAULT_DROPPER.schedule_drop((last_input_output, scheduler, versioned_cache));
 
@@ -1336,11 +1392,16 @@ where
             .block_gas_limit_type
             .add_block_limit_outcome_onchain()
         {
-            Some(BlockEndInfo::V0 {
+            Some(BlockEndInfo::V1 {
                 block_gas_limit_reached: false,
                 block_output_limit_reached: false,
                 block_effective_block_gas_units: 0,
                 block_approx_output_size: 0,
+                block_execution_gas_units: 0,
+                block_io_gas_units: 0,
+                block_conflict_penalty_gas_units: 0,
+                max_conflict_multiplier: 1,
+                module_publishing_conflict: false,
             })
         } else {
             None
@@ -19,6 +19,7 @@ pub struct BlockGasLimitProcessor<T: Transaction> {
     txn_fee_statements: Vec<FeeStatement>,
     txn_read_write_summaries: Vec<ReadWriteSummary<T>>,
     module_rw_conflict: bool,
+    max_conflict_multiplier: u64,
     start_time: Instant,
 }
 
@@ -32,6 +33,7 @@ impl<T: Transaction> BlockGasLimitProcessor<T> {
             txn_fee_statements: Vec::with_capacity(init_size),
             txn_read_write_summaries: Vec::with_capacity(init_size),
             module_rw_conflict: false,
+            max_conflict_multiplier: 1,
             start_time: Instant::now(),
         }
     }
@@ -71,6 +73,7 @@ impl<T: Transaction> BlockGasLimitProcessor<T> {
             assert_none!(txn_read_write_summary);
             1
         };
+        self.max_conflict_multiplier = self.max_conflict_multiplier.max(conflict_multiplier);
 
         // When the accumulated execution and io gas of the committed txns exceeds
         // PER_BLOCK_GAS_LIMIT, early halt BlockSTM. Storage fee does not count towards
@@ -108,13 +111,11 @@ impl<T: Transaction> BlockGasLimitProcessor<T> {
             return;
         };
 
-        self.accumulated_effective_block_gas = conflict_multiplier as u64
-            * (self.accumulated_fee_statement.execution_gas_used()
-                * self
-                    .block_gas_limit_type
-                    .execution_gas_effective_multiplier()
-                + self.accumulated_fee_statement.io_gas_used()
-                    * self.block_gas_limit_type.io_gas_effective_multiplier());
+        // All the transactions committed so far are charged the maximum conflict multiplier,
+        // as the whole block is executed sequentially after a module publishing conflict.
+        self.accumulated_effective_block_gas =
+            conflict_multiplier as u64 * self.get_accumulated_gas_without_conflicts();
+        self.max_conflict_multiplier = conflict_multiplier as u64;
         self.module_rw_conflict = true;
     }
 
@@ -170,6 +171,16 @@ impl<T: Transaction> BlockGasLimitProcessor<T> {
         self.accumulated_approx_output_size
     }
 
+    /// Effective block gas of the committed transactions if there were no conflicts.
+    fn get_accumulated_gas_without_conflicts(&self) -> u64 {
+        self.accumulated_fee_statement.execution_gas_used()
+            * self
+                .block_gas_limit_type
+                .execution_gas_effective_multiplier()
+            + self.accumulated_fee_statement.io_gas_used()
+                * self.block_gas_limit_type.io_gas_effective_multiplier()
+    }
+
     fn compute_conflict_multiplier(&self, conflict_overlap_length: usize) -> u64 {
         let start = self
             .txn_read_write_summaries
@@ -214,6 +225,10 @@ impl<T: Transaction> BlockGasLimitProcessor<T> {
                 .block_gas_limit_type
                 .block_gas_limit()
                 .map_or(false, |limit| accumulated_effective_block_gas >= limit),
+            conflict_penalty = accumulated_effective_block_gas
+                .saturating_sub(self.get_accumulated_gas_without_conflicts()),
+            max_conflict_multiplier = self.max_conflict_multiplier,
+            module_publishing_conflict = self.module_rw_conflict,
             approx_output_size = accumulated_approx_output_size,
             block_output_limit = self.block_gas_limit_type.block_output_limit().unwrap_or(0),
             block_output_limit_exceeded = self
@@ -253,7 +268,7 @@ impl<T: Transaction> BlockGasLimitProcessor<T> {
     }
 
     pub(crate) fn get_block_end_info(&self) -> BlockEndInfo {
-        BlockEndInfo::V0 {
+        BlockEndInfo::V1 {
             block_gas_limit_reached: self
                 .block_gas_limit_type
                 .block_gas_limit()
@@ -270,6 +285,13 @@ impl<T: Transaction> BlockGasLimitProcessor<T> {
                 .unwrap_or(false),
             block_effective_block_gas_units: self.get_effective_accumulated_block_gas(),
             block_approx_output_size: self.get_accumulated_approx_output_size(),
+            block_execution_gas_units: self.accumulated_fee_statement.execution_gas_used(),
+            block_io_gas_units: self.accumulated_fee_statement.io_gas_used(),
+            block_conflict_penalty_gas_units: self
+                .get_effective_accumulated_block_gas()
+                .saturating_sub(self.get_accumulated_gas_without_conflicts()),
+            max_conflict_multiplier: self.max_conflict_multiplier,
+            module_publishing_conflict: self.module_rw_conflict,
         }
     }
 }
@@ -533,4 +555,61 @@ mod test {
             55 * conflict_penalty_window as u64
         );
     }
+
+    #[test]
+    fn test_block_end_info_breakdown() {
+        let block_gas_limit = BlockGasLimitType::ComplexLimitV1 {
+            effective_block_gas_limit: 100,
+            execution_gas_effective_multiplier: 1,
+            io_gas_effective_multiplier: 1,
+            conflict_penalty_window: 4,
+            use_module_publishing_block_conflict: true,
+            block_output_limit: None,
+            include_user_txn_size_in_block_output: true,
+            add_block_limit_outcome_onchain: true,
+            use_granular_resource_group_conflicts: false,
+        };
+
+        let mut processor = BlockGasLimitProcessor::<TestTxn>::new(block_gas_limit, 10);
+        processor.accumulate_fee_statement(
+            execution_fee(10),
+            Some(ReadWriteSummary::new(
+                to_map(&[]),
+                to_map(&[InputOutputKey::Resource(1)]),
+            )),
+            None,
+        );
+        processor.accumulate_fee_statement(
+            execution_fee(20),
+            Some(ReadWriteSummary::new(
+                to_map(&[InputOutputKey::Resource(1)]),
+                to_map(&[InputOutputKey::Resource(1)]),
+            )),
+            None,
+        );
+        assert_eq!(processor.get_block_end_info(), BlockEndInfo::V1 {
+            block_gas_limit_reached: false,
+            block_output_limit_reached: false,
+            block_effective_block_gas_units: 50,
+            block_approx_output_size: 0,
+            block_execution_gas_units: 30,
+            block_io_gas_units: 0,
+            block_conflict_penalty_gas_units: 20,
+            max_conflict_multiplier: 2,
+            module_publishing_conflict: false,
+        });
+
+        processor.process_module_rw_conflict();
+        assert_eq!(processor.get_block_end_info(), BlockEndInfo::V1 {
+            block_gas_limit_reached: true,
+            block_output_limit_reached: false,
+            block_effective_block_gas_units: 120,
+            block_approx_output_size: 0,
+            block_execution_gas_units: 30,
+            block_io_gas_units: 0,
+            block_conflict_penalty_gas_units: 90,
+            max_conflict_multiplier: 4,
+            module_publishing_conflict: true,
+        });
+    }
 }
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    conflict_report::BlockConflictReport,
     errors::SequentialBlockExecutionError,
     executor::BlockExecutor,
     proptest_types::{
@@ -23,11 +24,12 @@ use aptos_aggregator::{
     delta_math::DeltaHistory,
 };
 use aptos_mvhashmap::types::TxnIndex;
+use aptos_temppath::TempPath;
 use aptos_types::{
     block_executor::config::BlockExecutorConfig,
     contract_event::TransactionEvent,
     executable::{ExecutableTestType, ModulePath},
-    state_store::state_value::StateValueMetadata,
+    state_store::{state_value::StateValueMetadata, StateViewId},
 };
 use claims::assert_matches;
 use fail::FailScenario;
@@ -403,6 +405,113 @@ fn cycle_transactions() {
     run_and_assert(transactions)
 }
 
+#[test]
+fn conflict_report() {
+    let key = KeyType(random::<[u8; 32]>(), false);
+    // Every transaction reads the value written by the previous one.
+    let transactions: Vec<_> = (0..WRITES_PER_KEY)
+        .map(|_| {
+            MockTransaction::from_behavior(MockIncarnation::<KeyType<[u8; 32]>, MockEvent>::new(
+                vec![key],                        // reads
+                vec![(key, random_value(false))], // writes
+                vec![],
+                vec![],
+                1, // gas
+            ))
+        })
+        .collect();
+
+    let data_view = DeltaDataView::<KeyType<[u8; 32]>> {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_cpus::get())
+            .build()
+            .unwrap(),
+    );
+    let report_dir = TempPath::new();
+    let mut config = BlockExecutorConfig::new_no_block_limit(num_cpus::get());
+    config.local.conflict_report_dir = Some(report_dir.path().to_path_buf());
+
+    let output = BlockExecutor::<
+        MockTransaction<KeyType<[u8; 32]>, MockEvent>,
+        MockTask<KeyType<[u8; 32]>, MockEvent>,
+        DeltaDataView<KeyType<[u8; 32]>>,
+        NoOpTransactionCommitHook<MockOutput<KeyType<[u8; 32]>, MockEvent>, usize>,
+        ExecutableTestType,
+    >::new(config, executor_thread_pool, None)
+    .execute_transactions_parallel(&(), &transactions, &data_view);
+    BaselineOutput::generate(&transactions, None).assert_parallel_output(&output);
+
+    let report_files = std::fs::read_dir(report_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert_eq!(report_files.len(), 1);
+    let report: BlockConflictReport =
+        serde_json::from_slice(&std::fs::read(&report_files[0]).unwrap()).unwrap();
+
+    assert_eq!(report.num_txns, WRITES_PER_KEY as u32);
+    assert!(report.executions.iter().all(|executions| *executions >= 1));
+    assert_eq!(
+        report.num_re_executions(),
+        report
+            .aborts
+            .iter()
+            .map(|aborts| *aborts as u64)
+            .sum::<u64>()
+    );
+    let dependencies = report
+        .dependencies
+        .iter()
+        .map(|dependency| (dependency.txn_idx, dependency.depends_on))
+        .collect::<Vec<_>>();
+    let expected_dependencies = (1..WRITES_PER_KEY as TxnIndex)
+        .map(|txn_idx| (txn_idx, txn_idx - 1))
+        .collect::<Vec<_>>();
+    assert_eq!(dependencies, expected_dependencies);
+    assert!(report
+        .hot_keys
+        .iter()
+        .all(|hot_key| hot_key.key == format!("{:?}", key)));
+}
+
+#[test]
+fn conflict_report_retention() {
+    let report_dir = TempPath::new();
+    let report = BlockConflictReport::default();
+    let first_versions = [0, 100, 200];
+
+    let mut paths = vec![];
+    for first_version in first_versions {
+        let state_view_id = StateViewId::ChunkExecution { first_version };
+        paths.push(report.save(report_dir.path(), state_view_id, 2).unwrap());
+        // Keeps the modification times of the reports apart.
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert!(paths[1].ends_with(format!("conflicts_chunk_{:020}.json", 100)));
+
+    // Re-executing a chunk replaces its report.
+    let state_view_id = StateViewId::ChunkExecution {
+        first_version: first_versions[2],
+    };
+    assert_eq!(
+        report.save(report_dir.path(), state_view_id, 2).unwrap(),
+        paths[2]
+    );
+
+    // Only the two most recent reports are kept.
+    let mut report_files = std::fs::read_dir(report_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    report_files.sort();
+    let mut expected_files = paths[1..].to_vec();
+    expected_files.sort();
+    assert_eq!(report_files, expected_files);
+}
+
 const NUM_BLOCKS: u64 = 10;
 const TXN_PER_BLOCK: u64 = 100;
 
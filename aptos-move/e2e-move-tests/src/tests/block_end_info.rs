@@ -0,0 +1,97 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::MoveHarness;
+use aptos_cached_packages::aptos_stdlib;
+use aptos_types::{
+    account_address::AccountAddress,
+    block_executor::config::BlockExecutorConfigFromOnchain,
+    on_chain_config::{BlockGasLimitType, FeatureFlag},
+    transaction::{BlockEndInfo, BlockEpiloguePayload, Transaction},
+};
+
+/// Executes a block with a single transfer (with the block end info enabled),
+/// and returns the block end info of the block.
+fn execute_transfer_block(h: &mut MoveHarness) -> BlockEndInfo {
+    let alice = h.new_account_at(AccountAddress::from_hex_literal("0xa11ce").unwrap());
+    let txn = h.create_transaction_payload(
+        &alice,
+        aptos_stdlib::aptos_coin_transfer(AccountAddress::from_hex_literal("0xb0b").unwrap(), 1),
+    );
+
+    let mut onchain_config = BlockExecutorConfigFromOnchain::on_but_large_for_test();
+    match &mut onchain_config.block_gas_limit_type {
+        BlockGasLimitType::ComplexLimitV1 {
+            add_block_limit_outcome_onchain,
+            ..
+        } => *add_block_limit_outcome_onchain = true,
+        _ => unreachable!("The test config must use ComplexLimitV1"),
+    };
+
+    let (outputs, block_end_info) = h
+        .executor
+        .execute_block_with_onchain_config(vec![txn], onchain_config)
+        .unwrap()
+        .into_inner();
+    assert_eq!(outputs.len(), 1);
+    block_end_info.unwrap()
+}
+
+#[test]
+fn test_block_end_info_v0_without_feature() {
+    let mut h = MoveHarness::new_with_features(vec![], vec![FeatureFlag::BLOCK_END_INFO_V1]);
+
+    // The breakdown of the block limits must not be persisted
+    let block_end_info = execute_transfer_block(&mut h);
+    assert!(matches!(block_end_info, BlockEndInfo::V0 { .. }));
+    assert!(block_end_info.block_effective_block_gas_units() > 0);
+
+    let api_block_end_info = aptos_api_types::transaction::BlockEndInfo::from(block_end_info);
+    assert!(api_block_end_info.block_limit_breakdown.is_none());
+}
+
+#[test]
+fn test_block_end_info_v1_with_feature() {
+    let mut h = MoveHarness::new_with_features(vec![FeatureFlag::BLOCK_END_INFO_V1], vec![]);
+
+    // The breakdown of the block limits must be persisted
+    let block_end_info = execute_transfer_block(&mut h);
+    match &block_end_info {
+        BlockEndInfo::V1 {
+            block_effective_block_gas_units,
+            block_execution_gas_units,
+            block_io_gas_units,
+            block_conflict_penalty_gas_units,
+            max_conflict_multiplier,
+            module_publishing_conflict,
+            ..
+        } => {
+            assert!(*block_execution_gas_units > 0);
+            assert_eq!(
+                *block_effective_block_gas_units,
+                block_execution_gas_units + block_io_gas_units + block_conflict_penalty_gas_units
+            );
+            assert_eq!(*max_conflict_multiplier, 1);
+            assert!(!module_publishing_conflict);
+        },
+        BlockEndInfo::V0 { .. } => panic!("Expected BlockEndInfo::V1, got {:?}", block_end_info),
+    }
+
+    // The block epilogue (as committed and served by the API) must contain the breakdown
+    let block_epilogue = Transaction::BlockEpilogue(BlockEpiloguePayload::V0 {
+        block_id: aptos_crypto::HashValue::zero(),
+        block_end_info: block_end_info.clone(),
+    });
+    let block_epilogue: Transaction =
+        bcs::from_bytes(&bcs::to_bytes(&block_epilogue).unwrap()).unwrap();
+    let block_end_info = match block_epilogue {
+        Transaction::BlockEpilogue(BlockEpiloguePayload::V0 { block_end_info, .. }) => {
+            block_end_info
+        },
+        _ => unreachable!(),
+    };
+    let api_block_end_info = aptos_api_types::transaction::BlockEndInfo::from(block_end_info);
+    let block_limit_breakdown = api_block_end_info.block_limit_breakdown.unwrap();
+    assert!(block_limit_breakdown.block_execution_gas_units > 0);
+    assert_eq!(block_limit_breakdown.max_conflict_multiplier, 1);
+}
@@ -0,0 +1,49 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, MoveHarness};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_types::{
+    account_address::AccountAddress, state_store::state_key::StateKey, transaction::Transaction,
+};
+use aptos_vm::execution_trace::record_execution_trace;
+use move_core_types::ident_str;
+
+#[test]
+fn test_record_execution_trace_twice_and_replay() {
+    let mut h = MoveHarness::new();
+    let alice = h.new_account_at(AccountAddress::from_hex_literal("0xa11ce").unwrap());
+    let bob = AccountAddress::from_hex_literal("0xb0b").unwrap();
+
+    // Load the modules used by a transfer into the code cache of the process
+    let txn = h.create_transaction_payload(&alice, aptos_stdlib::aptos_coin_transfer(bob, 1));
+    assert_success!(h.run(txn));
+
+    // Record the execution of another transfer twice
+    let txn = Transaction::UserTransaction(
+        h.create_transaction_payload(&alice, aptos_stdlib::aptos_coin_transfer(bob, 1)),
+    );
+    let state_view = h.executor.get_state_view();
+    let (output, first_bundle) = record_execution_trace(state_view, 1, txn.clone()).unwrap();
+    let (_, second_bundle) = record_execution_trace(state_view, 1, txn).unwrap();
+
+    // Verify the modules are recorded every time, even if they were cached before
+    let account_module = StateKey::module(&AccountAddress::ONE, ident_str!("account"));
+    let recorded_keys = |bundle: &aptos_vm::execution_trace::ExecutionTraceBundle| {
+        bundle
+            .reads
+            .iter()
+            .map(|read| read.state_key.clone())
+            .collect::<Vec<_>>()
+    };
+    assert!(recorded_keys(&first_bundle).contains(&account_module));
+    assert_eq!(recorded_keys(&first_bundle), recorded_keys(&second_bundle));
+
+    // Verify the bundles can be replayed without access to the state
+    for bundle in [first_bundle, second_bundle] {
+        let replayed_output = bundle.replay().unwrap();
+        assert_eq!(replayed_output.write_set(), output.write_set());
+        assert_eq!(replayed_output.gas_used(), output.gas_used());
+        assert_eq!(replayed_output.status(), output.status());
+    }
+}
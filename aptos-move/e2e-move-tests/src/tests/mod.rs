@@ -8,12 +8,14 @@ mod aggregator_v2;
 mod aggregator_v2_events;
 mod aggregator_v2_runtime_checks;
 mod attributes;
+mod block_end_info;
 mod chain_id;
 mod code_publishing;
 mod common;
 mod constructor_args;
 mod dependencies;
 mod error_map;
+mod execution_trace;
 mod fee_payer;
 mod fungible_asset;
 mod gas;
@@ -513,6 +513,28 @@ impl FakeExecutor {
         }
     }
 
+    /// Executes the given block of transactions with the given on-chain config, and returns
+    /// the block output (including the block end info, if enabled by the on-chain config).
+    /// This doesn't apply the results of successful transactions to the data store.
+    pub fn execute_block_with_onchain_config(
+        &self,
+        txn_block: Vec<SignedTransaction>,
+        onchain_config: BlockExecutorConfigFromOnchain,
+    ) -> Result<BlockOutput<TransactionOutput>, VMStatus> {
+        let sig_verified_block = into_signature_verified_block(
+            txn_block
+                .into_iter()
+                .map(Transaction::UserTransaction)
+                .collect(),
+        );
+        self.execute_block_output_impl_with_state_view(
+            &sig_verified_block,
+            onchain_config,
+            false,
+            self.get_state_view(),
+        )
+    }
+
     fn execute_transaction_block_impl_with_state_view(
         &self,
         txn_block: &[SignatureVerifiedTransaction],
@@ -520,6 +542,22 @@ impl FakeExecutor {
         sequential: bool,
         state_view: &(impl StateView + Sync),
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        self.execute_block_output_impl_with_state_view(
+            txn_block,
+            onchain_config,
+            sequential,
+            state_view,
+        )
+        .map(BlockOutput::into_transaction_outputs_forced)
+    }
+
+    fn execute_block_output_impl_with_state_view(
+        &self,
+        txn_block: &[SignatureVerifiedTransaction],
+        onchain_config: BlockExecutorConfigFromOnchain,
+        sequential: bool,
+        state_view: &(impl StateView + Sync),
+    ) -> Result<BlockOutput<TransactionOutput>, VMStatus> {
         let config = BlockExecutorConfig {
             local: BlockExecutorLocalConfig {
                 concurrency_level: if sequential {
@@ -529,6 +567,7 @@ impl FakeExecutor {
                 },
                 allow_fallback: self.allow_block_executor_fallback,
                 discard_failed_blocks: false,
+                conflict_report_dir: None,
             },
             onchain: onchain_config,
         };
@@ -542,7 +581,6 @@ impl FakeExecutor {
             config,
             None,
         )
-        .map(BlockOutput::into_transaction_outputs_forced)
     }
 
     pub fn execute_transaction_block_with_state_view(
@@ -644,6 +644,7 @@ pub fn setup_environment_and_start_node(
 
     // Set up the networks and gather the application network handles
     let peers_and_metadata = network::create_peers_and_metadata(&node_config);
+    admin_service.set_peer_reputation(peers_and_metadata.get_peer_reputation());
     let (
         network_runtimes,
         consensus_network_interfaces,
@@ -673,6 +674,7 @@ pub fn setup_environment_and_start_node(
     let (aptos_data_client, state_sync_runtimes, mempool_listener, consensus_notifier) =
         state_sync::start_state_sync_and_get_notification_handles(
             &node_config,
+            &admin_service,
             storage_service_network_interfaces,
             genesis_waypoint,
             event_subscription_service,
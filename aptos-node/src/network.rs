@@ -19,6 +19,7 @@ use aptos_mempool::network::MempoolSyncMsg;
 use aptos_network::{
     application::{
         interface::{NetworkClient, NetworkServiceEvents},
+        reputation::PeerReputation,
         storage::PeersAndMetadata,
     },
     protocols::network::{
@@ -236,10 +237,21 @@ fn extract_network_ids(node_config: &NodeConfig) -> Vec<NetworkId> {
         .collect()
 }
 
-/// Creates the global peers and metadata struct
+/// Creates the global peers and metadata struct (including the
+/// peer reputation service shared by all networks and applications)
 pub fn create_peers_and_metadata(node_config: &NodeConfig) -> Arc<PeersAndMetadata> {
+    let peer_reputation_config = node_config.peer_reputation.clone();
+    let ban_list_path = node_config
+        .get_data_dir()
+        .join(&peer_reputation_config.ban_list_file_name);
+    let peer_reputation = Arc::new(PeerReputation::new(
+        peer_reputation_config,
+        Some(ban_list_path),
+        TimeService::real(),
+    ));
+
     let network_ids = extract_network_ids(node_config);
-    PeersAndMetadata::new(&network_ids)
+    PeersAndMetadata::new_with_peer_reputation(&network_ids, peer_reputation)
 }
 
 /// Sets up all networks and returns the appropriate application network interfaces
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::network::ApplicationNetworkInterfaces;
+use aptos_admin_service::AdminService;
 use aptos_config::config::{NodeConfig, StateSyncConfig};
 use aptos_consensus_notifications::ConsensusNotifier;
 use aptos_data_client::{client::AptosDataClient, poller};
@@ -20,6 +21,7 @@ use aptos_network::application::{
     interface::{NetworkClient, NetworkClientInterface, NetworkServiceEvents},
     storage::PeersAndMetadata,
 };
+use aptos_rate_limiter::byte_rate_limit::ByteRateLimits;
 use aptos_schemadb::DB;
 use aptos_state_sync_driver::{
     driver_factory::{DriverFactory, StateSyncRuntimes},
@@ -128,6 +130,7 @@ pub fn create_event_subscription_service(
 /// Sets up all state sync runtimes and return the notification endpoints
 pub fn start_state_sync_and_get_notification_handles(
     node_config: &NodeConfig,
+    admin_service: &AdminService,
     storage_network_interfaces: ApplicationNetworkInterfaces<StorageServiceMessage>,
     waypoint: Waypoint,
     event_subscription_service: EventSubscriptionService,
@@ -174,7 +177,7 @@ pub fn start_state_sync_and_get_notification_handles(
         aptos_storage_service_notifications::new_storage_service_notifier_listener_pair();
 
     // Start the state sync storage service
-    let storage_service_runtime = setup_state_sync_storage_service(
+    let (storage_service_runtime, egress_rate_limits) = setup_state_sync_storage_service(
         state_sync_config,
         peers_and_metadata,
         network_service_events,
@@ -182,6 +185,12 @@ pub fn start_state_sync_and_get_notification_handles(
         storage_service_listener,
     )?;
 
+    // Allow the admin service to adjust the state sync rate limits at runtime
+    admin_service.set_state_sync_rate_limits(
+        aptos_data_client.get_ingress_byte_rate_limits(),
+        egress_rate_limits,
+    );
+
     // Create the state sync driver factory
     let state_sync = DriverFactory::create_and_spawn_driver(
         true,
@@ -272,7 +281,7 @@ fn setup_state_sync_storage_service(
     network_service_events: NetworkServiceEvents<StorageServiceMessage>,
     db_rw: &DbReaderWriter,
     storage_service_listener: StorageServiceNotificationListener,
-) -> anyhow::Result<Runtime> {
+) -> anyhow::Result<(Runtime, Arc<ByteRateLimits>)> {
     // Create a new state sync storage service runtime
     let storage_service_runtime = aptos_runtimes::spawn_named_runtime("stor-server".into(), None);
 
@@ -287,7 +296,8 @@ fn setup_state_sync_storage_service(
         StorageServiceNetworkEvents::new(network_service_events),
         storage_service_listener,
     );
+    let egress_rate_limits = service.get_egress_byte_rate_limits();
     storage_service_runtime.spawn(service.start());
 
-    Ok(storage_service_runtime)
+    Ok((storage_service_runtime, egress_rate_limits))
 }
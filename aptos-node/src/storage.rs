@@ -2,18 +2,40 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{anyhow, Result};
+use aptos_backup_cli::{
+    coordinators::restore::{RestoreCoordinator, RestoreCoordinatorOpt},
+    metadata::cache::MetadataCacheOpt,
+    storage::{
+        command_adapter::{config::CommandAdapterConfig, CommandAdapter},
+        local_fs::LocalFs,
+        BackupStorage,
+    },
+    utils::{GlobalRestoreOptions, RestoreRunMode},
+};
 use aptos_backup_service::start_backup_service;
-use aptos_config::{config::NodeConfig, utils::get_genesis_txn};
-use aptos_db::{fast_sync_storage_wrapper::FastSyncStorageWrapper, AptosDB};
+use aptos_config::{
+    config::{NodeConfig, RestoreFromBackupConfig, NO_OP_STORAGE_PRUNER_CONFIG},
+    utils::get_genesis_txn,
+};
+use aptos_db::{
+    fast_sync_storage_wrapper::FastSyncStorageWrapper, get_restore_handler::GetRestoreHandler,
+    AptosDB,
+};
 use aptos_executor::db_bootstrapper::maybe_bootstrap;
 use aptos_logger::{debug, info};
 use aptos_storage_interface::{DbReader, DbReaderWriter};
-use aptos_types::{ledger_info::LedgerInfoWithSignatures, waypoint::Waypoint};
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures, transaction::Version, waypoint::Waypoint,
+};
 use aptos_vm::AptosVM;
 use either::Either;
-use std::{fs, path::Path, sync::Arc, time::Instant};
+use std::{collections::HashMap, fs, path::Path, sync::Arc, time::Instant};
 use tokio::runtime::Runtime;
 
+// The files marking that the DB restore from the configured backup has started and completed
+const RESTORE_FROM_BACKUP_STARTED_FILE: &str = "restore_from_backup.started";
+const RESTORE_FROM_BACKUP_DONE_FILE: &str = "restore_from_backup.done";
+
 pub(crate) fn maybe_apply_genesis(
     db_rw: &DbReaderWriter,
     node_config: &NodeConfig,
@@ -35,6 +57,117 @@ pub(crate) fn maybe_apply_genesis(
     }
 }
 
+/// Initializes the backup storage configured to restore the DB from
+async fn init_backup_storage(config: &RestoreFromBackupConfig) -> Result<Arc<dyn BackupStorage>> {
+    Ok(
+        match (&config.local_fs_dir, &config.command_adapter_config) {
+            (Some(local_fs_dir), None) => Arc::new(LocalFs::new(local_fs_dir.clone())),
+            (None, Some(command_adapter_config)) => Arc::new(CommandAdapter::new(
+                CommandAdapterConfig::load_from_file(command_adapter_config).await?,
+            )),
+            _ => {
+                return Err(anyhow!(
+                    "Exactly one of local_fs_dir or command_adapter_config must be set!"
+                ))
+            },
+        },
+    )
+}
+
+/// Restores the epoch endings, the latest state snapshot and the transactions
+/// after it from the configured backup, using the backup restore handlers.
+/// This is only done once: an interrupted restore is resumed on the next start,
+/// but a completed one is never redone, as state sync has taken over since.
+/// A DB which already has a ledger (e.g., one synced with another bootstrapping
+/// mode) is never restored into, as the backup may not extend its ledger.
+fn maybe_restore_from_backup(node_config: &NodeConfig) -> Result<()> {
+    if !node_config
+        .state_sync
+        .state_sync_driver
+        .bootstrapping_mode
+        .is_restore_from_backup()
+    {
+        return Ok(());
+    }
+
+    let db_dir = node_config.storage.dir();
+    let done_file = db_dir.join(RESTORE_FROM_BACKUP_DONE_FILE);
+    if done_file.exists() {
+        info!("The DB has already been restored from the backup, skipping the restore.");
+        return Ok(());
+    }
+
+    let instant = Instant::now();
+    let config = &node_config.storage.restore_from_backup;
+    info!("Restoring the DB from the backup: {:?}", config);
+
+    // Open the DB to restore into. It is closed once the restore completes,
+    // before the node opens it.
+    let db = Arc::new(AptosDB::open_kv_only(
+        node_config.storage.get_dir_paths(),
+        false, /* readonly */
+        NO_OP_STORAGE_PRUNER_CONFIG,
+        node_config.storage.rocksdb_configs,
+        false, /* indexer */
+        node_config.storage.buffered_state_target_items,
+        node_config.storage.max_num_nodes_per_lru_cache_shard,
+    )?);
+
+    // Refuse to restore into a DB which already has a ledger, unless
+    // the ledger was written by an interrupted restore.
+    let started_file = db_dir.join(RESTORE_FROM_BACKUP_STARTED_FILE);
+    if !started_file.exists() {
+        if let Some(ledger_info) = db.get_latest_ledger_info_option()? {
+            return Err(anyhow!(
+                "The DB already has a ledger (at version {}), refusing to restore it from the backup! \
+                Remove the DB or use another bootstrapping mode.",
+                ledger_info.ledger_info().version()
+            ));
+        }
+        fs::write(&started_file, b"")?;
+    }
+    let restore_handler = db.get_restore_handler();
+
+    // The backup is verified against the waypoints the node trusts
+    let trusted_waypoints: HashMap<Version, Waypoint> = [
+        node_config.base.waypoint.genesis_waypoint(),
+        node_config.base.waypoint.waypoint(),
+    ]
+    .into_iter()
+    .map(|waypoint| (waypoint.version(), waypoint))
+    .collect();
+    let global_options = GlobalRestoreOptions {
+        target_version: config.target_version.unwrap_or(Version::MAX),
+        trusted_waypoints: Arc::new(trusted_waypoints),
+        run_mode: Arc::new(RestoreRunMode::Restore { restore_handler }),
+        concurrent_downloads: config.concurrent_downloads.unwrap_or_else(num_cpus::get),
+        replay_concurrency_level: config
+            .replay_concurrency_level
+            .unwrap_or_else(num_cpus::get),
+    };
+    let coordinator_opt = RestoreCoordinatorOpt {
+        metadata_cache_opt: MetadataCacheOpt::new(config.metadata_cache_dir.as_ref()),
+        replay_all: false,
+        ledger_history_start_version: None,
+        skip_epoch_endings: false,
+    };
+
+    let runtime = aptos_runtimes::spawn_named_runtime("db-restore".into(), None);
+    runtime.block_on(async {
+        let storage = init_backup_storage(config).await?;
+        RestoreCoordinator::new(coordinator_opt, global_options, storage)
+            .run()
+            .await
+    })?;
+
+    fs::write(&done_file, b"")?;
+    info!(
+        "Restored the DB from the backup in {} ms",
+        instant.elapsed().as_millis()
+    );
+    Ok(())
+}
+
 #[cfg(not(feature = "consensus-only-perf-test"))]
 pub(crate) fn bootstrap_db(
     node_config: &NodeConfig,
@@ -152,6 +285,9 @@ pub fn initialize_database_and_checkpoints(
         create_rocksdb_checkpoint_and_change_working_dir(node_config, working_dir);
     }
 
+    // Restore the database from a backup, if the node bootstraps from one
+    maybe_restore_from_backup(node_config)?;
+
     // Open the database
     let instant = Instant::now();
     let (_aptos_db, db_rw, backup_service) = bootstrap_db(node_config)?;
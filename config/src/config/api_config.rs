@@ -12,7 +12,7 @@ use crate::{
 };
 use aptos_types::{account_address::AccountAddress, chain_id::ChainId};
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use std::{collections::HashSet, net::SocketAddr};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -65,6 +65,17 @@ pub struct ApiConfig {
     ///
     /// This limits the execution length of a view function to the given gas used.
     pub max_gas_view_function: u64,
+    /// Maximum number of view functions that can be executed with the batch view API
+    pub max_view_batch_size: usize,
+    /// Maximum gas unit limit for all the view functions of a batch together
+    ///
+    /// Each view function of the batch is also limited by `max_gas_view_function`.
+    pub max_gas_view_batch: u64,
+    /// Limits on view functions and transaction simulations of requests without the API key
+    /// of a tier
+    pub execution_limits: ExecutionLimits,
+    /// Tiers of callers, identified by API key, with their own execution limits
+    pub api_tiers: Vec<ApiTier>,
     /// Optional: Maximum number of worker threads for the API.
     ///
     /// If not set, `runtime_worker_multiplier` will multiply times the number of CPU cores on the machine
@@ -99,6 +110,8 @@ pub const DEFAULT_MAX_PAGE_SIZE: u16 = 100;
 const DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE: u16 = 9999;
 const DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE: u16 = 9999;
 const DEFAULT_MAX_VIEW_GAS: u64 = 2_000_000; // We keep this value the same as the max number of gas allowed for one single transaction defined in aptos-gas.
+const DEFAULT_MAX_VIEW_BATCH_SIZE: usize = 50;
+const DEFAULT_MAX_VIEW_BATCH_GAS: u64 = 10_000_000;
 
 fn default_enabled() -> bool {
     true
@@ -131,6 +144,10 @@ impl Default for ApiConfig {
             max_account_resources_page_size: DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE,
             max_account_modules_page_size: DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE,
             max_gas_view_function: DEFAULT_MAX_VIEW_GAS,
+            max_view_batch_size: DEFAULT_MAX_VIEW_BATCH_SIZE,
+            max_gas_view_batch: DEFAULT_MAX_VIEW_BATCH_GAS,
+            execution_limits: ExecutionLimits::default(),
+            api_tiers: vec![],
             max_runtime_workers: None,
             runtime_worker_multiplier: 2,
             gas_estimation: GasEstimationConfig::default(),
@@ -200,6 +217,44 @@ impl ConfigSanitizer for ApiConfig {
             }
         }
 
+        // Verify the execution limits of the API tiers
+        let mut api_keys = HashSet::new();
+        for tier in &api_config.api_tiers {
+            for api_key in &tier.api_keys {
+                if !api_keys.insert(api_key) {
+                    return Err(Error::ConfigSanitizerFailed(
+                        sanitizer_name,
+                        format!("API key of tier {} is used by multiple tiers!", tier.name),
+                    ));
+                }
+            }
+        }
+        for limits in std::iter::once(&api_config.execution_limits)
+            .chain(api_config.api_tiers.iter().map(|tier| &tier.limits))
+        {
+            if limits.max_concurrent_view_functions == Some(0)
+                || limits.max_concurrent_simulations == Some(0)
+            {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "Execution concurrency limits must be greater than 0!".into(),
+                ));
+            }
+
+            // Timed out executions keep running until they return, so they
+            // must be bounded by a concurrency limit.
+            if (limits.view_function_timeout_ms.is_some()
+                && limits.max_concurrent_view_functions.is_none())
+                || (limits.simulation_timeout_ms.is_some()
+                    && limits.max_concurrent_simulations.is_none())
+            {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "Execution timeouts require a concurrency limit!".into(),
+                ));
+            }
+        }
+
         // Sanitize the gas estimation config
         GasEstimationConfig::sanitize(node_config, node_type, chain_id)?;
 
@@ -207,6 +262,45 @@ impl ConfigSanitizer for ApiConfig {
     }
 }
 
+/// Limits on the view functions and transaction simulations executed by the API for a tier of
+/// callers. Unset limits are not enforced, except for the gas of view functions which defaults
+/// to `max_gas_view_function`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExecutionLimits {
+    /// Maximum gas unit limit for each view function
+    pub max_gas_view_function: Option<u64>,
+    /// Maximum gas unit limit for each simulated transaction. Transactions with a higher max
+    /// gas amount are simulated with this one instead.
+    pub max_gas_simulation: Option<u64>,
+    /// Time after which a view function request is answered with an error. The execution
+    /// itself is bounded by gas, and is not interrupted: it keeps its slot of
+    /// `max_concurrent_view_functions` (which is required with a timeout) until it returns.
+    pub view_function_timeout_ms: Option<u64>,
+    /// Time after which a simulation request is answered with an error. The execution itself
+    /// is bounded by gas, and is not interrupted: it keeps its slot of
+    /// `max_concurrent_simulations` (which is required with a timeout) until it returns.
+    pub simulation_timeout_ms: Option<u64>,
+    /// Maximum number of view function requests executing at the same time, further requests
+    /// are rejected
+    pub max_concurrent_view_functions: Option<usize>,
+    /// Maximum number of simulation requests executing at the same time, further requests
+    /// are rejected
+    pub max_concurrent_simulations: Option<usize>,
+}
+
+/// A tier of API callers sharing the same execution limits
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiTier {
+    /// Name of the tier, used in errors and metrics
+    pub name: String,
+    /// API keys of the callers in this tier, sent in the `x-aptos-api-key` header
+    pub api_keys: Vec<String>,
+    /// Limits shared by all the callers of this tier
+    pub limits: ExecutionLimits,
+}
+
 // This is necessary because we can't import the EntryFunctionId type from the API types.
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -315,4 +409,52 @@ mod tests {
                 .unwrap_err();
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
+
+    #[test]
+    fn test_sanitize_duplicate_api_keys() {
+        // Create a node config with an API key shared by two tiers
+        let tier = |name: &str| ApiTier {
+            name: name.into(),
+            api_keys: vec!["key".into()],
+            limits: ExecutionLimits::default(),
+        };
+        let node_config = NodeConfig {
+            api: ApiConfig {
+                enabled: true,
+                api_tiers: vec![tier("public"), tier("partner")],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails because
+        // the tier of the API key is ambiguous.
+        let error =
+            ApiConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_timeout_without_concurrency_limit() {
+        // Create a node config with a simulation timeout but no concurrency limit
+        let node_config = NodeConfig {
+            api: ApiConfig {
+                enabled: true,
+                execution_limits: ExecutionLimits {
+                    simulation_timeout_ms: Some(1_000),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails because the
+        // timed out simulations would not be bounded.
+        let error =
+            ApiConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
 }
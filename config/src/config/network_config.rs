@@ -127,6 +127,10 @@ pub struct NetworkConfig {
     pub max_parallel_deserialization_tasks: Option<usize>,
     /// Whether or not to enable latency aware peer dialing
     pub enable_latency_aware_dialing: bool,
+    /// The weights used to prioritize outbound messages (by protocol class) on each connection
+    pub outbound_message_weights: OutboundMessageWeights,
+    /// Message capture configuration, if not specified, no messages are captured
+    pub message_capture_config: Option<MessageCaptureConfig>,
 }
 
 impl Default for NetworkConfig {
@@ -169,6 +173,8 @@ impl NetworkConfig {
             outbound_tx_buffer_size_bytes: None,
             max_parallel_deserialization_tasks: None,
             enable_latency_aware_dialing: true,
+            outbound_message_weights: OutboundMessageWeights::default(),
+            message_capture_config: None,
         };
 
         // Configure the number of parallel deserialization tasks
@@ -351,6 +357,7 @@ pub enum DiscoveryMethod {
     Onchain,
     File(FileDiscovery),
     Rest(RestDiscovery),
+    Seeds(SeedDiscovery),
     None,
 }
 
@@ -368,6 +375,41 @@ pub struct RestDiscovery {
     pub interval_secs: u64,
 }
 
+/// Discovers seed peers using DNS and a static list of seed addresses. Seeds
+/// are periodically re-resolved, and seeds that fail health checks or that
+/// present mismatched identities are filtered out.
+///
+/// Each DNS name is resolved as an SRV record (e.g., `_aptos-seed._tcp.example.com`).
+/// The identity of each SRV target is specified by the TXT records of the target,
+/// i.e., `aptos-pubkey=<x25519 public key>` and (optionally) `aptos-peer-id=<peer id>`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SeedDiscovery {
+    /// The DNS (SRV) names to resolve seeds from
+    pub dns_names: Vec<String>,
+    /// Static seed addresses. Each address must contain a `noise-ik` public key.
+    pub static_addrs: Vec<NetworkAddress>,
+    /// The interval (secs) at which seeds are re-resolved
+    pub interval_secs: u64,
+    /// Whether or not to filter out seeds that fail health checks (i.e., seeds
+    /// that can't complete a noise handshake with their advertised public key)
+    pub enable_health_checks: bool,
+    /// The timeout (ms) for each seed health check
+    pub health_check_timeout_ms: u64,
+}
+
+impl Default for SeedDiscovery {
+    fn default() -> Self {
+        Self {
+            dns_names: vec![],
+            static_addrs: vec![],
+            interval_secs: 300, // 5 minutes
+            enable_health_checks: true,
+            health_check_timeout_ms: 2_000, // 2 seconds
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct RateLimitConfig {
@@ -392,6 +434,66 @@ impl Default for RateLimitConfig {
     }
 }
 
+/// The relative weights used to schedule outbound messages across protocol
+/// classes on a single connection. When several classes have pending messages,
+/// a class with weight N sends up to N bytes for every byte sent by a class
+/// with weight 1. A weight of 0 is treated as 1 (i.e., no class starves).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutboundMessageWeights {
+    /// Consensus (including DKG, JWK consensus and consensus observer) messages
+    pub consensus: u32,
+    /// Mempool messages
+    pub mempool: u32,
+    /// State sync and storage service messages
+    pub state_sync: u32,
+    /// Netbench messages
+    pub netbench: u32,
+    /// All other messages (e.g., health checks and peer monitoring)
+    pub other: u32,
+}
+
+impl Default for OutboundMessageWeights {
+    fn default() -> Self {
+        Self {
+            consensus: 8,
+            mempool: 4,
+            state_sync: 2,
+            netbench: 1,
+            other: 4,
+        }
+    }
+}
+
+/// The configuration for capturing the messages exchanged with peers (e.g., to
+/// debug misbehaving peers). Captured messages are written to a set of rotating
+/// files in the capture directory. Once the maximum number of files is reached,
+/// the oldest file is deleted.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MessageCaptureConfig {
+    /// The directory in which to write the capture files
+    pub capture_dir: PathBuf,
+    /// The maximum size of a single capture file (before rotating to a new file)
+    pub max_file_size_bytes: u64,
+    /// The maximum number of capture files to retain
+    pub max_num_files: usize,
+    /// The maximum number of bytes of captured messages waiting to be written to
+    /// disk. If the writer falls behind, new messages are dropped (and not captured).
+    pub max_pending_bytes: u64,
+}
+
+impl Default for MessageCaptureConfig {
+    fn default() -> Self {
+        Self {
+            capture_dir: PathBuf::from("network_capture"),
+            max_file_size_bytes: 64 * 1024 * 1024, // 64 MiB
+            max_num_files: 16,
+            max_pending_bytes: 64 * 1024 * 1024, // 64 MiB
+        }
+    }
+}
+
 pub type PeerSet = HashMap<PeerId, Peer>;
 
 // TODO: Combine with RoleType?
@@ -532,4 +634,35 @@ mod tests {
         network_config.configure_num_deserialization_tasks();
         assert_eq!(network_config.max_parallel_deserialization_tasks, Some(1));
     }
+    #[test]
+    fn test_seed_discovery_deserialization() {
+        // Deserialize a seed discovery method (with some fields missing)
+        let discovery_method: DiscoveryMethod = serde_yaml::from_str(
+            r#"
+            seeds:
+                dns_names:
+                    - "_aptos-seed._tcp.example.com"
+                static_addrs:
+                    - "/ip4/1.2.3.4/tcp/6182/noise-ik/080e287879c918794170e258bfaddd75acac5b3e350419044655e4983a487120/handshake/0"
+                interval_secs: 60
+            "#,
+        )
+        .unwrap();
+
+        // Verify the missing fields use the default values
+        match discovery_method {
+            DiscoveryMethod::Seeds(seed_discovery) => {
+                assert_eq!(seed_discovery.dns_names, vec![
+                    "_aptos-seed._tcp.example.com".to_string()
+                ]);
+                assert_eq!(seed_discovery.static_addrs.len(), 1);
+                assert_eq!(seed_discovery.interval_secs, 60);
+                assert_eq!(
+                    seed_discovery.enable_health_checks,
+                    SeedDiscovery::default().enable_health_checks
+                );
+            },
+            discovery_method => panic!("Unexpected discovery method: {:?}", discovery_method),
+        }
+    }
 }
@@ -11,7 +11,8 @@ use crate::{
         persistable_config::PersistableConfig, utils::RootPath, AdminServiceConfig, ApiConfig,
         BaseConfig, ConsensusConfig, Error, ExecutionConfig, IndexerConfig, IndexerGrpcConfig,
         InspectionServiceConfig, LoggerConfig, MempoolConfig, NetworkConfig,
-        PeerMonitoringServiceConfig, SafetyRulesTestConfig, StateSyncConfig, StorageConfig,
+        PeerMonitoringServiceConfig, PeerReputationConfig, SafetyRulesTestConfig, StateSyncConfig,
+        StorageConfig,
     },
     network_id::NetworkId,
 };
@@ -74,6 +75,8 @@ pub struct NodeConfig {
     pub node_startup: NodeStartupConfig,
     #[serde(default)]
     pub peer_monitoring_service: PeerMonitoringServiceConfig,
+    #[serde(default)]
+    pub peer_reputation: PeerReputationConfig,
     /// In a randomness stall, set this to be on-chain `RandomnessConfigSeqNum` + 1.
     /// Once enough nodes restarted with the new value, the chain should unblock with randomness disabled.
     #[serde(default)]
@@ -0,0 +1,115 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::{
+    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
+};
+use aptos_types::chain_id::ChainId;
+use serde::{Deserialize, Serialize};
+
+/// The configuration for the shared peer reputation service. Applications
+/// (e.g., consensus, mempool and state sync) report peer misbehavior to the
+/// service, which accumulates a penalty score for each peer. Scores decay over
+/// time, and peers that exceed the configured thresholds are disconnected or
+/// temporarily banned.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PeerReputationConfig {
+    pub ban_duration_secs: u64,     // The duration (secs) of automatic bans
+    pub ban_list_file_name: String, // The file (in the data dir) used to persist the ban list
+    pub ban_penalty_threshold: u64, // The penalty at which a peer is temporarily banned
+    pub disconnect_penalty_threshold: u64, // The penalty at which a peer is disconnected
+    pub enable_automatic_bans: bool, // Whether or not to ban and disconnect peers automatically
+    pub penalty_half_life_secs: u64, // The time (secs) it takes for a penalty to decay by half
+}
+
+impl Default for PeerReputationConfig {
+    fn default() -> Self {
+        Self {
+            ban_duration_secs: 3600, // 1 hour
+            ban_list_file_name: "peer_ban_list.json".into(),
+            ban_penalty_threshold: 500,
+            disconnect_penalty_threshold: 200,
+            enable_automatic_bans: false,
+            penalty_half_life_secs: 600, // 10 minutes
+        }
+    }
+}
+
+impl ConfigSanitizer for PeerReputationConfig {
+    fn sanitize(
+        node_config: &NodeConfig,
+        _node_type: NodeType,
+        _chain_id: Option<ChainId>,
+    ) -> Result<(), Error> {
+        let sanitizer_name = Self::get_sanitizer_name();
+        let peer_reputation_config = &node_config.peer_reputation;
+
+        // Verify that penalties decay
+        if peer_reputation_config.penalty_half_life_secs == 0 {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The penalty half life must be greater than 0!".to_string(),
+            ));
+        }
+
+        // Verify that peers are disconnected before they are banned
+        if peer_reputation_config.disconnect_penalty_threshold
+            > peer_reputation_config.ban_penalty_threshold
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                format!(
+                    "The disconnect penalty threshold ({}) must not exceed the ban penalty threshold ({})!",
+                    peer_reputation_config.disconnect_penalty_threshold,
+                    peer_reputation_config.ban_penalty_threshold
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_penalty_half_life() {
+        // Create a peer reputation config without penalty decay
+        let node_config = NodeConfig {
+            peer_reputation: PeerReputationConfig {
+                penalty_half_life_secs: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that the config fails sanitization
+        let error =
+            PeerReputationConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_penalty_thresholds() {
+        // Create a peer reputation config with a ban threshold below the disconnect threshold
+        let node_config = NodeConfig {
+            peer_reputation: PeerReputationConfig {
+                ban_penalty_threshold: 100,
+                disconnect_penalty_threshold: 101,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that the config fails sanitization
+        let error =
+            PeerReputationConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Verify that the default config passes sanitization
+        PeerReputationConfig::sanitize(&NodeConfig::default(), NodeType::Validator, None).unwrap();
+    }
+}
@@ -44,6 +44,10 @@ pub enum BootstrappingMode {
     ExecuteTransactionsFromGenesis,
     /// Executes transactions or applies outputs from genesis (whichever is faster)
     ExecuteOrApplyFromGenesis,
+    /// Restores the epoch endings, the state snapshot and the transactions from a local
+    /// backup (see `storage.restore_from_backup`), and then executes transactions or
+    /// applies outputs from the restored version (whichever is faster)
+    RestoreFromBackup,
 }
 
 impl BootstrappingMode {
@@ -57,6 +61,7 @@ impl BootstrappingMode {
                 "execute_transactions_from_genesis"
             },
             BootstrappingMode::ExecuteOrApplyFromGenesis => "execute_or_apply_from_genesis",
+            BootstrappingMode::RestoreFromBackup => "restore_from_backup",
         }
     }
 
@@ -64,6 +69,11 @@ impl BootstrappingMode {
     pub fn is_fast_sync(&self) -> bool {
         *self == BootstrappingMode::DownloadLatestStates
     }
+
+    /// Returns true iff the bootstrapping mode restores from a local backup
+    pub fn is_restore_from_backup(&self) -> bool {
+        *self == BootstrappingMode::RestoreFromBackup
+    }
 }
 
 /// The continuous syncing mode determines how the node will stay up-to-date
@@ -106,6 +116,9 @@ pub struct StateSyncDriverConfig {
     pub fallback_to_output_syncing_secs: u64,
     /// The interval (ms) at which to check state sync progress
     pub progress_check_interval_ms: u64,
+    /// The maximum number of state snapshot partitions to download concurrently
+    /// when fast syncing. If 1, the snapshot is downloaded through a single stream.
+    pub max_concurrent_state_snapshot_partitions: u64,
     /// The maximum time (secs) to wait for connections from peers before auto-bootstrapping
     pub max_connection_deadline_secs: u64,
     /// The maximum number of notifications to process per driver loop
@@ -120,6 +133,8 @@ pub struct StateSyncDriverConfig {
     pub max_stream_wait_time_ms: u64,
     /// The version lag we'll tolerate before snapshot syncing
     pub num_versions_to_skip_snapshot_sync: u64,
+    /// The number of state values in each state snapshot partition
+    pub state_snapshot_partition_size: u64,
 }
 
 /// The default state sync driver config will be the one that gets (and keeps)
@@ -133,6 +148,7 @@ impl Default for StateSyncDriverConfig {
             enable_auto_bootstrapping: false,
             fallback_to_output_syncing_secs: 180, // 3 minutes
             progress_check_interval_ms: 100,
+            max_concurrent_state_snapshot_partitions: 1,
             max_connection_deadline_secs: 10,
             max_consecutive_stream_notifications: 10,
             max_num_stream_timeouts: 12,
@@ -140,6 +156,7 @@ impl Default for StateSyncDriverConfig {
             max_pending_mempool_notifications: 100,
             max_stream_wait_time_ms: 5000,
             num_versions_to_skip_snapshot_sync: 100_000_000, // At 5k TPS, this allows a node to fail for about 6 hours.
+            state_snapshot_partition_size: 1_000_000,
         }
     }
 }
@@ -147,6 +164,12 @@ impl Default for StateSyncDriverConfig {
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct StorageServiceConfig {
+    /// Maximum number of bytes per second to send to all peers (0 disables the limit)
+    pub max_egress_bytes_per_sec: u64,
+    /// Maximum number of bytes per second to send to each peer (0 disables the limit)
+    pub max_egress_bytes_per_sec_per_peer: u64,
+    /// Maximum time (ms) a response may wait for egress budget before it is dropped
+    pub max_egress_wait_time_ms: u64,
     /// Maximum number of epoch ending ledger infos per chunk
     pub max_epoch_chunk_size: u64,
     /// Maximum number of invalid requests per peer
@@ -161,6 +184,11 @@ pub struct StorageServiceConfig {
     pub max_num_active_subscriptions: u64,
     /// Maximum period (ms) of pending optimistic fetch requests
     pub max_optimistic_fetch_period_ms: u64,
+    /// Maximum number of (estimated) bytes of pending responses to all peers,
+    /// i.e., responses being read from storage or waiting for egress budget
+    pub max_pending_egress_bytes: u64,
+    /// Maximum number of (estimated) bytes of pending responses to each peer
+    pub max_pending_egress_bytes_per_peer: u64,
     /// Maximum number of state keys and values per chunk
     pub max_state_chunk_size: u64,
     /// Maximum period (ms) of pending subscription requests
@@ -180,6 +208,9 @@ pub struct StorageServiceConfig {
 impl Default for StorageServiceConfig {
     fn default() -> Self {
         Self {
+            max_egress_bytes_per_sec: 0,
+            max_egress_bytes_per_sec_per_peer: 0,
+            max_egress_wait_time_ms: 10_000, // 10 seconds (the network's inbound RPC timeout)
             max_epoch_chunk_size: MAX_EPOCH_CHUNK_SIZE,
             max_invalid_requests_per_peer: 500,
             max_lru_cache_size: 500, // At ~0.6MiB per chunk, this should take no more than 0.5GiB
@@ -187,6 +218,8 @@ impl Default for StorageServiceConfig {
             max_network_chunk_bytes: MAX_MESSAGE_SIZE as u64,
             max_num_active_subscriptions: 30,
             max_optimistic_fetch_period_ms: 5000, // 5 seconds
+            max_pending_egress_bytes: 2 * 1024 * 1024 * 1024, // 2 GiB
+            max_pending_egress_bytes_per_peer: 256 * 1024 * 1024, // 256 MiB
             max_state_chunk_size: MAX_STATE_CHUNK_SIZE,
             max_subscription_period_ms: 30_000, // 30 seconds
             max_transaction_chunk_size: MAX_TRANSACTION_CHUNK_SIZE,
@@ -404,10 +437,18 @@ pub struct AptosDataClientConfig {
     pub latency_monitor_loop_interval_ms: u64,
     /// Maximum number of epoch ending ledger infos per chunk
     pub max_epoch_chunk_size: u64,
+    /// Maximum number of bytes per second to receive from all peers (0 disables the limit).
+    /// The budget for each response (see `max_response_bytes`) is reserved before the
+    /// request is sent, and any unused bytes are refunded once the response arrives.
+    pub max_ingress_bytes_per_sec: u64,
+    /// Maximum number of bytes per second to receive from each peer (0 disables the limit)
+    pub max_ingress_bytes_per_sec_per_peer: u64,
     /// Maximum number of output reductions before transactions are returned
     pub max_num_output_reductions: u64,
     /// Maximum lag (in seconds) we'll tolerate when sending optimistic fetch requests
     pub max_optimistic_fetch_lag_secs: u64,
+    /// Maximum number of bytes expected in a response (used to reserve ingress budget)
+    pub max_response_bytes: u64,
     /// Maximum timeout (in ms) when waiting for a response (after exponential increases)
     pub max_response_timeout_ms: u64,
     /// Maximum number of state keys and values per chunk
@@ -437,9 +478,12 @@ impl Default for AptosDataClientConfig {
             latency_filtering_config: AptosLatencyFilteringConfig::default(),
             latency_monitor_loop_interval_ms: 100,
             max_epoch_chunk_size: MAX_EPOCH_CHUNK_SIZE,
+            max_ingress_bytes_per_sec: 0,
+            max_ingress_bytes_per_sec_per_peer: 0,
             max_num_output_reductions: 0,
             max_optimistic_fetch_lag_secs: 20, // 20 seconds
-            max_response_timeout_ms: 60_000,   // 60 seconds
+            max_response_bytes: MAX_MESSAGE_SIZE as u64,
+            max_response_timeout_ms: 60_000, // 60 seconds
             max_state_chunk_size: MAX_STATE_CHUNK_SIZE,
             max_subscription_lag_secs: 20, // 20 seconds
             max_transaction_chunk_size: MAX_TRANSACTION_CHUNK_SIZE,
@@ -483,6 +527,31 @@ impl ConfigSanitizer for StateSyncDriverConfig {
             ));
         }
 
+        // Verify that the state snapshot partitions are valid
+        if state_sync_driver_config.max_concurrent_state_snapshot_partitions == 0
+            || state_sync_driver_config.state_snapshot_partition_size == 0
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The number and size of the state snapshot partitions must be non-zero!"
+                    .to_string(),
+            ));
+        }
+
+        // Verify that a backup is configured for nodes restoring from one
+        if state_sync_driver_config
+            .bootstrapping_mode
+            .is_restore_from_backup()
+            && !node_config.storage.restore_from_backup.has_backup_storage()
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "Exactly one of local_fs_dir or command_adapter_config must be set in \
+                storage.restore_from_backup to bootstrap from a backup!"
+                    .to_string(),
+            ));
+        }
+
         Ok(())
     }
 }
@@ -788,6 +857,30 @@ mod tests {
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
 
+    #[test]
+    fn test_sanitize_restore_from_backup() {
+        // Create a node config that restores from a backup, without a backup storage
+        let mut node_config = NodeConfig {
+            state_sync: StateSyncConfig {
+                state_sync_driver: StateSyncDriverConfig {
+                    bootstrapping_mode: BootstrappingMode::RestoreFromBackup,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that sanitization fails
+        let error =
+            StateSyncConfig::sanitize(&node_config, NodeType::PublicFullnode, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Configure a backup storage and verify that sanitization succeeds
+        node_config.storage.restore_from_backup.local_fs_dir = Some("/backup".into());
+        StateSyncConfig::sanitize(&node_config, NodeType::PublicFullnode, None).unwrap();
+    }
+
     /// Creates and returns a node config with the syncing modes set to execution
     fn create_execution_mode_config() -> NodeConfig {
         NodeConfig {
@@ -8,7 +8,7 @@ use crate::{
 };
 use anyhow::{ensure, Result};
 use aptos_logger::warn;
-use aptos_types::chain_id::ChainId;
+use aptos_types::{chain_id::ChainId, transaction::Version};
 use arr_macro::arr;
 use number_range::NumberRangeOptions;
 use serde::{Deserialize, Serialize};
@@ -175,6 +175,35 @@ pub struct StorageConfig {
     /// If not specificed, will use `dir` as default.
     /// Only allowed when sharding is enabled.
     pub db_path_overrides: Option<DbPathConfig>,
+    /// The backup to restore the DB from, when bootstrapping from a backup
+    pub restore_from_backup: RestoreFromBackupConfig,
+}
+
+/// The location of a backup (as produced by the backup-cli) to restore the DB from before
+/// state sync starts, and how to restore it.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RestoreFromBackupConfig {
+    /// Directory of a LocalFs backup storage
+    pub local_fs_dir: Option<PathBuf>,
+    /// Config file of a CommandAdapter backup storage
+    pub command_adapter_config: Option<PathBuf>,
+    /// Directory to cache the backup metadata in (defaults to a temporary directory)
+    pub metadata_cache_dir: Option<PathBuf>,
+    /// The version to restore up to (defaults to the latest version in the backup)
+    pub target_version: Option<Version>,
+    /// The number of concurrent downloads from the backup storage (defaults to the number of CPUs)
+    pub concurrent_downloads: Option<usize>,
+    /// The concurrency level used to replay the transactions after the state snapshot
+    /// (defaults to the number of CPUs)
+    pub replay_concurrency_level: Option<usize>,
+}
+
+impl RestoreFromBackupConfig {
+    /// Returns true iff exactly one backup storage location is configured
+    pub fn has_backup_storage(&self) -> bool {
+        self.local_fs_dir.is_some() != self.command_adapter_config.is_some()
+    }
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
@@ -322,6 +351,7 @@ impl Default for StorageConfig {
             rocksdb_configs: RocksdbConfigs::default(),
             enable_indexer: false,
             db_path_overrides: None,
+            restore_from_backup: RestoreFromBackupConfig::default(),
             buffered_state_target_items: BUFFERED_STATE_TARGET_ITEMS,
             max_num_nodes_per_lru_cache_shard: DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
         }
@@ -76,7 +76,10 @@ use aptos_global_constants::CONSENSUS_KEY;
 use aptos_infallible::{duration_since_epoch, Mutex};
 use aptos_logger::prelude::*;
 use aptos_mempool::QuorumStoreRequest;
-use aptos_network::{application::interface::NetworkClient, protocols::network::Event};
+use aptos_network::{
+    application::{interface::NetworkClient, reputation::MisbehaviorSeverity},
+    protocols::network::Event,
+};
 use aptos_safety_rules::SafetyRulesManager;
 use aptos_secure_storage::{KVStorage, Storage};
 use aptos_types::{
@@ -1401,6 +1404,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                 self.config.quorum_store.batch_expiry_gap_when_init_usecs;
             let payload_manager = self.payload_manager.clone();
             let pending_blocks = self.pending_blocks.clone();
+            let network_sender = self.network_sender.clone();
             self.bounded_executor
                 .spawn(async move {
                     match monitor!(
@@ -1433,6 +1437,13 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                                 error = ?e,
                                 unverified_event = unverified_event
                             );
+                            if peer_id != my_peer_id {
+                                network_sender.report_misbehavior(
+                                    peer_id,
+                                    MisbehaviorSeverity::Medium,
+                                    "Invalid consensus message",
+                                );
+                            }
                         },
                     }
                 })
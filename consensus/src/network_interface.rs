@@ -22,7 +22,9 @@ use aptos_consensus_types::{
     vote_msg::VoteMsg,
 };
 use aptos_network::{
-    application::{error::Error, interface::NetworkClientInterface},
+    application::{
+        error::Error, interface::NetworkClientInterface, reputation::MisbehaviorSeverity,
+    },
     ProtocolId,
 };
 use aptos_types::{epoch_change::EpochChangeProof, PeerId};
@@ -211,4 +213,14 @@ impl<NetworkClient: NetworkClientInterface<ConsensusMsg>> ConsensusNetworkClient
         self.network_client
             .sort_peers_by_latency(NetworkId::Validator, peers);
     }
+
+    /// Reports a misbehaving peer to the peer reputation service. Note: peers
+    /// on the validator network are never disconnected or banned automatically.
+    pub fn report_misbehavior(&self, peer: PeerId, severity: MisbehaviorSeverity, reason: &str) {
+        let peer_network_id = self.get_peer_network_id_for_peer(peer);
+        self.network_client
+            .get_peers_and_metadata()
+            .get_peer_reputation()
+            .report_misbehavior(peer_network_id, severity, reason);
+    }
 }
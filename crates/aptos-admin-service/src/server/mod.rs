@@ -7,6 +7,8 @@ use aptos_consensus::{
 };
 use aptos_infallible::RwLock;
 use aptos_logger::info;
+use aptos_network::application::reputation::PeerReputation;
+use aptos_rate_limiter::byte_rate_limit::ByteRateLimits;
 use aptos_storage_interface::DbReaderWriter;
 use aptos_system_utils::utils::reply_with_status;
 #[cfg(target_os = "linux")]
@@ -26,6 +28,8 @@ use std::{
 use tokio::runtime::Runtime;
 
 mod consensus;
+mod network;
+mod state_sync;
 
 #[derive(Default)]
 pub struct Context {
@@ -34,6 +38,8 @@ pub struct Context {
     aptos_db: RwLock<Option<Arc<DbReaderWriter>>>,
     consensus_db: RwLock<Option<Arc<StorageWriteProxy>>>,
     quorum_store_db: RwLock<Option<Arc<QuorumStoreDB>>>,
+    state_sync_rate_limits: RwLock<Option<(Arc<ByteRateLimits>, Arc<ByteRateLimits>)>>,
+    peer_reputation: RwLock<Option<Arc<PeerReputation>>>,
 }
 
 impl Context {
@@ -49,6 +55,18 @@ impl Context {
         *self.consensus_db.write() = Some(consensus_db);
         *self.quorum_store_db.write() = Some(quorum_store_db);
     }
+
+    fn set_state_sync_rate_limits(
+        &self,
+        ingress_limits: Arc<ByteRateLimits>,
+        egress_limits: Arc<ByteRateLimits>,
+    ) {
+        *self.state_sync_rate_limits.write() = Some((ingress_limits, egress_limits));
+    }
+
+    fn set_peer_reputation(&self, peer_reputation: Arc<PeerReputation>) {
+        *self.peer_reputation.write() = Some(peer_reputation);
+    }
 }
 
 pub struct AdminService {
@@ -107,6 +125,19 @@ impl AdminService {
             .set_consensus_dbs(consensus_db, quorum_store_db)
     }
 
+    pub fn set_state_sync_rate_limits(
+        &self,
+        ingress_limits: Arc<ByteRateLimits>,
+        egress_limits: Arc<ByteRateLimits>,
+    ) {
+        self.context
+            .set_state_sync_rate_limits(ingress_limits, egress_limits)
+    }
+
+    pub fn set_peer_reputation(&self, peer_reputation: Arc<PeerReputation>) {
+        self.context.set_peer_reputation(peer_reputation)
+    }
+
     fn start(&self, address: SocketAddr, enabled: bool) {
         let context = self.context.clone();
         self.runtime.spawn(async move {
@@ -210,6 +241,38 @@ impl AdminService {
                     ))
                 }
             },
+            (hyper::Method::GET, "/debug/state_sync/rate_limits") => {
+                let state_sync_rate_limits = context.state_sync_rate_limits.read().clone();
+                if let Some((ingress_limits, egress_limits)) = state_sync_rate_limits {
+                    state_sync::handle_rate_limits_request(req, ingress_limits, egress_limits).await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "State sync rate limits are not available.",
+                    ))
+                }
+            },
+            (hyper::Method::GET, "/debug/network/peer_reputation")
+            | (hyper::Method::GET, "/debug/network/ban_peer")
+            | (hyper::Method::GET, "/debug/network/unban_peer") => {
+                let peer_reputation = context.peer_reputation.read().clone();
+                if let Some(peer_reputation) = peer_reputation {
+                    match req.uri().path() {
+                        "/debug/network/ban_peer" => {
+                            network::handle_ban_peer_request(req, peer_reputation).await
+                        },
+                        "/debug/network/unban_peer" => {
+                            network::handle_unban_peer_request(req, peer_reputation).await
+                        },
+                        _ => network::handle_peer_reputation_request(req, peer_reputation).await,
+                    }
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Peer reputation is not available.",
+                    ))
+                }
+            },
             _ => Ok(reply_with_status(StatusCode::NOT_FOUND, "Not found.")),
         }
     }
@@ -0,0 +1,118 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::info;
+use aptos_network::application::reputation::PeerReputation;
+use aptos_system_utils::utils::{reply_with, reply_with_status};
+use aptos_types::PeerId;
+use hyper::{Body, Request, Response, StatusCode};
+use std::{borrow::Cow, collections::HashMap, fmt::Write, sync::Arc, time::Duration};
+
+/// Returns the current peer bans and the penalties of all misbehaving peers
+pub async fn handle_peer_reputation_request(
+    _req: Request<Body>,
+    peer_reputation: Arc<PeerReputation>,
+) -> hyper::Result<Response<Body>> {
+    let mut body = String::new();
+
+    let banned_peers = peer_reputation.get_banned_peers();
+    let _ = writeln!(body, "Banned peers ({}):", banned_peers.len());
+    for peer_ban in banned_peers {
+        let expiration = match peer_ban.expiration_unix_secs {
+            Some(expiration_unix_secs) => format!("expires at unix secs {expiration_unix_secs}"),
+            None => "permanent".into(),
+        };
+        let network = match peer_ban.network_id {
+            Some(network_id) => network_id.to_string(),
+            None => "all networks".into(),
+        };
+        let _ = writeln!(
+            body,
+            "  {} ({}, {}): {}",
+            peer_ban.peer_id, network, expiration, peer_ban.reason
+        );
+    }
+
+    let penalties = peer_reputation.get_penalties();
+    let _ = writeln!(body, "Peer penalties ({}):", penalties.len());
+    for (peer_network_id, penalty) in penalties {
+        let _ = writeln!(body, "  {peer_network_id}: {penalty:.2}");
+    }
+
+    Ok(reply_with(vec![], body))
+}
+
+/// Bans the given peer (on all networks).
+///
+/// Supported parameters: `peer_id` (required), `duration_secs` (if missing,
+/// the ban is permanent) and `reason`.
+pub async fn handle_ban_peer_request(
+    req: Request<Body>,
+    peer_reputation: Arc<PeerReputation>,
+) -> hyper::Result<Response<Body>> {
+    let query = req.uri().query().unwrap_or("");
+    let query_pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).collect();
+
+    let peer_id = match parse_peer_id(&query_pairs) {
+        Ok(peer_id) => peer_id,
+        Err(err) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, err)),
+    };
+    let ban_duration = match query_pairs.get("duration_secs") {
+        Some(val) => match val.parse() {
+            Ok(duration_secs) => Some(Duration::from_secs(duration_secs)),
+            Err(err) => {
+                return Ok(reply_with_status(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid value for duration_secs: {err}"),
+                ))
+            },
+        },
+        None => None,
+    };
+    let reason = query_pairs
+        .get("reason")
+        .map_or("Manual ban via the admin service".into(), |reason| {
+            reason.to_string()
+        });
+
+    info!("Banning peer {peer_id} via the admin service: {reason}");
+    peer_reputation.ban_peer(peer_id, ban_duration, reason);
+
+    Ok(reply_with(vec![], format!("Banned peer {peer_id}.\n")))
+}
+
+/// Removes the ban (and resets the penalties) for the given peer.
+///
+/// Supported parameters: `peer_id` (required).
+pub async fn handle_unban_peer_request(
+    req: Request<Body>,
+    peer_reputation: Arc<PeerReputation>,
+) -> hyper::Result<Response<Body>> {
+    let query = req.uri().query().unwrap_or("");
+    let query_pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).collect();
+
+    let peer_id = match parse_peer_id(&query_pairs) {
+        Ok(peer_id) => peer_id,
+        Err(err) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, err)),
+    };
+
+    info!("Unbanning peer {peer_id} via the admin service.");
+    if peer_reputation.unban_peer(&peer_id) {
+        Ok(reply_with(vec![], format!("Unbanned peer {peer_id}.\n")))
+    } else {
+        Ok(reply_with(
+            vec![],
+            format!("Peer {peer_id} was not banned. Its penalties were reset.\n"),
+        ))
+    }
+}
+
+/// Parses the (required) peer id from the given query pairs
+fn parse_peer_id(query_pairs: &HashMap<Cow<str>, Cow<str>>) -> Result<PeerId, String> {
+    match query_pairs.get("peer_id") {
+        Some(val) => val
+            .parse()
+            .map_err(|err| format!("Invalid value for peer_id: {err}")),
+        None => Err("Missing required parameter: peer_id".into()),
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::info;
+use aptos_rate_limiter::byte_rate_limit::ByteRateLimits;
+use aptos_system_utils::utils::{reply_with, reply_with_status};
+use hyper::{Body, Request, Response, StatusCode};
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+/// Returns the current state sync byte rate limits. If the request contains
+/// any limits (as query parameters), those limits are updated first.
+/// A limit of 0 disables the corresponding rate limiting.
+///
+/// Supported parameters: `ingress_bytes_per_sec`, `ingress_bytes_per_sec_per_peer`,
+/// `egress_bytes_per_sec` and `egress_bytes_per_sec_per_peer`.
+pub async fn handle_rate_limits_request(
+    req: Request<Body>,
+    ingress_limits: Arc<ByteRateLimits>,
+    egress_limits: Arc<ByteRateLimits>,
+) -> hyper::Result<Response<Body>> {
+    let query = req.uri().query().unwrap_or("");
+    let query_pairs: HashMap<_, _> = url::form_urlencoded::parse(query.as_bytes()).collect();
+
+    // Parse the new limits (defaulting to the existing limits)
+    let (
+        ingress_bytes_per_sec,
+        ingress_bytes_per_sec_per_peer,
+        egress_bytes_per_sec,
+        egress_bytes_per_sec_per_peer,
+    ) = match (|| {
+        Ok::<_, String>((
+            parse_limit(
+                &query_pairs,
+                "ingress_bytes_per_sec",
+                ingress_limits.max_bytes_per_sec(),
+            )?,
+            parse_limit(
+                &query_pairs,
+                "ingress_bytes_per_sec_per_peer",
+                ingress_limits.max_bytes_per_sec_per_key(),
+            )?,
+            parse_limit(
+                &query_pairs,
+                "egress_bytes_per_sec",
+                egress_limits.max_bytes_per_sec(),
+            )?,
+            parse_limit(
+                &query_pairs,
+                "egress_bytes_per_sec_per_peer",
+                egress_limits.max_bytes_per_sec_per_key(),
+            )?,
+        ))
+    })() {
+        Ok(limits) => limits,
+        Err(err) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, err)),
+    };
+
+    // Update the limits
+    ingress_limits.update(ingress_bytes_per_sec, ingress_bytes_per_sec_per_peer);
+    egress_limits.update(egress_bytes_per_sec, egress_bytes_per_sec_per_peer);
+    info!(
+        "State sync rate limits: ingress {ingress_bytes_per_sec} bytes/s \
+        ({ingress_bytes_per_sec_per_peer} bytes/s per peer), egress {egress_bytes_per_sec} bytes/s \
+        ({egress_bytes_per_sec_per_peer} bytes/s per peer)."
+    );
+
+    Ok(reply_with(
+        vec![],
+        format!(
+            "ingress_bytes_per_sec: {ingress_bytes_per_sec}\n\
+            ingress_bytes_per_sec_per_peer: {ingress_bytes_per_sec_per_peer}\n\
+            egress_bytes_per_sec: {egress_bytes_per_sec}\n\
+            egress_bytes_per_sec_per_peer: {egress_bytes_per_sec_per_peer}\n"
+        ),
+    ))
+}
+
+/// Parses the limit with the given name, or returns the existing limit if it's missing
+fn parse_limit(
+    query_pairs: &HashMap<Cow<str>, Cow<str>>,
+    name: &str,
+    existing_limit: u64,
+) -> Result<u64, String> {
+    match query_pairs.get(name) {
+        Some(val) => val
+            .parse()
+            .map_err(|err| format!("Invalid value for {name}: {err}")),
+        None => Ok(existing_limit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_rate_limits() {
+        let ingress_limits = Arc::new(ByteRateLimits::new(1, 2));
+        let egress_limits = Arc::new(ByteRateLimits::new(3, 4));
+
+        // Send a request without any parameters and verify the limits are unchanged
+        let (status, body) = send_request("", ingress_limits.clone(), egress_limits.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            "ingress_bytes_per_sec: 1\ningress_bytes_per_sec_per_peer: 2\n\
+            egress_bytes_per_sec: 3\negress_bytes_per_sec_per_peer: 4\n"
+        );
+        assert_eq!(ingress_limits.max_bytes_per_sec(), 1);
+        assert_eq!(egress_limits.max_bytes_per_sec_per_key(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_update_rate_limits() {
+        let ingress_limits = Arc::new(ByteRateLimits::new(1, 2));
+        let egress_limits = Arc::new(ByteRateLimits::new(3, 4));
+
+        // Update a subset of the limits and verify the others are unchanged
+        let (status, body) = send_request(
+            "ingress_bytes_per_sec_per_peer=20&egress_bytes_per_sec=0",
+            ingress_limits.clone(),
+            egress_limits.clone(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            "ingress_bytes_per_sec: 1\ningress_bytes_per_sec_per_peer: 20\n\
+            egress_bytes_per_sec: 0\negress_bytes_per_sec_per_peer: 4\n"
+        );
+        assert_eq!(ingress_limits.max_bytes_per_sec(), 1);
+        assert_eq!(ingress_limits.max_bytes_per_sec_per_key(), 20);
+        assert_eq!(egress_limits.max_bytes_per_sec(), 0);
+        assert_eq!(egress_limits.max_bytes_per_sec_per_key(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_rate_limits() {
+        let ingress_limits = Arc::new(ByteRateLimits::new(1, 2));
+        let egress_limits = Arc::new(ByteRateLimits::new(3, 4));
+
+        // Send an invalid limit and verify that no limits are updated
+        let (status, body) = send_request(
+            "ingress_bytes_per_sec=10&egress_bytes_per_sec=-1",
+            ingress_limits.clone(),
+            egress_limits.clone(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.starts_with("Invalid value for egress_bytes_per_sec"));
+        assert_eq!(ingress_limits.max_bytes_per_sec(), 1);
+        assert_eq!(egress_limits.max_bytes_per_sec(), 3);
+    }
+
+    /// Sends a rate limits request with the given query and returns the status and body
+    async fn send_request(
+        query: &str,
+        ingress_limits: Arc<ByteRateLimits>,
+        egress_limits: Arc<ByteRateLimits>,
+    ) -> (StatusCode, String) {
+        let request = Request::builder()
+            .uri(format!("/debug/state_sync/rate_limits?{query}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = handle_rate_limits_request(request, ingress_limits, egress_limits)
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::rate_limit::{Bucket, SharedBucket, TokenBucketRateLimiter};
+use aptos_infallible::{Mutex, RwLock};
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// Byte rate limits (in bytes per second) that can be adjusted at runtime.
+/// A limit of 0 means that the corresponding traffic is not limited.
+#[derive(Debug, Default)]
+pub struct ByteRateLimits {
+    /// The maximum number of bytes per second across all keys
+    max_bytes_per_sec: AtomicU64,
+    /// The maximum number of bytes per second for each key
+    max_bytes_per_sec_per_key: AtomicU64,
+}
+
+impl ByteRateLimits {
+    pub fn new(max_bytes_per_sec: u64, max_bytes_per_sec_per_key: u64) -> Self {
+        Self {
+            max_bytes_per_sec: AtomicU64::new(max_bytes_per_sec),
+            max_bytes_per_sec_per_key: AtomicU64::new(max_bytes_per_sec_per_key),
+        }
+    }
+
+    /// Returns the maximum number of bytes per second across all keys
+    pub fn max_bytes_per_sec(&self) -> u64 {
+        self.max_bytes_per_sec.load(Ordering::Relaxed)
+    }
+
+    /// Returns the maximum number of bytes per second for each key
+    pub fn max_bytes_per_sec_per_key(&self) -> u64 {
+        self.max_bytes_per_sec_per_key.load(Ordering::Relaxed)
+    }
+
+    /// Updates the limits. The new limits apply to all subsequent acquisitions.
+    pub fn update(&self, max_bytes_per_sec: u64, max_bytes_per_sec_per_key: u64) {
+        self.max_bytes_per_sec
+            .store(max_bytes_per_sec, Ordering::Relaxed);
+        self.max_bytes_per_sec_per_key
+            .store(max_bytes_per_sec_per_key, Ordering::Relaxed);
+    }
+}
+
+/// The token buckets enforcing a specific set of byte rate limits
+struct ByteRateBuckets<Key: Eq + Hash + Clone + Debug> {
+    max_bytes_per_sec: u64,
+    max_bytes_per_sec_per_key: u64,
+    aggregate_bucket: SharedBucket,
+    key_buckets: TokenBucketRateLimiter<Key>,
+}
+
+impl<Key: Eq + Hash + Clone + Debug> ByteRateBuckets<Key> {
+    fn new(label: &'static str, max_bytes_per_sec: u64, max_bytes_per_sec_per_key: u64) -> Self {
+        let aggregate_bucket = if max_bytes_per_sec == 0 {
+            Bucket::open(label.to_string())
+        } else {
+            let rate = to_usize(max_bytes_per_sec);
+            Bucket::new(
+                label.to_string(),
+                label.to_string(),
+                "aggregate".to_string(),
+                rate,
+                rate,
+                rate,
+                None,
+            )
+        };
+        let key_buckets = if max_bytes_per_sec_per_key == 0 {
+            TokenBucketRateLimiter::open(label)
+        } else {
+            let rate = to_usize(max_bytes_per_sec_per_key);
+            TokenBucketRateLimiter::new(label, label.to_string(), 100, rate, rate, None)
+        };
+
+        Self {
+            max_bytes_per_sec,
+            max_bytes_per_sec_per_key,
+            aggregate_bucket: Arc::new(Mutex::new(aggregate_bucket)),
+            key_buckets,
+        }
+    }
+}
+
+/// A rate limiter for the number of bytes transferred, both per key (e.g.,
+/// per peer) and in aggregate. The limits are shared via `ByteRateLimits`,
+/// so they can be adjusted at runtime (e.g., by an admin endpoint).
+pub struct ByteRateLimiter<Key: Eq + Hash + Clone + Debug> {
+    label: &'static str,
+    limits: Arc<ByteRateLimits>,
+    buckets: RwLock<Arc<ByteRateBuckets<Key>>>,
+}
+
+impl<Key: Eq + Hash + Clone + Debug> ByteRateLimiter<Key> {
+    pub fn new(
+        label: &'static str,
+        max_bytes_per_sec: u64,
+        max_bytes_per_sec_per_key: u64,
+    ) -> Self {
+        Self {
+            label,
+            limits: Arc::new(ByteRateLimits::new(
+                max_bytes_per_sec,
+                max_bytes_per_sec_per_key,
+            )),
+            buckets: RwLock::new(Arc::new(ByteRateBuckets::new(
+                label,
+                max_bytes_per_sec,
+                max_bytes_per_sec_per_key,
+            ))),
+        }
+    }
+
+    /// Returns the (shared) limits of the rate limiter
+    pub fn limits(&self) -> Arc<ByteRateLimits> {
+        self.limits.clone()
+    }
+
+    /// Returns the buckets to acquire tokens from for the given key. If the
+    /// limits have changed, the buckets are recreated with the new limits.
+    fn buckets(&self, key: Key) -> [SharedBucket; 2] {
+        let max_bytes_per_sec = self.limits.max_bytes_per_sec();
+        let max_bytes_per_sec_per_key = self.limits.max_bytes_per_sec_per_key();
+
+        // Attempt a read lock first, as the limits rarely change
+        let mut buckets = { self.buckets.read().clone() };
+        if buckets.max_bytes_per_sec != max_bytes_per_sec
+            || buckets.max_bytes_per_sec_per_key != max_bytes_per_sec_per_key
+        {
+            buckets = Arc::new(ByteRateBuckets::new(
+                self.label,
+                max_bytes_per_sec,
+                max_bytes_per_sec_per_key,
+            ));
+            *self.buckets.write() = buckets.clone();
+        }
+
+        [
+            buckets.key_buckets.bucket(key),
+            buckets.aggregate_bucket.clone(),
+        ]
+    }
+
+    /// Garbage collects the buckets of all keys rejected by the given filter
+    /// (e.g., the keys of disconnected peers). Buckets in use are kept.
+    pub fn garbage_collect_keys<F: Fn(&Key) -> bool>(&self, retain: F) {
+        let buckets = { self.buckets.read().clone() };
+        buckets.key_buckets.garbage_collect_keys(retain);
+    }
+
+    /// Returns the given number of unused bytes for the key (e.g., if
+    /// fewer bytes were transferred than were acquired).
+    pub fn refund(&self, key: Key, num_bytes: u64) {
+        for bucket in self.buckets(key) {
+            bucket.lock().return_tokens(to_usize(num_bytes));
+        }
+    }
+
+    /// Waits until the given number of bytes can be transferred for the key
+    pub async fn acquire(&self, key: Key, num_bytes: u64) {
+        for bucket in self.buckets(key) {
+            let mut remaining = to_usize(num_bytes);
+            while remaining > 0 {
+                match acquire_tokens(&bucket, remaining) {
+                    Ok(acquired) => remaining -= acquired,
+                    Err(refill_time) => {
+                        tokio::time::sleep_until(tokio::time::Instant::from_std(refill_time)).await
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Acquires up to the requested number of tokens from the bucket. If no
+/// tokens are available, the time of the next refill is returned.
+fn acquire_tokens(bucket: &SharedBucket, requested: usize) -> Result<usize, Instant> {
+    bucket
+        .lock()
+        .acquire_tokens(requested)
+        .map(|acquired| acquired.min(requested))
+}
+
+/// Converts the given number of bytes into a number of tokens
+fn to_usize(num_bytes: u64) -> usize {
+    usize::try_from(num_bytes).unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_unlimited_rate() {
+        let rate_limiter = ByteRateLimiter::new("test", 0, 0);
+
+        // Acquiring any number of bytes should never block
+        let start_time = Instant::now();
+        for _ in 0..10 {
+            rate_limiter.acquire("key", u64::MAX).await;
+        }
+        assert!(start_time.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_per_key_rate() {
+        let rate_limiter = ByteRateLimiter::new("test", 0, 100);
+
+        // Exhaust the bucket for the first key
+        let start_time = Instant::now();
+        rate_limiter.acquire("key_1", 100).await;
+        assert!(start_time.elapsed() < Duration::from_secs(1));
+
+        // Verify that other keys are not throttled
+        rate_limiter.acquire("key_2", 100).await;
+        assert!(start_time.elapsed() < Duration::from_secs(1));
+
+        // Verify that the first key is now throttled until the next refill
+        rate_limiter.acquire("key_1", 50).await;
+        assert!(start_time.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_rate() {
+        let rate_limiter = ByteRateLimiter::new("test", 100, 0);
+
+        // Exhaust the aggregate bucket using several keys
+        let start_time = Instant::now();
+        rate_limiter.acquire("key_1", 60).await;
+        rate_limiter.acquire("key_2", 40).await;
+        assert!(start_time.elapsed() < Duration::from_secs(1));
+
+        // Verify that any key is now throttled (and that acquisitions
+        // larger than the limit are spread over several refills).
+        rate_limiter.acquire("key_3", 150).await;
+        assert!(start_time.elapsed() >= Duration::from_millis(1900));
+    }
+
+    #[tokio::test]
+    async fn test_garbage_collect_keys() {
+        let rate_limiter = ByteRateLimiter::new("test", 0, 100);
+
+        // Exhaust the buckets of two keys
+        let start_time = Instant::now();
+        rate_limiter.acquire("key_1", 100).await;
+        rate_limiter.acquire("key_2", 100).await;
+
+        // Garbage collect the first key, and verify it gets a new (full) bucket
+        rate_limiter.garbage_collect_keys(|key| *key == "key_2");
+        rate_limiter.acquire("key_1", 100).await;
+        assert!(start_time.elapsed() < Duration::from_secs(1));
+
+        // Verify the second key is still throttled
+        rate_limiter.acquire("key_2", 50).await;
+        assert!(start_time.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    async fn test_update_limits() {
+        let rate_limiter = ByteRateLimiter::new("test", 0, 100);
+
+        // Exhaust the bucket for the key
+        let start_time = Instant::now();
+        rate_limiter.acquire("key", 100).await;
+
+        // Remove the limit and verify the key is no longer throttled
+        let limits = rate_limiter.limits();
+        limits.update(0, 0);
+        rate_limiter.acquire("key", 1000).await;
+        assert!(start_time.elapsed() < Duration::from_secs(1));
+        assert_eq!(limits.max_bytes_per_sec(), 0);
+        assert_eq!(limits.max_bytes_per_sec_per_key(), 0);
+
+        // Add a new limit and verify the key is throttled again
+        limits.update(0, 10);
+        rate_limiter.acquire("key", 20).await;
+        assert!(start_time.elapsed() >= Duration::from_millis(900));
+    }
+}
@@ -5,4 +5,5 @@
 #![forbid(unsafe_code)]
 
 pub mod async_lib;
+pub mod byte_rate_limit;
 pub mod rate_limit;
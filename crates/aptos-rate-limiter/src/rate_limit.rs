@@ -174,6 +174,14 @@ impl<Key: Eq + Hash + Clone + Debug> TokenBucketRateLimiter<Key> {
         }
         remove
     }
+
+    /// Garbage collects all keys rejected by the given filter (e.g., keys of
+    /// disconnected peers). Buckets that are still in use are kept.
+    pub fn garbage_collect_keys<F: Fn(&Key) -> bool>(&self, retain: F) {
+        self.buckets
+            .write()
+            .retain(|key, bucket| retain(key) || Arc::strong_count(bucket) > 1);
+    }
 }
 
 /// A token bucket object that keeps track of everything related to a key
@@ -549,4 +557,22 @@ mod tests {
         assert!(!rate_limiter.try_garbage_collect_key(&key_to_keep));
         assert_num_keys(&rate_limiter, 1);
     }
+
+    #[test]
+    fn test_garbage_collect_keys() {
+        let rate_limiter = TokenBucketRateLimiter::test(1, 1);
+
+        // Create buckets for several keys, and hold onto one of them
+        let _bucket_arc = rate_limiter.bucket("in use");
+        for key in ["retained", "removed_1", "removed_2"] {
+            let _bucket_arc = rate_limiter.bucket(key);
+        }
+        assert_num_keys(&rate_limiter, 4);
+
+        // Only the rejected keys that aren't in use should be removed
+        rate_limiter.garbage_collect_keys(|key| *key == "retained");
+        assert_num_keys(&rate_limiter, 2);
+        assert!(rate_limiter.buckets.read().contains_key("in use"));
+        assert!(rate_limiter.buckets.read().contains_key("retained"));
+    }
 }
@@ -1,11 +1,14 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::types::{CliError, CliTypedResult};
+use crate::common::{
+    types::{CliError, CliTypedResult},
+    utils::{create_dir_if_not_exist, write_to_file},
+};
 use aptos_crypto::HashValue;
-use aptos_gas_profiling::FrameName;
+use aptos_gas_profiling::{ExecutionTrace, FrameName};
 use aptos_move_debugger::aptos_debugger::AptosDebugger;
-use aptos_types::transaction::SignedTransaction;
+use aptos_types::transaction::{ExecutionStatus, SignedTransaction, TransactionStatus};
 use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
 use aptos_vm_logging::log_schema::AdapterLogSchema;
 use aptos_vm_types::{output::VMOutput, resolver::StateStorageView};
@@ -106,9 +109,79 @@ pub fn profile_transaction_using_debugger(
         })?;
 
     // Generate a human-readable name for the report
-    let entry_point = gas_log.entry_point();
+    let human_readable_name = human_readable_name(gas_log.entry_point());
+    let raw_file_name = format!("txn-{}-{}", hash, human_readable_name);
+
+    // Generate the report
+    let path = Path::new("gas-profiling").join(raw_file_name);
+    gas_log.generate_html_report(&path, format!("Gas Report - {}", human_readable_name))?;
+
+    // Export the machine-readable profiles next to the report
+    let profile = gas_log.to_gas_profile();
+    let json = serde_json::to_vec_pretty(&profile).map_err(|err| {
+        CliError::UnexpectedError(format!("failed to serialize gas profile: {}", err))
+    })?;
+    write_to_file(&path.join("profile.json"), "Gas profile", &json)?;
+    let pprof = profile.to_pprof().map_err(|err| {
+        CliError::UnexpectedError(format!("failed to export gas profile to pprof: {}", err))
+    })?;
+    write_to_file(&path.join("profile.pb.gz"), "Gas profile", &pprof)?;
+
+    println!("Gas report saved to {}.", path.display());
+
+    Ok((vm_status, vm_output))
+}
+
+pub fn trace_transaction_using_debugger(
+    debugger: &AptosDebugger,
+    version: u64,
+    transaction: SignedTransaction,
+    hash: HashValue,
+) -> CliTypedResult<(VMStatus, VMOutput)> {
+    let (vm_status, vm_output, gas_log) = debugger
+        .execute_transaction_at_version_with_value_tracing(version, transaction)
+        .map_err(|err| {
+            CliError::UnexpectedError(format!("failed to simulate txn with tracing: {}", err))
+        })?;
+
+    let status = match vm_output.status() {
+        TransactionStatus::Keep(status) => status.clone(),
+        TransactionStatus::Discard(status_code) => {
+            ExecutionStatus::MiscellaneousError(Some(*status_code))
+        },
+        TransactionStatus::Retry => ExecutionStatus::MiscellaneousError(None),
+    };
+    let trace = ExecutionTrace::new(&gas_log.exec_io.call_graph, &status);
+
+    if let Some(abort_location) = &trace.abort_location {
+        println!(
+            "Transaction did not succeed, call stack: {}",
+            abort_location.call_stack.join(" -> ")
+        );
+    }
+
+    // Save the trace
+    let dir = Path::new("execution-traces");
+    create_dir_if_not_exist(dir)?;
+    let path = dir.join(format!(
+        "txn-{}-{}.json",
+        hash,
+        human_readable_name(gas_log.entry_point())
+    ));
+    let bytes = serde_json::to_vec_pretty(&trace).map_err(|err| {
+        CliError::UnexpectedError(format!("failed to serialize execution trace: {}", err))
+    })?;
+    write_to_file(&path, "Execution trace", &bytes)?;
+
+    println!("Execution trace saved to {}.", path.display());
 
-    let human_readable_name = match entry_point {
+    Ok((vm_status, vm_output))
+}
+
+/// Generates a human-readable name for the entry point of a transaction, to be used in the names
+/// of the generated files.
+fn human_readable_name(entry_point: &FrameName) -> String {
+    match entry_point {
         FrameName::Script => "script".to_string(),
         FrameName::Function {
             module_id, name, ..
@@ -121,14 +194,5 @@ pub fn profile_transaction_using_debugger(
             };
             format!("0x{}-{}-{}", addr_truncated, module_id.name(), name)
         },
-    };
-    let raw_file_name = format!("txn-{}-{}", hash, human_readable_name);
-
-    // Generate the report
-    let path = Path::new("gas-profiling").join(raw_file_name);
-    gas_log.generate_html_report(&path, format!("Gas Report - {}", human_readable_name))?;
-
-    println!("Gas report saved to {}.", path.display());
-
-    Ok((vm_status, vm_output))
+    }
 }
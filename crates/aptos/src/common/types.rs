@@ -1541,6 +1541,11 @@ pub struct TransactionOptions {
     /// flamegraphs that reflect the gas usage.
     #[clap(long)]
     pub(crate) profile_gas: bool,
+
+    /// If this option is set, simulate the transaction locally and save a trace of its execution,
+    /// including the arguments of every function call, as JSON.
+    #[clap(long)]
+    pub(crate) trace: bool,
 }
 
 impl TransactionOptions {
@@ -1893,6 +1898,18 @@ impl TransactionOptions {
         .await
     }
 
+    /// Simulates the transaction locally and traces its execution.
+    pub async fn trace_locally(
+        &self,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<TransactionSummary> {
+        println!();
+        println!("Simulating transaction locally with execution tracing...");
+
+        self.simulate_using_debugger(payload, local_simulation::trace_transaction_using_debugger)
+            .await
+    }
+
     pub async fn estimate_gas_price(&self) -> CliTypedResult<u64> {
         let client = self.rest_client()?;
         client
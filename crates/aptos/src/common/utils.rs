@@ -505,10 +505,17 @@ pub async fn profile_or_submit(
             "Cannot perform benchmarking and gas profiling at the same time.".to_string(),
         ));
     }
+    if txn_options_ref.trace && (txn_options_ref.profile_gas || txn_options_ref.benchmark) {
+        return Err(CliError::UnexpectedError(
+            "Cannot trace the execution while benchmarking or profiling gas.".to_string(),
+        ));
+    }
 
     // Profile gas if needed.
     if txn_options_ref.profile_gas {
         txn_options_ref.profile_gas(payload).await
+    } else if txn_options_ref.trace {
+        txn_options_ref.trace_locally(payload).await
     } else if txn_options_ref.benchmark {
         txn_options_ref.benchmark_locally(payload).await
     } else if txn_options_ref.local {
@@ -0,0 +1,64 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{CliCommand, CliError, CliTypedResult},
+    utils::read_from_file,
+};
+use aptos_gas_profiling::{GasProfile, GasProfileDiff, GAS_PROFILE_SCHEMA_VERSION};
+use async_trait::async_trait;
+use clap::Parser;
+use std::path::{Path, PathBuf};
+
+/// Compare the gas usage of two gas profiles, function by function
+///
+/// Gas profiles are saved as `profile.json` in the gas report directory when running a
+/// transaction with `--profile-gas`.  Only the functions whose gas usage changed are
+/// reported, with the largest changes first.  Costs are in internal gas units, which can be
+/// divided by `gas_scaling_factor` to get gas units.
+#[derive(Debug, Parser)]
+pub struct GasDiff {
+    /// Path to the gas profile to compare against
+    #[clap(long, value_parser)]
+    pub(crate) base: PathBuf,
+
+    /// Path to the new gas profile
+    #[clap(long, value_parser)]
+    pub(crate) new: PathBuf,
+
+    /// Only report functions whose total cost changed by at least this many internal gas units
+    #[clap(long, default_value_t = 0)]
+    pub(crate) threshold: u64,
+}
+
+fn load_gas_profile(path: &Path) -> CliTypedResult<GasProfile> {
+    let profile: GasProfile = serde_json::from_slice(&read_from_file(path)?).map_err(|err| {
+        CliError::UnableToParse("gas profile", format!("{}: {}", path.display(), err))
+    })?;
+    if profile.schema_version != GAS_PROFILE_SCHEMA_VERSION {
+        return Err(CliError::CommandArgumentError(format!(
+            "Gas profile {} has schema version {}, only version {} is supported",
+            path.display(),
+            profile.schema_version,
+            GAS_PROFILE_SCHEMA_VERSION
+        )));
+    }
+    Ok(profile)
+}
+
+#[async_trait]
+impl CliCommand<GasProfileDiff> for GasDiff {
+    fn command_name(&self) -> &'static str {
+        "GasDiff"
+    }
+
+    async fn execute(self) -> CliTypedResult<GasProfileDiff> {
+        let base = load_gas_profile(&self.base)?;
+        let new = load_gas_profile(&self.new)?;
+
+        let mut diff = base.diff(&new);
+        diff.functions
+            .retain(|function| function.total_delta.unsigned_abs() >= self.threshold);
+        Ok(diff)
+    }
+}
@@ -25,7 +25,7 @@ use crate::{
     },
     CliCommand, CliResult,
 };
-use aptos_crypto::HashValue;
+use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_framework::{
     docgen::DocgenOptions, extended_checks, natives::code::UpgradePolicy, prover::ProverOptions,
     BuildOptions, BuiltPackage,
@@ -71,10 +71,12 @@ use url::Url;
 mod aptos_debug_natives;
 pub mod bytecode;
 pub mod coverage;
+pub mod gas_diff;
 pub mod manifest;
 pub mod package_hooks;
 pub mod show;
 pub mod stored_package;
+pub mod upgrade_check;
 
 const HELLO_BLOCKCHAIN_EXAMPLE: &str = include_str!(
     "../../../../aptos-move/move-examples/hello_blockchain/sources/hello_blockchain.move"
@@ -88,6 +90,7 @@ const HELLO_BLOCKCHAIN_EXAMPLE: &str = include_str!(
 #[derive(Subcommand)]
 pub enum MoveTool {
     BuildPublishPayload(BuildPublishPayload),
+    CheckUpgrade(upgrade_check::CheckUpgrade),
     Clean(CleanPackage),
     #[clap(alias = "build")]
     Compile(CompilePackage),
@@ -103,6 +106,7 @@ pub enum MoveTool {
     #[clap(alias = "doc")]
     Document(DocumentPackage),
     Download(DownloadPackage),
+    GasDiff(gas_diff::GasDiff),
     Init(InitPackage),
     List(ListPackage),
     Prove(ProvePackage),
@@ -116,12 +120,14 @@ pub enum MoveTool {
     VerifyPackage(VerifyPackage),
     View(ViewFunction),
     Replay(Replay),
+    ReplayRange(ReplayRange),
 }
 
 impl MoveTool {
     pub async fn execute(self) -> CliResult {
         match self {
             MoveTool::BuildPublishPayload(tool) => tool.execute_serialized().await,
+            MoveTool::CheckUpgrade(tool) => tool.execute_serialized().await,
             MoveTool::Clean(tool) => tool.execute_serialized().await,
             MoveTool::Compile(tool) => tool.execute_serialized().await,
             MoveTool::CompileScript(tool) => tool.execute_serialized().await,
@@ -137,6 +143,7 @@ impl MoveTool {
             MoveTool::Decompile(tool) => tool.execute_serialized().await,
             MoveTool::Document(tool) => tool.execute_serialized().await,
             MoveTool::Download(tool) => tool.execute_serialized().await,
+            MoveTool::GasDiff(tool) => tool.execute_serialized().await,
             MoveTool::Init(tool) => tool.execute_serialized_success().await,
             MoveTool::List(tool) => tool.execute_serialized().await,
             MoveTool::Prove(tool) => tool.execute_serialized().await,
@@ -148,6 +155,7 @@ impl MoveTool {
             MoveTool::VerifyPackage(tool) => tool.execute_serialized().await,
             MoveTool::View(tool) => tool.execute_serialized().await,
             MoveTool::Replay(tool) => tool.execute_serialized().await,
+            MoveTool::ReplayRange(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -1574,15 +1582,62 @@ pub enum ReplayNetworkSelection {
     RestEndpoint(String),
 }
 
-/// Replay a comitted transaction using a local VM.
+/// Where to replay transactions from
 #[derive(Parser, Debug)]
-pub struct Replay {
+#[clap(group(clap::ArgGroup::new("replay_target")
+        .required(true)
+        .multiple(false)
+        .args(&["network", "db_path"]),
+))]
+pub struct ReplayTarget {
     /// The network to replay on.
     ///
     /// Possible values:
     ///     mainnet, testnet, <REST_ENDPOINT_URL>
-    #[clap(long)]
-    pub(crate) network: ReplayNetworkSelection,
+    #[clap(long, group = "replay_target")]
+    pub(crate) network: Option<ReplayNetworkSelection>,
+
+    /// Path to a local AptosDB to replay from, instead of a network
+    ///
+    /// This can be the database of a node, or one restored from a backup with
+    /// `aptos-db-tool restore bootstrap-db`.  This is much faster than replaying
+    /// from a network, and is not rate limited.
+    #[clap(long, group = "replay_target", value_parser)]
+    pub(crate) db_path: Option<PathBuf>,
+}
+
+impl ReplayTarget {
+    fn debugger(&self) -> CliTypedResult<AptosDebugger> {
+        use ReplayNetworkSelection::*;
+
+        if let Some(db_path) = &self.db_path {
+            return Ok(AptosDebugger::db(db_path)?);
+        }
+
+        let rest_endpoint = match &self.network {
+            Some(Mainnet) => "https://fullnode.mainnet.aptoslabs.com",
+            Some(Testnet) => "https://fullnode.testnet.aptoslabs.com",
+            Some(Devnet) => "https://fullnode.devnet.aptoslabs.com",
+            Some(RestEndpoint(url)) => url,
+            None => {
+                return Err(CliError::CommandArgumentError(
+                    "One of '--network' or '--db-path' must be provided".to_string(),
+                ))
+            },
+        };
+
+        Ok(AptosDebugger::rest_client(Client::new(
+            Url::parse(rest_endpoint)
+                .map_err(|_err| CliError::UnableToParse("url", rest_endpoint.to_string()))?,
+        ))?)
+    }
+}
+
+/// Replay a comitted transaction using a local VM.
+#[derive(Parser, Debug)]
+pub struct Replay {
+    #[clap(flatten)]
+    pub(crate) target: ReplayTarget,
 
     /// The id of the transaction to replay. Also being referred to as "version" in some contexts.
     #[clap(long)]
@@ -1621,25 +1676,13 @@ impl CliCommand<TransactionSummary> for Replay {
     }
 
     async fn execute(self) -> CliTypedResult<TransactionSummary> {
-        use ReplayNetworkSelection::*;
-
         if self.profile_gas && self.benchmark {
             return Err(CliError::UnexpectedError(
                 "Cannot perform benchmarking and gas profiling at the same time.".to_string(),
             ));
         }
 
-        let rest_endpoint = match &self.network {
-            Mainnet => "https://fullnode.mainnet.aptoslabs.com",
-            Testnet => "https://fullnode.testnet.aptoslabs.com",
-            Devnet => "https://fullnode.devnet.aptoslabs.com",
-            RestEndpoint(url) => url,
-        };
-
-        let debugger = AptosDebugger::rest_client(Client::new(
-            Url::parse(rest_endpoint)
-                .map_err(|_err| CliError::UnableToParse("url", rest_endpoint.to_string()))?,
-        ))?;
+        let debugger = self.target.debugger()?;
 
         // Fetch the transaction to replay.
         let (txn, txn_info) = debugger
@@ -1727,6 +1770,95 @@ impl CliCommand<TransactionSummary> for Replay {
     }
 }
 
+/// Replay a range of committed transactions using a local VM, and report where their
+/// outputs diverge from the recorded ones.
+///
+/// Transactions are replayed block by block, and each output is compared against the
+/// recorded status, gas used, write set and events.  For large ranges, replaying from a
+/// local database with `--db-path` is much faster than from a network.
+#[derive(Parser, Debug)]
+pub struct ReplayRange {
+    #[clap(flatten)]
+    pub(crate) target: ReplayTarget,
+
+    /// The version of the first transaction to replay
+    #[clap(long)]
+    pub(crate) start_version: u64,
+
+    /// The number of transactions to replay
+    #[clap(long)]
+    pub(crate) limit: u64,
+
+    /// The number of threads to execute each block with
+    #[clap(long, default_value_t = 1)]
+    pub(crate) concurrency_level: usize,
+}
+
+/// Summary of the replay of a range of transactions
+#[derive(Debug, Serialize)]
+pub struct ReplayRangeSummary {
+    pub start_version: u64,
+    pub num_replayed: u64,
+    pub num_diverged: u64,
+    pub diverged: Vec<DivergedTransaction>,
+}
+
+/// A transaction whose replayed output differs from the recorded one
+#[derive(Debug, Serialize)]
+pub struct DivergedTransaction {
+    pub version: u64,
+    pub transaction_hash: HashValue,
+    pub divergences: Vec<String>,
+}
+
+#[async_trait]
+impl CliCommand<ReplayRangeSummary> for ReplayRange {
+    fn command_name(&self) -> &'static str {
+        "ReplayRange"
+    }
+
+    async fn execute(self) -> CliTypedResult<ReplayRangeSummary> {
+        if self.limit == 0 {
+            return Err(CliError::CommandArgumentError(
+                "'--limit' must be greater than 0".to_string(),
+            ));
+        }
+
+        let debugger = self.target.debugger()?;
+        let mut diverged = vec![];
+        let num_replayed = debugger
+            .replay_past_transactions(
+                self.start_version,
+                self.limit,
+                self.concurrency_level,
+                |replayed_txn| {
+                    let divergences = replayed_txn.divergences();
+                    if divergences.is_empty() {
+                        return Ok(());
+                    }
+                    eprintln!("Version {} diverged:", replayed_txn.version);
+                    for divergence in &divergences {
+                        eprintln!("  {}", divergence);
+                    }
+                    diverged.push(DivergedTransaction {
+                        version: replayed_txn.version,
+                        transaction_hash: CryptoHash::hash(&replayed_txn.txn),
+                        divergences: divergences.iter().map(ToString::to_string).collect(),
+                    });
+                    Ok(())
+                },
+            )
+            .await?;
+
+        Ok(ReplayRangeSummary {
+            start_version: self.start_version,
+            num_replayed,
+            num_diverged: diverged.len() as u64,
+            diverged,
+        })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum FunctionArgType {
     Address,
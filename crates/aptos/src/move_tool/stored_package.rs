@@ -3,7 +3,7 @@
 
 use anyhow::bail;
 use aptos_framework::{
-    natives::code::{ModuleMetadata, PackageMetadata, PackageRegistry, UpgradePolicy},
+    natives::code::{ModuleMetadata, PackageDep, PackageMetadata, PackageRegistry, UpgradePolicy},
     unzip_metadata_str,
 };
 use aptos_rest_client::Client;
@@ -17,6 +17,7 @@ use std::{collections::BTreeMap, fmt, fs, path::Path};
 
 /// Represents the package registry at a given account.
 pub struct CachedPackageRegistry {
+    addr: AccountAddress,
     inner: PackageRegistry,
     bytecode: BTreeMap<String, Vec<u8>>,
 }
@@ -65,7 +66,16 @@ impl CachedPackageRegistry {
                 }
             }
         }
-        Ok(Self { inner, bytecode })
+        Ok(Self {
+            addr,
+            inner,
+            bytecode,
+        })
+    }
+
+    /// Returns the address of the account this registry is stored at.
+    pub fn address(&self) -> AccountAddress {
+        self.addr
     }
 
     /// Returns the list of packages in this registry by name.
@@ -140,6 +150,10 @@ impl<'a> CachedPackageMetadata<'a> {
         unzip_metadata_str(&self.metadata.manifest)
     }
 
+    pub fn deps(&self) -> &[PackageDep] {
+        &self.metadata.deps
+    }
+
     pub fn module_names(&self) -> Vec<&str> {
         self.metadata
             .modules
@@ -0,0 +1,607 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            CliCommand, CliError, CliTypedResult, MovePackageDir, ProfileOptions, RestOptions,
+        },
+        utils::get_feature_flag,
+    },
+    move_tool::{CachedPackageRegistry, IncludedArtifacts},
+};
+use aptos_framework::{natives::code::UpgradePolicy, BuildOptions, BuiltPackage};
+use aptos_rest_client::Client;
+use aptos_types::{account_address::AccountAddress, on_chain_config::FeatureFlag};
+use async_trait::async_trait;
+use clap::Parser;
+use move_binary_format::{
+    access::ModuleAccess,
+    compatibility::Compatibility,
+    file_format::{CompiledModule, Visibility},
+    normalized,
+};
+use move_bytecode_verifier::dependencies;
+use move_core_types::language_storage::ModuleId;
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Check how upgrading a package would affect the on-chain modules depending on it
+///
+/// Compiles the new version of the package, checks that it is a compatible upgrade of the
+/// published version, and links every published module depending on it against the new
+/// bytecode.  Dependent packages are searched for in the package registry of the account the
+/// package is published at, and of each of `--dependent-accounts`.  Reports the dependent
+/// modules which would fail to load after the upgrade, e.g. because they call a function
+/// which was removed, or whose signature or visibility changed.
+#[derive(Parser)]
+pub struct CheckUpgrade {
+    /// Accounts to search for packages depending on the upgraded package
+    ///
+    /// The account the package is published at is always searched.
+    #[clap(long, num_args = 0.., value_parser = crate::common::types::load_account_arg)]
+    pub(crate) dependent_accounts: Vec<AccountAddress>,
+
+    /// Artifacts to be generated when building this package.
+    #[clap(long, default_value_t = IncludedArtifacts::Sparse)]
+    pub(crate) included_artifacts: IncludedArtifacts,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// Summary of the effects of a package upgrade
+#[derive(Debug, Serialize)]
+pub struct UpgradeCheckSummary {
+    pub package: String,
+    pub account: AccountAddress,
+    /// Why the new package cannot replace the published one, empty if it can
+    pub compatibility_errors: Vec<String>,
+    pub num_dependent_modules: u64,
+    pub broken_dependents: Vec<BrokenDependent>,
+}
+
+/// A published module which would fail to load after the upgrade
+#[derive(Debug, Serialize)]
+pub struct BrokenDependent {
+    pub package: String,
+    pub module: String,
+    /// References to the upgraded package which no longer resolve
+    pub broken_references: Vec<String>,
+    /// The error linking the module against the new bytecode
+    pub link_error: Option<String>,
+}
+
+#[async_trait]
+impl CliCommand<UpgradeCheckSummary> for CheckUpgrade {
+    fn command_name(&self) -> &'static str {
+        "CheckUpgrade"
+    }
+
+    async fn execute(self) -> CliTypedResult<UpgradeCheckSummary> {
+        let build_options = BuildOptions {
+            install_dir: self.move_options.output_dir.clone(),
+            bytecode_version: self.move_options.bytecode_version,
+            ..self.included_artifacts.build_options(
+                self.move_options.dev,
+                self.move_options.skip_fetch_latest_git_deps,
+                self.move_options.named_addresses(),
+                self.move_options.override_std.clone(),
+                self.move_options.bytecode_version,
+                self.move_options.compiler_version,
+                self.move_options.language_version,
+                self.move_options.skip_attribute_checks,
+                self.move_options.check_test_code,
+            )
+        };
+        let pack = BuiltPackage::build(self.move_options.get_package_path()?, build_options)
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        let new_metadata = pack.extract_metadata()?;
+        let new_modules: BTreeMap<ModuleId, CompiledModule> = pack
+            .modules()
+            .map(|module| (module.self_id(), module.clone()))
+            .collect();
+        let account = match new_modules.keys().next() {
+            Some(id) => *id.address(),
+            None => {
+                return Err(CliError::CommandArgumentError(format!(
+                    "Package {} has no modules",
+                    pack.name()
+                )))
+            },
+        };
+
+        let url = self.rest_options.url(&self.profile_options)?;
+        let mut chain_modules = ChainModules::new(Client::new(url.clone()));
+
+        // Check the upgrade itself, as `code.move` and the VM would on publishing
+        let registry = fetch_registry(url.clone(), account).await;
+        let mut old_module_ids = BTreeSet::new();
+        let mut compatibility_errors = vec![];
+        let old_package = match &registry {
+            Some(registry) => registry.get_package(pack.name()).await.ok(),
+            None => None,
+        };
+        if let Some(old_package) = old_package {
+            let old_policy = old_package.upgrade_policy();
+            if old_policy == UpgradePolicy::immutable() {
+                compatibility_errors.push("the published package is immutable".to_string());
+            } else if new_metadata.upgrade_policy.policy < old_policy.policy {
+                compatibility_errors.push(format!(
+                    "the upgrade policy can't be weakened from `{}` to `{}`",
+                    old_policy, new_metadata.upgrade_policy
+                ));
+            }
+            // Friend functions are only checked if the chain doesn't treat them as private
+            let compatibility = if old_policy == UpgradePolicy::compat() {
+                let treat_friend_as_private =
+                    get_feature_flag(&chain_modules.client, FeatureFlag::TREAT_FRIEND_AS_PRIVATE)
+                        .await?;
+                Some(Compatibility::new(true, true, !treat_friend_as_private))
+            } else {
+                None
+            };
+            for name in old_package.module_names() {
+                let id = ModuleId::new(account, name.parse()?);
+                old_module_ids.insert(id.clone());
+                let Some(new_module) = new_modules.get(&id) else {
+                    compatibility_errors.push(format!("module `{}` was removed", name));
+                    continue;
+                };
+                if let Some(compatibility) = &compatibility {
+                    let old_module = chain_modules.get(&id).await?;
+                    if let Err(err) = compatibility.check(
+                        &normalized::Module::new(old_module),
+                        &normalized::Module::new(new_module),
+                    ) {
+                        compatibility_errors.push(format!(
+                            "module `{}` is not a compatible upgrade: {:?}",
+                            name,
+                            err.major_status()
+                        ));
+                    }
+                }
+            }
+        }
+        for error in &compatibility_errors {
+            eprintln!("Incompatible upgrade: {}", error);
+        }
+
+        // Link the published modules of the dependent packages against the new bytecode
+        let mut registries = vec![registry];
+        for dependent_account in &self.dependent_accounts {
+            if *dependent_account != account {
+                registries.push(fetch_registry(url.clone(), *dependent_account).await);
+            }
+        }
+        let mut num_dependent_modules = 0;
+        let mut broken_dependents = vec![];
+        for registry in registries.iter().flatten() {
+            for package_name in registry.package_names() {
+                let package = registry.get_package(package_name).await?;
+                let depends_on_upgraded = package
+                    .deps()
+                    .iter()
+                    .any(|dep| dep.account == account && dep.package_name == pack.name());
+                if !depends_on_upgraded {
+                    continue;
+                }
+                for module_name in package.module_names() {
+                    let id = ModuleId::new(registry.address(), module_name.parse()?);
+                    let module = chain_modules.get(&id).await?.clone();
+                    num_dependent_modules += 1;
+
+                    let broken_references =
+                        check_references(&module, account, &old_module_ids, &new_modules);
+                    let link_error = link_against_upgrade(
+                        &module,
+                        account,
+                        &old_module_ids,
+                        &new_modules,
+                        &mut chain_modules,
+                    )
+                    .await?;
+                    if broken_references.is_empty() && link_error.is_none() {
+                        continue;
+                    }
+
+                    eprintln!("Module {} of package {} would break:", id, package.name());
+                    for reference in &broken_references {
+                        eprintln!("  {}", reference);
+                    }
+                    if let Some(link_error) = &link_error {
+                        eprintln!("  failed to link: {}", link_error);
+                    }
+                    broken_dependents.push(BrokenDependent {
+                        package: package.name().to_string(),
+                        module: id.to_string(),
+                        broken_references,
+                        link_error,
+                    });
+                }
+            }
+        }
+
+        Ok(UpgradeCheckSummary {
+            package: pack.name().to_string(),
+            account,
+            compatibility_errors,
+            num_dependent_modules,
+            broken_dependents,
+        })
+    }
+}
+
+/// Fetches the package registry of the account, or returns `None` if it has none.
+async fn fetch_registry(url: Url, addr: AccountAddress) -> Option<CachedPackageRegistry> {
+    match CachedPackageRegistry::create(url, addr, false).await {
+        Ok(registry) => Some(registry),
+        Err(err) => {
+            eprintln!(
+                "Skipping account {}, failed to fetch its package registry: {:#}",
+                addr, err
+            );
+            None
+        },
+    }
+}
+
+/// Published modules fetched so far, by id.
+struct ChainModules {
+    client: Client,
+    modules: BTreeMap<ModuleId, CompiledModule>,
+}
+
+impl ChainModules {
+    fn new(client: Client) -> Self {
+        Self {
+            client,
+            modules: BTreeMap::new(),
+        }
+    }
+
+    async fn get(&mut self, id: &ModuleId) -> CliTypedResult<&CompiledModule> {
+        if !self.modules.contains_key(id) {
+            let bytes = self
+                .client
+                .get_account_module_bcs(*id.address(), id.name().as_str())
+                .await?
+                .into_inner();
+            let module = CompiledModule::deserialize(&bytes)
+                .map_err(|err| CliError::UnableToParse("module", format!("{}: {:?}", id, err)))?;
+            self.modules.insert(id.clone(), module);
+        }
+        Ok(&self.modules[id])
+    }
+}
+
+/// Returns whether the module is part of the upgraded package, before or after the upgrade.
+fn is_upgraded(
+    id: &ModuleId,
+    old_module_ids: &BTreeSet<ModuleId>,
+    new_modules: &BTreeMap<ModuleId, CompiledModule>,
+) -> bool {
+    old_module_ids.contains(id) || new_modules.contains_key(id)
+}
+
+/// Lists the structs and functions of the upgraded package used by the dependent module which
+/// are missing from the new bytecode, or whose declaration no longer matches.
+fn check_references(
+    module: &CompiledModule,
+    account: AccountAddress,
+    old_module_ids: &BTreeSet<ModuleId>,
+    new_modules: &BTreeMap<ModuleId, CompiledModule>,
+) -> Vec<String> {
+    let mut broken = vec![];
+    let self_id = module.self_id();
+    let upgraded_id = |handle_idx| {
+        let id = module.module_id_for_handle(module.module_handle_at(handle_idx));
+        (*id.address() == account && id != self_id && is_upgraded(&id, old_module_ids, new_modules))
+            .then_some(id)
+    };
+
+    for handle in module.struct_handles() {
+        let Some(owner_id) = upgraded_id(handle.module) else {
+            continue;
+        };
+        let name = module.identifier_at(handle.name);
+        let Some(owner) = new_modules.get(&owner_id) else {
+            broken.push(format!("struct {}::{}: module was removed", owner_id, name));
+            continue;
+        };
+        let def = owner
+            .struct_defs()
+            .iter()
+            .map(|def| owner.struct_handle_at(def.struct_handle))
+            .find(|def| owner.identifier_at(def.name) == name);
+        let Some(def) = def else {
+            broken.push(format!("struct {}::{}: struct was removed", owner_id, name));
+            continue;
+        };
+        if !handle.abilities.is_subset(def.abilities) {
+            broken.push(format!(
+                "struct {}::{}: abilities changed from {:?} to {:?}",
+                owner_id, name, handle.abilities, def.abilities
+            ));
+        }
+        let type_parameters_match =
+            handle.type_parameters.len() == def.type_parameters.len()
+                && handle.type_parameters.iter().zip(&def.type_parameters).all(
+                    |(local, defined)| {
+                        (!local.is_phantom || defined.is_phantom)
+                            && defined.constraints.is_subset(local.constraints)
+                    },
+                );
+        if !type_parameters_match {
+            broken.push(format!(
+                "struct {}::{}: type parameters changed",
+                owner_id, name
+            ));
+        }
+    }
+
+    for handle in module.function_handles() {
+        let Some(owner_id) = upgraded_id(handle.module) else {
+            continue;
+        };
+        let name = module.identifier_at(handle.name);
+        let Some(owner) = new_modules.get(&owner_id) else {
+            broken.push(format!(
+                "function {}::{}: module was removed",
+                owner_id, name
+            ));
+            continue;
+        };
+        let Some(def) = normalized::Function::new_from_name(owner, name) else {
+            broken.push(format!(
+                "function {}::{}: function was removed",
+                owner_id, name
+            ));
+            continue;
+        };
+        let callable = match def.visibility {
+            Visibility::Public => true,
+            Visibility::Friend => owner.immediate_friends().contains(&self_id),
+            Visibility::Private => false,
+        };
+        if !callable {
+            broken.push(format!(
+                "function {}::{}: visibility changed to {:?}",
+                owner_id, name, def.visibility
+            ));
+        }
+        let type_parameters_match = handle.type_parameters.len() == def.type_parameters.len()
+            && handle
+                .type_parameters
+                .iter()
+                .zip(&def.type_parameters)
+                .all(|(local, defined)| defined.is_subset(*local));
+        let to_types = |sig_idx| -> Vec<normalized::Type> {
+            module
+                .signature_at(sig_idx)
+                .0
+                .iter()
+                .map(|token| normalized::Type::new(module, token))
+                .collect()
+        };
+        if !type_parameters_match
+            || to_types(handle.parameters) != def.parameters
+            || to_types(handle.return_) != def.return_
+        {
+            broken.push(format!(
+                "function {}::{}: signature changed",
+                owner_id, name
+            ));
+        }
+    }
+
+    broken
+}
+
+/// Links the dependent module against its immediate dependencies, taking the modules of the
+/// upgraded package from the new bytecode, and returns the linking error if any.
+async fn link_against_upgrade(
+    module: &CompiledModule,
+    account: AccountAddress,
+    old_module_ids: &BTreeSet<ModuleId>,
+    new_modules: &BTreeMap<ModuleId, CompiledModule>,
+    chain_modules: &mut ChainModules,
+) -> CliTypedResult<Option<String>> {
+    let mut dependency_ids = vec![];
+    for id in module.immediate_dependencies() {
+        if *id.address() == account && is_upgraded(&id, old_module_ids, new_modules) {
+            if !new_modules.contains_key(&id) {
+                return Ok(Some(format!("MISSING_DEPENDENCY: {}", id)));
+            }
+        } else {
+            chain_modules.get(&id).await?;
+            dependency_ids.push(id);
+        }
+    }
+
+    let dependencies = new_modules
+        .values()
+        .chain(dependency_ids.iter().map(|id| &chain_modules.modules[id]));
+    Ok(dependencies::verify_module(module, dependencies)
+        .err()
+        .map(|err| format!("{:?}: {}", err.major_status(), err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_compiler::{
+        compiled_unit::AnnotatedCompiledUnit,
+        shared::{known_attributes::KnownAttribute, Flags, NumericalAddress},
+        Compiler,
+    };
+    use std::fs;
+
+    const OLD_LIB: &str = r#"
+        module 0xcafe::lib {
+            struct Coin has store, drop { value: u64 }
+            public fun mint(value: u64): Coin { Coin { value } }
+            public fun value(coin: &Coin): u64 { coin.value }
+        }
+    "#;
+
+    const APP: &str = r#"
+        module 0xbeef::app {
+            use 0xcafe::lib;
+            public fun run(): u64 {
+                let coin = lib::mint(1);
+                lib::value(&coin)
+            }
+        }
+    "#;
+
+    /// Compiles the target source against the dependency sources.
+    fn compile(target: &str, deps: &[&str]) -> Vec<CompiledModule> {
+        let dir = tempfile::tempdir().unwrap();
+        let write_source = |name: String, source: &str| {
+            let path = dir.path().join(name);
+            fs::write(&path, source).unwrap();
+            path.to_str().unwrap().to_string()
+        };
+        let target_path = write_source("target.move".to_string(), target);
+        let dep_paths = deps
+            .iter()
+            .enumerate()
+            .map(|(i, dep)| write_source(format!("dep_{}.move", i), dep))
+            .collect();
+        let (_, units) = Compiler::from_files(
+            vec![target_path],
+            dep_paths,
+            BTreeMap::<String, NumericalAddress>::new(),
+            Flags::empty(),
+            KnownAttribute::get_all_attribute_names(),
+        )
+        .build_and_report()
+        .unwrap();
+        units
+            .into_iter()
+            .map(|unit| match unit {
+                AnnotatedCompiledUnit::Module(module) => module.named_module.module,
+                AnnotatedCompiledUnit::Script(_) => panic!("expected a module"),
+            })
+            .collect()
+    }
+
+    /// Compiles the app against the old library, and the new library.
+    fn compile_upgrade(
+        new_lib: &str,
+    ) -> (
+        CompiledModule,
+        BTreeSet<ModuleId>,
+        BTreeMap<ModuleId, CompiledModule>,
+    ) {
+        let old_module_ids = compile(OLD_LIB, &[])
+            .iter()
+            .map(|module| module.self_id())
+            .collect();
+        let app = compile(APP, &[OLD_LIB]).pop().unwrap();
+        let new_modules = compile(new_lib, &[])
+            .into_iter()
+            .map(|module| (module.self_id(), module))
+            .collect();
+        (app, old_module_ids, new_modules)
+    }
+
+    async fn link(
+        app: &CompiledModule,
+        old_module_ids: &BTreeSet<ModuleId>,
+        new_modules: &BTreeMap<ModuleId, CompiledModule>,
+    ) -> Option<String> {
+        // The app only depends on the upgraded package, so nothing is fetched from the chain
+        let client = Client::new(Url::parse("http://localhost:8080").unwrap());
+        link_against_upgrade(
+            app,
+            lib_account(),
+            old_module_ids,
+            new_modules,
+            &mut ChainModules::new(client),
+        )
+        .await
+        .unwrap()
+    }
+
+    fn lib_account() -> AccountAddress {
+        AccountAddress::from_hex_literal("0xcafe").unwrap()
+    }
+
+    fn lib_id() -> ModuleId {
+        ModuleId::new(lib_account(), "lib".parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_compatible_upgrade() {
+        let new_lib = r#"
+            module 0xcafe::lib {
+                struct Coin has store, drop { value: u64 }
+                public fun mint(value: u64): Coin { Coin { value } }
+                public fun value(coin: &Coin): u64 { coin.value }
+                public fun burn(coin: Coin) { let Coin { value: _ } = coin; }
+            }
+        "#;
+        let (app, old_module_ids, new_modules) = compile_upgrade(new_lib);
+
+        assert!(check_references(&app, lib_account(), &old_module_ids, &new_modules).is_empty());
+        assert_eq!(link(&app, &old_module_ids, &new_modules).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_broken_references() {
+        let new_lib = r#"
+            module 0xcafe::lib {
+                struct Coin has store { value: u64 }
+                public fun mint(value: u64, extra: u64): Coin { Coin { value: value + extra } }
+                public(friend) fun value(coin: &Coin): u64 { coin.value }
+            }
+        "#;
+        let (app, old_module_ids, new_modules) = compile_upgrade(new_lib);
+
+        let mut broken = check_references(&app, lib_account(), &old_module_ids, &new_modules);
+        broken.sort();
+        let lib_id = lib_id();
+        assert_eq!(broken, vec![
+            format!("function {}::mint: signature changed", lib_id),
+            format!("function {}::value: visibility changed to Friend", lib_id),
+            format!(
+                "struct {}::Coin: abilities changed from {:?} to {:?}",
+                lib_id,
+                app.struct_handles()[0].abilities,
+                new_modules[&lib_id].struct_handles()[0].abilities
+            ),
+        ]);
+        let link_error = link(&app, &old_module_ids, &new_modules).await.unwrap();
+        assert!(link_error.starts_with("TYPE_MISMATCH"), "{}", link_error);
+    }
+
+    #[tokio::test]
+    async fn test_removed_module() {
+        let new_lib = r#"
+            module 0xcafe::other {
+                public fun answer(): u64 { 42 }
+            }
+        "#;
+        let (app, old_module_ids, new_modules) = compile_upgrade(new_lib);
+
+        let mut broken = check_references(&app, lib_account(), &old_module_ids, &new_modules);
+        broken.sort();
+        let lib_id = lib_id();
+        assert_eq!(broken, vec![
+            format!("function {}::mint: module was removed", lib_id),
+            format!("function {}::value: module was removed", lib_id),
+            format!("struct {}::Coin: module was removed", lib_id),
+        ]);
+        assert_eq!(
+            link(&app, &old_module_ids, &new_modules).await,
+            Some(format!("MISSING_DEPENDENCY: {}", lib_id))
+        );
+    }
+}
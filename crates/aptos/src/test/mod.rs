@@ -42,6 +42,10 @@ use crate::{
         AddStake, IncreaseLockup, InitializeStakeOwner, SetDelegatedVoter, SetOperator,
         UnlockStake, WithdrawStake,
     },
+    txn::{
+        BuildTransaction, SignTransaction, SubmitTransaction, TransactionSignature,
+        UnsignedTransactionFile, UnsignedTransactionSummary,
+    },
     CliCommand,
 };
 use aptos_config::config::Peer;
@@ -336,6 +340,84 @@ impl CliTestFramework {
         .await
     }
 
+    /// Builds an unsigned coin transfer, optionally paid for by a fee payer, into the file
+    pub async fn txn_build_transfer(
+        &self,
+        sender_index: usize,
+        receiver_index: usize,
+        amount: u64,
+        fee_payer_index: Option<usize>,
+        output_file: PathBuf,
+    ) -> CliTypedResult<UnsignedTransactionSummary> {
+        BuildTransaction {
+            entry_function_args: EntryFunctionArguments {
+                function_id: Some(MemberId::from_str("0x1::aptos_account::transfer").unwrap()),
+                arg_vec: ArgWithTypeVec {
+                    args: vec![
+                        ArgWithType::address(self.account_id(receiver_index)),
+                        ArgWithType::u64(amount),
+                    ],
+                },
+                type_arg_vec: TypeArgVec { type_args: vec![] },
+                json_file: None,
+            },
+            sender_account: Some(self.account_id(sender_index)),
+            sequence_number: None,
+            chain_id: None,
+            secondary_signer_addresses: vec![],
+            fee_payer_address: fee_payer_index.map(|index| self.account_id(index)),
+            gas_unit_price: None,
+            max_gas: 10_000,
+            expiration_secs: 60,
+            profile_options: Default::default(),
+            rest_options: self.rest_options(),
+            save_file: SaveFile {
+                output_file,
+                prompt_options: PromptOptions::yes(),
+            },
+        }
+        .execute()
+        .await
+    }
+
+    /// Signs the transaction file with the key of the account into the signature file
+    pub async fn txn_sign(
+        &self,
+        index: usize,
+        transaction_file: PathBuf,
+        output_file: PathBuf,
+    ) -> CliTypedResult<TransactionSignature> {
+        SignTransaction {
+            transaction_file: UnsignedTransactionFile { transaction_file },
+            signer_account: Some(self.account_id(index)),
+            private_key_options: PrivateKeyInputOptions::from_private_key(self.private_key(index))
+                .unwrap(),
+            encoding_options: Default::default(),
+            profile_options: Default::default(),
+            save_file: SaveFile {
+                output_file,
+                prompt_options: PromptOptions::yes(),
+            },
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn txn_submit(
+        &self,
+        transaction_file: PathBuf,
+        signature_files: Vec<PathBuf>,
+    ) -> CliTypedResult<TransactionSummary> {
+        SubmitTransaction {
+            transaction_file: UnsignedTransactionFile { transaction_file },
+            signature_files,
+            profile_options: Default::default(),
+            rest_options: self.rest_options(),
+        }
+        .execute()
+        .await
+    }
+
     pub async fn show_validator_config(
         &self,
         pool_index: usize,
@@ -63,16 +63,20 @@ async fn ensure_every_command_args_work() {
     assert_cmd_not_panic(&["aptos", "key", "extract-peer", "--help"]).await;
 
     assert_cmd_not_panic(&["aptos", "move"]).await;
+    assert_cmd_not_panic(&["aptos", "move", "check-upgrade", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "clean", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "compile", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "compile-script", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "decompile", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "disassemble", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "download", "--help"]).await;
+    assert_cmd_not_panic(&["aptos", "move", "gas-diff", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "init", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "list", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "prove", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "publish", "--help"]).await;
+    assert_cmd_not_panic(&["aptos", "move", "replay", "--help"]).await;
+    assert_cmd_not_panic(&["aptos", "move", "replay-range", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "run", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "run-script", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "move", "test", "--help"]).await;
@@ -108,6 +112,11 @@ async fn ensure_every_command_args_work() {
     assert_cmd_not_panic(&["aptos", "stake", "set-operator", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "stake", "unlock-stake", "--help"]).await;
     assert_cmd_not_panic(&["aptos", "stake", "withdraw-stake", "--help"]).await;
+
+    assert_cmd_not_panic(&["aptos", "txn"]).await;
+    assert_cmd_not_panic(&["aptos", "txn", "build", "--help"]).await;
+    assert_cmd_not_panic(&["aptos", "txn", "sign", "--help"]).await;
+    assert_cmd_not_panic(&["aptos", "txn", "submit", "--help"]).await;
 }
 
 /// Ensure we can parse URLs for args
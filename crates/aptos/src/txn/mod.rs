@@ -0,0 +1,728 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline transaction workflow
+//!
+//! Transactions are built into an unsigned transaction file with `txn build`, which can be
+//! carried to air-gapped machines to be signed with `txn sign`. Each signer (the sender, the
+//! secondary signers of a multi-agent transaction and the fee payer) produces its own
+//! signature file, and all of them are assembled and submitted with `txn submit`.
+
+use crate::common::{
+    types::{
+        CliCommand, CliConfig, CliError, CliTypedResult, ConfigSearchMode, EncodingOptions,
+        EntryFunctionArguments, PrivateKeyInputOptions, ProfileOptions, RestOptions, SaveFile,
+        TransactionSummary,
+    },
+    utils::{
+        chain_id, explorer_transaction_link, get_sequence_number, prompt_yes_with_override,
+        read_from_file,
+    },
+};
+use aptos_crypto::{hash::CryptoHash, signing_message, PrivateKey, SigningKey};
+use aptos_rest_client::Client;
+use aptos_sdk::types::{HardwareWalletAccount, HardwareWalletType};
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{
+        authenticator::AccountAuthenticator, RawTransaction, RawTransactionWithData,
+        SignedTransaction, TransactionPayload,
+    },
+};
+use async_trait::async_trait;
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Tool for building, signing and submitting transactions in separate steps
+///
+/// This allows transactions to be signed on an offline machine: `build` and `submit`
+/// are run on a machine with access to a fullnode, and `sign` on the machine holding
+/// the keys, with files carried between them.
+#[derive(Subcommand)]
+pub enum TxnTool {
+    Build(BuildTransaction),
+    Sign(SignTransaction),
+    Submit(SubmitTransaction),
+}
+
+impl TxnTool {
+    pub async fn execute(self) -> CliTypedResult<String> {
+        match self {
+            TxnTool::Build(tool) => tool.execute_serialized().await,
+            TxnTool::Sign(tool) => tool.execute_serialized().await,
+            TxnTool::Submit(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
+/// A transaction which has not been signed yet, along with the other accounts which have to
+/// sign it
+///
+/// This is the content of the files written by `txn build`, encoded as BCS.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    pub raw_txn: RawTransaction,
+    pub secondary_signer_addresses: Vec<AccountAddress>,
+    pub fee_payer_address: Option<AccountAddress>,
+}
+
+impl UnsignedTransaction {
+    /// The transaction with the additional signers, which is what the signers of multi-agent
+    /// and fee payer transactions sign
+    fn raw_txn_with_data(&self) -> Option<RawTransactionWithData> {
+        match self.fee_payer_address {
+            Some(fee_payer_address) => Some(RawTransactionWithData::new_fee_payer(
+                self.raw_txn.clone(),
+                self.secondary_signer_addresses.clone(),
+                fee_payer_address,
+            )),
+            None if !self.secondary_signer_addresses.is_empty() => {
+                Some(RawTransactionWithData::new_multi_agent(
+                    self.raw_txn.clone(),
+                    self.secondary_signer_addresses.clone(),
+                ))
+            },
+            None => None,
+        }
+    }
+
+    /// The message that every signer of the transaction signs
+    pub fn signing_message(&self) -> CliTypedResult<Vec<u8>> {
+        match self.raw_txn_with_data() {
+            Some(raw_txn_with_data) => to_signing_message(&raw_txn_with_data),
+            None => to_signing_message(&self.raw_txn),
+        }
+    }
+
+    /// All the accounts which have to sign the transaction, starting with the sender
+    pub fn signers(&self) -> Vec<AccountAddress> {
+        let mut signers = vec![self.raw_txn.sender()];
+        signers.extend(self.secondary_signer_addresses.iter().copied());
+        signers.extend(self.fee_payer_address);
+        signers
+    }
+
+    /// Checks that the authenticator is a valid signature of this transaction
+    pub fn verify(&self, authenticator: &AccountAuthenticator) -> CliTypedResult<()> {
+        let result = match self.raw_txn_with_data() {
+            Some(raw_txn_with_data) => authenticator.verify(&raw_txn_with_data),
+            None => authenticator.verify(&self.raw_txn),
+        };
+        result.map_err(|err| CliError::UnexpectedError(format!("Invalid signature: {}", err)))
+    }
+
+    /// Builds the signed transaction from the signatures of all of its signers
+    pub fn into_signed_transaction(
+        self,
+        signatures: &BTreeMap<AccountAddress, AccountAuthenticator>,
+    ) -> CliTypedResult<SignedTransaction> {
+        let missing_signers: Vec<_> = self
+            .signers()
+            .into_iter()
+            .filter(|signer| !signatures.contains_key(signer))
+            .map(|signer| signer.to_hex_literal())
+            .collect();
+        if !missing_signers.is_empty() {
+            return Err(CliError::CommandArgumentError(format!(
+                "Missing signatures of {}",
+                missing_signers.join(", ")
+            )));
+        }
+
+        let sender = signatures[&self.raw_txn.sender()].clone();
+        let secondary_signers = self
+            .secondary_signer_addresses
+            .iter()
+            .map(|address| signatures[address].clone())
+            .collect();
+        let signed_txn = match (self.fee_payer_address, sender) {
+            (Some(fee_payer_address), sender) => SignedTransaction::new_fee_payer(
+                self.raw_txn,
+                sender,
+                self.secondary_signer_addresses,
+                secondary_signers,
+                fee_payer_address,
+                signatures[&fee_payer_address].clone(),
+            ),
+            (None, sender) if !self.secondary_signer_addresses.is_empty() => {
+                SignedTransaction::new_multi_agent(
+                    self.raw_txn,
+                    sender,
+                    self.secondary_signer_addresses,
+                    secondary_signers,
+                )
+            },
+            (
+                None,
+                AccountAuthenticator::Ed25519 {
+                    public_key,
+                    signature,
+                },
+            ) => SignedTransaction::new(self.raw_txn, public_key, signature),
+            (None, sender) => SignedTransaction::new_single_sender(self.raw_txn, sender),
+        };
+        Ok(signed_txn)
+    }
+}
+
+/// The signature of a transaction by one of its signers
+///
+/// This is the content of the files written by `txn sign`, encoded as BCS.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSignature {
+    pub signer: AccountAddress,
+    pub authenticator: AccountAuthenticator,
+}
+
+/// Options for reading an unsigned transaction file
+#[derive(Debug, Parser)]
+pub struct UnsignedTransactionFile {
+    /// Path to the unsigned transaction file, as written by `txn build`
+    #[clap(long, value_parser)]
+    pub(crate) transaction_file: PathBuf,
+}
+
+impl UnsignedTransactionFile {
+    fn load(&self) -> CliTypedResult<UnsignedTransaction> {
+        let bytes = read_from_file(&self.transaction_file)?;
+        bcs::from_bytes(&bytes).map_err(|err| CliError::BCS("UnsignedTransaction", err))
+    }
+}
+
+/// Build an unsigned transaction and save it to a file
+///
+/// The sequence number of the sender, the chain id and the gas unit price can be given on the
+/// command line, so that the transaction can be built without access to a fullnode. The ones
+/// which are not given are fetched from the REST endpoint.
+///
+/// As the transaction may take time to be signed, consider setting a longer expiration
+/// with `--expiration-secs`.
+#[derive(Parser)]
+pub struct BuildTransaction {
+    #[clap(flatten)]
+    pub(crate) entry_function_args: EntryFunctionArguments,
+
+    /// Address of the sender of the transaction
+    ///
+    /// Defaults to the account of the profile
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) sender_account: Option<AccountAddress>,
+
+    /// Sequence number of the sender for the transaction
+    ///
+    /// Defaults to the current sequence number of the sender on-chain
+    #[clap(long)]
+    pub(crate) sequence_number: Option<u64>,
+
+    /// Chain id of the network the transaction is for, e.g. `testnet` or `4`
+    ///
+    /// Defaults to the chain id of the REST endpoint
+    #[clap(long)]
+    pub(crate) chain_id: Option<ChainId>,
+
+    /// Addresses of the secondary signers, for a multi-agent transaction
+    #[clap(long, num_args = 0.., value_parser = crate::common::types::load_account_arg)]
+    pub(crate) secondary_signer_addresses: Vec<AccountAddress>,
+
+    /// Address of the account paying for the gas of the transaction, if not the sender
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) fee_payer_address: Option<AccountAddress>,
+
+    /// Gas multiplier per unit of gas
+    ///
+    /// Defaults to the current estimated gas price
+    #[clap(long)]
+    pub(crate) gas_unit_price: Option<u64>,
+
+    /// Maximum amount of gas units to be used to send this transaction
+    #[clap(long)]
+    pub(crate) max_gas: u64,
+
+    /// Number of seconds from the current local computer time after which the transaction
+    /// expires
+    #[clap(long, default_value_t = 3600)]
+    pub(crate) expiration_secs: u64,
+
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) save_file: SaveFile,
+}
+
+/// A summary of a transaction in a transaction file
+#[derive(Debug, Serialize)]
+pub struct UnsignedTransactionSummary {
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub chain_id: ChainId,
+    pub expiration_timestamp_secs: u64,
+    pub max_gas_amount: u64,
+    pub gas_unit_price: u64,
+    pub signers: Vec<AccountAddress>,
+}
+
+impl From<&UnsignedTransaction> for UnsignedTransactionSummary {
+    fn from(txn: &UnsignedTransaction) -> Self {
+        Self {
+            sender: txn.raw_txn.sender(),
+            sequence_number: txn.raw_txn.sequence_number(),
+            chain_id: txn.raw_txn.chain_id(),
+            expiration_timestamp_secs: txn.raw_txn.expiration_timestamp_secs(),
+            max_gas_amount: txn.raw_txn.max_gas_amount(),
+            gas_unit_price: txn.raw_txn.gas_unit_price(),
+            signers: txn.signers(),
+        }
+    }
+}
+
+#[async_trait]
+impl CliCommand<UnsignedTransactionSummary> for BuildTransaction {
+    fn command_name(&self) -> &'static str {
+        "BuildTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<UnsignedTransactionSummary> {
+        self.save_file.check_file()?;
+
+        let sender = match self.sender_account {
+            Some(sender) => sender,
+            None => self.profile_options.account_address()?,
+        };
+
+        // Only connect to the REST endpoint if something has to be fetched
+        let client = if self.sequence_number.is_none()
+            || self.chain_id.is_none()
+            || self.gas_unit_price.is_none()
+        {
+            Some(self.rest_options.client(&self.profile_options)?)
+        } else {
+            None
+        };
+        let sequence_number = match (self.sequence_number, &client) {
+            (Some(sequence_number), _) => sequence_number,
+            (None, Some(client)) => get_sequence_number(client, sender).await?,
+            (None, None) => unreachable!("client is created when the sequence number is missing"),
+        };
+        let chain_id = match (self.chain_id, &client) {
+            (Some(chain_id), _) => chain_id,
+            (None, Some(client)) => chain_id(client).await?,
+            (None, None) => unreachable!("client is created when the chain id is missing"),
+        };
+        let gas_unit_price = match (self.gas_unit_price, &client) {
+            (Some(gas_unit_price), _) => gas_unit_price,
+            (None, Some(client)) => client.estimate_gas_price().await?.into_inner().gas_estimate,
+            (None, None) => unreachable!("client is created when the gas unit price is missing"),
+        };
+
+        let expiration_timestamp_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+            .as_secs()
+            + self.expiration_secs;
+        let raw_txn = RawTransaction::new(
+            sender,
+            sequence_number,
+            TransactionPayload::EntryFunction(self.entry_function_args.try_into()?),
+            self.max_gas,
+            gas_unit_price,
+            expiration_timestamp_secs,
+            chain_id,
+        );
+        let txn = UnsignedTransaction {
+            raw_txn,
+            secondary_signer_addresses: self.secondary_signer_addresses,
+            fee_payer_address: self.fee_payer_address,
+        };
+
+        let bytes = bcs::to_bytes(&txn).map_err(|err| CliError::BCS("UnsignedTransaction", err))?;
+        self.save_file
+            .save_to_file("Unsigned transaction", &bytes)?;
+        Ok((&txn).into())
+    }
+}
+
+/// Sign a transaction file built with `txn build`
+///
+/// This does not require access to a fullnode. The transaction is signed with the private key
+/// given on the command line or in the profile, or with the Ledger of the profile. The signature
+/// is saved to a file, to be given to `txn submit`.
+#[derive(Parser)]
+pub struct SignTransaction {
+    #[clap(flatten)]
+    pub(crate) transaction_file: UnsignedTransactionFile,
+
+    /// Address of the signing account, if it is not derived from the key, e.g. because its
+    /// authentication key has been rotated
+    #[clap(long, value_parser = crate::common::types::load_account_arg)]
+    pub(crate) signer_account: Option<AccountAddress>,
+
+    #[clap(flatten)]
+    pub(crate) private_key_options: PrivateKeyInputOptions,
+    #[clap(flatten)]
+    pub(crate) encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) save_file: SaveFile,
+}
+
+impl SignTransaction {
+    /// Whether the transaction is signed with the Ledger of the profile, i.e. no private key
+    /// is given on the command line and the profile is a Ledger profile
+    fn is_hardware_wallet(&self) -> CliTypedResult<bool> {
+        if self
+            .private_key_options
+            .extract_private_key_cli(self.encoding_options.encoding)?
+            .is_some()
+        {
+            return Ok(false);
+        }
+        let profile = CliConfig::load_profile(
+            self.profile_options.profile_name(),
+            ConfigSearchMode::CurrentDirAndParents,
+        )?;
+        Ok(profile.map_or(false, |profile| {
+            profile.private_key.is_none() && profile.derivation_path.is_some()
+        }))
+    }
+
+    /// Signs the message with the private key if there is one, or with the Ledger of the
+    /// profile otherwise
+    fn sign(&self, message: &[u8]) -> CliTypedResult<(AccountAddress, AccountAuthenticator)> {
+        let encoding = self.encoding_options.encoding;
+        if !self.is_hardware_wallet()? {
+            let (private_key, signer) = self.private_key_options.extract_private_key_and_address(
+                encoding,
+                &self.profile_options,
+                self.signer_account,
+            )?;
+            let signature = private_key.sign_arbitrary_message(message);
+            let authenticator = AccountAuthenticator::ed25519(private_key.public_key(), signature);
+            return Ok((signer, authenticator));
+        }
+
+        let (public_key, signer) = self.private_key_options.extract_public_key_and_address(
+            encoding,
+            &self.profile_options,
+            self.signer_account,
+        )?;
+        let derivation_path = self.profile_options.derivation_path()?.ok_or_else(|| {
+            CliError::UnexpectedError("The Ledger profile has no derivation path".to_string())
+        })?;
+        let hardware_wallet = HardwareWalletAccount::new(
+            signer,
+            public_key.clone(),
+            derivation_path,
+            HardwareWalletType::Ledger,
+            0,
+        );
+        eprintln!("Please confirm the transaction on your Ledger");
+        let signature = hardware_wallet.sign_arbitrary_message(message)?;
+        let authenticator = AccountAuthenticator::ed25519(public_key, signature);
+        Ok((signer, authenticator))
+    }
+}
+
+#[async_trait]
+impl CliCommand<TransactionSignature> for SignTransaction {
+    fn command_name(&self) -> &'static str {
+        "SignTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSignature> {
+        self.save_file.check_file()?;
+        let txn = self.transaction_file.load()?;
+
+        let summary = UnsignedTransactionSummary::from(&txn);
+        let summary = serde_json::to_string_pretty(&summary)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        let payload = serde_json::to_string_pretty(txn.raw_txn.payload())
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        eprintln!("Transaction: {}", summary);
+        eprintln!("Payload: {}", payload);
+        prompt_yes_with_override(
+            "Do you want to sign this transaction?",
+            self.save_file.prompt_options,
+        )?;
+
+        let (signer, authenticator) = self.sign(&txn.signing_message()?)?;
+        if !txn.signers().contains(&signer) {
+            return Err(CliError::CommandArgumentError(format!(
+                "Account {} is not a signer of the transaction",
+                signer.to_hex_literal()
+            )));
+        }
+
+        let signature = TransactionSignature {
+            signer,
+            authenticator,
+        };
+        let bytes =
+            bcs::to_bytes(&signature).map_err(|err| CliError::BCS("TransactionSignature", err))?;
+        self.save_file
+            .save_to_file("Transaction signature", &bytes)?;
+        Ok(signature)
+    }
+}
+
+/// Submit a transaction file built with `txn build`, along with the signatures of all of
+/// its signers
+#[derive(Parser)]
+pub struct SubmitTransaction {
+    #[clap(flatten)]
+    pub(crate) transaction_file: UnsignedTransactionFile,
+
+    /// Paths to the signature files written by `txn sign`, one for each signer
+    #[clap(long, num_args = 1.., required = true, value_parser)]
+    pub(crate) signature_files: Vec<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for SubmitTransaction {
+    fn command_name(&self) -> &'static str {
+        "SubmitTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let txn = self.transaction_file.load()?;
+
+        let signers = txn.signers();
+        let mut signatures = BTreeMap::new();
+        for path in &self.signature_files {
+            let signature: TransactionSignature = bcs::from_bytes(&read_from_file(path)?)
+                .map_err(|err| CliError::BCS("TransactionSignature", err))?;
+            if !signers.contains(&signature.signer) {
+                return Err(CliError::CommandArgumentError(format!(
+                    "{} is signed by {}, which is not a signer of the transaction",
+                    path.display(),
+                    signature.signer.to_hex_literal()
+                )));
+            }
+            txn.verify(&signature.authenticator)?;
+            signatures.insert(signature.signer, signature.authenticator);
+        }
+        let signed_txn = txn.into_signed_transaction(&signatures)?;
+
+        let client = self.rest_options.client(&self.profile_options)?;
+        submit_signed_transaction(&client, &self.profile_options, &signed_txn).await
+    }
+}
+
+async fn submit_signed_transaction(
+    client: &Client,
+    profile_options: &ProfileOptions,
+    signed_txn: &SignedTransaction,
+) -> CliTypedResult<TransactionSummary> {
+    client
+        .submit_bcs(signed_txn)
+        .await
+        .map_err(|err| CliError::ApiError(err.to_string()))?;
+    let network = profile_options
+        .profile()
+        .ok()
+        .and_then(|profile| profile.network);
+    eprintln!(
+        "Transaction submitted: {}",
+        explorer_transaction_link(signed_txn.committed_hash(), network)
+    );
+    let response = client
+        .wait_for_signed_transaction(signed_txn)
+        .await
+        .map_err(|err| CliError::ApiError(err.to_string()))?;
+    Ok(response.into_inner().into())
+}
+
+fn to_signing_message<T: CryptoHash + Serialize>(message: &T) -> CliTypedResult<Vec<u8>> {
+    signing_message(message).map_err(|err| CliError::UnexpectedError(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::types::{account_address_from_public_key, PromptOptions};
+    use aptos_cached_packages::aptos_stdlib;
+    use aptos_crypto::ed25519::Ed25519PrivateKey;
+    use aptos_keygen::KeyGen;
+    use aptos_temppath::TempPath;
+
+    fn address(private_key: &Ed25519PrivateKey) -> AccountAddress {
+        account_address_from_public_key(&private_key.public_key())
+    }
+
+    /// Writes the transaction to a file, as `txn build` does
+    fn save_transaction(txn: &UnsignedTransaction) -> TempPath {
+        let path = TempPath::new();
+        std::fs::write(path.path(), bcs::to_bytes(txn).unwrap()).unwrap();
+        path
+    }
+
+    fn unsigned_transaction(
+        sender: AccountAddress,
+        secondary_signer_addresses: Vec<AccountAddress>,
+        fee_payer_address: Option<AccountAddress>,
+    ) -> UnsignedTransaction {
+        UnsignedTransaction {
+            raw_txn: RawTransaction::new(
+                sender,
+                0,
+                aptos_stdlib::aptos_coin_transfer(AccountAddress::ONE, 10),
+                1000,
+                100,
+                u64::MAX,
+                ChainId::test(),
+            ),
+            secondary_signer_addresses,
+            fee_payer_address,
+        }
+    }
+
+    async fn sign(
+        txn_path: &TempPath,
+        private_key_options: PrivateKeyInputOptions,
+    ) -> CliTypedResult<TransactionSignature> {
+        let output_file = TempPath::new();
+        let signature = SignTransaction {
+            transaction_file: UnsignedTransactionFile {
+                transaction_file: txn_path.path().to_path_buf(),
+            },
+            signer_account: None,
+            private_key_options,
+            encoding_options: EncodingOptions::default(),
+            profile_options: ProfileOptions::default(),
+            save_file: SaveFile {
+                output_file: output_file.path().to_path_buf(),
+                prompt_options: PromptOptions::yes(),
+            },
+        }
+        .execute()
+        .await?;
+
+        // The signature file is what `txn submit` reads
+        let saved: TransactionSignature =
+            bcs::from_bytes(&read_from_file(output_file.path())?).unwrap();
+        assert_eq!(saved, signature);
+        Ok(signature)
+    }
+
+    async fn sign_with_key(
+        txn_path: &TempPath,
+        private_key: &Ed25519PrivateKey,
+    ) -> TransactionSignature {
+        sign(
+            txn_path,
+            PrivateKeyInputOptions::from_private_key(private_key).unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sign_single_sender() {
+        let mut keygen = KeyGen::from_seed([0; 32]);
+        let sender_key = keygen.generate_ed25519_private_key();
+        let txn = unsigned_transaction(address(&sender_key), vec![], None);
+        let txn_path = save_transaction(&txn);
+
+        let signature = sign_with_key(&txn_path, &sender_key).await;
+        assert_eq!(signature.signer, address(&sender_key));
+        txn.verify(&signature.authenticator).unwrap();
+
+        let signatures = BTreeMap::from([(signature.signer, signature.authenticator)]);
+        let signed_txn = txn.clone().into_signed_transaction(&signatures).unwrap();
+        signed_txn.verify_signature().unwrap();
+        assert_eq!(signed_txn.raw_transaction_ref(), &txn.raw_txn);
+    }
+
+    #[tokio::test]
+    async fn test_sign_fee_payer_with_secondary_signer() {
+        let mut keygen = KeyGen::from_seed([1; 32]);
+        let sender_key = keygen.generate_ed25519_private_key();
+        let secondary_key = keygen.generate_ed25519_private_key();
+        let fee_payer_key = keygen.generate_ed25519_private_key();
+        let txn = unsigned_transaction(
+            address(&sender_key),
+            vec![address(&secondary_key)],
+            Some(address(&fee_payer_key)),
+        );
+        let txn_path = save_transaction(&txn);
+
+        let mut signatures = BTreeMap::new();
+        for key in [&sender_key, &secondary_key, &fee_payer_key] {
+            let signature = sign_with_key(&txn_path, key).await;
+            txn.verify(&signature.authenticator).unwrap();
+            signatures.insert(signature.signer, signature.authenticator);
+        }
+
+        // All signers are required
+        let mut missing_fee_payer = signatures.clone();
+        missing_fee_payer.remove(&address(&fee_payer_key));
+        assert!(matches!(
+            txn.clone().into_signed_transaction(&missing_fee_payer),
+            Err(CliError::CommandArgumentError(_))
+        ));
+
+        let signed_txn = txn.into_signed_transaction(&signatures).unwrap();
+        signed_txn.verify_signature().unwrap();
+        let authenticator = signed_txn.authenticator();
+        assert_eq!(
+            authenticator.fee_payer_address(),
+            Some(address(&fee_payer_key))
+        );
+        assert_eq!(authenticator.secondary_signer_addresses(), vec![address(
+            &secondary_key
+        )]);
+    }
+
+    #[tokio::test]
+    async fn test_sign_rejects_other_signers() {
+        let mut keygen = KeyGen::from_seed([2; 32]);
+        let sender_key = keygen.generate_ed25519_private_key();
+        let other_key = keygen.generate_ed25519_private_key();
+        let txn = unsigned_transaction(address(&sender_key), vec![], None);
+        let txn_path = save_transaction(&txn);
+
+        let result = sign(
+            &txn_path,
+            PrivateKeyInputOptions::from_private_key(&other_key).unwrap(),
+        )
+        .await;
+        assert!(matches!(result, Err(CliError::CommandArgumentError(_))));
+
+        // A signature of another transaction doesn't verify
+        let other_txn = unsigned_transaction(address(&other_key), vec![], None);
+        let signature = sign_with_key(&save_transaction(&other_txn), &other_key).await;
+        assert!(txn.verify(&signature.authenticator).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sign_with_unreadable_key_file() {
+        let txn = unsigned_transaction(AccountAddress::ONE, vec![], None);
+        let txn_path = save_transaction(&txn);
+
+        // The error reading the key is reported, instead of falling back to a Ledger
+        let key_file = TempPath::new();
+        let result = sign(
+            &txn_path,
+            PrivateKeyInputOptions::from_file(key_file.path().to_path_buf()),
+        )
+        .await;
+        assert!(
+            matches!(result, Err(CliError::UnableToReadFile(_, _))),
+            "{:?}",
+            result
+        );
+    }
+}
@@ -8,7 +8,7 @@
 //! over how the internal queueing in the channel happens and how we schedule messages
 //! to be sent out from this channel.
 //! Internally, it uses the `PerKeyQueue` to store messages
-use crate::message_queues::{PerKeyQueue, QueueStyle};
+use crate::message_queues::{KeyQueueConfig, PerKeyQueue, QueueStyle};
 use anyhow::{ensure, Result};
 use aptos_infallible::{Mutex, NonZeroUsize};
 use aptos_metrics_core::IntCounterVec;
@@ -17,6 +17,7 @@ use futures::{
     stream::{FusedStream, Stream},
 };
 use std::{
+    collections::HashMap,
     fmt::{Debug, Formatter},
     hash::Hash,
     pin::Pin,
@@ -237,14 +238,39 @@ pub fn new<K: Eq + Hash + Clone, M>(
     queue_style: QueueStyle,
     max_queue_size_per_key: usize,
     counters: Option<&'static IntCounterVec>,
+) -> (Sender<K, M>, Receiver<K, M>) {
+    new_with_key_configs(
+        queue_style,
+        max_queue_size_per_key,
+        HashMap::new(),
+        None,
+        counters,
+    )
+}
+
+/// Create a new Channel with the given per-key configs (e.g., to prioritize
+/// some keys over others using weights) and returns the two ends of the channel.
+/// If a message cost function is given (e.g., the message size in bytes), the
+/// key weights are in units of that cost. Otherwise, each message costs 1.
+pub fn new_with_key_configs<K: Eq + Hash + Clone, M>(
+    queue_style: QueueStyle,
+    max_queue_size_per_key: usize,
+    key_configs: HashMap<K, KeyQueueConfig>,
+    message_cost: Option<fn(&M) -> usize>,
+    counters: Option<&'static IntCounterVec>,
 ) -> (Sender<K, M>, Receiver<K, M>) {
     let max_queue_size_per_key =
         NonZeroUsize!(max_queue_size_per_key, "aptos_channel cannot be of size 0");
     if let Some(counters) = counters {
         counters.reset();
     }
+    let mut internal_queue = PerKeyQueue::new(queue_style, max_queue_size_per_key, counters)
+        .with_key_configs(key_configs);
+    if let Some(message_cost) = message_cost {
+        internal_queue = internal_queue.with_message_cost(message_cost);
+    }
     let shared_state = Arc::new(Mutex::new(SharedState {
-        internal_queue: PerKeyQueue::new(queue_style, max_queue_size_per_key, counters),
+        internal_queue,
         waker: None,
         num_senders: 1,
         receiver_dropped: false,
@@ -2,7 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_metrics_core::IntCounterVec;
+use aptos_metrics_core::{IntCounterVec, IntGauge};
 use std::{
     collections::{HashMap, VecDeque},
     fmt::{Debug, Formatter, Result},
@@ -33,14 +33,31 @@ impl Default for QueueStyle {
     }
 }
 
+/// Optional configuration for a single key of a PerKeyQueue.
+#[derive(Clone, Debug)]
+pub struct KeyQueueConfig {
+    /// The credits given to the key's queue on each of its turns. Popping a
+    /// message consumes credits equal to the message's cost (1 by default, see
+    /// `with_message_cost`). Keys without a config have a weight of 1.
+    pub weight: NonZeroUsize,
+    /// Optional gauge for recording the number of pending messages in the
+    /// key's queue. The gauge may be shared by several queues.
+    pub pending_messages: Option<IntGauge>,
+}
+
 /// PerKeyQueue maintains a queue of messages per key. It
 /// is a bounded queue of messages per Key and the style (FIFO, LIFO) is
 /// configurable. When a new message is added using `push`, it is added to
 /// the key's queue.
 ///
 /// When `pop` is called, the next message is picked from one
-/// of the key's queue and returned. This happens in a round-robin
-/// fashion among keys.
+/// of the key's queue and returned. This happens in a (weighted) round-robin
+/// fashion among keys, i.e., a key with weight N has up to N messages popped
+/// before moving onto the next key. If messages have a cost (e.g., their size
+/// in bytes), this becomes deficit round-robin: each turn adds the key's
+/// weight to its credits, and messages are popped while the key can afford
+/// them. Unused credits are carried over to the key's next turn (unless its
+/// queue is empty), so large messages are eventually popped.
 ///
 /// If there are no messages, in any of the queues, `None` is returned.
 pub(crate) struct PerKeyQueue<K: Eq + Hash + Clone, T> {
@@ -61,6 +78,15 @@ pub(crate) struct PerKeyQueue<K: Eq + Hash + Clone, T> {
     /// Optional counters for recording # enqueued, # dequeued, and # dropped
     /// messages
     counters: Option<&'static IntCounterVec>,
+    /// Optional per-key configs (e.g., weights and gauges)
+    key_configs: HashMap<K, KeyQueueConfig>,
+    /// Returns the number of credits consumed by popping the given message
+    message_cost: fn(&T) -> usize,
+    /// The remaining credits of the keys in the round_robin_queue
+    key_credits: HashMap<K, usize>,
+    /// Whether the key at the front of the round_robin_queue has already been
+    /// given its credits for the current turn
+    front_key_credited: bool,
 }
 
 impl<K: Eq + Hash + Clone, T> Debug for PerKeyQueue<K, T> {
@@ -88,9 +114,49 @@ impl<K: Eq + Hash + Clone, T> PerKeyQueue<K, T> {
             round_robin_queue: VecDeque::new(),
             num_popped_since_gc: 0,
             counters,
+            key_configs: HashMap::new(),
+            message_cost: |_| 1,
+            key_credits: HashMap::new(),
+            front_key_credited: false,
         }
     }
 
+    /// Sets the per-key configs for the queue
+    pub(crate) fn with_key_configs(mut self, key_configs: HashMap<K, KeyQueueConfig>) -> Self {
+        self.key_configs = key_configs;
+        self
+    }
+
+    /// Sets the function used to compute the cost (in credits) of each message
+    pub(crate) fn with_message_cost(mut self, message_cost: fn(&T) -> usize) -> Self {
+        self.message_cost = message_cost;
+        self
+    }
+
+    /// Returns the weight of the given key
+    fn key_weight(&self, key: &K) -> usize {
+        self.key_configs
+            .get(key)
+            .map_or(1, |key_config| key_config.weight.get())
+    }
+
+    /// Returns the pending messages gauge of the given key (if one exists)
+    fn pending_messages_gauge(&self, key: &K) -> Option<&IntGauge> {
+        self.key_configs
+            .get(key)
+            .and_then(|key_config| key_config.pending_messages.as_ref())
+    }
+
+    /// Given a key, returns the next message that will be popped from its queue
+    fn peek_key_queue(&self, key: &K) -> Option<&T> {
+        self.per_key_queue
+            .get(key)
+            .and_then(|q| match self.queue_style {
+                QueueStyle::FIFO | QueueStyle::KLAST => q.front(),
+                QueueStyle::LIFO => q.back(),
+            })
+    }
+
     /// Given a key, pops the message from its queue and returns the message
     /// It also returns a boolean indicating whether the keys queue is empty
     /// after popping the message
@@ -128,7 +194,7 @@ impl<K: Eq + Hash + Clone, T> PerKeyQueue<K, T> {
 
         // Add the key to our round-robin queue if it's not already there
         if key_message_queue.is_empty() {
-            self.round_robin_queue.push_back(key);
+            self.round_robin_queue.push_back(key.clone());
         }
 
         // Push the message to the actual key message queue
@@ -148,6 +214,9 @@ impl<K: Eq + Hash + Clone, T> PerKeyQueue<K, T> {
             }
         } else {
             key_message_queue.push_back(message);
+            if let Some(gauge) = self.pending_messages_gauge(&key) {
+                gauge.inc();
+            }
             None
         }
     }
@@ -155,22 +224,49 @@ impl<K: Eq + Hash + Clone, T> PerKeyQueue<K, T> {
     /// pop a message from the appropriate queue in per_key_queue
     /// remove the key from the round_robin_queue if it has no more messages
     pub(crate) fn pop(&mut self) -> Option<T> {
-        let key = match self.round_robin_queue.pop_front() {
-            Some(v) => v,
-            _ => {
-                return None;
-            },
+        let key = loop {
+            let key = match self.round_robin_queue.front() {
+                Some(v) => v.clone(),
+                _ => {
+                    return None;
+                },
+            };
+
+            // If this is the start of the key's turn, give it its credits
+            if !self.front_key_credited {
+                let weight = self.key_weight(&key);
+                *self.key_credits.entry(key.clone()).or_default() += weight;
+                self.front_key_credited = true;
+            }
+
+            // If the key can't afford its next message, move onto the next key
+            // (the key keeps its credits for its next turn)
+            let credits = self.key_credits.get(&key).copied().unwrap_or_default();
+            let cost = self.peek_key_queue(&key).map_or(0, self.message_cost);
+            if cost <= credits {
+                self.key_credits.insert(key.clone(), credits - cost);
+                break key;
+            }
+            self.round_robin_queue.pop_front();
+            self.round_robin_queue.push_back(key);
+            self.front_key_credited = false;
         };
 
+        // Move onto the next key if this key is empty (its credits are dropped)
         let (message, is_q_empty) = self.pop_from_key_queue(&key);
-        if !is_q_empty {
-            self.round_robin_queue.push_back(key);
+        if is_q_empty {
+            self.round_robin_queue.pop_front();
+            self.key_credits.remove(&key);
+            self.front_key_credited = false;
         }
 
         if message.is_some() {
             if let Some(c) = self.counters.as_ref() {
                 c.with_label_values(&["dequeued"]).inc();
             }
+            if let Some(gauge) = self.pending_messages_gauge(&key) {
+                gauge.dec();
+            }
 
             // Remove empty per-key-queues every `POPS_PER_GC` successful dequeue
             // operations.
@@ -208,7 +304,27 @@ impl<K: Eq + Hash + Clone, T> PerKeyQueue<K, T> {
 
     /// Clears all the pending messages and cleans up the queue from the previous metadata.
     pub(crate) fn clear(&mut self) {
+        self.reset_pending_messages_gauges();
         self.per_key_queue.clear();
         self.round_robin_queue.clear();
+        self.key_credits.clear();
+        self.front_key_credited = false;
+    }
+
+    /// Removes all pending messages from the gauges (e.g., before the messages
+    /// are discarded).
+    fn reset_pending_messages_gauges(&self) {
+        for (key, queue) in self.per_key_queue.iter() {
+            if let Some(gauge) = self.pending_messages_gauge(key) {
+                gauge.sub(queue.len() as i64);
+            }
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, T> Drop for PerKeyQueue<K, T> {
+    fn drop(&mut self) {
+        // The gauges may be shared, so make sure our messages don't linger
+        self.reset_pending_messages_gauges();
     }
 }
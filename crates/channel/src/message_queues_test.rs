@@ -2,9 +2,11 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::message_queues::{PerKeyQueue, QueueStyle};
+use crate::message_queues::{KeyQueueConfig, PerKeyQueue, QueueStyle};
 use aptos_infallible::NonZeroUsize;
+use aptos_metrics_core::IntGauge;
 use aptos_types::account_address::AccountAddress;
+use std::collections::HashMap;
 
 /// This represents a proposal message from a validator
 #[derive(Debug, PartialEq, Eq)]
@@ -270,3 +272,130 @@ fn test_message_queue_clear() {
     });
     assert_eq!(q.pop().unwrap().msg, "msg3".to_string());
 }
+
+#[test]
+fn test_weighted_round_robin() {
+    let validator1 = AccountAddress::new([0u8; AccountAddress::LENGTH]);
+    let validator2 = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+
+    // Give validator1 a weight of 3 (validator2 has the default weight of 1)
+    let key_configs = HashMap::from([(validator1, KeyQueueConfig {
+        weight: NonZeroUsize!(3),
+        pending_messages: None,
+    })]);
+    let mut q =
+        PerKeyQueue::new(QueueStyle::FIFO, NonZeroUsize!(10), None).with_key_configs(key_configs);
+
+    for i in 1..=5 {
+        q.push(validator1, ProposalMsg {
+            msg: format!("validator1_msg{}", i),
+        });
+        q.push(validator2, ProposalMsg {
+            msg: format!("validator2_msg{}", i),
+        });
+    }
+
+    // Verify that validator1 gets 3 messages for every message of validator2
+    let expected_messages = [
+        "validator1_msg1",
+        "validator1_msg2",
+        "validator1_msg3",
+        "validator2_msg1",
+        "validator1_msg4",
+        "validator1_msg5",
+        "validator2_msg2",
+        "validator2_msg3",
+        "validator2_msg4",
+        "validator2_msg5",
+    ];
+    for expected_message in expected_messages {
+        assert_eq!(q.pop().unwrap().msg, expected_message.to_string());
+    }
+    assert_eq!(q.pop(), None);
+}
+
+#[test]
+fn test_weighted_round_robin_with_message_costs() {
+    let validator1 = AccountAddress::new([0u8; AccountAddress::LENGTH]);
+    let validator2 = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+
+    // Give both validators a weight of 10, and charge messages by their length
+    let key_config = KeyQueueConfig {
+        weight: NonZeroUsize!(10),
+        pending_messages: None,
+    };
+    let key_configs = HashMap::from([(validator1, key_config.clone()), (validator2, key_config)]);
+    let mut q = PerKeyQueue::new(QueueStyle::FIFO, NonZeroUsize!(10), None)
+        .with_key_configs(key_configs)
+        .with_message_cost(|message: &ProposalMsg| message.msg.len());
+
+    // Push large messages (with a cost of 20) for validator1, and small
+    // messages (with a cost of 6) for validator2.
+    for i in 1..=3 {
+        q.push(validator1, ProposalMsg {
+            msg: format!("large{:0>15}", i),
+        });
+    }
+    for i in 1..=6 {
+        q.push(validator2, ProposalMsg {
+            msg: format!("small{}", i),
+        });
+    }
+
+    // Verify that the validators share the queue by cost (not by number of
+    // messages), and that large messages are popped once enough credits
+    // have been accumulated.
+    let expected_messages = [
+        "small1",
+        "large000000000000001",
+        "small2",
+        "small3",
+        "small4",
+        "small5",
+        "large000000000000002",
+        "small6",
+        "large000000000000003",
+    ];
+    for expected_message in expected_messages {
+        assert_eq!(q.pop().unwrap().msg, expected_message.to_string());
+    }
+    assert_eq!(q.pop(), None);
+}
+
+#[test]
+fn test_pending_messages_gauge() {
+    let validator = AccountAddress::new([0u8; AccountAddress::LENGTH]);
+    let gauge = IntGauge::new("test_pending_messages", "test gauge").unwrap();
+    let key_configs = HashMap::from([(validator, KeyQueueConfig {
+        weight: NonZeroUsize!(1),
+        pending_messages: Some(gauge.clone()),
+    })]);
+
+    // Push more messages than the queue can hold and verify the gauge
+    let mut q = PerKeyQueue::new(QueueStyle::KLAST, NonZeroUsize!(2), None)
+        .with_key_configs(key_configs.clone());
+    for i in 0..3 {
+        q.push(validator, ProposalMsg {
+            msg: format!("msg{}", i),
+        });
+    }
+    assert_eq!(gauge.get(), 2);
+
+    // Pop a message and verify the gauge
+    assert_eq!(q.pop().unwrap().msg, "msg1".to_string());
+    assert_eq!(gauge.get(), 1);
+
+    // Clear the queue and verify the gauge
+    q.clear();
+    assert_eq!(gauge.get(), 0);
+
+    // Drop a queue with pending messages and verify the gauge
+    let mut q =
+        PerKeyQueue::new(QueueStyle::FIFO, NonZeroUsize!(2), None).with_key_configs(key_configs);
+    q.push(validator, ProposalMsg {
+        msg: "msg".to_string(),
+    });
+    assert_eq!(gauge.get(), 1);
+    drop(q);
+    assert_eq!(gauge.get(), 0);
+}
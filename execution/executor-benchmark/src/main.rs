@@ -266,6 +266,11 @@ struct Opt {
 
     #[clap(long)]
     skip_paranoid_checks: bool,
+
+    /// Write a report of the conflicts between transactions of every block executed in
+    /// parallel to this directory. Not supported with sharded execution.
+    #[clap(long)]
+    conflict_report_dir: Option<PathBuf>,
 }
 
 impl Opt {
@@ -567,6 +572,9 @@ fn main() {
     AptosVM::set_concurrency_level_once(execution_threads_per_shard);
     NativeExecutor::set_concurrency_level_once(execution_threads_per_shard);
     AptosVM::set_processed_transactions_detailed_counters();
+    if let Some(conflict_report_dir) = opt.conflict_report_dir.clone() {
+        AptosVM::set_conflict_report_dir_once(conflict_report_dir);
+    }
 
     let config = ProfilerConfig::new_with_defaults();
     let handler = ProfilerHandler::new(config);
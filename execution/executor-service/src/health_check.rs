@@ -0,0 +1,178 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Health checks of the remote executor shards. The coordinator periodically sends a health
+//! check to every shard, which the shard echoes back. A shard which hasn't answered within the
+//! timeout is considered unhealthy.
+
+use crate::metrics::REMOTE_EXECUTOR_UNHEALTHY_SHARDS;
+use aptos_infallible::Mutex;
+use aptos_logger::{info, warn};
+use aptos_secure_net::network_controller::{Message, NetworkController};
+use aptos_types::block_executor::partitioner::ShardId;
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+fn health_check_type(shard_id: ShardId) -> String {
+    format!("health_check_{}", shard_id)
+}
+
+fn health_check_ack_type(shard_id: ShardId) -> String {
+    format!("health_check_ack_{}", shard_id)
+}
+
+/// Answers the health checks of the coordinator on a shard.
+pub struct HealthCheckResponder {
+    shard_id: ShardId,
+    health_check_rx: Receiver<Message>,
+    ack_tx: Sender<Message>,
+}
+
+impl HealthCheckResponder {
+    pub fn new(
+        shard_id: ShardId,
+        controller: &mut NetworkController,
+        coordinator_address: SocketAddr,
+    ) -> Self {
+        Self {
+            shard_id,
+            health_check_rx: controller.create_inbound_channel(health_check_type(shard_id)),
+            ack_tx: controller
+                .create_outbound_channel(coordinator_address, health_check_ack_type(shard_id)),
+        }
+    }
+
+    /// Echoes the health checks back until the network controller is shut down.
+    pub fn start(&self) {
+        info!("Starting health check responder of shard {}", self.shard_id);
+        while let Ok(message) = self.health_check_rx.recv() {
+            if self.ack_tx.send(message).is_err() {
+                break;
+            }
+        }
+        info!("Stopping health check responder of shard {}", self.shard_id);
+    }
+}
+
+/// Sends health checks to the shards from the coordinator, and tracks when each shard last
+/// answered.
+pub struct ShardHealthMonitor {
+    last_acks: Arc<Vec<Mutex<Option<Instant>>>>,
+    timeout: Duration,
+    // Channels to send the health checks and receive their acks, moved to the thread sending
+    // them on start.
+    channels: Option<(Vec<Sender<Message>>, Vec<Receiver<Message>>)>,
+    stop: Arc<AtomicBool>,
+    join_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl ShardHealthMonitor {
+    /// Creates the channels of the health checks, which must be done before the network
+    /// controller is started. The health checks only start being sent on [`Self::start`].
+    pub fn new(
+        controller: &mut NetworkController,
+        remote_shard_addresses: &[SocketAddr],
+        timeout: Duration,
+    ) -> Self {
+        let channels = remote_shard_addresses
+            .iter()
+            .enumerate()
+            .map(|(shard_id, address)| {
+                (
+                    controller.create_outbound_channel(*address, health_check_type(shard_id)),
+                    controller.create_inbound_channel(health_check_ack_type(shard_id)),
+                )
+            })
+            .unzip();
+        Self {
+            last_acks: Arc::new(
+                (0..remote_shard_addresses.len())
+                    .map(|_| Mutex::new(None))
+                    .collect(),
+            ),
+            timeout,
+            channels: Some(channels),
+            stop: Arc::new(AtomicBool::new(false)),
+            join_handle: None,
+        }
+    }
+
+    /// Sends a health check to every shard at each interval, in a separate thread.
+    pub fn start(&mut self, interval: Duration) {
+        let (health_check_txs, ack_rxs) = self
+            .channels
+            .take()
+            .expect("Health monitor already started");
+        let last_acks = self.last_acks.clone();
+        let stop = self.stop.clone();
+        let join_handle = thread::Builder::new()
+            .name("shard-health-monitor".to_string())
+            .spawn(move || {
+                let mut seq: u64 = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    for (shard_id, ack_rx) in ack_rxs.iter().enumerate() {
+                        if ack_rx.try_iter().count() > 0 {
+                            *last_acks[shard_id].lock() = Some(Instant::now());
+                        }
+                    }
+                    let message = Message::new(bcs::to_bytes(&seq).unwrap());
+                    for health_check_tx in &health_check_txs {
+                        let _ = health_check_tx.send(message.clone());
+                    }
+                    seq += 1;
+                    thread::sleep(interval);
+                }
+            })
+            .expect("Failed to spawn thread");
+        self.join_handle = Some(join_handle);
+    }
+
+    /// Returns the shards which haven't answered a health check within the timeout.
+    pub fn unhealthy_shards(&self) -> Vec<ShardId> {
+        let unhealthy_shards = self
+            .last_acks
+            .iter()
+            .enumerate()
+            .filter(|(_, last_ack)| {
+                !matches!(*last_ack.lock(), Some(last_ack) if last_ack.elapsed() <= self.timeout)
+            })
+            .map(|(shard_id, _)| shard_id)
+            .collect::<Vec<_>>();
+        REMOTE_EXECUTOR_UNHEALTHY_SHARDS.set(unhealthy_shards.len() as i64);
+        unhealthy_shards
+    }
+
+    /// Waits until all the shards are healthy. Returns false if they are not after `timeout`.
+    pub fn wait_until_healthy(&self, timeout: Duration) -> bool {
+        let start = Instant::now();
+        loop {
+            let unhealthy_shards = self.unhealthy_shards();
+            if unhealthy_shards.is_empty() {
+                return true;
+            }
+            if start.elapsed() > timeout {
+                warn!(
+                    "Shards {:?} are still unhealthy after {:?}",
+                    unhealthy_shards, timeout
+                );
+                return false;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    pub fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(join_handle) = self.join_handle.take() {
+            let _ = join_handle.join();
+        }
+    }
+}
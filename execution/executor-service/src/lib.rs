@@ -12,6 +12,7 @@ use aptos_types::{
 use serde::{Deserialize, Serialize};
 
 mod error;
+pub mod health_check;
 pub mod local_executor_helper;
 mod metrics;
 pub mod process_executor_service;
@@ -21,8 +22,7 @@ pub mod remote_executor_client;
 pub mod remote_executor_service;
 mod remote_state_view;
 mod remote_state_view_service;
-#[cfg(test)]
-mod test_utils;
+pub mod test_utils;
 #[cfg(test)]
 mod tests;
 #[cfg(test)]
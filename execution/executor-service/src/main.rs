@@ -3,8 +3,9 @@
 
 use aptos_executor_service::process_executor_service::ProcessExecutorService;
 use aptos_logger::info;
+use aptos_secure_net::grpc_network_service::TlsConfig;
 use clap::Parser;
-use std::net::SocketAddr;
+use std::{net::SocketAddr, path::PathBuf};
 
 #[derive(Debug, Parser)]
 struct Args {
@@ -22,11 +23,42 @@ struct Args {
 
     #[clap(long)]
     pub coordinator_address: SocketAddr,
+
+    /// CA certificate the coordinator and the other shards are authenticated against. Enables
+    /// TLS on all the channels, along with `--tls-cert` and `--tls-key`.
+    #[clap(long, requires_all = ["tls_cert", "tls_key"])]
+    pub tls_ca_cert: Option<PathBuf>,
+
+    #[clap(long, requires = "tls_ca_cert")]
+    pub tls_cert: Option<PathBuf>,
+
+    #[clap(long, requires = "tls_ca_cert")]
+    pub tls_key: Option<PathBuf>,
+
+    /// Domain name the certificates of the coordinator and the other shards are issued for.
+    #[clap(long, default_value = "aptos-secure-net")]
+    pub tls_domain_name: String,
+}
+
+impl Args {
+    fn tls_config(&self) -> Option<TlsConfig> {
+        let ca_cert = self.tls_ca_cert.as_ref()?;
+        Some(
+            TlsConfig::from_files(
+                ca_cert,
+                self.tls_cert.as_ref().expect("--tls-cert is required"),
+                self.tls_key.as_ref().expect("--tls-key is required"),
+                self.tls_domain_name.clone(),
+            )
+            .expect("Failed to read the TLS certificates"),
+        )
+    }
 }
 
 fn main() {
     let args = Args::parse();
     aptos_logger::Logger::new().init();
+    let tls_config = args.tls_config();
 
     let (tx, rx) = crossbeam_channel::unbounded();
     ctrlc::set_handler(move || {
@@ -40,6 +72,7 @@ fn main() {
         args.num_executor_threads,
         args.coordinator_address,
         args.remote_executor_addresses,
+        tls_config,
     );
 
     rx.recv()
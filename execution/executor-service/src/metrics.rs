@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics_core::{
-    exponential_buckets, register_histogram_vec, register_int_counter_vec, HistogramVec,
-    IntCounterVec,
+    exponential_buckets, register_histogram_vec, register_int_counter_vec, register_int_gauge,
+    HistogramVec, IntCounterVec, IntGauge,
 };
 use once_cell::sync::Lazy;
 
@@ -44,3 +44,26 @@ pub static REMOTE_EXECUTOR_REMOTE_KV_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static REMOTE_EXECUTOR_UNHEALTHY_SHARDS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "remote_executor_unhealthy_shards",
+        "Number of remote executor shards which haven't answered the last health checks"
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_LOCAL_FALLBACK_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_local_fallback_count",
+        // metric description
+        "Number of blocks executed locally instead of on the remote shards, because: \
+         1. unhealthy_shards: some shards did not answer the health checks; \
+         2. shard_failure: some shards failed to return the result of the block; \
+         3. fallback_engaged: a shard failed during an earlier block;",
+        // metric labels (dimensions)
+        &["reason"],
+    )
+    .unwrap()
+});
@@ -4,6 +4,7 @@
 use crate::remote_executor_service::ExecutorService;
 use aptos_logger::info;
 use aptos_push_metrics::MetricsPusher;
+use aptos_secure_net::grpc_network_service::TlsConfig;
 use aptos_types::block_executor::partitioner::ShardId;
 use aptos_vm::AptosVM;
 use std::net::SocketAddr;
@@ -20,11 +21,12 @@ impl ProcessExecutorService {
         num_threads: usize,
         coordinator_address: SocketAddr,
         remote_shard_addresses: Vec<SocketAddr>,
+        tls_config: Option<TlsConfig>,
     ) -> Self {
         let self_address = remote_shard_addresses[shard_id];
         info!(
-            "Starting process remote executor service on {}; coordinator address: {}, other shard addresses: {:?}; num threads: {}",
-            self_address, coordinator_address, remote_shard_addresses, num_threads
+            "Starting process remote executor service on {}; coordinator address: {}, other shard addresses: {:?}; num threads: {}; TLS: {}",
+            self_address, coordinator_address, remote_shard_addresses, num_threads, tls_config.is_some()
         );
         aptos_node_resource_metrics::register_node_metrics_collector();
         let _mp = MetricsPusher::start_for_local_run(
@@ -39,6 +41,7 @@ impl ProcessExecutorService {
             self_address,
             coordinator_address,
             remote_shard_addresses,
+            tls_config,
         );
         executor_service.start();
         Self { executor_service }
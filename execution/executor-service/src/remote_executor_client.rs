@@ -2,11 +2,15 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
+    error::Error, health_check::ShardHealthMonitor, metrics::REMOTE_EXECUTOR_LOCAL_FALLBACK_COUNT,
     remote_state_view_service::RemoteStateViewService, ExecuteBlockCommand, RemoteExecutionRequest,
     RemoteExecutionResult,
 };
-use aptos_logger::{info, trace};
-use aptos_secure_net::network_controller::{Message, NetworkController};
+use aptos_logger::{error, info, trace, warn};
+use aptos_secure_net::{
+    grpc_network_service::TlsConfig,
+    network_controller::{Message, NetworkController},
+};
 use aptos_storage_interface::cached_state_view::CachedStateView;
 use aptos_types::{
     block_executor::{
@@ -18,20 +22,26 @@ use aptos_types::{
 };
 use aptos_vm::sharded_block_executor::{
     executor_client::{ExecutorClient, ShardedExecutionOutput},
+    local_executor_shard::{LocalExecutorClient, LocalExecutorService},
     ShardedBlockExecutor,
 };
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use once_cell::sync::{Lazy, OnceCell};
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
     thread,
+    time::Duration,
 };
 
 pub static COORDINATOR_PORT: u16 = 52200;
 
 static REMOTE_ADDRESSES: OnceCell<Vec<SocketAddr>> = OnceCell::new();
 static COORDINATOR_ADDRESS: OnceCell<SocketAddr> = OnceCell::new();
+static REMOTE_EXECUTOR_CONFIG: OnceCell<RemoteExecutorConfig> = OnceCell::new();
 
 pub fn set_remote_addresses(addresses: Vec<SocketAddr>) {
     REMOTE_ADDRESSES.set(addresses).ok();
@@ -55,6 +65,44 @@ pub fn get_coordinator_address() -> SocketAddr {
     }
 }
 
+pub fn set_remote_executor_config(config: RemoteExecutorConfig) {
+    REMOTE_EXECUTOR_CONFIG.set(config).ok();
+}
+
+pub fn get_remote_executor_config() -> RemoteExecutorConfig {
+    REMOTE_EXECUTOR_CONFIG.get().cloned().unwrap_or_default()
+}
+
+/// How the coordinator talks to the remote shards, and what it does when they fail.
+#[derive(Clone, Debug)]
+pub struct RemoteExecutorConfig {
+    /// Mutual TLS of the channels with the shards, which must use certificates signed by the
+    /// same CA.
+    pub tls_config: Option<TlsConfig>,
+    /// Time to wait for every shard to return the result of a block.
+    pub result_timeout: Duration,
+    pub health_check_interval: Duration,
+    /// A shard which hasn't answered a health check for this long is unhealthy.
+    pub health_check_timeout: Duration,
+    /// Execute the blocks locally when a shard is unhealthy or fails, instead of panicking.
+    /// Once a shard failed during the execution of a block, the state of the other shards is
+    /// unknown, so all the following blocks are executed locally until the coordinator is
+    /// restarted.
+    pub fallback_to_local: bool,
+}
+
+impl Default for RemoteExecutorConfig {
+    fn default() -> Self {
+        Self {
+            tls_config: None,
+            result_timeout: Duration::from_secs(60),
+            health_check_interval: Duration::from_millis(500),
+            health_check_timeout: Duration::from_secs(5),
+            fallback_to_local: true,
+        }
+    }
+}
+
 pub static REMOTE_SHARDED_BLOCK_EXECUTOR: Lazy<
     Arc<
         aptos_infallible::Mutex<
@@ -68,6 +116,7 @@ pub static REMOTE_SHARDED_BLOCK_EXECUTOR: Lazy<
             get_coordinator_address(),
             get_remote_addresses(),
             None,
+            get_remote_executor_config(),
         ),
     ))
 });
@@ -85,6 +134,13 @@ pub struct RemoteExecutorClient<S: StateView + Sync + Send + 'static> {
     result_rxs: Vec<Receiver<Message>>,
     // Thread pool used to pre-fetch the state values for the block in parallel and create an in-memory state view.
     thread_pool: Arc<rayon::ThreadPool>,
+    config: RemoteExecutorConfig,
+    health_monitor: ShardHealthMonitor,
+    // Set once a shard failed during the execution of a block, after which all the blocks are
+    // executed by the local executor.
+    fallback_engaged: AtomicBool,
+    // Created on the first fallback to local execution.
+    local_executor_client: OnceCell<LocalExecutorClient<S>>,
 
     phantom: std::marker::PhantomData<S>,
     _join_handle: Option<thread::JoinHandle<()>>,
@@ -94,9 +150,27 @@ pub struct RemoteExecutorClient<S: StateView + Sync + Send + 'static> {
 impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
     pub fn new(
         remote_shard_addresses: Vec<SocketAddr>,
-        mut controller: NetworkController,
+        controller: NetworkController,
         num_threads: Option<usize>,
     ) -> Self {
+        Self::new_with_config(
+            remote_shard_addresses,
+            controller,
+            num_threads,
+            RemoteExecutorConfig::default(),
+        )
+    }
+
+    pub fn new_with_config(
+        remote_shard_addresses: Vec<SocketAddr>,
+        controller: NetworkController,
+        num_threads: Option<usize>,
+        config: RemoteExecutorConfig,
+    ) -> Self {
+        let mut controller = match &config.tls_config {
+            Some(tls_config) => controller.with_tls(tls_config.clone()),
+            None => controller,
+        };
         let num_threads = num_threads.unwrap_or_else(num_cpus::get);
         let thread_pool = Arc::new(
             rayon::ThreadPoolBuilder::new()
@@ -119,6 +193,12 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
             })
             .unzip();
 
+        let mut health_monitor = ShardHealthMonitor::new(
+            controller_mut_ref,
+            &remote_shard_addresses,
+            config.health_check_timeout,
+        );
+
         let state_view_service = Arc::new(RemoteStateViewService::new(
             controller_mut_ref,
             remote_shard_addresses,
@@ -133,6 +213,7 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
             .unwrap();
 
         controller.start();
+        health_monitor.start(config.health_check_interval);
 
         Self {
             network_controller: controller,
@@ -141,6 +222,10 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
             command_txs: Arc::new(command_txs),
             result_rxs,
             thread_pool,
+            config,
+            health_monitor,
+            fallback_engaged: AtomicBool::new(false),
+            local_executor_client: OnceCell::new(),
             phantom: std::marker::PhantomData,
         }
     }
@@ -149,8 +234,9 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
         coordinator_address: SocketAddr,
         remote_shard_addresses: Vec<SocketAddr>,
         num_threads: Option<usize>,
+        config: RemoteExecutorConfig,
     ) -> ShardedBlockExecutor<S, RemoteExecutorClient<S>> {
-        ShardedBlockExecutor::new(RemoteExecutorClient::new(
+        ShardedBlockExecutor::new(RemoteExecutorClient::new_with_config(
             remote_shard_addresses,
             NetworkController::new(
                 "remote-executor-coordinator".to_string(),
@@ -158,33 +244,58 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
                 5000,
             ),
             num_threads,
+            config,
         ))
     }
 
-    fn get_output_from_shards(&self) -> Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus> {
+    /// Waits until all the shards answer the health checks. Returns false if they don't
+    /// after `timeout`.
+    pub fn wait_until_healthy(&self, timeout: Duration) -> bool {
+        self.health_monitor.wait_until_healthy(timeout)
+    }
+
+    /// Whether the blocks are executed locally because a shard failed.
+    pub fn is_fallback_engaged(&self) -> bool {
+        self.fallback_engaged.load(Ordering::Relaxed)
+    }
+
+    fn get_output_from_shards(
+        &self,
+    ) -> Result<Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus>, Error> {
         trace!("RemoteExecutorClient Waiting for results");
         let mut results = vec![];
-        for rx in self.result_rxs.iter() {
-            let received_bytes = rx.recv().unwrap().to_bytes();
-            let result: RemoteExecutionResult = bcs::from_bytes(&received_bytes).unwrap();
-            results.push(result.inner?);
+        for (shard_id, rx) in self.result_rxs.iter().enumerate() {
+            let received_bytes = match rx.recv_timeout(self.config.result_timeout) {
+                Ok(message) => message.to_bytes(),
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(Error::InternalError(format!(
+                        "Shard {} did not return the result of the block within {:?}",
+                        shard_id, self.config.result_timeout
+                    )))
+                },
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(Error::InternalError(format!(
+                        "Result channel of shard {} is disconnected",
+                        shard_id
+                    )))
+                },
+            };
+            let result: RemoteExecutionResult = bcs::from_bytes(&received_bytes)?;
+            match result.inner {
+                Ok(result) => results.push(result),
+                Err(vm_status) => return Ok(Err(vm_status)),
+            }
         }
-        Ok(results)
+        Ok(Ok(results))
     }
-}
 
-impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorClient<S> {
-    fn num_shards(&self) -> usize {
-        self.command_txs.len()
-    }
-
-    fn execute_block(
+    fn execute_block_remotely(
         &self,
         state_view: Arc<S>,
         transactions: PartitionedTransactions,
         concurrency_level_per_shard: usize,
         onchain_config: BlockExecutorConfigFromOnchain,
-    ) -> Result<ShardedExecutionOutput, VMStatus> {
+    ) -> Result<Result<ShardedExecutionOutput, VMStatus>, Error> {
         trace!("RemoteExecutorClient Sending block to shards");
         self.state_view_service.set_state_view(state_view);
         let (sub_blocks, global_txns) = transactions.into();
@@ -202,17 +313,107 @@ impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorC
             senders[shard_id]
                 .lock()
                 .unwrap()
-                .send(Message::new(bcs::to_bytes(&execution_request).unwrap()))
-                .unwrap();
+                .send(Message::new(bcs::to_bytes(&execution_request)?))
+                .map_err(|err| Error::InternalError(err.to_string()))?;
         }
 
-        let execution_results = self.get_output_from_shards()?;
+        let execution_results = self.get_output_from_shards();
 
         self.state_view_service.drop_state_view();
-        Ok(ShardedExecutionOutput::new(execution_results, vec![]))
+        Ok(execution_results?
+            .map(|execution_results| ShardedExecutionOutput::new(execution_results, vec![])))
+    }
+
+    fn execute_block_locally(
+        &self,
+        state_view: Arc<S>,
+        transactions: PartitionedTransactions,
+        concurrency_level_per_shard: usize,
+        onchain_config: BlockExecutorConfigFromOnchain,
+    ) -> Result<ShardedExecutionOutput, VMStatus> {
+        self.local_executor_client
+            .get_or_init(|| {
+                info!("Creating local executor shards to fall back to");
+                LocalExecutorService::setup_local_executor_shards(self.num_shards(), None)
+            })
+            .execute_block(
+                state_view,
+                transactions,
+                concurrency_level_per_shard,
+                onchain_config,
+            )
+    }
+}
+
+impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorClient<S> {
+    fn num_shards(&self) -> usize {
+        self.command_txs.len()
+    }
+
+    fn execute_block(
+        &self,
+        state_view: Arc<S>,
+        transactions: PartitionedTransactions,
+        concurrency_level_per_shard: usize,
+        onchain_config: BlockExecutorConfigFromOnchain,
+    ) -> Result<ShardedExecutionOutput, VMStatus> {
+        if !self.config.fallback_to_local {
+            return self
+                .execute_block_remotely(
+                    state_view,
+                    transactions,
+                    concurrency_level_per_shard,
+                    onchain_config,
+                )
+                .unwrap_or_else(|err| panic!("Remote execution failed: {}", err));
+        }
+
+        if self.is_fallback_engaged() {
+            REMOTE_EXECUTOR_LOCAL_FALLBACK_COUNT
+                .with_label_values(&["fallback_engaged"])
+                .inc();
+        } else {
+            let unhealthy_shards = self.health_monitor.unhealthy_shards();
+            if unhealthy_shards.is_empty() {
+                match self.execute_block_remotely(
+                    state_view.clone(),
+                    transactions.clone(),
+                    concurrency_level_per_shard,
+                    onchain_config.clone(),
+                ) {
+                    Ok(output) => return output,
+                    Err(err) => {
+                        error!(
+                            "Remote execution failed, executing this and all the following blocks locally: {}",
+                            err
+                        );
+                        self.fallback_engaged.store(true, Ordering::Relaxed);
+                        REMOTE_EXECUTOR_LOCAL_FALLBACK_COUNT
+                            .with_label_values(&["shard_failure"])
+                            .inc();
+                    },
+                }
+            } else {
+                warn!(
+                    "Shards {:?} are unhealthy, executing the block locally",
+                    unhealthy_shards
+                );
+                REMOTE_EXECUTOR_LOCAL_FALLBACK_COUNT
+                    .with_label_values(&["unhealthy_shards"])
+                    .inc();
+            }
+        }
+
+        self.execute_block_locally(
+            state_view,
+            transactions,
+            concurrency_level_per_shard,
+            onchain_config,
+        )
     }
 
     fn shutdown(&mut self) {
+        self.health_monitor.shutdown();
         self.network_controller.shutdown();
     }
 }
@@ -2,10 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    remote_cordinator_client::RemoteCoordinatorClient,
+    health_check::HealthCheckResponder, remote_cordinator_client::RemoteCoordinatorClient,
     remote_cross_shard_client::RemoteCrossShardClient, remote_state_view::RemoteStateViewClient,
 };
-use aptos_secure_net::network_controller::NetworkController;
+use aptos_secure_net::{grpc_network_service::TlsConfig, network_controller::NetworkController};
 use aptos_types::block_executor::partitioner::ShardId;
 use aptos_vm::sharded_block_executor::sharded_executor_service::ShardedExecutorService;
 use std::{net::SocketAddr, sync::Arc, thread};
@@ -16,6 +16,7 @@ pub struct ExecutorService {
     shard_id: ShardId,
     controller: NetworkController,
     executor_service: Arc<ShardedExecutorService<RemoteStateViewClient>>,
+    health_check_responder: Arc<HealthCheckResponder>,
 }
 
 impl ExecutorService {
@@ -26,14 +27,23 @@ impl ExecutorService {
         self_address: SocketAddr,
         coordinator_address: SocketAddr,
         remote_shard_addresses: Vec<SocketAddr>,
+        tls_config: Option<TlsConfig>,
     ) -> Self {
         let service_name = format!("executor_service-{}", shard_id);
         let mut controller = NetworkController::new(service_name, self_address, 5000);
+        if let Some(tls_config) = tls_config {
+            controller = controller.with_tls(tls_config);
+        }
         let coordinator_client = Arc::new(RemoteCoordinatorClient::new(
             shard_id,
             &mut controller,
             coordinator_address,
         ));
+        let health_check_responder = Arc::new(HealthCheckResponder::new(
+            shard_id,
+            &mut controller,
+            coordinator_address,
+        ));
         let cross_shard_client = Arc::new(RemoteCrossShardClient::new(
             &mut controller,
             remote_shard_addresses,
@@ -51,6 +61,7 @@ impl ExecutorService {
             shard_id,
             controller,
             executor_service,
+            health_check_responder,
         }
     }
 
@@ -64,6 +75,12 @@ impl ExecutorService {
                 executor_service_clone.start();
             })
             .expect("Failed to spawn thread");
+
+        let health_check_responder = self.health_check_responder.clone();
+        thread::Builder::new()
+            .name(format!("HealthCheckResponder-{}", self.shard_id))
+            .spawn(move || health_check_responder.start())
+            .expect("Failed to spawn thread");
     }
 
     pub fn shutdown(&mut self) {
@@ -9,7 +9,10 @@ use aptos_config::utils;
 use aptos_language_e2e_tests::data_store::FakeDataStore;
 use aptos_secure_net::network_controller::NetworkController;
 use aptos_vm::sharded_block_executor::ShardedBlockExecutor;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
+};
 
 pub fn create_thread_remote_executor_shards(
     num_shards: usize,
@@ -55,16 +58,12 @@ pub fn create_thread_remote_executor_shards(
 
 #[test]
 fn test_sharded_block_executor_no_conflict() {
-    use std::thread;
-
     let num_shards = 8;
     let (executor_client, mut executor_services) =
         create_thread_remote_executor_shards(num_shards, Some(2));
-    let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
-
     // wait for the servers to be ready before sending messages
-    // TODO: We need to pass this test without this sleep
-    thread::sleep(std::time::Duration::from_millis(10));
+    assert!(executor_client.wait_until_healthy(Duration::from_secs(10)));
+    let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
 
     test_utils::test_sharded_block_executor_no_conflict(sharded_block_executor);
 
@@ -75,16 +74,12 @@ fn test_sharded_block_executor_no_conflict() {
 
 #[test]
 fn test_sharded_block_executor_with_conflict() {
-    use std::thread;
-
     let num_shards = 8;
     let (executor_client, mut executor_services) =
         create_thread_remote_executor_shards(num_shards, Some(2));
-    let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
-
     // wait for the servers to be ready before sending messages
-    // TODO: We need to pass this test without this sleep
-    thread::sleep(std::time::Duration::from_millis(10));
+    assert!(executor_client.wait_until_healthy(Duration::from_secs(10)));
+    let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
 
     test_utils::sharded_block_executor_with_conflict(sharded_block_executor, 2);
 
@@ -27,6 +27,7 @@ impl ThreadExecutorService {
             self_address,
             coordinator_address,
             remote_shard_addresses,
+            None,
         );
         executor_service.start();
         Self {
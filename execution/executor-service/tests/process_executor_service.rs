@@ -0,0 +1,146 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs sharded execution against shards running in separate processes, talking to the
+//! coordinator over TLS, and compares the output with the unsharded execution.
+
+use aptos_config::utils;
+use aptos_executor_service::{
+    remote_executor_client::{RemoteExecutorClient, RemoteExecutorConfig},
+    test_utils,
+};
+use aptos_language_e2e_tests::data_store::FakeDataStore;
+use aptos_secure_net::{grpc_network_service::TlsConfig, network_controller::NetworkController};
+use aptos_vm::sharded_block_executor::ShardedBlockExecutor;
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    process::{Child, Command},
+    thread,
+    time::Duration,
+};
+
+const TLS_DOMAIN_NAME: &str = "aptos-secure-net";
+
+fn test_certs_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../secure/net/test_certs")
+}
+
+fn tls_config() -> TlsConfig {
+    let dir = test_certs_dir();
+    TlsConfig::from_files(
+        &dir.join("ca.pem"),
+        &dir.join("node.pem"),
+        &dir.join("node.key"),
+        TLS_DOMAIN_NAME.to_string(),
+    )
+    .unwrap()
+}
+
+fn localhost_address() -> SocketAddr {
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port())
+}
+
+/// Shard processes, killed when dropped so that a failing test doesn't leak them.
+struct ShardProcesses(Vec<Child>);
+
+impl ShardProcesses {
+    fn spawn(coordinator_address: SocketAddr, remote_shard_addresses: &[SocketAddr]) -> Self {
+        let certs_dir = test_certs_dir();
+        let children = (0..remote_shard_addresses.len())
+            .map(|shard_id| {
+                Command::new(env!("CARGO_BIN_EXE_aptos-executor-service"))
+                    .arg("--num-executor-threads")
+                    .arg("2")
+                    .arg("--shard-id")
+                    .arg(shard_id.to_string())
+                    .arg("--num-shards")
+                    .arg(remote_shard_addresses.len().to_string())
+                    .arg("--coordinator-address")
+                    .arg(coordinator_address.to_string())
+                    .arg("--remote-executor-addresses")
+                    .args(remote_shard_addresses.iter().map(|a| a.to_string()))
+                    .arg("--tls-ca-cert")
+                    .arg(certs_dir.join("ca.pem"))
+                    .arg("--tls-cert")
+                    .arg(certs_dir.join("node.pem"))
+                    .arg("--tls-key")
+                    .arg(certs_dir.join("node.key"))
+                    .arg("--tls-domain-name")
+                    .arg(TLS_DOMAIN_NAME)
+                    .spawn()
+                    .expect("Failed to spawn the executor service")
+            })
+            .collect();
+        Self(children)
+    }
+
+    fn kill(&mut self, shard_id: usize) {
+        let child = &mut self.0[shard_id];
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
+
+impl Drop for ShardProcesses {
+    fn drop(&mut self) {
+        for child in &mut self.0 {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+fn create_process_remote_executor_shards(
+    num_shards: usize,
+) -> (RemoteExecutorClient<FakeDataStore>, ShardProcesses) {
+    let coordinator_address = localhost_address();
+    let remote_shard_addresses = (0..num_shards)
+        .map(|_| localhost_address())
+        .collect::<Vec<_>>();
+    let shards = ShardProcesses::spawn(coordinator_address, &remote_shard_addresses);
+
+    let controller = NetworkController::new(
+        "remote-executor-coordinator".to_string(),
+        coordinator_address,
+        5000,
+    );
+    let config = RemoteExecutorConfig {
+        tls_config: Some(tls_config()),
+        health_check_timeout: Duration::from_secs(1),
+        ..RemoteExecutorConfig::default()
+    };
+    let executor_client =
+        RemoteExecutorClient::new_with_config(remote_shard_addresses, controller, None, config);
+    (executor_client, shards)
+}
+
+#[test]
+fn test_process_sharded_block_executor_no_conflict() {
+    let (executor_client, _shards) = create_process_remote_executor_shards(4);
+    assert!(executor_client.wait_until_healthy(Duration::from_secs(60)));
+
+    test_utils::test_sharded_block_executor_no_conflict(ShardedBlockExecutor::new(executor_client));
+}
+
+#[test]
+fn test_process_sharded_block_executor_with_conflict() {
+    let (executor_client, _shards) = create_process_remote_executor_shards(4);
+    assert!(executor_client.wait_until_healthy(Duration::from_secs(60)));
+
+    test_utils::sharded_block_executor_with_conflict(ShardedBlockExecutor::new(executor_client), 2);
+}
+
+#[test]
+fn test_process_sharded_block_executor_shard_failure() {
+    let (executor_client, mut shards) = create_process_remote_executor_shards(4);
+    assert!(executor_client.wait_until_healthy(Duration::from_secs(60)));
+
+    // The block is executed locally once the killed shard stops answering the health checks,
+    // and must still match the unsharded execution.
+    shards.kill(1);
+    thread::sleep(Duration::from_secs(2));
+    assert!(!executor_client.wait_until_healthy(Duration::ZERO));
+
+    test_utils::sharded_block_executor_with_conflict(ShardedBlockExecutor::new(executor_client), 2);
+}
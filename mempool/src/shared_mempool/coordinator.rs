@@ -25,6 +25,7 @@ use aptos_mempool_notifications::{MempoolCommitNotification, MempoolNotification
 use aptos_network::{
     application::{
         interface::{NetworkClientInterface, NetworkServiceEvents},
+        reputation::MisbehaviorSeverity,
         storage::PeersAndMetadata,
     },
     protocols::network::Event,
@@ -110,7 +111,7 @@ pub(crate) async fn coordinator<NetworkClient, TransactionValidator, ConfigProvi
                 tasks::execute_broadcast(peer, backoff, &mut smp, &mut scheduled_broadcasts, executor.clone()).await;
             },
             (network_id, event) = events.select_next_some() => {
-                handle_network_event(&bounded_executor, &mut smp, network_id, event).await;
+                handle_network_event(&bounded_executor, &mut smp, &peers_and_metadata, network_id, event).await;
             },
             _ = update_peers_interval.tick().fuse() => {
                 handle_update_peers(peers_and_metadata.clone(), &mut smp, &mut scheduled_broadcasts, executor.clone()).await;
@@ -260,6 +261,7 @@ async fn handle_mempool_reconfig_event<NetworkClient, TransactionValidator, Conf
 async fn handle_network_event<NetworkClient, TransactionValidator>(
     bounded_executor: &BoundedExecutor,
     smp: &mut SharedMempool<NetworkClient, TransactionValidator>,
+    peers_and_metadata: &PeersAndMetadata,
     network_id: NetworkId,
     event: Event<MempoolSyncMsg>,
 ) where
@@ -325,10 +327,17 @@ async fn handle_network_event<NetworkClient, TransactionValidator>(
         },
         Event::RpcRequest(peer_id, _msg, _, _res_tx) => {
             counters::unexpected_msg_count_inc(&network_id);
+            let peer_network_id = PeerNetworkId::new(network_id, peer_id);
             sample!(
                 SampleRate::Duration(Duration::from_secs(60)),
-                warn!(LogSchema::new(LogEntry::UnexpectedNetworkMsg)
-                    .peer(&PeerNetworkId::new(network_id, peer_id)))
+                warn!(LogSchema::new(LogEntry::UnexpectedNetworkMsg).peer(&peer_network_id))
+            );
+
+            // Mempool doesn't serve rpc requests, so report the peer
+            peers_and_metadata.get_peer_reputation().report_misbehavior(
+                peer_network_id,
+                MisbehaviorSeverity::Low,
+                "Unexpected mempool rpc request",
             );
         },
     }
@@ -12,10 +12,10 @@
 //! long as the latter is in its trusted peers set.
 use aptos_config::{
     config::{
-        DiscoveryMethod, NetworkConfig, Peer, PeerRole, PeerSet, RoleType, CONNECTION_BACKOFF_BASE,
-        CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONCURRENT_NETWORK_REQS, MAX_CONNECTION_DELAY_MS,
-        MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS,
-        NETWORK_CHANNEL_SIZE,
+        DiscoveryMethod, MessageCaptureConfig, NetworkConfig, OutboundMessageWeights, Peer,
+        PeerRole, PeerSet, RoleType, CONNECTION_BACKOFF_BASE, CONNECTIVITY_CHECK_INTERVAL_MS,
+        MAX_CONCURRENT_NETWORK_REQS, MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE,
+        MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS, NETWORK_CHANNEL_SIZE,
     },
     network_id::NetworkContext,
 };
@@ -24,6 +24,7 @@ use aptos_logger::prelude::*;
 use aptos_netcore::transport::tcp::TCPBufferCfg;
 use aptos_network::{
     application::storage::PeersAndMetadata,
+    capture::MessageCapture,
     connectivity_manager::{builder::ConnectivityManagerBuilder, ConnectivityRequest},
     constants::MAX_MESSAGE_SIZE,
     logging::NetworkSchema,
@@ -87,6 +88,7 @@ impl NetworkBuilder {
         max_concurrent_network_reqs: usize,
         inbound_connection_limit: usize,
         tcp_buffer_cfg: TCPBufferCfg,
+        outbound_message_weights: OutboundMessageWeights,
     ) -> Self {
         // A network cannot exist without a PeerManager
         // TODO:  construct this in create and pass it to new() as a parameter. The complication is manual construction of NetworkBuilder in various tests.
@@ -104,6 +106,7 @@ impl NetworkBuilder {
             enable_proxy_protocol,
             inbound_connection_limit,
             tcp_buffer_cfg,
+            outbound_message_weights,
         );
 
         NetworkBuilder {
@@ -144,6 +147,7 @@ impl NetworkBuilder {
             MAX_CONCURRENT_NETWORK_REQS,
             MAX_INBOUND_CONNECTIONS,
             TCPBufferCfg::default(),
+            OutboundMessageWeights::default(),
         );
 
         builder.add_connectivity_manager(
@@ -200,6 +204,7 @@ impl NetworkBuilder {
                 config.outbound_rx_buffer_size_bytes,
                 config.outbound_tx_buffer_size_bytes,
             ),
+            config.outbound_message_weights,
         );
 
         network_builder.add_connection_monitoring(
@@ -209,6 +214,10 @@ impl NetworkBuilder {
             config.max_parallel_deserialization_tasks,
         );
 
+        if let Some(message_capture_config) = &config.message_capture_config {
+            network_builder.add_message_capture(message_capture_config);
+        }
+
         // Always add a connectivity manager to keep track of known peers
         let seeds = merge_seeds(config);
 
@@ -225,7 +234,7 @@ impl NetworkBuilder {
         );
 
         network_builder.discovery_listeners = Some(Vec::new());
-        network_builder.setup_discovery(config, reconfig_subscription_service);
+        network_builder.setup_discovery(chain_id, config, reconfig_subscription_service);
 
         // Ensure there are no duplicate source types
         let set: HashSet<_> = network_builder
@@ -352,6 +361,7 @@ impl NetworkBuilder {
 
     fn setup_discovery(
         &mut self,
+        chain_id: ChainId,
         config: &NetworkConfig,
         mut reconfig_subscription_service: Option<&mut EventSubscriptionService>,
     ) {
@@ -389,6 +399,13 @@ impl NetworkBuilder {
                     Duration::from_secs(rest_discovery.interval_secs),
                     self.time_service.clone(),
                 ),
+                DiscoveryMethod::Seeds(seed_discovery) => DiscoveryChangeListener::seeds(
+                    self.network_context,
+                    conn_mgr_reqs_tx.clone(),
+                    chain_id,
+                    seed_discovery,
+                    self.time_service.clone(),
+                ),
                 DiscoveryMethod::None => {
                     continue;
                 },
@@ -400,6 +417,28 @@ impl NetworkBuilder {
         }
     }
 
+    /// Add a message capture to the network, recording all messages exchanged with peers.
+    fn add_message_capture(&mut self, message_capture_config: &MessageCaptureConfig) -> &mut Self {
+        let message_capture = MessageCapture::new(
+            self.network_context,
+            self.time_service.clone(),
+            message_capture_config,
+        )
+        .unwrap_or_else(|error| {
+            panic!(
+                "{} Failed to create the network message capture! Error: {:?}",
+                self.network_context, error
+            )
+        });
+        self.peer_manager_builder
+            .add_message_capture(Arc::new(message_capture));
+        debug!(
+            NetworkSchema::new(&self.network_context),
+            "{} Created message capture", self.network_context
+        );
+        self
+    }
+
     /// Add a HealthChecker to the network.
     fn add_connection_monitoring(
         &mut self,
@@ -36,3 +36,12 @@ pub static NETWORK_KEY_MISMATCH: Lazy<IntGaugeVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static SEED_DISCOVERY_RESULTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_seed_discovery_results",
+        "Counters for the results of seed discovery (e.g., healthy, unhealthy or identity mismatches)",
+        &["network_id", "result"]
+    )
+    .unwrap()
+});
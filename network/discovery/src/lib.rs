@@ -3,10 +3,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    counters::DISCOVERY_COUNTS, file::FileStream, rest::RestStream,
+    counters::DISCOVERY_COUNTS,
+    file::FileStream,
+    rest::RestStream,
+    seeds::{SeedResolver, SeedStream},
     validator_set::ValidatorSetStream,
 };
-use aptos_config::{config::PeerSet, network_id::NetworkContext};
+use aptos_config::{
+    config::{PeerSet, SeedDiscovery},
+    network_id::NetworkContext,
+};
 use aptos_crypto::x25519;
 use aptos_event_notifications::ReconfigNotificationListener;
 use aptos_logger::prelude::*;
@@ -16,11 +22,12 @@ use aptos_network::{
     logging::NetworkSchema,
 };
 use aptos_time_service::TimeService;
-use aptos_types::on_chain_config::OnChainConfigProvider;
+use aptos_types::{chain_id::ChainId, on_chain_config::OnChainConfigProvider};
 use futures::{Stream, StreamExt};
 use std::{
     path::Path,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
@@ -29,13 +36,20 @@ use tokio::runtime::Handle;
 mod counters;
 mod file;
 mod rest;
+mod seeds;
 mod validator_set;
 
+pub use seeds::{
+    DnsResolver, NoiseSeedHealthChecker, NoopSeedHealthChecker, SeedHealthChecker, SrvRecord,
+    StaticDnsResolver, SystemDnsResolver,
+};
+
 #[derive(Debug)]
 pub enum DiscoveryError {
     IO(std::io::Error),
     Parsing(String),
     Rest(aptos_rest_client::error::RestError),
+    Dns(String),
 }
 
 /// A union type for all implementations of `DiscoveryChangeListenerTrait`
@@ -50,6 +64,7 @@ enum DiscoveryChangeStream<P: OnChainConfigProvider> {
     ValidatorSet(ValidatorSetStream<P>),
     File(FileStream),
     Rest(RestStream),
+    Seeds(SeedStream),
 }
 
 impl<P: OnChainConfigProvider> Stream for DiscoveryChangeStream<P> {
@@ -60,6 +75,7 @@ impl<P: OnChainConfigProvider> Stream for DiscoveryChangeStream<P> {
             Self::ValidatorSet(stream) => Pin::new(stream).poll_next(cx),
             Self::File(stream) => Pin::new(stream).poll_next(cx),
             Self::Rest(stream) => Pin::new(stream).poll_next(cx),
+            Self::Seeds(stream) => Pin::new(stream).poll_next(cx),
         }
     }
 }
@@ -125,6 +141,63 @@ impl<P: OnChainConfigProvider> DiscoveryChangeListener<P> {
         }
     }
 
+    pub fn seeds(
+        network_context: NetworkContext,
+        update_channel: aptos_channels::Sender<ConnectivityRequest>,
+        chain_id: ChainId,
+        seed_discovery: &SeedDiscovery,
+        time_service: TimeService,
+    ) -> Self {
+        let health_checker: Arc<dyn SeedHealthChecker> = if seed_discovery.enable_health_checks {
+            Arc::new(NoiseSeedHealthChecker::new(
+                network_context,
+                chain_id,
+                Duration::from_millis(seed_discovery.health_check_timeout_ms),
+            ))
+        } else {
+            Arc::new(NoopSeedHealthChecker)
+        };
+        Self::seeds_with_resolvers(
+            network_context,
+            update_channel,
+            seed_discovery,
+            Arc::new(SystemDnsResolver::new()),
+            health_checker,
+            Duration::from_secs(seed_discovery.interval_secs),
+            time_service,
+        )
+    }
+
+    /// Creates a seed discovery listener that uses the given DNS resolver
+    /// and health checker (e.g., a local DNS stub for testing).
+    pub fn seeds_with_resolvers(
+        network_context: NetworkContext,
+        update_channel: aptos_channels::Sender<ConnectivityRequest>,
+        seed_discovery: &SeedDiscovery,
+        dns_resolver: Arc<dyn DnsResolver>,
+        health_checker: Arc<dyn SeedHealthChecker>,
+        interval_duration: Duration,
+        time_service: TimeService,
+    ) -> Self {
+        let seed_resolver = SeedResolver::new(
+            network_context,
+            seed_discovery,
+            dns_resolver,
+            health_checker,
+        );
+        let source_stream = DiscoveryChangeStream::Seeds(SeedStream::new(
+            seed_resolver,
+            interval_duration,
+            time_service,
+        ));
+        DiscoveryChangeListener {
+            discovery_source: DiscoverySource::Seeds,
+            network_context,
+            update_channel,
+            source_stream,
+        }
+    }
+
     pub fn start(self, executor: &Handle) {
         spawn_named!("DiscoveryChangeListener", executor, Box::pin(self).run());
     }
@@ -0,0 +1,944 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{counters::SEED_DISCOVERY_RESULTS, DiscoveryError};
+use aptos_config::{
+    config::{Peer, PeerRole, PeerSet, SeedDiscovery, HANDSHAKE_VERSION},
+    network_id::NetworkContext,
+};
+use aptos_crypto::{x25519, ValidCryptoMaterialStringExt};
+use aptos_infallible::RwLock;
+use aptos_logger::{info, warn};
+use aptos_netcore::transport::{quic::QuicTransport, tcp::TcpTransport};
+use aptos_network::{
+    logging::NetworkSchema,
+    noise::HandshakeAuthMode,
+    protocols::wire::handshake::v1::ProtocolIdSet,
+    transport::{AptosNetTransport, APTOS_QUIC_TRANSPORT, APTOS_TCP_TRANSPORT},
+};
+use aptos_time_service::{Interval, TimeService, TimeServiceTrait};
+use aptos_types::{
+    account_address::from_identity_public_key,
+    chain_id::ChainId,
+    network_address::{parse_dns_quic, parse_ip_quic, NetworkAddress},
+    PeerId,
+};
+use async_trait::async_trait;
+use futures::{future::BoxFuture, FutureExt, Stream, TryFutureExt};
+use hickory_resolver::{
+    config::{ResolverConfig, ResolverOpts},
+    TokioAsyncResolver,
+};
+use rand::{rngs::OsRng, Rng};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// The TXT record prefix specifying the x25519 public key of a seed
+const TXT_PUBLIC_KEY_PREFIX: &str = "aptos-pubkey=";
+/// The TXT record prefix specifying the (optional) peer id of a seed
+const TXT_PEER_ID_PREFIX: &str = "aptos-peer-id=";
+
+/// A single DNS SRV record
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SrvRecord {
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    pub target: String,
+}
+
+/// A simple interface for resolving the DNS records used by seed discovery
+#[async_trait]
+pub trait DnsResolver: Send + Sync {
+    /// Returns the SRV records for the given name
+    async fn lookup_srv(&self, name: &str) -> Result<Vec<SrvRecord>, DiscoveryError>;
+
+    /// Returns the TXT records for the given name (each as a single string)
+    async fn lookup_txt(&self, name: &str) -> Result<Vec<String>, DiscoveryError>;
+}
+
+/// A DNS resolver that queries the system's DNS servers
+pub struct SystemDnsResolver {
+    resolver: TokioAsyncResolver,
+}
+
+impl SystemDnsResolver {
+    pub fn new() -> Self {
+        // Use the system configuration if possible, otherwise fall back to the defaults
+        let resolver = TokioAsyncResolver::tokio_from_system_conf().unwrap_or_else(|error| {
+            warn!(
+                "Failed to load the system DNS configuration! Using the defaults. Error: {:?}",
+                error
+            );
+            TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default())
+        });
+        Self { resolver }
+    }
+}
+
+impl Default for SystemDnsResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl DnsResolver for SystemDnsResolver {
+    async fn lookup_srv(&self, name: &str) -> Result<Vec<SrvRecord>, DiscoveryError> {
+        let srv_lookup = self
+            .resolver
+            .srv_lookup(name)
+            .await
+            .map_err(|error| DiscoveryError::Dns(error.to_string()))?;
+        Ok(srv_lookup
+            .iter()
+            .map(|srv| SrvRecord {
+                priority: srv.priority(),
+                weight: srv.weight(),
+                port: srv.port(),
+                target: srv.target().to_utf8().trim_end_matches('.').to_string(),
+            })
+            .collect())
+    }
+
+    async fn lookup_txt(&self, name: &str) -> Result<Vec<String>, DiscoveryError> {
+        let txt_lookup = self
+            .resolver
+            .txt_lookup(name)
+            .await
+            .map_err(|error| DiscoveryError::Dns(error.to_string()))?;
+        Ok(txt_lookup
+            .iter()
+            .map(|txt| {
+                txt.txt_data()
+                    .iter()
+                    .map(|data| String::from_utf8_lossy(data))
+                    .collect::<String>()
+            })
+            .collect())
+    }
+}
+
+/// A local DNS stub that serves records from memory. This is useful for
+/// testing seed discovery without a real DNS server.
+#[derive(Default)]
+pub struct StaticDnsResolver {
+    srv_records: RwLock<HashMap<String, Vec<SrvRecord>>>,
+    txt_records: RwLock<HashMap<String, Vec<String>>>,
+}
+
+impl StaticDnsResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an SRV record for the given name
+    pub fn add_srv_record(&self, name: &str, srv_record: SrvRecord) {
+        self.srv_records
+            .write()
+            .entry(name.to_string())
+            .or_default()
+            .push(srv_record);
+    }
+
+    /// Adds a TXT record for the given name
+    pub fn add_txt_record(&self, name: &str, txt_record: &str) {
+        self.txt_records
+            .write()
+            .entry(name.to_string())
+            .or_default()
+            .push(txt_record.to_string());
+    }
+
+    /// Removes all records for the given name
+    pub fn remove_records(&self, name: &str) {
+        self.srv_records.write().remove(name);
+        self.txt_records.write().remove(name);
+    }
+}
+
+#[async_trait]
+impl DnsResolver for StaticDnsResolver {
+    async fn lookup_srv(&self, name: &str) -> Result<Vec<SrvRecord>, DiscoveryError> {
+        self.srv_records
+            .read()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| DiscoveryError::Dns(format!("No SRV records found for {}", name)))
+    }
+
+    async fn lookup_txt(&self, name: &str) -> Result<Vec<String>, DiscoveryError> {
+        self.txt_records
+            .read()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| DiscoveryError::Dns(format!("No TXT records found for {}", name)))
+    }
+}
+
+/// A simple interface for checking the health of seeds before they are dialed
+#[async_trait]
+pub trait SeedHealthChecker: Send + Sync {
+    /// Returns an error iff the seed at the given address is unhealthy
+    async fn check_health(&self, peer_id: PeerId, address: &NetworkAddress) -> Result<(), String>;
+}
+
+/// A health checker that dials seeds over AptosNet, i.e., performs a noise IK
+/// handshake against the public key in the seed address, followed by the
+/// AptosNet handshake (which also verifies the chain and network ids). The
+/// connection is closed as soon as the handshakes complete.
+///
+/// The health checks use a fresh (ephemeral) identity, so that they don't
+/// interfere with the connections of this node to the seeds.
+pub struct NoiseSeedHealthChecker {
+    tcp_transport: AptosNetTransport<TcpTransport>,
+    quic_transport: AptosNetTransport<QuicTransport>,
+    timeout: Duration,
+}
+
+impl NoiseSeedHealthChecker {
+    pub fn new(network_context: NetworkContext, chain_id: ChainId, timeout: Duration) -> Self {
+        // Create the ephemeral identity
+        let identity_key_bytes: [u8; x25519::PRIVATE_KEY_SIZE] = OsRng.gen();
+        let peer_id =
+            from_identity_public_key(x25519::PrivateKey::from(identity_key_bytes).public_key());
+        let network_context = NetworkContext::new(
+            network_context.role(),
+            network_context.network_id(),
+            peer_id,
+        );
+
+        // Create the transports (with all known protocols, so that the
+        // protocol negotiation doesn't fail the handshake).
+        let tcp_transport = AptosNetTransport::new(
+            APTOS_TCP_TRANSPORT,
+            network_context,
+            TimeService::real(),
+            x25519::PrivateKey::from(identity_key_bytes),
+            HandshakeAuthMode::server_only(&[network_context.network_id()]),
+            HANDSHAKE_VERSION,
+            chain_id,
+            ProtocolIdSet::all_known(),
+            false, /* Disable proxy protocol */
+        );
+        let quic_transport = AptosNetTransport::new(
+            APTOS_QUIC_TRANSPORT,
+            network_context,
+            TimeService::real(),
+            x25519::PrivateKey::from(identity_key_bytes),
+            HandshakeAuthMode::server_only(&[network_context.network_id()]),
+            HANDSHAKE_VERSION,
+            chain_id,
+            ProtocolIdSet::all_known(),
+            false, /* Disable proxy protocol */
+        );
+
+        Self {
+            tcp_transport,
+            quic_transport,
+            timeout,
+        }
+    }
+}
+
+#[async_trait]
+impl SeedHealthChecker for NoiseSeedHealthChecker {
+    async fn check_health(&self, peer_id: PeerId, address: &NetworkAddress) -> Result<(), String> {
+        // Dial the seed using the transport for the address
+        let protocols = address.as_slice();
+        let dial_result =
+            if parse_ip_quic(protocols).is_some() || parse_dns_quic(protocols).is_some() {
+                self.quic_transport
+                    .dial(peer_id, address.clone())
+                    .map(|dial| dial.map_ok(drop).boxed())
+            } else {
+                self.tcp_transport
+                    .dial(peer_id, address.clone())
+                    .map(|dial| dial.map_ok(drop).boxed())
+            };
+        let health_check = dial_result.map_err(|error| error.to_string())?;
+
+        // Wait for the handshakes to complete (the connection is dropped immediately)
+        tokio::time::timeout(self.timeout, health_check)
+            .await
+            .map_err(|_| format!("The health check timed out after {:?}", self.timeout))?
+            .map_err(|error| error.to_string())
+    }
+}
+
+/// A health checker that treats all seeds as healthy (i.e., health checks are disabled)
+pub struct NoopSeedHealthChecker;
+
+#[async_trait]
+impl SeedHealthChecker for NoopSeedHealthChecker {
+    async fn check_health(
+        &self,
+        _peer_id: PeerId,
+        _address: &NetworkAddress,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// A candidate seed (before identity and health filtering)
+#[derive(Clone, Debug)]
+struct SeedCandidate {
+    peer_id: Option<PeerId>, // The advertised peer id (if any)
+    address: NetworkAddress,
+}
+
+/// Resolves the seeds from DNS and the static seed addresses, and filters
+/// out the seeds that present mismatched identities or fail health checks.
+pub struct SeedResolver {
+    network_context: NetworkContext,
+    dns_names: Vec<String>,
+    static_addrs: Vec<NetworkAddress>,
+    dns_resolver: Arc<dyn DnsResolver>,
+    health_checker: Arc<dyn SeedHealthChecker>,
+}
+
+impl SeedResolver {
+    pub fn new(
+        network_context: NetworkContext,
+        seed_discovery: &SeedDiscovery,
+        dns_resolver: Arc<dyn DnsResolver>,
+        health_checker: Arc<dyn SeedHealthChecker>,
+    ) -> Self {
+        Self {
+            network_context,
+            dns_names: seed_discovery.dns_names.clone(),
+            static_addrs: seed_discovery.static_addrs.clone(),
+            dns_resolver,
+            health_checker,
+        }
+    }
+
+    /// Resolves all seeds and returns the healthy seeds with valid identities.
+    /// An error is only returned if every DNS name fails to resolve and there
+    /// are no static seeds (to avoid clearing the seeds on transient failures).
+    pub async fn resolve_seeds(&self) -> Result<PeerSet, DiscoveryError> {
+        // Gather the candidate seeds
+        let mut seed_candidates: Vec<_> = self
+            .static_addrs
+            .iter()
+            .map(|address| SeedCandidate {
+                peer_id: None,
+                address: address.clone(),
+            })
+            .collect();
+        let mut last_dns_error = None;
+        let mut num_resolved_names = 0;
+        for dns_name in &self.dns_names {
+            match self.resolve_dns_name(dns_name).await {
+                Ok(dns_seed_candidates) => {
+                    num_resolved_names += 1;
+                    seed_candidates.extend(dns_seed_candidates);
+                },
+                Err(error) => {
+                    warn!(
+                        NetworkSchema::new(&self.network_context),
+                        "{} Failed to resolve the seed DNS name {}! Error: {:?}",
+                        self.network_context,
+                        dns_name,
+                        error
+                    );
+                    last_dns_error = Some(error);
+                },
+            }
+        }
+        if let Some(error) = last_dns_error {
+            if num_resolved_names == 0 && self.static_addrs.is_empty() {
+                return Err(error);
+            }
+        }
+
+        // Verify the identities of the candidates
+        let verified_seeds: Vec<_> = seed_candidates
+            .into_iter()
+            .filter_map(
+                |seed_candidate| match verify_seed_identity(&seed_candidate) {
+                    Ok(verified_seed) => Some(verified_seed),
+                    Err(error) => {
+                        self.update_seed_result_metric("identity_mismatch");
+                        warn!(
+                            NetworkSchema::new(&self.network_context),
+                            "{} Ignoring seed {} with a mismatched identity: {}",
+                            self.network_context,
+                            seed_candidate.address,
+                            error
+                        );
+                        None
+                    },
+                },
+            )
+            .collect();
+
+        // Check the health of the seeds (concurrently)
+        let health_checks = verified_seeds
+            .iter()
+            .map(|(peer_id, address, _)| self.health_checker.check_health(*peer_id, address));
+        let health_check_results = futures::future::join_all(health_checks).await;
+
+        // Build the peer set from the healthy seeds
+        let mut peer_set = PeerSet::new();
+        for ((peer_id, address, public_key), health_check_result) in
+            verified_seeds.into_iter().zip(health_check_results)
+        {
+            if let Err(error) = health_check_result {
+                self.update_seed_result_metric("unhealthy");
+                info!(
+                    NetworkSchema::new(&self.network_context),
+                    "{} Ignoring unhealthy seed {} at {}: {}",
+                    self.network_context,
+                    peer_id,
+                    address,
+                    error
+                );
+                continue;
+            }
+
+            self.update_seed_result_metric("healthy");
+            match peer_set.entry(peer_id) {
+                Entry::Occupied(mut entry) => {
+                    let peer = entry.get_mut();
+                    if !peer.addresses.contains(&address) {
+                        peer.addresses.push(address);
+                    }
+                },
+                Entry::Vacant(entry) => {
+                    entry.insert(Peer::new(
+                        vec![address],
+                        HashSet::from([public_key]),
+                        PeerRole::Upstream,
+                    ));
+                },
+            }
+        }
+
+        Ok(peer_set)
+    }
+
+    /// Resolves the SRV records (and the corresponding TXT identity records)
+    /// for the given DNS name.
+    async fn resolve_dns_name(&self, dns_name: &str) -> Result<Vec<SeedCandidate>, DiscoveryError> {
+        let mut seed_candidates = vec![];
+        for srv_record in self.dns_resolver.lookup_srv(dns_name).await? {
+            // Fetch the identity of the target
+            let txt_records = match self.dns_resolver.lookup_txt(&srv_record.target).await {
+                Ok(txt_records) => txt_records,
+                Err(error) => {
+                    self.update_seed_result_metric("invalid_record");
+                    warn!(
+                        NetworkSchema::new(&self.network_context),
+                        "{} Failed to resolve the identity of seed {}! Error: {:?}",
+                        self.network_context,
+                        srv_record.target,
+                        error
+                    );
+                    continue;
+                },
+            };
+
+            // Create the seed candidate
+            match create_seed_candidate(&srv_record, &txt_records) {
+                Ok(seed_candidate) => seed_candidates.push(seed_candidate),
+                Err(error) => {
+                    self.update_seed_result_metric("invalid_record");
+                    warn!(
+                        NetworkSchema::new(&self.network_context),
+                        "{} Ignoring invalid seed records for {}: {}",
+                        self.network_context,
+                        srv_record.target,
+                        error
+                    );
+                },
+            }
+        }
+        Ok(seed_candidates)
+    }
+
+    /// Updates the seed discovery result metric
+    fn update_seed_result_metric(&self, result: &str) {
+        SEED_DISCOVERY_RESULTS
+            .with_label_values(&[self.network_context.network_id().as_str(), result])
+            .inc();
+    }
+}
+
+/// Creates a seed candidate from the given SRV record and the TXT records of the target
+fn create_seed_candidate(
+    srv_record: &SrvRecord,
+    txt_records: &[String],
+) -> Result<SeedCandidate, String> {
+    // Parse the public key and (optional) peer id
+    let mut public_keys = HashSet::new();
+    let mut peer_ids = HashSet::new();
+    for txt_record in txt_records {
+        let txt_record = txt_record.trim();
+        if let Some(public_key) = txt_record.strip_prefix(TXT_PUBLIC_KEY_PREFIX) {
+            let public_key = x25519::PublicKey::from_encoded_string(public_key)
+                .map_err(|error| format!("Invalid public key: {}", error))?;
+            public_keys.insert(public_key);
+        } else if let Some(peer_id) = txt_record.strip_prefix(TXT_PEER_ID_PREFIX) {
+            let peer_id =
+                PeerId::from_str(peer_id).map_err(|error| format!("Invalid peer id: {}", error))?;
+            peer_ids.insert(peer_id);
+        }
+    }
+
+    // Verify there is exactly one public key and at most one peer id
+    if public_keys.len() != 1 {
+        return Err(format!(
+            "Expected exactly one public key, but found {}!",
+            public_keys.len()
+        ));
+    }
+    if peer_ids.len() > 1 {
+        return Err(format!(
+            "Expected at most one peer id, but found {}!",
+            peer_ids.len()
+        ));
+    }
+    let public_key = public_keys.into_iter().next().unwrap();
+    let peer_id = peer_ids.into_iter().next();
+
+    // Create the network address
+    let address = NetworkAddress::from_str(&format!(
+        "/dns/{}/tcp/{}",
+        srv_record.target, srv_record.port
+    ))
+    .map_err(|error| format!("Invalid seed address: {}", error))?
+    .append_prod_protos(public_key, HANDSHAKE_VERSION);
+
+    Ok(SeedCandidate { peer_id, address })
+}
+
+/// Verifies the identity of the seed candidate, i.e., that the address contains
+/// a public key, and that the peer id (if advertised) is derived from the key.
+/// Returns the peer id, address and public key of the seed.
+fn verify_seed_identity(
+    seed_candidate: &SeedCandidate,
+) -> Result<(PeerId, NetworkAddress, x25519::PublicKey), String> {
+    let public_key = seed_candidate
+        .address
+        .find_noise_proto()
+        .ok_or_else(|| "The seed address is missing a noise-ik public key!".to_string())?;
+    let derived_peer_id = from_identity_public_key(public_key);
+    if let Some(peer_id) = seed_candidate.peer_id {
+        if peer_id != derived_peer_id {
+            return Err(format!(
+                "The advertised peer id ({}) does not match the public key (expected peer id: {})!",
+                peer_id, derived_peer_id
+            ));
+        }
+    }
+
+    Ok((derived_peer_id, seed_candidate.address.clone(), public_key))
+}
+
+/// A discovery stream that periodically resolves the seeds
+pub struct SeedStream {
+    seed_resolver: Arc<SeedResolver>,
+    interval: Pin<Box<Interval>>,
+    pending_resolution: Option<BoxFuture<'static, Result<PeerSet, DiscoveryError>>>,
+}
+
+impl SeedStream {
+    pub(crate) fn new(
+        seed_resolver: SeedResolver,
+        interval_duration: Duration,
+        time_service: TimeService,
+    ) -> Self {
+        SeedStream {
+            seed_resolver: Arc::new(seed_resolver),
+            interval: Box::pin(time_service.interval(interval_duration)),
+            pending_resolution: None,
+        }
+    }
+}
+
+impl Stream for SeedStream {
+    type Item = Result<PeerSet, DiscoveryError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Wait for delay, and start resolving the seeds
+        if self.pending_resolution.is_none() {
+            futures::ready!(self.interval.as_mut().poll_next(cx));
+            let seed_resolver = self.seed_resolver.clone();
+            self.pending_resolution =
+                Some(async move { seed_resolver.resolve_seeds().await }.boxed());
+        }
+
+        // Wait for the seeds to be resolved
+        let resolution_result = futures::ready!(self
+            .pending_resolution
+            .as_mut()
+            .expect("The pending resolution should exist!")
+            .as_mut()
+            .poll(cx));
+        self.pending_resolution = None;
+
+        Poll::Ready(Some(resolution_result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiscoveryChangeListener;
+    use aptos_config::network_id::{NetworkId, RoleType};
+    use aptos_crypto::{PrivateKey, Uniform};
+    use aptos_event_notifications::DbBackedOnChainConfig;
+    use aptos_network::connectivity_manager::{ConnectivityRequest, DiscoverySource};
+    use aptos_types::network_address::Protocol;
+    use futures::StreamExt;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const SEED_DNS_NAME: &str = "_aptos-seed._tcp.example.com";
+
+    /// A health checker that marks the given addresses as unhealthy
+    struct StubSeedHealthChecker {
+        unhealthy_addresses: HashSet<NetworkAddress>,
+    }
+
+    #[async_trait]
+    impl SeedHealthChecker for StubSeedHealthChecker {
+        async fn check_health(
+            &self,
+            _peer_id: PeerId,
+            address: &NetworkAddress,
+        ) -> Result<(), String> {
+            if self.unhealthy_addresses.contains(address) {
+                Err("Unhealthy seed".into())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_dns_seeds() {
+        // Add several seeds to the DNS stub
+        let dns_resolver = Arc::new(StaticDnsResolver::new());
+        let public_key_1 = add_dns_seed(&dns_resolver, "seed1.example.com", 6182, None);
+        let public_key_2 = add_dns_seed(
+            &dns_resolver,
+            "seed2.example.com",
+            6183,
+            Some(from_identity_public_key),
+        );
+
+        // Resolve the seeds and verify the peer set
+        let seed_resolver = create_seed_resolver(dns_resolver, vec![], HashSet::new());
+        let peer_set = seed_resolver.resolve_seeds().await.unwrap();
+        assert_eq!(peer_set.len(), 2);
+        for (public_key, target, port) in [
+            (public_key_1, "seed1.example.com", 6182),
+            (public_key_2, "seed2.example.com", 6183),
+        ] {
+            let peer = peer_set.get(&from_identity_public_key(public_key)).unwrap();
+            assert_eq!(peer.keys, HashSet::from([public_key]));
+            assert_eq!(peer.addresses, vec![create_dns_address(
+                target, port, public_key
+            )]);
+            assert_eq!(peer.role, PeerRole::Upstream);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_seeds_identity_mismatch() {
+        // Add a valid seed and a seed with a mismatched peer id to the DNS stub
+        let dns_resolver = Arc::new(StaticDnsResolver::new());
+        let public_key = add_dns_seed(&dns_resolver, "seed1.example.com", 6182, None);
+        add_dns_seed(
+            &dns_resolver,
+            "seed2.example.com",
+            6182,
+            Some(|_| PeerId::random()),
+        );
+
+        // Add a seed with multiple public keys to the DNS stub
+        let public_key_3 = add_dns_seed(&dns_resolver, "seed3.example.com", 6182, None);
+        dns_resolver.add_txt_record(
+            "seed3.example.com",
+            &format!("{}{}", TXT_PUBLIC_KEY_PREFIX, create_public_key(10)),
+        );
+
+        // Add a static seed without a public key
+        let static_addrs = vec![NetworkAddress::from_str("/ip4/1.2.3.4/tcp/6182").unwrap()];
+
+        // Resolve the seeds and verify only the valid seed is returned
+        let seed_resolver = create_seed_resolver(dns_resolver, static_addrs, HashSet::new());
+        let peer_set = seed_resolver.resolve_seeds().await.unwrap();
+        assert_eq!(peer_set.len(), 1);
+        assert!(peer_set.contains_key(&from_identity_public_key(public_key)));
+        assert!(!peer_set.contains_key(&from_identity_public_key(public_key_3)));
+    }
+
+    #[tokio::test]
+    async fn test_noise_seed_health_checker() {
+        // Start a seed that accepts AptosNet connections
+        let chain_id = ChainId::test();
+        let seed_key = x25519::PrivateKey::generate(&mut StdRng::seed_from_u64(40));
+        let seed_public_key = seed_key.public_key();
+        let seed_network_context = NetworkContext::new(
+            RoleType::FullNode,
+            NetworkId::Public,
+            from_identity_public_key(seed_public_key),
+        );
+        let seed_transport = AptosNetTransport::new(
+            APTOS_TCP_TRANSPORT,
+            seed_network_context,
+            TimeService::real(),
+            seed_key,
+            HandshakeAuthMode::server_only(&[NetworkId::Public]),
+            HANDSHAKE_VERSION,
+            chain_id,
+            ProtocolIdSet::all_known(),
+            false, /* Disable proxy protocol */
+        );
+        let (inbound_upgrades, seed_addr) = seed_transport
+            .listen_on(NetworkAddress::from_str("/ip4/127.0.0.1/tcp/0").unwrap())
+            .unwrap();
+        tokio::spawn(async move {
+            let mut inbound_upgrades = Box::pin(inbound_upgrades);
+            while let Some(Ok((inbound_upgrade, _))) = inbound_upgrades.next().await {
+                tokio::spawn(inbound_upgrade);
+            }
+        });
+        let seed_port = match seed_addr.as_slice() {
+            [_, Protocol::Tcp(port), ..] => *port,
+            _ => panic!("Unexpected seed address: {}", seed_addr),
+        };
+
+        // Add the seed to the DNS stub, but with a mismatched public key
+        let seed_target = "localhost";
+        let dns_resolver = Arc::new(StaticDnsResolver::new());
+        dns_resolver.add_srv_record(SEED_DNS_NAME, SrvRecord {
+            priority: 0,
+            weight: 0,
+            port: seed_port,
+            target: seed_target.into(),
+        });
+        dns_resolver.add_txt_record(
+            seed_target,
+            &format!("{}{}", TXT_PUBLIC_KEY_PREFIX, create_public_key(50)),
+        );
+
+        // Verify the seed fails the health check (i.e., the noise handshake)
+        let seed_discovery = SeedDiscovery {
+            dns_names: vec![SEED_DNS_NAME.into()],
+            ..Default::default()
+        };
+        let network_context =
+            NetworkContext::new(RoleType::FullNode, NetworkId::Public, PeerId::random());
+        let seed_resolver = SeedResolver::new(
+            network_context,
+            &seed_discovery,
+            dns_resolver.clone(),
+            Arc::new(NoiseSeedHealthChecker::new(
+                network_context,
+                chain_id,
+                Duration::from_secs(5),
+            )),
+        );
+        let peer_set = seed_resolver.resolve_seeds().await.unwrap();
+        assert!(peer_set.is_empty());
+
+        // Advertise the correct public key and verify the seed is now healthy
+        dns_resolver.remove_records(seed_target);
+        dns_resolver.add_txt_record(
+            seed_target,
+            &format!("{}{}", TXT_PUBLIC_KEY_PREFIX, seed_public_key),
+        );
+        let peer_set = seed_resolver.resolve_seeds().await.unwrap();
+        assert_eq!(peer_set.len(), 1);
+        let peer = peer_set
+            .get(&from_identity_public_key(seed_public_key))
+            .unwrap();
+        assert_eq!(peer.addresses, vec![create_dns_address(
+            seed_target,
+            seed_port,
+            seed_public_key
+        )]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_seeds_health_filtering() {
+        // Add a DNS seed and create a static seed
+        let dns_resolver = Arc::new(StaticDnsResolver::new());
+        let dns_public_key = add_dns_seed(&dns_resolver, "seed1.example.com", 6182, None);
+        let static_public_key = create_public_key(20);
+        let static_addr = NetworkAddress::from_str("/ip4/1.2.3.4/tcp/6182")
+            .unwrap()
+            .append_prod_protos(static_public_key, HANDSHAKE_VERSION);
+
+        // Mark the DNS seed as unhealthy
+        let unhealthy_addresses = HashSet::from([create_dns_address(
+            "seed1.example.com",
+            6182,
+            dns_public_key,
+        )]);
+
+        // Resolve the seeds and verify only the healthy (static) seed is returned
+        let seed_resolver =
+            create_seed_resolver(dns_resolver, vec![static_addr.clone()], unhealthy_addresses);
+        let peer_set = seed_resolver.resolve_seeds().await.unwrap();
+        assert_eq!(peer_set.len(), 1);
+        let peer = peer_set
+            .get(&from_identity_public_key(static_public_key))
+            .unwrap();
+        assert_eq!(peer.addresses, vec![static_addr]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_seeds_dns_failure() {
+        // Resolve the seeds without any DNS records or static seeds and verify an error is returned
+        let dns_resolver = Arc::new(StaticDnsResolver::new());
+        let seed_resolver = create_seed_resolver(dns_resolver.clone(), vec![], HashSet::new());
+        assert!(matches!(
+            seed_resolver.resolve_seeds().await,
+            Err(DiscoveryError::Dns(_))
+        ));
+
+        // Resolve the seeds with a static seed and verify the static seed is returned
+        let static_public_key = create_public_key(30);
+        let static_addr = NetworkAddress::from_str("/ip4/1.2.3.4/tcp/6182")
+            .unwrap()
+            .append_prod_protos(static_public_key, HANDSHAKE_VERSION);
+        let seed_resolver = create_seed_resolver(dns_resolver, vec![static_addr], HashSet::new());
+        let peer_set = seed_resolver.resolve_seeds().await.unwrap();
+        assert_eq!(peer_set.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_seed_listener() {
+        // Add a seed to the DNS stub
+        let dns_resolver = Arc::new(StaticDnsResolver::new());
+        let public_key = add_dns_seed(&dns_resolver, "seed1.example.com", 6182, None);
+
+        // Create and start the seed discovery listener
+        let (conn_mgr_reqs_tx, mut conn_mgr_reqs_rx) = aptos_channels::new(
+            1,
+            &aptos_network::counters::PENDING_CONNECTIVITY_MANAGER_REQUESTS,
+        );
+        let seed_discovery = SeedDiscovery {
+            dns_names: vec![SEED_DNS_NAME.into()],
+            ..Default::default()
+        };
+        let listener = DiscoveryChangeListener::<DbBackedOnChainConfig>::seeds_with_resolvers(
+            NetworkContext::mock(),
+            conn_mgr_reqs_tx,
+            &seed_discovery,
+            dns_resolver.clone(),
+            Arc::new(NoopSeedHealthChecker),
+            Duration::from_millis(5),
+            TimeService::real(),
+        );
+        tokio::spawn(Box::pin(listener).run());
+
+        // Verify the seed is discovered
+        match conn_mgr_reqs_rx.next().await {
+            Some(ConnectivityRequest::UpdateDiscoveredPeers(DiscoverySource::Seeds, peers)) => {
+                assert!(peers.contains_key(&from_identity_public_key(public_key)));
+            },
+            request => panic!("Unexpected discovery request: {:?}", request),
+        }
+
+        // Remove the seed from DNS and verify the seeds are refreshed
+        dns_resolver.remove_records(SEED_DNS_NAME);
+        dns_resolver.add_srv_record(SEED_DNS_NAME, SrvRecord {
+            priority: 0,
+            weight: 0,
+            port: 6182,
+            target: "seed2.example.com".into(),
+        });
+        loop {
+            match conn_mgr_reqs_rx.next().await {
+                Some(ConnectivityRequest::UpdateDiscoveredPeers(DiscoverySource::Seeds, peers)) => {
+                    if peers.is_empty() {
+                        break; // The seed was removed
+                    }
+                },
+                request => panic!("Unexpected discovery request: {:?}", request),
+            }
+        }
+    }
+
+    /// Adds a seed (SRV and TXT records) to the DNS stub and returns the public
+    /// key of the seed. If a peer id function is given, the peer id is also added.
+    fn add_dns_seed(
+        dns_resolver: &StaticDnsResolver,
+        target: &str,
+        port: u16,
+        peer_id_fn: Option<fn(x25519::PublicKey) -> PeerId>,
+    ) -> x25519::PublicKey {
+        // Add the SRV record
+        dns_resolver.add_srv_record(SEED_DNS_NAME, SrvRecord {
+            priority: 0,
+            weight: 0,
+            port,
+            target: target.into(),
+        });
+
+        // Add the TXT records
+        let public_key =
+            create_public_key(target.bytes().map(u64::from).sum::<u64>() + port as u64);
+        dns_resolver.add_txt_record(target, &format!("{}{}", TXT_PUBLIC_KEY_PREFIX, public_key));
+        if let Some(peer_id_fn) = peer_id_fn {
+            dns_resolver.add_txt_record(
+                target,
+                &format!("{}{}", TXT_PEER_ID_PREFIX, peer_id_fn(public_key)),
+            );
+        }
+        dns_resolver.add_txt_record(target, "unrelated=record");
+
+        public_key
+    }
+
+    /// Creates the network address for the given DNS seed
+    fn create_dns_address(
+        target: &str,
+        port: u16,
+        public_key: x25519::PublicKey,
+    ) -> NetworkAddress {
+        NetworkAddress::from_str(&format!("/dns/{}/tcp/{}", target, port))
+            .unwrap()
+            .append_prod_protos(public_key, HANDSHAKE_VERSION)
+    }
+
+    /// Creates a deterministic public key from the given seed
+    fn create_public_key(seed: u64) -> x25519::PublicKey {
+        let mut rng = StdRng::seed_from_u64(seed);
+        x25519::PrivateKey::generate(&mut rng).public_key()
+    }
+
+    /// Creates a seed resolver that uses the given DNS stub, static
+    /// seed addresses and unhealthy addresses.
+    fn create_seed_resolver(
+        dns_resolver: Arc<StaticDnsResolver>,
+        static_addrs: Vec<NetworkAddress>,
+        unhealthy_addresses: HashSet<NetworkAddress>,
+    ) -> SeedResolver {
+        let seed_discovery = SeedDiscovery {
+            dns_names: vec![SEED_DNS_NAME.into()],
+            static_addrs,
+            ..Default::default()
+        };
+        SeedResolver::new(
+            NetworkContext::new(RoleType::FullNode, NetworkId::Public, PeerId::random()),
+            &seed_discovery,
+            dns_resolver,
+            Arc::new(StubSeedHealthChecker {
+                unhealthy_addresses,
+            }),
+        )
+    }
+}
@@ -0,0 +1,496 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::counters;
+use aptos_config::{
+    config::PeerReputationConfig,
+    network_id::{NetworkId, PeerNetworkId},
+};
+use aptos_infallible::{Mutex, RwLock};
+use aptos_logger::{error, info, warn};
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::PeerId;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Penalties below this value are considered negligible (and are pruned)
+const NEGLIGIBLE_PENALTY: f64 = 1.0;
+
+/// The severity of a misbehavior reported against a peer
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MisbehaviorSeverity {
+    /// The peer is not helpful, e.g., it sent an unexpected message or timed out
+    Low,
+    /// The peer sent invalid data, e.g., a malformed or unverifiable message
+    Medium,
+    /// The peer appears to be actively malicious, e.g., it sent an invalid proof
+    High,
+    /// The peer is definitely malicious and should be banned immediately
+    Critical,
+}
+
+impl MisbehaviorSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MisbehaviorSeverity::Low => "low",
+            MisbehaviorSeverity::Medium => "medium",
+            MisbehaviorSeverity::High => "high",
+            MisbehaviorSeverity::Critical => "critical",
+        }
+    }
+
+    /// Returns the penalty added to a peer's score for this misbehavior
+    fn get_penalty(&self, peer_reputation_config: &PeerReputationConfig) -> f64 {
+        match self {
+            MisbehaviorSeverity::Low => 5.0,
+            MisbehaviorSeverity::Medium => 25.0,
+            MisbehaviorSeverity::High => 100.0,
+            MisbehaviorSeverity::Critical => peer_reputation_config.ban_penalty_threshold as f64,
+        }
+    }
+}
+
+/// A ban for a single peer
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PeerBan {
+    /// The peer that is banned
+    pub peer_id: PeerId,
+    /// The network on which the peer is banned. Automatic bans only apply to
+    /// the network on which the misbehavior was reported. If None, the ban
+    /// applies to all networks (e.g., manual bans by the operator).
+    #[serde(default)]
+    pub network_id: Option<NetworkId>,
+    /// The time (unix secs) at which the ban expires (if None, the ban is permanent)
+    pub expiration_unix_secs: Option<u64>,
+    /// The reason for the ban
+    pub reason: String,
+}
+
+impl PeerBan {
+    /// Returns true iff the ban applies to the given peer (and network)
+    fn applies_to(&self, peer_network_id: &PeerNetworkId) -> bool {
+        self.peer_id == peer_network_id.peer_id()
+            && self.network_id.map_or(true, |network_id| {
+                network_id == peer_network_id.network_id()
+            })
+    }
+
+    /// Returns true iff the ban has expired at the given time
+    fn has_expired(&self, now_unix_secs: u64) -> bool {
+        self.expiration_unix_secs
+            .map_or(false, |expiration_unix_secs| {
+                now_unix_secs >= expiration_unix_secs
+            })
+    }
+}
+
+/// The penalty score of a single peer
+#[derive(Clone, Debug)]
+struct PeerPenalty {
+    penalty: f64,         // The penalty (at the time of the last update)
+    last_update: Instant, // The time of the last update
+}
+
+/// A shared service that tracks the reputation of peers across all protocols.
+///
+/// Applications report peer misbehavior using [`PeerReputation::report_misbehavior`].
+/// Each report adds a penalty to the peer's score, and penalties decay
+/// exponentially over time. Once the penalty of a peer exceeds the disconnect
+/// threshold, the peer should be disconnected (and not reconnected until the
+/// penalty has decayed). Once the penalty exceeds the ban threshold, the peer is
+/// temporarily banned on the same network. Bans are persisted across restarts.
+///
+/// Note: automatic disconnects and bans are never applied to the validator
+/// network, as the validator set is determined on-chain. Misbehavior is still
+/// tracked (for inspection), and operators may still ban validators manually.
+#[derive(Debug)]
+pub struct PeerReputation {
+    peer_reputation_config: PeerReputationConfig,
+    ban_list_path: Option<PathBuf>, // If None, bans are not persisted
+    time_service: TimeService,
+
+    peer_penalties: RwLock<HashMap<PeerNetworkId, PeerPenalty>>,
+    last_penalty_prune: Mutex<Instant>, // The last time negligible penalties were pruned
+    banned_peers: RwLock<HashMap<(PeerId, Option<NetworkId>), PeerBan>>,
+
+    // The ban list is persisted by a dedicated thread, so that
+    // misbehavior reports never block on file system operations.
+    ban_list_persister: Option<Mutex<mpsc::Sender<BanListUpdate>>>,
+}
+
+impl PeerReputation {
+    pub fn new(
+        peer_reputation_config: PeerReputationConfig,
+        ban_list_path: Option<PathBuf>,
+        time_service: TimeService,
+    ) -> Self {
+        let ban_list_persister = ban_list_path
+            .clone()
+            .map(|ban_list_path| Mutex::new(spawn_ban_list_persister(ban_list_path)));
+        let peer_reputation = Self {
+            peer_reputation_config,
+            ban_list_path,
+            last_penalty_prune: Mutex::new(time_service.now()),
+            time_service,
+            peer_penalties: RwLock::new(HashMap::new()),
+            banned_peers: RwLock::new(HashMap::new()),
+            ban_list_persister,
+        };
+        peer_reputation.load_ban_list();
+        peer_reputation
+    }
+
+    /// Reports a misbehavior by the given peer. If the peer's penalty exceeds the
+    /// ban threshold (and automatic bans are enabled), the peer is banned.
+    pub fn report_misbehavior(
+        &self,
+        peer_network_id: PeerNetworkId,
+        severity: MisbehaviorSeverity,
+        reason: &str,
+    ) {
+        counters::peer_misbehavior_reports(peer_network_id.network_id(), severity.as_str()).inc();
+
+        // Update the penalty for the peer
+        let now = self.time_service.now();
+        let penalty = {
+            let added_penalty = severity.get_penalty(&self.peer_reputation_config);
+            let mut peer_penalties = self.peer_penalties.write();
+            match peer_penalties.entry(peer_network_id) {
+                Entry::Occupied(mut entry) => {
+                    let peer_penalty = entry.get_mut();
+                    peer_penalty.penalty = self.decay_penalty(peer_penalty, now) + added_penalty;
+                    peer_penalty.last_update = now;
+                    peer_penalty.penalty
+                },
+                Entry::Vacant(entry) => {
+                    entry.insert(PeerPenalty {
+                        penalty: added_penalty,
+                        last_update: now,
+                    });
+                    added_penalty
+                },
+            }
+        };
+
+        // Prune negligible penalties (if enough time has elapsed since the last prune)
+        self.maybe_prune_penalties(now);
+
+        // Ban the peer (on the same network) if the penalty is too high
+        if self.automatic_actions_enabled(&peer_network_id)
+            && penalty >= self.peer_reputation_config.ban_penalty_threshold as f64
+            && !self.is_banned(&peer_network_id)
+        {
+            let ban_duration = Duration::from_secs(self.peer_reputation_config.ban_duration_secs);
+            self.insert_ban(
+                peer_network_id.peer_id(),
+                Some(peer_network_id.network_id()),
+                Some(ban_duration),
+                format!("Automatic ban: {}", reason),
+            );
+        } else {
+            info!(
+                "Peer misbehavior reported for {} (severity: {}, penalty: {:.2}): {}",
+                peer_network_id,
+                severity.as_str(),
+                penalty,
+                reason
+            );
+        }
+    }
+
+    /// Returns the current (decayed) penalty of the given peer
+    pub fn get_penalty(&self, peer_network_id: &PeerNetworkId) -> f64 {
+        let now = self.time_service.now();
+        self.peer_penalties
+            .read()
+            .get(peer_network_id)
+            .map_or(0.0, |peer_penalty| self.decay_penalty(peer_penalty, now))
+    }
+
+    /// Returns the current (decayed) penalties of all peers with a
+    /// non-negligible penalty, sorted from highest to lowest.
+    pub fn get_penalties(&self) -> Vec<(PeerNetworkId, f64)> {
+        // Remove all negligible penalties
+        let now = self.time_service.now();
+        let mut peer_penalties = self.peer_penalties.write();
+        peer_penalties
+            .retain(|_, peer_penalty| self.decay_penalty(peer_penalty, now) >= NEGLIGIBLE_PENALTY);
+
+        // Sort the penalties
+        let mut penalties: Vec<_> = peer_penalties
+            .iter()
+            .map(|(peer_network_id, peer_penalty)| {
+                (*peer_network_id, self.decay_penalty(peer_penalty, now))
+            })
+            .collect();
+        penalties.sort_by(|(_, first), (_, second)| second.total_cmp(first));
+        penalties
+    }
+
+    /// Returns true iff the connection to the given peer should be closed
+    /// (and the peer should not be dialed or accepted), i.e., if the peer is
+    /// banned on the network, or if its penalty exceeds the disconnect threshold.
+    pub fn should_disconnect(&self, peer_network_id: &PeerNetworkId) -> bool {
+        if self.is_banned(peer_network_id) {
+            return true;
+        }
+
+        self.automatic_actions_enabled(peer_network_id)
+            && self.get_penalty(peer_network_id)
+                >= self.peer_reputation_config.disconnect_penalty_threshold as f64
+    }
+
+    /// Returns true iff the given peer is currently banned on the given network
+    pub fn is_banned(&self, peer_network_id: &PeerNetworkId) -> bool {
+        let now_unix_secs = self.time_service.now_unix_time().as_secs();
+        self.banned_peers.read().values().any(|peer_ban| {
+            peer_ban.applies_to(peer_network_id) && !peer_ban.has_expired(now_unix_secs)
+        })
+    }
+
+    /// Returns all active peer bans
+    pub fn get_banned_peers(&self) -> Vec<PeerBan> {
+        self.remove_expired_bans();
+        let mut banned_peers: Vec<_> = self.banned_peers.read().values().cloned().collect();
+        banned_peers.sort_by_key(|peer_ban| (peer_ban.peer_id, peer_ban.network_id));
+        banned_peers
+    }
+
+    /// Bans the given peer (on all networks) for the specified duration. If
+    /// no duration is specified, the ban is permanent (until it is removed).
+    pub fn ban_peer(&self, peer_id: PeerId, ban_duration: Option<Duration>, reason: String) {
+        self.insert_ban(peer_id, None, ban_duration, reason);
+    }
+
+    /// Removes all bans for the given peer, and resets the peer's penalties.
+    /// Returns true iff the peer was banned.
+    pub fn unban_peer(&self, peer_id: &PeerId) -> bool {
+        let was_banned = {
+            let mut banned_peers = self.banned_peers.write();
+            let num_bans = banned_peers.len();
+            banned_peers.retain(|(banned_peer_id, _), _| banned_peer_id != peer_id);
+            num_bans != banned_peers.len()
+        };
+        self.peer_penalties
+            .write()
+            .retain(|peer_network_id, _| peer_network_id.peer_id() != *peer_id);
+        if was_banned {
+            info!("Removed the ban for peer {}", peer_id);
+            self.persist_ban_list();
+        }
+        was_banned
+    }
+
+    /// Flushes the ban list, i.e., blocks until all pending ban list
+    /// updates have been persisted.
+    pub fn flush_ban_list(&self) {
+        if let Some(ban_list_persister) = &self.ban_list_persister {
+            let (flush_tx, flush_rx) = mpsc::channel();
+            if ban_list_persister
+                .lock()
+                .send(BanListUpdate::Flush(flush_tx))
+                .is_ok()
+            {
+                let _ = flush_rx.recv();
+            }
+        }
+    }
+
+    /// Inserts a ban for the given peer (and network, if specified)
+    fn insert_ban(
+        &self,
+        peer_id: PeerId,
+        network_id: Option<NetworkId>,
+        ban_duration: Option<Duration>,
+        reason: String,
+    ) {
+        let now_unix_secs = self.time_service.now_unix_time().as_secs();
+        let peer_ban = PeerBan {
+            peer_id,
+            network_id,
+            expiration_unix_secs: ban_duration
+                .map(|ban_duration| now_unix_secs.saturating_add(ban_duration.as_secs())),
+            reason,
+        };
+        warn!(
+            "Banning peer {} (network: {:?}, expiration unix secs: {:?}): {}",
+            peer_id, network_id, peer_ban.expiration_unix_secs, peer_ban.reason
+        );
+
+        self.banned_peers
+            .write()
+            .insert((peer_id, network_id), peer_ban);
+        self.persist_ban_list();
+    }
+
+    /// Removes all negligible penalties, if at least one penalty
+    /// half life has elapsed since the last prune. This bounds the
+    /// number of tracked peers (e.g., on the public network).
+    fn maybe_prune_penalties(&self, now: Instant) {
+        let prune_interval =
+            Duration::from_secs(self.peer_reputation_config.penalty_half_life_secs);
+        {
+            let mut last_penalty_prune = self.last_penalty_prune.lock();
+            if now.saturating_duration_since(*last_penalty_prune) < prune_interval {
+                return;
+            }
+            *last_penalty_prune = now;
+        }
+
+        self.peer_penalties
+            .write()
+            .retain(|_, peer_penalty| self.decay_penalty(peer_penalty, now) >= NEGLIGIBLE_PENALTY);
+    }
+
+    /// Returns true iff automatic disconnects and bans apply to the given peer
+    fn automatic_actions_enabled(&self, peer_network_id: &PeerNetworkId) -> bool {
+        self.peer_reputation_config.enable_automatic_bans
+            && peer_network_id.network_id() != NetworkId::Validator
+    }
+
+    /// Returns the penalty of the peer (decayed to the given time)
+    fn decay_penalty(&self, peer_penalty: &PeerPenalty, now: Instant) -> f64 {
+        let half_life_secs = self.peer_reputation_config.penalty_half_life_secs.max(1) as f64;
+        let elapsed_secs = now
+            .saturating_duration_since(peer_penalty.last_update)
+            .as_secs_f64();
+        peer_penalty.penalty * 0.5f64.powf(elapsed_secs / half_life_secs)
+    }
+
+    /// Removes all expired bans (and persists the ban list if any were removed)
+    fn remove_expired_bans(&self) {
+        let now_unix_secs = self.time_service.now_unix_time().as_secs();
+        let num_removed_bans = {
+            let mut banned_peers = self.banned_peers.write();
+            let num_bans = banned_peers.len();
+            banned_peers.retain(|_, peer_ban| !peer_ban.has_expired(now_unix_secs));
+            num_bans - banned_peers.len()
+        };
+        if num_removed_bans > 0 {
+            self.persist_ban_list();
+        }
+    }
+
+    /// Loads the persisted ban list (if one exists). Expired bans are ignored.
+    fn load_ban_list(&self) {
+        let ban_list_path = match &self.ban_list_path {
+            Some(ban_list_path) if ban_list_path.exists() => ban_list_path,
+            _ => return,
+        };
+
+        let peer_bans = fs::read_to_string(ban_list_path)
+            .map_err(|error| error.to_string())
+            .and_then(|ban_list| {
+                serde_json::from_str::<Vec<PeerBan>>(&ban_list).map_err(|error| error.to_string())
+            });
+        match peer_bans {
+            Ok(peer_bans) => {
+                let now_unix_secs = self.time_service.now_unix_time().as_secs();
+                let mut banned_peers = self.banned_peers.write();
+                for peer_ban in peer_bans {
+                    if !peer_ban.has_expired(now_unix_secs) {
+                        banned_peers.insert((peer_ban.peer_id, peer_ban.network_id), peer_ban);
+                    }
+                }
+                info!(
+                    "Loaded {} peer bans from {:?}",
+                    banned_peers.len(),
+                    ban_list_path
+                );
+            },
+            Err(error) => {
+                warn!(
+                    "Failed to load the peer ban list from {:?}! Error: {}",
+                    ban_list_path, error
+                );
+            },
+        }
+    }
+
+    /// Sends a snapshot of the ban list to the persister thread
+    /// (if a ban list path is configured).
+    fn persist_ban_list(&self) {
+        let mut peer_bans: Vec<_> = self.banned_peers.read().values().cloned().collect();
+        counters::set_num_banned_peers(peer_bans.len());
+
+        if let Some(ban_list_persister) = &self.ban_list_persister {
+            peer_bans.sort_by_key(|peer_ban| (peer_ban.peer_id, peer_ban.network_id));
+            if let Err(error) = ban_list_persister
+                .lock()
+                .send(BanListUpdate::Persist(peer_bans))
+            {
+                error!(
+                    "Failed to send the ban list to the persister thread! Error: {}",
+                    error
+                );
+            }
+        }
+    }
+}
+
+/// An update sent to the ban list persister thread
+#[derive(Debug)]
+enum BanListUpdate {
+    Persist(Vec<PeerBan>),   // Persist the given ban list
+    Flush(mpsc::Sender<()>), // Notify the sender once all previous updates are persisted
+}
+
+/// Spawns a thread that persists ban list updates to the given path,
+/// and returns the sender for the updates. The thread terminates once
+/// the sender is dropped.
+fn spawn_ban_list_persister(ban_list_path: PathBuf) -> mpsc::Sender<BanListUpdate> {
+    let (update_tx, update_rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("ban-list-persist".into())
+        .spawn(move || {
+            while let Ok(update) = update_rx.recv() {
+                // Only persist the latest ban list (older updates are stale)
+                let mut latest_peer_bans = None;
+                let mut flush_notifiers = vec![];
+                for update in std::iter::once(update).chain(update_rx.try_iter()) {
+                    match update {
+                        BanListUpdate::Persist(peer_bans) => latest_peer_bans = Some(peer_bans),
+                        BanListUpdate::Flush(flush_notifier) => {
+                            flush_notifiers.push(flush_notifier)
+                        },
+                    }
+                }
+
+                if let Some(peer_bans) = latest_peer_bans {
+                    if let Err(error) = write_ban_list(&ban_list_path, &peer_bans) {
+                        warn!(
+                            "Failed to persist the peer ban list to {:?}! Error: {}",
+                            ban_list_path, error
+                        );
+                    }
+                }
+                for flush_notifier in flush_notifiers {
+                    let _ = flush_notifier.send(());
+                }
+            }
+        })
+        .expect("Failed to spawn the ban list persister thread!");
+    update_tx
+}
+
+/// Atomically writes the ban list to the given path (i.e., the ban list
+/// is written to a temporary file, which then replaces the existing file).
+fn write_ban_list(ban_list_path: &Path, peer_bans: &[PeerBan]) -> Result<(), String> {
+    let ban_list = serde_json::to_string_pretty(peer_bans).map_err(|error| error.to_string())?;
+    let temp_ban_list_path = ban_list_path.with_extension("tmp");
+    fs::write(&temp_ban_list_path, ban_list).map_err(|error| error.to_string())?;
+    fs::rename(&temp_ban_list_path, ban_list_path).map_err(|error| error.to_string())
+}
+
+impl Default for PeerReputation {
+    fn default() -> Self {
+        Self::new(PeerReputationConfig::default(), None, TimeService::real())
+    }
+}
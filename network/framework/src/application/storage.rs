@@ -6,6 +6,7 @@ use crate::{
     application::{
         error::Error,
         metadata::{ConnectionState, PeerMetadata},
+        reputation::PeerReputation,
     },
     counters,
     peer_manager::ConnectionNotification,
@@ -52,16 +53,28 @@ pub struct PeersAndMetadata {
     cached_peers_and_metadata: Arc<ArcSwap<HashMap<NetworkId, HashMap<PeerId, PeerMetadata>>>>,
 
     subscribers: Mutex<Vec<tokio::sync::mpsc::Sender<ConnectionNotification>>>,
+
+    // The reputation of all peers (shared by all networks and applications)
+    peer_reputation: Arc<PeerReputation>,
 }
 
 impl PeersAndMetadata {
     pub fn new(network_ids: &[NetworkId]) -> Arc<PeersAndMetadata> {
+        Self::new_with_peer_reputation(network_ids, Arc::new(PeerReputation::default()))
+    }
+
+    /// Creates the container using the given (shared) peer reputation service
+    pub fn new_with_peer_reputation(
+        network_ids: &[NetworkId],
+        peer_reputation: Arc<PeerReputation>,
+    ) -> Arc<PeersAndMetadata> {
         // Create the container
         let mut peers_and_metadata = PeersAndMetadata {
             peers_and_metadata: RwLock::new(HashMap::new()),
             trusted_peers: HashMap::new(),
             cached_peers_and_metadata: Arc::new(ArcSwap::from(Arc::new(HashMap::new()))),
             subscribers: Mutex::new(vec![]),
+            peer_reputation,
         };
 
         // Initialize each network mapping and trusted peer set
@@ -87,6 +100,12 @@ impl PeersAndMetadata {
         Arc::new(peers_and_metadata)
     }
 
+    /// Returns the peer reputation service. Applications should use this
+    /// to report misbehaving peers.
+    pub fn get_peer_reputation(&self) -> Arc<PeerReputation> {
+        self.peer_reputation.clone()
+    }
+
     /// Returns all peers. Note: this will return disconnected and unhealthy peers, so
     /// it is not recommended for applications to use this interface. Instead,
     /// `get_connected_peers_and_metadata()` should be used.
@@ -7,6 +7,7 @@ use crate::{
         error::Error,
         interface::{NetworkClient, NetworkClientInterface, NetworkServiceEvents},
         metadata::{ConnectionState, PeerMetadata},
+        reputation::{MisbehaviorSeverity, PeerReputation},
         storage::PeersAndMetadata,
     },
     peer_manager::{
@@ -22,10 +23,12 @@ use crate::{
 };
 use aptos_channels::{aptos_channel, message_queues::QueueStyle};
 use aptos_config::{
-    config::{Peer, PeerRole, PeerSet},
+    config::{Peer, PeerReputationConfig, PeerRole, PeerSet},
     network_id::{NetworkId, PeerNetworkId},
 };
 use aptos_peer_monitoring_service_types::PeerMonitoringMetadata;
+use aptos_temppath::TempPath;
+use aptos_time_service::TimeService;
 use aptos_types::{account_address::AccountAddress, PeerId};
 use futures::channel::oneshot;
 use futures_util::StreamExt;
@@ -973,6 +976,182 @@ fn check_connected_peers_and_metadata(
     compare_vectors_ignore_order(connected_peers_and_metadata, expected_peers);
 }
 
+#[test]
+fn test_peer_reputation_decay_and_disconnect() {
+    // Create the peer reputation service (with automatic bans enabled)
+    let time_service = TimeService::mock();
+    let peer_reputation_config = PeerReputationConfig {
+        enable_automatic_bans: true,
+        ..Default::default()
+    };
+    let peer_reputation =
+        PeerReputation::new(peer_reputation_config.clone(), None, time_service.clone());
+
+    // Report several misbehaviors for a public peer
+    let peer_network_id = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+    for _ in 0..2 {
+        peer_reputation.report_misbehavior(peer_network_id, MisbehaviorSeverity::High, "test");
+    }
+
+    // Verify the peer should be disconnected, but is not banned
+    assert_eq!(peer_reputation.get_penalty(&peer_network_id), 200.0);
+    assert!(peer_reputation.should_disconnect(&peer_network_id));
+    assert!(!peer_reputation.is_banned(&peer_network_id));
+
+    // Elapse a single half life and verify the penalty has decayed
+    let mock_time_service = time_service.into_mock();
+    mock_time_service.advance(Duration::from_secs(
+        peer_reputation_config.penalty_half_life_secs,
+    ));
+    assert_eq!(peer_reputation.get_penalty(&peer_network_id), 100.0);
+    assert!(!peer_reputation.should_disconnect(&peer_network_id));
+
+    // Verify the penalty is reported
+    assert_eq!(peer_reputation.get_penalties(), vec![(
+        peer_network_id,
+        100.0
+    )]);
+
+    // Elapse several half lives and report a misbehavior for another peer
+    mock_time_service.advance(Duration::from_secs(
+        10 * peer_reputation_config.penalty_half_life_secs,
+    ));
+    let other_peer_network_id = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+    peer_reputation.report_misbehavior(other_peer_network_id, MisbehaviorSeverity::Low, "test");
+
+    // Verify the negligible penalty was pruned when the misbehavior was reported
+    assert_eq!(peer_reputation.get_penalty(&peer_network_id), 0.0);
+    assert_eq!(peer_reputation.get_penalties(), vec![(
+        other_peer_network_id,
+        5.0
+    )]);
+}
+
+#[test]
+fn test_peer_reputation_automatic_ban() {
+    // Create the peer reputation service (with automatic bans enabled)
+    let time_service = TimeService::mock();
+    let peer_reputation_config = PeerReputationConfig {
+        enable_automatic_bans: true,
+        ..Default::default()
+    };
+    let peer_reputation =
+        PeerReputation::new(peer_reputation_config.clone(), None, time_service.clone());
+
+    // Report a critical misbehavior for a public and a validator peer
+    let public_peer = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+    let validator_peer = PeerNetworkId::new(NetworkId::Validator, PeerId::random());
+    for peer_network_id in [public_peer, validator_peer] {
+        peer_reputation.report_misbehavior(peer_network_id, MisbehaviorSeverity::Critical, "test");
+    }
+
+    // Verify that only the public peer is banned (validators are never banned automatically)
+    assert!(peer_reputation.is_banned(&public_peer));
+    assert!(peer_reputation.should_disconnect(&public_peer));
+    assert!(!peer_reputation.is_banned(&validator_peer));
+    assert!(!peer_reputation.should_disconnect(&validator_peer));
+
+    // Verify the ban only applies to the network on which the peer misbehaved
+    let banned_peers = peer_reputation.get_banned_peers();
+    assert_eq!(banned_peers.len(), 1);
+    assert_eq!(banned_peers[0].network_id, Some(NetworkId::Public));
+    for network_id in [NetworkId::Validator, NetworkId::Vfn] {
+        let other_network_peer = PeerNetworkId::new(network_id, public_peer.peer_id());
+        assert!(!peer_reputation.is_banned(&other_network_peer));
+        assert!(!peer_reputation.should_disconnect(&other_network_peer));
+    }
+
+    // Elapse the ban duration and verify the ban has expired
+    time_service.into_mock().advance(Duration::from_secs(
+        peer_reputation_config.ban_duration_secs,
+    ));
+    assert!(!peer_reputation.is_banned(&public_peer));
+    assert!(peer_reputation.get_banned_peers().is_empty());
+}
+
+#[test]
+fn test_peer_reputation_disabled_automatic_bans() {
+    // Create the peer reputation service with automatic bans disabled
+    let peer_reputation_config = PeerReputationConfig {
+        enable_automatic_bans: false,
+        ..Default::default()
+    };
+    let peer_reputation = PeerReputation::new(peer_reputation_config, None, TimeService::mock());
+
+    // Report a critical misbehavior and verify the peer is not banned or disconnected
+    let peer_network_id = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+    peer_reputation.report_misbehavior(peer_network_id, MisbehaviorSeverity::Critical, "test");
+    assert!(!peer_reputation.is_banned(&peer_network_id));
+    assert!(!peer_reputation.should_disconnect(&peer_network_id));
+
+    // Ban the peer manually and verify the peer should be disconnected (on all networks)
+    peer_reputation.ban_peer(peer_network_id.peer_id(), None, "manual".into());
+    assert!(peer_reputation.should_disconnect(&peer_network_id));
+    let validator_peer = PeerNetworkId::new(NetworkId::Validator, peer_network_id.peer_id());
+    assert!(peer_reputation.should_disconnect(&validator_peer));
+
+    // Unban the peer and verify the peer is no longer banned (and the penalty is reset)
+    assert!(peer_reputation.unban_peer(&peer_network_id.peer_id()));
+    assert!(!peer_reputation.unban_peer(&peer_network_id.peer_id()));
+    assert!(!peer_reputation.should_disconnect(&peer_network_id));
+    assert_eq!(peer_reputation.get_penalty(&peer_network_id), 0.0);
+}
+
+#[test]
+fn test_peer_reputation_persisted_bans() {
+    // Create the peer reputation service with a ban list file
+    let ban_list_dir = TempPath::new();
+    ban_list_dir.create_as_dir().unwrap();
+    let ban_list_path = ban_list_dir.path().join("peer_ban_list.json");
+    let time_service = TimeService::mock();
+    let peer_reputation = PeerReputation::new(
+        PeerReputationConfig::default(),
+        Some(ban_list_path.clone()),
+        time_service.clone(),
+    );
+
+    // Ban several peers (one permanently, and one temporarily)
+    let permanently_banned_peer = PeerId::random();
+    let temporarily_banned_peer = PeerId::random();
+    peer_reputation.ban_peer(permanently_banned_peer, None, "permanent".into());
+    peer_reputation.ban_peer(
+        temporarily_banned_peer,
+        Some(Duration::from_secs(10)),
+        "temporary".into(),
+    );
+    peer_reputation.flush_ban_list();
+
+    // Verify the bans are loaded by a new peer reputation service
+    let peer_reputation = PeerReputation::new(
+        PeerReputationConfig::default(),
+        Some(ban_list_path.clone()),
+        time_service.clone(),
+    );
+    for banned_peer in [permanently_banned_peer, temporarily_banned_peer] {
+        let banned_peer = PeerNetworkId::new(NetworkId::Public, banned_peer);
+        assert!(peer_reputation.is_banned(&banned_peer));
+    }
+
+    // Elapse the temporary ban and verify that only the permanent ban is loaded
+    time_service
+        .clone()
+        .into_mock()
+        .advance(Duration::from_secs(10));
+    let peer_reputation = PeerReputation::new(
+        PeerReputationConfig::default(),
+        Some(ban_list_path),
+        time_service,
+    );
+    assert_eq!(
+        peer_reputation
+            .get_banned_peers()
+            .iter()
+            .map(|peer_ban| peer_ban.peer_id)
+            .collect::<Vec<_>>(),
+        vec![permanently_banned_peer]
+    );
+}
+
 /// Verifies that the connected and supported peers are correct
 fn check_connected_supported_peers(
     peers_and_metadata: &Arc<PeersAndMetadata>,
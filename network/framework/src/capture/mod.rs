@@ -0,0 +1,442 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Opt-in capture of the network messages exchanged with peers.
+//!
+//! When enabled (see [`MessageCaptureConfig`]), each `Peer` actor forwards the
+//! [`NetworkMessage`]s it sends and receives (after stream reassembly) to a
+//! [`MessageCapture`]. Messages are written by a dedicated thread to a set of
+//! rotating capture files, so that capturing never blocks the network stack.
+//! The messages waiting to be written are bounded by size: if the writer falls
+//! behind, newly captured messages are dropped (before they are copied).
+//!
+//! Each capture file is a sequence of records, where every record is a
+//! little-endian `u32` length prefix followed by a BCS serialized
+//! [`CapturedMessage`]. Records are at most [`MAX_CAPTURE_RECORD_SIZE`] bytes
+//! (larger messages are never captured). Capture files can be read back using
+//! [`read_capture_file`] and [`read_capture_dir`], and replayed against an
+//! application using the `testutils` harness.
+
+use crate::{
+    constants::MAX_MESSAGE_SIZE,
+    counters,
+    counters::{INBOUND_LABEL, OUTBOUND_LABEL},
+    logging::NetworkSchema,
+    protocols::wire::messaging::v1::NetworkMessage,
+    ProtocolId,
+};
+use anyhow::{anyhow, Context};
+use aptos_config::{
+    config::MessageCaptureConfig,
+    network_id::{NetworkContext, NetworkId},
+};
+use aptos_logger::prelude::*;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::PeerId;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+};
+
+#[cfg(test)]
+mod test;
+
+// Capture state labels
+const CAPTURED_LABEL: &str = "captured";
+const DROPPED_LABEL: &str = "dropped";
+
+/// The file extension used for all capture files
+pub const CAPTURE_FILE_EXTENSION: &str = "capture";
+
+/// The maximum size of a single record in a capture file
+pub const MAX_CAPTURE_RECORD_SIZE: usize = MAX_MESSAGE_SIZE + CAPTURED_MESSAGE_OVERHEAD;
+
+/// An upper bound on the size of a captured message, excluding the message data
+const CAPTURED_MESSAGE_OVERHEAD: usize = 1024;
+
+/// The direction of a captured message (relative to the local node)
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MessageDirection {
+    /// The message was received from the remote peer
+    Inbound,
+    /// The message was sent to the remote peer
+    Outbound,
+}
+
+impl MessageDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageDirection::Inbound => INBOUND_LABEL,
+            MessageDirection::Outbound => OUTBOUND_LABEL,
+        }
+    }
+}
+
+/// A single network message captured on a connection
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CapturedMessage {
+    /// The (unix) time at which the message was captured
+    pub timestamp_usecs: u64,
+    /// The network on which the message was exchanged
+    pub network_id: NetworkId,
+    /// The remote peer that sent (or received) the message
+    pub peer_id: PeerId,
+    /// The direction of the message
+    pub direction: MessageDirection,
+    /// The network message (including the BCS payload)
+    pub message: NetworkMessage,
+}
+
+impl CapturedMessage {
+    /// Returns the protocol id of the message. Note: rpc responses and error
+    /// messages don't carry a protocol id (rpc responses can be matched to their
+    /// requests using the request id).
+    pub fn protocol_id(&self) -> Option<ProtocolId> {
+        match &self.message {
+            NetworkMessage::DirectSendMsg(message) => Some(message.protocol_id),
+            NetworkMessage::RpcRequest(request) => Some(request.protocol_id),
+            NetworkMessage::RpcResponse(_) | NetworkMessage::Error(_) => None,
+        }
+    }
+}
+
+/// Returns an upper bound on the size of the captured message (i.e., the
+/// number of bytes it holds while pending, and the size of its record).
+fn captured_message_size(message: &NetworkMessage) -> usize {
+    message.data_len() + CAPTURED_MESSAGE_OVERHEAD
+}
+
+/// A handle used to capture the messages exchanged with peers on a network
+pub struct MessageCapture {
+    network_context: NetworkContext,
+    time_service: TimeService,
+    max_pending_bytes: u64,
+    pending_bytes: Arc<AtomicU64>, // The size of the messages not yet written
+    captured_messages_tx: Sender<CapturedMessage>,
+}
+
+impl MessageCapture {
+    /// Creates a new message capture for the given network and spawns the
+    /// thread responsible for writing the capture files.
+    pub fn new(
+        network_context: NetworkContext,
+        time_service: TimeService,
+        capture_config: &MessageCaptureConfig,
+    ) -> io::Result<Self> {
+        let capture_file_writer = CaptureFileWriter::new(network_context, capture_config)?;
+        let pending_bytes = Arc::new(AtomicU64::new(0));
+        let (captured_messages_tx, captured_messages_rx) = mpsc::channel();
+        let writer_pending_bytes = pending_bytes.clone();
+        thread::Builder::new()
+            .name(format!("capture-{}", network_context.network_id().as_str()))
+            .spawn(move || capture_file_writer.run(captured_messages_rx, writer_pending_bytes))?;
+
+        info!(
+            NetworkSchema::new(&network_context),
+            "{} Capturing network messages to: {:?}", network_context, capture_config.capture_dir
+        );
+
+        Ok(Self {
+            network_context,
+            time_service,
+            max_pending_bytes: capture_config.max_pending_bytes,
+            pending_bytes,
+            captured_messages_tx,
+        })
+    }
+
+    /// Captures the given message exchanged with the specified peer. This
+    /// never blocks: if the message is too large, or too many bytes are
+    /// pending, the message is dropped (without being copied).
+    pub fn capture(&self, peer_id: PeerId, direction: MessageDirection, message: &NetworkMessage) {
+        let capture_state = if self.try_capture(peer_id, direction, message) {
+            CAPTURED_LABEL
+        } else {
+            DROPPED_LABEL
+        };
+        counters::increment_message_captures(
+            &self.network_context,
+            direction.as_str(),
+            capture_state,
+        );
+    }
+
+    /// Reserves space for the message in the pending bytes and sends it to
+    /// the writer. Returns false iff the message was dropped.
+    fn try_capture(
+        &self,
+        peer_id: PeerId,
+        direction: MessageDirection,
+        message: &NetworkMessage,
+    ) -> bool {
+        let message_size = captured_message_size(message);
+        if message_size > MAX_CAPTURE_RECORD_SIZE {
+            return false;
+        }
+        let message_size = message_size as u64;
+        let reserved = self
+            .pending_bytes
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |pending_bytes| {
+                let pending_bytes = pending_bytes.saturating_add(message_size);
+                (pending_bytes <= self.max_pending_bytes).then_some(pending_bytes)
+            })
+            .is_ok();
+        if !reserved {
+            return false;
+        }
+
+        let captured_message = CapturedMessage {
+            timestamp_usecs: self.time_service.now_unix_time().as_micros() as u64,
+            network_id: self.network_context.network_id(),
+            peer_id,
+            direction,
+            message: message.clone(),
+        };
+        if self.captured_messages_tx.send(captured_message).is_err() {
+            self.pending_bytes.fetch_sub(message_size, Ordering::AcqRel);
+            return false;
+        }
+        true
+    }
+}
+
+/// Writes captured messages to a set of rotating capture files
+struct CaptureFileWriter {
+    network_context: NetworkContext,
+    capture_dir: PathBuf,
+    max_file_size_bytes: u64,
+    max_num_files: usize,
+    current_file: Option<(BufWriter<File>, u64)>, // The file writer and bytes written
+    last_file_timestamp_usecs: u64,
+}
+
+impl CaptureFileWriter {
+    fn new(
+        network_context: NetworkContext,
+        capture_config: &MessageCaptureConfig,
+    ) -> io::Result<Self> {
+        fs::create_dir_all(&capture_config.capture_dir)?;
+        Ok(Self {
+            network_context,
+            capture_dir: capture_config.capture_dir.clone(),
+            max_file_size_bytes: capture_config.max_file_size_bytes,
+            max_num_files: capture_config.max_num_files.max(1),
+            current_file: None,
+            last_file_timestamp_usecs: 0,
+        })
+    }
+
+    /// Writes all captured messages until the capture handle is dropped.
+    /// The size of each message is released from the pending bytes once
+    /// the message has been handled.
+    fn run(
+        mut self,
+        captured_messages_rx: Receiver<CapturedMessage>,
+        pending_bytes: Arc<AtomicU64>,
+    ) {
+        while let Ok(captured_message) = captured_messages_rx.recv() {
+            // Write the message and any others that are already pending,
+            // before flushing the capture file.
+            let mut result = self.write_pending_message(captured_message, &pending_bytes);
+            while result.is_ok() {
+                match captured_messages_rx.try_recv() {
+                    Ok(captured_message) => {
+                        result = self.write_pending_message(captured_message, &pending_bytes)
+                    },
+                    Err(_) => break,
+                }
+            }
+            if let Err(error) = result.and_then(|()| self.flush()) {
+                warn!(
+                    NetworkSchema::new(&self.network_context),
+                    error = %error,
+                    "{} Failed to write captured network messages! Error: {:?}",
+                    self.network_context,
+                    error
+                );
+                self.current_file = None; // Start a new file on the next message
+            }
+        }
+    }
+
+    /// Writes the given pending message and releases its size from the pending bytes
+    fn write_pending_message(
+        &mut self,
+        captured_message: CapturedMessage,
+        pending_bytes: &AtomicU64,
+    ) -> io::Result<()> {
+        let message_size = captured_message_size(&captured_message.message) as u64;
+        let result = self.write_message(&captured_message);
+        drop(captured_message);
+        pending_bytes.fetch_sub(message_size, Ordering::AcqRel);
+        result
+    }
+
+    /// Writes the given message to the current capture file (rotating the
+    /// capture files if required).
+    fn write_message(&mut self, captured_message: &CapturedMessage) -> io::Result<()> {
+        let record = bcs::to_bytes(captured_message)
+            .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+
+        // Rotate the capture file if the current file is full
+        let should_rotate = match &self.current_file {
+            Some((_, bytes_written)) => *bytes_written >= self.max_file_size_bytes,
+            None => true,
+        };
+        if should_rotate {
+            self.rotate_capture_file(captured_message.timestamp_usecs)?;
+        }
+
+        // Write the length prefixed record
+        let (file, bytes_written) = self
+            .current_file
+            .as_mut()
+            .expect("The capture file should have been created!");
+        file.write_all(&(record.len() as u32).to_le_bytes())?;
+        file.write_all(&record)?;
+        *bytes_written += (record.len() + 4) as u64;
+
+        Ok(())
+    }
+
+    /// Flushes the current capture file (if any)
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some((file, _)) = self.current_file.as_mut() {
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Creates a new capture file and deletes the oldest files (so that at
+    /// most `max_num_files` capture files are retained for this network).
+    fn rotate_capture_file(&mut self, timestamp_usecs: u64) -> io::Result<()> {
+        self.flush()?;
+        self.current_file = None;
+
+        // Delete the oldest capture files
+        let file_prefix = self.capture_file_prefix();
+        let capture_files = list_capture_files(&self.capture_dir, Some(&file_prefix))?;
+        let num_files_to_delete = (capture_files.len() + 1).saturating_sub(self.max_num_files);
+        for capture_file in capture_files.iter().take(num_files_to_delete) {
+            fs::remove_file(capture_file)?;
+        }
+
+        // Create the new capture file. The file names contain (unique)
+        // zero-padded timestamps, so they sort chronologically.
+        let file_timestamp_usecs = timestamp_usecs.max(self.last_file_timestamp_usecs + 1);
+        self.last_file_timestamp_usecs = file_timestamp_usecs;
+        let file_path = self.capture_dir.join(format!(
+            "{}{:020}.{}",
+            file_prefix, file_timestamp_usecs, CAPTURE_FILE_EXTENSION
+        ));
+        let file = OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(file_path)?;
+        self.current_file = Some((BufWriter::new(file), 0));
+
+        Ok(())
+    }
+
+    /// Returns the file name prefix for all capture files of this network
+    fn capture_file_prefix(&self) -> String {
+        format!("{}-", self.network_context.network_id().as_str())
+    }
+}
+
+/// Returns the capture files in the given directory (optionally filtered by
+/// file name prefix), sorted from oldest to newest.
+fn list_capture_files(capture_dir: &Path, file_prefix: Option<&str>) -> io::Result<Vec<PathBuf>> {
+    let mut capture_files = vec![];
+    for entry in fs::read_dir(capture_dir)? {
+        let path = entry?.path();
+        let file_name = path
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .unwrap_or_default();
+        let has_extension = path.extension().and_then(|extension| extension.to_str())
+            == Some(CAPTURE_FILE_EXTENSION);
+        let has_prefix = file_prefix.map_or(true, |file_prefix| file_name.starts_with(file_prefix));
+        if path.is_file() && has_extension && has_prefix {
+            capture_files.push(path);
+        }
+    }
+    capture_files.sort_by(|first, second| first.file_name().cmp(&second.file_name()));
+    Ok(capture_files)
+}
+
+/// Reads all captured messages from the given capture file. A truncated final
+/// record (e.g., if the node crashed while writing it) is ignored, but records
+/// larger than [`MAX_CAPTURE_RECORD_SIZE`] are rejected.
+pub fn read_capture_file(file_path: &Path) -> anyhow::Result<Vec<CapturedMessage>> {
+    let file = File::open(file_path)
+        .with_context(|| format!("Failed to open capture file: {:?}", file_path))?;
+    let mut reader = BufReader::new(file);
+
+    let mut captured_messages = vec![];
+    loop {
+        // Read the length prefix
+        let mut length_bytes = [0u8; 4];
+        match reader.read_exact(&mut length_bytes) {
+            Ok(()) => (),
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => break,
+            Err(error) => return Err(error.into()),
+        }
+
+        // Read and deserialize the record
+        let record_size = u32::from_le_bytes(length_bytes) as usize;
+        if record_size > MAX_CAPTURE_RECORD_SIZE {
+            return Err(anyhow!(
+                "Invalid record size in {:?}: {} bytes (the maximum is {} bytes)",
+                file_path,
+                record_size,
+                MAX_CAPTURE_RECORD_SIZE
+            ));
+        }
+        let mut record = vec![0u8; record_size];
+        match reader.read_exact(&mut record) {
+            Ok(()) => (),
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => break,
+            Err(error) => return Err(error.into()),
+        }
+        let captured_message = bcs::from_bytes(&record).map_err(|error| {
+            anyhow!(
+                "Failed to deserialize a captured message in {:?}: {}",
+                file_path,
+                error
+            )
+        })?;
+        captured_messages.push(captured_message);
+    }
+
+    Ok(captured_messages)
+}
+
+/// Reads all captured messages from the capture files in the given directory
+/// (in the order they were captured). If a network is specified, only the
+/// capture files for that network are read.
+pub fn read_capture_dir(
+    capture_dir: &Path,
+    network_id: Option<NetworkId>,
+) -> anyhow::Result<Vec<CapturedMessage>> {
+    let file_prefix = network_id.map(|network_id| format!("{}-", network_id.as_str()));
+    let capture_files = list_capture_files(capture_dir, file_prefix.as_deref())
+        .with_context(|| format!("Failed to list capture files in: {:?}", capture_dir))?;
+
+    let mut captured_messages = vec![];
+    for capture_file in capture_files {
+        captured_messages.extend(read_capture_file(&capture_file)?);
+    }
+
+    // Capture files for different networks are interleaved, so sort by time
+    captured_messages.sort_by_key(|captured_message| captured_message.timestamp_usecs);
+    Ok(captured_messages)
+}
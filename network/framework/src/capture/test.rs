@@ -0,0 +1,307 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    capture::{
+        captured_message_size, list_capture_files, read_capture_dir, read_capture_file,
+        CaptureFileWriter, CapturedMessage, MessageCapture, MessageDirection,
+    },
+    protocols::wire::messaging::v1::{DirectSendMsg, NetworkMessage, RpcRequest, RpcResponse},
+    ProtocolId,
+};
+use aptos_config::{
+    config::MessageCaptureConfig,
+    network_id::{NetworkContext, NetworkId},
+};
+use aptos_temppath::TempPath;
+use aptos_time_service::TimeService;
+use aptos_types::PeerId;
+use std::{fs::OpenOptions, io::Write, sync::atomic::Ordering, thread, time::Duration};
+
+#[test]
+fn test_capture_round_trip() {
+    // Create a capture writer with a single (large) capture file
+    let capture_dir = create_capture_dir();
+    let capture_config = create_capture_config(&capture_dir, 1024 * 1024, 1);
+    let mut capture_file_writer =
+        CaptureFileWriter::new(NetworkContext::mock(), &capture_config).unwrap();
+
+    // Write several different messages
+    let captured_messages = vec![
+        create_captured_message(
+            0,
+            MessageDirection::Inbound,
+            create_direct_send(vec![0; 10]),
+        ),
+        create_captured_message(
+            1,
+            MessageDirection::Outbound,
+            NetworkMessage::RpcRequest(RpcRequest {
+                protocol_id: ProtocolId::StorageServiceRpc,
+                request_id: 10,
+                priority: 0,
+                raw_request: vec![1, 2, 3],
+            }),
+        ),
+        create_captured_message(
+            2,
+            MessageDirection::Inbound,
+            NetworkMessage::RpcResponse(RpcResponse {
+                request_id: 10,
+                priority: 0,
+                raw_response: vec![4, 5, 6],
+            }),
+        ),
+    ];
+    for captured_message in &captured_messages {
+        capture_file_writer.write_message(captured_message).unwrap();
+    }
+    capture_file_writer.flush().unwrap();
+
+    // Verify the messages can be read back (in order)
+    let capture_files = list_capture_files(capture_dir.path(), None).unwrap();
+    assert_eq!(capture_files.len(), 1);
+    assert_eq!(
+        read_capture_file(&capture_files[0]).unwrap(),
+        captured_messages
+    );
+    assert_eq!(
+        read_capture_dir(capture_dir.path(), Some(NetworkId::Validator)).unwrap(),
+        captured_messages
+    );
+    assert!(
+        read_capture_dir(capture_dir.path(), Some(NetworkId::Public))
+            .unwrap()
+            .is_empty()
+    );
+
+    // Verify the protocol ids of the captured messages
+    let protocol_ids: Vec<_> = captured_messages
+        .iter()
+        .map(|captured_message| captured_message.protocol_id())
+        .collect();
+    assert_eq!(protocol_ids, vec![
+        Some(ProtocolId::ConsensusDirectSendBcs),
+        Some(ProtocolId::StorageServiceRpc),
+        None
+    ]);
+}
+
+#[test]
+fn test_capture_file_rotation() {
+    // Create a capture writer that rotates files after every message
+    let capture_dir = create_capture_dir();
+    let max_num_files = 3;
+    let capture_config = create_capture_config(&capture_dir, 1, max_num_files);
+    let mut capture_file_writer =
+        CaptureFileWriter::new(NetworkContext::mock(), &capture_config).unwrap();
+
+    // Write several messages (all with the same timestamp)
+    let num_messages = 10;
+    let captured_messages: Vec<_> = (0..num_messages)
+        .map(|index| {
+            create_captured_message(
+                0,
+                MessageDirection::Inbound,
+                create_direct_send(vec![index as u8]),
+            )
+        })
+        .collect();
+    for captured_message in &captured_messages {
+        capture_file_writer.write_message(captured_message).unwrap();
+    }
+    capture_file_writer.flush().unwrap();
+
+    // Verify that only the newest files (and messages) are retained
+    let capture_files = list_capture_files(capture_dir.path(), None).unwrap();
+    assert_eq!(capture_files.len(), max_num_files);
+    assert_eq!(
+        read_capture_dir(capture_dir.path(), None).unwrap(),
+        captured_messages[num_messages - max_num_files..].to_vec()
+    );
+}
+
+#[test]
+fn test_truncated_capture_file() {
+    // Write a single message to a capture file
+    let capture_dir = create_capture_dir();
+    let capture_config = create_capture_config(&capture_dir, 1024 * 1024, 1);
+    let mut capture_file_writer =
+        CaptureFileWriter::new(NetworkContext::mock(), &capture_config).unwrap();
+    let captured_message = create_captured_message(
+        0,
+        MessageDirection::Outbound,
+        create_direct_send(vec![7; 20]),
+    );
+    capture_file_writer
+        .write_message(&captured_message)
+        .unwrap();
+    capture_file_writer.flush().unwrap();
+
+    // Append a truncated record to the capture file
+    let capture_files = list_capture_files(capture_dir.path(), None).unwrap();
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(&capture_files[0])
+        .unwrap();
+    file.write_all(&100u32.to_le_bytes()).unwrap();
+    file.write_all(&[1, 2, 3]).unwrap();
+
+    // Verify the truncated record is ignored
+    assert_eq!(read_capture_file(&capture_files[0]).unwrap(), vec![
+        captured_message
+    ]);
+}
+
+#[test]
+fn test_oversized_capture_record() {
+    // Write a single message to a capture file
+    let capture_dir = create_capture_dir();
+    let capture_config = create_capture_config(&capture_dir, 1024 * 1024, 1);
+    let mut capture_file_writer =
+        CaptureFileWriter::new(NetworkContext::mock(), &capture_config).unwrap();
+    let captured_message =
+        create_captured_message(0, MessageDirection::Inbound, create_direct_send(vec![1; 5]));
+    capture_file_writer
+        .write_message(&captured_message)
+        .unwrap();
+    capture_file_writer.flush().unwrap();
+
+    // Append a record with a huge length prefix
+    let capture_files = list_capture_files(capture_dir.path(), None).unwrap();
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(&capture_files[0])
+        .unwrap();
+    file.write_all(&u32::MAX.to_le_bytes()).unwrap();
+    file.write_all(&[1, 2, 3]).unwrap();
+
+    // Verify the record is rejected (instead of allocating its length)
+    let error = read_capture_file(&capture_files[0]).unwrap_err();
+    assert!(error.to_string().contains("Invalid record size"));
+}
+
+#[test]
+fn test_message_capture_pending_bytes() {
+    // Create a message capture that only allows a few small messages to be pending
+    let capture_dir = create_capture_dir();
+    let small_message = create_direct_send(vec![0; 10]);
+    let large_message = create_direct_send(vec![0; 10_000]);
+    let capture_config = MessageCaptureConfig {
+        max_pending_bytes: 2 * captured_message_size(&small_message) as u64,
+        ..create_capture_config(&capture_dir, 1024 * 1024, 2)
+    };
+    let message_capture =
+        MessageCapture::new(NetworkContext::mock(), TimeService::mock(), &capture_config).unwrap();
+
+    // Capture a message that exceeds the pending bytes and verify it is dropped
+    let peer_id = PeerId::random();
+    assert!(!message_capture.try_capture(peer_id, MessageDirection::Inbound, &large_message));
+    assert_eq!(message_capture.pending_bytes.load(Ordering::Acquire), 0);
+
+    // Capture a small message and verify it is captured
+    assert!(message_capture.try_capture(peer_id, MessageDirection::Outbound, &small_message));
+
+    // Wait until the message has been written and verify the pending bytes are released
+    let mut captured_messages = vec![];
+    for _ in 0..100 {
+        captured_messages = read_capture_dir(capture_dir.path(), None).unwrap();
+        if !captured_messages.is_empty()
+            && message_capture.pending_bytes.load(Ordering::Acquire) == 0
+        {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert_eq!(captured_messages.len(), 1);
+    assert_eq!(captured_messages[0].message, small_message);
+    assert_eq!(message_capture.pending_bytes.load(Ordering::Acquire), 0);
+}
+
+#[test]
+fn test_message_capture() {
+    // Create a message capture
+    let capture_dir = create_capture_dir();
+    let capture_config = create_capture_config(&capture_dir, 1024 * 1024, 2);
+    let message_capture =
+        MessageCapture::new(NetworkContext::mock(), TimeService::mock(), &capture_config).unwrap();
+
+    // Capture several messages
+    let peer_id = PeerId::random();
+    let num_messages = 5;
+    for index in 0..num_messages {
+        message_capture.capture(
+            peer_id,
+            MessageDirection::Inbound,
+            &create_direct_send(vec![index as u8]),
+        );
+    }
+    drop(message_capture);
+
+    // Wait until all messages have been written by the capture thread
+    let mut captured_messages = vec![];
+    for _ in 0..100 {
+        captured_messages = read_capture_dir(capture_dir.path(), None).unwrap();
+        if captured_messages.len() == num_messages {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    // Verify the captured messages
+    assert_eq!(captured_messages.len(), num_messages);
+    for (index, captured_message) in captured_messages.iter().enumerate() {
+        assert_eq!(captured_message.peer_id, peer_id);
+        assert_eq!(captured_message.network_id, NetworkId::Validator);
+        assert_eq!(captured_message.direction, MessageDirection::Inbound);
+        assert_eq!(
+            captured_message.message,
+            create_direct_send(vec![index as u8])
+        );
+    }
+}
+
+/// Creates a temporary capture directory
+fn create_capture_dir() -> TempPath {
+    let capture_dir = TempPath::new();
+    capture_dir.create_as_dir().unwrap();
+    capture_dir
+}
+
+/// Creates a capture config using the given directory and limits
+fn create_capture_config(
+    capture_dir: &TempPath,
+    max_file_size_bytes: u64,
+    max_num_files: usize,
+) -> MessageCaptureConfig {
+    MessageCaptureConfig {
+        capture_dir: capture_dir.path().to_path_buf(),
+        max_file_size_bytes,
+        max_num_files,
+        ..Default::default()
+    }
+}
+
+/// Creates a captured message with the given timestamp, direction and message
+fn create_captured_message(
+    timestamp_usecs: u64,
+    direction: MessageDirection,
+    message: NetworkMessage,
+) -> CapturedMessage {
+    CapturedMessage {
+        timestamp_usecs,
+        network_id: NetworkId::Validator,
+        peer_id: PeerId::random(),
+        direction,
+        message,
+    }
+}
+
+/// Creates a direct send message with the given payload
+fn create_direct_send(raw_msg: Vec<u8>) -> NetworkMessage {
+    NetworkMessage::DirectSendMsg(DirectSendMsg {
+        protocol_id: ProtocolId::ConsensusDirectSendBcs,
+        priority: 0,
+        raw_msg,
+    })
+}
@@ -36,7 +36,7 @@ use crate::{
 };
 use aptos_config::{
     config::{Peer, PeerRole, PeerSet},
-    network_id::NetworkContext,
+    network_id::{NetworkContext, PeerNetworkId},
 };
 use aptos_crypto::x25519;
 use aptos_infallible::RwLock;
@@ -140,6 +140,7 @@ pub enum DiscoverySource {
     OnChainValidatorSet,
     File,
     Rest,
+    Seeds,
     Config,
 }
 
@@ -156,6 +157,7 @@ impl fmt::Display for DiscoverySource {
             DiscoverySource::File => "File",
             DiscoverySource::Config => "Config",
             DiscoverySource::Rest => "Rest",
+            DiscoverySource::Seeds => "Seeds",
         })
     }
 }
@@ -528,6 +530,48 @@ where
         }
     }
 
+    /// Disconnect from all peers that should be disconnected due to
+    /// misbehavior (i.e., peers that are banned, or that have accumulated
+    /// too high a penalty in the peer reputation service).
+    async fn close_misbehaving_connections(&mut self) {
+        // Identify misbehaving peers
+        let peer_reputation = self.peers_and_metadata.get_peer_reputation();
+        let network_id = self.network_context.network_id();
+        let misbehaving_peers: Vec<_> = self
+            .connected
+            .keys()
+            .filter(|peer_id| {
+                peer_reputation.should_disconnect(&PeerNetworkId::new(network_id, **peer_id))
+            })
+            .cloned()
+            .collect();
+
+        // Close existing connections to misbehaving peers
+        for misbehaving_peer in misbehaving_peers {
+            info!(
+                NetworkSchema::new(&self.network_context).remote_peer(&misbehaving_peer),
+                "{} Closing connection to misbehaving peer {}",
+                self.network_context,
+                misbehaving_peer.short_str()
+            );
+
+            if let Err(disconnect_error) = self
+                .connection_reqs_tx
+                .disconnect_peer(misbehaving_peer)
+                .await
+            {
+                info!(
+                    NetworkSchema::new(&self.network_context).remote_peer(&misbehaving_peer),
+                    error = %disconnect_error,
+                    "{} Failed to close connection to misbehaving peer {}, error: {}",
+                    self.network_context,
+                    misbehaving_peer.short_str(),
+                    disconnect_error
+                );
+            }
+        }
+    }
+
     /// Cancel all pending dials to peers that are no longer eligible.
     ///
     /// For instance, a validator might leave the validator set after a
@@ -573,6 +617,7 @@ where
         let role = self.network_context.role();
         let roles_to_dial = network_id.upstream_roles(&role);
         let discovered_peers = self.discovered_peers.read().peer_set.clone();
+        let peer_reputation = self.peers_and_metadata.get_peer_reputation();
         let eligible_peers: Vec<_> = discovered_peers
             .into_iter()
             .filter(|(peer_id, peer)| {
@@ -580,6 +625,8 @@ where
                     && !self.connected.contains_key(peer_id) // The node is not already connected
                     && !self.dial_queue.contains_key(peer_id) // There is no pending dial to this node
                     && roles_to_dial.contains(&peer.role) // We can dial this role
+                    && !peer_reputation.should_disconnect(&PeerNetworkId::new(network_id, *peer_id))
+                // The node is not misbehaving
             })
             .collect();
 
@@ -825,6 +872,8 @@ where
         self.cancel_stale_dials().await;
         // Disconnect from connected peers that are no longer eligible.
         self.close_stale_connections().await;
+        // Disconnect from connected peers that are misbehaving.
+        self.close_misbehaving_connections().await;
         // Dial peers which are eligible but are neither connected nor queued for dialing in the
         // future.
         self.dial_eligible_peers(pending_dials).await;
@@ -2,8 +2,8 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::protocols::wire::handshake::v1::ProtocolId;
-use aptos_config::network_id::NetworkContext;
+use crate::protocols::wire::handshake::v1::{ProtocolClass, ProtocolId};
+use aptos_config::network_id::{NetworkContext, NetworkId};
 use aptos_metrics_core::{
     exponential_buckets, register_histogram_vec, register_int_counter_vec, register_int_gauge,
     register_int_gauge_vec, Histogram, HistogramTimer, HistogramVec, IntCounter, IntCounterVec,
@@ -424,6 +424,28 @@ pub static PENDING_WIRE_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Gauge of messages pending in queue to be sent out on the wire (by protocol class)
+pub static PENDING_WIRE_MESSAGES_BY_CLASS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_network_pending_wire_messages_by_class",
+        "Number of pending wire messages for each protocol class",
+        &["role_type", "network_id", "peer_id", "protocol_class"]
+    )
+    .unwrap()
+});
+
+pub fn pending_wire_messages_by_class(
+    network_context: &NetworkContext,
+    protocol_class: ProtocolClass,
+) -> IntGauge {
+    PENDING_WIRE_MESSAGES_BY_CLASS.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        network_context.peer_id().short_str().as_str(),
+        protocol_class.as_str(),
+    ])
+}
+
 /// Counter of messages pending in queue to be sent out on the multiplex channel
 pub static PENDING_MULTIPLEX_MESSAGE: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
@@ -638,3 +660,46 @@ pub static OP_MEASURE: Lazy<HistogramVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// Counter of captured network messages (by capture state)
+pub static NETWORK_MESSAGE_CAPTURES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_message_captures",
+        "Number of captured network messages",
+        &["network_id", "direction", "state"]
+    )
+    .unwrap()
+});
+
+/// Increments the message capture counter for the given direction and state
+pub fn increment_message_captures(network_context: &NetworkContext, direction: &str, state: &str) {
+    NETWORK_MESSAGE_CAPTURES
+        .with_label_values(&[network_context.network_id().as_str(), direction, state])
+        .inc();
+}
+
+pub static PEER_MISBEHAVIOR_REPORTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_peer_misbehavior_reports",
+        "Number of peer misbehavior reports by network and severity",
+        &["network_id", "severity"]
+    )
+    .unwrap()
+});
+
+pub fn peer_misbehavior_reports(network_id: NetworkId, severity: &str) -> IntCounter {
+    PEER_MISBEHAVIOR_REPORTS.with_label_values(&[network_id.as_str(), severity])
+}
+
+pub static NUM_BANNED_PEERS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_network_num_banned_peers",
+        "Number of peers currently banned by the peer reputation service"
+    )
+    .unwrap()
+});
+
+/// Updates the number of banned peers
+pub fn set_num_banned_peers(num_banned_peers: usize) {
+    NUM_BANNED_PEERS.set(num_banned_peers as i64);
+}
@@ -16,6 +16,7 @@
 //! [`PeerManager`]: crate::peer_manager::PeerManager
 
 use crate::{
+    capture::{MessageCapture, MessageDirection},
     counters::{
         self, network_application_inbound_traffic, network_application_outbound_traffic,
         FAILED_LABEL, RECEIVED_LABEL, SENT_LABEL,
@@ -26,16 +27,22 @@ use crate::{
         direct_send::Message,
         rpc::{error::RpcError, InboundRpcRequest, InboundRpcs, OutboundRpcRequest, OutboundRpcs},
         stream::{InboundStreamBuffer, OutboundStream, StreamMessage},
-        wire::messaging::v1::{
-            DirectSendMsg, ErrorCode, MultiplexMessage, MultiplexMessageSink,
-            MultiplexMessageStream, NetworkMessage, Priority, ReadError, WriteError,
+        wire::{
+            handshake::v1::ProtocolClass,
+            messaging::v1::{
+                DirectSendMsg, ErrorCode, MultiplexMessage, MultiplexMessageSink,
+                MultiplexMessageStream, NetworkMessage, Priority, ReadError, WriteError,
+            },
         },
     },
-    transport::{self, Connection, ConnectionMetadata},
+    transport::{self, Connection, ConnectionMetadata, ProtocolStreams},
     ProtocolId,
 };
-use aptos_channels::{aptos_channel, message_queues::QueueStyle};
-use aptos_config::network_id::NetworkContext;
+use aptos_channels::{
+    aptos_channel,
+    message_queues::{KeyQueueConfig, QueueStyle},
+};
+use aptos_config::{config::OutboundMessageWeights, network_id::NetworkContext};
 use aptos_logger::prelude::*;
 use aptos_short_hex_str::AsShortHexStr;
 use aptos_time_service::{TimeService, TimeServiceTrait};
@@ -45,12 +52,12 @@ use futures::{
     self,
     channel::oneshot,
     io::{AsyncRead, AsyncWrite},
-    stream::StreamExt,
+    stream::{self, BoxStream, Stream, StreamExt},
     SinkExt,
 };
 use futures_util::stream::select;
 use serde::Serialize;
-use std::{fmt, panic, time::Duration};
+use std::{collections::HashMap, fmt, num::NonZeroUsize, panic, sync::Arc, time::Duration};
 use tokio::{runtime::Handle, time::timeout};
 use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
@@ -62,6 +69,46 @@ mod test;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod fuzzing;
 
+/// The maximum number of messages buffered between the multiplex and writer
+/// tasks. This is kept small so that pending messages remain in the (weighted)
+/// per-class write queue, where they can still be prioritized.
+const MAX_PENDING_MULTIPLEX_MESSAGES: usize = 2;
+
+/// The maximum number of outbound messages pending in the write queue for each
+/// protocol class (the oldest messages are dropped once full). The classes
+/// together hold about 1K messages, which bounds the memory held for a slow
+/// peer, while leaving each class enough room to absorb its own bursts.
+const MAX_PENDING_WIRE_MESSAGES_PER_CLASS: usize = 256;
+
+/// The number of bytes a protocol class with a weight of 1 may send on each
+/// of its turns in the write queue. Larger messages are sent once the class
+/// has accumulated enough credits (over several turns).
+const OUTBOUND_CREDITS_PER_WEIGHT: usize = 64 * 1024; // 64 KiB
+
+/// The outbound write queues of a [`Peer`]. Messages are written on the stream
+/// of their protocol class if the connection has one (see
+/// [`ProtocolStreams`]), and on the connection socket otherwise.
+#[derive(Clone, Debug)]
+pub struct WriteRequestSender {
+    socket_tx: aptos_channel::Sender<ProtocolClass, NetworkMessage>,
+    protocol_stream_txs:
+        HashMap<ProtocolClass, aptos_channel::Sender<ProtocolClass, NetworkMessage>>,
+}
+
+impl WriteRequestSender {
+    /// Queues the message for writing on the stream of the given protocol class
+    pub fn push(
+        &self,
+        protocol_class: ProtocolClass,
+        message: NetworkMessage,
+    ) -> anyhow::Result<()> {
+        self.protocol_stream_txs
+            .get(&protocol_class)
+            .unwrap_or(&self.socket_tx)
+            .push(protocol_class, message)
+    }
+}
+
 /// Requests [`Peer`] receives from the [`PeerManager`](crate::peer_manager::PeerManager).
 #[derive(Debug)]
 pub enum PeerRequest {
@@ -120,6 +167,8 @@ pub struct Peer<TSocket> {
     connection_metadata: ConnectionMetadata,
     /// Underlying connection.
     connection: Option<TSocket>,
+    /// The additional streams of the connection, one per protocol class (if any).
+    protocol_streams: ProtocolStreams,
     /// Channel to notify PeerManager that we've disconnected.
     connection_notifs_tx: aptos_channels::Sender<TransportNotification<TSocket>>,
     /// Channel to receive requests from PeerManager to send messages and rpcs.
@@ -136,8 +185,13 @@ pub struct Peer<TSocket> {
     max_frame_size: usize,
     /// The maximum size of an inbound or outbound request message
     max_message_size: usize,
-    /// Inbound stream buffer
-    inbound_stream: InboundStreamBuffer,
+    /// Inbound stream buffers, one for the socket (`None`) and one for each
+    /// protocol stream, as fragmented messages may be interleaved across them.
+    inbound_streams: HashMap<Option<ProtocolClass>, InboundStreamBuffer>,
+    /// The weights used to prioritize outbound messages by protocol class
+    outbound_message_weights: OutboundMessageWeights,
+    /// The capture for inbound and outbound messages (if enabled)
+    message_capture: Option<Arc<MessageCapture>>,
 }
 
 impl<TSocket> Peer<TSocket>
@@ -158,19 +212,31 @@ where
         max_concurrent_outbound_rpcs: u32,
         max_frame_size: usize,
         max_message_size: usize,
+        outbound_message_weights: OutboundMessageWeights,
+        message_capture: Option<Arc<MessageCapture>>,
     ) -> Self {
         let Connection {
             metadata: connection_metadata,
             socket,
+            protocol_streams,
         } = connection;
         let remote_peer_id = connection_metadata.remote_peer_id;
         let max_fragments = max_message_size / max_frame_size;
+        let inbound_streams = std::iter::once(None)
+            .chain(
+                protocol_streams
+                    .iter()
+                    .map(|(protocol_class, _)| Some(*protocol_class)),
+            )
+            .map(|protocol_class| (protocol_class, InboundStreamBuffer::new(max_fragments)))
+            .collect();
         Self {
             network_context,
             executor,
             time_service: time_service.clone(),
             connection_metadata,
             connection: Some(socket),
+            protocol_streams,
             connection_notifs_tx,
             peer_reqs_rx,
             peer_notifs_tx,
@@ -190,7 +256,9 @@ where
             state: State::Connected,
             max_frame_size,
             max_message_size,
-            inbound_stream: InboundStreamBuffer::new(max_fragments),
+            inbound_streams,
+            outbound_message_weights,
+            message_capture,
         }
     }
 
@@ -208,27 +276,43 @@ where
             remote_peer_id.short_str()
         );
 
-        // Split the connection into a ReadHalf and a WriteHalf.
+        // Split the connection (and each protocol stream) into a ReadHalf and a
+        // WriteHalf. Inbound messages are read from all of them, tagged with the
+        // protocol class of their stream (`None` for the socket). Each reader
+        // ends with a `None` message, so that we notice when any of them closes.
         let (read_socket, write_socket) =
             tokio::io::split(self.connection.take().unwrap().compat());
-
-        let mut reader =
-            MultiplexMessageStream::new(read_socket.compat(), self.max_frame_size).fuse();
+        let mut readers = vec![Self::tagged_reader(
+            None,
+            MultiplexMessageStream::new(read_socket.compat(), self.max_frame_size),
+        )];
         let writer = MultiplexMessageSink::new(write_socket.compat_write(), self.max_frame_size);
 
-        // Start writer "process" as a separate task. We receive two handles to
-        // communicate with the task:
-        //   1. `write_reqs_tx`: Queue of pending NetworkMessages to write.
-        //   2. `close_tx`: Handle to close the task and underlying connection.
-        let (mut write_reqs_tx, writer_close_tx) = Self::start_writer_task(
-            &self.executor,
-            self.time_service.clone(),
-            self.connection_metadata.clone(),
-            self.network_context,
-            writer,
-            self.max_frame_size,
-            self.max_message_size,
-        );
+        // Start writer "process" as a separate task (for the socket and each
+        // protocol stream). We receive two handles to communicate with the tasks:
+        //   1. `write_reqs_tx`: Queues of pending NetworkMessages to write.
+        //   2. `close_tx`: Handles to close the tasks and underlying connection.
+        let (socket_tx, socket_close_tx) = self.start_writer(writer);
+        let mut writer_close_txs = vec![socket_close_tx];
+        let mut protocol_stream_txs = HashMap::new();
+        for (protocol_class, protocol_stream) in std::mem::take(&mut self.protocol_streams) {
+            let (read_stream, write_stream) = tokio::io::split(protocol_stream.compat());
+            readers.push(Self::tagged_reader(
+                Some(protocol_class),
+                MultiplexMessageStream::new(read_stream.compat(), self.max_frame_size),
+            ));
+            let (stream_tx, stream_close_tx) = self.start_writer(MultiplexMessageSink::new(
+                write_stream.compat_write(),
+                self.max_frame_size,
+            ));
+            protocol_stream_txs.insert(protocol_class, stream_tx);
+            writer_close_txs.push(stream_close_tx);
+        }
+        let mut write_reqs_tx = WriteRequestSender {
+            socket_tx,
+            protocol_stream_txs,
+        };
+        let mut reader = stream::select_all(readers);
 
         // Start main Peer event loop.
         let reason = loop {
@@ -250,8 +334,8 @@ where
                 // the wire from the remote peer.
                 maybe_message = reader.next() => {
                     match maybe_message {
-                        Some(message) =>  {
-                            if let Err(err) = self.handle_inbound_message(message, &mut write_reqs_tx) {
+                        Some((protocol_class, Some(message))) =>  {
+                            if let Err(err) = self.handle_inbound_message(protocol_class, message, &mut write_reqs_tx) {
                                 warn!(
                                     NetworkSchema::new(&self.network_context)
                                         .connection_metadata(&self.connection_metadata),
@@ -263,8 +347,9 @@ where
                                 );
                             }
                         },
-                        // The socket was gracefully closed by the remote peer.
-                        None => self.shutdown(DisconnectReason::ConnectionLost),
+                        // The socket (or a protocol stream) was gracefully closed
+                        // by the remote peer.
+                        Some((_, None)) | None => self.shutdown(DisconnectReason::ConnectionLost),
                     }
                 },
                 // Drive the queue of pending inbound rpcs. When one is fulfilled
@@ -312,10 +397,49 @@ where
 
         // Finish shutting down the connection. Close the writer task and notify
         // PeerManager that this connection has shutdown.
-        self.do_shutdown(write_reqs_tx, writer_close_tx, reason)
+        self.do_shutdown(write_reqs_tx, writer_close_txs, reason)
             .await;
     }
 
+    /// Tags the messages read from a socket or protocol stream with its protocol
+    /// class, and appends a final `None` message once the reader ends.
+    fn tagged_reader(
+        protocol_class: Option<ProtocolClass>,
+        reader: impl Stream<Item = Result<MultiplexMessage, ReadError>> + Send + 'static,
+    ) -> BoxStream<
+        'static,
+        (
+            Option<ProtocolClass>,
+            Option<Result<MultiplexMessage, ReadError>>,
+        ),
+    > {
+        reader
+            .map(move |message| (protocol_class, Some(message)))
+            .chain(stream::once(async move { (protocol_class, None) }))
+            .boxed()
+    }
+
+    /// Starts a writer task (see `start_writer_task`) for the given sink
+    fn start_writer(
+        &self,
+        writer: MultiplexMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
+    ) -> (
+        aptos_channel::Sender<ProtocolClass, NetworkMessage>,
+        oneshot::Sender<()>,
+    ) {
+        Self::start_writer_task(
+            &self.executor,
+            self.time_service.clone(),
+            self.connection_metadata.clone(),
+            self.network_context,
+            writer,
+            self.max_frame_size,
+            self.max_message_size,
+            self.outbound_message_weights,
+            self.message_capture.clone(),
+        )
+    }
+
     // Start a new task on the given executor which is responsible for writing outbound messages on
     // the wire. The function returns two channels which can be used to send instructions to the
     // task:
@@ -323,6 +447,14 @@ where
     // 2. The second channel is used to instruct the task to close the connection and terminate.
     // If outbound messages are queued when the task receives a close instruction, it discards
     // them and immediately closes the connection.
+    //
+    // Outbound messages are queued per protocol class, and are dequeued using weighted
+    // (deficit) round-robin over the message bytes (according to the given weights). This
+    // ensures that bulk transfers (e.g., state sync) cannot delay latency sensitive messages
+    // (e.g., consensus). Messages that are too large for a single frame are charged in full
+    // when they are dequeued, and their fragments are then written interleaved with the other
+    // messages (one frame at a time), so they cannot hold up the connection either.
+    #[allow(clippy::too_many_arguments)]
     fn start_writer_task(
         executor: &Handle,
         time_service: TimeService,
@@ -331,20 +463,46 @@ where
         mut writer: MultiplexMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
         max_frame_size: usize,
         max_message_size: usize,
+        outbound_message_weights: OutboundMessageWeights,
+        message_capture: Option<Arc<MessageCapture>>,
     ) -> (
-        aptos_channel::Sender<(), NetworkMessage>,
+        aptos_channel::Sender<ProtocolClass, NetworkMessage>,
         oneshot::Sender<()>,
     ) {
         let remote_peer_id = connection_metadata.remote_peer_id;
-        let (write_reqs_tx, mut write_reqs_rx): (aptos_channel::Sender<(), NetworkMessage>, _) =
-            aptos_channel::new(
-                QueueStyle::KLAST,
-                1024,
-                Some(&counters::PENDING_WIRE_MESSAGES),
-            );
+        let key_configs = ProtocolClass::all()
+            .iter()
+            .map(|protocol_class| {
+                // The weights are in bytes, as messages are charged by their size
+                let weight = (protocol_class.weight(&outbound_message_weights) as usize)
+                    .max(1)
+                    .saturating_mul(OUTBOUND_CREDITS_PER_WEIGHT);
+                let key_config = KeyQueueConfig {
+                    weight: NonZeroUsize::new(weight).unwrap_or(NonZeroUsize::MIN),
+                    pending_messages: Some(counters::pending_wire_messages_by_class(
+                        &network_context,
+                        *protocol_class,
+                    )),
+                };
+                (*protocol_class, key_config)
+            })
+            .collect::<HashMap<_, _>>();
+        let (write_reqs_tx, mut write_reqs_rx): (
+            aptos_channel::Sender<ProtocolClass, NetworkMessage>,
+            _,
+        ) = aptos_channel::new_with_key_configs(
+            QueueStyle::KLAST,
+            MAX_PENDING_WIRE_MESSAGES_PER_CLASS,
+            key_configs,
+            Some(NetworkMessage::data_len),
+            Some(&counters::PENDING_WIRE_MESSAGES),
+        );
         let (close_tx, mut close_rx) = oneshot::channel();
 
-        let (mut msg_tx, msg_rx) = aptos_channels::new(1024, &counters::PENDING_MULTIPLEX_MESSAGE);
+        let (mut msg_tx, msg_rx) = aptos_channels::new(
+            MAX_PENDING_MULTIPLEX_MESSAGES,
+            &counters::PENDING_MULTIPLEX_MESSAGE,
+        );
         let (stream_msg_tx, stream_msg_rx) =
             aptos_channels::new(1024, &counters::PENDING_MULTIPLEX_STREAM);
 
@@ -419,6 +577,10 @@ where
             let mut outbound_stream =
                 OutboundStream::new(max_frame_size, max_message_size, stream_msg_tx);
             while let Some(message) = write_reqs_rx.next().await {
+                if let Some(message_capture) = &message_capture {
+                    message_capture.capture(remote_peer_id, MessageDirection::Outbound, &message);
+                }
+
                 // either channel full would block the other one
                 let result = if outbound_stream.should_stream(&message) {
                     outbound_stream.stream_message(message).await
@@ -447,6 +609,10 @@ where
         &mut self,
         message: NetworkMessage,
     ) -> Result<(), PeerManagerError> {
+        if let Some(message_capture) = &self.message_capture {
+            message_capture.capture(self.remote_peer_id(), MessageDirection::Inbound, &message);
+        }
+
         match message {
             NetworkMessage::DirectSendMsg(message) => self.handle_inbound_direct_send(message),
             NetworkMessage::Error(error_msg) => {
@@ -484,14 +650,19 @@ where
 
     fn handle_inbound_stream_message(
         &mut self,
+        protocol_class: Option<ProtocolClass>,
         message: StreamMessage,
     ) -> Result<(), PeerManagerError> {
+        let inbound_stream = self
+            .inbound_streams
+            .get_mut(&protocol_class)
+            .expect("Every socket and protocol stream has an inbound stream buffer");
         match message {
             StreamMessage::Header(header) => {
-                self.inbound_stream.new_stream(header)?;
+                inbound_stream.new_stream(header)?;
             },
             StreamMessage::Fragment(fragment) => {
-                if let Some(message) = self.inbound_stream.append_fragment(fragment)? {
+                if let Some(message) = inbound_stream.append_fragment(fragment)? {
                     self.handle_inbound_network_message(message)?;
                 }
             },
@@ -501,8 +672,9 @@ where
 
     fn handle_inbound_message(
         &mut self,
+        protocol_class: Option<ProtocolClass>,
         message: Result<MultiplexMessage, ReadError>,
-        write_reqs_tx: &mut aptos_channel::Sender<(), NetworkMessage>,
+        write_reqs_tx: &mut WriteRequestSender,
     ) -> Result<(), PeerManagerError> {
         trace!(
             NetworkSchema::new(&self.network_context)
@@ -524,7 +696,7 @@ where
                     let error_code = ErrorCode::parsing_error(*message_type, *protocol_id);
                     let message = NetworkMessage::Error(error_code);
 
-                    write_reqs_tx.push((), message)?;
+                    write_reqs_tx.push(ProtocolClass::Other, message)?;
                     return Err(err.into());
                 },
                 ReadError::IoError(_) => {
@@ -537,7 +709,9 @@ where
 
         match message {
             MultiplexMessage::Message(message) => self.handle_inbound_network_message(message),
-            MultiplexMessage::Stream(message) => self.handle_inbound_stream_message(message),
+            MultiplexMessage::Stream(message) => {
+                self.handle_inbound_stream_message(protocol_class, message)
+            },
         }
     }
 
@@ -588,7 +762,7 @@ where
     fn handle_outbound_request(
         &mut self,
         request: PeerRequest,
-        write_reqs_tx: &mut aptos_channel::Sender<(), NetworkMessage>,
+        write_reqs_tx: &mut WriteRequestSender,
     ) {
         trace!(
             "Peer {} PeerRequest::{:?}",
@@ -608,7 +782,7 @@ where
                     raw_msg: Vec::from(message.mdata.as_ref()),
                 });
 
-                match write_reqs_tx.push((), message) {
+                match write_reqs_tx.push(protocol_id.protocol_class(), message) {
                     Ok(_) => {
                         self.update_outbound_direct_send_metrics(protocol_id, message_len as u64);
                     },
@@ -664,25 +838,27 @@ where
 
     async fn do_shutdown(
         mut self,
-        write_req_tx: aptos_channel::Sender<(), NetworkMessage>,
-        writer_close_tx: oneshot::Sender<()>,
+        write_req_tx: WriteRequestSender,
+        writer_close_txs: Vec<oneshot::Sender<()>>,
         reason: DisconnectReason,
     ) {
-        // Drop the sender to shut down multiplex task.
+        // Drop the senders to shut down the multiplex tasks.
         drop(write_req_tx);
 
-        // Send a close instruction to the writer task. On receipt of this
-        // instruction, the writer task drops all pending outbound messages and
-        // closes the connection.
-        if let Err(e) = writer_close_tx.send(()) {
-            info!(
-                NetworkSchema::new(&self.network_context)
-                    .connection_metadata(&self.connection_metadata),
-                error = ?e,
-                "{} Failed to send close instruction to writer task. It must already be terminating/terminated. Error: {:?}",
-                self.network_context,
-                e
-            );
+        // Send a close instruction to the writer tasks. On receipt of this
+        // instruction, a writer task drops all pending outbound messages and
+        // closes the connection (or stream).
+        for writer_close_tx in writer_close_txs {
+            if let Err(e) = writer_close_tx.send(()) {
+                info!(
+                    NetworkSchema::new(&self.network_context)
+                        .connection_metadata(&self.connection_metadata),
+                    error = ?e,
+                    "{} Failed to send close instruction to writer task. It must already be terminating/terminated. Error: {:?}",
+                    self.network_context,
+                    e
+                );
+            }
         }
 
         let remote_peer_id = self.remote_peer_id();
@@ -7,13 +7,14 @@ use crate::{
         INBOUND_RPC_TIMEOUT_MS, MAX_CONCURRENT_INBOUND_RPCS, MAX_CONCURRENT_OUTBOUND_RPCS,
         MAX_FRAME_SIZE, MAX_MESSAGE_SIZE, NETWORK_CHANNEL_SIZE,
     },
-    peer::{DisconnectReason, Peer, PeerNotification, PeerRequest},
+    peer::{DisconnectReason, Peer, PeerNotification, PeerRequest, OUTBOUND_CREDITS_PER_WEIGHT},
     peer_manager::TransportNotification,
     protocols::{
         direct_send::Message,
         rpc::{error::RpcError, InboundRpcRequest, OutboundRpcRequest},
+        stream::StreamMessage,
         wire::{
-            handshake::v1::{MessagingProtocolVersion, ProtocolIdSet},
+            handshake::v1::{MessagingProtocolVersion, ProtocolClass, ProtocolIdSet},
             messaging::v1::{
                 DirectSendMsg, MultiplexMessage, MultiplexMessageSink, MultiplexMessageStream,
                 NetworkMessage, RpcRequest, RpcResponse,
@@ -24,9 +25,14 @@ use crate::{
     ProtocolId,
 };
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
-use aptos_config::{config::PeerRole, network_id::NetworkContext};
+use aptos_config::{
+    config::{OutboundMessageWeights, PeerRole},
+    network_id::NetworkContext,
+};
 use aptos_memsocket::MemorySocket;
-use aptos_netcore::transport::ConnectionOrigin;
+use aptos_netcore::transport::{
+    quic::QuicTransport, ConnectionOrigin, MultiStreamSocket, Transport,
+};
 use aptos_time_service::{MockTimeService, TimeService};
 use aptos_types::{network_address::NetworkAddress, PeerId};
 use bytes::Bytes;
@@ -37,7 +43,11 @@ use futures::{
     stream::{StreamExt, TryStreamExt},
     SinkExt,
 };
-use std::{collections::HashSet, str::FromStr, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    time::Duration,
+};
 use tokio::runtime::{Handle, Runtime};
 use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
@@ -69,6 +79,7 @@ fn build_test_peer(
             PeerRole::Unknown,
         ),
         socket: a,
+        protocol_streams: vec![],
     };
 
     let (connection_notifs_tx, connection_notifs_rx) = aptos_channels::new_test(1);
@@ -90,6 +101,8 @@ fn build_test_peer(
         MAX_CONCURRENT_OUTBOUND_RPCS,
         MAX_FRAME_SIZE,
         MAX_MESSAGE_SIZE,
+        OutboundMessageWeights::default(),
+        None,
     );
     let peer_handle = PeerHandle(peer_reqs_tx);
 
@@ -947,3 +960,210 @@ fn peers_send_multiplex() {
 
     rt.block_on(future::join3(peer_a.start(), peer_b.start(), test));
 }
+
+// A burst of state sync messages should not starve consensus messages queued behind it.
+#[test]
+fn peer_consensus_not_starved_by_state_sync() {
+    ::aptos_logger::Logger::init_for_testing();
+
+    // Use a single threaded runtime, so that the writer task only starts
+    // dequeuing messages once we yield (i.e., after all messages are queued).
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let (connection, mut remote_connection) = MemorySocket::new_pair();
+    let (mut write_reqs_tx, _writer_close_tx) = Peer::<MemorySocket>::start_writer_task(
+        rt.handle(),
+        TimeService::mock(),
+        ConnectionMetadata::mock(PeerId::random()),
+        NetworkContext::mock(),
+        MultiplexMessageSink::new(connection, MAX_FRAME_SIZE),
+        MAX_FRAME_SIZE,
+        MAX_MESSAGE_SIZE,
+        OutboundMessageWeights::default(),
+        None,
+    );
+    let mut remote_stream = MultiplexMessageStream::new(&mut remote_connection, MAX_FRAME_SIZE);
+
+    // Queue a burst of state sync messages, followed by a consensus message
+    let state_sync_message_size = 16 * 1024;
+    let num_state_sync_messages = 40;
+    for _ in 0..num_state_sync_messages {
+        push_direct_send(
+            &mut write_reqs_tx,
+            ProtocolId::StateSyncDirectSend,
+            state_sync_message_size,
+        );
+    }
+    push_direct_send(&mut write_reqs_tx, ProtocolId::ConsensusDirectSendBcs, 1024);
+
+    // Verify the consensus message is sent once the state sync class has used
+    // its credits (instead of after the entire burst).
+    let num_messages_per_turn = OutboundMessageWeights::default().state_sync as usize
+        * OUTBOUND_CREDITS_PER_WEIGHT
+        / state_sync_message_size;
+    let protocol_ids = rt.block_on(async {
+        let mut protocol_ids = vec![];
+        for _ in 0..=num_state_sync_messages {
+            protocol_ids.push(read_direct_send_protocol(&mut remote_stream).await);
+        }
+        protocol_ids
+    });
+    let consensus_index = protocol_ids
+        .iter()
+        .position(|protocol_id| *protocol_id == ProtocolId::ConsensusDirectSendBcs)
+        .unwrap();
+    assert!(num_messages_per_turn < num_state_sync_messages);
+    assert_eq!(consensus_index, num_messages_per_turn);
+
+    // Queue a state sync message that has to be streamed (i.e., is larger than
+    // a frame), followed by a consensus message.
+    push_direct_send(
+        &mut write_reqs_tx,
+        ProtocolId::StateSyncDirectSend,
+        MAX_FRAME_SIZE + 1024 * 1024,
+    );
+    push_direct_send(&mut write_reqs_tx, ProtocolId::ConsensusDirectSendBcs, 1024);
+
+    // Verify the consensus message is sent before the stream
+    rt.block_on(async {
+        assert_eq!(
+            read_direct_send_protocol(&mut remote_stream).await,
+            ProtocolId::ConsensusDirectSendBcs
+        );
+        match remote_stream.next().await.unwrap().unwrap() {
+            MultiplexMessage::Stream(StreamMessage::Header(header)) => {
+                assert_eq!(header.num_fragments, 1);
+            },
+            message => panic!("Expected a stream header, received: {:?}", message),
+        }
+    });
+}
+
+// A stalled state sync stream should not delay consensus messages (when the
+// connection carries a stream per protocol class).
+#[test]
+fn peer_consensus_not_blocked_by_stalled_state_sync_stream() {
+    ::aptos_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    let executor = rt.handle().clone();
+
+    rt.block_on(async move {
+        // Connect over QUIC, and open a stream per protocol class
+        let transport = QuicTransport {
+            max_streams: ProtocolClass::all().len() as u32,
+            ..QuicTransport::default()
+        };
+        let (mut listener, listen_addr) = transport
+            .listen_on("/ip4/127.0.0.1/quic/0".parse().unwrap())
+            .unwrap();
+        let dial = transport.dial(PeerId::random(), listen_addr).unwrap();
+        let accept = async move {
+            let (inbound, _) = listener.next().await.unwrap().unwrap();
+            inbound.await
+        };
+        let (socket, remote_socket) = future::join(dial, accept).await;
+        let (socket, remote_socket) = (socket.unwrap(), remote_socket.unwrap());
+        let (streams, remote_streams) =
+            (socket.streams().unwrap(), remote_socket.streams().unwrap());
+        let mut protocol_streams = vec![];
+        let mut remote_protocol_streams = HashMap::new();
+        for protocol_class in ProtocolClass::all() {
+            let stream = streams
+                .open_stream(protocol_class.stream_id())
+                .await
+                .unwrap();
+            protocol_streams.push((*protocol_class, stream));
+            let (stream_id, remote_stream) = remote_streams.accept_stream().await.unwrap();
+            remote_protocol_streams.insert(stream_id, remote_stream);
+        }
+
+        // Start the peer on the dialer side
+        let connection = Connection {
+            metadata: ConnectionMetadata::mock(PeerId::random()),
+            socket,
+            protocol_streams,
+        };
+        let (connection_notifs_tx, _connection_notifs_rx) = aptos_channels::new_test(1);
+        let (peer_reqs_tx, peer_reqs_rx) =
+            aptos_channel::new(QueueStyle::FIFO, NETWORK_CHANNEL_SIZE, None);
+        let (peer_notifs_tx, _peer_notifs_rx) =
+            aptos_channel::new(QueueStyle::FIFO, NETWORK_CHANNEL_SIZE, None);
+        let peer = Peer::new(
+            NetworkContext::mock(),
+            executor.clone(),
+            TimeService::real(),
+            connection,
+            connection_notifs_tx,
+            peer_reqs_rx,
+            peer_notifs_tx,
+            Duration::from_millis(INBOUND_RPC_TIMEOUT_MS),
+            MAX_CONCURRENT_INBOUND_RPCS,
+            MAX_CONCURRENT_OUTBOUND_RPCS,
+            MAX_FRAME_SIZE,
+            MAX_MESSAGE_SIZE,
+            OutboundMessageWeights::default(),
+            None,
+        );
+        executor.spawn(peer.start());
+        let mut peer_handle = PeerHandle(peer_reqs_tx);
+
+        // Send more state sync data than the remote will buffer on its (never
+        // read) state sync stream, and then send a consensus message.
+        for _ in 0..8 {
+            peer_handle.send_direct_send(Message {
+                protocol_id: ProtocolId::StateSyncDirectSend,
+                mdata: Bytes::from(vec![0; 1024 * 1024]),
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        peer_handle.send_direct_send(Message {
+            protocol_id: ProtocolId::ConsensusDirectSendBcs,
+            mdata: Bytes::from_static(b"vote"),
+        });
+
+        // The consensus message should arrive on the consensus stream, while the
+        // state sync stream is stalled.
+        let remote_consensus_stream = remote_protocol_streams
+            .remove(&ProtocolClass::Consensus.stream_id())
+            .unwrap();
+        let mut remote_consensus_stream =
+            MultiplexMessageStream::new(remote_consensus_stream, MAX_FRAME_SIZE);
+        let protocol_id = tokio::time::timeout(
+            Duration::from_secs(10),
+            read_direct_send_protocol(&mut remote_consensus_stream),
+        )
+        .await
+        .expect("The consensus message was delayed by the state sync stream");
+        assert_eq!(protocol_id, ProtocolId::ConsensusDirectSendBcs);
+        drop(remote_socket);
+    });
+}
+
+/// Pushes a direct send message with the given protocol and size to the write queue
+fn push_direct_send(
+    write_reqs_tx: &mut aptos_channel::Sender<ProtocolClass, NetworkMessage>,
+    protocol_id: ProtocolId,
+    message_size: usize,
+) {
+    let message = NetworkMessage::DirectSendMsg(DirectSendMsg {
+        protocol_id,
+        priority: 0,
+        raw_msg: vec![0; message_size],
+    });
+    write_reqs_tx
+        .push(protocol_id.protocol_class(), message)
+        .unwrap();
+}
+
+/// Reads the next message from the stream, and returns its protocol (the
+/// message is expected to be a direct send).
+async fn read_direct_send_protocol(
+    stream: &mut MultiplexMessageStream<impl AsyncRead + Unpin>,
+) -> ProtocolId {
+    match stream.next().await.unwrap().unwrap() {
+        MultiplexMessage::Message(NetworkMessage::DirectSendMsg(message)) => message.protocol_id,
+        message => panic!("Expected a direct send message, received: {:?}", message),
+    }
+}
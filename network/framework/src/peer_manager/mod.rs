@@ -12,6 +12,7 @@
 //!  notification about new/lost Peers to the rest of the network stack.
 //!  * An actor responsible for dialing and listening for new connections.
 use crate::{
+    capture::MessageCapture,
     constants,
     counters::{self},
     logging::*,
@@ -23,7 +24,10 @@ use crate::{
     ProtocolId,
 };
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
-use aptos_config::network_id::{NetworkContext, PeerNetworkId};
+use aptos_config::{
+    config::OutboundMessageWeights,
+    network_id::{NetworkContext, PeerNetworkId},
+};
 use aptos_logger::prelude::*;
 use aptos_netcore::transport::{ConnectionOrigin, Transport};
 use aptos_short_hex_str::AsShortHexStr;
@@ -119,6 +123,10 @@ where
     max_message_size: usize,
     /// Inbound connection limit separate of outbound connections
     inbound_connection_limit: usize,
+    /// The weights used to prioritize outbound messages by protocol class
+    outbound_message_weights: OutboundMessageWeights,
+    /// The capture for messages exchanged with peers (if enabled)
+    message_capture: Option<Arc<MessageCapture>>,
 }
 
 impl<TTransport, TSocket> PeerManager<TTransport, TSocket>
@@ -147,6 +155,8 @@ where
         max_frame_size: usize,
         max_message_size: usize,
         inbound_connection_limit: usize,
+        outbound_message_weights: OutboundMessageWeights,
+        message_capture: Option<Arc<MessageCapture>>,
     ) -> Self {
         let (transport_notifs_tx, transport_notifs_rx) = aptos_channels::new(
             channel_size,
@@ -189,6 +199,8 @@ where
             max_frame_size,
             max_message_size,
             inbound_connection_limit,
+            outbound_message_weights,
+            message_capture,
         }
     }
 
@@ -353,6 +365,28 @@ where
             },
         };
 
+        // Reject connections from peers that are banned or misbehaving
+        let peer_network_id = PeerNetworkId::new(
+            self.network_context.network_id(),
+            conn.metadata.remote_peer_id,
+        );
+        if self
+            .peers_and_metadata
+            .get_peer_reputation()
+            .should_disconnect(&peer_network_id)
+        {
+            info!(
+                NetworkSchema::new(&self.network_context)
+                    .connection_metadata_with_address(&conn.metadata),
+                "{} Connection rejected due to peer misbehavior: {}",
+                self.network_context,
+                conn.metadata
+            );
+            counters::connections_rejected(&self.network_context, conn.metadata.origin).inc();
+            self.disconnect(conn);
+            return;
+        }
+
         // Verify that we have not reached the max connection limit for unknown inbound peers
         if conn.metadata.origin == ConnectionOrigin::Inbound {
             // Everything below here is meant for unknown peers only. The role comes from
@@ -671,6 +705,8 @@ where
             constants::MAX_CONCURRENT_OUTBOUND_RPCS,
             self.max_frame_size,
             self.max_message_size,
+            self.outbound_message_weights,
+            self.message_capture.clone(),
         );
         self.executor.spawn(peer.start());
 
@@ -16,7 +16,10 @@
 use crate::counters::{start_serialization_timer, DESERIALIZATION_LABEL, SERIALIZATION_LABEL};
 use anyhow::anyhow;
 use aptos_compression::client::CompressionClient;
-use aptos_config::{config::MAX_APPLICATION_MESSAGE_SIZE, network_id::NetworkId};
+use aptos_config::{
+    config::{OutboundMessageWeights, MAX_APPLICATION_MESSAGE_SIZE},
+    network_id::NetworkId,
+};
 use aptos_types::chain_id::ChainId;
 #[cfg(any(test, feature = "fuzzing"))]
 use proptest_derive::Arbitrary;
@@ -153,6 +156,39 @@ impl ProtocolId {
         ]
     }
 
+    /// Returns the class of the protocol (used to prioritize outbound messages)
+    pub fn protocol_class(self) -> ProtocolClass {
+        use ProtocolId::*;
+        match self {
+            ConsensusRpcBcs
+            | ConsensusDirectSendBcs
+            | ConsensusDirectSendJson
+            | ConsensusRpcJson
+            | ConsensusRpcCompressed
+            | ConsensusDirectSendCompressed
+            | DKGDirectSendCompressed
+            | DKGDirectSendBcs
+            | DKGDirectSendJson
+            | DKGRpcCompressed
+            | DKGRpcBcs
+            | DKGRpcJson
+            | JWKConsensusDirectSendCompressed
+            | JWKConsensusDirectSendBcs
+            | JWKConsensusDirectSendJson
+            | JWKConsensusRpcCompressed
+            | JWKConsensusRpcBcs
+            | JWKConsensusRpcJson
+            | ConsensusObserver
+            | ConsensusObserverRpc => ProtocolClass::Consensus,
+            MempoolDirectSend | MempoolRpc => ProtocolClass::Mempool,
+            StateSyncDirectSend | StorageServiceRpc => ProtocolClass::StateSync,
+            NetbenchDirectSend | NetbenchRpc => ProtocolClass::Netbench,
+            DiscoveryDirectSend | HealthCheckerRpc | PeerMonitoringServiceRpc => {
+                ProtocolClass::Other
+            },
+        }
+    }
+
     /// Specifies how to encode messages for a given `ProtocolId`
     fn encoding(self) -> Encoding {
         match self {
@@ -275,6 +311,81 @@ impl fmt::Display for ProtocolId {
     }
 }
 
+//
+// ProtocolClass
+//
+
+/// A coarse grouping of application protocols. Outbound messages are
+/// scheduled across classes using the configured `OutboundMessageWeights`.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum ProtocolClass {
+    Consensus,
+    Mempool,
+    StateSync,
+    Netbench,
+    Other,
+}
+
+impl ProtocolClass {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProtocolClass::Consensus => "consensus",
+            ProtocolClass::Mempool => "mempool",
+            ProtocolClass::StateSync => "state_sync",
+            ProtocolClass::Netbench => "netbench",
+            ProtocolClass::Other => "other",
+        }
+    }
+
+    /// Returns all protocol classes
+    pub const fn all() -> &'static [ProtocolClass] {
+        &[
+            ProtocolClass::Consensus,
+            ProtocolClass::Mempool,
+            ProtocolClass::StateSync,
+            ProtocolClass::Netbench,
+            ProtocolClass::Other,
+        ]
+    }
+
+    /// Returns the id of the stream carrying the messages of the class, on
+    /// transports that support multiple streams per connection (e.g., QUIC).
+    pub fn stream_id(self) -> u8 {
+        match self {
+            ProtocolClass::Consensus => 0,
+            ProtocolClass::Mempool => 1,
+            ProtocolClass::StateSync => 2,
+            ProtocolClass::Netbench => 3,
+            ProtocolClass::Other => 4,
+        }
+    }
+
+    /// Returns the class whose messages are carried by the given stream id
+    pub fn from_stream_id(stream_id: u8) -> Option<ProtocolClass> {
+        ProtocolClass::all()
+            .iter()
+            .find(|protocol_class| protocol_class.stream_id() == stream_id)
+            .copied()
+    }
+
+    /// Returns the weight of the class in the given weights
+    pub fn weight(self, weights: &OutboundMessageWeights) -> u32 {
+        match self {
+            ProtocolClass::Consensus => weights.consensus,
+            ProtocolClass::Mempool => weights.mempool,
+            ProtocolClass::StateSync => weights.state_sync,
+            ProtocolClass::Netbench => weights.netbench,
+            ProtocolClass::Other => weights.other,
+        }
+    }
+}
+
+impl fmt::Display for ProtocolClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 //
 // ProtocolIdSet
 //
@@ -183,3 +183,44 @@ fn ignore_unknown_protocols() {
         ProtocolIdSet::empty(),
     );
 }
+
+#[test]
+fn protocol_classes() {
+    assert_eq!(
+        ProtocolId::ConsensusDirectSendCompressed.protocol_class(),
+        ProtocolClass::Consensus
+    );
+    assert_eq!(
+        ProtocolId::DKGRpcBcs.protocol_class(),
+        ProtocolClass::Consensus
+    );
+    assert_eq!(
+        ProtocolId::MempoolDirectSend.protocol_class(),
+        ProtocolClass::Mempool
+    );
+    assert_eq!(
+        ProtocolId::StorageServiceRpc.protocol_class(),
+        ProtocolClass::StateSync
+    );
+    assert_eq!(
+        ProtocolId::NetbenchRpc.protocol_class(),
+        ProtocolClass::Netbench
+    );
+    assert_eq!(
+        ProtocolId::HealthCheckerRpc.protocol_class(),
+        ProtocolClass::Other
+    );
+
+    // Verify the default weights prioritize consensus > mempool > state sync > netbench
+    let weights = OutboundMessageWeights::default();
+    let class_weights: Vec<_> = [
+        ProtocolClass::Consensus,
+        ProtocolClass::Mempool,
+        ProtocolClass::StateSync,
+        ProtocolClass::Netbench,
+    ]
+    .iter()
+    .map(|protocol_class| protocol_class.weight(&weights))
+    .collect();
+    assert!(class_weights.windows(2).all(|pair| pair[0] > pair[1]));
+}
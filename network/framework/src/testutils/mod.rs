@@ -10,6 +10,7 @@ use std::sync::Arc;
 
 pub mod builder;
 pub mod fake_socket;
+pub mod replay;
 pub mod test_framework;
 pub mod test_node;
 
@@ -0,0 +1,167 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    capture::{CapturedMessage, MessageDirection},
+    peer_manager::PeerManagerNotification,
+    protocols::{
+        direct_send::Message,
+        rpc::{error::RpcError, InboundRpcRequest},
+        wire::messaging::v1::{NetworkMessage, RequestId, RpcResponse},
+    },
+    testutils::test_node::{mock_conn_metadata, TestNode},
+    ProtocolId,
+};
+use aptos_channels::aptos_channel::ElementStatus;
+use aptos_config::{
+    config::PeerRole,
+    network_id::{NetworkId, PeerNetworkId},
+};
+use aptos_netcore::transport::ConnectionOrigin;
+use bytes::Bytes;
+use futures::channel::oneshot;
+use std::collections::HashSet;
+
+/// An rpc request replayed against a node, awaiting the node's response
+pub struct ReplayedRpcRequest {
+    /// The peer that sent the request (in the capture)
+    pub peer_network_id: PeerNetworkId,
+    /// The protocol of the request
+    pub protocol_id: ProtocolId,
+    /// The request id (in the capture). This can be used to find the
+    /// corresponding (captured) response.
+    pub request_id: RequestId,
+    /// The channel on which the node will send its response
+    pub response_rx: oneshot::Receiver<Result<Bytes, RpcError>>,
+}
+
+impl ReplayedRpcRequest {
+    /// Returns the response sent to the peer in the capture (if one was captured)
+    pub fn captured_response<'a>(
+        &self,
+        captured_messages: &'a [CapturedMessage],
+    ) -> Option<&'a RpcResponse> {
+        captured_messages
+            .iter()
+            .filter(|captured_message| {
+                captured_message.direction == MessageDirection::Outbound
+                    && captured_message.network_id == self.peer_network_id.network_id()
+                    && captured_message.peer_id == self.peer_network_id.peer_id()
+            })
+            .find_map(|captured_message| match &captured_message.message {
+                NetworkMessage::RpcResponse(response) if response.request_id == self.request_id => {
+                    Some(response)
+                },
+                _ => None,
+            })
+    }
+}
+
+/// A summary of the captured messages replayed against a node
+#[derive(Default)]
+pub struct ReplaySummary {
+    /// The number of direct send messages that were replayed
+    pub num_direct_sends: usize,
+    /// The rpc requests that were replayed (in capture order)
+    pub rpc_requests: Vec<ReplayedRpcRequest>,
+    /// The number of captured messages that were skipped (e.g., outbound
+    /// messages, rpc responses, or messages for networks the node doesn't have)
+    pub num_skipped: usize,
+}
+
+/// Replays the given captured messages against the application handlers of the
+/// node, as if the messages were received from the network. This allows
+/// message sequences observed in the wild to be reproduced locally.
+///
+/// Only inbound direct send messages and rpc requests are replayed (in capture
+/// order). Each remote peer is connected (locally) before its first message is
+/// replayed. Every message is fed to the node only once the previous message has
+/// been dequeued by the application, so the application must be running (and
+/// consuming messages for all replayed protocols) for the replay to complete.
+pub async fn replay_captured_messages<Node: TestNode>(
+    node: &Node,
+    captured_messages: impl IntoIterator<Item = CapturedMessage>,
+) -> ReplaySummary {
+    let network_ids: HashSet<_> = node.network_ids().into_iter().collect();
+    let mut connected_peers = HashSet::new();
+    let mut replay_summary = ReplaySummary::default();
+
+    for captured_message in captured_messages {
+        // Only replay inbound messages for the networks of the node
+        let network_id = captured_message.network_id;
+        if captured_message.direction != MessageDirection::Inbound
+            || !network_ids.contains(&network_id)
+        {
+            replay_summary.num_skipped += 1;
+            continue;
+        }
+
+        // Create the notification for the application
+        let peer_id = captured_message.peer_id;
+        let peer_network_id = PeerNetworkId::new(network_id, peer_id);
+        let (protocol_id, notification) = match captured_message.message {
+            NetworkMessage::DirectSendMsg(message) => {
+                replay_summary.num_direct_sends += 1;
+                let notification = PeerManagerNotification::RecvMessage(peer_id, Message {
+                    protocol_id: message.protocol_id,
+                    mdata: Bytes::from(message.raw_msg),
+                });
+                (message.protocol_id, notification)
+            },
+            NetworkMessage::RpcRequest(request) => {
+                let (res_tx, response_rx) = oneshot::channel();
+                replay_summary.rpc_requests.push(ReplayedRpcRequest {
+                    peer_network_id,
+                    protocol_id: request.protocol_id,
+                    request_id: request.request_id,
+                    response_rx,
+                });
+                let notification = PeerManagerNotification::RecvRpc(peer_id, InboundRpcRequest {
+                    protocol_id: request.protocol_id,
+                    data: Bytes::from(request.raw_request),
+                    res_tx,
+                });
+                (request.protocol_id, notification)
+            },
+            NetworkMessage::RpcResponse(_) | NetworkMessage::Error(_) => {
+                replay_summary.num_skipped += 1;
+                continue;
+            },
+        };
+
+        // Connect the peer (if this is the first message from the peer)
+        if connected_peers.insert(peer_network_id) {
+            let connection_metadata = mock_conn_metadata(
+                peer_network_id,
+                replayed_peer_role(network_id),
+                ConnectionOrigin::Inbound,
+                node.default_protocols(),
+            );
+            node.connect_self(network_id, connection_metadata);
+        }
+
+        // Feed the notification to the node and wait for it to be dequeued
+        let (status_tx, status_rx) = oneshot::channel();
+        node.get_inbound_handle(network_id)
+            .inbound_message_sender
+            .push_with_feedback((peer_id, protocol_id), notification, Some(status_tx))
+            .unwrap();
+        if let Ok(ElementStatus::Dropped(_)) = status_rx.await {
+            panic!(
+                "A replayed message was dropped by the node! Peer: {}, protocol: {}",
+                peer_network_id, protocol_id
+            );
+        }
+    }
+
+    replay_summary
+}
+
+/// Returns the role used for replayed peers on the given network
+fn replayed_peer_role(network_id: NetworkId) -> PeerRole {
+    match network_id {
+        NetworkId::Validator => PeerRole::Validator,
+        NetworkId::Vfn => PeerRole::ValidatorFullNode,
+        NetworkId::Public => PeerRole::Unknown,
+    }
+}
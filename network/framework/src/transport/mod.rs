@@ -7,7 +7,9 @@ use crate::{
     noise::{stream::NoiseStream, AntiReplayTimestamps, HandshakeAuthMode, NoiseUpgrader},
     protocols::{
         identity::exchange_handshake,
-        wire::handshake::v1::{HandshakeMsg, MessagingProtocolVersion, ProtocolIdSet},
+        wire::handshake::v1::{
+            HandshakeMsg, MessagingProtocolVersion, ProtocolClass, ProtocolIdSet,
+        },
     },
 };
 use aptos_config::{
@@ -19,17 +21,23 @@ use aptos_id_generator::{IdGenerator, U32IdGenerator};
 use aptos_logger::prelude::*;
 // Re-exposed for aptos-network-checker
 pub use aptos_netcore::transport::tcp::{resolve_and_connect, TCPBufferCfg, TcpSocket};
-use aptos_netcore::transport::{proxy_protocol, tcp, ConnectionOrigin, Transport};
+use aptos_netcore::transport::{
+    proxy_protocol,
+    quic::{self, QuicStream, QuicStreams},
+    tcp, ConnectionOrigin, MultiStreamSocket, Transport,
+};
 use aptos_short_hex_str::AsShortHexStr;
 use aptos_time_service::{timeout, TimeService, TimeServiceTrait};
 use aptos_types::{
     chain_id::ChainId,
-    network_address::{parse_dns_tcp, parse_ip_tcp, parse_memory, NetworkAddress},
+    network_address::{
+        parse_dns_quic, parse_dns_tcp, parse_ip_quic, parse_ip_tcp, parse_memory, NetworkAddress,
+    },
     PeerId,
 };
 use futures::{
     future::{Future, FutureExt},
-    io::{AsyncRead, AsyncWrite},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
     stream::{Stream, StreamExt, TryStreamExt},
 };
 use serde::{Deserialize, Serialize};
@@ -45,6 +53,10 @@ pub const TRANSPORT_TIMEOUT: Duration = Duration::from_secs(30);
 /// TODO: Add ability to support more than one messaging protocol.
 pub const SUPPORTED_MESSAGING_PROTOCOL: MessagingProtocolVersion = MessagingProtocolVersion::V1;
 
+/// The label of the keying material used to bind the additional streams of a
+/// connection to its noise session.
+const STREAM_BINDING_LABEL: &[u8] = b"aptosnet stream binding";
+
 /// Global connection-id generator.
 static CONNECTION_ID_GENERATOR: ConnectionIdGenerator = ConnectionIdGenerator::new();
 
@@ -58,6 +70,16 @@ pub const APTOS_TCP_TRANSPORT: tcp::TcpTransport = tcp::TcpTransport {
     tcp_buff_cfg: tcp::TCPBufferCfg::new(),
 };
 
+/// quic::Transport with Aptos-specific configuration applied.
+pub const APTOS_QUIC_TRANSPORT: quic::QuicTransport = quic::QuicTransport {
+    // Keep idle connections alive, similar to tcp keepalives.
+    keep_alive_interval: Some(Duration::from_secs(10)),
+    // Close connections that have been unresponsive for longer than the transport timeout.
+    max_idle_timeout: Some(TRANSPORT_TIMEOUT),
+    // Use a stream for each protocol class, so that they don't head-of-line block each other.
+    max_streams: ProtocolClass::all().len() as u32,
+};
+
 /// A trait alias for "socket-like" things.
 pub trait TSocket: AsyncRead + AsyncWrite + Send + fmt::Debug + Unpin + 'static {}
 
@@ -183,12 +205,18 @@ impl fmt::Display for ConnectionMetadata {
     }
 }
 
+/// The additional streams of a connection, each carrying the messages of a
+/// single protocol class. These are only supported by some transports (e.g.,
+/// QUIC); otherwise, all messages are carried by the connection socket.
+pub type ProtocolStreams = Vec<(ProtocolClass, QuicStream)>;
+
 /// The `Connection` struct consists of connection metadata and the actual socket for
 /// communication.
 #[derive(Debug)]
 pub struct Connection<TSocket> {
     pub socket: TSocket,
     pub metadata: ConnectionMetadata,
+    pub protocol_streams: ProtocolStreams,
 }
 
 /// Convenience function for adding a timeout to a Future that returns an `io::Result`.
@@ -242,12 +270,75 @@ fn add_pp_addr(proxy_protocol_enabled: bool, error: io::Error, addr: &NetworkAdd
     }
 }
 
+/// Binds the additional streams of a connection to the noise session on its
+/// socket, and then opens (or accepts) a stream for each protocol class.
+///
+/// The additional streams are only protected by the TLS session of the
+/// connection, which doesn't authenticate the remote peer. So, both peers send
+/// the keying material exported from their TLS session over the (authenticated)
+/// noise session, and check that it matches their own. A man-in-the-middle
+/// terminating TLS has a different TLS session with each peer, so the check fails.
+async fn upgrade_streams<T: TSocket>(
+    socket: &mut NoiseStream<T>,
+    streams: Option<QuicStreams>,
+    origin: ConnectionOrigin,
+) -> io::Result<ProtocolStreams> {
+    let streams = match streams {
+        Some(streams) => streams,
+        None => return Ok(vec![]),
+    };
+
+    // Exchange the keying material over the noise session
+    let keying_material = streams.export_keying_material(STREAM_BINDING_LABEL)?;
+    socket.write_all(&keying_material).await?;
+    socket.flush().await?;
+    let mut remote_keying_material = [0u8; quic::KEYING_MATERIAL_LENGTH];
+    socket.read_exact(&mut remote_keying_material).await?;
+    if keying_material != remote_keying_material {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "The TLS session of the connection doesn't match the noise session",
+        ));
+    }
+
+    // The dialer opens a stream for each protocol class, and the listener accepts them
+    let num_protocol_classes = ProtocolClass::all().len();
+    let mut protocol_streams = ProtocolStreams::with_capacity(num_protocol_classes);
+    match origin {
+        ConnectionOrigin::Outbound => {
+            for protocol_class in ProtocolClass::all() {
+                let stream = streams.open_stream(protocol_class.stream_id()).await?;
+                protocol_streams.push((*protocol_class, stream));
+            }
+        },
+        ConnectionOrigin::Inbound => {
+            while protocol_streams.len() < num_protocol_classes {
+                let (stream_id, stream) = streams.accept_stream().await?;
+                let protocol_class = ProtocolClass::from_stream_id(stream_id)
+                    .filter(|protocol_class| {
+                        !protocol_streams
+                            .iter()
+                            .any(|(accepted_class, _)| accepted_class == protocol_class)
+                    })
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Unexpected or duplicate stream id: {}", stream_id),
+                        )
+                    })?;
+                protocol_streams.push((protocol_class, stream));
+            }
+        },
+    }
+    Ok(protocol_streams)
+}
+
 /// Upgrade an inbound connection. This means we run a Noise IK handshake for
 /// authentication and then negotiate common supported protocols. If
 /// `ctxt.noise.auth_mode` is `HandshakeAuthMode::Mutual( anti_replay_timestamps , trusted_peers )`,
 /// then we will only allow connections from peers with a pubkey in the `trusted_peers`
 /// set. Otherwise, we will allow inbound connections from any pubkey.
-async fn upgrade_inbound<T: TSocket>(
+async fn upgrade_inbound<T: TSocket + MultiStreamSocket>(
     ctxt: Arc<UpgradeContext>,
     fut_socket: impl Future<Output = io::Result<T>>,
     addr: NetworkAddress,
@@ -273,6 +364,7 @@ async fn upgrade_inbound<T: TSocket>(
     } else {
         addr
     };
+    let streams = socket.streams();
 
     // try authenticating via noise handshake
     let (mut socket, remote_peer_id, peer_role) =
@@ -305,6 +397,11 @@ async fn upgrade_inbound<T: TSocket>(
         .await
         .map_err(|err| add_pp_addr(proxy_protocol_enabled, err, &addr))?;
 
+    // bind the additional streams of the connection (if any) to the noise session
+    let protocol_streams = upgrade_streams(&mut socket, streams, origin)
+        .await
+        .map_err(|err| add_pp_addr(proxy_protocol_enabled, err, &addr))?;
+
     // try to negotiate common aptosnet version and supported application protocols
     let (messaging_protocol, application_protocols) = handshake_msg
         .perform_handshake(&remote_handshake)
@@ -333,12 +430,13 @@ async fn upgrade_inbound<T: TSocket>(
             application_protocols,
             peer_role,
         ),
+        protocol_streams,
     })
 }
 
 /// Upgrade an outbound connection. This means we run a Noise IK handshake for
 /// authentication and then negotiate common supported protocols.
-pub async fn upgrade_outbound<T: TSocket>(
+pub async fn upgrade_outbound<T: TSocket + MultiStreamSocket>(
     ctxt: Arc<UpgradeContext>,
     fut_socket: impl Future<Output = io::Result<T>>,
     addr: NetworkAddress,
@@ -347,6 +445,7 @@ pub async fn upgrade_outbound<T: TSocket>(
 ) -> io::Result<Connection<NoiseStream<T>>> {
     let origin = ConnectionOrigin::Outbound;
     let socket = fut_socket.await?;
+    let streams = socket.streams();
 
     // noise handshake
     let (mut socket, peer_role) = ctxt
@@ -385,6 +484,9 @@ pub async fn upgrade_outbound<T: TSocket>(
     };
     let remote_handshake = exchange_handshake(&handshake_msg, &mut socket).await?;
 
+    // bind the additional streams of the connection (if any) to the noise session
+    let protocol_streams = upgrade_streams(&mut socket, streams, origin).await?;
+
     // try to negotiate common aptosnet version and supported application protocols
     let (messaging_protocol, application_protocols) = handshake_msg
         .perform_handshake(&remote_handshake)
@@ -408,6 +510,7 @@ pub async fn upgrade_outbound<T: TSocket>(
             application_protocols,
             peer_role,
         ),
+        protocol_streams,
     })
 }
 
@@ -434,7 +537,7 @@ pub struct AptosNetTransport<TTransport> {
 impl<TTransport> AptosNetTransport<TTransport>
 where
     TTransport: Transport<Error = io::Error>,
-    TTransport::Output: TSocket,
+    TTransport::Output: TSocket + MultiStreamSocket,
     TTransport::Outbound: Send + 'static,
     TTransport::Inbound: Send + 'static,
     TTransport::Listener: Send + 'static,
@@ -487,13 +590,15 @@ where
         let (base_transport_protos, base_transport_suffix) = parse_ip_tcp(protos)
             .map(|x| (&protos[..2], x.1))
             .or_else(|| parse_dns_tcp(protos).map(|x| (&protos[..2], x.1)))
+            .or_else(|| parse_ip_quic(protos).map(|x| (&protos[..2], x.1)))
+            .or_else(|| parse_dns_quic(protos).map(|x| (&protos[..2], x.1)))
             .or_else(|| parse_memory(protos).map(|x| (&protos[..1], x.1)))
             .ok_or_else(|| {
                 io::Error::new(
                     io::ErrorKind::InvalidInput,
                     format!(
                         "Unexpected dialing network address: '{}', expected: \
-                         memory, ip+tcp, or dns+tcp",
+                         memory, ip+tcp, dns+tcp, ip+quic or dns+quic",
                         addr
                     ),
                 )
@@ -539,6 +644,14 @@ where
     /// `/dns/<ipaddr>/tcp/<port>` or
     /// `/dns4/<ipaddr>/tcp/<port>` or
     /// `/dns6/<ipaddr>/tcp/<port>`
+    ///
+    /// If the base transport is `QuicTransport`, then `/<base_transport>` is:
+    ///
+    /// `/ip4/<ipaddr>/quic/<port>` or
+    /// `/ip6/<ipaddr>/quic/<port>` or
+    /// `/dns/<ipaddr>/quic/<port>` or
+    /// `/dns4/<ipaddr>/quic/<port>` or
+    /// `/dns6/<ipaddr>/quic/<port>`
     pub fn dial(
         &self,
         peer_id: PeerId,
@@ -585,6 +698,11 @@ where
     ///
     /// `/ip4/<ipaddr>/tcp/<port>` or
     /// `/ip6/<ipaddr>/tcp/<port>`
+    ///
+    /// If the base transport is `QuicTransport`, then we expect:
+    ///
+    /// `/ip4/<ipaddr>/quic/<port>` or
+    /// `/ip6/<ipaddr>/quic/<port>`
     pub fn listen_on(
         &self,
         addr: NetworkAddress,
@@ -641,7 +759,7 @@ where
 impl<TTransport: Transport> Transport for AptosNetTransport<TTransport>
 where
     TTransport: Transport<Error = io::Error> + Send + 'static,
-    TTransport::Output: TSocket,
+    TTransport::Output: TSocket + MultiStreamSocket,
     TTransport::Outbound: Send + 'static,
     TTransport::Inbound: Send + 'static,
     TTransport::Listener: Send + 'static,
@@ -4,7 +4,9 @@
 
 use crate::{
     application::storage::PeersAndMetadata,
-    protocols::wire::handshake::v1::{MessagingProtocolVersion, ProtocolId, ProtocolIdSet},
+    protocols::wire::handshake::v1::{
+        MessagingProtocolVersion, ProtocolClass, ProtocolId, ProtocolIdSet,
+    },
     testutils,
     transport::*,
 };
@@ -12,7 +14,7 @@ use aptos_config::config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION};
 use aptos_crypto::{test_utils::TEST_SEED, traits::Uniform, x25519, x25519::PrivateKey};
 use aptos_netcore::{
     framing::{read_u16frame, write_u16frame},
-    transport::{memory, ConnectionOrigin, Transport},
+    transport::{memory, ConnectionOrigin, MultiStreamSocket, Transport},
 };
 use aptos_time_service::MockTimeService;
 use aptos_types::{
@@ -22,7 +24,11 @@ use aptos_types::{
     PeerId,
 };
 use bytes::{Bytes, BytesMut};
-use futures::{future, io::AsyncWriteExt, stream::StreamExt};
+use futures::{
+    future,
+    io::{AsyncReadExt, AsyncWriteExt},
+    stream::StreamExt,
+};
 use rand::{rngs::StdRng, SeedableRng};
 use std::{io, iter::FromIterator, sync::Arc};
 use tokio::runtime::Runtime;
@@ -58,7 +64,7 @@ fn setup<TTransport>(
 )
 where
     TTransport: Transport<Error = io::Error> + Clone,
-    TTransport::Output: TSocket,
+    TTransport::Output: TSocket + MultiStreamSocket,
     TTransport::Outbound: Send + 'static,
     TTransport::Inbound: Send + 'static,
     TTransport::Listener: Send + 'static,
@@ -223,6 +229,16 @@ fn expect_ip4_tcp_noise_addr(addr: &NetworkAddress) {
     );
 }
 
+/// Check that the network address matches the format
+/// `"/ip4/<ipaddr>/quic/<port>/noise-ik/<pubkey>/handshake/<version>"`
+fn expect_ip4_quic_noise_addr(addr: &NetworkAddress) {
+    assert!(
+        matches!(addr.as_slice(), [Ip4(_), Quic(_), NoiseIK(_), Handshake(_)]),
+        "addr: '{}'",
+        addr
+    );
+}
+
 fn test_transport_success<TTransport>(
     base_transport: TTransport,
     auth: Auth,
@@ -230,7 +246,7 @@ fn test_transport_success<TTransport>(
     expect_formatted_addr: fn(&NetworkAddress),
 ) where
     TTransport: Transport<Error = io::Error> + Clone,
-    TTransport::Output: TSocket,
+    TTransport::Output: TSocket + MultiStreamSocket,
     TTransport::Outbound: Send + 'static,
     TTransport::Inbound: Send + 'static,
     TTransport::Listener: Send + 'static,
@@ -313,7 +329,7 @@ fn test_transport_rejects_unauthed_dialer<TTransport>(
     expect_formatted_addr: fn(&NetworkAddress),
 ) where
     TTransport: Transport<Error = io::Error> + Clone,
-    TTransport::Output: TSocket,
+    TTransport::Output: TSocket + MultiStreamSocket,
     TTransport::Outbound: Send + 'static,
     TTransport::Inbound: Send + 'static,
     TTransport::Listener: Send + 'static,
@@ -372,7 +388,7 @@ fn test_transport_maybe_mutual<TTransport>(
     expect_formatted_addr: fn(&NetworkAddress),
 ) where
     TTransport: Transport<Error = io::Error> + Clone,
-    TTransport::Output: TSocket,
+    TTransport::Output: TSocket + MultiStreamSocket,
     TTransport::Outbound: Send + 'static,
     TTransport::Inbound: Send + 'static,
     TTransport::Listener: Send + 'static,
@@ -587,6 +603,106 @@ fn test_tcp_transport_rejects_unauthed_dialer() {
     );
 }
 
+//////////////////////////////////////
+// AptosNetTransport<QuicTransport> //
+//////////////////////////////////////
+
+#[test]
+fn test_quic_transport_mutual_auth() {
+    test_transport_success(
+        APTOS_QUIC_TRANSPORT.clone(),
+        Auth::Mutual,
+        "/ip4/127.0.0.1/quic/0",
+        expect_ip4_quic_noise_addr,
+    );
+}
+
+#[test]
+fn test_quic_transport_server_only_auth() {
+    test_transport_success(
+        APTOS_QUIC_TRANSPORT.clone(),
+        Auth::ServerOnly,
+        "/ip4/127.0.0.1/quic/0",
+        expect_ip4_quic_noise_addr,
+    );
+}
+
+#[test]
+fn test_quic_transport_protocol_streams() {
+    let (
+        rt,
+        _mock_time,
+        (listener_peer_id, listener_transport),
+        (_dialer_peer_id, dialer_transport),
+        _,
+        _,
+    ) = setup(APTOS_QUIC_TRANSPORT.clone(), Auth::Mutual);
+
+    let _guard = rt.enter();
+    let (mut inbounds, listener_addr) = listener_transport
+        .listen_on("/ip4/127.0.0.1/quic/0".parse().unwrap())
+        .unwrap();
+
+    // Both peers should have a stream for each protocol class
+    let listener_task = async move {
+        let (inbound, _dialer_addr) = inbounds.next().await.unwrap().unwrap();
+        let conn = inbound.await.unwrap();
+        let protocol_classes: Vec<_> = conn
+            .protocol_streams
+            .iter()
+            .map(|(protocol_class, _)| *protocol_class)
+            .collect();
+        assert_eq!(protocol_classes, ProtocolClass::all());
+
+        // Exchange messages on the state sync stream
+        let (_, mut stream) = conn
+            .protocol_streams
+            .into_iter()
+            .find(|(protocol_class, _)| *protocol_class == ProtocolClass::StateSync)
+            .unwrap();
+        let mut buf = [0; 6];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"foobar");
+        stream.write_all(b"barbaz").await.unwrap();
+        stream.close().await.unwrap();
+
+        // Wait for the dialer to be done (before closing the connection)
+        let _ = stream.read_to_end(&mut vec![]).await;
+    };
+    let dialer_task = async move {
+        let conn = dialer_transport
+            .dial(listener_peer_id, listener_addr)
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(conn.protocol_streams.len(), ProtocolClass::all().len());
+
+        // Exchange messages on the state sync stream
+        let (_, mut stream) = conn
+            .protocol_streams
+            .into_iter()
+            .find(|(protocol_class, _)| *protocol_class == ProtocolClass::StateSync)
+            .unwrap();
+        stream.write_all(b"foobar").await.unwrap();
+        stream.flush().await.unwrap();
+        let mut buf = [0; 6];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"barbaz");
+        stream.close().await.unwrap();
+    };
+
+    rt.block_on(future::join(listener_task, dialer_task));
+}
+
+#[test]
+fn test_quic_transport_rejects_unauthed_dialer() {
+    test_transport_rejects_unauthed_dialer(
+        APTOS_QUIC_TRANSPORT.clone(),
+        "/ip4/127.0.0.1/quic/0",
+        expect_ip4_quic_noise_addr,
+    );
+}
+
 /// Inserts the given peers into the trusted peer set for the specified network
 fn insert_trusted_peers(
     peers_and_metadata: &Arc<PeersAndMetadata>,
@@ -22,6 +22,7 @@ pub mod boxed;
 #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
 pub mod memory;
 pub mod proxy_protocol;
+pub mod quic;
 pub mod tcp;
 
 /// Origin of how a Connection was established.
@@ -115,6 +116,16 @@ pub trait Transport {
         Self: Sized;
 }
 
+/// A trait for the sockets of transports that can carry additional streams to
+/// the remote peer, alongside the socket itself, on the same connection.
+pub trait MultiStreamSocket {
+    /// Returns a handle to open and accept the additional streams of the
+    /// connection, or `None` if the transport doesn't support them (e.g., TCP).
+    fn streams(&self) -> Option<quic::QuicStreams> {
+        None
+    }
+}
+
 impl<T: ?Sized> TransportExt for T where T: Transport {}
 
 /// An extension trait for [`Transport`]s that provides a variety of convenient
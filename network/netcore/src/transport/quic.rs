@@ -0,0 +1,708 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! QUIC Transport
+//!
+//! Connections are established over UDP using [quinn]. Every connection
+//! carries a primary bidirectional stream, opened by the dialer, which is
+//! returned as the transport's output and is what the upper layers (noise,
+//! the AptosNet handshake and the peer actor) run on, exactly like a TCP
+//! socket. The dialer may also open up to `max_streams` additional streams,
+//! each tagged with a one byte stream id (see [`QuicStreams`]). Streams are
+//! flow controlled independently, so a stalled stream does not head-of-line
+//! block the others.
+//!
+//! Note: QUIC mandates TLS 1.3, but the certificates used here are ephemeral
+//! and self-signed, and the dialer does not verify them. TLS therefore does
+//! not authenticate the remote peer on its own: peer identity is established
+//! by the noise handshake on the primary stream, just as with TCP. Before the
+//! additional streams are used, the upper layers must bind them to the noise
+//! session, e.g., by exchanging [`QuicStreams::export_keying_material`] over
+//! it. A man-in-the-middle terminating TLS ends up with different TLS sessions
+//! (and keying material) on either side, so the binding then fails.
+//!
+//! [quinn]: https://docs.rs/quinn
+use crate::transport::{ConnectionOrigin, MultiStreamSocket, Transport};
+use aptos_types::{
+    network_address::{parse_dns_quic, parse_ip_quic, IpFilter, NetworkAddress, Protocol},
+    PeerId,
+};
+use futures::{
+    future::{BoxFuture, Future, FutureExt},
+    io::{AsyncRead, AsyncWrite},
+    stream::Stream,
+};
+use quinn::{
+    crypto::rustls::{QuicClientConfig, QuicServerConfig},
+    ClientConfig, Connection, Endpoint, IdleTimeout, RecvStream, SendStream, ServerConfig,
+    TransportConfig, VarInt,
+};
+use rustls::{
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+    pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName, UnixTime},
+    DigitallySignedStruct, SignatureScheme,
+};
+use std::{
+    error::Error,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::net::lookup_host;
+
+/// The ALPN protocol negotiated by all AptosNet QUIC connections
+const ALPN_PROTOCOL: &[u8] = b"aptosnet";
+
+/// The TLS server name used when dialing. Certificates are not verified (see
+/// the module documentation), so this is only used to fill in the SNI.
+const SERVER_NAME: &str = "aptosnet";
+
+/// The stream id written at the start of the primary stream of a connection.
+/// QUIC streams are only announced to the remote once data is sent on them.
+pub const PRIMARY_STREAM_ID: u8 = u8::MAX;
+
+/// The length of the keying material exported from the TLS session of a connection
+pub const KEYING_MATERIAL_LENGTH: usize = 32;
+
+/// The error code used when explicitly closing a connection
+const CLOSE_ERROR_CODE: u32 = 0;
+
+/// Transport to build QUIC connections
+#[derive(Debug, Clone, Default)]
+pub struct QuicTransport {
+    /// Interval at which keep-alive packets are sent, or `None` to keep default.
+    pub keep_alive_interval: Option<Duration>,
+    /// Idle time after which a connection is closed, or `None` to keep default.
+    pub max_idle_timeout: Option<Duration>,
+    /// The number of additional streams (besides the primary stream) the dialer
+    /// may concurrently open on a connection.
+    pub max_streams: u32,
+}
+
+impl QuicTransport {
+    fn transport_config(&self, origin: ConnectionOrigin) -> io::Result<TransportConfig> {
+        let mut transport_config = TransportConfig::default();
+        transport_config.keep_alive_interval(self.keep_alive_interval);
+        // Only the dialer opens streams (see the module documentation)
+        let max_remote_streams = match origin {
+            ConnectionOrigin::Inbound => self.max_streams.saturating_add(1),
+            ConnectionOrigin::Outbound => 0,
+        };
+        transport_config.max_concurrent_bidi_streams(VarInt::from_u32(max_remote_streams));
+        transport_config.max_concurrent_uni_streams(VarInt::from_u32(0));
+        if let Some(max_idle_timeout) = self.max_idle_timeout {
+            let max_idle_timeout = IdleTimeout::try_from(max_idle_timeout).map_err(quic_error)?;
+            transport_config.max_idle_timeout(Some(max_idle_timeout));
+        }
+        Ok(transport_config)
+    }
+
+    fn server_config(&self) -> io::Result<ServerConfig> {
+        // Generate an ephemeral self-signed certificate. Peers are authenticated
+        // by the noise handshake, so this only exists to satisfy TLS.
+        let certified_key =
+            rcgen::generate_simple_self_signed(vec![SERVER_NAME.into()]).map_err(quic_error)?;
+        let certificate = certified_key.cert.der().clone();
+        let private_key = PrivateKeyDer::from(PrivatePkcs8KeyDer::from(
+            certified_key.key_pair.serialize_der(),
+        ));
+
+        let mut server_crypto = rustls::ServerConfig::builder_with_provider(crypto_provider())
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(quic_error)?
+            .with_no_client_auth()
+            .with_single_cert(vec![certificate], private_key)
+            .map_err(quic_error)?;
+        server_crypto.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+
+        let server_crypto = QuicServerConfig::try_from(server_crypto).map_err(quic_error)?;
+        let mut server_config = ServerConfig::with_crypto(Arc::new(server_crypto));
+        server_config.transport_config(Arc::new(self.transport_config(ConnectionOrigin::Inbound)?));
+        Ok(server_config)
+    }
+
+    fn client_config(&self) -> io::Result<ClientConfig> {
+        let provider = crypto_provider();
+        let mut client_crypto = rustls::ClientConfig::builder_with_provider(provider.clone())
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(quic_error)?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(SkipServerVerification(provider)))
+            .with_no_client_auth();
+        client_crypto.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+
+        let client_crypto = QuicClientConfig::try_from(client_crypto).map_err(quic_error)?;
+        let mut client_config = ClientConfig::new(Arc::new(client_crypto));
+        client_config
+            .transport_config(Arc::new(self.transport_config(ConnectionOrigin::Outbound)?));
+        Ok(client_config)
+    }
+}
+
+impl Transport for QuicTransport {
+    type Error = ::std::io::Error;
+    type Inbound = BoxFuture<'static, io::Result<QuicSocket>>;
+    type Listener = QuicListenerStream;
+    type Outbound = BoxFuture<'static, io::Result<QuicSocket>>;
+    type Output = QuicSocket;
+
+    fn listen_on(
+        &self,
+        addr: NetworkAddress,
+    ) -> Result<(Self::Listener, NetworkAddress), Self::Error> {
+        let ((ipaddr, port), addr_suffix) =
+            parse_ip_quic(addr.as_slice()).ok_or_else(|| invalid_addr_error(&addr))?;
+        if !addr_suffix.is_empty() {
+            return Err(invalid_addr_error(&addr));
+        }
+
+        let endpoint = Endpoint::server(self.server_config()?, SocketAddr::new(ipaddr, port))?;
+        let listen_addr = quic_network_address(endpoint.local_addr()?);
+
+        Ok((QuicListenerStream::new(endpoint), listen_addr))
+    }
+
+    fn dial(&self, _peer_id: PeerId, addr: NetworkAddress) -> Result<Self::Outbound, Self::Error> {
+        let protos = addr.as_slice();
+
+        // ensure addr is well formed to save some work before potentially
+        // spawning a dial task that will fail anyway.
+        parse_ip_quic(protos)
+            .map(|_| ())
+            .or_else(|| parse_dns_quic(protos).map(|_| ()))
+            .ok_or_else(|| invalid_addr_error(&addr))?;
+
+        let client_config = self.client_config()?;
+        Ok(resolve_and_connect(addr, client_config).boxed())
+    }
+}
+
+/// Note: we need to take ownership of this `NetworkAddress` (instead of just
+/// borrowing the `&[Protocol]` slice) so this future can be `Send + 'static`.
+async fn resolve_and_connect(
+    addr: NetworkAddress,
+    client_config: ClientConfig,
+) -> io::Result<QuicSocket> {
+    let protos = addr.as_slice();
+
+    if let Some(((ipaddr, port), _addr_suffix)) = parse_ip_quic(protos) {
+        // this is an /ip4 or /ip6 address, so we can just connect without any
+        // extra resolving or filtering.
+        connect(SocketAddr::new(ipaddr, port), client_config).await
+    } else if let Some(((ip_filter, dns_name, port), _addr_suffix)) = parse_dns_quic(protos) {
+        // resolve dns name and filter
+        let socketaddr_iter = resolve_with_filter(ip_filter, dns_name.as_ref(), port).await?;
+        let mut last_err = None;
+
+        // try to connect until the first succeeds
+        for socketaddr in socketaddr_iter {
+            match connect(socketaddr, client_config.clone()).await {
+                Ok(socket) => return Ok(socket),
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "could not resolve dns name to any address: name: {}, ip filter: {:?}",
+                    dns_name.as_ref(),
+                    ip_filter,
+                ),
+            )
+        }))
+    } else {
+        Err(invalid_addr_error(&addr))
+    }
+}
+
+/// Try to lookup the dns name, then filter addrs according to the `IpFilter`.
+async fn resolve_with_filter(
+    ip_filter: IpFilter,
+    dns_name: &str,
+    port: u16,
+) -> io::Result<impl Iterator<Item = SocketAddr> + '_> {
+    Ok(lookup_host((dns_name, port))
+        .await?
+        .filter(move |socketaddr| ip_filter.matches(socketaddr.ip())))
+}
+
+/// Establishes a QUIC connection with the given remote and opens the primary stream
+async fn connect(remote_addr: SocketAddr, client_config: ClientConfig) -> io::Result<QuicSocket> {
+    // Bind an ephemeral local endpoint of the same address family
+    let bind_ip = if remote_addr.is_ipv4() {
+        IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+    } else {
+        IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+    };
+    let mut endpoint = Endpoint::client(SocketAddr::new(bind_ip, 0))?;
+    endpoint.set_default_client_config(client_config);
+
+    let connection = endpoint
+        .connect(remote_addr, SERVER_NAME)
+        .map_err(quic_error)?
+        .await
+        .map_err(quic_error)?;
+
+    // QUIC streams are only announced to the remote once data is sent on them,
+    // so we write the stream id immediately to let the listener accept it.
+    let stream = open_tagged_stream(&connection, PRIMARY_STREAM_ID).await?;
+    Ok(QuicSocket::new(connection, stream))
+}
+
+/// Accepts an inbound QUIC connection and its primary stream
+async fn accept(incoming: quinn::Incoming) -> io::Result<QuicSocket> {
+    let connection = incoming.await.map_err(quic_error)?;
+
+    let (stream_id, stream) = accept_tagged_stream(&connection).await?;
+    if stream_id != PRIMARY_STREAM_ID {
+        connection.close(VarInt::from_u32(CLOSE_ERROR_CODE), b"unexpected stream");
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Expected the primary stream ({}), but got stream: {}",
+                PRIMARY_STREAM_ID, stream_id
+            ),
+        ));
+    }
+    Ok(QuicSocket::new(connection, stream))
+}
+
+async fn open_tagged_stream(connection: &Connection, stream_id: u8) -> io::Result<QuicStream> {
+    let (mut send, recv) = connection.open_bi().await.map_err(quic_error)?;
+    send.write_all(&[stream_id]).await?;
+    Ok(QuicStream { send, recv })
+}
+
+async fn accept_tagged_stream(connection: &Connection) -> io::Result<(u8, QuicStream)> {
+    let (send, mut recv) = connection.accept_bi().await.map_err(quic_error)?;
+    let mut stream_id = [0u8; 1];
+    recv.read_exact(&mut stream_id).await.map_err(quic_error)?;
+    Ok((stream_id[0], QuicStream { send, recv }))
+}
+
+/// Converts a local or remote UDP socket address into a QUIC `NetworkAddress`
+fn quic_network_address(socket_addr: SocketAddr) -> NetworkAddress {
+    let ip_proto = Protocol::from(socket_addr.ip());
+    let quic_proto = Protocol::Quic(socket_addr.port());
+    NetworkAddress::from_protocols(vec![ip_proto, quic_proto]).unwrap()
+}
+
+fn crypto_provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+fn quic_error<E: Into<Box<dyn Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error)
+}
+
+fn invalid_addr_error(addr: &NetworkAddress) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid NetworkAddress: '{}'", addr),
+    )
+}
+
+/// A certificate verifier that accepts any server certificate (but still
+/// checks the handshake signatures). See the module documentation for how
+/// the connection is authenticated instead.
+#[derive(Debug)]
+struct SkipServerVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[must_use = "streams do nothing unless polled"]
+pub struct QuicListenerStream {
+    endpoint: Endpoint,
+    accept: BoxFuture<'static, Option<quinn::Incoming>>,
+}
+
+impl QuicListenerStream {
+    fn new(endpoint: Endpoint) -> Self {
+        let accept = Self::accept_next(endpoint.clone());
+        Self { endpoint, accept }
+    }
+
+    fn accept_next(endpoint: Endpoint) -> BoxFuture<'static, Option<quinn::Incoming>> {
+        async move { endpoint.accept().await }.boxed()
+    }
+}
+
+impl Stream for QuicListenerStream {
+    type Item = io::Result<(BoxFuture<'static, io::Result<QuicSocket>>, NetworkAddress)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Option<Self::Item>> {
+        match self.accept.as_mut().poll(context) {
+            Poll::Ready(Some(incoming)) => {
+                self.accept = Self::accept_next(self.endpoint.clone());
+                let dialer_addr = quic_network_address(incoming.remote_address());
+                Poll::Ready(Some(Ok((accept(incoming).boxed(), dialer_addr))))
+            },
+            // The endpoint has been closed
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// A single bidirectional QUIC stream
+#[derive(Debug)]
+pub struct QuicStream {
+    send: SendStream,
+    recv: RecvStream,
+}
+
+impl AsyncRead for QuicStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        AsyncRead::poll_read(Pin::new(&mut self.recv), context, buf)
+    }
+}
+
+impl AsyncWrite for QuicStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        AsyncWrite::poll_write(Pin::new(&mut self.send), context, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_flush(Pin::new(&mut self.send), context)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_close(Pin::new(&mut self.send), context)
+    }
+}
+
+/// A handle to open (or accept) the additional streams of a QUIC connection,
+/// each tagged with a stream id.
+#[derive(Clone, Debug)]
+pub struct QuicStreams {
+    connection: Connection,
+}
+
+impl QuicStreams {
+    /// Opens a new stream on the connection, tagged with the given stream id
+    pub async fn open_stream(&self, stream_id: u8) -> io::Result<QuicStream> {
+        if stream_id == PRIMARY_STREAM_ID {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Stream id {} is reserved for the primary stream", stream_id),
+            ));
+        }
+        open_tagged_stream(&self.connection, stream_id).await
+    }
+
+    /// Waits for the remote peer to open a new stream, and returns it along
+    /// with its stream id.
+    pub async fn accept_stream(&self) -> io::Result<(u8, QuicStream)> {
+        let (stream_id, stream) = accept_tagged_stream(&self.connection).await?;
+        if stream_id == PRIMARY_STREAM_ID {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Stream id {} is reserved for the primary stream", stream_id),
+            ));
+        }
+        Ok((stream_id, stream))
+    }
+
+    /// Exports keying material from the TLS session of the connection (see
+    /// RFC 5705). Both ends of the connection derive the same keying material
+    /// for the same label.
+    pub fn export_keying_material(&self, label: &[u8]) -> io::Result<[u8; KEYING_MATERIAL_LENGTH]> {
+        let mut keying_material = [0u8; KEYING_MATERIAL_LENGTH];
+        self.connection
+            .export_keying_material(&mut keying_material, label, &[])
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    "Failed to export keying material from the TLS session",
+                )
+            })?;
+        Ok(keying_material)
+    }
+}
+
+/// A QUIC connection, exposed as its primary stream
+///
+/// Reads and writes go to the primary stream, so the socket can be used
+/// anywhere a `TcpSocket` can. The additional streams of the connection are
+/// available through [`MultiStreamSocket::streams`].
+#[derive(Debug)]
+pub struct QuicSocket {
+    connection: Connection,
+    primary_stream: QuicStream,
+}
+
+impl QuicSocket {
+    fn new(connection: Connection, primary_stream: QuicStream) -> Self {
+        Self {
+            connection,
+            primary_stream,
+        }
+    }
+
+    /// Returns the address of the remote peer
+    pub fn remote_address(&self) -> SocketAddr {
+        self.connection.remote_address()
+    }
+}
+
+impl MultiStreamSocket for QuicSocket {
+    fn streams(&self) -> Option<QuicStreams> {
+        Some(QuicStreams {
+            connection: self.connection.clone(),
+        })
+    }
+}
+
+impl AsyncRead for QuicSocket {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.primary_stream).poll_read(context, buf)
+    }
+}
+
+impl AsyncWrite for QuicSocket {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.primary_stream).poll_write(context, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.primary_stream).poll_flush(context)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.primary_stream).poll_close(context)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transport::{ConnectionOrigin, Transport, TransportExt};
+    use aptos_types::PeerId;
+    use futures::{
+        future::{join, FutureExt},
+        io::{AsyncReadExt, AsyncWriteExt},
+        stream::StreamExt,
+    };
+
+    #[tokio::test]
+    async fn simple_listen_and_dial() -> Result<(), ::std::io::Error> {
+        let t = QuicTransport::default().and_then(|mut out, _addr, origin| async move {
+            match origin {
+                ConnectionOrigin::Inbound => {
+                    out.write_all(b"Earth").await?;
+                    out.flush().await?;
+                    let mut buf = [0; 3];
+                    out.read_exact(&mut buf).await?;
+                    assert_eq!(&buf, b"Air");
+                },
+                ConnectionOrigin::Outbound => {
+                    let mut buf = [0; 5];
+                    out.read_exact(&mut buf).await?;
+                    assert_eq!(&buf, b"Earth");
+                    out.write_all(b"Air").await?;
+                    out.flush().await?;
+                },
+            }
+            Ok(out)
+        });
+
+        let (listener, addr) = t.listen_on("/ip4/127.0.0.1/quic/0".parse().unwrap())?;
+        let peer_id = PeerId::random();
+        let dial = t.dial(peer_id, addr)?;
+        let listener = listener.into_future().then(|(maybe_result, _stream)| {
+            let (incoming, _addr) = maybe_result.unwrap().unwrap();
+            incoming.map(Result::unwrap)
+        });
+
+        let (outgoing, _incoming) = join(dial, listener).await;
+        assert!(outgoing.is_ok());
+        Ok(())
+    }
+
+    async fn connect(t: QuicTransport) -> Result<(QuicSocket, QuicSocket), ::std::io::Error> {
+        let (mut listener, addr) = t.listen_on("/ip4/127.0.0.1/quic/0".parse().unwrap())?;
+        assert!(parse_ip_quic(addr.as_slice()).is_some());
+
+        let dial = t.dial(PeerId::random(), addr)?;
+        let accept = async move {
+            let (inbound, _addr) = listener.next().await.unwrap().unwrap();
+            inbound.await
+        };
+        let (outbound, inbound) = join(dial, accept).await;
+        Ok((outbound?, inbound?))
+    }
+
+    #[tokio::test]
+    async fn independent_streams() -> Result<(), ::std::io::Error> {
+        let t = QuicTransport {
+            max_streams: 2,
+            ..QuicTransport::default()
+        };
+        let (outbound, inbound) = connect(t).await?;
+        let (outbound_streams, inbound_streams) =
+            (outbound.streams().unwrap(), inbound.streams().unwrap());
+
+        // The primary stream id is reserved
+        assert!(outbound_streams
+            .open_stream(PRIMARY_STREAM_ID)
+            .await
+            .is_err());
+
+        // Open two streams and only write to the second one. The listener
+        // should still receive the data without waiting on the first stream.
+        let mut stream_1 = outbound_streams.open_stream(1).await?;
+        let mut stream_2 = outbound_streams.open_stream(2).await?;
+
+        // The dialer can't open more than `max_streams` additional streams
+        let open_stream =
+            tokio::time::timeout(Duration::from_millis(200), outbound_streams.open_stream(3)).await;
+        assert!(open_stream.is_err());
+
+        stream_2.write_all(b"Water").await?;
+        stream_2.flush().await?;
+
+        let (stream_id, _) = inbound_streams.accept_stream().await?;
+        assert_eq!(stream_id, 1);
+        let (stream_id, mut inbound_stream_2) = inbound_streams.accept_stream().await?;
+        assert_eq!(stream_id, 2);
+        let mut buf = [0; 5];
+        inbound_stream_2.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"Water");
+
+        // The first stream is still usable
+        stream_1.write_all(b"Fire").await?;
+        stream_1.close().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn no_additional_streams_by_default() -> Result<(), ::std::io::Error> {
+        let (outbound, inbound) = connect(QuicTransport::default()).await?;
+
+        // The dialer must not be able to open another stream
+        let open_stream = tokio::time::timeout(
+            Duration::from_millis(200),
+            outbound.streams().unwrap().open_stream(1),
+        )
+        .await;
+        assert!(open_stream.is_err());
+
+        // The listener must not be able to open any stream either
+        let open_stream = tokio::time::timeout(
+            Duration::from_millis(200),
+            inbound.streams().unwrap().open_stream(1),
+        )
+        .await;
+        assert!(open_stream.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keying_material() -> Result<(), ::std::io::Error> {
+        let (outbound, inbound) = connect(QuicTransport::default()).await?;
+        let (outbound_streams, inbound_streams) =
+            (outbound.streams().unwrap(), inbound.streams().unwrap());
+
+        // Both ends derive the same keying material for the same label
+        let keying_material = outbound_streams.export_keying_material(b"label")?;
+        assert_eq!(
+            keying_material,
+            inbound_streams.export_keying_material(b"label")?
+        );
+        assert_ne!(
+            keying_material,
+            inbound_streams.export_keying_material(b"other label")?
+        );
+
+        // Different connections have different keying material
+        let (other_outbound, _other_inbound) = connect(QuicTransport::default()).await?;
+        assert_ne!(
+            keying_material,
+            other_outbound
+                .streams()
+                .unwrap()
+                .export_keying_material(b"label")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_multiaddrs() {
+        let t = QuicTransport::default();
+
+        let result = t.listen_on("/memory/0".parse().unwrap());
+        assert!(result.is_err());
+
+        let result = t.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap());
+        assert!(result.is_err());
+
+        let peer_id = PeerId::random();
+        let result = t.dial(peer_id, "/ip4/127.0.0.1/tcp/22".parse().unwrap());
+        assert!(result.is_err());
+    }
+}
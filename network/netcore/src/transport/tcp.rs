@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! TCP Transport
-use crate::transport::Transport;
+use crate::transport::{MultiStreamSocket, Transport};
 use aptos_proxy::Proxy;
 use aptos_types::{
     network_address::{parse_dns_tcp, parse_ip_tcp, parse_tcp, IpFilter, NetworkAddress},
@@ -379,6 +379,8 @@ impl TcpSocket {
     }
 }
 
+impl MultiStreamSocket for TcpSocket {}
+
 impl AsyncRead for TcpSocket {
     fn poll_read(
         mut self: Pin<&mut Self>,
@@ -11,17 +11,72 @@ use aptos_protos::remote_executor::v1::{
 use crossbeam_channel::Sender;
 use std::{
     collections::HashMap,
+    fs, io,
     net::SocketAddr,
+    path::Path,
     sync::{Arc, Mutex},
 };
 use tokio::{runtime::Runtime, sync::oneshot};
 use tonic::{
-    transport::{Channel, Server},
+    transport::{Certificate, Channel, ClientTlsConfig, Identity, Server, ServerTlsConfig},
     Request, Response, Status,
 };
 
 const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 80;
 
+/// Mutual TLS between the nodes: every node presents a certificate signed by the CA, and only
+/// accepts connections from nodes presenting one as well.
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    ca_certificate: Certificate,
+    identity: Identity,
+    /// Name the certificates of the nodes are issued for, checked by the clients
+    domain_name: String,
+}
+
+impl TlsConfig {
+    pub fn new(
+        ca_certificate_pem: impl AsRef<[u8]>,
+        certificate_pem: impl AsRef<[u8]>,
+        private_key_pem: impl AsRef<[u8]>,
+        domain_name: String,
+    ) -> Self {
+        Self {
+            ca_certificate: Certificate::from_pem(ca_certificate_pem),
+            identity: Identity::from_pem(certificate_pem, private_key_pem),
+            domain_name,
+        }
+    }
+
+    /// Loads the PEM encoded certificates and private key from files.
+    pub fn from_files(
+        ca_certificate_path: &Path,
+        certificate_path: &Path,
+        private_key_path: &Path,
+        domain_name: String,
+    ) -> io::Result<Self> {
+        Ok(Self::new(
+            fs::read(ca_certificate_path)?,
+            fs::read(certificate_path)?,
+            fs::read(private_key_path)?,
+            domain_name,
+        ))
+    }
+
+    fn server_config(&self) -> ServerTlsConfig {
+        ServerTlsConfig::new()
+            .identity(self.identity.clone())
+            .client_ca_root(self.ca_certificate.clone())
+    }
+
+    fn client_config(&self) -> ClientTlsConfig {
+        ClientTlsConfig::new()
+            .ca_certificate(self.ca_certificate.clone())
+            .identity(self.identity.clone())
+            .domain_name(self.domain_name.clone())
+    }
+}
+
 pub struct GRPCNetworkMessageServiceServerWrapper {
     inbound_handlers: Arc<Mutex<HashMap<MessageType, Sender<Message>>>>,
     self_addr: SocketAddr,
@@ -46,10 +101,11 @@ impl GRPCNetworkMessageServiceServerWrapper {
         _service: String,
         server_addr: SocketAddr,
         rpc_timeout_ms: u64,
+        tls_config: Option<TlsConfig>,
         server_shutdown_rx: oneshot::Receiver<()>,
     ) {
         rt.spawn(async move {
-            self.start_async(server_addr, rpc_timeout_ms, server_shutdown_rx)
+            self.start_async(server_addr, rpc_timeout_ms, tls_config, server_shutdown_rx)
                 .await;
         });
     }
@@ -58,6 +114,7 @@ impl GRPCNetworkMessageServiceServerWrapper {
         self,
         server_addr: SocketAddr,
         rpc_timeout_ms: u64,
+        tls_config: Option<TlsConfig>,
         server_shutdown_rx: oneshot::Receiver<()>,
     ) {
         let reflection_service = tonic_reflection::server::Builder::configure()
@@ -72,7 +129,13 @@ impl GRPCNetworkMessageServiceServerWrapper {
         //           the server
         //       (2) There is no easy way to know if/when the server has started successfully. Hence
         //           we may need to implement a healthcheck service to check if the server is up
-        Server::builder()
+        let mut server_builder = Server::builder();
+        if let Some(tls_config) = tls_config {
+            server_builder = server_builder
+                .tls_config(tls_config.server_config())
+                .expect("Invalid TLS config");
+        }
+        server_builder
             .timeout(std::time::Duration::from_millis(rpc_timeout_ms))
             .add_service(
                 NetworkMessageServiceServer::new(self).max_decoding_message_size(MAX_MESSAGE_SIZE),
@@ -121,19 +184,26 @@ pub struct GRPCNetworkMessageServiceClientWrapper {
 }
 
 impl GRPCNetworkMessageServiceClientWrapper {
-    pub fn new(rt: &Runtime, remote_addr: SocketAddr) -> Self {
+    pub fn new(rt: &Runtime, remote_addr: SocketAddr, tls_config: Option<&TlsConfig>) -> Self {
         Self {
             remote_addr: remote_addr.to_string(),
-            remote_channel: rt
-                .block_on(async { Self::get_channel(format!("http://{}", remote_addr)).await }),
+            remote_channel: rt.block_on(async { Self::get_channel(remote_addr, tls_config).await }),
         }
     }
 
-    async fn get_channel(remote_addr: String) -> NetworkMessageServiceClient<Channel> {
+    async fn get_channel(
+        remote_addr: SocketAddr,
+        tls_config: Option<&TlsConfig>,
+    ) -> NetworkMessageServiceClient<Channel> {
         info!("Trying to connect to remote server at {:?}", remote_addr);
-        let conn = tonic::transport::Endpoint::new(remote_addr)
-            .unwrap()
-            .connect_lazy();
+        let conn = match tls_config {
+            Some(tls_config) => tonic::transport::Endpoint::new(format!("https://{}", remote_addr))
+                .unwrap()
+                .tls_config(tls_config.client_config())
+                .expect("Invalid TLS config"),
+            None => tonic::transport::Endpoint::new(format!("http://{}", remote_addr)).unwrap(),
+        }
+        .connect_lazy();
         NetworkMessageServiceClient::new(conn).max_decoding_message_size(MAX_MESSAGE_SIZE)
     }
 
@@ -148,14 +218,13 @@ impl GRPCNetworkMessageServiceClientWrapper {
             message_type: mt.get_type(),
         });
         // TODO: Retry with exponential backoff on failures
-        match self.remote_channel.simple_msg_exchange(request).await {
-            Ok(_) => {},
-            Err(e) => {
-                panic!(
-                    "Error '{}' sending message to {} on node {:?}",
-                    e, self.remote_addr, sender_addr
-                );
-            },
+        // A failure is not fatal: the message is dropped, and it is up to the receiver to
+        // detect that the remote node is unreachable, e.g. by timing out on the response.
+        if let Err(e) = self.remote_channel.simple_msg_exchange(request).await {
+            error!(
+                "Error '{}' sending message to {} on node {:?}",
+                e, self.remote_addr, sender_addr
+            );
         }
     }
 }
@@ -187,10 +256,11 @@ fn basic_test() {
         "unit tester".to_string(),
         server_addr,
         1000,
+        None,
         server_shutdown_rx,
     );
 
-    let mut grpc_client = GRPCNetworkMessageServiceClientWrapper::new(&rt, server_addr);
+    let mut grpc_client = GRPCNetworkMessageServiceClientWrapper::new(&rt, server_addr, None);
 
     let client_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port());
     let test_message_content = "test1".as_bytes().to_vec();
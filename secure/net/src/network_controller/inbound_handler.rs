@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    grpc_network_service::GRPCNetworkMessageServiceServerWrapper,
+    grpc_network_service::{GRPCNetworkMessageServiceServerWrapper, TlsConfig},
     network_controller::{Message, MessageType},
 };
 use aptos_logger::warn;
@@ -41,7 +41,11 @@ impl InboundHandler {
         inbound_handlers.insert(MessageType::new(message_type), sender);
     }
 
-    pub fn start(&self, rt: &Runtime) -> Option<oneshot::Sender<()>> {
+    pub fn start(
+        &self,
+        rt: &Runtime,
+        tls_config: Option<TlsConfig>,
+    ) -> Option<oneshot::Sender<()>> {
         if self.inbound_handlers.lock().unwrap().is_empty() {
             return None;
         }
@@ -57,6 +61,7 @@ impl InboundHandler {
             self.service.clone(),
             self.listen_addr,
             self.rpc_timeout_ms,
+            tls_config,
             server_shutdown_rx,
         );
         Some(server_shutdown_tx)
@@ -1,8 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::network_controller::{
-    inbound_handler::InboundHandler, outbound_handler::OutboundHandler,
+use crate::{
+    grpc_network_service::TlsConfig,
+    network_controller::{inbound_handler::InboundHandler, outbound_handler::OutboundHandler},
 };
 use aptos_logger::{info, warn};
 use crossbeam_channel::{unbounded, Receiver, Sender};
@@ -88,6 +89,7 @@ pub struct NetworkController {
     inbound_server_shutdown_tx: Option<oneshot::Sender<()>>,
     outbound_task_shutdown_tx: Option<Sender<Message>>,
     listen_addr: SocketAddr,
+    tls_config: Option<TlsConfig>,
 }
 
 impl NetworkController {
@@ -108,9 +110,17 @@ impl NetworkController {
             inbound_server_shutdown_tx: None,
             outbound_task_shutdown_tx: None,
             listen_addr,
+            tls_config: None,
         }
     }
 
+    /// Authenticates and encrypts the channels to and from the other nodes with mutual TLS.
+    /// All the nodes must be configured with certificates signed by the same CA.
+    pub fn with_tls(mut self, tls_config: TlsConfig) -> Self {
+        self.tls_config = Some(tls_config);
+        self
+    }
+
     pub fn create_outbound_channel(
         &mut self,
         remote_peer_addr: SocketAddr,
@@ -144,8 +154,10 @@ impl NetworkController {
             .inbound_handler
             .lock()
             .unwrap()
-            .start(&self.inbound_rpc_runtime);
-        self.outbound_task_shutdown_tx = self.outbound_handler.start(&self.outbound_rpc_runtime);
+            .start(&self.inbound_rpc_runtime, self.tls_config.clone());
+        self.outbound_task_shutdown_tx = self
+            .outbound_handler
+            .start(&self.outbound_rpc_runtime, self.tls_config.as_ref());
     }
 
     // TODO: This is still not a very clean shutdown. We don't wait for the full shutdown after
@@ -167,7 +179,10 @@ impl NetworkController {
 
 #[cfg(test)]
 mod tests {
-    use crate::network_controller::{Message, NetworkController};
+    use crate::{
+        grpc_network_service::TlsConfig,
+        network_controller::{Message, NetworkController},
+    };
     use aptos_config::utils;
     use std::{
         net::{IpAddr, Ipv4Addr, SocketAddr},
@@ -176,6 +191,21 @@ mod tests {
 
     #[test]
     fn test_basic_send_receive() {
+        send_receive(None);
+    }
+
+    #[test]
+    fn test_basic_send_receive_with_tls() {
+        let tls_config = TlsConfig::new(
+            include_bytes!("../../test_certs/ca.pem"),
+            include_bytes!("../../test_certs/node.pem"),
+            include_bytes!("../../test_certs/node.key"),
+            "aptos-secure-net".to_string(),
+        );
+        send_receive(Some(tls_config));
+    }
+
+    fn send_receive(tls_config: Option<TlsConfig>) {
         let server_port1 = utils::get_available_port();
         let server_addr1 = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port1);
 
@@ -186,6 +216,10 @@ mod tests {
             NetworkController::new("test1".to_string(), server_addr1, 1000);
         let mut network_controller2 =
             NetworkController::new("test2".to_string(), server_addr2, 1000);
+        if let Some(tls_config) = tls_config {
+            network_controller1 = network_controller1.with_tls(tls_config.clone());
+            network_controller2 = network_controller2.with_tls(tls_config);
+        }
 
         let test1_sender =
             network_controller2.create_outbound_channel(server_addr1, "test1".to_string());
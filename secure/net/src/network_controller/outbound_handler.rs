@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    grpc_network_service::GRPCNetworkMessageServiceClientWrapper,
+    grpc_network_service::{GRPCNetworkMessageServiceClientWrapper, TlsConfig},
     network_controller::{
         inbound_handler::InboundHandler, metrics::NETWORK_HANDLER_TIMER, Message, MessageType,
     },
@@ -52,7 +52,11 @@ impl OutboundHandler {
             .push((receiver, remote_addr, MessageType::new(message_type)));
     }
 
-    pub fn start(&mut self, rt: &Runtime) -> Option<Sender<Message>> {
+    pub fn start(
+        &mut self,
+        rt: &Runtime,
+        tls_config: Option<&TlsConfig>,
+    ) -> Option<Sender<Message>> {
         if self.handlers.is_empty() {
             return None;
         }
@@ -71,7 +75,7 @@ impl OutboundHandler {
         self.remote_addresses.iter().for_each(|remote_addr| {
             grpc_clients.insert(
                 *remote_addr,
-                GRPCNetworkMessageServiceClientWrapper::new(rt, *remote_addr),
+                GRPCNetworkMessageServiceClientWrapper::new(rt, *remote_addr, tls_config),
             );
         });
 
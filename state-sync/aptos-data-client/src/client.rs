@@ -27,9 +27,12 @@ use aptos_id_generator::{IdGenerator, U64IdGenerator};
 use aptos_infallible::Mutex;
 use aptos_logger::{info, sample, sample::SampleRate, trace, warn};
 use aptos_network::{
-    application::{interface::NetworkClient, storage::PeersAndMetadata},
+    application::{
+        interface::NetworkClient, reputation::MisbehaviorSeverity, storage::PeersAndMetadata,
+    },
     protocols::network::RpcError,
 };
+use aptos_rate_limiter::byte_rate_limit::{ByteRateLimiter, ByteRateLimits};
 use aptos_storage_interface::DbReader;
 use aptos_storage_service_client::StorageServiceClient;
 use aptos_storage_service_types::{
@@ -95,6 +98,9 @@ pub struct AptosDataClient {
     data_client_config: Arc<AptosDataClientConfig>,
     /// The underlying AptosNet storage service client.
     storage_service_client: StorageServiceClient<NetworkClient<StorageServiceMessage>>,
+    /// The rate limiter for the bytes received from peers. The budget for
+    /// each response is reserved before the request is sent.
+    ingress_rate_limiter: Arc<ByteRateLimiter<PeerNetworkId>>,
     /// The state of the active subscription stream.
     active_subscription_state: Arc<Mutex<Option<SubscriptionState>>>,
     /// All of the data-client specific data we have on each network peer.
@@ -125,6 +131,11 @@ impl AptosDataClient {
             base_config,
             data_client_config: data_client_config.clone(),
             storage_service_client: storage_service_client.clone(),
+            ingress_rate_limiter: Arc::new(ByteRateLimiter::new(
+                "data_client_ingress",
+                data_client_config.max_ingress_bytes_per_sec,
+                data_client_config.max_ingress_bytes_per_sec_per_peer,
+            )),
             active_subscription_state: Arc::new(Mutex::new(None)),
             peer_states: Arc::new(PeerStates::new(data_client_config.clone())),
             global_summary_cache: Arc::new(ArcSwap::from(Arc::new(GlobalDataSummary::empty()))),
@@ -155,6 +166,12 @@ impl AptosDataClient {
         self.storage_service_client.get_peers_and_metadata()
     }
 
+    /// Returns the ingress byte rate limits of the data client. These
+    /// can be updated at runtime (e.g., via the admin service).
+    pub fn get_ingress_byte_rate_limits(&self) -> Arc<ByteRateLimits> {
+        self.ingress_rate_limiter.limits()
+    }
+
     /// Updates the metrics and logs for peer states. This includes
     /// peer priorities and request distributions.
     pub fn update_peer_metrics_and_logs(&self) {
@@ -224,7 +241,9 @@ impl AptosDataClient {
         // Get all connected peers
         let all_connected_peers = self.get_all_connected_peers()?;
 
-        // Garbage collect the disconnected peers
+        // Garbage collect the disconnected peers (and their rate limits)
+        self.ingress_rate_limiter
+            .garbage_collect_keys(|peer| all_connected_peers.contains(peer));
         self.peer_states
             .garbage_collect_peer_states(all_connected_peers);
 
@@ -772,6 +791,15 @@ impl AptosDataClient {
         );
         self.update_sent_request_metrics(peer, &request);
 
+        // Wait until the (maximum) response fits within the ingress limits
+        // before sending the request. This ensures the limits cap the bytes
+        // received, and not just the processing of responses.
+        let num_reserved_bytes =
+            request.max_response_bytes(self.data_client_config.max_response_bytes);
+        self.ingress_rate_limiter
+            .acquire(peer, num_reserved_bytes)
+            .await;
+
         // Send the request and process the result
         let result = self
             .storage_service_client
@@ -794,6 +822,15 @@ impl AptosDataClient {
                 // Update the received response metrics
                 self.update_received_response_metrics(peer, &request);
 
+                // Settle the reserved ingress bytes using the size of the
+                // response. The size is only known for compressed responses
+                // (without serializing the response again), so uncompressed
+                // responses are charged the reserved bytes.
+                if let Some(num_response_bytes) = response.get_num_compressed_bytes() {
+                    self.settle_ingress_bytes(peer, num_reserved_bytes, num_response_bytes)
+                        .await;
+                }
+
                 // For now, record all responses that at least pass the data
                 // client layer successfully. An alternative might also have the
                 // consumer notify both success and failure via the callback.
@@ -814,6 +851,9 @@ impl AptosDataClient {
                 Ok(Response::new(context, response))
             },
             Err(error) => {
+                // No data was received, so refund the reserved ingress bytes
+                self.ingress_rate_limiter.refund(peer, num_reserved_bytes);
+
                 // Convert network error and storage service error types into
                 // data client errors. Also categorize the error type for scoring
                 // purposes.
@@ -854,15 +894,55 @@ impl AptosDataClient {
         }
     }
 
+    /// Settles the ingress bytes reserved for a request with the number of
+    /// bytes actually received, i.e., waits for any additional bytes, or
+    /// refunds the unused bytes.
+    async fn settle_ingress_bytes(
+        &self,
+        peer: PeerNetworkId,
+        num_reserved_bytes: u64,
+        num_received_bytes: u64,
+    ) {
+        if num_received_bytes > num_reserved_bytes {
+            self.ingress_rate_limiter
+                .acquire(peer, num_received_bytes - num_reserved_bytes)
+                .await;
+        } else {
+            self.ingress_rate_limiter
+                .refund(peer, num_reserved_bytes - num_received_bytes);
+        }
+    }
+
     /// Updates the score of the peer who sent the response with the specified id
     fn notify_bad_response(
         &self,
         _id: ResponseId,
         peer: PeerNetworkId,
-        _request: &StorageServiceRequest,
+        request: &StorageServiceRequest,
         error_type: ErrorType,
     ) {
+        // Only malicious responses (e.g., proof verification failures) are
+        // reported to the (shared) peer reputation service. Other errors
+        // (e.g., timeouts and expired subscriptions) are common on healthy
+        // peers, so they only affect the peer score (used for peer selection).
+        let is_malicious = matches!(error_type, ErrorType::Malicious);
+
+        // Update the score of the peer (used for peer selection)
         self.peer_states.update_score_error(peer, error_type);
+
+        // Report the misbehavior to the (shared) peer reputation service
+        if is_malicious {
+            self.get_peers_and_metadata()
+                .get_peer_reputation()
+                .report_misbehavior(
+                    peer,
+                    MisbehaviorSeverity::High,
+                    &format!(
+                        "Malicious storage service response for: {}",
+                        request.get_label()
+                    ),
+                );
+        }
     }
 
     /// Creates a storage service request using the given data request
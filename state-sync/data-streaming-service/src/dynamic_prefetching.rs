@@ -693,6 +693,7 @@ mod test {
         let stream_request = StreamRequest::GetAllStates(GetAllStatesRequest {
             version: 0,
             start_index: 0,
+            end_index: None,
         });
 
         // Create and return the stream engine
@@ -189,6 +189,15 @@ pub struct StateStreamEngine {
 
 impl StateStreamEngine {
     fn new(request: &GetAllStatesRequest) -> Result<Self, Error> {
+        if let Some(end_index) = request.end_index {
+            if end_index < request.start_index {
+                return Err(Error::UnsupportedRequestEncountered(format!(
+                    "The end index must not be less than the start index! Request: {:?}",
+                    request
+                )));
+            }
+        }
+
         Ok(StateStreamEngine {
             request: request.clone(),
             state_num_requested: false,
@@ -222,6 +231,18 @@ impl StateStreamEngine {
             Error::UnexpectedErrorEncountered("Number of states is not initialized!".into())
         })
     }
+
+    /// Returns the last state index to fetch, bounded by the end index of the request
+    fn get_last_state_index(&self) -> Result<u64, Error> {
+        let last_state_index = self
+            .get_number_of_states()?
+            .checked_sub(1)
+            .ok_or_else(|| Error::IntegerOverflow("End state index has overflown!".into()))?;
+        Ok(match self.request.end_index {
+            Some(end_index) => end_index.min(last_state_index),
+            None => last_state_index,
+        })
+    }
 }
 
 impl DataStreamEngine for StateStreamEngine {
@@ -233,13 +254,15 @@ impl DataStreamEngine for StateStreamEngine {
         global_data_summary: &GlobalDataSummary,
         _unique_id_generator: Arc<U64IdGenerator>,
     ) -> Result<Vec<DataClientRequest>, Error> {
-        // Check if we should wait for the number of states to be returned
-        if self.number_of_states.is_none() && self.state_num_requested {
+        // Check if we should wait for the number of states to be returned,
+        // or if there's nothing to fetch in the requested range.
+        if (self.number_of_states.is_none() && self.state_num_requested) || self.stream_is_complete
+        {
             return Ok(vec![]);
         }
 
         // If we have the number of states, send the requests
-        if let Some(number_of_states) = self.number_of_states {
+        if self.number_of_states.is_some() {
             // Calculate the number of requests to send
             let num_requests_to_send = calculate_num_requests_to_send(
                 max_number_of_requests,
@@ -248,9 +271,7 @@ impl DataStreamEngine for StateStreamEngine {
             );
 
             // Calculate the end index
-            let end_state_index = number_of_states
-                .checked_sub(1)
-                .ok_or_else(|| Error::IntegerOverflow("End state index has overflown!".into()))?;
+            let end_state_index = self.get_last_state_index()?;
 
             // Create the client requests
             let client_requests = create_data_client_request_batch(
@@ -341,10 +362,7 @@ impl DataStreamEngine for StateStreamEngine {
                 })?;
 
                 // Check if the stream is complete
-                let last_stream_index = self
-                    .get_number_of_states()?
-                    .checked_sub(1)
-                    .ok_or_else(|| Error::IntegerOverflow("End index has overflown!".into()))?;
+                let last_stream_index = self.get_last_state_index()?;
                 if last_received_index >= last_stream_index {
                     self.stream_is_complete = true;
                 }
@@ -370,8 +388,14 @@ impl DataStreamEngine for StateStreamEngine {
                     );
                     self.state_num_requested = false;
 
-                    // Sanity check the response before saving it.
-                    if number_of_states < self.next_request_index {
+                    // Sanity check the response before saving it. If the requested range
+                    // is bounded and starts past the last state, there's nothing to stream.
+                    if self.request.end_index.is_some()
+                        && number_of_states <= self.next_request_index
+                    {
+                        self.number_of_states = Some(number_of_states);
+                        self.stream_is_complete = true;
+                    } else if number_of_states < self.next_request_index {
                         return Err(Error::NoDataToFetch(format!(
                             "The next state index to fetch is higher than the \
                             total number of states. Next index: {:?}, total states: {:?}",
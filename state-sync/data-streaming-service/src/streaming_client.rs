@@ -45,6 +45,19 @@ pub trait DataStreamingClient {
         start_index: Option<u64>,
     ) -> Result<DataStreamListener, Error>;
 
+    /// Fetches the state values at the specified version from `start_index`
+    /// to `end_index` (inclusive). If the version has fewer state values, the
+    /// stream ends at the last state value (and no state values are returned
+    /// if `start_index` is past it). The specified version must be an epoch
+    /// ending version, otherwise an error will be returned. State proofs are
+    /// at the same version.
+    async fn get_state_values_in_range(
+        &self,
+        version: Version,
+        start_index: u64,
+        end_index: u64,
+    ) -> Result<DataStreamListener, Error>;
+
     /// Fetches all epoch ending ledger infos starting at `start_epoch`
     /// (inclusive) and ending at the last known epoch advertised in the network.
     async fn get_all_epoch_ending_ledger_infos(
@@ -214,6 +227,7 @@ pub struct GetAllEpochEndingLedgerInfosRequest {
 pub struct GetAllStatesRequest {
     pub version: Version,
     pub start_index: u64,
+    pub end_index: Option<u64>, // If None, all states from the start index are fetched
 }
 
 /// A client request for fetching all transactions with proofs.
@@ -344,6 +358,21 @@ impl DataStreamingClient for StreamingServiceClient {
         let client_request = StreamRequest::GetAllStates(GetAllStatesRequest {
             version,
             start_index,
+            end_index: None,
+        });
+        self.send_request_and_await_response(client_request).await
+    }
+
+    async fn get_state_values_in_range(
+        &self,
+        version: u64,
+        start_index: u64,
+        end_index: u64,
+    ) -> Result<DataStreamListener, Error> {
+        let client_request = StreamRequest::GetAllStates(GetAllStatesRequest {
+            version,
+            start_index,
+            end_index: Some(end_index),
         });
         self.send_request_and_await_response(client_request).await
     }
@@ -809,6 +809,7 @@ mod streaming_service_tests {
         let stream_request = StreamRequest::GetAllStates(GetAllStatesRequest {
             version: MIN_ADVERTISED_STATES,
             start_index: 0,
+            end_index: None,
         });
         create_request_message_and_receiver(stream_request)
     }
@@ -532,6 +532,7 @@ fn transform_state_values_stream_notifications() {
     let stream_request = StreamRequest::GetAllStates(GetAllStatesRequest {
         version,
         start_index,
+        end_index: None,
     });
 
     // Create a global data summary with a single state range
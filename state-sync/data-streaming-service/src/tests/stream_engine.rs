@@ -3,10 +3,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    data_notification::{DataClientRequest, EpochEndingLedgerInfosRequest},
+    data_notification::{
+        DataClientRequest, EpochEndingLedgerInfosRequest, NumberOfStatesRequest,
+        StateValuesWithProofRequest,
+    },
     error::Error,
-    stream_engine::{DataStreamEngine, EpochEndingStreamEngine, StreamEngine},
-    streaming_client::{GetAllEpochEndingLedgerInfosRequest, StreamRequest},
+    stream_engine::{DataStreamEngine, EpochEndingStreamEngine, StateStreamEngine, StreamEngine},
+    streaming_client::{GetAllEpochEndingLedgerInfosRequest, GetAllStatesRequest, StreamRequest},
     tests::{
         utils,
         utils::{create_ledger_info, initialize_logger},
@@ -304,6 +307,151 @@ fn test_update_epoch_ending_stream_panic() {
         .unwrap();
 }
 
+#[test]
+fn test_bounded_state_stream_engine() {
+    // Create a state stream engine for a bounded range of state indices
+    let version = 100;
+    let mut stream_engine = create_state_stream_engine(version, 50, Some(149));
+
+    // Verify the number of states is requested first
+    let global_data_summary = create_state_chunk_sizes(40);
+    let client_requests = stream_engine
+        .create_data_client_requests(
+            10,
+            10,
+            0,
+            &global_data_summary,
+            create_notification_id_generator(),
+        )
+        .unwrap();
+    assert_eq!(client_requests, vec![DataClientRequest::NumberOfStates(
+        NumberOfStatesRequest { version }
+    )]);
+
+    // Process the number of states response
+    let result = stream_engine
+        .transform_client_response_into_notification(
+            &client_requests[0],
+            ResponsePayload::NumberOfStates(1000),
+            create_notification_id_generator(),
+        )
+        .unwrap();
+    assert!(result.is_none());
+
+    // Verify the state value requests stop at the end index
+    let client_requests = stream_engine
+        .create_data_client_requests(
+            10,
+            10,
+            0,
+            &global_data_summary,
+            create_notification_id_generator(),
+        )
+        .unwrap();
+    let expected_ranges = [(50, 89), (90, 129), (130, 149)];
+    assert_eq!(client_requests.len(), expected_ranges.len());
+    for (client_request, (start_index, end_index)) in client_requests.iter().zip(expected_ranges) {
+        assert_eq!(
+            client_request,
+            &DataClientRequest::StateValuesWithProof(StateValuesWithProofRequest {
+                version,
+                start_index,
+                end_index,
+            })
+        );
+    }
+    assert!(!stream_engine.is_stream_complete());
+}
+
+#[test]
+fn test_bounded_state_stream_engine_past_last_state() {
+    // Create a state stream engine for a range past the last state
+    let version = 100;
+    let mut stream_engine = create_state_stream_engine(version, 2000, Some(2999));
+
+    // Process the number of states response
+    let number_of_states_request =
+        DataClientRequest::NumberOfStates(NumberOfStatesRequest { version });
+    let result = stream_engine
+        .transform_client_response_into_notification(
+            &number_of_states_request,
+            ResponsePayload::NumberOfStates(1000),
+            create_notification_id_generator(),
+        )
+        .unwrap();
+    assert!(result.is_none());
+
+    // Verify the stream is complete and no requests are sent
+    assert!(stream_engine.is_stream_complete());
+    let client_requests = stream_engine
+        .create_data_client_requests(
+            10,
+            10,
+            0,
+            &create_state_chunk_sizes(40),
+            create_notification_id_generator(),
+        )
+        .unwrap();
+    assert!(client_requests.is_empty());
+
+    // Verify an unbounded stream starting past the last state is still an error
+    let mut stream_engine = create_state_stream_engine(version, 2000, None);
+    let result = stream_engine.transform_client_response_into_notification(
+        &number_of_states_request,
+        ResponsePayload::NumberOfStates(1000),
+        create_notification_id_generator(),
+    );
+    assert_matches!(result, Err(Error::NoDataToFetch(_)));
+}
+
+fn create_state_stream_engine(
+    version: u64,
+    start_index: u64,
+    end_index: Option<u64>,
+) -> StateStreamEngine {
+    initialize_logger();
+
+    // Create a states stream request
+    let stream_request = StreamRequest::GetAllStates(GetAllStatesRequest {
+        version,
+        start_index,
+        end_index,
+    });
+
+    // Create a global data summary advertising the states at the version
+    let mut global_data_summary = GlobalDataSummary::empty();
+    global_data_summary.advertised_data.states =
+        vec![CompleteDataRange::new(version, version).unwrap()];
+
+    // Create a new state stream engine
+    let data_streaming_config = DataStreamingServiceConfig::default();
+    match StreamEngine::new(
+        data_streaming_config,
+        &stream_request,
+        &global_data_summary.advertised_data,
+    )
+    .unwrap()
+    {
+        StreamEngine::StateStreamEngine(stream_engine) => stream_engine,
+        unexpected_engine => {
+            panic!(
+                "Expected state stream engine but got {:?}",
+                unexpected_engine
+            );
+        },
+    }
+}
+
+fn create_state_chunk_sizes(state_chunk_size: u64) -> GlobalDataSummary {
+    let mut optimal_chunk_sizes = OptimalChunkSizes::empty();
+    optimal_chunk_sizes.state_chunk_size = state_chunk_size;
+
+    let mut global_data_summary = GlobalDataSummary::empty();
+    global_data_summary.optimal_chunk_sizes = optimal_chunk_sizes;
+
+    global_data_summary
+}
+
 fn create_epoch_ending_stream_engine(start_epoch: u64, end_epoch: u64) -> EpochEndingStreamEngine {
     initialize_logger();
 
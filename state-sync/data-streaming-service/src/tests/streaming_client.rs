@@ -46,6 +46,7 @@ fn test_get_all_states() {
     let expected_request = StreamRequest::GetAllStates(GetAllStatesRequest {
         version: request_version,
         start_index: 0,
+        end_index: None,
     });
 
     // Spawn a new server thread to handle any stream requests
@@ -0,0 +1,272 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{error::Error, network::StorageServiceRequester, storage::TrustedStateStorage};
+use aptos_crypto::hash::CryptoHash;
+use aptos_logger::info;
+use aptos_storage_service_types::{
+    requests::{
+        DataRequest, EpochEndingLedgerInfoRequest, StateValueWithProofRequest,
+        StorageServiceRequest, TransactionsWithProofRequest,
+    },
+    responses::{StateValueWithProof, StorageServerSummary, StorageServiceResponse},
+};
+use aptos_types::{
+    epoch_change::EpochChangeProof,
+    ledger_info::LedgerInfoWithSignatures,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::{TransactionListWithProof, Version},
+    trusted_state::{TrustedState, TrustedStateChange},
+    waypoint::Waypoint,
+};
+
+/// A light client that follows the chain using only validator-signed ledger
+/// infos and proofs. All data is fetched from untrusted peers and verified
+/// against the latest trusted state before it is used.
+pub struct LightClient<Requester> {
+    latest_ledger_info: Option<LedgerInfoWithSignatures>, // The latest verified ledger info (if known)
+    requester: Requester,
+    storage: TrustedStateStorage,
+    trusted_state: TrustedState,
+    use_compression: bool,
+}
+
+impl<Requester: StorageServiceRequester> LightClient<Requester> {
+    /// Creates a new light client. If a trusted state has been persisted and
+    /// is not older than the given waypoint, the client resumes from it.
+    /// Otherwise, the client starts from the waypoint.
+    pub fn new(
+        requester: Requester,
+        storage: TrustedStateStorage,
+        waypoint: Waypoint,
+        use_compression: bool,
+    ) -> Result<Self, Error> {
+        let trusted_state = match storage.load()? {
+            Some(trusted_state) if trusted_state.version() >= waypoint.version() => trusted_state,
+            _ => TrustedState::from_epoch_waypoint(waypoint),
+        };
+
+        Ok(Self {
+            latest_ledger_info: None,
+            requester,
+            storage,
+            trusted_state,
+            use_compression,
+        })
+    }
+
+    /// Returns the latest verified ledger info. This is only known once the
+    /// client has synced (i.e., it is not persisted across restarts).
+    pub fn latest_ledger_info(&self) -> Option<&LedgerInfoWithSignatures> {
+        self.latest_ledger_info.as_ref()
+    }
+
+    /// Returns the current trusted state
+    pub fn trusted_state(&self) -> &TrustedState {
+        &self.trusted_state
+    }
+
+    /// Fetches the latest ledger info advertised by a peer and ratchets the
+    /// trusted state forward to it, verifying every epoch change on the way.
+    /// Returns the latest verified ledger info.
+    pub async fn sync_to_latest(&mut self) -> Result<LedgerInfoWithSignatures, Error> {
+        let storage_server_summary: StorageServerSummary = self
+            .send_request(DataRequest::GetStorageServerSummary)
+            .await?
+            .try_into()?;
+        let latest_ledger_info = storage_server_summary
+            .data_summary
+            .synced_ledger_info
+            .ok_or_else(|| {
+                Error::UnexpectedResponse("The peer did not advertise a synced ledger info!".into())
+            })?;
+
+        self.ratchet_to_ledger_info(&latest_ledger_info).await?;
+        Ok(latest_ledger_info)
+    }
+
+    /// Ratchets the trusted state forward to the given (unverified) ledger
+    /// info. Epoch ending ledger infos are fetched as required to verify all
+    /// epoch changes between the trusted state and the ledger info.
+    pub async fn ratchet_to_ledger_info(
+        &mut self,
+        latest_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<(), Error> {
+        let target_epoch = latest_ledger_info.ledger_info().next_block_epoch();
+        let mut start_epoch = self.get_trusted_epoch();
+
+        loop {
+            // Fetch the epoch ending ledger infos required to reach the target epoch
+            let epoch_change_proof = if start_epoch < target_epoch {
+                let mut epoch_change_proof = self
+                    .fetch_epoch_ending_ledger_infos(start_epoch, target_epoch - 1)
+                    .await?;
+                let last_ledger_info = epoch_change_proof
+                    .ledger_info_with_sigs
+                    .last()
+                    .ok_or_else(|| {
+                        Error::UnexpectedResponse("Received no epoch ending ledger infos!".into())
+                    })?
+                    .ledger_info();
+                let last_epoch = last_ledger_info.epoch();
+
+                // If the entire chunk is older than the trusted waypoint, skip it
+                if last_ledger_info.version() < self.trusted_state.version() {
+                    start_epoch = last_epoch + 1;
+                    continue;
+                }
+
+                // The response may have been truncated by the peer
+                epoch_change_proof.more = last_epoch + 1 < target_epoch;
+                epoch_change_proof
+            } else if self.trusted_state.is_epoch_waypoint() {
+                return Err(Error::VerificationError(format!(
+                    "The trusted waypoint is ahead of the ledger info! Waypoint version: {}, \
+                    ledger info version: {}",
+                    self.trusted_state.version(),
+                    latest_ledger_info.ledger_info().version()
+                )));
+            } else {
+                EpochChangeProof::new(vec![], false) // No epoch changes are required
+            };
+
+            // Verify the ledger info and epoch changes against the trusted state
+            let trusted_state_change = self
+                .trusted_state
+                .verify_and_ratchet_inner(latest_ledger_info, &epoch_change_proof)
+                .map_err(|error| Error::VerificationError(error.to_string()))?;
+            let verified_ledger_info = match &trusted_state_change {
+                TrustedStateChange::Epoch {
+                    new_state,
+                    latest_epoch_change_li,
+                } if new_state.version() != latest_ledger_info.ledger_info().version() => {
+                    (*latest_epoch_change_li).clone()
+                },
+                _ => latest_ledger_info.clone(),
+            };
+
+            // Persist the new trusted state and update the latest ledger info
+            if let Some(new_trusted_state) = trusted_state_change.new_state() {
+                self.storage.save(&new_trusted_state)?;
+                info!(
+                    "Ratcheted the light client to a new trusted state! Version: {}, epoch: {}",
+                    verified_ledger_info.ledger_info().version(),
+                    verified_ledger_info.ledger_info().epoch()
+                );
+                self.trusted_state = new_trusted_state;
+            }
+            let synced_to_target = verified_ledger_info.ledger_info().version()
+                == latest_ledger_info.ledger_info().version();
+            self.latest_ledger_info = Some(verified_ledger_info);
+
+            // Continue ratcheting until the target ledger info is verified
+            if synced_to_target {
+                return Ok(());
+            }
+            start_epoch = self.get_trusted_epoch();
+        }
+    }
+
+    /// Fetches the state value for the given key at the specified version and
+    /// proves it against the latest verified ledger info. Returns `None` if
+    /// the proof shows the key does not exist at the version.
+    ///
+    /// Note: the version must be a state checkpoint version (e.g., the end of
+    /// a block), as the state root is only committed at these versions.
+    pub async fn prove_state_value(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<Option<StateValue>, Error> {
+        let ledger_info = self.latest_ledger_info.as_ref().ok_or_else(|| {
+            Error::VerificationError("The light client has no verified ledger info!".into())
+        })?;
+        let proof_version = ledger_info.ledger_info().version();
+        if version > proof_version {
+            return Err(Error::VerificationError(format!(
+                "The requested version ({}) is newer than the latest verified version ({})!",
+                version, proof_version
+            )));
+        }
+
+        // Fetch the transaction info at the version and verify it to identify the state root
+        let transaction_list_with_proof: TransactionListWithProof = self
+            .send_request(DataRequest::GetTransactionsWithProof(
+                TransactionsWithProofRequest {
+                    proof_version,
+                    start_version: version,
+                    end_version: version,
+                    include_events: false,
+                },
+            ))
+            .await?
+            .try_into()?;
+        transaction_list_with_proof
+            .verify(ledger_info.ledger_info(), Some(version))
+            .map_err(|error| Error::VerificationError(error.to_string()))?;
+        let state_root_hash = transaction_list_with_proof
+            .proof
+            .transaction_infos
+            .first()
+            .and_then(|transaction_info| transaction_info.state_checkpoint_hash())
+            .ok_or_else(|| {
+                Error::VerificationError(format!(
+                    "Version {} is not a state checkpoint version!",
+                    version
+                ))
+            })?;
+
+        // Fetch the state value and verify the proof against the state root
+        let (state_value, sparse_merkle_proof): StateValueWithProof = self
+            .send_request(DataRequest::GetStateValueWithProof(
+                StateValueWithProofRequest {
+                    version,
+                    state_key: state_key.clone(),
+                },
+            ))
+            .await?
+            .try_into()?;
+        sparse_merkle_proof
+            .verify(state_root_hash, state_key.hash(), state_value.as_ref())
+            .map_err(|error| Error::VerificationError(error.to_string()))?;
+
+        Ok(state_value)
+    }
+
+    /// Fetches the epoch ending ledger infos for the given epoch range
+    async fn fetch_epoch_ending_ledger_infos(
+        &self,
+        start_epoch: u64,
+        expected_end_epoch: u64,
+    ) -> Result<EpochChangeProof, Error> {
+        let epoch_change_proof = self
+            .send_request(DataRequest::GetEpochEndingLedgerInfos(
+                EpochEndingLedgerInfoRequest {
+                    start_epoch,
+                    expected_end_epoch,
+                },
+            ))
+            .await?
+            .try_into()?;
+        Ok(epoch_change_proof)
+    }
+
+    /// Returns the first epoch not yet verified by the trusted state. If the
+    /// trusted state is a waypoint, the epoch is unknown, so we start at
+    /// epoch 0 and skip any epoch ending ledger infos older than the waypoint.
+    fn get_trusted_epoch(&self) -> u64 {
+        match &self.trusted_state {
+            TrustedState::EpochWaypoint(_) => 0,
+            TrustedState::EpochState { epoch_state, .. } => epoch_state.epoch,
+        }
+    }
+
+    /// Sends the given data request and returns the (unverified) response
+    async fn send_request(
+        &self,
+        data_request: DataRequest,
+    ) -> Result<StorageServiceResponse, Error> {
+        let request = StorageServiceRequest::new(data_request, self.use_compression);
+        self.requester.send_request(request).await
+    }
+}
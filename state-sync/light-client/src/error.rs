@@ -0,0 +1,22 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use thiserror::Error;
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Network error: {0}")]
+    NetworkError(String),
+    #[error("Failed to access the trusted state storage: {0}")]
+    StorageError(String),
+    #[error("Unexpected response received from a peer: {0}")]
+    UnexpectedResponse(String),
+    #[error("Failed to verify the data received from a peer: {0}")]
+    VerificationError(String),
+}
+
+impl From<aptos_storage_service_types::responses::Error> for Error {
+    fn from(error: aptos_storage_service_types::responses::Error) -> Self {
+        Error::UnexpectedResponse(error.to_string())
+    }
+}
@@ -0,0 +1,23 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! A light client that follows the chain using only ledger infos and proofs.
+//!
+//! Starting from a trusted waypoint, the light client fetches epoch ending
+//! ledger infos from (untrusted) peers and verifies the validator signatures
+//! across each epoch change. Only the latest trusted state is persisted. Once
+//! synced, the client can prove individual state values at any version covered
+//! by its latest verified ledger info.
+
+pub mod client;
+pub mod error;
+pub mod network;
+pub mod storage;
+
+#[cfg(test)]
+mod tests;
+
+pub use client::LightClient;
+pub use error::Error;
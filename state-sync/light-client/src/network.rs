@@ -0,0 +1,71 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::Error;
+use aptos_network::application::interface::NetworkClientInterface;
+use aptos_storage_service_client::StorageServiceClient;
+use aptos_storage_service_types::{
+    requests::StorageServiceRequest, responses::StorageServiceResponse, StorageServiceMessage,
+};
+use async_trait::async_trait;
+use rand::seq::IteratorRandom;
+use std::time::Duration;
+
+/// The interface used by the light client to send storage service requests.
+/// Responses are never trusted, so implementations are free to send each
+/// request to any peer.
+#[async_trait]
+pub trait StorageServiceRequester: Send + Sync + 'static {
+    /// Sends the given request and returns the (unverified) response
+    async fn send_request(
+        &self,
+        request: StorageServiceRequest,
+    ) -> Result<StorageServiceResponse, Error>;
+}
+
+/// A storage service requester that sends each request to a randomly
+/// selected peer (amongst all currently connected peers).
+#[derive(Clone)]
+pub struct NetworkRequester<NetworkClient> {
+    request_timeout: Duration,
+    storage_service_client: StorageServiceClient<NetworkClient>,
+}
+
+impl<NetworkClient: NetworkClientInterface<StorageServiceMessage>> NetworkRequester<NetworkClient> {
+    pub fn new(
+        storage_service_client: StorageServiceClient<NetworkClient>,
+        request_timeout_ms: u64,
+    ) -> Self {
+        Self {
+            request_timeout: Duration::from_millis(request_timeout_ms),
+            storage_service_client,
+        }
+    }
+}
+
+#[async_trait]
+impl<NetworkClient: NetworkClientInterface<StorageServiceMessage> + 'static> StorageServiceRequester
+    for NetworkRequester<NetworkClient>
+{
+    async fn send_request(
+        &self,
+        request: StorageServiceRequest,
+    ) -> Result<StorageServiceResponse, Error> {
+        // Select a random peer to service the request
+        let peer = self
+            .storage_service_client
+            .get_available_peers()
+            .map_err(|error| Error::NetworkError(error.to_string()))?
+            .into_iter()
+            .choose(&mut rand::thread_rng())
+            .ok_or_else(|| {
+                Error::NetworkError("No connected peers are available to service requests!".into())
+            })?;
+
+        // Send the request to the peer
+        self.storage_service_client
+            .send_request(peer, self.request_timeout, request)
+            .await
+            .map_err(|error| Error::NetworkError(error.to_string()))
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::Error;
+use aptos_types::trusted_state::TrustedState;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The name of the file holding the latest trusted state
+const TRUSTED_STATE_FILE_NAME: &str = "trusted_state.bcs";
+
+/// A simple file-backed storage that persists the latest trusted state of
+/// the light client. Only a single trusted state is stored, i.e., each save
+/// overwrites the previously stored state.
+#[derive(Clone, Debug)]
+pub struct TrustedStateStorage {
+    file_path: PathBuf,
+}
+
+impl TrustedStateStorage {
+    pub fn new(storage_dir: &Path) -> Self {
+        Self {
+            file_path: storage_dir.join(TRUSTED_STATE_FILE_NAME),
+        }
+    }
+
+    /// Returns the persisted trusted state (if one exists)
+    pub fn load(&self) -> Result<Option<TrustedState>, Error> {
+        if !self.file_path.exists() {
+            return Ok(None);
+        }
+
+        let bytes = fs::read(&self.file_path).map_err(|error| {
+            Error::StorageError(format!(
+                "Failed to read the trusted state file {:?}: {:?}",
+                self.file_path, error
+            ))
+        })?;
+        let trusted_state = bcs::from_bytes(&bytes).map_err(|error| {
+            Error::StorageError(format!(
+                "Failed to deserialize the trusted state: {:?}",
+                error
+            ))
+        })?;
+        Ok(Some(trusted_state))
+    }
+
+    /// Persists the given trusted state, replacing any previous state
+    pub fn save(&self, trusted_state: &TrustedState) -> Result<(), Error> {
+        let bytes = bcs::to_bytes(trusted_state).map_err(|error| {
+            Error::StorageError(format!(
+                "Failed to serialize the trusted state: {:?}",
+                error
+            ))
+        })?;
+
+        // Write the state to a temporary file and rename it. This ensures
+        // the stored state is never partially written (e.g., on a crash).
+        let temp_file_path = self.file_path.with_extension("tmp");
+        if let Some(storage_dir) = self.file_path.parent() {
+            fs::create_dir_all(storage_dir).map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to create the storage directory {:?}: {:?}",
+                    storage_dir, error
+                ))
+            })?;
+        }
+        fs::write(&temp_file_path, bytes)
+            .and_then(|_| fs::rename(&temp_file_path, &self.file_path))
+            .map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to write the trusted state file {:?}: {:?}",
+                    self.file_path, error
+                ))
+            })
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    error::Error,
+    storage::TrustedStateStorage,
+    tests::utils::{create_signer, sign_ledger_info, MockRequester, TestChain},
+    LightClient,
+};
+use aptos_temppath::TempPath;
+use aptos_types::{state_store::state_key::StateKey, waypoint::Waypoint};
+use claims::{assert_matches, assert_none};
+
+#[tokio::test]
+async fn test_sync_across_epochs() {
+    // Test different epoch chunk sizes (to verify truncated responses are handled)
+    for max_epoch_chunk_size in [1, 3, 100] {
+        // Create a test chain and the light client
+        let test_chain = TestChain::new(10);
+        let temp_path = TempPath::new();
+        let mut light_client = create_light_client(&test_chain, &temp_path, max_epoch_chunk_size);
+
+        // Sync to the latest ledger info
+        let latest_ledger_info = light_client.sync_to_latest().await.unwrap();
+        assert_eq!(latest_ledger_info, test_chain.latest_ledger_info);
+
+        // Verify the trusted state and latest ledger info
+        let trusted_state = light_client.trusted_state().clone();
+        assert_eq!(
+            trusted_state.version(),
+            latest_ledger_info.ledger_info().version()
+        );
+        assert_eq!(
+            light_client.latest_ledger_info(),
+            Some(&test_chain.latest_ledger_info)
+        );
+
+        // Verify the latest trusted state was persisted
+        let storage = TrustedStateStorage::new(temp_path.path());
+        assert_eq!(storage.load().unwrap(), Some(trusted_state));
+    }
+}
+
+#[tokio::test]
+async fn test_sync_resumes_from_persisted_state() {
+    // Create a test chain and sync the light client to the latest ledger info
+    let test_chain = TestChain::new(5);
+    let temp_path = TempPath::new();
+    let mut light_client = create_light_client(&test_chain, &temp_path, 2);
+    light_client.sync_to_latest().await.unwrap();
+    let trusted_state = light_client.trusted_state().clone();
+
+    // Create a new light client and verify it resumes from the persisted state
+    let mut light_client = create_light_client(&test_chain, &temp_path, 2);
+    assert_eq!(light_client.trusted_state(), &trusted_state);
+    assert_none!(light_client.latest_ledger_info());
+
+    // Sync to the same ledger info and verify the trusted state is unchanged
+    light_client.sync_to_latest().await.unwrap();
+    assert_eq!(light_client.trusted_state(), &trusted_state);
+    assert_eq!(
+        light_client.latest_ledger_info(),
+        Some(&test_chain.latest_ledger_info)
+    );
+
+    // Extend the chain and verify the light client ratchets from the persisted state
+    let extended_test_chain = TestChain::new(8);
+    let mut light_client = create_light_client(&extended_test_chain, &temp_path, 2);
+    light_client.sync_to_latest().await.unwrap();
+    assert_eq!(
+        light_client.trusted_state().version(),
+        extended_test_chain
+            .latest_ledger_info
+            .ledger_info()
+            .version()
+    );
+}
+
+#[tokio::test]
+async fn test_sync_invalid_signatures() {
+    // Create a test chain where the latest ledger info is signed by the wrong validator
+    let mut test_chain = TestChain::new(4);
+    let latest_ledger_info = test_chain.latest_ledger_info.ledger_info().clone();
+    test_chain.latest_ledger_info = sign_ledger_info(&create_signer(100), latest_ledger_info);
+
+    // Verify the light client fails to sync to the latest ledger info
+    let temp_path = TempPath::new();
+    let mut light_client = create_light_client(&test_chain, &temp_path, 100);
+    let error = light_client.sync_to_latest().await.unwrap_err();
+    assert_matches!(error, Error::VerificationError(_));
+
+    // Verify the trusted state was not updated
+    assert!(light_client.trusted_state().is_epoch_waypoint());
+    assert_none!(light_client.latest_ledger_info());
+    assert_none!(TrustedStateStorage::new(temp_path.path()).load().unwrap());
+}
+
+#[tokio::test]
+async fn test_prove_state_value_without_ledger_info() {
+    // Create a test chain and the light client
+    let test_chain = TestChain::new(2);
+    let temp_path = TempPath::new();
+    let light_client = create_light_client(&test_chain, &temp_path, 100);
+
+    // Verify state values cannot be proven before the light client has synced
+    let error = light_client
+        .prove_state_value(&StateKey::raw(b"test_key"), 0)
+        .await
+        .unwrap_err();
+    assert_matches!(error, Error::VerificationError(_));
+}
+
+/// Creates a light client (starting at the genesis waypoint of the given chain)
+fn create_light_client(
+    test_chain: &TestChain,
+    temp_path: &TempPath,
+    max_epoch_chunk_size: u64,
+) -> LightClient<MockRequester> {
+    let genesis_waypoint = Waypoint::new_epoch_boundary(test_chain.genesis_ledger_info()).unwrap();
+    LightClient::new(
+        MockRequester::new(test_chain.clone(), max_epoch_chunk_size),
+        TrustedStateStorage::new(temp_path.path()),
+        genesis_waypoint,
+        false,
+    )
+    .unwrap()
+}
@@ -0,0 +1,34 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{storage::TrustedStateStorage, tests::utils::TestChain};
+use aptos_temppath::TempPath;
+use aptos_types::{trusted_state::TrustedState, waypoint::Waypoint};
+
+#[test]
+fn test_trusted_state_storage() {
+    // Create the trusted state storage
+    let temp_path = TempPath::new();
+    let storage = TrustedStateStorage::new(temp_path.path());
+
+    // Verify that no trusted state is stored initially
+    assert_eq!(storage.load().unwrap(), None);
+
+    // Save a trusted state and verify it is loaded correctly
+    let test_chain = TestChain::new(2);
+    let genesis_waypoint = Waypoint::new_epoch_boundary(test_chain.genesis_ledger_info()).unwrap();
+    let trusted_state = TrustedState::from_epoch_waypoint(genesis_waypoint);
+    storage.save(&trusted_state).unwrap();
+    assert_eq!(storage.load().unwrap(), Some(trusted_state));
+
+    // Save a new trusted state and verify it replaces the old one
+    let epoch_ending_ledger_info = test_chain.epoch_ending_ledger_infos[1].ledger_info();
+    let new_trusted_state =
+        TrustedState::from_epoch_waypoint(Waypoint::new_any(epoch_ending_ledger_info));
+    storage.save(&new_trusted_state).unwrap();
+    assert_eq!(storage.load().unwrap(), Some(new_trusted_state.clone()));
+
+    // Verify the state is persisted across storage instances
+    let storage = TrustedStateStorage::new(temp_path.path());
+    assert_eq!(storage.load().unwrap(), Some(new_trusted_state));
+}
@@ -0,0 +1,173 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{error::Error, network::StorageServiceRequester};
+use aptos_crypto::HashValue;
+use aptos_storage_service_types::{
+    requests::{DataRequest, StorageServiceRequest},
+    responses::{DataResponse, DataSummary, StorageServerSummary, StorageServiceResponse},
+};
+use aptos_types::{
+    aggregate_signature::{AggregateSignature, PartialSignatures},
+    block_info::BlockInfo,
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    transaction::Version,
+    validator_signer::ValidatorSigner,
+    validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier},
+};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+
+/// The number of versions in each test epoch
+const VERSIONS_PER_EPOCH: u64 = 100;
+
+/// A simple test chain holding all epoch ending ledger infos
+/// (starting at genesis) and the latest ledger info. Test chains are
+/// deterministic, so a longer chain always extends a shorter one.
+#[derive(Clone)]
+pub struct TestChain {
+    pub epoch_ending_ledger_infos: Vec<LedgerInfoWithSignatures>,
+    pub latest_ledger_info: LedgerInfoWithSignatures,
+}
+
+impl TestChain {
+    /// Creates a new chain with the specified number of epoch changes
+    /// (including genesis). Each epoch is run by a single validator.
+    pub fn new(num_epoch_changes: u64) -> Self {
+        let epoch_ending_ledger_infos = (0..num_epoch_changes)
+            .map(|epoch| {
+                create_ledger_info(
+                    epoch,
+                    epoch * VERSIONS_PER_EPOCH,
+                    Some(create_epoch_state(epoch + 1)),
+                )
+            })
+            .collect();
+        let latest_ledger_info = create_ledger_info(
+            num_epoch_changes,
+            (num_epoch_changes - 1) * VERSIONS_PER_EPOCH + VERSIONS_PER_EPOCH / 2,
+            None,
+        );
+
+        Self {
+            epoch_ending_ledger_infos,
+            latest_ledger_info,
+        }
+    }
+
+    /// Returns the genesis ledger info
+    pub fn genesis_ledger_info(&self) -> &LedgerInfo {
+        self.epoch_ending_ledger_infos[0].ledger_info()
+    }
+}
+
+/// A mock requester that serves data from a test chain, returning at
+/// most `max_epoch_chunk_size` epoch ending ledger infos per response.
+pub struct MockRequester {
+    max_epoch_chunk_size: u64,
+    test_chain: TestChain,
+}
+
+impl MockRequester {
+    pub fn new(test_chain: TestChain, max_epoch_chunk_size: u64) -> Self {
+        Self {
+            max_epoch_chunk_size,
+            test_chain,
+        }
+    }
+}
+
+#[async_trait]
+impl StorageServiceRequester for MockRequester {
+    async fn send_request(
+        &self,
+        request: StorageServiceRequest,
+    ) -> Result<StorageServiceResponse, Error> {
+        let data_response = match request.data_request {
+            DataRequest::GetStorageServerSummary => {
+                DataResponse::StorageServerSummary(StorageServerSummary {
+                    data_summary: DataSummary {
+                        synced_ledger_info: Some(self.test_chain.latest_ledger_info.clone()),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+            },
+            DataRequest::GetEpochEndingLedgerInfos(request) => {
+                let end_epoch = request
+                    .expected_end_epoch
+                    .min(request.start_epoch + self.max_epoch_chunk_size - 1);
+                let ledger_info_with_sigs = self.test_chain.epoch_ending_ledger_infos
+                    [request.start_epoch as usize..=end_epoch as usize]
+                    .to_vec();
+                DataResponse::EpochEndingLedgerInfos(EpochChangeProof::new(
+                    ledger_info_with_sigs,
+                    false,
+                ))
+            },
+            data_request => {
+                return Err(Error::NetworkError(format!(
+                    "Unexpected request: {:?}",
+                    data_request
+                )))
+            },
+        };
+        Ok(StorageServiceResponse::new(data_response, request.use_compression).unwrap())
+    }
+}
+
+/// Returns the signer of the given epoch
+pub fn create_signer(epoch: u64) -> ValidatorSigner {
+    ValidatorSigner::random([epoch as u8; 32])
+}
+
+/// Creates the epoch state for the given epoch
+fn create_epoch_state(epoch: u64) -> EpochState {
+    EpochState::new(epoch, create_validator_verifier(&create_signer(epoch)))
+}
+
+/// Creates a validator verifier holding only the given signer
+fn create_validator_verifier(signer: &ValidatorSigner) -> ValidatorVerifier {
+    let validator_info = ValidatorConsensusInfo::new(signer.author(), signer.public_key(), 1);
+    ValidatorVerifier::new(vec![validator_info])
+}
+
+/// Creates a ledger info (signed by the validator of the epoch) at the given version
+pub fn create_ledger_info(
+    epoch: u64,
+    version: Version,
+    next_epoch_state: Option<EpochState>,
+) -> LedgerInfoWithSignatures {
+    let block_info = BlockInfo::new(
+        epoch,
+        0,
+        HashValue::zero(),
+        HashValue::zero(),
+        version,
+        0,
+        next_epoch_state,
+    );
+    let ledger_info = LedgerInfo::new(block_info, HashValue::zero());
+
+    // The genesis ledger info is verified using the waypoint
+    if epoch == 0 {
+        return LedgerInfoWithSignatures::new(ledger_info, AggregateSignature::empty());
+    }
+
+    sign_ledger_info(&create_signer(epoch), ledger_info)
+}
+
+/// Signs the ledger info using the given signer
+pub fn sign_ledger_info(
+    signer: &ValidatorSigner,
+    ledger_info: LedgerInfo,
+) -> LedgerInfoWithSignatures {
+    let signature = signer.sign(&ledger_info).unwrap();
+    let partial_signatures = PartialSignatures::new(BTreeMap::from([(signer.author(), signature)]));
+    let aggregate_signature = create_validator_verifier(signer)
+        .aggregate_signatures(&partial_signatures)
+        .unwrap();
+    LedgerInfoWithSignatures::new(ledger_info, aggregate_signature)
+}
@@ -9,6 +9,7 @@ use crate::{
     metadata_storage::MetadataStorageInterface,
     metrics,
     metrics::ExecutingComponent,
+    state_snapshot_downloader::PartitionedStateDownloader,
     storage_synchronizer::{NotificationMetadata, StorageSynchronizerInterface},
     utils,
     utils::{OutputFallbackHandler, SpeculativeStreamState, PENDING_DATA_LOG_FREQ_SECS},
@@ -304,6 +305,9 @@ pub struct Bootstrapper<MetadataStorage, StorageSyncer, StreamingClient> {
     // The storage to write metadata about the syncing progress
     metadata_storage: MetadataStorage,
 
+    // The downloader fetching state snapshot partitions concurrently (if enabled)
+    partitioned_state_downloader: Option<PartitionedStateDownloader>,
+
     // The handler for output fallback behaviour
     output_fallback_handler: OutputFallbackHandler,
 
@@ -356,6 +360,7 @@ impl<
             bootstrapped: false,
             driver_configuration,
             metadata_storage,
+            partitioned_state_downloader: None,
             output_fallback_handler,
             speculative_stream_state: None,
             streaming_client,
@@ -366,9 +371,14 @@ impl<
         }
     }
 
-    /// Returns the bootstrapping mode of the node
+    /// Returns the bootstrapping mode of the node. Nodes restoring from a
+    /// backup have already restored it into storage before state sync starts,
+    /// so they only have to execute or apply the rest of the data from peers.
     fn get_bootstrapping_mode(&self) -> BootstrappingMode {
-        self.driver_configuration.config.bootstrapping_mode
+        match self.driver_configuration.config.bootstrapping_mode {
+            BootstrappingMode::RestoreFromBackup => BootstrappingMode::ExecuteOrApplyFromGenesis,
+            bootstrapping_mode => bootstrapping_mode,
+        }
     }
 
     /// Returns true iff the node has already completed bootstrapping
@@ -428,7 +438,10 @@ impl<
             ));
         }
 
-        if self.active_data_stream.is_some() {
+        if self.partitioned_state_downloader.is_some() {
+            // We're downloading state snapshot partitions. Process any downloaded chunks!
+            self.process_partitioned_state_values().await?;
+        } else if self.active_data_stream.is_some() {
             // We have an active data stream. Process any notifications!
             self.process_active_stream_notifications().await?;
         } else if self.storage_synchronizer.pending_storage_data() {
@@ -663,6 +676,73 @@ impl<
         Ok(())
     }
 
+    /// Drives the partitioned state snapshot download and processes
+    /// the downloaded chunks (in order) once they become available.
+    async fn process_partitioned_state_values(&mut self) -> Result<(), Error> {
+        // Prune the chunks that have already been persisted by the snapshot receiver
+        let ledger_info_to_sync = self.get_ledger_info_to_sync()?;
+        if self.metadata_storage.previous_snapshot_sync_target()?
+            == Some(ledger_info_to_sync.clone())
+        {
+            let last_persisted_state_value_index = self
+                .metadata_storage
+                .get_last_persisted_state_value_index(&ledger_info_to_sync)?;
+            if let Some(partitioned_state_downloader) = self.partitioned_state_downloader.as_mut() {
+                partitioned_state_downloader.prune_persisted_chunks(
+                    &self.metadata_storage,
+                    last_persisted_state_value_index,
+                )?;
+            }
+        }
+
+        // Drive the partition downloads
+        let next_state_index_to_process = self.state_value_syncer.next_state_index_to_process;
+        if let Some(partitioned_state_downloader) = self.partitioned_state_downloader.as_mut() {
+            partitioned_state_downloader
+                .drive_progress(
+                    &self.metadata_storage,
+                    &mut self.streaming_client,
+                    next_state_index_to_process,
+                )
+                .await?;
+        }
+
+        // Process the downloaded chunks in order
+        let state_sync_driver_config = &self.driver_configuration.config;
+        for _ in 0..state_sync_driver_config.max_consecutive_stream_notifications {
+            let next_state_index_to_process = self.state_value_syncer.next_state_index_to_process;
+            let next_chunk_to_process = match self.partitioned_state_downloader.as_mut() {
+                Some(partitioned_state_downloader) => partitioned_state_downloader
+                    .next_chunk_to_process(&self.metadata_storage, next_state_index_to_process)?,
+                None => return Ok(()), // The downloader was reset
+            };
+            match next_chunk_to_process {
+                Some((notification_id, state_value_chunk_with_proof)) => {
+                    self.process_state_values_payload(
+                        notification_id,
+                        state_value_chunk_with_proof,
+                    )
+                    .await?;
+                },
+                None => break, // The next chunk hasn't been downloaded yet
+            }
+        }
+
+        // If all chunks have been processed, the download is complete
+        let next_state_index_to_process = self.state_value_syncer.next_state_index_to_process;
+        if let Some(partitioned_state_downloader) = self.partitioned_state_downloader.as_ref() {
+            if partitioned_state_downloader.is_complete(next_state_index_to_process) {
+                info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                    "All state snapshot partitions have been processed! Last index: {:?}",
+                    next_state_index_to_process - 1
+                )));
+                self.reset_active_stream(None).await?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Fetches state values (as required to bootstrap the node)
     async fn fetch_missing_state_values(
         &mut self,
@@ -721,6 +801,33 @@ impl<
             // Fetch the missing state values
             self.state_value_syncer
                 .update_next_state_index_to_process(next_state_index_to_process);
+            let state_sync_driver_config = &self.driver_configuration.config;
+            if state_sync_driver_config.max_concurrent_state_snapshot_partitions > 1 {
+                // Download the state snapshot partitions concurrently
+                let expected_root_hash = self
+                    .get_transaction_output_to_sync()?
+                    .proof
+                    .transaction_infos
+                    .first()
+                    .ok_or_else(|| {
+                        Error::UnexpectedError("Target transaction info does not exist!".into())
+                    })?
+                    .ensure_state_checkpoint_hash()
+                    .map_err(|error| {
+                        Error::UnexpectedError(format!(
+                            "State checkpoint must exist! Error: {:?}",
+                            error
+                        ))
+                    })?;
+                self.partitioned_state_downloader = Some(PartitionedStateDownloader::new(
+                    target_ledger_info_version,
+                    expected_root_hash,
+                    state_sync_driver_config.max_concurrent_state_snapshot_partitions,
+                    state_sync_driver_config.state_snapshot_partition_size,
+                    state_sync_driver_config.max_consecutive_stream_notifications,
+                ));
+                return Ok(());
+            }
             self.streaming_client
                 .get_all_state_values(
                     target_ledger_info_version,
@@ -1538,6 +1645,19 @@ impl<
         &mut self,
         notification_and_feedback: Option<NotificationAndFeedback>,
     ) -> Result<(), Error> {
+        if let Some(mut partitioned_state_downloader) = self.partitioned_state_downloader.take() {
+            // Discard the downloaded chunk that caused the reset (if any)
+            if let Some(notification_and_feedback) = &notification_and_feedback {
+                partitioned_state_downloader.invalidate_chunk(
+                    &self.metadata_storage,
+                    notification_and_feedback.notification_id,
+                )?;
+            }
+            partitioned_state_downloader
+                .terminate_all_streams(&mut self.streaming_client)
+                .await?;
+        }
+
         if let Some(active_data_stream) = &self.active_data_stream {
             let data_stream_id = active_data_stream.data_stream_id;
             utils::terminate_stream_with_feedback(
@@ -14,6 +14,7 @@ mod logging;
 pub mod metadata_storage;
 pub mod metrics;
 mod notification_handlers;
+mod state_snapshot_downloader;
 mod storage_synchronizer;
 mod utils;
 
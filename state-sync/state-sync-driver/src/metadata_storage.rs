@@ -3,7 +3,9 @@
 
 use crate::{
     error::Error,
-    metadata_storage::database_schema::{MetadataKey, MetadataSchema, MetadataValue},
+    metadata_storage::database_schema::{
+        MetadataKey, MetadataSchema, MetadataValue, StateSnapshotChunk, StateSnapshotChunkSchema,
+    },
 };
 use anyhow::{anyhow, Result};
 use aptos_logger::prelude::*;
@@ -12,7 +14,10 @@ use aptos_schemadb::{
     schema::{KeyCodec, ValueCodec},
     ColumnFamilyName, Options, SchemaBatch, DB,
 };
-use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures, state_store::state_value::StateValueChunkWithProof,
+    transaction::Version,
+};
 use serde::{Deserialize, Serialize};
 use std::{path::Path, sync::Arc, time::Instant};
 
@@ -47,6 +52,52 @@ pub trait MetadataStorageInterface {
         last_persisted_state_value_index: u64,
         snapshot_sync_completed: bool,
     ) -> Result<(), Error>;
+
+    /// Returns the download progress of the given state snapshot partition
+    /// for the snapshot at the target version. If the partition hasn't been
+    /// downloaded for the target version, None is returned.
+    fn get_state_snapshot_partition_progress(
+        &self,
+        target_version: Version,
+        partition: u64,
+    ) -> Result<Option<StateSnapshotPartitionProgress>, Error>;
+
+    /// Persists a downloaded (but not yet verified) chunk of state values for
+    /// the given partition, along with the new download progress of the partition.
+    fn save_state_snapshot_partition_chunk(
+        &self,
+        target_version: Version,
+        partition: u64,
+        state_value_chunk_with_proof: StateValueChunkWithProof,
+        partition_completed: bool,
+    ) -> Result<(), Error>;
+
+    /// Returns the downloaded chunk of the snapshot at the target version that
+    /// contains the given state value index. If no such chunk exists, None is returned.
+    fn get_state_snapshot_chunk(
+        &self,
+        target_version: Version,
+        state_value_index: u64,
+    ) -> Result<Option<StateValueChunkWithProof>, Error>;
+
+    /// Discards the downloaded chunks of the partition starting at the
+    /// given index (up to the end index), so that they're downloaded again.
+    fn reset_state_snapshot_partition(
+        &self,
+        target_version: Version,
+        partition: u64,
+        start_index: u64,
+        end_index: u64,
+    ) -> Result<(), Error>;
+
+    /// Deletes the downloaded chunks starting at or after the start index that end
+    /// before the given state value index. Returns the index to start the next prune
+    /// from, i.e., the first index of the earliest chunk that was not deleted.
+    fn prune_state_snapshot_chunks(
+        &self,
+        start_index: u64,
+        state_value_index: u64,
+    ) -> Result<u64, Error>;
 }
 
 /// The name of the state sync db file
@@ -55,6 +106,9 @@ pub const STATE_SYNC_DB_NAME: &str = "state_sync_db";
 /// The name of the metadata column family
 const METADATA_CF_NAME: ColumnFamilyName = "metadata";
 
+/// The name of the column family holding the downloaded state snapshot chunks
+const STATE_SNAPSHOT_CHUNKS_CF_NAME: ColumnFamilyName = "state_snapshot_chunks";
+
 /// A metadata storage implementation that uses a RocksDB backend to persist data
 #[derive(Clone)]
 pub struct PersistentMetadataStorage {
@@ -74,7 +128,7 @@ impl PersistentMetadataStorage {
         let database = DB::open(
             state_sync_db_path.clone(),
             "state_sync",
-            vec![METADATA_CF_NAME],
+            vec![METADATA_CF_NAME, STATE_SNAPSHOT_CHUNKS_CF_NAME],
             &options,
         )
         .unwrap_or_else(|error| {
@@ -106,10 +160,13 @@ impl PersistentMetadataStorage {
                     ))
                 })?;
         match maybe_metadata_value {
-            Some(metadata_value) => {
-                let MetadataValue::StateSnapshotSync(snapshot_progress) = metadata_value;
+            Some(MetadataValue::StateSnapshotSync(snapshot_progress)) => {
                 Ok(Some(snapshot_progress))
             },
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Unexpected metadata value for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
             None => Ok(None),
         }
     }
@@ -137,6 +194,36 @@ impl PersistentMetadataStorage {
         }
     }
 
+    /// Returns the chunks of state values persisted starting at the given index
+    fn get_state_snapshot_chunks_iter(
+        &self,
+        start_index: u64,
+    ) -> Result<impl Iterator<Item = Result<(u64, StateSnapshotChunk), Error>> + '_, Error> {
+        let mut iterator = self
+            .database
+            .iter::<StateSnapshotChunkSchema>()
+            .map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to create the state snapshot chunks iterator. Error: {:?}",
+                    error
+                ))
+            })?;
+        iterator.seek(&start_index).map_err(|error| {
+            Error::StorageError(format!(
+                "Failed to seek the state snapshot chunks iterator. Error: {:?}",
+                error
+            ))
+        })?;
+        Ok(iterator.map(|result| {
+            result.map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to read a state snapshot chunk. Error: {:?}",
+                    error
+                ))
+            })
+        }))
+    }
+
     /// Write the key value pair to the database
     fn commit_key_value(
         &self,
@@ -224,6 +311,204 @@ impl MetadataStorageInterface for PersistentMetadataStorage {
         // Insert the new key/value pair
         self.commit_key_value(metadata_key, metadata_value)
     }
+
+    fn get_state_snapshot_partition_progress(
+        &self,
+        target_version: Version,
+        partition: u64,
+    ) -> Result<Option<StateSnapshotPartitionProgress>, Error> {
+        let metadata_key = MetadataKey::StateSnapshotPartition(partition);
+        let maybe_metadata_value =
+            self.database
+                .get::<MetadataSchema>(&metadata_key)
+                .map_err(|error| {
+                    Error::StorageError(format!(
+                        "Failed to read metadata value for key: {:?}. Error: {:?}",
+                        metadata_key, error
+                    ))
+                })?;
+        match maybe_metadata_value {
+            Some(MetadataValue::StateSnapshotPartition(partition_progress)) => {
+                // Ignore the progress of any previous snapshot sync
+                Ok((partition_progress.target_version == target_version)
+                    .then_some(partition_progress))
+            },
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Unexpected metadata value for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    fn save_state_snapshot_partition_chunk(
+        &self,
+        target_version: Version,
+        partition: u64,
+        state_value_chunk_with_proof: StateValueChunkWithProof,
+        partition_completed: bool,
+    ) -> Result<(), Error> {
+        let first_index = state_value_chunk_with_proof.first_index;
+        let next_state_value_index = state_value_chunk_with_proof
+            .last_index
+            .checked_add(1)
+            .ok_or_else(|| {
+                Error::IntegerOverflow("The next state value index has overflown!".into())
+            })?;
+
+        // Write the chunk and the partition progress atomically
+        let batch = SchemaBatch::new();
+        let metadata_key = MetadataKey::StateSnapshotPartition(partition);
+        let metadata_value =
+            MetadataValue::StateSnapshotPartition(StateSnapshotPartitionProgress {
+                target_version,
+                next_state_value_index,
+                partition_completed,
+            });
+        let state_snapshot_chunk = StateSnapshotChunk {
+            target_version,
+            state_value_chunk_with_proof,
+        };
+        batch
+            .put::<StateSnapshotChunkSchema>(&first_index, &state_snapshot_chunk)
+            .and_then(|_| batch.put::<MetadataSchema>(&metadata_key, &metadata_value))
+            .map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to batch put the state snapshot chunk at index: {:?}. Error: {:?}",
+                    first_index, error
+                ))
+            })?;
+        self.database.write_schemas(batch).map_err(|error| {
+            Error::StorageError(format!(
+                "Failed to write the state snapshot chunk at index: {:?}. Error: {:?}",
+                first_index, error
+            ))
+        })
+    }
+
+    fn get_state_snapshot_chunk(
+        &self,
+        target_version: Version,
+        state_value_index: u64,
+    ) -> Result<Option<StateValueChunkWithProof>, Error> {
+        // Find the last chunk starting at or before the index
+        let mut iterator = self
+            .database
+            .rev_iter::<StateSnapshotChunkSchema>()
+            .map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to create the state snapshot chunks iterator. Error: {:?}",
+                    error
+                ))
+            })?;
+        iterator
+            .seek_for_prev(&state_value_index)
+            .map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to seek the state snapshot chunks iterator. Error: {:?}",
+                    error
+                ))
+            })?;
+        let maybe_chunk = iterator.next().transpose().map_err(|error| {
+            Error::StorageError(format!(
+                "Failed to read the state snapshot chunk at index: {:?}. Error: {:?}",
+                state_value_index, error
+            ))
+        })?;
+
+        // Verify the chunk contains the index and belongs to the target snapshot
+        Ok(maybe_chunk.and_then(|(_, state_snapshot_chunk)| {
+            let state_value_chunk_with_proof = state_snapshot_chunk.state_value_chunk_with_proof;
+            (state_snapshot_chunk.target_version == target_version
+                && state_value_chunk_with_proof.last_index >= state_value_index)
+                .then_some(state_value_chunk_with_proof)
+        }))
+    }
+
+    fn reset_state_snapshot_partition(
+        &self,
+        target_version: Version,
+        partition: u64,
+        start_index: u64,
+        end_index: u64,
+    ) -> Result<(), Error> {
+        // Delete the chunks in the range
+        let batch = SchemaBatch::new();
+        for result in self.get_state_snapshot_chunks_iter(start_index)? {
+            let (first_index, _) = result?;
+            if first_index > end_index {
+                break;
+            }
+            batch
+                .delete::<StateSnapshotChunkSchema>(&first_index)
+                .map_err(|error| {
+                    Error::StorageError(format!(
+                        "Failed to batch delete the state snapshot chunk at index: {:?}. Error: {:?}",
+                        first_index, error
+                    ))
+                })?;
+        }
+
+        // Rewind the partition progress to the start index
+        let metadata_key = MetadataKey::StateSnapshotPartition(partition);
+        let metadata_value =
+            MetadataValue::StateSnapshotPartition(StateSnapshotPartitionProgress {
+                target_version,
+                next_state_value_index: start_index,
+                partition_completed: false,
+            });
+        batch
+            .put::<MetadataSchema>(&metadata_key, &metadata_value)
+            .map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to batch put the metadata key and value. Key: {:?}, Value: {:?}. Error: {:?}",
+                    metadata_key, metadata_value, error
+                ))
+            })?;
+        self.database.write_schemas(batch).map_err(|error| {
+            Error::StorageError(format!(
+                "Failed to reset the state snapshot partition: {:?}. Error: {:?}",
+                partition, error
+            ))
+        })
+    }
+
+    fn prune_state_snapshot_chunks(
+        &self,
+        start_index: u64,
+        state_value_index: u64,
+    ) -> Result<u64, Error> {
+        // Chunks don't overlap, so only the last chunk before the
+        // index can contain the index (and must be kept).
+        let mut next_start_index = state_value_index;
+        let batch = SchemaBatch::new();
+        for result in self.get_state_snapshot_chunks_iter(start_index)? {
+            let (first_index, state_snapshot_chunk) = result?;
+            if first_index >= state_value_index {
+                break;
+            }
+            if state_snapshot_chunk.state_value_chunk_with_proof.last_index >= state_value_index {
+                next_start_index = first_index;
+                break;
+            }
+            batch
+                .delete::<StateSnapshotChunkSchema>(&first_index)
+                .map_err(|error| {
+                    Error::StorageError(format!(
+                        "Failed to batch delete the state snapshot chunk at index: {:?}. Error: {:?}",
+                        first_index, error
+                    ))
+                })?;
+        }
+        self.database.write_schemas(batch).map_err(|error| {
+            Error::StorageError(format!(
+                "Failed to prune the state snapshot chunks. Error: {:?}",
+                error
+            ))
+        })?;
+
+        Ok(next_start_index)
+    }
 }
 
 /// A simple struct for recording the progress of a state snapshot sync
@@ -234,6 +519,14 @@ pub struct StateSnapshotProgress {
     pub snapshot_sync_completed: bool,
 }
 
+/// A simple struct for recording the download progress of a state snapshot partition
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateSnapshotPartitionProgress {
+    pub target_version: Version,
+    pub next_state_value_index: u64,
+    pub partition_completed: bool,
+}
+
 /// The raw schema format used by the database
 pub mod database_schema {
     use super::*;
@@ -247,11 +540,26 @@ pub mod database_schema {
     // |   metadata key    | metadata value  |
     define_schema!(MetadataSchema, MetadataKey, MetadataValue, METADATA_CF_NAME);
 
+    // This defines a physical storage schema for the downloaded state snapshot chunks.
+    //
+    // The key will be the big endian encoded index of the first state value in the chunk.
+    // The value will be a bcs serialized StateSnapshotChunk type.
+    //
+    // |<-------key------->|<-----value----->|
+    // |    first index    |      chunk      |
+    define_schema!(
+        StateSnapshotChunkSchema,
+        u64,
+        StateSnapshotChunk,
+        STATE_SNAPSHOT_CHUNKS_CF_NAME
+    );
+
     /// A metadata key that can be inserted into the database
     #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
     #[repr(u8)]
     pub enum MetadataKey {
-        StateSnapshotSync, // A state snapshot sync that was started
+        StateSnapshotSync,           // A state snapshot sync that was started
+        StateSnapshotPartition(u64), // A state snapshot partition that was started
     }
 
     /// A metadata value that can be inserted into the database
@@ -259,6 +567,45 @@ pub mod database_schema {
     #[repr(u8)]
     pub enum MetadataValue {
         StateSnapshotSync(StateSnapshotProgress), // A state snapshot sync progress marker
+        StateSnapshotPartition(StateSnapshotPartitionProgress), // A partition download progress marker
+    }
+
+    /// A downloaded chunk of state values, for the snapshot at the target version
+    #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+    pub struct StateSnapshotChunk {
+        pub target_version: Version,
+        pub state_value_chunk_with_proof: StateValueChunkWithProof,
+    }
+
+    impl KeyCodec<StateSnapshotChunkSchema> for u64 {
+        fn encode_key(&self) -> Result<Vec<u8>> {
+            Ok(self.to_be_bytes().to_vec())
+        }
+
+        fn decode_key(data: &[u8]) -> Result<Self> {
+            let bytes: [u8; 8] = data.try_into().map_err(|error| {
+                anyhow!(
+                    "Failed to decode state snapshot chunk key: {:?}. Error: {:?}",
+                    data,
+                    error
+                )
+            })?;
+            Ok(u64::from_be_bytes(bytes))
+        }
+    }
+
+    impl ValueCodec<StateSnapshotChunkSchema> for StateSnapshotChunk {
+        fn encode_value(&self) -> Result<Vec<u8>> {
+            bcs::to_bytes(self).map_err(|error| {
+                anyhow!("Failed to encode state snapshot chunk. Error: {:?}", error)
+            })
+        }
+
+        fn decode_value(data: &[u8]) -> Result<Self> {
+            bcs::from_bytes::<StateSnapshotChunk>(data).map_err(|error| {
+                anyhow!("Failed to decode state snapshot chunk. Error: {:?}", error)
+            })
+        }
     }
 
     impl KeyCodec<MetadataSchema> for MetadataKey {
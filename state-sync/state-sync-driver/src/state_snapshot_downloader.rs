@@ -0,0 +1,506 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    error::Error,
+    logging::{LogEntry, LogSchema},
+    metadata_storage::MetadataStorageInterface,
+    utils,
+};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_data_streaming_service::{
+    data_notification::{DataPayload, NotificationId},
+    data_stream::DataStreamListener,
+    streaming_client::{DataStreamingClient, NotificationAndFeedback, NotificationFeedback},
+};
+use aptos_logger::prelude::*;
+use aptos_types::{state_store::state_value::StateValueChunkWithProof, transaction::Version};
+use futures::{FutureExt, StreamExt};
+use std::collections::{BTreeMap, HashMap};
+
+/// A partition of the state snapshot that is currently being downloaded
+struct ActivePartition {
+    // The data stream fetching the states of the partition
+    data_stream: DataStreamListener,
+
+    // The index of the last state value in the partition
+    end_index: u64,
+
+    // The index of the next state value expected from the stream
+    next_state_value_index: u64,
+}
+
+/// The location of a downloaded chunk that was handed out for processing
+#[derive(Clone, Copy, Debug)]
+struct ProcessedChunk {
+    partition: u64,
+    first_index: u64,
+    last_index: u64,
+}
+
+/// A downloader that fetches the state snapshot at a target version by
+/// splitting the state value indices into fixed-size partitions and streaming
+/// several partitions concurrently (each stream is served by the peers selected
+/// by the data client). Downloaded chunks are persisted to the metadata storage
+/// before they're verified, along with the progress of each partition, so that
+/// downloads can resume after a restart. Chunks are handed out for processing
+/// strictly in state value index order, as required by the snapshot receiver.
+pub struct PartitionedStateDownloader {
+    // The partitions currently being downloaded
+    active_partitions: BTreeMap<u64, ActivePartition>,
+
+    // The expected root hash of every chunk in the snapshot
+    expected_root_hash: HashValue,
+
+    // The index of the last state value in the snapshot (once it's known)
+    last_state_value_index: Option<u64>,
+
+    // The maximum number of partitions to download concurrently
+    max_concurrent_partitions: u64,
+
+    // The maximum number of notifications to process per stream each round
+    max_stream_notifications: u64,
+
+    // The id to assign to the next chunk handed out for processing
+    next_notification_id: NotificationId,
+
+    // The number of state values in each partition
+    partition_size: u64,
+
+    // The chunks handed out for processing (indexed by notification id)
+    processed_chunks: HashMap<NotificationId, ProcessedChunk>,
+
+    // The index to start the next prune of the persisted chunks from
+    pruned_state_value_index: u64,
+
+    // The version of the state snapshot being downloaded
+    target_version: Version,
+}
+
+impl PartitionedStateDownloader {
+    pub fn new(
+        target_version: Version,
+        expected_root_hash: HashValue,
+        max_concurrent_partitions: u64,
+        partition_size: u64,
+        max_stream_notifications: u64,
+    ) -> Self {
+        Self {
+            active_partitions: BTreeMap::new(),
+            expected_root_hash,
+            last_state_value_index: None,
+            max_concurrent_partitions,
+            max_stream_notifications,
+            next_notification_id: 0,
+            partition_size,
+            processed_chunks: HashMap::new(),
+            pruned_state_value_index: 0,
+            target_version,
+        }
+    }
+
+    /// Returns true iff all state values in the snapshot have been handed out
+    pub fn is_complete(&self, next_state_index_to_process: u64) -> bool {
+        self.last_state_value_index
+            .map(|last_index| next_state_index_to_process > last_index)
+            .unwrap_or(false)
+    }
+
+    /// Deletes the downloaded chunks that have already been persisted by the
+    /// snapshot receiver (i.e., that end before the given index). Pruning
+    /// resumes from where the previous prune stopped.
+    pub fn prune_persisted_chunks<MetadataStorage: MetadataStorageInterface>(
+        &mut self,
+        metadata_storage: &MetadataStorage,
+        last_persisted_state_value_index: u64,
+    ) -> Result<(), Error> {
+        if last_persisted_state_value_index > self.pruned_state_value_index {
+            self.pruned_state_value_index = metadata_storage.prune_state_snapshot_chunks(
+                self.pruned_state_value_index,
+                last_persisted_state_value_index,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Starts the downloads of the partitions following the next state index
+    /// to process and persists any chunks received by the active streams.
+    pub async fn drive_progress<
+        MetadataStorage: MetadataStorageInterface,
+        StreamingClient: DataStreamingClient + Clone,
+    >(
+        &mut self,
+        metadata_storage: &MetadataStorage,
+        streaming_client: &mut StreamingClient,
+        next_state_index_to_process: u64,
+    ) -> Result<(), Error> {
+        self.start_partition_downloads(
+            metadata_storage,
+            streaming_client,
+            next_state_index_to_process,
+        )
+        .await?;
+        self.process_partition_notifications(metadata_storage, streaming_client)
+            .await
+    }
+
+    /// Starts a data stream for each partition in the download window
+    /// that is neither active nor complete.
+    async fn start_partition_downloads<
+        MetadataStorage: MetadataStorageInterface,
+        StreamingClient: DataStreamingClient + Clone,
+    >(
+        &mut self,
+        metadata_storage: &MetadataStorage,
+        streaming_client: &mut StreamingClient,
+        next_state_index_to_process: u64,
+    ) -> Result<(), Error> {
+        let first_partition = next_state_index_to_process / self.partition_size;
+        for partition in first_partition..first_partition + self.max_concurrent_partitions {
+            // Identify the partition bounds
+            let start_index = partition * self.partition_size;
+            if let Some(last_state_value_index) = self.last_state_value_index {
+                if start_index > last_state_value_index {
+                    break; // There are no more partitions to download
+                }
+            }
+            if self.active_partitions.contains_key(&partition) {
+                continue; // The partition is already being downloaded
+            }
+            let end_index = start_index + self.partition_size - 1;
+
+            // Identify the next state value index to download (skipping
+            // anything that has already been downloaded or processed).
+            let next_state_value_index = match metadata_storage
+                .get_state_snapshot_partition_progress(self.target_version, partition)?
+            {
+                Some(partition_progress) => {
+                    if partition == first_partition
+                        && partition_progress.next_state_value_index > next_state_index_to_process
+                        && metadata_storage
+                            .get_state_snapshot_chunk(
+                                self.target_version,
+                                next_state_index_to_process,
+                            )?
+                            .is_none()
+                    {
+                        // The chunk to process next is missing (e.g., it was pruned
+                        // before it was persisted), so it must be downloaded again.
+                        metadata_storage.reset_state_snapshot_partition(
+                            self.target_version,
+                            partition,
+                            next_state_index_to_process,
+                            end_index,
+                        )?;
+                        next_state_index_to_process
+                    } else if partition_progress.partition_completed {
+                        continue; // The partition has already been downloaded
+                    } else {
+                        partition_progress.next_state_value_index
+                    }
+                },
+                None => start_index,
+            }
+            .max(next_state_index_to_process);
+            if next_state_value_index > end_index {
+                continue; // Everything in the partition has been downloaded
+            }
+
+            // Start the data stream for the partition
+            let data_stream = streaming_client
+                .get_state_values_in_range(self.target_version, next_state_value_index, end_index)
+                .await?;
+            info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                "Started downloading state snapshot partition: {:?} (indices: {:?} to {:?}). \
+                Data stream ID: {:?}",
+                partition, next_state_value_index, end_index, data_stream.data_stream_id
+            )));
+            self.active_partitions.insert(partition, ActivePartition {
+                data_stream,
+                end_index,
+                next_state_value_index,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Processes any notifications already pending on the active partition streams
+    async fn process_partition_notifications<
+        MetadataStorage: MetadataStorageInterface,
+        StreamingClient: DataStreamingClient + Clone,
+    >(
+        &mut self,
+        metadata_storage: &MetadataStorage,
+        streaming_client: &mut StreamingClient,
+    ) -> Result<(), Error> {
+        let partitions: Vec<u64> = self.active_partitions.keys().copied().collect();
+        for partition in partitions {
+            for _ in 0..self.max_stream_notifications {
+                // Fetch the next notification for the partition (if any)
+                let active_partition = match self.active_partitions.get_mut(&partition) {
+                    Some(active_partition) => active_partition,
+                    None => break, // The stream has been terminated
+                };
+                let data_notification = match active_partition
+                    .data_stream
+                    .select_next_some()
+                    .now_or_never()
+                {
+                    Some(data_notification) => data_notification,
+                    None => break, // No notifications are pending
+                };
+
+                // Process the notification
+                let notification_id = data_notification.notification_id;
+                let notification_feedback = match data_notification.data_payload {
+                    DataPayload::StateValuesWithProof(state_value_chunk_with_proof) => {
+                        match self.verify_partition_chunk(partition, &state_value_chunk_with_proof)
+                        {
+                            Ok(()) => {
+                                if self.save_partition_chunk(
+                                    metadata_storage,
+                                    partition,
+                                    state_value_chunk_with_proof,
+                                )? {
+                                    None // The partition has been downloaded
+                                } else {
+                                    continue; // Wait for the rest of the partition
+                                }
+                            },
+                            Err(error) => {
+                                warn!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                                    "Received an invalid chunk for state snapshot partition: {:?}! Error: {:?}",
+                                    partition, error
+                                )));
+                                Some(NotificationFeedback::InvalidPayloadData)
+                            },
+                        }
+                    },
+                    DataPayload::EndOfStream => Some(NotificationFeedback::EndOfStream),
+                    _ => Some(NotificationFeedback::PayloadTypeIsIncorrect),
+                };
+
+                // The stream is no longer required. If the partition is still
+                // incomplete, a new stream will be started on the next round.
+                let notification_and_feedback =
+                    notification_feedback.map(|notification_feedback| {
+                        NotificationAndFeedback::new(notification_id, notification_feedback)
+                    });
+                self.terminate_partition_stream(
+                    streaming_client,
+                    partition,
+                    notification_and_feedback,
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Verifies that the given chunk continues the download of the partition
+    fn verify_partition_chunk(
+        &self,
+        partition: u64,
+        state_value_chunk_with_proof: &StateValueChunkWithProof,
+    ) -> Result<(), Error> {
+        let active_partition = self.active_partitions.get(&partition).ok_or_else(|| {
+            Error::UnexpectedError(format!(
+                "The state snapshot partition is not active: {:?}",
+                partition
+            ))
+        })?;
+
+        // Verify the chunk indices
+        let first_index = state_value_chunk_with_proof.first_index;
+        let last_index = state_value_chunk_with_proof.last_index;
+        if first_index != active_partition.next_state_value_index
+            || last_index < first_index
+            || last_index > active_partition.end_index
+        {
+            return Err(Error::VerificationError(format!(
+                "The state value indices were invalid! Expected start: {:?}, end bound: {:?}, \
+                received: {:?} to {:?}",
+                active_partition.next_state_value_index,
+                active_partition.end_index,
+                first_index,
+                last_index
+            )));
+        }
+
+        // Verify the number of state values
+        let num_state_values = state_value_chunk_with_proof.raw_values.len() as u64;
+        if num_state_values != last_index - first_index + 1 {
+            return Err(Error::VerificationError(format!(
+                "The number of state values was invalid! Expected: {:?}, received: {:?}",
+                last_index - first_index + 1,
+                num_state_values
+            )));
+        }
+
+        // Verify the root hash
+        if state_value_chunk_with_proof.root_hash != self.expected_root_hash {
+            return Err(Error::VerificationError(format!(
+                "The chunk root hash: {:?} didn't match the expected hash: {:?}!",
+                state_value_chunk_with_proof.root_hash, self.expected_root_hash
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Persists the given chunk for the partition and returns true
+    /// iff the partition has now been completely downloaded.
+    fn save_partition_chunk<MetadataStorage: MetadataStorageInterface>(
+        &mut self,
+        metadata_storage: &MetadataStorage,
+        partition: u64,
+        state_value_chunk_with_proof: StateValueChunkWithProof,
+    ) -> Result<bool, Error> {
+        let active_partition = self.active_partitions.get_mut(&partition).ok_or_else(|| {
+            Error::UnexpectedError(format!(
+                "The state snapshot partition is not active: {:?}",
+                partition
+            ))
+        })?;
+
+        // Check if the partition (or the entire snapshot) is complete
+        let last_index = state_value_chunk_with_proof.last_index;
+        let is_last_chunk = state_value_chunk_with_proof.is_last_chunk();
+        let partition_completed = is_last_chunk || last_index >= active_partition.end_index;
+
+        // Persist the chunk and update the partition progress
+        metadata_storage.save_state_snapshot_partition_chunk(
+            self.target_version,
+            partition,
+            state_value_chunk_with_proof,
+            partition_completed,
+        )?;
+        active_partition.next_state_value_index = last_index + 1;
+        if is_last_chunk {
+            self.last_state_value_index = Some(last_index);
+        }
+
+        Ok(partition_completed)
+    }
+
+    /// Returns the next downloaded chunk to process (starting at the given
+    /// index), along with the notification id to use when processing it.
+    pub fn next_chunk_to_process<MetadataStorage: MetadataStorageInterface>(
+        &mut self,
+        metadata_storage: &MetadataStorage,
+        next_state_index_to_process: u64,
+    ) -> Result<Option<(NotificationId, StateValueChunkWithProof)>, Error> {
+        if self.is_complete(next_state_index_to_process) {
+            return Ok(None);
+        }
+
+        // Fetch the chunk containing the next state index
+        let mut state_value_chunk_with_proof = match metadata_storage
+            .get_state_snapshot_chunk(self.target_version, next_state_index_to_process)?
+        {
+            Some(state_value_chunk_with_proof) => state_value_chunk_with_proof,
+            None => return Ok(None), // The chunk hasn't been downloaded yet
+        };
+        let processed_chunk = ProcessedChunk {
+            partition: state_value_chunk_with_proof.first_index / self.partition_size,
+            first_index: state_value_chunk_with_proof.first_index,
+            last_index: state_value_chunk_with_proof.last_index,
+        };
+        if state_value_chunk_with_proof.first_index < next_state_index_to_process {
+            trim_chunk_start(
+                &mut state_value_chunk_with_proof,
+                next_state_index_to_process,
+            );
+        }
+
+        // Track the chunk so that it can be invalidated if processing fails
+        let notification_id = self.next_notification_id;
+        self.next_notification_id += 1;
+        self.processed_chunks
+            .retain(|_, chunk| chunk.last_index >= next_state_index_to_process);
+        self.processed_chunks
+            .insert(notification_id, processed_chunk);
+
+        Ok(Some((notification_id, state_value_chunk_with_proof)))
+    }
+
+    /// Discards the chunk processed with the given notification id (and the
+    /// rest of its partition) so that it is downloaded again. Returns false if
+    /// the notification id doesn't belong to a chunk handed out by the downloader.
+    pub fn invalidate_chunk<MetadataStorage: MetadataStorageInterface>(
+        &mut self,
+        metadata_storage: &MetadataStorage,
+        notification_id: NotificationId,
+    ) -> Result<bool, Error> {
+        let processed_chunk = match self.processed_chunks.remove(&notification_id) {
+            Some(processed_chunk) => processed_chunk,
+            None => return Ok(false),
+        };
+
+        info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+            "Invalidating the state snapshot chunk: {:?}",
+            processed_chunk
+        )));
+        let end_index = (processed_chunk.partition + 1) * self.partition_size - 1;
+        metadata_storage.reset_state_snapshot_partition(
+            self.target_version,
+            processed_chunk.partition,
+            processed_chunk.first_index,
+            end_index,
+        )?;
+        if let Some(last_state_value_index) = self.last_state_value_index {
+            if last_state_value_index >= processed_chunk.first_index {
+                self.last_state_value_index = None; // The last chunk must be downloaded again
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Terminates the data streams of all active partitions
+    pub async fn terminate_all_streams<StreamingClient: DataStreamingClient + Clone>(
+        &mut self,
+        streaming_client: &mut StreamingClient,
+    ) -> Result<(), Error> {
+        let partitions: Vec<u64> = self.active_partitions.keys().copied().collect();
+        for partition in partitions {
+            self.terminate_partition_stream(streaming_client, partition, None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Terminates the data stream of the given partition with the feedback
+    async fn terminate_partition_stream<StreamingClient: DataStreamingClient + Clone>(
+        &mut self,
+        streaming_client: &mut StreamingClient,
+        partition: u64,
+        notification_and_feedback: Option<NotificationAndFeedback>,
+    ) -> Result<(), Error> {
+        if let Some(active_partition) = self.active_partitions.remove(&partition) {
+            utils::terminate_stream_with_feedback(
+                streaming_client,
+                active_partition.data_stream.data_stream_id,
+                notification_and_feedback,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Removes all state values before the given index from the chunk. The
+/// range proof remains valid because it only depends on the last key
+/// in the chunk and the left siblings of its path.
+fn trim_chunk_start(state_value_chunk_with_proof: &mut StateValueChunkWithProof, start_index: u64) {
+    let num_values_to_skip = (start_index - state_value_chunk_with_proof.first_index) as usize;
+    state_value_chunk_with_proof
+        .raw_values
+        .drain(..num_values_to_skip);
+    state_value_chunk_with_proof.first_index = start_index;
+    if let Some((state_key, _)) = state_value_chunk_with_proof.raw_values.first() {
+        state_value_chunk_with_proof.first_key = CryptoHash::hash(state_key);
+    }
+}
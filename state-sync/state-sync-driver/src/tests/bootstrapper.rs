@@ -561,6 +561,43 @@ async fn test_data_stream_transaction_outputs() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_data_stream_restore_from_backup() {
+    // Create test data
+    let highest_version = 9998765;
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 1);
+
+    // Create a driver configuration that restores from a backup
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::RestoreFromBackup;
+
+    // Create the mock streaming client (the rest of the data is
+    // synced like with the execute or apply from genesis mode).
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender, data_stream_listener) = create_data_stream_listener();
+    mock_streaming_client
+        .expect_get_all_transactions_or_outputs()
+        .times(1)
+        .with(eq(1), eq(highest_version), eq(highest_version), eq(false))
+        .return_once(move |_, _, _, _| Ok(data_stream_listener));
+
+    // Create the bootstrapper
+    let (mut bootstrapper, _) =
+        create_bootstrapper(driver_configuration, mock_streaming_client, None, true);
+
+    // Insert an epoch ending ledger info into the verified states of the bootstrapper
+    manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info];
+
+    // Drive progress to initialize the transaction or output stream
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 async fn test_data_stream_transactions_or_outputs() {
     // Create test data
@@ -1246,6 +1283,95 @@ async fn test_snapshot_sync_existing_state() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_snapshot_sync_partitioned_existing_state() {
+    // Create test data
+    let synced_version = GENESIS_TRANSACTION_VERSION; // Genesis is the highest synced
+    let highest_version = 1000000;
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 1);
+    let last_persisted_index = 4567;
+
+    // Create a driver configuration that downloads the state snapshot partitions concurrently
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+    driver_configuration
+        .config
+        .max_concurrent_state_snapshot_partitions = 2;
+    driver_configuration.config.state_snapshot_partition_size = 1000;
+
+    // Create the mock streaming client (the partitions should start
+    // at the partition containing the last persisted index).
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender_1, data_stream_listener_1) = create_data_stream_listener();
+    let (_notification_sender_2, data_stream_listener_2) = create_data_stream_listener();
+    mock_streaming_client
+        .expect_get_state_values_in_range()
+        .times(1)
+        .with(eq(highest_version), eq(last_persisted_index), eq(4999))
+        .return_once(move |_, _, _| Ok(data_stream_listener_1));
+    mock_streaming_client
+        .expect_get_state_values_in_range()
+        .times(1)
+        .with(eq(highest_version), eq(5000), eq(5999))
+        .return_once(move |_, _, _| Ok(data_stream_listener_2));
+
+    // Create the mock metadata storage
+    let mut metadata_storage = MockMetadataStorage::new();
+    let highest_ledger_info_clone = highest_ledger_info.clone();
+    metadata_storage
+        .expect_previous_snapshot_sync_target()
+        .returning(move || Ok(Some(highest_ledger_info_clone.clone())));
+    metadata_storage
+        .expect_is_snapshot_sync_complete()
+        .returning(|_| Ok(false));
+    metadata_storage
+        .expect_get_last_persisted_state_value_index()
+        .returning(move |_| Ok(last_persisted_index));
+    metadata_storage
+        .expect_prune_state_snapshot_chunks()
+        .times(1)
+        .with(eq(0), eq(last_persisted_index))
+        .returning(|_, _| Ok(4000));
+    metadata_storage
+        .expect_get_state_snapshot_partition_progress()
+        .returning(|_, _| Ok(None));
+    metadata_storage
+        .expect_get_state_snapshot_chunk()
+        .returning(|_, _| Ok(None));
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        mock_streaming_client,
+        metadata_storage,
+        None,
+        synced_version,
+        true,
+    );
+
+    // Insert an epoch ending ledger info into the verified states of the bootstrapper
+    manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+    // Manually insert a transaction output to sync
+    bootstrapper
+        .get_state_value_syncer()
+        .set_transaction_output_to_sync(create_output_list_with_proof());
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info.clone()];
+
+    // Drive progress to initialize the partitioned download and start the partition streams
+    for _ in 0..2 {
+        drive_progress(&mut bootstrapper, &global_data_summary, false)
+            .await
+            .unwrap();
+    }
+
+    // Verify the bootstrapper is still waiting for the partitions to be downloaded
+    assert!(!bootstrapper.is_bootstrapped());
+}
+
 #[tokio::test]
 async fn test_snapshot_sync_fresh_state() {
     // Create test data
@@ -4,13 +4,18 @@
 use crate::{
     metadata_storage::{
         database_schema::{MetadataKey, MetadataSchema, MetadataValue},
-        MetadataStorageInterface, PersistentMetadataStorage, StateSnapshotProgress,
+        MetadataStorageInterface, PersistentMetadataStorage, StateSnapshotPartitionProgress,
+        StateSnapshotProgress,
+    },
+    tests::utils::{
+        create_epoch_ending_ledger_info, create_ledger_info_at_version,
+        create_state_value_chunk_with_proof,
     },
-    tests::utils::{create_epoch_ending_ledger_info, create_ledger_info_at_version},
 };
 use aptos_schemadb::schema::fuzzing::assert_encode_decode;
 use aptos_temppath::TempPath;
-use claims::{assert_err, assert_none};
+use aptos_types::state_store::state_value::StateValueChunkWithProof;
+use claims::{assert_err, assert_none, assert_some};
 
 #[test]
 fn test_create_then_open() {
@@ -167,3 +172,128 @@ fn test_writes_to_different_targets() {
         .update_last_persisted_state_value_index(&target_ledger_info, 10101, false)
         .unwrap_err();
 }
+
+#[test]
+fn test_state_snapshot_partition_chunks() {
+    // Create a new metadata storage
+    let tmp_dir = TempPath::new();
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+
+    // Verify no partition progress or chunks exist
+    let target_version = 1000;
+    assert_none!(metadata_storage
+        .get_state_snapshot_partition_progress(target_version, 0)
+        .unwrap());
+    assert_none!(metadata_storage
+        .get_state_snapshot_chunk(target_version, 0)
+        .unwrap());
+
+    // Save several chunks for two partitions (of size 100)
+    for (partition, first_index, last_index, partition_completed) in
+        [(0, 0, 49, false), (0, 50, 99, true), (1, 100, 149, false)]
+    {
+        let state_value_chunk_with_proof = create_chunk(first_index, last_index);
+        metadata_storage
+            .save_state_snapshot_partition_chunk(
+                target_version,
+                partition,
+                state_value_chunk_with_proof,
+                partition_completed,
+            )
+            .unwrap();
+    }
+
+    // Drop the handle to the storage (mimic a reboot)
+    drop(metadata_storage);
+
+    // Verify the partition progress
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+    verify_partition_progress(&metadata_storage, target_version, 0, 100, true);
+    verify_partition_progress(&metadata_storage, target_version, 1, 150, false);
+    assert_none!(metadata_storage
+        .get_state_snapshot_partition_progress(target_version + 1, 0)
+        .unwrap());
+
+    // Verify the chunks containing each index
+    for (state_value_index, expected_first_index) in [(0, 0), (49, 0), (50, 50), (120, 100)] {
+        let state_value_chunk_with_proof = metadata_storage
+            .get_state_snapshot_chunk(target_version, state_value_index)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            state_value_chunk_with_proof.first_index,
+            expected_first_index
+        );
+    }
+    assert_none!(metadata_storage
+        .get_state_snapshot_chunk(target_version, 150)
+        .unwrap());
+    assert_none!(metadata_storage
+        .get_state_snapshot_chunk(target_version + 1, 0)
+        .unwrap());
+
+    // Reset the first partition and verify the chunks were discarded
+    metadata_storage
+        .reset_state_snapshot_partition(target_version, 0, 50, 99)
+        .unwrap();
+    verify_partition_progress(&metadata_storage, target_version, 0, 50, false);
+    assert_some!(metadata_storage
+        .get_state_snapshot_chunk(target_version, 49)
+        .unwrap());
+    assert_none!(metadata_storage
+        .get_state_snapshot_chunk(target_version, 50)
+        .unwrap());
+    assert_some!(metadata_storage
+        .get_state_snapshot_chunk(target_version, 100)
+        .unwrap());
+
+    // Prune the chunks and verify only the chunks ending before the index were removed
+    let next_start_index = metadata_storage
+        .prune_state_snapshot_chunks(0, 120)
+        .unwrap();
+    assert_eq!(next_start_index, 100);
+    assert_none!(metadata_storage
+        .get_state_snapshot_chunk(target_version, 0)
+        .unwrap());
+    assert_some!(metadata_storage
+        .get_state_snapshot_chunk(target_version, 120)
+        .unwrap());
+
+    // Prune from the returned index and verify the rest of the chunks were removed
+    let next_start_index = metadata_storage
+        .prune_state_snapshot_chunks(next_start_index, 150)
+        .unwrap();
+    assert_eq!(next_start_index, 150);
+    assert_none!(metadata_storage
+        .get_state_snapshot_chunk(target_version, 120)
+        .unwrap());
+}
+
+/// Creates a test state value chunk for the given indices
+fn create_chunk(first_index: u64, last_index: u64) -> StateValueChunkWithProof {
+    let mut state_value_chunk_with_proof = create_state_value_chunk_with_proof(false);
+    state_value_chunk_with_proof.first_index = first_index;
+    state_value_chunk_with_proof.last_index = last_index;
+    state_value_chunk_with_proof
+}
+
+/// Verifies the persisted progress of the given partition
+fn verify_partition_progress(
+    metadata_storage: &PersistentMetadataStorage,
+    target_version: u64,
+    partition: u64,
+    expected_next_state_value_index: u64,
+    expected_partition_completed: bool,
+) {
+    let partition_progress = metadata_storage
+        .get_state_snapshot_partition_progress(target_version, partition)
+        .unwrap();
+    assert_eq!(
+        partition_progress,
+        Some(StateSnapshotPartitionProgress {
+            target_version,
+            next_state_value_index: expected_next_state_value_index,
+            partition_completed: expected_partition_completed,
+        })
+    );
+}
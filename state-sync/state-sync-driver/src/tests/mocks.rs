@@ -3,7 +3,7 @@
 
 use crate::{
     error::Error,
-    metadata_storage::MetadataStorageInterface,
+    metadata_storage::{MetadataStorageInterface, StateSnapshotPartitionProgress},
     storage_synchronizer::{NotificationMetadata, StorageSynchronizerInterface},
     tests::utils::{create_empty_epoch_state, create_epoch_ending_ledger_info},
 };
@@ -356,6 +356,40 @@ mock! {
             last_persisted_state_value_index: u64,
             snapshot_sync_completed: bool,
         ) -> Result<(), Error>;
+
+        fn get_state_snapshot_partition_progress(
+            &self,
+            target_version: Version,
+            partition: u64,
+        ) -> Result<Option<StateSnapshotPartitionProgress>, Error>;
+
+        fn save_state_snapshot_partition_chunk(
+            &self,
+            target_version: Version,
+            partition: u64,
+            state_value_chunk_with_proof: StateValueChunkWithProof,
+            partition_completed: bool,
+        ) -> Result<(), Error>;
+
+        fn get_state_snapshot_chunk(
+            &self,
+            target_version: Version,
+            state_value_index: u64,
+        ) -> Result<Option<StateValueChunkWithProof>, Error>;
+
+        fn reset_state_snapshot_partition(
+            &self,
+            target_version: Version,
+            partition: u64,
+            start_index: u64,
+            end_index: u64,
+        ) -> Result<(), Error>;
+
+        fn prune_state_snapshot_chunks(
+            &self,
+            start_index: u64,
+            state_value_index: u64,
+        ) -> Result<u64, Error>;
     }
 
     impl Clone for MetadataStorage {
@@ -386,6 +420,13 @@ mock! {
             start_index: Option<u64>,
         ) -> AnyhowResult<DataStreamListener, aptos_data_streaming_service::error::Error>;
 
+        async fn get_state_values_in_range(
+            &self,
+            version: Version,
+            start_index: u64,
+            end_index: u64,
+        ) -> AnyhowResult<DataStreamListener, aptos_data_streaming_service::error::Error>;
+
         async fn get_all_epoch_ending_ledger_infos(
             &self,
             start_epoch: Epoch,
@@ -8,5 +8,6 @@ mod driver;
 mod driver_factory;
 mod metadata_storage;
 mod mocks;
+mod state_snapshot_downloader;
 mod storage_synchronizer;
 mod utils;
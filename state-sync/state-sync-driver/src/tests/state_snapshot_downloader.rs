@@ -0,0 +1,315 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    metadata_storage::{MetadataStorageInterface, PersistentMetadataStorage},
+    state_snapshot_downloader::PartitionedStateDownloader,
+    tests::{
+        mocks::{create_mock_streaming_client, MockMetadataStorage},
+        utils::{create_data_stream_listener, create_state_value_chunk_with_proof},
+    },
+};
+use aptos_crypto::HashValue;
+use aptos_data_streaming_service::data_notification::{DataNotification, DataPayload};
+use aptos_temppath::TempPath;
+use aptos_types::state_store::{
+    state_key::StateKey,
+    state_value::{StateValue, StateValueChunkWithProof},
+};
+use claims::{assert_none, assert_ok, assert_some};
+use futures::SinkExt;
+use mockall::{predicate::eq, Sequence};
+
+#[tokio::test]
+async fn test_partition_assignment() {
+    // Create test data
+    let target_version = 1000;
+    let next_state_index_to_process = 250;
+
+    // Create the mock streaming client (the download window starts
+    // at the partition containing the next index to process).
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender_1, data_stream_listener_1) = create_data_stream_listener();
+    let (_notification_sender_2, data_stream_listener_2) = create_data_stream_listener();
+    let data_stream_id_1 = data_stream_listener_1.data_stream_id;
+    let data_stream_id_2 = data_stream_listener_2.data_stream_id;
+    mock_streaming_client
+        .expect_get_state_values_in_range()
+        .times(1)
+        .with(eq(target_version), eq(250), eq(299))
+        .return_once(move |_, _, _| Ok(data_stream_listener_1));
+    mock_streaming_client
+        .expect_get_state_values_in_range()
+        .times(1)
+        .with(eq(target_version), eq(300), eq(399))
+        .return_once(move |_, _, _| Ok(data_stream_listener_2));
+    for data_stream_id in [data_stream_id_1, data_stream_id_2] {
+        mock_streaming_client
+            .expect_terminate_stream_with_feedback()
+            .times(1)
+            .with(eq(data_stream_id), eq(None))
+            .return_const(Ok(()));
+    }
+
+    // Create the downloader and metadata storage
+    let tmp_dir = TempPath::new();
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+    let mut downloader =
+        PartitionedStateDownloader::new(target_version, HashValue::random(), 2, 100, 10);
+
+    // Drive progress several times and verify each partition is only streamed once
+    for _ in 0..3 {
+        assert_ok!(
+            downloader
+                .drive_progress(
+                    &metadata_storage,
+                    &mut mock_streaming_client,
+                    next_state_index_to_process
+                )
+                .await
+        );
+    }
+
+    // Verify no chunks are available and terminate the streams
+    assert_none!(downloader
+        .next_chunk_to_process(&metadata_storage, next_state_index_to_process)
+        .unwrap());
+    assert!(!downloader.is_complete(next_state_index_to_process));
+    assert_ok!(
+        downloader
+            .terminate_all_streams(&mut mock_streaming_client)
+            .await
+    );
+}
+
+#[tokio::test]
+async fn test_partition_resume() {
+    // Create test data
+    let target_version = 1000;
+    let expected_root_hash = HashValue::random();
+
+    // Persist the progress of a previous download (the first partition
+    // is complete and the second partition is partially downloaded).
+    let tmp_dir = TempPath::new();
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+    metadata_storage
+        .save_state_snapshot_partition_chunk(
+            target_version,
+            0,
+            create_chunk(0, 99, expected_root_hash, false),
+            true,
+        )
+        .unwrap();
+    metadata_storage
+        .save_state_snapshot_partition_chunk(
+            target_version,
+            1,
+            create_chunk(100, 149, expected_root_hash, false),
+            false,
+        )
+        .unwrap();
+
+    // Create the mock streaming client and verify only the
+    // rest of the second partition is downloaded again.
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender, data_stream_listener) = create_data_stream_listener();
+    let data_stream_id = data_stream_listener.data_stream_id;
+    mock_streaming_client
+        .expect_get_state_values_in_range()
+        .times(1)
+        .with(eq(target_version), eq(150), eq(199))
+        .return_once(move |_, _, _| Ok(data_stream_listener));
+    mock_streaming_client
+        .expect_terminate_stream_with_feedback()
+        .times(1)
+        .with(eq(data_stream_id), eq(None))
+        .return_const(Ok(()));
+
+    // Create the downloader (mimic a reboot) and drive progress
+    let mut downloader =
+        PartitionedStateDownloader::new(target_version, expected_root_hash, 2, 100, 10);
+    assert_ok!(
+        downloader
+            .drive_progress(&metadata_storage, &mut mock_streaming_client, 0)
+            .await
+    );
+
+    // Verify the persisted chunks are handed out in order
+    let (_, state_value_chunk_with_proof) = downloader
+        .next_chunk_to_process(&metadata_storage, 0)
+        .unwrap()
+        .unwrap();
+    assert_eq!(state_value_chunk_with_proof.first_index, 0);
+    assert_eq!(state_value_chunk_with_proof.last_index, 99);
+    let (_, state_value_chunk_with_proof) = downloader
+        .next_chunk_to_process(&metadata_storage, 100)
+        .unwrap()
+        .unwrap();
+    assert_eq!(state_value_chunk_with_proof.first_index, 100);
+    assert_eq!(state_value_chunk_with_proof.last_index, 149);
+    assert_none!(downloader
+        .next_chunk_to_process(&metadata_storage, 150)
+        .unwrap());
+
+    // Terminate the stream
+    assert_ok!(
+        downloader
+            .terminate_all_streams(&mut mock_streaming_client)
+            .await
+    );
+}
+
+#[tokio::test]
+async fn test_partition_out_of_order_completion() {
+    // Create test data
+    let target_version = 1000;
+    let expected_root_hash = HashValue::random();
+
+    // Create the mock streaming client
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let mut expectation_sequence = Sequence::new();
+    let (mut notification_sender_1, data_stream_listener_1) = create_data_stream_listener();
+    let (mut notification_sender_2, data_stream_listener_2) = create_data_stream_listener();
+    let data_stream_id_1 = data_stream_listener_1.data_stream_id;
+    let data_stream_id_2 = data_stream_listener_2.data_stream_id;
+    mock_streaming_client
+        .expect_get_state_values_in_range()
+        .times(1)
+        .with(eq(target_version), eq(0), eq(99))
+        .return_once(move |_, _, _| Ok(data_stream_listener_1));
+    mock_streaming_client
+        .expect_get_state_values_in_range()
+        .times(1)
+        .with(eq(target_version), eq(100), eq(199))
+        .return_once(move |_, _, _| Ok(data_stream_listener_2));
+    mock_streaming_client
+        .expect_terminate_stream_with_feedback()
+        .times(1)
+        .with(eq(data_stream_id_2), eq(None))
+        .return_const(Ok(()))
+        .in_sequence(&mut expectation_sequence);
+    mock_streaming_client
+        .expect_terminate_stream_with_feedback()
+        .times(1)
+        .with(eq(data_stream_id_1), eq(None))
+        .return_const(Ok(()))
+        .in_sequence(&mut expectation_sequence);
+
+    // Create the downloader and start the partition streams
+    let tmp_dir = TempPath::new();
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+    let mut downloader =
+        PartitionedStateDownloader::new(target_version, expected_root_hash, 2, 100, 10);
+    assert_ok!(
+        downloader
+            .drive_progress(&metadata_storage, &mut mock_streaming_client, 0)
+            .await
+    );
+
+    // Complete the second (and last) partition first
+    let data_notification = DataNotification::new(
+        0,
+        DataPayload::StateValuesWithProof(create_chunk(100, 149, expected_root_hash, true)),
+    );
+    notification_sender_2.send(data_notification).await.unwrap();
+    assert_ok!(
+        downloader
+            .drive_progress(&metadata_storage, &mut mock_streaming_client, 0)
+            .await
+    );
+
+    // Verify nothing can be processed until the first partition is downloaded
+    assert_some!(metadata_storage
+        .get_state_snapshot_chunk(target_version, 100)
+        .unwrap());
+    assert_none!(downloader
+        .next_chunk_to_process(&metadata_storage, 0)
+        .unwrap());
+    assert!(!downloader.is_complete(0));
+
+    // Complete the first partition
+    let data_notification = DataNotification::new(
+        1,
+        DataPayload::StateValuesWithProof(create_chunk(0, 99, expected_root_hash, false)),
+    );
+    notification_sender_1.send(data_notification).await.unwrap();
+    assert_ok!(
+        downloader
+            .drive_progress(&metadata_storage, &mut mock_streaming_client, 0)
+            .await
+    );
+
+    // Verify the chunks are now handed out in order
+    let (notification_id, state_value_chunk_with_proof) = downloader
+        .next_chunk_to_process(&metadata_storage, 0)
+        .unwrap()
+        .unwrap();
+    assert_eq!(notification_id, 0);
+    assert_eq!(state_value_chunk_with_proof.first_index, 0);
+    assert_eq!(state_value_chunk_with_proof.last_index, 99);
+    let (notification_id, state_value_chunk_with_proof) = downloader
+        .next_chunk_to_process(&metadata_storage, 100)
+        .unwrap()
+        .unwrap();
+    assert_eq!(notification_id, 1);
+    assert_eq!(state_value_chunk_with_proof.first_index, 100);
+    assert_eq!(state_value_chunk_with_proof.last_index, 149);
+
+    // Verify the download is complete
+    assert!(downloader.is_complete(150));
+    assert_none!(downloader
+        .next_chunk_to_process(&metadata_storage, 150)
+        .unwrap());
+}
+
+#[test]
+fn test_prune_persisted_chunks() {
+    // Create the mock metadata storage
+    let mut metadata_storage = MockMetadataStorage::new();
+    let mut expectation_sequence = Sequence::new();
+    metadata_storage
+        .expect_prune_state_snapshot_chunks()
+        .times(1)
+        .with(eq(0), eq(120))
+        .returning(|_, _| Ok(100))
+        .in_sequence(&mut expectation_sequence);
+    metadata_storage
+        .expect_prune_state_snapshot_chunks()
+        .times(1)
+        .with(eq(100), eq(250))
+        .returning(|_, _| Ok(250))
+        .in_sequence(&mut expectation_sequence);
+
+    // Create the downloader
+    let mut downloader = PartitionedStateDownloader::new(1000, HashValue::random(), 2, 100, 10);
+
+    // Verify pruning resumes from the previous watermark
+    assert_ok!(downloader.prune_persisted_chunks(&metadata_storage, 120));
+    assert_ok!(downloader.prune_persisted_chunks(&metadata_storage, 250));
+
+    // Verify nothing is pruned if the persisted index hasn't moved past the watermark
+    assert_ok!(downloader.prune_persisted_chunks(&metadata_storage, 250));
+    assert_ok!(downloader.prune_persisted_chunks(&metadata_storage, 0));
+}
+
+/// Creates a test state value chunk for the given indices and root hash
+fn create_chunk(
+    first_index: u64,
+    last_index: u64,
+    root_hash: HashValue,
+    last_chunk: bool,
+) -> StateValueChunkWithProof {
+    let mut state_value_chunk_with_proof = create_state_value_chunk_with_proof(last_chunk);
+    state_value_chunk_with_proof.first_index = first_index;
+    state_value_chunk_with_proof.last_index = last_index;
+    state_value_chunk_with_proof.root_hash = root_hash;
+    state_value_chunk_with_proof.raw_values = (first_index..=last_index)
+        .map(|index| {
+            (
+                StateKey::raw(&index.to_be_bytes()),
+                StateValue::new_legacy(index.to_be_bytes().to_vec().into()),
+            )
+        })
+        .collect();
+    state_value_chunk_with_proof
+}
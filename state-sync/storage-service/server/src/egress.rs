@@ -0,0 +1,215 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::Error;
+use aptos_config::{config::StorageServiceConfig, network_id::PeerNetworkId};
+use aptos_infallible::Mutex;
+use aptos_rate_limiter::byte_rate_limit::{ByteRateLimiter, ByteRateLimits};
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+use tokio::time::{timeout_at, Instant};
+
+/// The egress limiter of the storage server. Responses are throttled by the
+/// egress byte rate limits, and the bytes of pending responses (i.e., those
+/// being read from storage or waiting for egress budget) are bounded, both
+/// per peer and in total. This prevents peers from queueing unbounded work.
+pub struct EgressLimiter {
+    config: StorageServiceConfig,
+    rate_limiter: ByteRateLimiter<PeerNetworkId>,
+    pending_bytes: Mutex<PendingBytes>,
+}
+
+/// The number of bytes of pending responses, per peer and in total
+#[derive(Default)]
+struct PendingBytes {
+    total: u64,
+    per_peer: HashMap<PeerNetworkId, u64>,
+}
+
+impl EgressLimiter {
+    pub fn new(config: StorageServiceConfig) -> Self {
+        let rate_limiter = ByteRateLimiter::new(
+            "storage_service_egress",
+            config.max_egress_bytes_per_sec,
+            config.max_egress_bytes_per_sec_per_peer,
+        );
+        Self {
+            config,
+            rate_limiter,
+            pending_bytes: Mutex::new(PendingBytes::default()),
+        }
+    }
+
+    /// Returns the (shared) egress byte rate limits
+    pub fn limits(&self) -> Arc<ByteRateLimits> {
+        self.rate_limiter.limits()
+    }
+
+    /// Garbage collects the egress rate limits of all peers rejected by the
+    /// given filter (e.g., disconnected peers).
+    pub fn garbage_collect_peers<F: Fn(&PeerNetworkId) -> bool>(&self, retain: F) {
+        self.rate_limiter.garbage_collect_keys(retain);
+    }
+
+    /// Reserves the given (estimated) number of bytes for a pending response
+    /// to the peer. If the pending bytes (for the peer, or in total) would
+    /// exceed their limits, an error is returned and nothing is reserved.
+    pub fn reserve(
+        self: &Arc<Self>,
+        peer_network_id: PeerNetworkId,
+        num_bytes: u64,
+    ) -> Result<EgressReservation, Error> {
+        let mut pending_bytes = self.pending_bytes.lock();
+        let peer_pending_bytes = pending_bytes
+            .per_peer
+            .get(&peer_network_id)
+            .copied()
+            .unwrap_or(0);
+        if peer_pending_bytes.saturating_add(num_bytes)
+            > self.config.max_pending_egress_bytes_per_peer
+        {
+            return Err(Error::EgressLimitExceeded(format!(
+                "Too many pending response bytes for the peer: {}",
+                peer_pending_bytes
+            )));
+        }
+        if pending_bytes.total.saturating_add(num_bytes) > self.config.max_pending_egress_bytes {
+            return Err(Error::EgressLimitExceeded(format!(
+                "Too many pending response bytes in total: {}",
+                pending_bytes.total
+            )));
+        }
+
+        // Reserve the bytes
+        pending_bytes.total += num_bytes;
+        pending_bytes
+            .per_peer
+            .insert(peer_network_id, peer_pending_bytes + num_bytes);
+
+        Ok(EgressReservation {
+            egress_limiter: self.clone(),
+            peer_network_id,
+            num_bytes,
+        })
+    }
+
+    /// Releases the given number of pending bytes for the peer
+    fn release(&self, peer_network_id: PeerNetworkId, num_bytes: u64) {
+        let mut pending_bytes = self.pending_bytes.lock();
+        pending_bytes.total = pending_bytes.total.saturating_sub(num_bytes);
+        if let Some(peer_pending_bytes) = pending_bytes.per_peer.get_mut(&peer_network_id) {
+            *peer_pending_bytes = peer_pending_bytes.saturating_sub(num_bytes);
+            if *peer_pending_bytes == 0 {
+                pending_bytes.per_peer.remove(&peer_network_id);
+            }
+        }
+    }
+}
+
+/// A reservation of pending response bytes for a peer (see `EgressLimiter`).
+/// The bytes are released when the reservation is dropped.
+pub struct EgressReservation {
+    egress_limiter: Arc<EgressLimiter>,
+    peer_network_id: PeerNetworkId,
+    num_bytes: u64,
+}
+
+impl EgressReservation {
+    /// Waits until the reserved number of bytes fits within the egress rate
+    /// limits. Returns false iff the deadline expires first.
+    pub async fn acquire(&self, deadline: Instant) -> bool {
+        self.acquire_bytes(self.num_bytes, deadline).await
+    }
+
+    /// Settles the reservation with the actual number of bytes in the response.
+    /// If the response is larger than reserved, this waits for the difference
+    /// (returning false iff the deadline expires first). Otherwise, the unused
+    /// bytes are refunded.
+    pub async fn settle(&self, num_bytes: u64, deadline: Instant) -> bool {
+        match num_bytes.cmp(&self.num_bytes) {
+            Ordering::Greater => {
+                self.acquire_bytes(num_bytes - self.num_bytes, deadline)
+                    .await
+            },
+            Ordering::Less => {
+                self.egress_limiter
+                    .rate_limiter
+                    .refund(self.peer_network_id, self.num_bytes - num_bytes);
+                true
+            },
+            Ordering::Equal => true,
+        }
+    }
+
+    /// Waits until the given number of bytes fits within the egress rate
+    /// limits. Returns false iff the deadline expires first.
+    async fn acquire_bytes(&self, num_bytes: u64, deadline: Instant) -> bool {
+        let acquire = self
+            .egress_limiter
+            .rate_limiter
+            .acquire(self.peer_network_id, num_bytes);
+        timeout_at(deadline, acquire).await.is_ok()
+    }
+}
+
+impl Drop for EgressReservation {
+    fn drop(&mut self) {
+        self.egress_limiter
+            .release(self.peer_network_id, self.num_bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_config::network_id::NetworkId;
+    use aptos_types::PeerId;
+    use std::time::Duration;
+
+    #[test]
+    fn test_pending_bytes_limits() {
+        let config = StorageServiceConfig {
+            max_pending_egress_bytes: 150,
+            max_pending_egress_bytes_per_peer: 100,
+            ..Default::default()
+        };
+        let egress_limiter = Arc::new(EgressLimiter::new(config));
+        let peer_1 = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+        let peer_2 = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+
+        // Verify a peer can't exceed its own limit
+        let reservation_1 = egress_limiter.reserve(peer_1, 60).unwrap();
+        assert!(egress_limiter.reserve(peer_1, 50).is_err());
+
+        // Verify the peers can't exceed the total limit
+        let reservation_2 = egress_limiter.reserve(peer_2, 90).unwrap();
+        assert!(egress_limiter.reserve(peer_1, 1).is_err());
+
+        // Release the reservations and verify the bytes can be reserved again
+        drop(reservation_1);
+        drop(reservation_2);
+        egress_limiter.reserve(peer_1, 100).unwrap();
+        assert!(egress_limiter.pending_bytes.lock().per_peer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_deadline() {
+        let config = StorageServiceConfig {
+            max_egress_bytes_per_sec_per_peer: 100,
+            ..Default::default()
+        };
+        let egress_limiter = Arc::new(EgressLimiter::new(config));
+        let peer = PeerNetworkId::new(NetworkId::Public, PeerId::random());
+        let deadline = Instant::now() + Duration::from_millis(500);
+
+        // Acquire the entire budget of the peer, and refund half of it
+        let reservation = egress_limiter.reserve(peer, 100).unwrap();
+        assert!(reservation.acquire(deadline).await);
+        assert!(reservation.settle(50, deadline).await);
+
+        // Verify the refunded budget can be acquired, but no more
+        let reservation = egress_limiter.reserve(peer, 50).unwrap();
+        assert!(reservation.acquire(deadline).await);
+        assert!(!reservation.settle(60, deadline).await);
+        assert!(Instant::now() >= deadline);
+    }
+}
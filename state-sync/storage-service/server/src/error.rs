@@ -6,6 +6,8 @@ use thiserror::Error;
 
 #[derive(Clone, Debug, Deserialize, Error, PartialEq, Eq, Serialize)]
 pub enum Error {
+    #[error("Egress limit exceeded: {0}")]
+    EgressLimitExceeded(String),
     #[error("Invalid request received: {0}")]
     InvalidRequest(String),
     #[error("Storage error encountered: {0}")]
@@ -20,6 +22,7 @@ impl Error {
     /// Returns a summary label for the error type
     pub fn get_label(&self) -> &'static str {
         match self {
+            Error::EgressLimitExceeded(_) => "egress_limit_exceeded",
             Error::InvalidRequest(_) => "invalid_request",
             Error::StorageErrorEncountered(_) => "storage_error",
             Error::TooManyInvalidRequests(_) => "too_many_invalid_requests",
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    egress::EgressLimiter,
     error::Error,
     logging::{LogEntry, LogSchema},
     metrics,
@@ -10,7 +11,7 @@ use crate::{
         SUBSCRIPTION_FAILURE, SUBSCRIPTION_NEW_STREAM,
     },
     moderator::RequestModerator,
-    network::ResponseSender,
+    network::{serialize_response, ResponseSender},
     optimistic_fetch::OptimisticFetchRequest,
     storage::StorageReaderInterface,
     subscription::{SubscriptionRequest, SubscriptionStreamRequests},
@@ -21,9 +22,10 @@ use aptos_logger::{debug, sample, sample::SampleRate, trace, warn};
 use aptos_network::protocols::wire::handshake::v1::ProtocolId;
 use aptos_storage_service_types::{
     requests::{
-        DataRequest, EpochEndingLedgerInfoRequest, StateValuesWithProofRequest,
-        StorageServiceRequest, TransactionOutputsWithProofRequest,
-        TransactionsOrOutputsWithProofRequest, TransactionsWithProofRequest,
+        DataRequest, EpochEndingLedgerInfoRequest, EventsWithProofRequest,
+        StateValueWithProofRequest, StateValuesWithProofRequest, StorageServiceRequest,
+        TransactionOutputsWithProofRequest, TransactionsOrOutputsWithProofRequest,
+        TransactionsWithProofRequest,
     },
     responses::{
         DataResponse, ServerProtocolVersion, StorageServerSummary, StorageServiceResponse,
@@ -36,6 +38,7 @@ use arc_swap::ArcSwap;
 use dashmap::{mapref::entry::Entry, DashMap};
 use mini_moka::sync::Cache;
 use std::{sync::Arc, time::Duration};
+use tokio::time::Instant;
 
 /// Storage server constants
 const ERROR_LOG_FREQUENCY_SECS: u64 = 5; // The frequency to log errors
@@ -78,10 +81,13 @@ impl<T: StorageReaderInterface> Handler<T> {
     }
 
     /// Handles the given storage service request and responds to the
-    /// request directly.
-    pub fn process_request_and_respond(
-        &self,
+    /// request directly. Egress budget is reserved for the (estimated)
+    /// response before the request is processed, and settled once the
+    /// actual size of the response is known.
+    pub async fn process_request_and_respond(
+        self,
         storage_service_config: StorageServiceConfig,
+        egress_limiter: Arc<EgressLimiter>,
         peer_network_id: PeerNetworkId,
         protocol_id: ProtocolId,
         request: StorageServiceRequest,
@@ -102,26 +108,90 @@ impl<T: StorageReaderInterface> Handler<T> {
             request.get_label(),
         );
 
-        // Handle any optimistic fetch requests
-        if request.data_request.is_optimistic_fetch() {
-            self.handle_optimistic_fetch_request(peer_network_id, request, response_sender);
+        // Handle any optimistic fetch and subscription requests. All handler
+        // methods are currently CPU-bound and synchronous I/O-bound, so we
+        // run them on the blocking thread pool.
+        if request.data_request.is_optimistic_fetch()
+            || request.data_request.is_subscription_request()
+        {
+            let handle_request = move || {
+                if request.data_request.is_optimistic_fetch() {
+                    self.handle_optimistic_fetch_request(peer_network_id, request, response_sender);
+                } else {
+                    self.handle_subscription_request(
+                        storage_service_config,
+                        peer_network_id,
+                        request,
+                        response_sender,
+                    );
+                }
+            };
+            if let Err(error) = tokio::task::spawn_blocking(handle_request).await {
+                warn!(LogSchema::new(LogEntry::StorageServiceError)
+                    .peer_network_id(&peer_network_id)
+                    .message(&format!("Failed to handle the request: {:?}", error)));
+            }
             return;
         }
 
-        // Handle any subscription requests
-        if request.data_request.is_subscription_request() {
-            self.handle_subscription_request(
-                storage_service_config,
-                peer_network_id,
-                request,
-                response_sender,
-            );
+        // Reserve egress budget for the response before reading any data. If
+        // too many responses are already pending, the request is rejected.
+        let deadline =
+            Instant::now() + Duration::from_millis(storage_service_config.max_egress_wait_time_ms);
+        let num_estimated_bytes =
+            request.max_response_bytes(storage_service_config.max_network_chunk_bytes);
+        let egress_reservation = match egress_limiter.reserve(peer_network_id, num_estimated_bytes)
+        {
+            Ok(egress_reservation) => egress_reservation,
+            Err(error) => {
+                increment_counter(
+                    &metrics::STORAGE_ERRORS_ENCOUNTERED,
+                    peer_network_id.network_id(),
+                    error.get_label().into(),
+                );
+                self.send_response(
+                    request,
+                    Err(StorageServiceError::InternalError(error.to_string())),
+                    response_sender,
+                );
+                return;
+            },
+        };
+        if !egress_reservation.acquire(deadline).await {
+            drop_expired_response(&peer_network_id, &request);
             return;
         }
 
-        // Process the request and return the response to the client
-        let response = self.process_request(&peer_network_id, request.clone(), false);
-        self.send_response(request, response, response_sender);
+        // Process the request and serialize the response (on the blocking thread pool)
+        let process_request = move || {
+            let response = self.process_request(&peer_network_id, request.clone(), false);
+            log_storage_response(request.clone(), &response);
+            (request, serialize_response(response))
+        };
+        let (request, serialized_response) =
+            match tokio::task::spawn_blocking(process_request).await {
+                Ok(processed_request) => processed_request,
+                Err(error) => {
+                    warn!(LogSchema::new(LogEntry::StorageServiceError)
+                        .peer_network_id(&peer_network_id)
+                        .message(&format!("Failed to process the request: {:?}", error)));
+                    return;
+                },
+            };
+
+        // Settle the egress budget using the size of the serialized response,
+        // and send the response (unless the deadline has expired).
+        let num_response_bytes = serialized_response
+            .as_ref()
+            .map_or(0, |response_bytes| response_bytes.len() as u64);
+        if !egress_reservation
+            .settle(num_response_bytes, deadline)
+            .await
+        {
+            drop_expired_response(&peer_network_id, &request);
+            return;
+        }
+        response_sender.send_serialized(serialized_response);
     }
 
     /// Processes the given request and returns the response
@@ -409,6 +479,10 @@ impl<T: StorageReaderInterface> Handler<T> {
             DataRequest::GetTransactionsOrOutputsWithProof(request) => {
                 self.get_transactions_or_outputs_with_proof(request)
             },
+            DataRequest::GetStateValueWithProof(request) => {
+                self.get_state_value_with_proof(request)
+            },
+            DataRequest::GetEventsWithProof(request) => self.get_events_with_proof(request),
             _ => Err(Error::UnexpectedErrorEncountered(format!(
                 "Received an unexpected request: {:?}",
                 request
@@ -458,6 +532,31 @@ impl<T: StorageReaderInterface> Handler<T> {
         ))
     }
 
+    fn get_events_with_proof(
+        &self,
+        request: &EventsWithProofRequest,
+    ) -> aptos_storage_service_types::Result<DataResponse, Error> {
+        let event_list_with_proof = self.storage.get_events_with_proof(
+            request.proof_version,
+            request.start_version,
+            request.end_version,
+            &request.event_filter,
+        )?;
+
+        Ok(DataResponse::EventsWithProof(event_list_with_proof))
+    }
+
+    fn get_state_value_with_proof(
+        &self,
+        request: &StateValueWithProofRequest,
+    ) -> aptos_storage_service_types::Result<DataResponse, Error> {
+        let state_value_with_proof = self
+            .storage
+            .get_state_value_with_proof(request.version, &request.state_key)?;
+
+        Ok(DataResponse::StateValueWithProof(state_value_with_proof))
+    }
+
     fn get_epoch_ending_ledger_infos(
         &self,
         request: &EpochEndingLedgerInfoRequest,
@@ -567,6 +666,27 @@ fn update_new_subscription_metrics(peer_network_id: PeerNetworkId) {
 }
 
 /// Logs the response sent by storage for a peer request
+/// Drops a response that couldn't be sent before its deadline (the client
+/// will have stopped waiting for it).
+fn drop_expired_response(peer_network_id: &PeerNetworkId, request: &StorageServiceRequest) {
+    let error = Error::EgressLimitExceeded(
+        "The response deadline expired while waiting for egress budget!".into(),
+    );
+    increment_counter(
+        &metrics::STORAGE_ERRORS_ENCOUNTERED,
+        peer_network_id.network_id(),
+        "egress_deadline_expired".into(),
+    );
+    sample!(
+        SampleRate::Duration(Duration::from_secs(ERROR_LOG_FREQUENCY_SECS)),
+        warn!(LogSchema::new(LogEntry::StorageServiceError)
+            .error(&error)
+            .peer_network_id(peer_network_id)
+            .request(request)
+        );
+    );
+}
+
 fn log_storage_response(
     storage_request: StorageServiceRequest,
     storage_response: &aptos_storage_service_types::Result<
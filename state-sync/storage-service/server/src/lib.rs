@@ -16,6 +16,7 @@ use aptos_config::{
 };
 use aptos_logger::prelude::*;
 use aptos_network::application::storage::PeersAndMetadata;
+use aptos_rate_limiter::byte_rate_limit::ByteRateLimits;
 use aptos_storage_service_notifications::StorageServiceNotificationListener;
 use aptos_storage_service_types::{
     requests::StorageServiceRequest,
@@ -24,6 +25,7 @@ use aptos_storage_service_types::{
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use arc_swap::ArcSwap;
 use dashmap::DashMap;
+use egress::EgressLimiter;
 use error::Error;
 use futures::stream::StreamExt;
 use handler::Handler;
@@ -35,6 +37,7 @@ use storage::StorageReaderInterface;
 use thiserror::Error;
 use tokio::runtime::Handle;
 
+mod egress;
 mod error;
 mod handler;
 mod logging;
@@ -81,6 +84,12 @@ pub struct StorageServiceServer<T> {
     // A moderator for incoming peer requests
     request_moderator: Arc<RequestModerator>,
 
+    // A limiter for the bytes sent to peers
+    egress_limiter: Arc<EgressLimiter>,
+
+    // The peers and metadata (used to garbage collect disconnected peers)
+    peers_and_metadata: Arc<PeersAndMetadata>,
+
     // The listener for notifications from state sync
     storage_service_listener: Option<StorageServiceNotificationListener>,
 
@@ -111,10 +120,11 @@ impl<T: StorageReaderInterface + Send + Sync> StorageServiceServer<T> {
         let request_moderator = Arc::new(RequestModerator::new(
             aptos_data_client_config,
             cached_storage_server_summary.clone(),
-            peers_and_metadata,
+            peers_and_metadata.clone(),
             storage_service_config,
             time_service.clone(),
         ));
+        let egress_limiter = Arc::new(EgressLimiter::new(storage_service_config));
         let storage_service_listener = Some(storage_service_listener);
 
         Self {
@@ -127,6 +137,8 @@ impl<T: StorageReaderInterface + Send + Sync> StorageServiceServer<T> {
             optimistic_fetches,
             subscriptions,
             request_moderator,
+            egress_limiter,
+            peers_and_metadata,
             storage_service_listener,
             runtime,
         }
@@ -353,11 +365,14 @@ impl<T: StorageReaderInterface + Send + Sync> StorageServiceServer<T> {
     }
 
     /// Spawns a non-terminating task that refreshes the unhealthy
-    /// peer states in the request moderator.
+    /// peer states in the request moderator, and garbage collects
+    /// the egress rate limits of disconnected peers.
     async fn spawn_moderator_peer_refresher(&mut self) {
         // Clone all required components for the task
         let config = self.storage_service_config;
         let request_moderator = self.request_moderator.clone();
+        let egress_limiter = self.egress_limiter.clone();
+        let peers_and_metadata = self.peers_and_metadata.clone();
         let time_service = self.time_service.clone();
 
         // Spawn the task
@@ -377,6 +392,21 @@ impl<T: StorageReaderInterface + Send + Sync> StorageServiceServer<T> {
                         .error(&error)
                         .message("Failed to refresh the request moderator!"));
                 }
+
+                // Garbage collect the egress rate limits of disconnected peers
+                match peers_and_metadata.get_connected_peers_and_metadata() {
+                    Ok(connected_peers_and_metadata) => egress_limiter
+                        .garbage_collect_peers(|peer_network_id| {
+                            connected_peers_and_metadata.contains_key(peer_network_id)
+                        }),
+                    Err(error) => {
+                        error!(LogSchema::new(LogEntry::RequestModeratorRefresh)
+                            .message(&format!(
+                                "Failed to get the connected peers to garbage collect the egress rate limits: {:?}",
+                                error
+                            )));
+                    },
+                }
             }
         });
     }
@@ -388,9 +418,9 @@ impl<T: StorageReaderInterface + Send + Sync> StorageServiceServer<T> {
 
         // Handle the storage requests as they arrive
         while let Some(network_request) = self.network_requests.next().await {
-            // All handler methods are currently CPU-bound and synchronous
-            // I/O-bound, so we want to spawn on the blocking thread pool to
-            // avoid starving other async tasks on the same runtime.
+            // Handle each request in a separate task. The handler processes the
+            // request on the blocking thread pool (to avoid starving other async
+            // tasks on the same runtime), but waits for egress budget here.
             let storage = self.storage.clone();
             let config = self.storage_service_config;
             let cached_storage_server_summary = self.cached_storage_server_summary.clone();
@@ -398,8 +428,9 @@ impl<T: StorageReaderInterface + Send + Sync> StorageServiceServer<T> {
             let subscriptions = self.subscriptions.clone();
             let lru_response_cache = self.lru_response_cache.clone();
             let request_moderator = self.request_moderator.clone();
+            let egress_limiter = self.egress_limiter.clone();
             let time_service = self.time_service.clone();
-            self.runtime.spawn_blocking(move || {
+            self.runtime.spawn(async move {
                 Handler::new(
                     cached_storage_server_summary,
                     optimistic_fetches,
@@ -411,15 +442,23 @@ impl<T: StorageReaderInterface + Send + Sync> StorageServiceServer<T> {
                 )
                 .process_request_and_respond(
                     config,
+                    egress_limiter,
                     network_request.peer_network_id,
                     network_request.protocol_id,
                     network_request.storage_service_request,
                     network_request.response_sender,
-                );
+                )
+                .await;
             });
         }
     }
 
+    /// Returns the egress byte rate limits of the server. These can
+    /// be updated at runtime (e.g., via the admin service).
+    pub fn get_egress_byte_rate_limits(&self) -> Arc<ByteRateLimits> {
+        self.egress_limiter.limits()
+    }
+
     #[cfg(test)]
     /// Returns a copy of the request moderator for test purposes
     pub(crate) fn get_request_moderator(&self) -> Arc<RequestModerator> {
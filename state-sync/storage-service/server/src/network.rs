@@ -105,10 +105,19 @@ impl ResponseSender {
     }
 
     pub fn send(self, response: Result<StorageServiceResponse>) {
-        let msg = StorageServiceMessage::Response(response);
-        let result = bcs::to_bytes(&msg)
-            .map(Bytes::from)
-            .map_err(RpcError::BcsError);
-        let _ = self.response_tx.send(result);
+        self.send_serialized(serialize_response(response));
     }
+
+    /// Sends a response that has already been serialized (see `serialize_response`)
+    pub fn send_serialized(self, response: Result<Bytes, RpcError>) {
+        let _ = self.response_tx.send(response);
+    }
+}
+
+/// Serializes the given response into a storage service message
+pub fn serialize_response(response: Result<StorageServiceResponse>) -> Result<Bytes, RpcError> {
+    let msg = StorageServiceMessage::Response(response);
+    bcs::to_bytes(&msg)
+        .map(Bytes::from)
+        .map_err(RpcError::BcsError)
 }
@@ -5,14 +5,24 @@ use crate::{error::Error, metrics::increment_network_frame_overflow};
 use aptos_config::config::StorageServiceConfig;
 use aptos_logger::debug;
 use aptos_storage_interface::{AptosDbError, DbReader, Result as StorageResult};
-use aptos_storage_service_types::responses::{
-    CompleteDataRange, DataResponse, DataSummary, TransactionOrOutputListWithProof,
+use aptos_storage_service_types::{
+    requests::EventFilter,
+    responses::{
+        CompleteDataRange, DataResponse, DataSummary, StateValueWithProof,
+        TransactionOrOutputListWithProof,
+    },
 };
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
-    transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
+    proof::SparseMerkleProof,
+    state_store::{
+        state_key::StateKey,
+        state_value::{StateValue, StateValueChunkWithProof},
+    },
+    transaction::{
+        EventListWithProof, TransactionListWithProof, TransactionOutputListWithProof, Version,
+    },
 };
 use serde::Serialize;
 use std::{cmp::min, sync::Arc};
@@ -89,6 +99,27 @@ pub trait StorageReaderInterface: Clone + Send + 'static {
         start_index: u64,
         end_index: u64,
     ) -> aptos_storage_service_types::Result<StateValueChunkWithProof, Error>;
+
+    /// Returns the events (matching the given `event_filter`) emitted by
+    /// the transactions from `start_version` to `end_version` (inclusive),
+    /// with a proof relative to the `proof_version`. In some cases, less
+    /// transactions may be covered (e.g., due to network or chunk limits).
+    fn get_events_with_proof(
+        &self,
+        proof_version: u64,
+        start_version: u64,
+        end_version: u64,
+        event_filter: &EventFilter,
+    ) -> aptos_storage_service_types::Result<EventListWithProof, Error>;
+
+    /// Returns the state value (if any) for the specified `state_key` at
+    /// the given `version`, together with a sparse merkle proof relative
+    /// to the state root at that version.
+    fn get_state_value_with_proof(
+        &self,
+        version: u64,
+        state_key: &StateKey,
+    ) -> aptos_storage_service_types::Result<StateValueWithProof, Error>;
 }
 
 /// The underlying implementation of the StorageReaderInterface, used by the
@@ -498,6 +529,51 @@ impl StorageReaderInterface for StorageReader {
             version, start_index, end_index
         )))
     }
+
+    fn get_events_with_proof(
+        &self,
+        proof_version: u64,
+        start_version: u64,
+        end_version: u64,
+        event_filter: &EventFilter,
+    ) -> aptos_storage_service_types::Result<EventListWithProof, Error> {
+        // Fetch the transaction outputs (the chunk and network limits for
+        // outputs also bound the size of the events in the response).
+        let TransactionOutputListWithProof {
+            transactions_and_outputs,
+            first_transaction_output_version,
+            proof,
+        } = self.get_transaction_outputs_with_proof(proof_version, start_version, end_version)?;
+
+        // Only keep the events of the transactions with matching events
+        let events = transactions_and_outputs
+            .into_iter()
+            .zip(start_version..)
+            .filter_map(|((_, output), version)| {
+                let events = output.events();
+                events
+                    .iter()
+                    .any(|event| event_filter.matches(event))
+                    .then(|| (version, events.to_vec()))
+            })
+            .collect();
+
+        Ok(EventListWithProof::new(
+            events,
+            first_transaction_output_version,
+            proof,
+        ))
+    }
+
+    fn get_state_value_with_proof(
+        &self,
+        version: u64,
+        state_key: &StateKey,
+    ) -> aptos_storage_service_types::Result<StateValueWithProof, Error> {
+        self.storage
+            .get_state_value_with_proof_by_version(state_key, version)
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))
+    }
 }
 
 // A simple macro that wraps each storage read call with a timer
@@ -578,6 +654,12 @@ impl DbReader for TimedStorageReader {
             start_idx: usize,
             chunk_size: usize,
         ) -> StorageResult<StateValueChunkWithProof>;
+
+        fn get_state_value_with_proof_by_version(
+            &self,
+            state_key: &StateKey,
+            version: Version,
+        ) -> StorageResult<(Option<StateValue>, SparseMerkleProof)>;
     );
 }
 
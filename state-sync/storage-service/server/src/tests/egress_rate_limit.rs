@@ -0,0 +1,101 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::mock::MockClient;
+use aptos_config::{config::StorageServiceConfig, network_id::NetworkId};
+use aptos_storage_service_types::{
+    requests::{DataRequest, StorageServiceRequest},
+    responses::{DataResponse, ServerProtocolVersion},
+    StorageServiceError,
+};
+use aptos_types::PeerId;
+use claims::assert_matches;
+use std::time::Duration;
+use tokio::time::timeout;
+
+#[test]
+fn test_throttled_responses_release_blocking_threads() {
+    // Create a runtime with a single blocking thread (to detect any
+    // throttled responses that hold onto the blocking thread).
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .max_blocking_threads(1)
+        .enable_all()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        // Create a storage service config with a tiny per-peer egress limit
+        let storage_service_config = StorageServiceConfig {
+            max_egress_bytes_per_sec_per_peer: 1,
+            max_egress_wait_time_ms: 3_000,
+            ..Default::default()
+        };
+
+        // Create the storage client and server
+        let (mut mock_client, service, _, _, _) =
+            MockClient::new(None, Some(storage_service_config));
+        let egress_rate_limits = service.get_egress_byte_rate_limits();
+        tokio::spawn(service.start());
+
+        // Send a request from a peer and verify the response is throttled
+        let mut throttled_receiver = mock_client
+            .send_request(
+                create_protocol_version_request(),
+                Some(PeerId::random()),
+                Some(NetworkId::Public),
+            )
+            .await;
+        assert!(timeout(Duration::from_secs(1), &mut throttled_receiver)
+            .await
+            .is_err());
+
+        // Remove the egress limits (e.g., as done by the admin service)
+        egress_rate_limits.update(0, 0);
+        assert_eq!(egress_rate_limits.max_bytes_per_sec_per_key(), 0);
+
+        // Send a request from another peer and verify the response is
+        // sent, even though the first response is still being throttled.
+        let response = mock_client
+            .process_request(create_protocol_version_request())
+            .await
+            .unwrap();
+        assert_eq!(
+            response.get_data_response().unwrap(),
+            DataResponse::ServerProtocolVersion(ServerProtocolVersion {
+                protocol_version: 1
+            })
+        );
+
+        // Verify the throttled response is dropped once its deadline expires
+        let response = timeout(Duration::from_secs(10), throttled_receiver)
+            .await
+            .unwrap();
+        assert!(response.is_err());
+    });
+}
+
+#[tokio::test]
+async fn test_too_many_pending_responses() {
+    // Create a storage service config with a pending bytes limit (per peer)
+    // that is smaller than the size estimated for any response.
+    let storage_service_config = StorageServiceConfig {
+        max_pending_egress_bytes_per_peer: 1_000,
+        ..Default::default()
+    };
+
+    // Create the storage client and server
+    let (mut mock_client, service, _, _, _) = MockClient::new(None, Some(storage_service_config));
+    tokio::spawn(service.start());
+
+    // Send a request and verify it is rejected
+    let response = mock_client
+        .process_request(create_protocol_version_request())
+        .await
+        .unwrap_err();
+    assert_matches!(response, StorageServiceError::InternalError(_));
+}
+
+/// Creates a request to fetch the server protocol version
+fn create_protocol_version_request() -> StorageServiceRequest {
+    StorageServiceRequest::new(DataRequest::GetServerProtocolVersion, false)
+}
@@ -0,0 +1,179 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::{mock, mock::MockClient, utils};
+use aptos_storage_service_types::{
+    requests::EventFilter, responses::DataResponse, StorageServiceError,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    contract_event::ContractEvent,
+    event::EventKey,
+    transaction::{EventListWithProof, TransactionOutputListWithProof},
+};
+use claims::assert_matches;
+
+#[tokio::test]
+async fn test_get_events_with_proof_event_key() {
+    // Create test events
+    let event_key = EventKey::new(0, AccountAddress::ONE);
+    let type_tag = create_type_tag_event().type_tag().clone();
+    let matching_event = ContractEvent::new_v1(event_key, 0, type_tag.clone(), vec![]);
+    let other_event =
+        ContractEvent::new_v1(EventKey::new(1, AccountAddress::ONE), 0, type_tag, vec![]);
+
+    // Create an output list where some transactions emit matching events
+    let events_at_versions = vec![
+        (2, vec![other_event.clone(), matching_event.clone()]),
+        (5, vec![matching_event.clone()]),
+        (7, vec![other_event.clone()]),
+    ];
+    let (output_list_with_proof, start_version, end_version) =
+        create_output_list_with_events(events_at_versions.clone());
+
+    // Fetch the events and verify only the transactions with matching events are returned
+    let expected_events = events_at_versions[0..2].to_vec();
+    for use_compression in [true, false] {
+        let event_list_with_proof = get_events_with_proof(
+            output_list_with_proof.clone(),
+            start_version,
+            end_version,
+            EventFilter::EventKey(event_key),
+            use_compression,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            event_list_with_proof,
+            EventListWithProof::new(
+                expected_events.clone(),
+                Some(start_version),
+                output_list_with_proof.proof.clone()
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_get_events_with_proof_event_type() {
+    // Create test events (of different types)
+    let matching_event = create_type_tag_event();
+    let other_event = ContractEvent::new_v2_with_type_tag_str("0x1::test::OtherEvent", vec![]);
+
+    // Create an output list where some transactions emit matching events
+    let events_at_versions = vec![
+        (0, vec![matching_event.clone()]),
+        (3, vec![other_event.clone()]),
+        (9, vec![other_event.clone(), matching_event.clone()]),
+    ];
+    let (output_list_with_proof, start_version, end_version) =
+        create_output_list_with_events(events_at_versions.clone());
+
+    // Fetch the events and verify only the transactions with matching events are returned
+    let event_list_with_proof = get_events_with_proof(
+        output_list_with_proof.clone(),
+        start_version,
+        end_version,
+        EventFilter::EventType(matching_event.type_tag().clone()),
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(event_list_with_proof.events, vec![
+        events_at_versions[0].clone(),
+        events_at_versions[2].clone()
+    ]);
+    assert_eq!(event_list_with_proof.proof, output_list_with_proof.proof);
+}
+
+#[tokio::test]
+async fn test_get_events_with_proof_not_serviceable() {
+    // Create test data
+    let start_version = 10;
+    let end_version = 20;
+    let proof_version = 30;
+
+    // Create the storage client and server (that cannot create the proof)
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(None, None);
+    utils::update_storage_server_summary(&mut service, proof_version - 1, 10);
+    tokio::spawn(service.start());
+
+    // Process a request to fetch events with a proof
+    let response = utils::get_events_with_proof(
+        &mut mock_client,
+        start_version,
+        end_version,
+        proof_version,
+        EventFilter::EventType(create_type_tag_event().type_tag().clone()),
+        false,
+    )
+    .await
+    .unwrap_err();
+
+    // Verify the request is not serviceable
+    assert_matches!(response, StorageServiceError::InvalidRequest(_));
+}
+
+/// Creates a test output list (for versions 0 to 9) where the transactions
+/// at the specified versions emit the given events. Returns the output list
+/// and the start and end versions.
+fn create_output_list_with_events(
+    events_at_versions: Vec<(u64, Vec<ContractEvent>)>,
+) -> (TransactionOutputListWithProof, u64, u64) {
+    let start_version = 0;
+    let end_version = 9;
+    let mut output_list_with_proof =
+        utils::create_output_list_with_proof(start_version, end_version, end_version);
+    for (version, events) in events_at_versions {
+        output_list_with_proof.transactions_and_outputs[version as usize].1 =
+            utils::create_test_transaction_output_with_events(events);
+    }
+
+    (output_list_with_proof, start_version, end_version)
+}
+
+/// Creates a test event with a type tag (and no event key)
+fn create_type_tag_event() -> ContractEvent {
+    ContractEvent::new_v2_with_type_tag_str("0x1::test::Event", vec![])
+}
+
+/// Serves the given output list and fetches the events (matching
+/// the event filter) from the storage server.
+async fn get_events_with_proof(
+    output_list_with_proof: TransactionOutputListWithProof,
+    start_version: u64,
+    end_version: u64,
+    event_filter: EventFilter,
+    use_compression: bool,
+) -> Result<EventListWithProof, StorageServiceError> {
+    // Create the mock db reader
+    let mut db_reader = mock::create_mock_db_reader();
+    let num_outputs = end_version - start_version + 1;
+    utils::expect_get_transaction_outputs(
+        &mut db_reader,
+        start_version,
+        num_outputs,
+        end_version,
+        output_list_with_proof,
+    );
+
+    // Create the storage client and server
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(Some(db_reader), None);
+    utils::update_storage_server_summary(&mut service, end_version + 100, 10);
+    tokio::spawn(service.start());
+
+    // Process the request and extract the events
+    let response = utils::get_events_with_proof(
+        &mut mock_client,
+        start_version,
+        end_version,
+        end_version,
+        event_filter,
+        use_compression,
+    )
+    .await?;
+    match response.get_data_response().unwrap() {
+        DataResponse::EventsWithProof(event_list_with_proof) => Ok(event_list_with_proof),
+        _ => panic!("Expected events with proof but got: {:?}", response),
+    }
+}
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod cache;
+mod egress_rate_limit;
 mod epoch_ending;
+mod events;
 mod mock;
 mod new_transaction_outputs;
 mod new_transactions;
@@ -11,6 +13,7 @@ mod number_of_states;
 mod optimistic_fetch;
 mod protocol_version;
 mod request_moderator;
+mod state_value;
 mod state_values;
 mod storage_summary;
 mod subscribe_transaction_outputs;
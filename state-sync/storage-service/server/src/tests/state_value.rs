@@ -0,0 +1,127 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::{mock, mock::MockClient, utils};
+use anyhow::format_err;
+use aptos_crypto::HashValue;
+use aptos_storage_interface::AptosDbError;
+use aptos_storage_service_types::{
+    responses::{DataResponse, StorageServiceResponse},
+    StorageServiceError,
+};
+use aptos_types::{
+    proof::{SparseMerkleLeafNode, SparseMerkleProof},
+    state_store::{state_key::StateKey, state_value::StateValue},
+};
+use claims::assert_matches;
+use mockall::predicate::eq;
+
+#[tokio::test]
+async fn test_get_state_value_with_proof() {
+    // Test both v1 and v2 data requests
+    for use_compression in [true, false] {
+        // Create test data
+        let version = 101;
+        let state_key = StateKey::raw(b"test_key");
+        let state_value = StateValue::new_legacy(vec![1, 2, 3].into());
+        let proof = SparseMerkleProof::new(
+            Some(SparseMerkleLeafNode::new(
+                HashValue::random(),
+                HashValue::random(),
+            )),
+            vec![HashValue::random()],
+        );
+
+        // Create the mock db reader
+        let mut db_reader = mock::create_mock_db_reader();
+        let response_value = Some(state_value.clone());
+        let response_proof = proof.clone();
+        db_reader
+            .expect_get_state_value_with_proof_by_version()
+            .times(1)
+            .with(eq(state_key.clone()), eq(version))
+            .returning(move |_, _| Ok((response_value.clone(), response_proof.clone())));
+
+        // Create the storage client and server
+        let (mut mock_client, mut service, _, _, _) = MockClient::new(Some(db_reader), None);
+        utils::update_storage_server_summary(&mut service, version, 10);
+        tokio::spawn(service.start());
+
+        // Process a request to fetch the state value
+        let response = utils::get_state_value_with_proof(
+            &mut mock_client,
+            version,
+            state_key,
+            use_compression,
+        )
+        .await
+        .unwrap();
+
+        // Verify the response is correct
+        if use_compression {
+            assert_matches!(response, StorageServiceResponse::CompressedResponse(_, _));
+        } else {
+            assert_matches!(response, StorageServiceResponse::RawResponse(_));
+        }
+        assert_eq!(
+            response.get_data_response().unwrap(),
+            DataResponse::StateValueWithProof((Some(state_value), proof))
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_get_state_value_with_proof_not_serviceable() {
+    // Create test data
+    let version = 101;
+
+    // Create the storage client and server (that cannot service the request)
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(None, None);
+    utils::update_storage_server_summary(&mut service, version - 1, 10);
+    tokio::spawn(service.start());
+
+    // Process a request to fetch a state value at a version that isn't synced
+    let response = utils::get_state_value_with_proof(
+        &mut mock_client,
+        version,
+        StateKey::raw(b"test_key"),
+        false,
+    )
+    .await
+    .unwrap_err();
+
+    // Verify the request is not serviceable
+    assert_matches!(response, StorageServiceError::InvalidRequest(_));
+}
+
+#[tokio::test]
+async fn test_get_state_value_with_proof_invalid() {
+    // Create test data
+    let version = 1;
+    let state_key = StateKey::raw(b"test_key");
+
+    // Create the mock db reader
+    let mut db_reader = mock::create_mock_db_reader();
+    db_reader
+        .expect_get_state_value_with_proof_by_version()
+        .times(1)
+        .with(eq(state_key.clone()), eq(version))
+        .returning(move |_, _| {
+            Err(AptosDbError::NotFound(
+                format_err!("Version does not exist!").to_string(),
+            ))
+        });
+
+    // Create the storage client and server
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(Some(db_reader), None);
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Process a request to fetch the state value
+    let response = utils::get_state_value_with_proof(&mut mock_client, version, state_key, false)
+        .await
+        .unwrap_err();
+
+    // Verify the response is correct
+    assert_matches!(response, StorageServiceError::InternalError(_));
+}
@@ -20,7 +20,9 @@ use aptos_storage_service_notifications::{
 };
 use aptos_storage_service_types::{
     requests::{
-        DataRequest, StateValuesWithProofRequest, StorageServiceRequest,
+        DataRequest, EventFilter, EventsWithProofRequest, StateValueWithProofRequest,
+        StateValuesWithProofRequest,
+        StorageServiceRequest,
         SubscribeTransactionOutputsWithProofRequest,
         SubscribeTransactionsOrOutputsWithProofRequest, SubscribeTransactionsWithProofRequest,
         SubscriptionStreamMetadata, TransactionsWithProofRequest,
@@ -34,10 +36,12 @@ use aptos_types::{
     aggregate_signature::AggregateSignature,
     block_info::BlockInfo,
     chain_id::ChainId,
+    contract_event::ContractEvent,
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     on_chain_config::ValidatorSet,
+    state_store::state_key::StateKey,
     transaction::{
         ExecutionStatus, RawTransaction, Script, SignedTransaction, Transaction,
         TransactionAuxiliaryData, TransactionListWithProof, TransactionOutput,
@@ -280,9 +284,14 @@ pub fn create_transaction_list_with_proof(
 
 /// Creates a test transaction output
 fn create_test_transaction_output() -> TransactionOutput {
+    create_test_transaction_output_with_events(vec![])
+}
+
+/// Creates a test transaction output that emits the given events
+pub fn create_test_transaction_output_with_events(events: Vec<ContractEvent>) -> TransactionOutput {
     TransactionOutput::new(
         WriteSet::default(),
-        vec![],
+        events,
         0,
         TransactionStatus::Keep(ExecutionStatus::MiscellaneousError(None)),
         TransactionAuxiliaryData::default(),
@@ -486,6 +495,38 @@ pub async fn get_state_values_with_proof(
     send_storage_request(mock_client, use_compression, data_request).await
 }
 
+/// Sends an events with proof request and processes the response
+pub async fn get_events_with_proof(
+    mock_client: &mut MockClient,
+    start_version: u64,
+    end_version: u64,
+    proof_version: u64,
+    event_filter: EventFilter,
+    use_compression: bool,
+) -> Result<StorageServiceResponse, StorageServiceError> {
+    let data_request = DataRequest::GetEventsWithProof(EventsWithProofRequest {
+        proof_version,
+        start_version,
+        end_version,
+        event_filter,
+    });
+    send_storage_request(mock_client, use_compression, data_request).await
+}
+
+/// Sends a state value with proof request and processes the response
+pub async fn get_state_value_with_proof(
+    mock_client: &mut MockClient,
+    version: u64,
+    state_key: StateKey,
+    use_compression: bool,
+) -> Result<StorageServiceResponse, StorageServiceError> {
+    let data_request = DataRequest::GetStateValueWithProof(StateValueWithProofRequest {
+        version,
+        state_key,
+    });
+    send_storage_request(mock_client, use_compression, data_request).await
+}
+
 /// Sends a transactions with proof request and processes the response
 pub async fn get_transactions_with_proof(
     mock_client: &mut MockClient,
@@ -2,9 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::COMPRESSION_SUFFIX_LABEL;
-use aptos_types::transaction::Version;
+use aptos_types::{
+    contract_event::ContractEvent, event::EventKey, state_store::state_key::StateKey,
+    transaction::Version,
+};
+use move_core_types::language_storage::TypeTag;
 use serde::{Deserialize, Serialize};
 
+/// The maximum number of bytes expected in responses that don't hold a chunk
+/// of data (e.g., the server protocol version or the storage server summary).
+const MAX_NON_CHUNK_RESPONSE_BYTES: u64 = 64 * 1024; // 64 KiB
+
 /// A storage service request.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct StorageServiceRequest {
@@ -28,6 +36,21 @@ impl StorageServiceRequest {
         }
         label
     }
+
+    /// Returns an estimate of the maximum number of bytes in the response to
+    /// the request, given the maximum number of bytes in a response chunk.
+    /// Note: the estimate may be exceeded, e.g., if a single item (which is
+    /// always returned) is larger than the chunk limit.
+    pub fn max_response_bytes(&self, max_chunk_bytes: u64) -> u64 {
+        match self.data_request {
+            DataRequest::GetNumberOfStatesAtVersion(_)
+            | DataRequest::GetServerProtocolVersion
+            | DataRequest::GetStorageServerSummary => {
+                MAX_NON_CHUNK_RESPONSE_BYTES.min(max_chunk_bytes)
+            },
+            _ => max_chunk_bytes,
+        }
+    }
 }
 
 /// A single data request.
@@ -47,6 +70,8 @@ pub enum DataRequest {
     SubscribeTransactionOutputsWithProof(SubscribeTransactionOutputsWithProofRequest), // Subscribes to transaction outputs with a proof
     SubscribeTransactionsOrOutputsWithProof(SubscribeTransactionsOrOutputsWithProofRequest), // Subscribes to transactions or outputs with a proof
     SubscribeTransactionsWithProof(SubscribeTransactionsWithProofRequest), // Subscribes to transactions with a proof
+    GetStateValueWithProof(StateValueWithProofRequest), // Fetches a single state value with a proof
+    GetEventsWithProof(EventsWithProofRequest), // Fetches a list of events (matching a filter) with a proof
 }
 
 impl DataRequest {
@@ -73,6 +98,8 @@ impl DataRequest {
                 "subscribe_transactions_or_outputs_with_proof"
             },
             Self::SubscribeTransactionsWithProof(_) => "subscribe_transactions_with_proof",
+            Self::GetStateValueWithProof(_) => "get_state_value_with_proof",
+            Self::GetEventsWithProof(_) => "get_events_with_proof",
         }
     }
 
@@ -104,6 +131,34 @@ pub struct EpochEndingLedgerInfoRequest {
     pub expected_end_epoch: u64, // The epoch to finish at
 }
 
+/// A storage service request for fetching the events (matching the given
+/// filter) emitted by the transactions in the specified version range, with
+/// a corresponding proof relative to the `proof_version`.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct EventsWithProofRequest {
+    pub proof_version: u64,        // The version the proof should be relative to
+    pub start_version: u64,        // The starting version of the transaction range
+    pub end_version: u64,          // The ending version of the transaction range (inclusive)
+    pub event_filter: EventFilter, // The filter identifying the events of interest
+}
+
+/// A filter identifying the events of interest in an events request
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum EventFilter {
+    EventKey(EventKey), // Matches all events with the given event key
+    EventType(TypeTag), // Matches all events with the given type
+}
+
+impl EventFilter {
+    /// Returns true iff the given event matches the filter
+    pub fn matches(&self, event: &ContractEvent) -> bool {
+        match self {
+            Self::EventKey(event_key) => event.event_key() == Some(event_key),
+            Self::EventType(type_tag) => event.type_tag() == type_tag,
+        }
+    }
+}
+
 /// A storage service request for fetching a new transaction output list
 /// beyond the already known version and epoch.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -130,6 +185,14 @@ pub struct StateValuesWithProofRequest {
     pub end_index: u64,   // The index to stop fetching state values (inclusive)
 }
 
+/// A storage service request for fetching a single state value (by key)
+/// with a corresponding sparse merkle proof at the specified version.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct StateValueWithProofRequest {
+    pub version: u64,        // The version to fetch the state value at
+    pub state_key: StateKey, // The key of the state value to fetch
+}
+
 /// A storage service request for fetching a transaction output list with a
 /// corresponding proof.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
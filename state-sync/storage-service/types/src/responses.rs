@@ -3,12 +3,13 @@
 
 use crate::{
     requests::DataRequest::{
-        GetEpochEndingLedgerInfos, GetNewTransactionOutputsWithProof,
+        GetEpochEndingLedgerInfos, GetEventsWithProof, GetNewTransactionOutputsWithProof,
         GetNewTransactionsOrOutputsWithProof, GetNewTransactionsWithProof,
-        GetNumberOfStatesAtVersion, GetServerProtocolVersion, GetStateValuesWithProof,
-        GetStorageServerSummary, GetTransactionOutputsWithProof, GetTransactionsOrOutputsWithProof,
-        GetTransactionsWithProof, SubscribeTransactionOutputsWithProof,
-        SubscribeTransactionsOrOutputsWithProof, SubscribeTransactionsWithProof,
+        GetNumberOfStatesAtVersion, GetServerProtocolVersion, GetStateValueWithProof,
+        GetStateValuesWithProof, GetStorageServerSummary, GetTransactionOutputsWithProof,
+        GetTransactionsOrOutputsWithProof, GetTransactionsWithProof,
+        SubscribeTransactionOutputsWithProof, SubscribeTransactionsOrOutputsWithProof,
+        SubscribeTransactionsWithProof,
     },
     responses::Error::DegenerateRangeError,
     Epoch, StorageServiceRequest, COMPRESSION_SUFFIX_LABEL,
@@ -21,8 +22,11 @@ use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
-    transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
+    proof::SparseMerkleProof,
+    state_store::state_value::{StateValue, StateValueChunkWithProof},
+    transaction::{
+        EventListWithProof, TransactionListWithProof, TransactionOutputListWithProof, Version,
+    },
 };
 use num_traits::{PrimInt, Zero};
 #[cfg(test)]
@@ -116,6 +120,16 @@ impl StorageServiceResponse {
     pub fn is_compressed(&self) -> bool {
         matches!(self, Self::CompressedResponse(_, _))
     }
+
+    /// Returns the number of compressed bytes (if the response is compressed)
+    pub fn get_num_compressed_bytes(&self) -> Option<u64> {
+        match self {
+            StorageServiceResponse::CompressedResponse(_, compressed_data) => {
+                Some(compressed_data.len() as u64)
+            },
+            StorageServiceResponse::RawResponse(_) => None,
+        }
+    }
 }
 
 /// A useful type to hold optional transaction data
@@ -124,6 +138,9 @@ pub type TransactionOrOutputListWithProof = (
     Option<TransactionOutputListWithProof>,
 );
 
+/// A useful type to hold a single (optional) state value and its proof
+pub type StateValueWithProof = (Option<StateValue>, SparseMerkleProof);
+
 /// A single data response.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[allow(clippy::large_enum_variant)]
@@ -139,6 +156,8 @@ pub enum DataResponse {
     TransactionsWithProof(TransactionListWithProof),
     NewTransactionsOrOutputsWithProof((TransactionOrOutputListWithProof, LedgerInfoWithSignatures)),
     TransactionsOrOutputsWithProof(TransactionOrOutputListWithProof),
+    StateValueWithProof(StateValueWithProof),
+    EventsWithProof(EventListWithProof),
 }
 
 impl DataResponse {
@@ -156,6 +175,8 @@ impl DataResponse {
             Self::TransactionsWithProof(_) => "transactions_with_proof",
             Self::NewTransactionsOrOutputsWithProof(_) => "new_transactions_or_outputs_with_proof",
             Self::TransactionsOrOutputsWithProof(_) => "transactions_or_outputs_with_proof",
+            Self::StateValueWithProof(_) => "state_value_with_proof",
+            Self::EventsWithProof(_) => "events_with_proof",
         }
     }
 }
@@ -240,6 +261,36 @@ impl TryFrom<StorageServiceResponse> for (TransactionListWithProof, LedgerInfoWi
     }
 }
 
+impl TryFrom<StorageServiceResponse> for StateValueWithProof {
+    type Error = crate::responses::Error;
+
+    fn try_from(response: StorageServiceResponse) -> crate::Result<Self, Self::Error> {
+        let data_response = response.get_data_response()?;
+        match data_response {
+            DataResponse::StateValueWithProof(inner) => Ok(inner),
+            _ => Err(Error::UnexpectedResponseError(format!(
+                "expected state_value_with_proof, found {}",
+                data_response.get_label()
+            ))),
+        }
+    }
+}
+
+impl TryFrom<StorageServiceResponse> for EventListWithProof {
+    type Error = crate::responses::Error;
+
+    fn try_from(response: StorageServiceResponse) -> crate::Result<Self, Self::Error> {
+        let data_response = response.get_data_response()?;
+        match data_response {
+            DataResponse::EventsWithProof(inner) => Ok(inner),
+            _ => Err(Error::UnexpectedResponseError(format!(
+                "expected events_with_proof, found {}",
+                data_response.get_label()
+            ))),
+        }
+    }
+}
+
 impl TryFrom<StorageServiceResponse> for u64 {
     type Error = crate::responses::Error;
 
@@ -488,6 +539,40 @@ impl DataSummary {
 
                 can_serve_states && can_create_proof
             },
+            GetStateValueWithProof(request) => {
+                let can_serve_state = self
+                    .states
+                    .map(|range| range.contains(request.version))
+                    .unwrap_or(false);
+
+                let can_create_proof = self
+                    .synced_ledger_info
+                    .as_ref()
+                    .map(|li| li.ledger_info().version() >= request.version)
+                    .unwrap_or(false);
+
+                can_serve_state && can_create_proof
+            },
+            GetEventsWithProof(request) => {
+                let desired_range =
+                    match CompleteDataRange::new(request.start_version, request.end_version) {
+                        Ok(desired_range) => desired_range,
+                        Err(_) => return false,
+                    };
+
+                let can_serve_outputs = self
+                    .transaction_outputs
+                    .map(|range| range.superset_of(&desired_range))
+                    .unwrap_or(false);
+
+                let can_create_proof = self
+                    .synced_ledger_info
+                    .as_ref()
+                    .map(|li| li.ledger_info().version() >= request.proof_version)
+                    .unwrap_or(false);
+
+                can_serve_outputs && can_create_proof
+            },
             GetTransactionOutputsWithProof(request) => {
                 let desired_range =
                     match CompleteDataRange::new(request.start_version, request.end_version) {
@@ -3,9 +3,10 @@
 
 use crate::{
     requests::{
-        DataRequest, EpochEndingLedgerInfoRequest, NewTransactionOutputsWithProofRequest,
-        NewTransactionsOrOutputsWithProofRequest, NewTransactionsWithProofRequest,
-        StateValuesWithProofRequest, SubscribeTransactionOutputsWithProofRequest,
+        DataRequest, EpochEndingLedgerInfoRequest, EventFilter, EventsWithProofRequest,
+        NewTransactionOutputsWithProofRequest, NewTransactionsOrOutputsWithProofRequest,
+        NewTransactionsWithProofRequest, StateValuesWithProofRequest,
+        SubscribeTransactionOutputsWithProofRequest,
         SubscribeTransactionsOrOutputsWithProofRequest, SubscribeTransactionsWithProofRequest,
         SubscriptionStreamMetadata, TransactionOutputsWithProofRequest,
         TransactionsOrOutputsWithProofRequest, TransactionsWithProofRequest,
@@ -17,8 +18,10 @@ use aptos_config::config::AptosDataClientConfig;
 use aptos_crypto::hash::HashValue;
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::{
+    account_address::AccountAddress,
     aggregate_signature::AggregateSignature,
     block_info::BlockInfo,
+    event::EventKey,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     transaction::Version,
 };
@@ -353,6 +356,45 @@ fn test_data_summary_service_transaction_outputs() {
     }
 }
 
+#[test]
+fn test_data_summary_service_events() {
+    // Create a data client config and data summary
+    let data_client_config = AptosDataClientConfig::default();
+    let data_summary = DataSummary {
+        synced_ledger_info: Some(create_ledger_info_at_version(250)),
+        transaction_outputs: Some(create_data_range(100, 200)),
+        ..Default::default()
+    };
+
+    // Verify the different requests that can be serviced
+    for compression in [true, false] {
+        // Test the valid data ranges and proofs
+        let valid_ranges_and_proofs = vec![(100, 200, 225), (125, 175, 250), (200, 200, 200)];
+        verify_can_service_event_requests(
+            &data_client_config,
+            &data_summary,
+            compression,
+            valid_ranges_and_proofs,
+            true,
+        );
+
+        // Test the missing data ranges, invalid proofs and invalid data ranges
+        let invalid_ranges_and_proofs = vec![
+            (99, 200, 225),
+            (150, 250, 225),
+            (100, 200, 251),
+            (175, 125, 225),
+        ];
+        verify_can_service_event_requests(
+            &data_client_config,
+            &data_summary,
+            compression,
+            invalid_ranges_and_proofs,
+            false,
+        );
+    }
+}
+
 #[test]
 fn test_data_summary_service_transactions_or_outputs() {
     // Create a data client config and data summary
@@ -595,6 +637,22 @@ fn create_outputs_request(
     StorageServiceRequest::new(data_request, use_compression)
 }
 
+/// Creates a new events request
+fn create_events_request(
+    proof_version: Version,
+    start_version: Version,
+    end_version: Version,
+    use_compression: bool,
+) -> StorageServiceRequest {
+    let data_request = DataRequest::GetEventsWithProof(EventsWithProofRequest {
+        proof_version,
+        start_version,
+        end_version,
+        event_filter: EventFilter::EventKey(EventKey::new(0, AccountAddress::ONE)),
+    });
+    StorageServiceRequest::new(data_request, use_compression)
+}
+
 /// Creates a new subscription request
 fn create_subscription_request(known_version: u64, use_compression: bool) -> StorageServiceRequest {
     // Create a new subscription stream metadata
@@ -880,6 +938,30 @@ fn verify_can_service_output_requests(
     }
 }
 
+/// Verifies the serviceability of the event requests
+fn verify_can_service_event_requests(
+    data_client_config: &AptosDataClientConfig,
+    data_summary: &DataSummary,
+    use_compression: bool,
+    event_ranges: Vec<(u64, u64, u64)>,
+    expect_service: bool,
+) {
+    for (start_version, end_version, proof_version) in event_ranges {
+        // Create the events request
+        let request =
+            create_events_request(proof_version, start_version, end_version, use_compression);
+
+        // Verify the serviceability of the request
+        verify_serviceability(
+            data_client_config,
+            data_summary,
+            None,
+            request,
+            expect_service,
+        );
+    }
+}
+
 /// A simple helper method to verify the serviceability of a request
 fn verify_serviceability(
     data_client_config: &AptosDataClientConfig,
@@ -5,6 +5,7 @@ use crate::smoke_test_environment::SwarmBuilder;
 use movement::{account::create::DEFAULT_FUNDED_COINS, common::types::GasOptions};
 use aptos_crypto::{PrivateKey, ValidCryptoMaterialStringExt};
 use aptos_keygen::KeyGen;
+use tempfile::TempDir;
 
 #[tokio::test]
 async fn test_account_flow() {
@@ -136,3 +137,58 @@ async fn test_account_key_rotation() {
         .await
         .expect("New key should be able to transfer");
 }
+
+#[tokio::test]
+async fn test_offline_transaction_flow() {
+    let (_swarm, cli, _faucet) = SwarmBuilder::new_local(1)
+        .with_aptos()
+        .build_with_cli(3)
+        .await;
+    let dir = TempDir::new().unwrap();
+    let txn_file = dir.path().join("transfer.txn");
+    let sender_signature_file = dir.path().join("sender.sig");
+    let fee_payer_signature_file = dir.path().join("fee_payer.sig");
+
+    // Build a transfer from the first account, paid for by the third one
+    let transfer_amount = 100;
+    let summary = cli
+        .txn_build_transfer(0, 1, transfer_amount, Some(2), txn_file.clone())
+        .await
+        .unwrap();
+    assert_eq!(summary.signers, vec![cli.account_id(0), cli.account_id(2)]);
+
+    // Sign it with the keys of the sender and the fee payer
+    let signature = cli
+        .txn_sign(0, txn_file.clone(), sender_signature_file.clone())
+        .await
+        .unwrap();
+    assert_eq!(signature.signer, cli.account_id(0));
+    cli.txn_sign(2, txn_file.clone(), fee_payer_signature_file.clone())
+        .await
+        .unwrap();
+
+    // The transaction can't be submitted without the signature of the fee payer
+    cli.txn_submit(txn_file.clone(), vec![sender_signature_file.clone()])
+        .await
+        .expect_err("Submitting without the fee payer signature should fail");
+
+    // Submit it with all the signatures
+    let summary = cli
+        .txn_submit(txn_file, vec![
+            sender_signature_file,
+            fee_payer_signature_file,
+        ])
+        .await
+        .unwrap();
+    assert_eq!(summary.success, Some(true));
+    assert_eq!(summary.sender, Some(cli.account_id(0)));
+
+    // The fee payer paid for the gas
+    let octa_spent = summary.gas_used.unwrap() * summary.gas_unit_price.unwrap();
+    cli.assert_account_balance_now(0, DEFAULT_FUNDED_COINS - transfer_amount)
+        .await;
+    cli.assert_account_balance_now(1, DEFAULT_FUNDED_COINS + transfer_amount)
+        .await;
+    cli.assert_account_balance_now(2, DEFAULT_FUNDED_COINS - octa_spent)
+        .await;
+}
@@ -166,9 +166,15 @@ impl Interpreter {
                         .map(|(_idx, val)| val)
                         .collect::<Vec<_>>();
 
+                    // The callee's return values are at the top of the operand stack.
+                    let ret_vals = self
+                        .operand_stack
+                        .last_n(current_frame.function.return_tys().len())
+                        .map_err(|e| self.set_location(e))?;
+
                     // TODO: Check if the error location is set correctly.
                     gas_meter
-                        .charge_drop_frame(non_ref_vals.iter())
+                        .charge_drop_frame(non_ref_vals.iter(), ret_vals)
                         .map_err(|e| self.set_location(e))?;
 
                     self.access_control
@@ -503,6 +503,7 @@ impl<'b> GasMeter for GasStatus<'b> {
     fn charge_drop_frame(
         &mut self,
         _locals: impl Iterator<Item = impl ValueView>,
+        _ret_vals: impl ExactSizeIterator<Item = impl ValueView>,
     ) -> PartialVMResult<()> {
         Ok(())
     }
@@ -294,9 +294,12 @@ pub trait GasMeter {
         args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
     ) -> PartialVMResult<()>;
 
+    /// Called when a function returns, with the locals that are dropped and the values it
+    /// returns to its caller (which are not dropped, but can be observed, e.g. for tracing).
     fn charge_drop_frame(
         &mut self,
         locals: impl Iterator<Item = impl ValueView> + Clone,
+        ret_vals: impl ExactSizeIterator<Item = impl ValueView> + Clone,
     ) -> PartialVMResult<()>;
 
     fn charge_create_ty(&mut self, num_nodes: NumTypeNodes) -> PartialVMResult<()>;
@@ -536,6 +539,7 @@ impl GasMeter for UnmeteredGasMeter {
     fn charge_drop_frame(
         &mut self,
         _locals: impl Iterator<Item = impl ValueView>,
+        _ret_vals: impl ExactSizeIterator<Item = impl ValueView>,
     ) -> PartialVMResult<()> {
         Ok(())
     }
@@ -3,6 +3,7 @@
 
 use crate::on_chain_config::BlockGasLimitType;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Local, per-node configuration.
 #[derive(Clone, Debug)]
@@ -14,6 +15,9 @@ pub struct BlockExecutorLocalConfig {
     // If true, we will discard the failed blocks and continue with the next block.
     // (allow_fallback needs to be set)
     pub discard_failed_blocks: bool,
+    // If specified, a report of the conflicts between transactions is written to this
+    // directory for every block executed in parallel. Only the most recent reports are kept.
+    pub conflict_report_dir: Option<PathBuf>,
 }
 
 /// Configuration from on-chain configuration, that is
@@ -73,6 +77,7 @@ impl BlockExecutorConfig {
                 concurrency_level,
                 allow_fallback: true,
                 discard_failed_blocks: false,
+                conflict_report_dir: None,
             },
             onchain: BlockExecutorConfigFromOnchain::new_no_block_limit(),
         }
@@ -87,6 +92,7 @@ impl BlockExecutorConfig {
                 concurrency_level,
                 allow_fallback: true,
                 discard_failed_blocks: false,
+                conflict_report_dir: None,
             },
             onchain: BlockExecutorConfigFromOnchain::new_maybe_block_limit(maybe_block_gas_limit),
         }
@@ -97,6 +97,8 @@ pub enum FeatureFlag {
     /// AIP-105 (https://github.com/aptos-foundation/AIPs/blob/main/aips/aip-105.md)
     NATIVE_MEMORY_OPERATIONS = 80,
     ACCOUNT_ABSTRACTION = 85,
+    /// Persists the breakdown of the block limits (BlockEndInfo::V1) in the block epilogue
+    BLOCK_END_INFO_V1 = 86,
     DECOMMISSION_CORE_RESOURCES = 222,
     GOVERNED_GAS_POOL = 223,
 }
@@ -331,6 +333,10 @@ impl Features {
         self.is_enabled(FeatureFlag::NATIVE_MEMORY_OPERATIONS)
     }
 
+    pub fn is_block_end_info_v1_enabled(&self) -> bool {
+        self.is_enabled(FeatureFlag::BLOCK_END_INFO_V1)
+    }
+
     pub fn get_max_identifier_size(&self) -> u64 {
         if self.is_enabled(FeatureFlag::LIMIT_MAX_IDENTIFIER_LENGTH) {
             IDENTIFIER_SIZE_MAX
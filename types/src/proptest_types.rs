@@ -1273,22 +1273,57 @@ impl Arbitrary for BlockEndInfo {
     type Strategy = BoxedStrategy<Self>;
 
     fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
-        (any::<bool>(), any::<bool>(), any::<u64>(), any::<u64>())
+        let v0 = (any::<bool>(), any::<bool>(), any::<u64>(), any::<u64>()).prop_map(
+            |(
+                block_gas_limit_reached,
+                block_output_limit_reached,
+                block_effective_block_gas,
+                block_approx_output_size,
+            )| {
+                BlockEndInfo::V0 {
+                    block_gas_limit_reached,
+                    block_output_limit_reached,
+                    block_effective_block_gas_units: block_effective_block_gas,
+                    block_approx_output_size,
+                }
+            },
+        );
+        let v1 = (
+            any::<bool>(),
+            any::<bool>(),
+            any::<u64>(),
+            any::<u64>(),
+            any::<u64>(),
+            any::<u64>(),
+            any::<u64>(),
+            any::<u64>(),
+            any::<bool>(),
+        )
             .prop_map(
                 |(
                     block_gas_limit_reached,
                     block_output_limit_reached,
-                    block_effective_block_gas,
+                    block_effective_block_gas_units,
                     block_approx_output_size,
+                    block_execution_gas_units,
+                    block_io_gas_units,
+                    block_conflict_penalty_gas_units,
+                    max_conflict_multiplier,
+                    module_publishing_conflict,
                 )| {
-                    BlockEndInfo::V0 {
+                    BlockEndInfo::V1 {
                         block_gas_limit_reached,
                         block_output_limit_reached,
-                        block_effective_block_gas_units: block_effective_block_gas,
+                        block_effective_block_gas_units,
                         block_approx_output_size,
+                        block_execution_gas_units,
+                        block_io_gas_units,
+                        block_conflict_penalty_gas_units,
+                        max_conflict_multiplier,
+                        module_publishing_conflict,
                     }
                 },
-            )
-            .boxed()
+            );
+        prop_oneof![v0, v1].boxed()
     }
 }
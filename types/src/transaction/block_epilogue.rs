@@ -36,16 +36,96 @@ pub enum BlockEndInfo {
         /// Total output size block produced
         block_approx_output_size: u64,
     },
+    /// Same as V0, with a breakdown of the effective block gas, to help tune the block limits.
+    V1 {
+        /// Whether block gas limit was reached
+        block_gas_limit_reached: bool,
+        /// Whether block output limit was reached
+        block_output_limit_reached: bool,
+        /// Total gas_units block consumed
+        block_effective_block_gas_units: u64,
+        /// Total output size block produced
+        block_approx_output_size: u64,
+        /// Total execution gas_units of the committed transactions, before multipliers
+        block_execution_gas_units: u64,
+        /// Total io gas_units of the committed transactions, before multipliers
+        block_io_gas_units: u64,
+        /// Effective gas_units added by the conflict multipliers, including the
+        /// module publishing conflict
+        block_conflict_penalty_gas_units: u64,
+        /// Largest conflict multiplier applied to a committed transaction
+        max_conflict_multiplier: u64,
+        /// Whether a module was both published and used in the block, in which case all
+        /// transactions are charged the maximum conflict multiplier
+        module_publishing_conflict: bool,
+    },
 }
 
 impl BlockEndInfo {
     pub fn limit_reached(&self) -> bool {
+        self.block_gas_limit_reached() || self.block_output_limit_reached()
+    }
+
+    pub fn block_gas_limit_reached(&self) -> bool {
         match self {
             Self::V0 {
                 block_gas_limit_reached,
+                ..
+            }
+            | Self::V1 {
+                block_gas_limit_reached,
+                ..
+            } => *block_gas_limit_reached,
+        }
+    }
+
+    pub fn block_output_limit_reached(&self) -> bool {
+        match self {
+            Self::V0 {
                 block_output_limit_reached,
                 ..
-            } => *block_gas_limit_reached || *block_output_limit_reached,
+            }
+            | Self::V1 {
+                block_output_limit_reached,
+                ..
+            } => *block_output_limit_reached,
+        }
+    }
+
+    pub fn block_effective_block_gas_units(&self) -> u64 {
+        match self {
+            Self::V0 {
+                block_effective_block_gas_units,
+                ..
+            }
+            | Self::V1 {
+                block_effective_block_gas_units,
+                ..
+            } => *block_effective_block_gas_units,
+        }
+    }
+
+    pub fn block_approx_output_size(&self) -> u64 {
+        match self {
+            Self::V0 {
+                block_approx_output_size,
+                ..
+            }
+            | Self::V1 {
+                block_approx_output_size,
+                ..
+            } => *block_approx_output_size,
+        }
+    }
+
+    /// Drops the breakdown of V1. The block epilogue persists V0 unless the BLOCK_END_INFO_V1
+    /// feature is enabled on-chain, so that the outputs of existing blocks do not change on replay.
+    pub fn into_v0(self) -> Self {
+        Self::V0 {
+            block_gas_limit_reached: self.block_gas_limit_reached(),
+            block_output_limit_reached: self.block_output_limit_reached(),
+            block_effective_block_gas_units: self.block_effective_block_gas_units(),
+            block_approx_output_size: self.block_approx_output_size(),
         }
     }
 }
@@ -1833,6 +1833,92 @@ impl TransactionOutputListWithProof {
     }
 }
 
+/// A list of events with a proof. The proof authenticates the transaction
+/// infos of all versions in the range (starting at `first_transaction_version`),
+/// while `events` only holds the event lists of the transactions (in the range)
+/// that emitted events of interest. Each event list is complete (i.e., it holds
+/// all events emitted by the transaction), as this is required to recompute the
+/// event root hash of the transaction.
+///
+/// Note: the proof cannot show that no other transactions in the range emitted
+/// events of interest. This requires the events of all transactions.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct EventListWithProof {
+    pub events: Vec<(Version, Vec<ContractEvent>)>,
+    pub first_transaction_version: Option<Version>,
+    pub proof: TransactionInfoListWithProof,
+}
+
+impl EventListWithProof {
+    pub fn new(
+        events: Vec<(Version, Vec<ContractEvent>)>,
+        first_transaction_version: Option<Version>,
+        proof: TransactionInfoListWithProof,
+    ) -> Self {
+        Self {
+            events,
+            first_transaction_version,
+            proof,
+        }
+    }
+
+    /// A convenience function to create an empty proof. Mostly used for tests.
+    pub fn new_empty() -> Self {
+        Self::new(vec![], None, TransactionInfoListWithProof::new_empty())
+    }
+
+    /// Verifies the event list with proof using the given `ledger_info`.
+    /// This method will ensure:
+    /// 1. All transaction infos exist on the given `ledger_info`.
+    /// 2. If `first_transaction_version` is None, the transaction info list is empty.
+    ///    Otherwise, the list starts at `first_transaction_version`.
+    /// 3. Each event list belongs to a transaction in the proven range (in strictly
+    ///    increasing version order) and matches the event root hash of the transaction.
+    pub fn verify(
+        &self,
+        ledger_info: &LedgerInfo,
+        first_transaction_version: Option<Version>,
+    ) -> Result<()> {
+        // Verify the first transaction versions match
+        ensure!(
+            self.first_transaction_version == first_transaction_version,
+            "First transaction version ({:?}) doesn't match given version ({:?}).",
+            self.first_transaction_version,
+            first_transaction_version,
+        );
+
+        // Verify each event list against the corresponding transaction info
+        let mut previous_version = None;
+        for (version, events) in &self.events {
+            ensure!(
+                previous_version.map_or(true, |previous| *version > previous),
+                "The event lists are not in strictly increasing version order! \
+                 Version: {}, previous version: {:?}.",
+                version,
+                previous_version,
+            );
+            let txn_info = self
+                .first_transaction_version
+                .and_then(|first_version| version.checked_sub(first_version))
+                .and_then(|index| self.proof.transaction_infos.get(index as usize))
+                .ok_or_else(|| {
+                    format_err!(
+                        "The event list version ({}) is outside of the proven range!",
+                        version
+                    )
+                })?;
+            verify_events_against_root_hash(events, txn_info)?;
+            previous_version = Some(*version);
+        }
+
+        // Verify the transaction infos are proven by the ledger info.
+        self.proof
+            .verify(ledger_info, self.first_transaction_version)?;
+
+        Ok(())
+    }
+}
+
 /// Verifies a list of events against an expected event root hash. This is done
 /// by calculating the hash of the events using an event accumulator hasher.
 fn verify_events_against_root_hash(